@@ -0,0 +1,66 @@
+use client_core::config::{AppConfig, ModelsConfig};
+use client_core::ipc::{ConfigState, start_ipc_server};
+
+use opencode::ipc_server::IpcServerState;
+use opencode::tauri_commands::ipc_status_response::IpcStatusResponse;
+
+// ============================================================================
+// Integration tests for the IPC server state behind get_ipc_status and
+// refresh_ipc_token
+// ============================================================================
+
+const TEST_AUTH_TOKEN: &str = "test-token-status";
+
+/// **VALUE**: Verifies the IPC status reflects a running server, then a shut-down one.
+///
+/// **WHY THIS MATTERS**: Blazor polls `get_ipc_status` to decide whether to reconnect; a
+/// stopped server reported as running leaves the UI retrying a dead port.
+///
+/// **BUG THIS CATCHES**: Would catch the status being read from stale startup values
+/// rather than the handle, or token rotation succeeding after shutdown.
+#[tokio::test]
+async fn given_ipc_server_when_shut_down_then_status_reports_not_running() {
+    // GIVEN: A running IPC server held in the Tauri state
+    let ipc_port = 19923;
+    let config_dir = std::env::temp_dir().join("opencode-test-ipc-status");
+    let config_state = ConfigState::new(config_dir, AppConfig::default(), ModelsConfig::default());
+    let handle = start_ipc_server(ipc_port, Some(TEST_AUTH_TOKEN.to_string()), config_state)
+        .await
+        .expect("IPC server should start");
+    let server = IpcServerState::new(handle);
+
+    // THEN: It reports running with no connections
+    assert_eq!(
+        server.status().await,
+        IpcStatusResponse {
+            port: ipc_port,
+            running: true,
+            connections: 0,
+        }
+    );
+
+    // THEN: The token can be rotated
+    let token = server
+        .rotate_auth_token()
+        .await
+        .expect("Running server should rotate its token");
+    assert_ne!(token.as_str(), TEST_AUTH_TOKEN);
+
+    // WHEN: Shutting the server down (twice is harmless)
+    server.shutdown().await;
+    server.shutdown().await;
+
+    // THEN: It reports not running, and rotation is refused
+    assert_eq!(
+        server.status().await,
+        IpcStatusResponse {
+            port: ipc_port,
+            running: false,
+            connections: 0,
+        }
+    );
+    assert!(server.rotate_auth_token().await.is_none());
+
+    // THEN: The port is free again
+    std::net::TcpListener::bind(("127.0.0.1", ipc_port)).expect("Port should be released");
+}
@@ -1 +1,3 @@
+mod ipc_server;
+mod logger;
 mod server;
@@ -42,9 +42,6 @@ async fn given_real_discovery_when_updating_state_then_integration_works() {
         // THEN: State update should succeed
         assert!(update_result.is_ok(), "State update should succeed");
 
-        // Give actor time to process
-        tokio::task::yield_now().await;
-
         // AND: State should contain the discovered server
         let retrieved = state.get_server().await;
         assert!(retrieved.is_some(), "State should have server");
@@ -73,15 +70,14 @@ async fn given_server_in_state_when_checking_health_then_uses_state_data() {
         name: String::from("opencode"),
         command: String::from("opencode serve"),
         owned: false,
+        discovered_at: 0,
+        last_health_ok: 0,
     };
     state
         .update(StateCommand::SetServer(mock_server.clone()))
         .await
         .unwrap();
 
-    // Give actor time to process
-    tokio::task::yield_now().await;
-
     // WHEN: Getting server from state and checking health
     let server_info = state.get_server().await;
     assert!(server_info.is_some());
@@ -98,6 +94,35 @@ async fn given_server_in_state_when_checking_health_then_uses_state_data() {
     );
 }
 
+/// **VALUE**: Tests that `update` only returns once the actor has applied the command.
+///
+/// **WHY THIS MATTERS**: Commands store a server and then immediately read it back (or
+/// hand it to the frontend); a queued-but-unapplied update makes them see stale state.
+///
+/// **BUG THIS CATCHES**: Would catch `update` returning as soon as the command is queued.
+#[tokio::test]
+async fn given_update_when_reading_immediately_then_change_visible() {
+    // GIVEN: Fresh AppState
+    let state = AppState::new();
+    let server = IpcServerInfo {
+        pid: 22222,
+        port: 4002,
+        base_url: String::from("http://127.0.0.1:4002"),
+        name: String::from("opencode"),
+        command: String::from("opencode serve"),
+        owned: true,
+        discovered_at: 0,
+        last_health_ok: 0,
+    };
+
+    // WHEN/THEN: Each update is visible to the very next read, with no yield in between
+    state.update(StateCommand::SetServer(server)).await.unwrap();
+    assert_eq!(state.get_server().await.map(|s| s.pid), Some(22222));
+
+    state.update(StateCommand::ClearServer).await.unwrap();
+    assert!(state.get_server().await.is_none());
+}
+
 /// **VALUE**: Tests that concurrent state reads during simulated discovery don't deadlock.
 ///
 /// **WHY THIS MATTERS**: UI might be polling state while discovery is updating it.
@@ -124,6 +149,8 @@ async fn given_concurrent_reads_and_writes_when_executed_then_no_deadlock() {
             name: String::from("opencode"),
             command: String::from("opencode serve"),
             owned: true,
+            discovered_at: 0,
+            last_health_ok: 0,
         };
         state1.update(StateCommand::SetServer(server)).await
     });
@@ -1,19 +1,35 @@
+use std::sync::{Arc, RwLock};
+
 #[derive(Clone)]
 pub struct IpcConfig {
     port: u16,
-    auth_token: String,
+    auth_token: Arc<RwLock<String>>,
 }
 
 impl IpcConfig {
     pub fn new(port: u16, auth_token: String) -> Self {
-        Self { port, auth_token }
+        Self {
+            port,
+            auth_token: Arc::new(RwLock::new(auth_token)),
+        }
     }
 
     pub fn port(&self) -> u16 {
         self.port
     }
 
-    pub fn auth_token(&self) -> &str {
-        &self.auth_token
+    pub fn auth_token(&self) -> String {
+        self.auth_token
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Replace the token handed to Blazor (after the IPC server rotated it).
+    pub fn set_auth_token(&self, auth_token: String) {
+        *self
+            .auth_token
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = auth_token;
     }
 }
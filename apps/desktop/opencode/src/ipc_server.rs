@@ -0,0 +1,57 @@
+use crate::tauri_commands::ipc_status_response::IpcStatusResponse;
+
+use client_core::ipc::{IpcAuthToken, IpcServerHandle};
+
+use log::info;
+use tokio::sync::Mutex;
+
+/// The IPC server started at setup, shared with Tauri commands.
+///
+/// Holds the [`IpcServerHandle`] until [`shutdown`](Self::shutdown), after which the
+/// server is reported as not running.
+pub struct IpcServerState {
+    port: u16,
+    handle: Mutex<Option<IpcServerHandle>>,
+}
+
+impl IpcServerState {
+    pub fn new(handle: IpcServerHandle) -> Self {
+        Self {
+            port: handle.port(),
+            handle: Mutex::new(Some(handle)),
+        }
+    }
+
+    /// Port, running state and open connections of the server.
+    pub async fn status(&self) -> IpcStatusResponse {
+        let handle = self.handle.lock().await;
+        match handle.as_ref() {
+            Some(handle) => IpcStatusResponse {
+                port: self.port,
+                running: handle.is_running(),
+                connections: handle.connection_count(),
+            },
+            None => IpcStatusResponse {
+                port: self.port,
+                running: false,
+                connections: 0,
+            },
+        }
+    }
+
+    /// Rotate the server's auth token, returning the new one.
+    ///
+    /// Returns `None` if the server has been shut down.
+    pub async fn rotate_auth_token(&self) -> Option<IpcAuthToken> {
+        let handle = self.handle.lock().await;
+        Some(handle.as_ref()?.rotate_auth_token().await)
+    }
+
+    /// Stop the server and free its port. Does nothing if it is already stopped.
+    pub async fn shutdown(&self) {
+        if let Some(handle) = self.handle.lock().await.take() {
+            handle.shutdown().await;
+            info!("IPC server on port {} stopped", self.port);
+        }
+    }
+}
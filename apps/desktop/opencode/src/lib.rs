@@ -3,7 +3,9 @@
 
 pub mod error;
 pub mod ipc_config;
+pub mod ipc_server;
 pub mod logger;
+pub mod rotating_file;
 pub mod state;
 pub mod tauri_commands;
 
@@ -1,12 +1,26 @@
 //! Production-grade logging for OpenCode desktop application.
 //!
 //! Provides dual output (stdout with colors + file) with thread-safe initialization.
+//!
+//! The file output is plain text by default. Set `OPENCODE_LOG_FORMAT=json` to write one
+//! JSON object per record instead, for log aggregators. Stdout stays colored text either way.
+//!
+//! The file is rolled to `opencode.log.1` (shifting older backups up) once it passes
+//! [`LoggerConfig::max_file_bytes`], so long-running sessions don't grow it without bound.
+//!
+//! [`LOG_LEVEL`] is only the starting level: records are filtered through
+//! [`client_core::logging`], so the level (globally or per module) can be changed while the
+//! app runs, e.g. over IPC with `IpcSetLogLevelRequest`.
 
 use crate::error::OpencodeError;
+use crate::rotating_file::RotatingFile;
+
+use client_core::logging;
 
 use common::ErrorLocation;
 
-use std::io::stdout;
+use std::env;
+use std::io::{Write, stdout};
 use std::path::Path;
 use std::sync::Once;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -17,6 +31,7 @@ use fern::colors::Color::{Blue, Green, Magenta, Red, Yellow};
 use fern::colors::ColoredLevelConfig;
 use humantime::format_rfc3339;
 use log::{LevelFilter, info, warn};
+use serde_json::json;
 
 /// Thread-safe initialization guard.
 static INIT_LOGGER_ONCE: Once = Once::new();
@@ -27,22 +42,84 @@ static LOGGER_ALREADY_CALLED: AtomicBool = AtomicBool::new(false);
 /// Log file name.
 const LOG_FILE_NAME: &str = "opencode.log";
 
+/// Environment variable selecting the log file format ("text" or "json").
+pub const LOG_FORMAT_ENV_VAR: &str = "OPENCODE_LOG_FORMAT";
+
+/// Default size at which the log file is rolled (10 MB).
+const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default number of rolled log files kept.
+const DEFAULT_MAX_BACKUPS: usize = 5;
+
 /// Message logged when logger is successfully initialized.
 const LOGGER_INITIALIZED_MESSAGE_PREFIX: &str = "Logger initialized with level: ";
 
 /// Warning message when logger is called multiple times.
 const LOGGER_ALREADY_INITIALIZED_MESSAGE: &str = "Logger already initialized";
 
-/// Default log level for debug builds.
+/// Initial log level for debug builds.
 #[cfg(debug_assertions)]
 const LOG_LEVEL: LevelFilter = LevelFilter::Debug;
 
-/// Default log level for release builds.
+/// Initial log level for release builds.
 #[cfg(not(debug_assertions))]
 const LOG_LEVEL: LevelFilter = LevelFilter::Info;
 
+/// Format of the log file output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `[date - LEVEL] message [file:line]` lines.
+    #[default]
+    Text,
+    /// One JSON object per line with `timestamp`, `level`, `target`, `file`, `line`, `message`.
+    Json,
+}
+
+impl LogFormat {
+    /// Read the format from [`LOG_FORMAT_ENV_VAR`]; unset or unknown values mean [`LogFormat::Text`].
+    pub fn from_env() -> Self {
+        match env::var(LOG_FORMAT_ENV_VAR) {
+            Ok(value) if value.trim().eq_ignore_ascii_case("json") => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// Log file settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggerConfig {
+    /// Format of the log file output.
+    pub format: LogFormat,
+    /// Size in bytes after which the log file is rolled.
+    pub max_file_bytes: u64,
+    /// Rolled files kept (`opencode.log.1` is the newest); 0 truncates without keeping any.
+    pub max_backups: usize,
+}
+
+impl LoggerConfig {
+    /// Default settings with the format read from [`LOG_FORMAT_ENV_VAR`].
+    pub fn from_env() -> Self {
+        Self {
+            format: LogFormat::from_env(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_backups: DEFAULT_MAX_BACKUPS,
+        }
+    }
+}
+
 /// Initialize the logger with dual output (stdout + file).
 ///
+/// Uses [`LoggerConfig::from_env`].
+///
 /// This function is safe to call multiple times - subsequent calls will
 /// log a warning and return Ok. The actual initialization runs exactly once.
 ///
@@ -56,6 +133,20 @@ const LOG_LEVEL: LevelFilter = LevelFilter::Info;
 /// - Log file cannot be created
 /// - Logger dispatch configuration fails
 pub fn initialize(log_dir: &Path) -> Result<(), OpencodeError> {
+    initialize_with_config(log_dir, &LoggerConfig::from_env())
+}
+
+/// Initialize the logger with dual output (stdout + file) using explicit file settings.
+///
+/// Like [`initialize`], only the first call takes effect; later calls keep the settings
+/// chosen by the first.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Log file cannot be created
+/// - Logger dispatch configuration fails
+pub fn initialize_with_config(log_dir: &Path, config: &LoggerConfig) -> Result<(), OpencodeError> {
     if LOGGER_ALREADY_CALLED.swap(true, Ordering::SeqCst) {
         warn!("{LOGGER_ALREADY_INITIALIZED_MESSAGE}");
         return Ok(());
@@ -64,9 +155,12 @@ pub fn initialize(log_dir: &Path) -> Result<(), OpencodeError> {
     let mut result = Ok(());
 
     INIT_LOGGER_ONCE.call_once(|| {
-        result = initialize_internal(log_dir);
+        result = initialize_internal(log_dir, config);
         if result.is_ok() {
-            info!("{LOGGER_INITIALIZED_MESSAGE_PREFIX}{LOG_LEVEL:?}");
+            info!(
+                "{LOGGER_INITIALIZED_MESSAGE_PREFIX}{LOG_LEVEL:?} (file format: {:?})",
+                config.format
+            );
         }
     });
 
@@ -75,7 +169,7 @@ pub fn initialize(log_dir: &Path) -> Result<(), OpencodeError> {
 
 /// Internal logger initialization with dual dispatch.
 #[track_caller]
-fn initialize_internal(log_dir: &Path) -> Result<(), OpencodeError> {
+fn initialize_internal(log_dir: &Path, config: &LoggerConfig) -> Result<(), OpencodeError> {
     let log_file_path = log_dir.join(LOG_FILE_NAME);
 
     // Color configuration for stdout
@@ -86,8 +180,10 @@ fn initialize_internal(log_dir: &Path) -> Result<(), OpencodeError> {
         .error(Red)
         .trace(Magenta);
 
-    // Base dispatch with level filter
-    let base_dispatch = Dispatch::new().level(LOG_LEVEL);
+    // Base dispatch: pass everything to the runtime-adjustable filter
+    let base_dispatch = Dispatch::new()
+        .level(LevelFilter::Trace)
+        .filter(logging::is_enabled);
 
     // Stdout dispatch (colored)
     let stdout_dispatch = Dispatch::new()
@@ -103,9 +199,9 @@ fn initialize_internal(log_dir: &Path) -> Result<(), OpencodeError> {
         })
         .chain(stdout());
 
-    // File dispatch (plain text, no colors)
-    let file_dispatch = Dispatch::new()
-        .format(move |out, message, record| {
+    // File dispatch (plain text or JSON lines, no colors)
+    let file_dispatch = match config.format {
+        LogFormat::Text => Dispatch::new().format(move |out, message, record| {
             out.finish(format_args!(
                 "[{date} - {level}] {message} [{file}:{line}]",
                 date = format_rfc3339(SystemTime::now()),
@@ -114,13 +210,26 @@ fn initialize_internal(log_dir: &Path) -> Result<(), OpencodeError> {
                 file = record.file().unwrap_or("unknown"),
                 line = record.line().unwrap_or(0)
             ))
-        })
-        .chain(
-            fern::log_file(&log_file_path).map_err(|e| OpencodeError::Opencode {
-                message: format!("Failed to create log file: {e}"),
-                location: ErrorLocation::from(std::panic::Location::caller()),
-            })?,
-        );
+        }),
+        LogFormat::Json => Dispatch::new().format(move |out, message, record| {
+            let entry = json!({
+                "timestamp": format_rfc3339(SystemTime::now()).to_string(),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "file": record.file().unwrap_or("unknown"),
+                "line": record.line().unwrap_or(0),
+                "message": message.to_string(),
+            });
+            out.finish(format_args!("{entry}"))
+        }),
+    };
+
+    let log_file = RotatingFile::open(&log_file_path, config.max_file_bytes, config.max_backups)
+        .map_err(|e| OpencodeError::Opencode {
+            message: format!("Failed to create log file: {e}"),
+            location: ErrorLocation::from(std::panic::Location::caller()),
+        })?;
+    let file_dispatch = file_dispatch.chain(Box::new(log_file) as Box<dyn Write + Send>);
 
     // Apply the configuration
     base_dispatch
@@ -132,5 +241,8 @@ fn initialize_internal(log_dir: &Path) -> Result<(), OpencodeError> {
             location: ErrorLocation::from(std::panic::Location::caller()),
         })?;
 
+    // `apply()` set the max level to Trace; start at the configured level instead
+    logging::set_level(LOG_LEVEL, None);
+
     Ok(())
 }
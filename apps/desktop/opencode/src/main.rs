@@ -3,11 +3,13 @@
 
 use opencode::error::OpencodeError;
 use opencode::ipc_config::IpcConfig;
+use opencode::ipc_server::IpcServerState;
 use opencode::logger::initialize as LoggerInitialize;
 use opencode::state::AppState;
 use opencode::tauri_commands;
 
-use client_core::ipc::{ConfigState, start_ipc_server};
+use client_core::discovery::spawn::stop_owned_servers;
+use client_core::ipc::{ConfigState, IpcAuthToken, start_ipc_server};
 
 use common::ErrorLocation;
 
@@ -15,13 +17,14 @@ use std::fs::create_dir_all;
 use std::panic::Location;
 
 use log::{info, warn};
-use tauri::Manager;
-use uuid::Uuid;
+use tauri::{Manager, RunEvent};
 
 fn main() {
     tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
             tauri_commands::ipc_config_response::get_ipc_config,
+            tauri_commands::ipc_config_response::refresh_ipc_token,
+            tauri_commands::ipc_status_response::get_ipc_status,
         ])
         .setup(|app| {
             // Get app data directory for logs
@@ -72,8 +75,14 @@ fn main() {
                     client_core::config::AppConfig::default()
                 });
 
-            let models_config = client_core::config::ModelsConfig::load(&resource_dir)
-                .unwrap_or_else(|e| {
+            // Models edited at runtime are saved to config_dir; otherwise use the bundled copy
+            let models_dir = if config_dir.join("models.toml").exists() {
+                &config_dir
+            } else {
+                &resource_dir
+            };
+            let models_config =
+                client_core::config::ModelsConfig::load(models_dir).unwrap_or_else(|e| {
                     warn!("Failed to load models.toml, using defaults: {}", e);
                     client_core::config::ModelsConfig::default()
                 });
@@ -92,19 +101,19 @@ fn main() {
             // Initialize AppState AFTER Tauri runtime is running
             app.manage(AppState::default());
 
-            // Start IPC WebSocket server
+            // Start IPC WebSocket server (falls back to a nearby port if this one is taken)
             let ipc_port = 19876;
-            let auth_token = Uuid::new_v4().to_string();
+            let auth_token = IpcAuthToken::generate();
 
             info!("Starting IPC server on port {ipc_port}");
             info!("IPC auth token: {auth_token}");
 
-            let token_clone = auth_token.clone();
+            let token_clone = auth_token.as_str().to_string();
 
             // Start IPC server and verify it binds successfully
             let config_state_clone = config_state.clone(); // 🆕 ADD THIS LINE
             let rt = tauri::async_runtime::handle();
-            let _ipc_handle = rt
+            let ipc_handle = rt
                 .block_on(async {
                     start_ipc_server(ipc_port, Some(token_clone), config_state_clone).await // 🆕 ADD config_state_clone
                 })
@@ -113,13 +122,39 @@ fn main() {
                     location: ErrorLocation::from(Location::caller()),
                 })?;
 
-            info!("IPC server started successfully");
-
-            // Store IPC config for Blazor to retrieve
-            app.manage(IpcConfig::new(ipc_port, auth_token));
+            let ipc_port = ipc_handle.port();
+            info!("IPC server started successfully on port {ipc_port}");
+
+            // Store IPC config and server for Blazor to retrieve
+            app.manage(IpcConfig::new(ipc_port, auth_token.as_str().to_string()));
+            app.manage(IpcServerState::new(ipc_handle));
+
+            // Pick up external edits to config.json (the app still works without it)
+            match rt.block_on(config_state.watch_config_file()) {
+                Ok(config_watcher) => {
+                    info!("Watching config.json for external changes");
+                    // Managed so it lives as long as the app; dropping it stops watching
+                    app.manage(config_watcher);
+                }
+                Err(e) => warn!(
+                    "Failed to watch config.json, external edits need a restart: {}",
+                    e
+                ),
+            }
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                if let Some(ipc_server) = app.try_state::<IpcServerState>() {
+                    tauri::async_runtime::block_on(ipc_server.shutdown());
+                }
+
+                // Don't leave servers we spawned running after the app exits
+                let stopped = tauri::async_runtime::block_on(stop_owned_servers());
+                info!("Stopped {stopped} spawned OpenCode server(s) on exit");
+            }
+        });
 }
@@ -0,0 +1,116 @@
+//! Size-based log file rotation.
+//!
+//! [`RotatingFile`] appends to `<path>` and, once it grows past a threshold, renames it to
+//! `<path>.1` (shifting `<path>.1` to `<path>.2`, and so on) and starts a fresh file.
+
+use std::fs::{File, OpenOptions, remove_file, rename};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// An append-only file that rolls itself over by size.
+///
+/// Rotation happens on [`flush`](Write::flush), never mid-write, so a record written and
+/// flushed as a unit (as fern does per log line) is never split across two files. A file
+/// can therefore exceed `max_bytes` by up to one record.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_backups: usize,
+    /// Closed during rotation (Windows can't rename an open file); reopened on next use.
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, rotating first if it is already over `max_bytes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Active log file
+    /// * `max_bytes` - Size after which the file is rolled
+    /// * `max_backups` - Rolled files kept; 0 truncates without keeping any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be opened or the initial rotation fails.
+    pub fn open(path: &Path, max_bytes: u64, max_backups: usize) -> io::Result<Self> {
+        let file = open_append(path)?;
+        let size = file.metadata()?.len();
+
+        let mut rotating = Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            max_backups,
+            file: Some(file),
+            size,
+        };
+        if rotating.size >= max_bytes {
+            rotating.rotate()?;
+        }
+
+        Ok(rotating)
+    }
+
+    /// Path of the `index`-th backup (`<path>.<index>`).
+    pub fn backup_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{index}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        // Close the active file before renaming it
+        drop(self.file.take());
+
+        if self.max_backups > 0 {
+            let oldest = self.backup_path(self.max_backups);
+            if oldest.exists() {
+                remove_file(&oldest)?;
+            }
+            for index in (1..self.max_backups).rev() {
+                let from = self.backup_path(index);
+                if from.exists() {
+                    rename(&from, self.backup_path(index + 1))?;
+                }
+            }
+            rename(&self.path, self.backup_path(1))?;
+        } else {
+            remove_file(&self.path)?;
+        }
+
+        self.file = Some(open_append(&self.path)?);
+        self.size = 0;
+        Ok(())
+    }
+
+    fn file(&mut self) -> io::Result<&mut File> {
+        match self.file {
+            Some(ref mut file) => Ok(file),
+            None => {
+                let file = open_append(&self.path)?;
+                Ok(self.file.insert(file))
+            }
+        }
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file()?.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file()?.flush()?;
+        if self.size >= self.max_bytes {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
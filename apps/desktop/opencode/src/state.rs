@@ -3,7 +3,7 @@ use client_core::proto::IpcServerInfo;
 use std::sync::Arc;
 
 use log::{info, warn};
-use tokio::sync::{Mutex, RwLock, mpsc};
+use tokio::sync::{Mutex, RwLock, mpsc, oneshot};
 
 /// Commands that mutate application state.
 ///
@@ -18,6 +18,9 @@ pub enum StateCommand {
     ClearServer,
 }
 
+/// A command queued for the actor, with the sender it acknowledges once the command is applied.
+type QueuedCommand = (StateCommand, oneshot::Sender<()>);
+
 /// Application state manager.
 ///
 /// Uses an actor pattern to ensure all state mutations are serialized.
@@ -28,7 +31,7 @@ pub enum StateCommand {
 #[derive(Clone)]
 pub struct AppState {
     /// Channel to send state mutation commands to the actor
-    command_tx: Arc<Mutex<Option<mpsc::Sender<StateCommand>>>>,
+    command_tx: Arc<Mutex<Option<mpsc::Sender<QueuedCommand>>>>,
 
     /// Shared read-only access to server info
     server: Arc<RwLock<Option<IpcServerInfo>>>,
@@ -49,17 +52,25 @@ impl AppState {
         }
     }
 
-    /// Send a state update command.
+    /// Send a state update command and wait until the actor has applied it.
+    ///
+    /// Once this returns, reads such as [`get_server`](Self::get_server) see the change.
     ///
     /// Returns an error if the state actor has died (should never happen).
     pub async fn update(&self, cmd: StateCommand) -> Result<(), String> {
-        self.ensure_actor().await; // ADD THIS LINE
-
-        let tx_guard = self.command_tx.lock().await; // CHANGED: now needs lock
-        let tx = tx_guard.as_ref().ok_or("Actor not initialized")?; // CHANGED
-        tx.send(cmd)
+        self.ensure_actor().await;
+
+        let (ack, applied) = oneshot::channel();
+        {
+            let tx_guard = self.command_tx.lock().await;
+            let tx = tx_guard.as_ref().ok_or("Actor not initialized")?;
+            tx.send((cmd, ack))
+                .await
+                .map_err(|e| format!("State actor died: {}", e))?;
+        }
+        applied
             .await
-            .map_err(|e| format!("State actor died: {}", e))
+            .map_err(|e| format!("State actor dropped command: {}", e))
     }
 
     /// Get current server info (read-only).
@@ -101,12 +112,12 @@ impl Default for AppState {
 /// This ensures that all state mutations are serialized and
 /// prevents race conditions between concurrent operations.
 async fn state_actor(
-    mut command_rx: mpsc::Receiver<StateCommand>,
+    mut command_rx: mpsc::Receiver<QueuedCommand>,
     server: Arc<RwLock<Option<IpcServerInfo>>>,
 ) {
     info!("State actor started");
 
-    while let Some(cmd) = command_rx.recv().await {
+    while let Some((cmd, ack)) = command_rx.recv().await {
         match cmd {
             StateCommand::SetServer(new_server) => {
                 let mut server_write = server.write().await;
@@ -137,6 +148,8 @@ async fn state_actor(
                 *server_write = None;
             }
         }
+        // The caller may have stopped waiting; the command is applied either way
+        let _ = ack.send(());
     }
 
     warn!("State actor stopped - this should not happen during normal operation");
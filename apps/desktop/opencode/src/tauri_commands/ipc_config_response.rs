@@ -1,4 +1,11 @@
+use crate::error::OpencodeError;
 use crate::ipc_config::IpcConfig;
+use crate::ipc_server::IpcServerState;
+
+use common::ErrorLocation;
+
+use std::panic::Location;
+
 use log::info;
 use serde::Serialize;
 use tauri::State;
@@ -15,6 +22,31 @@ pub fn get_ipc_config(config: State<'_, IpcConfig>) -> IpcConfigResponse {
 
     IpcConfigResponse {
         port: config.port(),
-        auth_token: config.auth_token().to_string(),
+        auth_token: config.auth_token(),
     }
 }
+
+/// Rotate the IPC auth token and return the config with the new token.
+///
+/// Connections already authenticated stay open; new ones must use the returned token.
+#[tauri::command]
+pub async fn refresh_ipc_token(
+    config: State<'_, IpcConfig>,
+    server: State<'_, IpcServerState>,
+) -> Result<IpcConfigResponse, OpencodeError> {
+    info!("Blazor requested IPC token refresh");
+
+    let token = server
+        .rotate_auth_token()
+        .await
+        .ok_or_else(|| OpencodeError::Opencode {
+            message: "IPC server is not running".to_string(),
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+    config.set_auth_token(token.as_str().to_string());
+
+    Ok(IpcConfigResponse {
+        port: config.port(),
+        auth_token: config.auth_token(),
+    })
+}
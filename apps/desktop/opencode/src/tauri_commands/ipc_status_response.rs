@@ -0,0 +1,23 @@
+use crate::error::OpencodeError;
+use crate::ipc_server::IpcServerState;
+
+use log::debug;
+use serde::Serialize;
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct IpcStatusResponse {
+    pub port: u16,
+    pub running: bool,
+    pub connections: usize,
+}
+
+#[tauri::command]
+pub async fn get_ipc_status(
+    server: State<'_, IpcServerState>,
+) -> Result<IpcStatusResponse, OpencodeError> {
+    let status = server.status().await;
+    debug!("Blazor requested IPC status: {status:?}");
+
+    Ok(status)
+}
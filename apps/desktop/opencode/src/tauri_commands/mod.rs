@@ -1 +1,2 @@
 pub mod ipc_config_response;
+pub mod ipc_status_response;
@@ -1,2 +1,3 @@
 mod error;
 mod logger;
+mod rotating_file;
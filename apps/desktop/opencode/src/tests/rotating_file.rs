@@ -0,0 +1,105 @@
+// Unit tests for size-based log file rotation
+
+use crate::rotating_file::RotatingFile;
+
+use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+use std::io::Write;
+use std::path::PathBuf;
+
+fn fresh_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    remove_dir_all(&dir).ok();
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// **VALUE**: Verifies that the log file rolls over once it passes the size threshold.
+///
+/// **WHY THIS MATTERS**: The desktop app can run for weeks; without rotation the log grows
+/// until it fills the disk.
+///
+/// **BUG THIS CATCHES**: Would catch if backups aren't shifted (`.1` -> `.2`), if more than
+/// `max_backups` files are kept, or if the active file isn't started fresh after a roll.
+#[test]
+fn given_writes_past_threshold_when_flushed_then_rolls_to_numbered_backups() {
+    // GIVEN: A rotating file with a 100-byte threshold keeping 2 backups
+    let dir = fresh_dir("opencode-test-rotating-file-roll");
+    let path = dir.join("opencode.log");
+    let mut file = RotatingFile::open(&path, 100, 2).unwrap();
+
+    // WHEN: Writing four 60-byte records, flushing after each (as fern does)
+    for n in 0..4 {
+        writeln!(file, "record {n} {}", "x".repeat(50)).unwrap();
+        file.flush().unwrap();
+    }
+
+    // THEN: Records 0-1 and 2-3 each filled a file that was rolled; only 2 backups remain
+    let newest = read_to_string(file.backup_path(1)).unwrap();
+    let older = read_to_string(file.backup_path(2)).unwrap();
+    assert!(newest.starts_with("record 2") && newest.contains("record 3"));
+    assert!(older.starts_with("record 0") && older.contains("record 1"));
+    assert!(
+        !file.backup_path(3).exists(),
+        "Only 2 backups should be kept"
+    );
+
+    // AND: The active file was truncated
+    assert_eq!(read_to_string(&path).unwrap(), "");
+
+    // AND: Writing continues in the fresh file
+    writeln!(file, "record 4").unwrap();
+    file.flush().unwrap();
+    assert_eq!(read_to_string(&path).unwrap(), "record 4\n");
+
+    // Cleanup
+    remove_dir_all(&dir).ok();
+}
+
+/// **VALUE**: Verifies that an oversized log left by a previous run is rolled on open.
+///
+/// **WHY THIS MATTERS**: The app is often restarted; if the size check only counted bytes
+/// written by this process, a log could grow by a full threshold per launch.
+///
+/// **BUG THIS CATCHES**: Would catch if the existing file size is ignored at open.
+#[test]
+fn given_existing_oversized_file_when_opened_then_rolls_before_writing() {
+    // GIVEN: An existing log already over the threshold
+    let dir = fresh_dir("opencode-test-rotating-file-open");
+    let path = dir.join("opencode.log");
+    write(&path, "x".repeat(200)).unwrap();
+
+    // WHEN: Opening it with a 100-byte threshold
+    let file = RotatingFile::open(&path, 100, 1).unwrap();
+
+    // THEN: The old contents moved to the backup and the active file is empty
+    assert_eq!(read_to_string(file.backup_path(1)).unwrap().len(), 200);
+    assert_eq!(read_to_string(&path).unwrap(), "");
+
+    // Cleanup
+    remove_dir_all(&dir).ok();
+}
+
+/// **VALUE**: Verifies that `max_backups = 0` truncates without keeping old logs.
+///
+/// **WHY THIS MATTERS**: Users short on disk space can opt out of backups entirely.
+///
+/// **BUG THIS CATCHES**: Would catch an off-by-one that still writes `.1` when no backups
+/// are requested.
+#[test]
+fn given_zero_backups_when_threshold_passed_then_truncates_without_backup() {
+    // GIVEN: A rotating file keeping no backups
+    let dir = fresh_dir("opencode-test-rotating-file-no-backups");
+    let path = dir.join("opencode.log");
+    let mut file = RotatingFile::open(&path, 10, 0).unwrap();
+
+    // WHEN: Writing past the threshold
+    writeln!(file, "a record longer than ten bytes").unwrap();
+    file.flush().unwrap();
+
+    // THEN: The file was truncated and no backup exists
+    assert_eq!(read_to_string(&path).unwrap(), "");
+    assert!(!file.backup_path(1).exists());
+
+    // Cleanup
+    remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,46 @@
+// Benchmarks for field_normalizer
+// Measures normalize_json/denormalize_json on a realistic list_sessions payload
+
+use client_core::field_normalizer::{denormalize_json, normalize_json};
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use serde_json::{Value, json};
+
+/// A list_sessions response with 500 sessions, shaped like OpenCode's output.
+fn sessions_payload() -> Value {
+    let sessions: Vec<Value> = (0..500)
+        .map(|i| {
+            json!({
+                "id": format!("ses_{i:04}"),
+                "projectID": "proj_abc",
+                "directory": "/home/user/project",
+                "parentID": if i % 10 == 0 { Value::Null } else { json!("ses_0000") },
+                "title": format!("Session {i}"),
+                "version": "1.0.0",
+                "time": { "created": 1_700_000_000 + i, "updated": 1_700_000_500 + i },
+                "summary": { "additions": i % 50, "deletions": i % 7, "files": i % 5 },
+                "tags": ["rust", "tauri", "ipc"]
+            })
+        })
+        .collect();
+    Value::Array(sessions)
+}
+
+fn bench_normalize(c: &mut Criterion) {
+    let payload = sessions_payload();
+    let normalized = normalize_json(payload.clone());
+
+    c.bench_function("normalize_json/500_sessions", |b| {
+        b.iter_batched(|| payload.clone(), normalize_json, BatchSize::SmallInput)
+    });
+    c.bench_function("denormalize_json/500_sessions", |b| {
+        b.iter_batched(
+            || normalized.clone(),
+            denormalize_json,
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_normalize);
+criterion_main!(benches);
@@ -5,6 +5,36 @@ use std::path::PathBuf;
 
 const OPENCODE_FIELDS_TOML: &str = "opencode_fields.toml";
 const OPENCODE_FIELDS_GENERATED_FILE: &str = "field_normalizer.rs";
+const IPC_PROTO: &str = "../../proto/ipc.proto";
+const SERDE_DERIVE: &str = "#[derive(serde::Serialize, serde::Deserialize)]";
+
+/// Proto paths reachable from the IPC envelopes outside `ipc.proto` itself.
+///
+/// The JSON protocol mode serializes whole envelopes, and OpenCode responses are parsed
+/// into the same types. Packages not listed here (events, models, providers) stay
+/// protobuf-only.
+const SERDE_PATHS: &[&str] = &[
+    ".opencode.agent",
+    ".opencode.auth",
+    ".opencode.message",
+    ".opencode.session.OcFileDiff",
+    ".opencode.session.OcPermissionAction",
+    ".opencode.session.OcPermissionRule",
+    ".opencode.session.OcPermissionRuleset",
+    ".opencode.session.OcSessionInfo",
+    ".opencode.session.OcSessionList",
+    ".opencode.session.OcSessionRevert",
+    ".opencode.session.OcSessionShare",
+    ".opencode.session.OcSessionSummary",
+    ".opencode.session.OcSessionTime",
+    ".opencode.tool.OcToolState",
+    ".opencode.tool.OcToolStateCompleted",
+    ".opencode.tool.OcToolStateError",
+    ".opencode.tool.OcToolStatePending",
+    ".opencode.tool.OcToolStateRunning",
+    ".opencode.tool.OcToolTime",
+    ".opencode.tool.OcToolTimeWithEnd",
+];
 
 fn main() {
     // Existing: Compile protobuf files
@@ -15,63 +45,27 @@ fn main() {
 }
 
 fn compile_protos() {
-    prost_build::Config::new()
+    // Rebuild when any .proto changes (they live outside this package)
+    println!("cargo:rerun-if-changed=../../proto");
+
+    let mut config = prost_build::Config::new();
+    for path in SERDE_PATHS {
+        config.type_attribute(path, SERDE_DERIVE);
+    }
+    // Every IPC envelope and payload can be sent as a JSON text frame
+    for name in ipc_type_names() {
+        config.type_attribute(format!(".opencode.{name}"), SERDE_DERIVE);
+    }
+
+    config
         .type_attribute(".", "#[allow(clippy::large_enum_variant)]")
         .extern_path(".google.protobuf.Struct", "::prost_wkt_types::Struct")
         .extern_path(".google.protobuf.Value", "::prost_wkt_types::Value")
         .extern_path(".google.protobuf.ListValue", "::prost_wkt_types::ListValue")
-        .type_attribute(
-            "IpcServerInfo",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcSessionInfo",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcSessionTime",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcSessionSummary",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
         .field_attribute(
             "opencode.session.OcSessionSummary.diffs",
             "#[serde(default)]",
         )
-        .type_attribute(
-            "opencode.session.OcFileDiff",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcSessionShare",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcSessionRevert",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcPermissionAction",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcPermissionRule",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcPermissionRuleset",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.session.OcSessionList",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.OcAssistantMessage",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
         .field_attribute(
             "opencode.message.OcAssistantMessage.session_id",
             "#[serde(default)]",
@@ -104,18 +98,14 @@ fn compile_protos() {
             "opencode.message.OcAssistantMessage.error",
             "#[serde(default)]",
         )
-        .type_attribute(
-            "opencode.message.OcUserMessage",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.OcMessage",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.OcModelReference",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
+        .field_attribute(
+            "opencode.message.OcUserMessage.session_id",
+            "#[serde(default)]",
         )
+        .field_attribute("opencode.message.OcUserMessage.role", "#[serde(default)]")
+        .field_attribute("opencode.message.OcUserMessage.model", "#[serde(default)]")
+        .field_attribute("opencode.message.OcUserMessage.parts", "#[serde(default)]")
+        .field_attribute("opencode.message.OcUserMessage.text", "#[serde(default)]")
         .field_attribute(
             "opencode.message.OcModelReference.model_id",
             "#[serde(default)]",
@@ -124,39 +114,11 @@ fn compile_protos() {
             "opencode.message.OcModelReference.provider_id",
             "#[serde(default)]",
         )
-        .type_attribute(
-            "opencode.message.OcTokens",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.OcMessageError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcTextPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcReasoningPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.OcMessage.message",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
         .type_attribute(
             "opencode.message.part.OcPart.part",
-            "#[derive(serde::Serialize, serde::Deserialize)] #[serde(rename_all = \"snake_case\")]",
+            "#[serde(rename_all = \"snake_case\")]",
         )
         .field_attribute("opencode.message.part.OcPart.part", "#[serde(flatten)]")
-        .type_attribute(
-            "opencode.message.OcTokenUsage",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
         .field_attribute("opencode.message.OcTokenUsage.input", "#[serde(default)]")
         .field_attribute("opencode.message.OcTokenUsage.output", "#[serde(default)]")
         .field_attribute(
@@ -167,110 +129,6 @@ fn compile_protos() {
             "opencode.message.OcTokenUsage.cache_write",
             "#[serde(default)]",
         )
-        .type_attribute(
-            "opencode.message.error.OcMessageError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.error.OcMessageError.error",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.error.OcApiError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.error.OcProviderAuthError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.error.OcUnknownError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.error.OcOutputLengthError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.error.OcAbortedError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcToolPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcFilePart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcPatchPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcSnapshotPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcAgentPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcCompactionPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcSubtaskPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcStepStartPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcStepFinishPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcRetryPart",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.message.part.OcTokenUsage",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolState",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolState.state",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolStatePending",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolStateRunning",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolStateCompleted",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolStateError",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolTime",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
-        .type_attribute(
-            "opencode.tool.OcToolTimeWithEnd",
-            "#[derive(serde::Serialize, serde::Deserialize)]",
-        )
         .compile_protos(
             &[
                 // OpenCode canonical models (from JSON Schemas)
@@ -285,13 +143,31 @@ fn compile_protos() {
                 "../../proto/oc_message_error.proto",
                 "../../proto/oc_event.proto",
                 // IPC protocol layer
-                "../../proto/ipc.proto",
+                IPC_PROTO,
             ],
             &["../../proto/"],
         )
         .unwrap();
 }
 
+/// Names of the top-level messages and enums declared in `ipc.proto`.
+///
+/// Nested types (oneofs) inherit the attribute through their parent's path.
+fn ipc_type_names() -> Vec<String> {
+    let proto =
+        fs::read_to_string(IPC_PROTO).unwrap_or_else(|e| panic!("Failed to read {IPC_PROTO}: {e}"));
+
+    proto
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("message ")
+                .or_else(|| line.strip_prefix("enum "))
+        })
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
 fn generate_field_normalizer() {
     // Read configuration file
     let config_path = PathBuf::from(OPENCODE_FIELDS_TOML);
@@ -416,43 +292,17 @@ fn generate_rust_code(mappings: &HashMap<String, String>) -> String {
     code.push_str("}\n\n");
 
     // normalize_json function
-    code.push_str("/// Transform JavaScript field names to snake_case recursively\n");
+    code.push_str("/// Transform JavaScript field names to snake_case at every nesting level\n");
     code.push_str("/// Use this on JSON received from OpenCode server\n");
     code.push_str("pub fn normalize_json(value: Value) -> Value {\n");
-    code.push_str("    match value {\n");
-    code.push_str("        Value::Object(map) => {\n");
-    code.push_str("            let normalized = map.into_iter()\n");
-    code.push_str(
-        "                .map(|(k, v)| (normalize_key(&k).into_owned(), normalize_json(v)))\n",
-    );
-    code.push_str("                .collect();\n");
-    code.push_str("            Value::Object(normalized)\n");
-    code.push_str("        }\n");
-    code.push_str("        Value::Array(arr) => {\n");
-    code.push_str("            Value::Array(arr.into_iter().map(normalize_json).collect())\n");
-    code.push_str("        }\n");
-    code.push_str("        other => other,\n");
-    code.push_str("    }\n");
+    code.push_str("    rename_keys(value, &|k| TO_SNAKE.get(k).copied())\n");
     code.push_str("}\n\n");
 
     // denormalize_json function
-    code.push_str("/// Transform snake_case field names to JavaScript recursively\n");
+    code.push_str("/// Transform snake_case field names to JavaScript at every nesting level\n");
     code.push_str("/// Use this on JSON being sent to OpenCode server\n");
     code.push_str("pub fn denormalize_json(value: Value) -> Value {\n");
-    code.push_str("    match value {\n");
-    code.push_str("        Value::Object(map) => {\n");
-    code.push_str("            let denormalized = map.into_iter()\n");
-    code.push_str(
-        "                .map(|(k, v)| (denormalize_key(&k).into_owned(), denormalize_json(v)))\n",
-    );
-    code.push_str("                .collect();\n");
-    code.push_str("            Value::Object(denormalized)\n");
-    code.push_str("        }\n");
-    code.push_str("        Value::Array(arr) => {\n");
-    code.push_str("            Value::Array(arr.into_iter().map(denormalize_json).collect())\n");
-    code.push_str("        }\n");
-    code.push_str("        other => other,\n");
-    code.push_str("    }\n");
+    code.push_str("    rename_keys(value, &|k| TO_JS.get(k).copied())\n");
     code.push_str("}\n");
 
     code
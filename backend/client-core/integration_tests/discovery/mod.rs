@@ -1,2 +1,3 @@
 mod process;
+mod remote;
 mod spawn;
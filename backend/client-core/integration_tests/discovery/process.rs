@@ -1,5 +1,38 @@
-use client_core::discovery::process::{check_health, discover, stop_pid};
+use client_core::discovery::process::{
+    HealthCheckConfig, HealthStatus, ServerProbe, check_health, check_health_status,
+    check_health_with, discover, probe, probe_with, stop_on_port, stop_pid, validate_server_info,
+};
 use client_core::discovery::set_override_port;
+use client_core::error::discovery::DiscoveryError;
+use client_core::proto::IpcServerInfo;
+
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mock_health(endpoint: &str, status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(endpoint))
+        .respond_with(ResponseTemplate::new(status))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn server_info(port: u32, base_url: &str) -> IpcServerInfo {
+    IpcServerInfo {
+        pid: 1234,
+        port,
+        base_url: base_url.to_string(),
+        name: "opencode".to_string(),
+        command: "opencode serve".to_string(),
+        owned: true,
+        discovered_at: 0,
+        last_health_ok: 0,
+    }
+}
 
 // ============================================================================
 // Public API tests for process discovery and management
@@ -49,6 +82,22 @@ fn given_pid_1_when_stop_pid_called_then_refuses_and_returns_false() {
     assert!(!result, "Should never kill PID 1 (init process)");
 }
 
+/// **VALUE**: Verifies `stop_on_port()` reports nothing stopped for a free port.
+///
+/// **BUG THIS CATCHES**: Would catch a free port being treated as an error.
+#[test]
+fn given_free_port_when_stop_on_port_then_returns_false() {
+    // GIVEN: A port that was just released
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    // WHEN / THEN
+    assert!(!stop_on_port(port).unwrap());
+}
+
 // ----------------------------------------------------------------------------
 // check_health() - Server health check tests
 // ----------------------------------------------------------------------------
@@ -111,6 +160,193 @@ async fn given_empty_url_when_check_health_called_then_returns_false() {
     assert!(!result, "Should return false for empty URL");
 }
 
+/// **VALUE**: Verifies that a 204 No Content health response counts as healthy by default.
+///
+/// **WHY THIS MATTERS**: Some OpenCode versions answer health probes with an empty 204. Treating
+/// that as down would make the app spawn a second server next to a working one.
+///
+/// **BUG THIS CATCHES**: Would catch if the default check only accepts exactly 200.
+#[tokio::test]
+async fn given_server_returning_204_when_check_health_called_then_returns_true() {
+    // GIVEN: A server answering /doc with 204
+    let server = mock_health("/doc", 204).await;
+
+    // WHEN: Checking health with the default config
+    let result = check_health(&server.uri()).await;
+
+    // THEN: Should be healthy
+    assert!(result, "204 should count as healthy");
+}
+
+/// **VALUE**: Verifies that a 503 is reported as an unexpected status, not as unreachable.
+///
+/// **WHY THIS MATTERS**: "Server down" and "server answering wrongly" need different fixes
+/// (start it vs. upgrade it); callers can only tell users which one if we keep them apart.
+///
+/// **BUG THIS CATCHES**: Would catch if every failure collapses into `Unreachable`, or if the
+/// status code is lost.
+#[tokio::test]
+async fn given_server_returning_503_when_check_health_status_called_then_reports_unexpected_status()
+{
+    // GIVEN: A server answering /doc with 503
+    let server = mock_health("/doc", 503).await;
+    let config = HealthCheckConfig::default();
+
+    // WHEN: Checking health status
+    let status = check_health_status(&server.uri(), &config).await;
+
+    // THEN: The status code is reported, and the bool API says unhealthy
+    assert_eq!(status, HealthStatus::UnexpectedStatus(503));
+    assert!(!check_health_with(&server.uri(), &config).await);
+}
+
+/// **VALUE**: Verifies that nothing listening is reported as unreachable.
+///
+/// **WHY THIS MATTERS**: This is the "start a server" signal for callers.
+///
+/// **BUG THIS CATCHES**: Would catch if connection errors are reported as a status code.
+#[tokio::test]
+async fn given_unreachable_port_when_check_health_status_called_then_reports_unreachable() {
+    // GIVEN: A port with no server and a short timeout
+    let config = HealthCheckConfig {
+        timeout: Duration::from_millis(500),
+        ..Default::default()
+    };
+
+    // WHEN: Checking health status
+    let status = check_health_status("http://127.0.0.1:65534", &config).await;
+
+    // THEN: Should be unreachable
+    assert_eq!(status, HealthStatus::Unreachable);
+}
+
+/// **VALUE**: Verifies a healthy server is probed as reachable with its status and latency.
+///
+/// **BUG THIS CATCHES**: Would catch a probe that drops the status or never measures
+/// latency for a successful answer.
+#[tokio::test]
+async fn given_server_returning_200_when_probed_then_reachable_with_status_and_latency() {
+    // GIVEN: A server answering /doc with 200
+    let server = mock_health("/doc", 200).await;
+
+    // WHEN: Probing it
+    let result = probe(&server.uri()).await;
+
+    // THEN: Reachable, 200, with a latency, and healthy under the default config
+    assert!(result.reachable);
+    assert_eq!(result.http_status, Some(200));
+    assert!(result.latency_ms.is_some());
+    assert_eq!(
+        result.health(&HealthCheckConfig::default()),
+        HealthStatus::Healthy
+    );
+}
+
+/// **VALUE**: Verifies a server answering 503 is probed as reachable, not gone.
+///
+/// **WHY THIS MATTERS**: OpenCode answers 503 while it is restarting; the UI should say
+/// "restarting" and wait, not offer to start a second server.
+///
+/// **BUG THIS CATCHES**: Would catch error statuses being reported as unreachable, the
+/// way the bool `check_health` reports them.
+#[tokio::test]
+async fn given_server_returning_503_when_probed_then_reachable_with_status() {
+    // GIVEN: A server answering /doc with 503
+    let server = mock_health("/doc", 503).await;
+
+    // WHEN: Probing it
+    let result = probe(&server.uri()).await;
+
+    // THEN: It answered, with 503, so it is reachable but not healthy
+    assert!(result.reachable);
+    assert_eq!(result.http_status, Some(503));
+    assert!(result.latency_ms.is_some());
+    assert!(!check_health(&server.uri()).await);
+}
+
+/// **VALUE**: Verifies nothing listening is probed as unreachable with no status or latency.
+///
+/// **BUG THIS CATCHES**: Would catch a connection error being given a placeholder status
+/// (e.g. 0) that the UI would read as "answered".
+#[tokio::test]
+async fn given_unreachable_port_when_probed_then_unreachable() {
+    // GIVEN: A port with no server and a short timeout
+    let config = HealthCheckConfig {
+        timeout: Duration::from_millis(500),
+        ..Default::default()
+    };
+
+    // WHEN: Probing it
+    let result = probe_with("http://127.0.0.1:65534", &config).await;
+
+    // THEN: Nothing was observed
+    assert_eq!(
+        result,
+        ServerProbe {
+            reachable: false,
+            http_status: None,
+            latency_ms: None,
+        }
+    );
+}
+
+/// **VALUE**: Verifies that the endpoint and accepted statuses are configurable.
+///
+/// **WHY THIS MATTERS**: Other OpenCode versions expose health on a different path and may
+/// answer 304; the probe must be adjustable without code changes.
+///
+/// **BUG THIS CATCHES**: Would catch if `check_health_with()` ignores the configured endpoint,
+/// or if `accept_statuses` doesn't replace the default 2xx rule.
+#[tokio::test]
+async fn given_custom_config_when_check_health_with_called_then_uses_endpoint_and_statuses() {
+    // GIVEN: A server answering /health with 304, and one answering /health with 200
+    let not_modified = mock_health("/health", 304).await;
+    let ok = mock_health("/health", 200).await;
+    let config = HealthCheckConfig {
+        endpoint: "/health".to_string(),
+        accept_statuses: vec![304],
+        ..Default::default()
+    };
+
+    // WHEN / THEN: Only the listed status is accepted, on the configured endpoint
+    assert!(check_health_with(&not_modified.uri(), &config).await);
+    assert_eq!(
+        check_health_status(&ok.uri(), &config).await,
+        HealthStatus::UnexpectedStatus(200)
+    );
+    assert!(
+        !check_health(&not_modified.uri()).await,
+        "Default config probes /doc, which isn't mounted"
+    );
+}
+
+/// **VALUE**: Verifies a server listening only on IPv6 loopback is health-checked at its
+/// bracketed address.
+///
+/// **WHY THIS MATTERS**: Discovery reports IPv6-only servers as `http://[::1]:port`; if the
+/// probe can't handle that URL, every such server looks down.
+///
+/// **BUG THIS CATCHES**: Would catch the host being parsed or rebuilt without brackets
+/// when the endpoint is joined.
+#[tokio::test]
+async fn given_ipv6_loopback_server_when_check_health_called_then_returns_true() {
+    // GIVEN: A server bound to [::1] only, answering /doc
+    let listener = std::net::TcpListener::bind("[::1]:0").expect("bind IPv6 loopback");
+    let port = listener.local_addr().unwrap().port();
+    let server = MockServer::builder().listener(listener).start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    // WHEN: Checking health at the bracketed URL
+    let result = check_health(&format!("http://[::1]:{port}")).await;
+
+    // THEN: Should be healthy
+    assert!(result, "IPv6 loopback server should be reachable");
+}
+
 // ----------------------------------------------------------------------------
 // discover() - Server discovery tests
 // ----------------------------------------------------------------------------
@@ -157,3 +393,77 @@ fn given_no_servers_running_when_discover_called_then_returns_ok() {
         "Discovery should not error when no servers found"
     );
 }
+
+// ----------------------------------------------------------------------------
+// validate_server_info() - Port validation tests
+// ----------------------------------------------------------------------------
+
+/// **VALUE**: Verifies port 0 is rejected.
+///
+/// **WHY THIS MATTERS**: Port 0 means "pick any port" when binding; as a server address it
+/// produces a base URL nothing listens on.
+///
+/// **BUG THIS CATCHES**: Would catch if a server that reported port 0 is handed to the UI
+/// as connectable.
+#[test]
+fn given_port_zero_when_validate_server_info_then_validation_error() {
+    // GIVEN: Server info with port 0
+    let info = server_info(0, "http://127.0.0.1:0");
+
+    // WHEN: Validating it
+    let result = validate_server_info(&info);
+
+    // THEN: Rejected with a clear message
+    match result {
+        Err(DiscoveryError::Validation { message, .. }) => {
+            assert_eq!(message, "Port must be non-zero");
+        }
+        other => panic!("Expected validation error, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies a base URL naming a different port than `port` is rejected.
+///
+/// **WHY THIS MATTERS**: Health checks use `base_url` while other callers use `port`;
+/// if they disagree, the two paths talk to different servers.
+///
+/// **BUG THIS CATCHES**: Would catch if the base URL's port isn't compared, including the
+/// implicit default port of a URL without one.
+#[test]
+fn given_mismatched_base_url_port_when_validate_server_info_then_validation_error() {
+    // GIVEN: Base URLs whose port differs from the recorded one
+    for (port, base_url) in [
+        (4096, "http://127.0.0.1:4097"),
+        (4096, "http://devbox"),
+        (4096, "not a url"),
+        (70000, "http://127.0.0.1:70000"),
+    ] {
+        // WHEN: Validating it
+        let result = validate_server_info(&server_info(port, base_url));
+
+        // THEN: Rejected
+        assert!(
+            matches!(result, Err(DiscoveryError::Validation { .. })),
+            "Expected validation error for {port} / '{base_url}', got {result:?}"
+        );
+    }
+}
+
+/// **VALUE**: Verifies consistent server info passes, including default ports.
+///
+/// **WHY THIS MATTERS**: Validation runs on every spawn; a false rejection makes the app
+/// unable to start a server at all.
+///
+/// **BUG THIS CATCHES**: Would catch if a URL without an explicit port (https on 443) is
+/// treated as a mismatch.
+#[test]
+fn given_matching_port_when_validate_server_info_then_ok() {
+    // GIVEN: Consistent port/base URL pairs
+    for (port, base_url) in [(4096, "http://127.0.0.1:4096"), (443, "https://devbox")] {
+        // WHEN: Validating it
+        let result = validate_server_info(&server_info(port, base_url));
+
+        // THEN: Accepted
+        assert!(result.is_ok(), "Expected {port} / '{base_url}' to be valid");
+    }
+}
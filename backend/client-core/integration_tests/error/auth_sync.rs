@@ -0,0 +1,85 @@
+use client_core::error::AuthSyncError;
+use client_core::error::opencode_client::OpencodeClientError;
+use client_core::proto::message::error::OcMessageError;
+
+use common::{ErrorLocation, RedactedDisplay};
+use std::panic::Location;
+
+/// **VALUE**: Verifies `ProviderSync` errors show the reason phrase next to the status.
+///
+/// **WHY THIS MATTERS**: "HTTP 429" means little to most users; "HTTP 429 Too Many
+/// Requests" tells them to wait instead of checking their key.
+///
+/// **BUG THIS CATCHES**: Would catch if the Display format drops the reason phrase or
+/// shows the phrase for the wrong code.
+#[test]
+fn given_provider_sync_error_when_displayed_then_includes_reason_phrase() {
+    // GIVEN: A provider rejection with a 429 status
+    let err = AuthSyncError::from_http_response("openai", 429, "slow down");
+
+    // WHEN: Displaying it
+    let message = err.to_string();
+
+    // THEN: The status is followed by its reason phrase and the server message
+    assert!(
+        message.starts_with(
+            "Provider sync failed for 'openai': HTTP 429 Too Many Requests - slow down"
+        ),
+        "unexpected message: {message}"
+    );
+}
+
+/// **VALUE**: Verifies a key echoed in a provider's response is masked when redacted.
+///
+/// **WHY THIS MATTERS**: Some providers quote the rejected key in their error body; the
+/// sync failure is logged and shown in the UI.
+///
+/// **BUG THIS CATCHES**: Would catch if `AuthSyncError` stops implementing
+/// `RedactedDisplay`, or if the key survives redaction.
+#[test]
+fn given_key_in_provider_response_when_redacted_then_key_masked() {
+    // GIVEN: A rejection whose body echoes the key
+    let key = "sk-proj-abcdefghijklmnop1234";
+    let err = AuthSyncError::from_http_response("openai", 401, format!("Incorrect key {key}"));
+
+    // WHEN: Redacting it
+    let message = err.redacted_to_string();
+
+    // THEN: The key is masked, the rest is kept
+    assert!(!message.contains(key), "key leaked: {message}");
+    assert!(message.contains("Incorrect key sk-[REDACTED]"), "{message}");
+}
+
+/// **VALUE**: Verifies client errors that never touched the network aren't reported as
+/// network failures.
+///
+/// **WHY THIS MATTERS**: Metrics group sync failures by category; a failed generation
+/// counted as "network" sends whoever reads them looking at connectivity.
+///
+/// **BUG THIS CATCHES**: Would catch an `Assistant` or `InvalidRequest` error mapped to
+/// `Network`.
+#[test]
+fn given_local_client_errors_when_converted_then_request_category() {
+    // GIVEN: A failed assistant message and a request rejected before sending
+    let client_errors = [
+        OpencodeClientError::Assistant {
+            message: "provider rejected the key".to_string(),
+            error: Box::new(OcMessageError::default()),
+            location: ErrorLocation::from(Location::caller()),
+        },
+        OpencodeClientError::InvalidRequest {
+            message: "title must not be empty".to_string(),
+            location: ErrorLocation::from(Location::caller()),
+        },
+    ];
+
+    for client_error in &client_errors {
+        // WHEN: Converting it to an auth sync error
+        let err = AuthSyncError::from_client_error("openai", client_error);
+
+        // THEN: It's a non-retryable request error for the provider
+        assert_eq!(err.error_category(), "request", "{client_error}");
+        assert!(!err.is_retryable());
+        assert_eq!(err.provider(), Some("openai"));
+    }
+}
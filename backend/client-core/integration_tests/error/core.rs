@@ -0,0 +1,98 @@
+use client_core::error::opencode_client::OpencodeClientError;
+use client_core::error::spawn::SpawnError;
+use client_core::error::{AuthSyncError, CoreError};
+use common::ErrorLocation;
+
+use std::panic::Location;
+
+/// Fails with an auth sync error, propagated with `?`.
+fn sync_keys() -> Result<(), CoreError> {
+    Err(AuthSyncError::EnvLoad {
+        message: "could not read .env".to_string(),
+        location: ErrorLocation::from(Location::caller()),
+    })?;
+    Ok(())
+}
+
+/// Fails with an OpenCode client error, propagated with `?`.
+fn list_sessions() -> Result<(), CoreError> {
+    Err(OpencodeClientError::Server {
+        message: "HTTP 503 - list sessions: unavailable".to_string(),
+        status: Some(503),
+        location: ErrorLocation::from(Location::caller()),
+    })?;
+    Ok(())
+}
+
+/// **VALUE**: Verifies `?` turns an `AuthSyncError` into `CoreError::AuthSync` unchanged.
+///
+/// **WHY THIS MATTERS**: Code mixing auth sync with discovery or config returns
+/// `CoreError`; the user must still see the auth sync message, not a generic wrapper.
+///
+/// **BUG THIS CATCHES**: Would catch a missing `From` impl (compile error here) or a
+/// variant that adds its own prefix instead of displaying transparently.
+#[test]
+fn given_auth_sync_error_when_propagated_then_core_error_keeps_message() {
+    // WHEN: An auth sync error is propagated into CoreError
+    let error = sync_keys().unwrap_err();
+
+    // THEN: It is the AuthSync variant and displays as the original error
+    let CoreError::AuthSync(ref inner) = error else {
+        panic!("Expected CoreError::AuthSync, got {error:?}");
+    };
+    assert_eq!(error.to_string(), inner.to_string());
+    assert!(
+        error
+            .to_string()
+            .starts_with("Environment load failed: could not read .env")
+    );
+}
+
+/// **VALUE**: Verifies `?` turns an `OpencodeClientError` into `CoreError::OpencodeClient`
+/// unchanged.
+///
+/// **BUG THIS CATCHES**: Would catch a missing `From` impl, or the wrapped error's details
+/// (e.g. its HTTP status) being lost on the way.
+#[test]
+fn given_opencode_client_error_when_propagated_then_core_error_keeps_message() {
+    // WHEN: An OpenCode client error is propagated into CoreError
+    let error = list_sessions().unwrap_err();
+
+    // THEN: It is the OpencodeClient variant, displays as the original, and keeps its status
+    let CoreError::OpencodeClient(ref inner) = error else {
+        panic!("Expected CoreError::OpencodeClient, got {error:?}");
+    };
+    assert_eq!(error.to_string(), inner.to_string());
+    assert!(
+        error
+            .to_string()
+            .contains("HTTP 503 - list sessions: unavailable")
+    );
+    assert_eq!(inner.status_code(), Some(503));
+}
+
+/// **VALUE**: Verifies `CoreError::details` exposes the wrapped error's category and
+/// retryability, and is `None` for errors without details.
+///
+/// **WHY THIS MATTERS**: `ensure_server` returns `CoreError`; the IPC handler reports its
+/// details so the frontend can offer a retry for a server that was slow to start.
+///
+/// **BUG THIS CATCHES**: Would catch a variant returning the wrong inner error, or a
+/// spawn timeout being reported as permanent.
+#[test]
+fn given_core_errors_when_details_requested_then_inner_details_returned() {
+    // GIVEN: A spawn timeout and an auth sync error, both wrapped in CoreError
+    let spawn = CoreError::from(SpawnError::Timeout {
+        message: "server not ready after 10s".to_string(),
+        location: ErrorLocation::from(Location::caller()),
+    });
+    let auth = sync_keys().unwrap_err();
+
+    // WHEN: Asking for their details
+    let spawn_details = spawn.details().expect("spawn errors have details");
+
+    // THEN: The spawn timeout is a retryable timeout; the auth sync error has none
+    assert_eq!(spawn_details.category(), "timeout");
+    assert!(spawn_details.is_retryable());
+    assert!(auth.details().is_none());
+}
@@ -0,0 +1,174 @@
+use client_core::error::ErrorDetails;
+use client_core::error::ipc::IpcError;
+use client_core::error::opencode_client::OpencodeClientError;
+use client_core::proto::{IpcErrorCode, IpcErrorResponse};
+
+use common::{ErrorLocation, RedactedDisplay};
+
+use std::panic::Location;
+
+use prost::Message;
+
+#[track_caller]
+fn server_error(status: u16) -> OpencodeClientError {
+    OpencodeClientError::Server {
+        message: format!("HTTP {status} - upstream failure"),
+        status: Some(status),
+        location: ErrorLocation::from(Location::caller()),
+    }
+}
+
+fn assert_same_location(actual: &ErrorLocation, expected: &ErrorLocation) {
+    assert_eq!(
+        (actual.file.as_ref(), actual.line, actual.column),
+        (expected.file.as_ref(), expected.line, expected.column)
+    );
+}
+
+/// **VALUE**: Verifies a server-side error keeps its location and category across protobuf.
+///
+/// **WHY THIS MATTERS**: The frontend only sees what survives the IPC boundary; without the
+/// structured fields it can't show file:line or decide whether to offer a retry.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - `with_details` drops the location, category, or retryability
+/// - The client maps the wrong location (its own) into `remote_location`
+/// - The legacy `message` string is no longer populated
+#[test]
+fn given_opencode_client_error_when_sent_as_ipc_error_response_then_details_round_trip() {
+    // GIVEN: A retryable 503 from the OpenCode server
+    let source = server_error(503);
+
+    // WHEN: Encoding it as an IPC error response and decoding it on the client side
+    let response = IpcErrorResponse::with_details(
+        IpcErrorCode::ServerError,
+        format!("Failed to send message: {source}"),
+        &source,
+    );
+    let decoded = IpcErrorResponse::decode(&response.encode_to_vec()[..]).unwrap();
+    let error = IpcError::from(decoded);
+
+    // THEN: Code, message, server location, category and retryability are preserved
+    match &error {
+        IpcError::Remote {
+            code,
+            message,
+            remote_location,
+            category,
+            retryable,
+            ..
+        } => {
+            assert_eq!(*code, IpcErrorCode::ServerError);
+            assert!(message.starts_with("Failed to send message: Server Error: HTTP 503"));
+            assert_same_location(
+                remote_location.as_ref().expect("Location should be sent"),
+                source.location(),
+            );
+            assert_eq!(category, "server_error");
+            assert!(*retryable);
+        }
+        other => panic!("Expected Remote, got {other:?}"),
+    }
+
+    // THEN: The client error reports the server's details through the same trait
+    assert_eq!(error.category(), "remote");
+    assert!(error.is_retryable());
+    assert_same_location(error.location(), source.location());
+}
+
+/// **VALUE**: Verifies error enums round-trip through JSON with location and category intact.
+///
+/// **WHY THIS MATTERS**: Errors are also passed to the webview as JSON; a lossy round trip
+/// would make the same failure look different depending on the path it took.
+///
+/// **BUG THIS CATCHES**: Would catch if the variant tag, a field, or the location is lost
+/// in serialization.
+#[test]
+fn given_errors_when_round_tripped_through_json_then_location_and_category_preserved() {
+    // GIVEN: A client error and a remote IPC error
+    let client_error = OpencodeClientError::Timeout {
+        context: "list sessions".to_string(),
+        message: "operation timed out".to_string(),
+        location: ErrorLocation::from(Location::caller()),
+    };
+    let remote = IpcError::Remote {
+        code: IpcErrorCode::NoServer,
+        message: "No OpenCode server connected".to_string(),
+        remote_location: Some(ErrorLocation::new("src/ipc/server.rs", 42, 9)),
+        category: "no_server".to_string(),
+        retryable: false,
+        location: ErrorLocation::from(Location::caller()),
+    };
+
+    // WHEN: Serializing to JSON and back
+    let client_json = serde_json::to_string(&client_error).unwrap();
+    let client_back: OpencodeClientError = serde_json::from_str(&client_json).unwrap();
+    let remote_json = serde_json::to_string(&remote).unwrap();
+    let remote_back: IpcError = serde_json::from_str(&remote_json).unwrap();
+
+    // THEN: Variant, category, retryability and location survive
+    assert_eq!(client_back.error_category(), "timeout");
+    assert!(client_back.is_retryable());
+    assert_same_location(client_back.location(), client_error.location());
+
+    match remote_back {
+        IpcError::Remote {
+            code,
+            remote_location: Some(location),
+            category,
+            ..
+        } => {
+            assert_eq!(code, IpcErrorCode::NoServer);
+            assert_eq!(category, "no_server");
+            assert_same_location(&location, &ErrorLocation::new("src/ipc/server.rs", 42, 9));
+        }
+        other => panic!("Expected Remote with location, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies keys in IPC handler errors are masked when redacted.
+///
+/// **WHY THIS MATTERS**: Handler errors are logged and sent to the frontend, and often
+/// wrap OpenCode errors that quote request data.
+///
+/// **BUG THIS CATCHES**: Would catch if `IpcError` stops implementing `RedactedDisplay`, or
+/// if the key survives redaction.
+#[test]
+fn given_key_in_ipc_error_when_redacted_then_key_masked() {
+    // GIVEN: An IPC error wrapping a message with a key
+    let key = "sk-abcdefghijklmnop1234";
+    let err = IpcError::Read {
+        message: format!("Failed to sync key {key}"),
+        location: ErrorLocation::from(Location::caller()),
+    };
+
+    // WHEN: Redacting it
+    let message = err.redacted_to_string();
+
+    // THEN: The key is masked
+    assert!(!message.contains(key), "key leaked: {message}");
+    assert!(
+        message.contains("Failed to sync key sk-[REDACTED]"),
+        "{message}"
+    );
+}
+
+/// **VALUE**: Verifies a dropped connection isn't reported as retryable.
+///
+/// **WHY THIS MATTERS**: The server may already have handled the request in flight;
+/// a caller that retries on `is_retryable()` would send it twice (two messages, two
+/// sessions).
+///
+/// **BUG THIS CATCHES**: Would catch `ConnectionLost` being grouped with retryable errors.
+#[test]
+fn given_connection_lost_when_checked_then_not_retryable() {
+    // GIVEN: A connection that dropped with a request in flight
+    let err = IpcError::ConnectionLost {
+        message: "connection closed".to_string(),
+        location: ErrorLocation::from(Location::caller()),
+    };
+
+    // THEN: It is not retryable
+    assert_eq!(err.error_category(), "connection_lost");
+    assert!(!err.is_retryable());
+}
@@ -1,2 +1,6 @@
+mod auth_sync;
+mod core;
 mod discovery;
+mod ipc;
+mod opencode_client;
 mod spawn;
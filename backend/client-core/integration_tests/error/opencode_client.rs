@@ -0,0 +1,306 @@
+use client_core::error::opencode_client::OpencodeClientError;
+use client_core::opencode_client::OpencodeClient;
+use common::{ErrorLocation, RedactedDisplay};
+
+use std::panic::Location;
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[track_caller]
+fn server_error(status: u16) -> OpencodeClientError {
+    OpencodeClientError::Server {
+        message: format!("HTTP {status} - upstream failure"),
+        status: Some(status),
+        location: ErrorLocation::from(Location::caller()),
+    }
+}
+
+/// **VALUE**: Verifies a 429 server rejection is reported as retryable with its status.
+///
+/// **WHY THIS MATTERS**: Rate limiting is transient; callers should back off and retry
+/// instead of surfacing an error to the user.
+///
+/// **BUG THIS CATCHES**: Would catch if the status isn't kept on the server error or if
+/// 429 is missing from the retryable set.
+#[test]
+fn given_429_server_error_when_classified_then_is_retryable() {
+    // GIVEN: A rate-limit rejection
+    let err = server_error(429);
+
+    // WHEN/THEN: It exposes its status and is retryable, but isn't a transport failure
+    assert_eq!(err.status_code(), Some(429));
+    assert!(err.is_retryable());
+    assert!(!err.is_timeout());
+    assert!(!err.is_connection());
+}
+
+/// **VALUE**: Verifies a 500 server rejection keeps its status but is not retryable.
+///
+/// **WHY THIS MATTERS**: A 500 usually means the request hit a server bug; repeating it
+/// only delays the error (matching `AuthSyncError`'s classification).
+///
+/// **BUG THIS CATCHES**: Would catch if every server error is treated as transient or if
+/// the two error types disagree on which statuses are retryable.
+#[test]
+fn given_500_server_error_when_classified_then_is_not_retryable() {
+    // GIVEN: An internal server error
+    let err = server_error(500);
+
+    // WHEN/THEN: The status is available but the error is not retryable
+    assert_eq!(err.status_code(), Some(500));
+    assert!(!err.is_retryable());
+}
+
+/// **VALUE**: Verifies a server error without a status has no status code.
+///
+/// **WHY THIS MATTERS**: Some server errors describe malformed responses rather than
+/// HTTP rejections; inventing a status would misclassify them.
+///
+/// **BUG THIS CATCHES**: Would catch if the status is inferred from the message text.
+#[test]
+fn given_server_error_without_status_when_classified_then_has_no_status_code() {
+    // GIVEN: A server error that isn't an HTTP rejection, quoting a status in its message
+    let err = OpencodeClientError::Server {
+        message: "Expected an array of messages, got 'HTTP 503'".to_string(),
+        status: None,
+        location: ErrorLocation::from(Location::caller()),
+    };
+
+    // WHEN/THEN: No status and not retryable
+    assert_eq!(err.status_code(), None);
+    assert!(!err.is_retryable());
+}
+
+/// **VALUE**: Verifies a request timeout is categorized as a retryable timeout.
+///
+/// **WHY THIS MATTERS**: A slow server (e.g. mid-restart) is a transient condition, and
+/// the UI reports timeouts differently from refused connections.
+///
+/// **BUG THIS CATCHES**: Would catch if `From<reqwest::Error>` drops the timeout flag
+/// by converting the error to a string first.
+#[tokio::test]
+async fn given_slow_server_when_request_times_out_then_error_is_retryable_timeout() {
+    // GIVEN: A server that responds far slower than the client timeout
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("[]")
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::with_timeout(&server.uri(), Duration::from_millis(50)).unwrap();
+
+    // WHEN: Listing sessions
+    let err = client.list_sessions().await.unwrap_err();
+
+    // THEN: The error is a retryable timeout without a status
+    assert!(err.is_timeout());
+    assert!(!err.is_connection());
+    assert!(err.is_retryable());
+    assert_eq!(err.status_code(), None);
+}
+
+/// **VALUE**: Verifies a refused connection is categorized as a retryable connection error.
+///
+/// **WHY THIS MATTERS**: A refused connection means the server is down or restarting,
+/// which callers can wait out.
+///
+/// **BUG THIS CATCHES**: Would catch if connection failures lose their category when
+/// converted from `reqwest::Error`.
+#[tokio::test]
+async fn given_no_listener_when_request_sent_then_error_is_retryable_connection() {
+    // GIVEN: A port with nothing listening (bind, then release it)
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    let client = OpencodeClient::new(&format!("http://127.0.0.1:{port}")).unwrap();
+
+    // WHEN: Listing sessions
+    let err = client.list_sessions().await.unwrap_err();
+
+    // THEN: The error is a retryable connection failure
+    assert!(err.is_connection());
+    assert!(!err.is_timeout());
+    assert!(err.is_retryable());
+}
+
+/// **VALUE**: Verifies `from_reqwest` turns a timed-out request into `Timeout`.
+///
+/// **WHY THIS MATTERS**: A slow server and a dead one need different messages ("still
+/// working" vs. "not running"), and timeouts are worth retrying.
+///
+/// **BUG THIS CATCHES**: Would catch if timeouts fall through to the generic network case.
+#[tokio::test]
+async fn given_timed_out_reqwest_error_when_from_reqwest_then_timeout_with_context() {
+    // GIVEN: A reqwest timeout from a server slower than the client
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+    let error = reqwest::Client::new()
+        .get(server.uri())
+        .timeout(Duration::from_millis(50))
+        .send()
+        .await
+        .unwrap_err();
+
+    // WHEN: Categorizing it
+    let err = OpencodeClientError::from_reqwest("list sessions", &error);
+
+    // THEN: It is a retryable timeout that names the operation
+    assert!(
+        matches!(&err, OpencodeClientError::Timeout { context, .. } if context == "list sessions")
+    );
+    assert!(err.is_retryable());
+    assert_eq!(err.error_category(), "timeout");
+}
+
+/// **VALUE**: Verifies `from_reqwest` turns a refused connection into a `Network` error.
+///
+/// **WHY THIS MATTERS**: "Server not running" is the most common failure; the UI offers to
+/// start the server only when it can recognize it.
+///
+/// **BUG THIS CATCHES**: Would catch if `is_connection` isn't recorded.
+#[tokio::test]
+async fn given_refused_connection_when_from_reqwest_then_network_connection_error() {
+    // GIVEN: A reqwest error from a port with nothing listening
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    let error = reqwest::get(format!("http://127.0.0.1:{port}"))
+        .await
+        .unwrap_err();
+
+    // WHEN: Categorizing it
+    let err = OpencodeClientError::from_reqwest("create session", &error);
+
+    // THEN: It is a retryable connection failure
+    assert!(matches!(
+        err,
+        OpencodeClientError::Network {
+            is_connection: true,
+            ..
+        }
+    ));
+    assert!(err.is_retryable());
+    assert_eq!(err.error_category(), "connection");
+    assert!(err.to_string().contains("create session"));
+}
+
+/// **VALUE**: Verifies `from_reqwest` turns a status error into `Server` with its status.
+///
+/// **WHY THIS MATTERS**: A 500 means the server is up but failing; it must not be
+/// confused with a network problem.
+///
+/// **BUG THIS CATCHES**: Would catch if the status is dropped, which also breaks
+/// `status_code()` and status-based retry decisions.
+#[tokio::test]
+async fn given_status_reqwest_error_when_from_reqwest_then_server_error_with_status() {
+    // GIVEN: A reqwest error produced from a 500 response
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let error = reqwest::get(server.uri())
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap_err();
+
+    // WHEN: Categorizing it
+    let err = OpencodeClientError::from_reqwest("abort message", &error);
+
+    // THEN: It is a server error carrying the status
+    assert!(matches!(err, OpencodeClientError::Server { .. }));
+    assert_eq!(err.status_code(), Some(500));
+    assert!(!err.is_retryable());
+    assert_eq!(err.error_category(), "server_error");
+}
+
+/// **VALUE**: Verifies `from_reqwest` turns an undecodable body into `Json`.
+///
+/// **WHY THIS MATTERS**: A body we can't parse usually means an OpenCode version mismatch;
+/// retrying won't help and reporting it as a network error would mislead.
+///
+/// **BUG THIS CATCHES**: Would catch if decode errors are reported as network errors.
+#[tokio::test]
+async fn given_decode_reqwest_error_when_from_reqwest_then_json_error() {
+    // GIVEN: A reqwest error from decoding a non-JSON body
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+        .mount(&server)
+        .await;
+    let error = reqwest::get(server.uri())
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap_err();
+
+    // WHEN: Categorizing it
+    let err = OpencodeClientError::from_reqwest("list messages", &error);
+
+    // THEN: It is a non-retryable JSON error
+    assert!(matches!(err, OpencodeClientError::Json { .. }));
+    assert!(!err.is_retryable());
+    assert_eq!(err.error_category(), "json");
+}
+
+/// **VALUE**: Verifies other transport failures are a non-retryable `Network` error.
+///
+/// **WHY THIS MATTERS**: Errors like an unusable URL won't fix themselves; retrying them
+/// only delays the error the user needs to see.
+///
+/// **BUG THIS CATCHES**: Would catch if every network error is marked retryable.
+#[tokio::test]
+async fn given_other_transport_error_when_from_reqwest_then_non_retryable_network_error() {
+    // GIVEN: A reqwest error for a URL reqwest can't send to
+    let error = reqwest::get("ftp://127.0.0.1/").await.unwrap_err();
+
+    // WHEN: Categorizing it
+    let err = OpencodeClientError::from_reqwest("sync API key", &error);
+
+    // THEN: It is a network error without a connection failure
+    assert!(matches!(
+        err,
+        OpencodeClientError::Network {
+            is_connection: false,
+            ..
+        }
+    ));
+    assert!(!err.is_retryable());
+    assert_eq!(err.error_category(), "network");
+}
+
+/// **VALUE**: Verifies keys in OpenCode server error bodies are masked when redacted.
+///
+/// **WHY THIS MATTERS**: `sync_api_key` failures carry the server's response body, which
+/// can include the submitted key, and are logged by the auth sync.
+///
+/// **BUG THIS CATCHES**: Would catch if `OpencodeClientError` stops implementing
+/// `RedactedDisplay`, or if the key survives redaction.
+#[test]
+fn given_key_in_server_error_when_redacted_then_key_masked() {
+    // GIVEN: A server error quoting a key
+    let key = "sk-ant-REDACTED";
+    let err = OpencodeClientError::Server {
+        message: format!("HTTP 400 - invalid key {key}"),
+        status: Some(400),
+        location: ErrorLocation::from(Location::caller()),
+    };
+
+    // WHEN: Redacting it
+    let message = err.redacted_to_string();
+
+    // THEN: The key is masked
+    assert!(!message.contains(key), "key leaked: {message}");
+    assert!(message.contains("invalid key sk-[REDACTED]"), "{message}");
+}
@@ -0,0 +1,1146 @@
+use crate::ipc_tests::helpers::{
+    TEST_AUTH_TOKEN, start_killable_ipc_server, start_test_ipc_server,
+    start_test_ipc_server_with_config_state,
+};
+
+use client_core::config::{AppConfig, ModelsConfig};
+use client_core::error::ipc::IpcError;
+use client_core::ipc::{
+    ConfigState, ConnectionPhase, ConnectionStatus, IpcClient, ReconnectPolicy,
+};
+use client_core::proto::{IpcCuratedModel, IpcErrorCode, ipc_server_state_event};
+
+use std::time::Duration;
+
+use tempfile::TempDir;
+
+fn curated(name: &str, provider: &str, model_id: &str) -> IpcCuratedModel {
+    IpcCuratedModel {
+        name: name.to_string(),
+        provider: provider.to_string(),
+        model_id: model_id.to_string(),
+    }
+}
+
+/// Start a server whose config dir is `dir`, returning a connected client.
+async fn connect_with_config_dir(
+    ipc_port: u16,
+    dir: &TempDir,
+) -> (client_core::ipc::IpcServerHandle, IpcClient) {
+    let config_state = ConfigState::new(
+        dir.path().to_path_buf(),
+        AppConfig::default(),
+        ModelsConfig::default(),
+    );
+    let handle = start_test_ipc_server_with_config_state(
+        ipc_port,
+        Some(String::from(TEST_AUTH_TOKEN)),
+        config_state,
+    )
+    .await
+    .expect("Failed to start IPC server");
+    let client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+    (handle, client)
+}
+
+/// **VALUE**: Verifies the typed client authenticates and round-trips a config request.
+///
+/// **WHY THIS MATTERS**: `IpcClient` is the entry point for tooling and tests; if connect or
+/// response decoding is wrong, every consumer sees confusing failures.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - The auth handshake isn't performed on connect
+/// - Request IDs collide with the reserved auth ID
+/// - The response variant isn't matched to the request
+#[tokio::test]
+async fn given_running_server_when_client_get_config_then_returns_app_config() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19892;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    // GIVEN: Connected typed client
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+
+    // WHEN: Requesting config twice over the same connection
+    let first = client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+    let second = client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+
+    // THEN: Both responses carry the deserializable app config
+    let app_config: AppConfig =
+        serde_json::from_str(&first.app_config_json).expect("Should be valid AppConfig JSON");
+    assert_eq!(app_config.version, AppConfig::default().version);
+    assert_eq!(first.app_config_json, second.app_config_json);
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies a wrong token surfaces as an auth error from `connect`.
+///
+/// **WHY THIS MATTERS**: Tooling must be able to tell "bad token" apart from "server down".
+///
+/// **BUG THIS CATCHES**: Would catch if a rejected handshake returns a usable client.
+#[tokio::test]
+async fn given_wrong_token_when_client_connect_then_returns_auth_error() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19893;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    // WHEN: Connecting with the wrong token
+    let result = IpcClient::connect(ipc_port, "wrong-token").await;
+
+    // THEN: Auth error is returned
+    assert!(matches!(result, Err(IpcError::Auth { .. })));
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies server error responses are decoded into `IpcError::Remote`.
+///
+/// **WHY THIS MATTERS**: Callers need the server's error code to react (e.g. prompt to
+/// connect a server) instead of parsing strings.
+///
+/// **BUG THIS CATCHES**: Would catch if error payloads are reported as unexpected variants.
+#[tokio::test]
+async fn given_no_opencode_server_when_client_list_sessions_then_returns_remote_error() {
+    // GIVEN: IPC server with no OpenCode server connected
+    let ipc_port = 19894;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+
+    // WHEN: Listing sessions
+    let result = client.list_sessions().await;
+
+    // THEN: Server error is surfaced with its code
+    match result {
+        Err(IpcError::Remote { code, .. }) => assert_eq!(code, IpcErrorCode::InternalError),
+        other => panic!("Expected remote error, got {other:?}"),
+    }
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies an invalid models config is rejected over IPC and not applied.
+///
+/// **WHY THIS MATTERS**: The frontend needs a clear failure when it sends a bad provider
+/// definition, and the running backend must keep using the last good config.
+///
+/// **BUG THIS CATCHES**: Would catch if the handler skips `ModelsConfig::validate` and
+/// reports success for a config the actor silently drops (or worse, applies).
+#[tokio::test]
+async fn given_invalid_auth_type_when_client_update_models_config_then_rejected() {
+    // GIVEN: IPC server running on test port with default models config
+    let ipc_port = 19896;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+    let before = client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+
+    // WHEN: Sending a provider with an unknown auth_type
+    let invalid = r#"{
+        "providers": [{
+            "name": "openai",
+            "display_name": "OpenAI",
+            "api_key_env": "OPENAI_API_KEY",
+            "models_url": "https://api.openai.com/v1/models",
+            "auth_type": "magic",
+            "response_format": {
+                "models_path": "data",
+                "model_id_field": "id",
+                "model_name_field": "id"
+            }
+        }]
+    }"#;
+    let response = client
+        .update_models_config(invalid)
+        .await
+        .expect("update_models_config should return a response");
+
+    // THEN: The update is rejected with the validation reason
+    assert!(!response.success);
+    let error = response
+        .error
+        .expect("Rejected update should carry an error");
+    assert!(error.contains("auth_type"), "unexpected error: {error}");
+
+    // THEN: The models config is unchanged
+    let after = client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+    let models: ModelsConfig =
+        serde_json::from_str(&after.models_config_json).expect("Should be valid ModelsConfig JSON");
+    assert!(models.providers.is_empty());
+    assert_eq!(before.models_config_json, after.models_config_json);
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies an invalid app config is rejected over IPC with its validation reason.
+///
+/// **WHY THIS MATTERS**: The settings UI must tell the user why a change didn't stick
+/// instead of showing it as saved.
+///
+/// **BUG THIS CATCHES**: Would catch if the handler reports success while the config actor
+/// drops the rejected config, or collapses the reason into a generic message.
+#[tokio::test]
+async fn given_out_of_range_font_size_when_client_update_config_then_rejected_with_reason() {
+    // GIVEN: IPC server running on test port with default app config
+    let ipc_port = 19906;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+    let before = client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+
+    // WHEN: Sending a font size outside 8.0-72.0
+    let mut config = AppConfig::default();
+    config.ui.base_font_points = 200.0;
+    let response = client
+        .update_config(&serde_json::to_string(&config).unwrap())
+        .await
+        .expect("update_config should return a response");
+
+    // THEN: The update is rejected with a reason naming the setting
+    assert!(!response.success);
+    let error = response
+        .error
+        .expect("Rejected update should carry an error");
+    assert!(
+        error.contains("base_font_points"),
+        "unexpected error: {error}"
+    );
+
+    // THEN: The app config is unchanged
+    let after = client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+    assert_eq!(before.app_config_json, after.app_config_json);
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies curated models added over IPC are deduplicated and saved to models.toml.
+///
+/// **WHY THIS MATTERS**: The model picker is built from the curated list; it must survive
+/// restarts and must not show the same model twice.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - The add handler bypasses `ModelsConfig::add_curated_model` (no dedup)
+/// - The change is kept in memory only and never written to models.toml
+#[tokio::test]
+async fn given_curated_model_when_added_twice_then_persisted_once() {
+    // GIVEN: IPC server with a temp config dir
+    let dir = TempDir::new().unwrap();
+    let (handle, mut client) = connect_with_config_dir(19897, &dir).await;
+
+    // WHEN: Adding the same provider/model twice (different display names)
+    let first = client
+        .add_curated_model(curated("GPT-4o", "openai", "gpt-4o"))
+        .await
+        .expect("add should succeed");
+    let second = client
+        .add_curated_model(curated("GPT-4o (again)", "openai", "gpt-4o"))
+        .await
+        .expect("add should succeed");
+
+    // THEN: The curated list holds one entry, keeping the first name
+    assert_eq!(first, vec![curated("GPT-4o", "openai", "gpt-4o")]);
+    assert_eq!(second, first);
+
+    // THEN: models.toml on disk matches
+    let saved = ModelsConfig::load(dir.path()).expect("models.toml should load");
+    let saved_curated = saved.get_curated_models();
+    assert_eq!(saved_curated.len(), 1);
+    assert_eq!(saved_curated[0].name, "GPT-4o");
+    assert_eq!(saved_curated[0].model_id, "gpt-4o");
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies removing curated models over IPC, including ones that aren't curated.
+///
+/// **WHY THIS MATTERS**: The UI may send a stale remove (double click, two windows); that
+/// must not fail or disturb the remaining models.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Removing an absent model returns an error
+/// - Removal matches on provider only and drops sibling models
+/// - The removal isn't written to models.toml
+#[tokio::test]
+async fn given_curated_models_when_removed_then_persisted_and_absent_is_noop() {
+    // GIVEN: IPC server with two curated models from the same provider
+    let dir = TempDir::new().unwrap();
+    let (handle, mut client) = connect_with_config_dir(19898, &dir).await;
+    client
+        .add_curated_model(curated("GPT-4o", "openai", "gpt-4o"))
+        .await
+        .expect("add should succeed");
+    client
+        .add_curated_model(curated("GPT-4o mini", "openai", "gpt-4o-mini"))
+        .await
+        .expect("add should succeed");
+
+    // WHEN: Removing a model that isn't curated
+    let unchanged = client
+        .remove_curated_model("anthropic", "claude-sonnet")
+        .await
+        .expect("remove of absent model should succeed");
+
+    // THEN: Both models remain
+    assert_eq!(unchanged.len(), 2);
+
+    // WHEN: Removing one curated model
+    let remaining = client
+        .remove_curated_model("openai", "gpt-4o")
+        .await
+        .expect("remove should succeed");
+
+    // THEN: Only the sibling remains, in memory and on disk
+    assert_eq!(
+        remaining,
+        vec![curated("GPT-4o mini", "openai", "gpt-4o-mini")]
+    );
+    let saved = ModelsConfig::load(dir.path()).expect("models.toml should load");
+    let saved_ids: Vec<&str> = saved
+        .get_curated_models()
+        .iter()
+        .map(|m| m.model_id.as_str())
+        .collect();
+    assert_eq!(saved_ids, vec!["gpt-4o-mini"]);
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies a chat message round-trips through IPC to the OpenCode server and back.
+///
+/// **WHY THIS MATTERS**: Sending a message is the core feature of the app; this covers the
+/// whole path from IPC request to `OpencodeClient::send_message` and back to an `OcMessage`.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - The handler isn't wired to the spawned server's client
+/// - Model/provider/agent fields are dropped on the way to OpenCode
+/// - The assistant reply isn't returned as `SendMessageResponse`
+#[cfg(unix)]
+#[tokio::test]
+async fn given_connected_server_when_client_send_message_then_returns_assistant_reply() {
+    use crate::ipc_tests::helpers::install_fake_opencode;
+    use client_core::proto::IpcSendMessageRequest;
+    use client_core::proto::message::oc_message::Message;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // GIVEN: A mock OpenCode server that answers health checks and one chat message
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&opencode)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/message"))
+        .and(body_partial_json(serde_json::json!({
+            "model": { "modelID": "claude-3-5-sonnet-20241022", "providerID": "anthropic" },
+            "agent": "build"
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "info": {
+                "id": "msg_assistant_1",
+                "sessionID": "ses_test",
+                "role": "assistant",
+                "time": { "created": 1767225601000u64, "completed": 1767225603000u64 },
+                "parentID": "msg_user_1",
+                "modelID": "claude-3-5-sonnet-20241022",
+                "providerID": "anthropic",
+                "cost": 0.0012,
+                "tokens": { "input": 12, "output": 5, "reasoning": 0, "cache": { "read": 0, "write": 0 } }
+            },
+            "parts": [{
+                "id": "prt_assistant_1",
+                "sessionID": "ses_test",
+                "messageID": "msg_assistant_1",
+                "type": "text",
+                "text": "2 + 2 = 4"
+            }]
+        })))
+        .expect(1)
+        .mount(&opencode)
+        .await;
+
+    // GIVEN: A fake `opencode` binary that reports the mock server's URL
+    let _fake_opencode = install_fake_opencode(&format!(
+        "#!/bin/sh\necho \"opencode server listening on {}\"\n",
+        opencode.uri()
+    ))
+    .await;
+
+    // GIVEN: Connected client with the (fake) server spawned
+    let ipc_port = 19899;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+    let server = client
+        .spawn_server(None)
+        .await
+        .expect("spawn_server should succeed");
+    assert_eq!(server.base_url, opencode.uri());
+
+    // WHEN: Sending a message
+    let reply = client
+        .send_message(IpcSendMessageRequest {
+            session_id: "ses_test".to_string(),
+            text: "What is 2 + 2?".to_string(),
+            model_id: "claude-3-5-sonnet-20241022".to_string(),
+            provider_id: "anthropic".to_string(),
+            agent: None,
+        })
+        .await
+        .expect("send_message should succeed");
+
+    // THEN: The assistant reply comes back with its parts
+    match reply.message {
+        Some(Message::Assistant(assistant)) => {
+            assert_eq!(assistant.id, "msg_assistant_1");
+            assert_eq!(assistant.session_id, "ses_test");
+            assert_eq!(assistant.parts.len(), 1);
+        }
+        other => panic!("Expected assistant message, got {other:?}"),
+    }
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies send_message without a connected server fails with `NoServer`.
+///
+/// **WHY THIS MATTERS**: The UI shows "start the server" guidance only for `NoServer`;
+/// a generic server error would send users looking for a broken OpenCode instance.
+///
+/// **BUG THIS CATCHES**: Would catch if the no-server case is reported as `ServerError`
+/// or if the handler panics on a missing client.
+#[tokio::test]
+async fn given_no_opencode_server_when_client_send_message_then_returns_no_server() {
+    use client_core::proto::IpcSendMessageRequest;
+
+    // GIVEN: IPC server without an OpenCode server
+    let ipc_port = 19900;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+
+    // WHEN: Sending a message
+    let result = client
+        .send_message(IpcSendMessageRequest {
+            session_id: "ses_test".to_string(),
+            text: "hello".to_string(),
+            model_id: "gpt-4o".to_string(),
+            provider_id: "openai".to_string(),
+            agent: None,
+        })
+        .await;
+
+    // THEN: The server reports NoServer
+    match result {
+        Err(IpcError::Remote { code, .. }) => assert_eq!(code, IpcErrorCode::NoServer),
+        other => panic!("Expected NoServer error, got {other:?}"),
+    }
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies token rotation affects new handshakes only.
+///
+/// **WHY THIS MATTERS**: Rotation is the response to a leaked token; it must lock the old
+/// token out immediately without disconnecting the frontend that is already authenticated.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Connections keep validating against the token captured at server start
+/// - Rotation tears down existing authenticated connections
+#[tokio::test]
+async fn given_rotated_token_when_connecting_then_only_new_token_accepted() {
+    // GIVEN: IPC server with a client authenticated under the original token
+    let ipc_port = 19902;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut existing = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect with the original token");
+
+    // WHEN: Rotating the token
+    let new_token = handle.rotate_auth_token().await;
+    assert_ne!(new_token.as_str(), TEST_AUTH_TOKEN);
+
+    // THEN: The old token is rejected for new handshakes
+    match IpcClient::connect(ipc_port, TEST_AUTH_TOKEN).await {
+        Err(IpcError::Auth { .. }) => {}
+        Err(other) => panic!("Expected Auth error, got {other:?}"),
+        Ok(_) => panic!("Old token must be rejected after rotation"),
+    }
+
+    // THEN: The new token is accepted
+    let fresh = IpcClient::connect(ipc_port, new_token.as_str())
+        .await
+        .expect("Client should connect with the rotated token");
+
+    // THEN: The existing connection keeps working
+    existing
+        .get_config()
+        .await
+        .expect("Existing connection should survive rotation");
+
+    fresh.close().await.expect("close should succeed");
+    existing.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies the handle reports its port, that it is running, and how many
+/// connections it is serving.
+///
+/// **WHY THIS MATTERS**: The desktop app shows this status to the frontend; a count that
+/// never drops would make a disconnected frontend look connected.
+///
+/// **BUG THIS CATCHES**: Would catch connection slots not being released when a client
+/// closes, or the handle reporting the requested rather than the bound port.
+#[tokio::test]
+async fn given_running_server_when_client_connects_and_closes_then_handle_reports_status() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19922;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    assert_eq!(handle.port(), ipc_port);
+    assert!(handle.is_running());
+    assert_eq!(handle.connection_count(), 0);
+
+    // WHEN: A client connects
+    let client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect");
+
+    // THEN: It is counted
+    assert_eq!(handle.connection_count(), 1);
+
+    // WHEN: The client closes
+    client.close().await.expect("close should succeed");
+
+    // THEN: Its slot is released once the server notices
+    let released = async {
+        while handle.connection_count() != 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), released)
+        .await
+        .expect("connection should be released after close");
+    assert!(handle.is_running());
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies the handle exposes each connection's auth phase.
+///
+/// **WHY THIS MATTERS**: The desktop app sits outside `client-core`; without a public view
+/// of the auth lifecycle it can't tell an authenticated frontend from one still handshaking.
+///
+/// **BUG THIS CATCHES**: Would catch phases not being published to the handle, or a closed
+/// connection staying listed.
+#[tokio::test]
+async fn given_authenticated_client_when_reading_handle_then_phase_reported() {
+    // GIVEN: IPC server running on test port with no connections
+    let ipc_port = 19930;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    assert!(handle.connection_phases().is_empty());
+
+    // WHEN: A client connects and authenticates
+    let client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect");
+
+    // THEN: Its connection is listed as authenticated
+    let phases = handle.connection_phases();
+    assert_eq!(phases.len(), 1);
+    assert_eq!(phases[0].1, ConnectionPhase::Authenticated);
+
+    // WHEN: The client closes
+    client.close().await.expect("close should succeed");
+
+    // THEN: It is no longer listed once the server notices
+    let removed = async {
+        while !handle.connection_phases().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), removed)
+        .await
+        .expect("connection phase should be removed after close");
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies directories set over IPC reach OpenCode as the directory header.
+///
+/// **WHY THIS MATTERS**: The UI switches projects by setting the directory; if the header
+/// doesn't follow, sessions are created and listed in the wrong project.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - `SetDirectory` isn't routed to the state actor
+/// - The reply is sent before the directory is applied (racing the next request)
+/// - Session handlers ignore the per-session override
+#[cfg(unix)]
+#[tokio::test]
+async fn given_directories_set_when_calling_opencode_then_header_matches() {
+    use crate::ipc_tests::helpers::install_fake_opencode;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // GIVEN: A mock OpenCode server that only answers with the expected directory headers
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&opencode)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .and(header("x-opencode-directory", "/work/project"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .expect(1)
+        .mount(&opencode)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/session/ses_other"))
+        .and(header("x-opencode-directory", "/work/other"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("true"))
+        .expect(1)
+        .mount(&opencode)
+        .await;
+
+    // GIVEN: A fake `opencode` binary that reports the mock server's URL
+    let _fake_opencode = install_fake_opencode(&format!(
+        "#!/bin/sh\necho \"opencode server listening on {}\"\n",
+        opencode.uri()
+    ))
+    .await;
+
+    // GIVEN: Connected client with the (fake) server spawned
+    let ipc_port = 19903;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+    client
+        .spawn_server(None)
+        .await
+        .expect("spawn_server should succeed");
+
+    // WHEN: Setting a default directory and a per-session override
+    assert!(
+        client
+            .set_directory(None, Some("/work/project"))
+            .await
+            .unwrap()
+    );
+    assert!(
+        client
+            .set_directory(Some("ses_other"), Some("/work/other"))
+            .await
+            .unwrap()
+    );
+
+    // THEN: Each request carries the matching directory
+    let sessions = client
+        .list_sessions()
+        .await
+        .expect("list_sessions should match the default directory");
+    assert!(sessions.sessions.is_empty());
+    let deleted = client
+        .delete_session("ses_other")
+        .await
+        .expect("delete_session should succeed");
+    assert!(deleted, "delete should match the session's directory");
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies abort without a connected server fails with `NoServer`.
+///
+/// **WHY THIS MATTERS**: The stop button can be pressed after the server went away; the UI
+/// needs the same "start the server" guidance as for sending.
+///
+/// **BUG THIS CATCHES**: Would catch if the abort request isn't routed or panics on a
+/// missing client.
+#[tokio::test]
+async fn given_no_opencode_server_when_client_abort_message_then_returns_no_server() {
+    // GIVEN: IPC server with no OpenCode server connected
+    let ipc_port = 19905;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+
+    // WHEN: Aborting a session
+    let result = client.abort_message("ses_test").await;
+
+    // THEN: NoServer is reported
+    match result {
+        Err(IpcError::Remote { code, .. }) => assert_eq!(code, IpcErrorCode::NoServer),
+        other => panic!("Expected NoServer error, got {other:?}"),
+    }
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies a rename with a blank title is rejected as an invalid message.
+///
+/// **WHY THIS MATTERS**: The frontend shows invalid-input errors inline; a blank title must
+/// not be reported as a server failure (or reach OpenCode at all).
+///
+/// **BUG THIS CATCHES**: Would catch if the update request isn't routed, or if validation
+/// happens only after looking up a server.
+#[tokio::test]
+async fn given_blank_title_when_client_update_session_then_returns_invalid_message() {
+    // GIVEN: IPC server with no OpenCode server connected
+    let ipc_port = 19927;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+
+    // WHEN: Renaming a session to a blank title
+    let result = client.update_session("ses_test", " ").await;
+
+    // THEN: The request is rejected as invalid
+    match result {
+        Err(IpcError::Remote { code, .. }) => assert_eq!(code, IpcErrorCode::InvalidMessage),
+        other => panic!("Expected InvalidMessage error, got {other:?}"),
+    }
+
+    // WHEN: Renaming with a valid title but no server
+    let result = client.update_session("ses_test", "Renamed").await;
+
+    // THEN: NoServer is reported
+    match result {
+        Err(IpcError::Remote { code, .. }) => assert_eq!(code, IpcErrorCode::NoServer),
+        other => panic!("Expected NoServer error, got {other:?}"),
+    }
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies the log level can be changed over IPC and bad levels are rejected.
+///
+/// **WHY THIS MATTERS**: Users diagnosing an issue need trace logs from the running app;
+/// a typo in the level must be reported instead of silently leaving logging unchanged.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - The handler doesn't apply the level (`log::max_level()` unchanged)
+/// - The response doesn't report the previous level and affected target
+/// - An unknown level name is accepted
+#[tokio::test]
+async fn given_running_server_when_client_set_log_level_then_level_changes_live() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19907;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    let mut client = IpcClient::connect(ipc_port, TEST_AUTH_TOKEN)
+        .await
+        .expect("Client should connect and authenticate");
+
+    // WHEN: Setting the default level to trace, then back to info
+    let raised = client
+        .set_log_level("TRACE", None)
+        .await
+        .expect("set_log_level should succeed");
+    let max_while_raised = log::max_level();
+    let restored = client
+        .set_log_level("info", None)
+        .await
+        .expect("set_log_level should succeed");
+
+    // THEN: The level applied live and the responses describe each change
+    assert_eq!(max_while_raised, log::LevelFilter::Trace);
+    assert_eq!(raised.level, "trace");
+    assert_eq!(raised.target, "", "No target means all modules");
+    assert_eq!(restored.previous_level, "trace");
+    assert_eq!(restored.level, "info");
+
+    // WHEN: Sending an unknown level
+    let result = client.set_log_level("verbose", None).await;
+
+    // THEN: Rejected as an invalid message naming the valid levels
+    match result {
+        Err(IpcError::Remote { code, message, .. }) => {
+            assert_eq!(code, IpcErrorCode::InvalidMessage);
+            assert!(message.contains("trace"), "unexpected message: {message}");
+        }
+        other => panic!("Expected InvalidMessage error, got {other:?}"),
+    }
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies single config values can be read and written over IPC.
+///
+/// **WHY THIS MATTERS**: The settings UI changes one value at a time; a rejected value
+/// must come back as an error and leave the saved config untouched.
+///
+/// **BUG THIS CATCHES**: Would catch if invalid values are persisted, if unknown paths
+/// succeed, or if a valid write isn't visible to later reads.
+#[tokio::test]
+async fn given_config_value_requests_when_client_gets_and_sets_then_validated_and_persisted() {
+    // GIVEN: IPC server with a default config in a temp dir
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let (handle, mut client) = connect_with_config_dir(19908, &dir).await;
+
+    // WHEN: Reading the font size, then setting an invalid and a valid value
+    let initial = client
+        .get_config_value("ui.base_font_points")
+        .await
+        .expect("get_config_value should succeed");
+    let rejected = client.set_config_value("ui.base_font_points", "200").await;
+    let unknown = client.get_config_value("ui.missing").await;
+    let updated = client
+        .set_config_value("ui.base_font_points", "16.0")
+        .await
+        .expect("valid value should be accepted");
+
+    // THEN: Invalid input is rejected and the valid value is stored and saved
+    assert_eq!(initial.value_json, "14.0");
+    assert!(matches!(
+        rejected,
+        Err(IpcError::Remote {
+            code: IpcErrorCode::InvalidMessage,
+            ..
+        })
+    ));
+    assert!(matches!(
+        unknown,
+        Err(IpcError::Remote {
+            code: IpcErrorCode::InvalidMessage,
+            ..
+        })
+    ));
+    assert_eq!(updated.value_json, "16.0");
+    let saved = AppConfig::load(dir.path()).expect("saved config should load");
+    assert_eq!(saved.ui.base_font_points, 16.0);
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies a config section can be reset to defaults over IPC.
+///
+/// **WHY THIS MATTERS**: Users with a broken config recover from the app instead of
+/// deleting config.json by hand.
+///
+/// **BUG THIS CATCHES**: Would catch if the reset isn't persisted, resets other sections,
+/// or accepts unknown section names.
+#[tokio::test]
+async fn given_customized_config_when_client_resets_ui_then_defaults_saved_and_server_kept() {
+    // GIVEN: IPC server with customized UI and server settings
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let (handle, mut client) = connect_with_config_dir(19909, &dir).await;
+    client
+        .set_config_value("ui.base_font_points", "20.0")
+        .await
+        .expect("font size should be accepted");
+    client
+        .set_config_value("server.last_opencode_url", "\"http://localhost:4096\"")
+        .await
+        .expect("server URL should be accepted");
+
+    // WHEN: Resetting an unknown section, then the UI section
+    let unknown = client.reset_config("network").await;
+    let response = client
+        .reset_config("ui")
+        .await
+        .expect("reset_config should succeed");
+
+    // THEN: Unknown sections are rejected and the UI reset is returned and saved
+    assert!(matches!(
+        unknown,
+        Err(IpcError::Remote {
+            code: IpcErrorCode::InvalidMessage,
+            ..
+        })
+    ));
+    let returned: AppConfig =
+        serde_json::from_str(&response.app_config_json).expect("response should be a config");
+    let saved = AppConfig::load(dir.path()).expect("saved config should load");
+    assert_eq!(returned, saved);
+    assert_eq!(saved.ui, AppConfig::default().ui);
+    assert_eq!(
+        saved.server.last_opencode_url.as_deref(),
+        Some("http://localhost:4096")
+    );
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies subscribed clients receive pushed config change events.
+///
+/// **WHY THIS MATTERS**: The frontend learns about state changes from pushes instead of
+/// polling; events must reach it even while it is awaiting another response.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Subscriptions aren't acknowledged or events aren't forwarded
+/// - Events interleaved with another response are dropped by the client
+/// - Events carry the old config instead of the updated one
+#[tokio::test]
+async fn given_subscribed_client_when_config_value_set_then_receives_config_updated_event() {
+    // GIVEN: A client subscribed to events
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let (handle, mut client) = connect_with_config_dir(19915, &dir).await;
+    client
+        .subscribe_events()
+        .await
+        .expect("subscribe_events should succeed");
+
+    // WHEN: Changing a config value over the same connection
+    client
+        .set_config_value("ui.base_font_points", "18.0")
+        .await
+        .expect("valid value should be accepted");
+    let event = tokio::time::timeout(std::time::Duration::from_secs(2), client.next_event())
+        .await
+        .expect("event should arrive")
+        .expect("next_event should succeed");
+
+    // THEN: The pushed event carries the updated config
+    match event.event {
+        Some(ipc_server_state_event::Event::ConfigUpdated(updated)) => {
+            let config: AppConfig =
+                serde_json::from_str(&updated.app_config_json).expect("event should be a config");
+            assert_eq!(config.ui.base_font_points, 18.0);
+        }
+        other => panic!("Expected ConfigUpdated, got {other:?}"),
+    }
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies the server counts requests, failures and errors per message type.
+///
+/// **WHY THIS MATTERS**: Metrics are how slow or failing IPC paths are spotted in the
+/// field; wrong counts point debugging at the wrong handler.
+///
+/// **BUG THIS CATCHES**: Would catch if a message type isn't counted, if error responses
+/// aren't attributed to their request, or if durations skip the histogram.
+#[tokio::test]
+async fn given_several_requests_when_client_gets_metrics_then_counters_match() {
+    // GIVEN: IPC server with a config dir
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let (handle, mut client) = connect_with_config_dir(19918, &dir).await;
+
+    // WHEN: Two successful requests and one failing request, then reading metrics
+    client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+    client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+    let unknown = client.get_config_value("nope").await;
+    let metrics = client
+        .get_metrics()
+        .await
+        .expect("get_metrics should succeed");
+
+    // THEN: Each message type is counted, and the failure is attributed to its type
+    assert!(unknown.is_err(), "unknown key should fail");
+    let by_type = |name: &str| {
+        metrics
+            .message_types
+            .iter()
+            .find(|m| m.message_type == name)
+            .unwrap_or_else(|| panic!("{name} should be counted"))
+    };
+    let get_config = by_type("get_config");
+    assert_eq!(get_config.count, 2);
+    assert_eq!(get_config.failures, 0);
+    assert_eq!(get_config.duration_buckets.iter().sum::<u64>(), 2);
+    assert_eq!(
+        get_config.duration_buckets.len(),
+        metrics.duration_bucket_bounds_micros.len() + 1
+    );
+    let get_config_value = by_type("get_config_value");
+    assert_eq!(get_config_value.count, 1);
+    assert_eq!(get_config_value.failures, 1);
+    // The metrics request is counted before its snapshot is taken
+    assert_eq!(by_type("get_metrics").count, 1);
+    let invalid = metrics
+        .errors
+        .iter()
+        .find(|e| e.code == IpcErrorCode::InvalidMessage as i32)
+        .expect("InvalidMessage errors should be counted");
+    assert_eq!(invalid.count, 1);
+
+    client.close().await.expect("close should succeed");
+    handle.shutdown().await;
+}
+
+/// Reconnect policy with short delays so tests don't wait on the production backoff.
+fn fast_reconnect() -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_interval: Duration::from_millis(20),
+        max_interval: Duration::from_millis(200),
+        max_elapsed_time: Duration::from_secs(5),
+        ..Default::default()
+    }
+}
+
+/// **VALUE**: Verifies the client reconnects with backoff after the server dies and comes back.
+///
+/// **WHY THIS MATTERS**: The app process can restart its IPC server; the frontend should
+/// resume without redoing the handshake itself or being restarted.
+///
+/// **BUG THIS CATCHES**: Would catch if a dead connection isn't noticed before sending, if
+/// reconnect gives up while the server is still down briefly, or if the reconnecting state
+/// is never published.
+#[tokio::test]
+async fn given_server_killed_and_restarted_when_client_requests_then_reconnects() {
+    // GIVEN: Connected client whose server is killed, and restarted a little later
+    let ipc_port = 19919;
+    let server = start_killable_ipc_server(ipc_port, TEST_AUTH_TOKEN).await;
+    let mut client = IpcClient::connect_with(ipc_port, TEST_AUTH_TOKEN, Some(fast_reconnect()))
+        .await
+        .expect("Client should connect and authenticate");
+    client
+        .get_config()
+        .await
+        .expect("get_config should succeed");
+
+    let mut status = client.watch_status();
+    let observed = tokio::spawn(async move {
+        let mut seen = Vec::new();
+        while status.changed().await.is_ok() {
+            let current = *status.borrow_and_update();
+            seen.push(current);
+            if current == ConnectionStatus::Connected {
+                break;
+            }
+        }
+        seen
+    });
+
+    server.kill().await;
+    let restarted = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+            .await
+            .expect("Failed to restart IPC server")
+    });
+
+    // WHEN: Issuing a request
+    let result = client.get_config().await;
+
+    // THEN: The request succeeds over a new connection, after reconnect attempts
+    assert!(
+        result.is_ok(),
+        "request should succeed after reconnect: {result:?}"
+    );
+    assert_eq!(client.status(), ConnectionStatus::Connected);
+    let seen = observed.await.expect("status observer panicked");
+    assert!(
+        seen.iter()
+            .any(|s| matches!(s, ConnectionStatus::Reconnecting { .. })),
+        "reconnecting state should be published: {seen:?}"
+    );
+
+    client.close().await.expect("close should succeed");
+    restarted
+        .await
+        .expect("restart task panicked")
+        .shutdown()
+        .await;
+}
+
+/// **VALUE**: Verifies a lost connection fails requests deterministically when reconnect
+/// is disabled.
+///
+/// **WHY THIS MATTERS**: Callers that manage reconnection themselves need a clear,
+/// matchable error instead of a hang or a generic read failure.
+///
+/// **BUG THIS CATCHES**: Would catch if the client retries despite the policy, or reports
+/// a dropped connection as something other than `ConnectionLost`.
+#[tokio::test]
+async fn given_reconnect_disabled_when_server_killed_then_request_fails_with_connection_lost() {
+    // GIVEN: Client without reconnection whose server is killed
+    let ipc_port = 19920;
+    let server = start_killable_ipc_server(ipc_port, TEST_AUTH_TOKEN).await;
+    let mut client = IpcClient::connect_with(ipc_port, TEST_AUTH_TOKEN, None)
+        .await
+        .expect("Client should connect and authenticate");
+    server.kill().await;
+
+    // WHEN: Issuing a request
+    let result = client.get_config().await;
+
+    // THEN: It fails with ConnectionLost and the client reports itself disconnected
+    assert!(
+        matches!(result, Err(IpcError::ConnectionLost { .. })),
+        "Expected ConnectionLost, got {result:?}"
+    );
+    assert_eq!(client.status(), ConnectionStatus::Disconnected);
+}
@@ -7,7 +7,12 @@
 //! - Connection state checks
 
 use client_core::config::{AppConfig, ModelsConfig};
-use client_core::ipc::{ConfigState, IpcServerHandle, start_ipc_server};
+#[cfg(unix)]
+use client_core::discovery::{clear_override_binary, set_override_binary};
+use client_core::ipc::protocol::IPC_PROTOCOL_VERSION;
+use client_core::ipc::{
+    ConfigState, IpcServerHandle, IpcServerOptions, start_ipc_server, start_ipc_server_with_options,
+};
 use client_core::proto::{
     IpcAuthHandshake, IpcAuthHandshakeResponse, IpcClientMessage, IpcServerMessage,
     ipc_client_message, ipc_server_message,
@@ -16,12 +21,55 @@ use client_core::proto::{
 use futures_util::{SinkExt, StreamExt};
 use prost::Message as ProstMessage;
 use std::path::PathBuf;
+use tempfile::TempDir;
 use tokio::net::TcpStream;
+use tokio::sync::{Mutex, MutexGuard};
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
 
 /// Test constants for authentication
 pub const TEST_AUTH_TOKEN: &str = "test-token-12345";
 
+/// Serializes tests that install a fake `opencode` binary.
+#[cfg(unix)]
+static FAKE_OPENCODE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// A fake `opencode` binary used for spawning; the override is cleared on drop.
+#[cfg(unix)]
+pub struct FakeOpencode {
+    _dir: TempDir,
+    _guard: MutexGuard<'static, ()>,
+}
+
+#[cfg(unix)]
+impl Drop for FakeOpencode {
+    fn drop(&mut self) {
+        clear_override_binary();
+    }
+}
+
+/// Test helper: Spawn a shell script instead of `opencode` until the guard drops.
+///
+/// `spawn_server` runs this script instead of the real binary.
+#[cfg(unix)]
+pub async fn install_fake_opencode(script: &str) -> FakeOpencode {
+    use std::os::unix::fs::PermissionsExt;
+
+    let guard = FAKE_OPENCODE_LOCK.lock().await;
+
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let fake_binary = dir.path().join("opencode");
+    std::fs::write(&fake_binary, script).expect("Failed to write script");
+    std::fs::set_permissions(&fake_binary, std::fs::Permissions::from_mode(0o755))
+        .expect("Failed to chmod script");
+
+    set_override_binary(&fake_binary);
+
+    FakeOpencode {
+        _dir: dir,
+        _guard: guard,
+    }
+}
+
 /// Test helper: Create a test ConfigState with defaults.
 pub fn create_test_config_state() -> ConfigState {
     ConfigState::new(
@@ -40,6 +88,67 @@ pub async fn start_test_ipc_server(
     start_ipc_server(ipc_port, auth_token, config_state).await
 }
 
+/// Test helper: Start IPC server with a caller-provided config state (e.g. a temp config dir).
+pub async fn start_test_ipc_server_with_config_state(
+    ipc_port: u16,
+    auth_token: Option<String>,
+    config_state: ConfigState,
+) -> Result<IpcServerHandle, client_core::error::ipc::IpcError> {
+    start_ipc_server(ipc_port, auth_token, config_state).await
+}
+
+/// Test helper: Start IPC server with custom server options.
+pub async fn start_test_ipc_server_with_options(
+    ipc_port: u16,
+    auth_token: Option<String>,
+    options: IpcServerOptions,
+) -> Result<IpcServerHandle, client_core::error::ipc::IpcError> {
+    let config_state = create_test_config_state();
+    start_ipc_server_with_options(ipc_port, auth_token, config_state, options).await
+}
+
+/// An IPC server running on its own runtime, so it can be killed like a crashed process.
+pub struct KillableIpcServer {
+    kill: tokio::sync::oneshot::Sender<()>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl KillableIpcServer {
+    /// Drops the server's runtime, aborting every connection without a close frame.
+    ///
+    /// Returns once the port is free again.
+    pub async fn kill(self) {
+        let _ = self.kill.send(());
+        tokio::task::spawn_blocking(move || self.thread.join())
+            .await
+            .expect("Failed to join server thread")
+            .expect("Server thread panicked");
+    }
+}
+
+/// Test helper: Start an IPC server on a dedicated thread and runtime.
+pub async fn start_killable_ipc_server(ipc_port: u16, auth_token: &str) -> KillableIpcServer {
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    let (kill, kill_rx) = tokio::sync::oneshot::channel::<()>();
+    let auth_token = auth_token.to_string();
+
+    let thread = std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to build server runtime");
+        runtime.block_on(async {
+            let started = start_test_ipc_server(ipc_port, Some(auth_token)).await;
+            let _ = ready_tx.send(started.map(|_handle| ()));
+            let _ = kill_rx.await;
+        });
+        // Dropping the runtime aborts the accept loop and every connection task
+    });
+
+    ready_rx
+        .await
+        .expect("Server thread exited early")
+        .expect("Failed to start IPC server");
+    KillableIpcServer { kill, thread }
+}
+
 /// Test helper: Connect to IPC server and return WebSocket stream.
 pub async fn connect_to_server(ipc_port: u16) -> WebSocketStream<MaybeTlsStream<TcpStream>> {
     let url = format!("ws://127.0.0.1:{}", ipc_port);
@@ -79,12 +188,22 @@ pub async fn receive_protobuf<T: ProstMessage + Default>(
 pub async fn authenticate(
     ws: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
     token: &str,
+) -> IpcAuthHandshakeResponse {
+    authenticate_with_version(ws, token, IPC_PROTOCOL_VERSION).await
+}
+
+/// Test helper: Send auth handshake claiming `protocol_version` and return response.
+pub async fn authenticate_with_version(
+    ws: &mut WebSocketStream<MaybeTlsStream<TcpStream>>,
+    token: &str,
+    protocol_version: u32,
 ) -> IpcAuthHandshakeResponse {
     let auth_msg = IpcClientMessage {
         request_id: 1,
         payload: Some(ipc_client_message::Payload::AuthHandshake(
             IpcAuthHandshake {
                 token: token.to_string(),
+                protocol_version,
             },
         )),
     };
@@ -108,3 +227,51 @@ pub async fn is_connection_closed(ws: &mut WebSocketStream<MaybeTlsStream<TcpStr
         Ok(Some(Err(_))) => true,
     }
 }
+
+/// Target prefix of the IPC server's log records.
+const IPC_LOG_TARGET: &str = "client_core::ipc";
+
+static CAPTURED_LOGS: std::sync::Mutex<Vec<(log::Level, String)>> =
+    std::sync::Mutex::new(Vec::new());
+static INSTALL_LOG_CAPTURE: std::sync::Once = std::sync::Once::new();
+
+/// Records IPC server log records (info and above) for assertions.
+struct CaptureLogger;
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target().starts_with(IPC_LOG_TARGET) && metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            CAPTURED_LOGS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE_LOGGER: CaptureLogger = CaptureLogger;
+
+/// Test helper: Start capturing IPC server logs (installs the logger once per test binary).
+pub fn install_log_capture() {
+    INSTALL_LOG_CAPTURE.call_once(|| {
+        log::set_logger(&CAPTURE_LOGGER).expect("No other logger in integration tests");
+        log::set_max_level(log::LevelFilter::Info);
+    });
+}
+
+/// Test helper: Captured IPC server log messages at `level` that contain `needle`.
+pub fn captured_logs(level: log::Level, needle: &str) -> Vec<String> {
+    CAPTURED_LOGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .filter(|(captured_level, message)| *captured_level == level && message.contains(needle))
+        .map(|(_, message)| message.clone())
+        .collect()
+}
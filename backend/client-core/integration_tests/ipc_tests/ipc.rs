@@ -1,12 +1,24 @@
 use crate::ipc_tests::helpers::{
-    TEST_AUTH_TOKEN, authenticate, connect_to_server, is_connection_closed, receive_protobuf,
-    send_protobuf, start_test_ipc_server,
+    TEST_AUTH_TOKEN, authenticate, authenticate_with_version, captured_logs, connect_to_server,
+    install_log_capture, is_connection_closed, receive_protobuf, send_protobuf,
+    start_test_ipc_server, start_test_ipc_server_with_options,
 };
 
+use client_core::error::ipc::IpcError;
+use client_core::ipc::IpcServerOptions;
+use client_core::ipc::protocol::{IPC_PROTOCOL_VERSION, MIN_IPC_PROTOCOL_VERSION};
 use client_core::proto::{
     IpcClientMessage, IpcListSessionsRequest, IpcServerMessage, ipc_client_message,
 };
 
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use prost::Message as ProstMessage;
+use tokio_tungstenite::MaybeTlsStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
 /// **VALUE**: Verifies that authenticated clients can send protobuf messages and receive responses.
 ///
 /// **WHY THIS MATTERS**: After authentication, the IPC layer must correctly handle binary protobuf
@@ -216,6 +228,99 @@ async fn given_invalid_token_when_auth_handshake_then_rejected() {
 
 // -------------------------------------------------------------------------- //
 
+/// **VALUE**: Verifies that a client speaking the server's protocol version is accepted.
+///
+/// **WHY THIS MATTERS**: The handshake now negotiates a version; matching builds of the
+/// frontend and backend must keep working and learn the version they agreed on.
+///
+/// **BUG THIS CATCHES**: Would catch if the negotiated version isn't reported or matching
+/// clients are rejected.
+#[tokio::test]
+async fn given_matching_protocol_version_when_auth_handshake_then_success_with_version() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19912;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    // WHEN: Client authenticates with the server's protocol version
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response =
+        authenticate_with_version(&mut ws, TEST_AUTH_TOKEN, IPC_PROTOCOL_VERSION).await;
+
+    // THEN: Auth succeeds on that version
+    assert!(auth_response.success, "Auth should succeed");
+    assert_eq!(auth_response.protocol_version, IPC_PROTOCOL_VERSION);
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies that a client older than the supported range is rejected up front.
+///
+/// **WHY THIS MATTERS**: A stale frontend otherwise fails later with confusing protobuf
+/// decode errors; rejecting at the handshake gives a clear "update" message.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Unversioned (0) clients are let into the message loop
+/// - The rejection doesn't name the versions involved
+/// - The connection stays open after the rejection
+#[tokio::test]
+async fn given_older_protocol_version_when_auth_handshake_then_rejected_and_closed() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19913;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    // WHEN: Client authenticates with a version below the supported minimum
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response =
+        authenticate_with_version(&mut ws, TEST_AUTH_TOKEN, MIN_IPC_PROTOCOL_VERSION - 1).await;
+
+    // THEN: Auth fails with a version error carrying the server's version
+    assert!(!auth_response.success, "Auth should fail for old client");
+    let error = auth_response.error.expect("Should have error message");
+    assert!(
+        error.contains("protocol version"),
+        "unexpected error: {error}"
+    );
+    assert_eq!(auth_response.protocol_version, IPC_PROTOCOL_VERSION);
+
+    // THEN: Connection is closed
+    assert!(is_connection_closed(&mut ws).await);
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies that a newer client is negotiated down to the server's version.
+///
+/// **WHY THIS MATTERS**: A frontend updated ahead of the backend should still connect,
+/// and learn it must restrict itself to the older protocol.
+///
+/// **BUG THIS CATCHES**: Would catch if newer clients are rejected, or told they can use
+/// a version the server doesn't speak.
+#[tokio::test]
+async fn given_newer_protocol_version_when_auth_handshake_then_negotiated_down() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19914;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    // WHEN: Client authenticates with a version newer than the server's
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response =
+        authenticate_with_version(&mut ws, TEST_AUTH_TOKEN, IPC_PROTOCOL_VERSION + 1).await;
+
+    // THEN: Auth succeeds on the server's version
+    assert!(auth_response.success, "Auth should succeed");
+    assert_eq!(auth_response.protocol_version, IPC_PROTOCOL_VERSION);
+
+    handle.shutdown().await;
+}
+
+// -------------------------------------------------------------------------- //
+
 /// **VALUE**: Verifies that non-auth first message results in connection closure.
 ///
 /// **WHY THIS MATTERS**: Security - first message MUST be auth handshake.
@@ -326,6 +431,7 @@ async fn given_authenticated_when_send_second_auth_then_error() {
         payload: Some(ipc_client_message::Payload::AuthHandshake(
             client_core::proto::IpcAuthHandshake {
                 token: TEST_AUTH_TOKEN.to_string(),
+                protocol_version: IPC_PROTOCOL_VERSION,
             },
         )),
     };
@@ -562,3 +668,981 @@ async fn given_authenticated_and_server_when_stop_server_then_succeeds() {
         _ => panic!("Expected StopServerResponse"),
     }
 }
+
+// -------------------------------------------------------------------------- //
+
+/// **VALUE**: Verifies that shutting down the IPC server releases its port.
+///
+/// **WHY THIS MATTERS**: Tests use fixed ports and the app restarts the server on exit paths.
+/// If the listener outlives `shutdown()`, the next bind on the same port fails.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - The accept loop ignores the shutdown signal
+/// - `shutdown()` returns before the listener is dropped
+/// - New connections are still accepted after shutdown
+#[tokio::test]
+async fn given_running_server_when_shutdown_then_port_can_be_rebound() {
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19889;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+
+    // WHEN: Server is shut down
+    handle.shutdown().await;
+
+    // THEN: New connections are refused
+    let url = format!("ws://127.0.0.1:{}", ipc_port);
+    assert!(
+        tokio_tungstenite::connect_async(&url).await.is_err(),
+        "Should not accept connections after shutdown"
+    );
+
+    // THEN: The port can be bound again
+    let restarted = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Port should be free after shutdown");
+
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(
+        auth_response.success,
+        "Restarted server should accept clients"
+    );
+
+    restarted.shutdown().await;
+}
+
+// -------------------------------------------------------------------------- //
+
+/// **VALUE**: Verifies that the server closes connections whose client never answers pings.
+///
+/// **WHY THIS MATTERS**: Half-open connections (laptop sleep, dropped network) otherwise
+/// linger until the next write fails, holding per-connection state indefinitely.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - The server never sends keepalive pings
+/// - A missing pong doesn't close the connection
+/// - The read loop blocks on `read.next()` without servicing the ping timer
+#[tokio::test]
+async fn given_client_ignoring_pings_when_pong_timeout_elapses_then_server_closes_connection() {
+    // GIVEN: IPC server with a short keepalive configuration
+    let ipc_port = 19890;
+    let options = IpcServerOptions {
+        ping_interval: Duration::from_millis(100),
+        pong_timeout: Duration::from_millis(100),
+        ..Default::default()
+    };
+    let handle =
+        start_test_ipc_server_with_options(ipc_port, Some(String::from(TEST_AUTH_TOKEN)), options)
+            .await
+            .expect("Failed to start IPC server");
+
+    // GIVEN: Authenticated client that stops reading (so it never sends pongs)
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    // WHEN: Client stays silent past ping interval + pong timeout
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // THEN: Server has closed the connection (only pings/close remain to be read)
+    let closed = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Ping(_))) => continue,
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => return true,
+                Some(Ok(_)) => return false,
+            }
+        }
+    })
+    .await
+    .unwrap_or(false);
+    assert!(closed, "Server should close connection after missed pong");
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies that a client answering pings stays connected.
+///
+/// **WHY THIS MATTERS**: Keepalive must only reap dead connections, never healthy ones.
+///
+/// **BUG THIS CATCHES**: Would catch if received pongs don't reset the deadline.
+#[tokio::test]
+async fn given_client_answering_pings_when_keepalive_runs_then_connection_stays_open() {
+    // GIVEN: IPC server with a short keepalive configuration
+    let ipc_port = 19891;
+    let options = IpcServerOptions {
+        ping_interval: Duration::from_millis(100),
+        pong_timeout: Duration::from_millis(200),
+        ..Default::default()
+    };
+    let handle =
+        start_test_ipc_server_with_options(ipc_port, Some(String::from(TEST_AUTH_TOKEN)), options)
+            .await
+            .expect("Failed to start IPC server");
+
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    // WHEN: Client keeps reading (tungstenite answers pings automatically)
+    let _ = tokio::time::timeout(Duration::from_millis(600), async {
+        while let Some(Ok(_)) = ws.next().await {}
+    })
+    .await;
+
+    // THEN: Requests are still served
+    let msg = IpcClientMessage {
+        request_id: 2,
+        payload: Some(ipc_client_message::Payload::ListSessions(
+            IpcListSessionsRequest {},
+        )),
+    };
+    send_protobuf(&mut ws, &msg).await;
+    let response = loop {
+        match ws.next().await {
+            Some(Ok(Message::Binary(data))) => {
+                break IpcServerMessage::decode(&data[..]).expect("Failed to decode");
+            }
+            Some(Ok(_)) => continue,
+            other => panic!("Connection closed unexpectedly: {other:?}"),
+        }
+    };
+    assert_eq!(response.request_id, 2);
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies that the server closes authenticated connections that go silent.
+///
+/// **WHY THIS MATTERS**: A client that stays connected but never sends anything (e.g. a
+/// stuck tool) otherwise holds per-connection state for the life of the app.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Connections never time out while the socket stays open
+/// - Keepalive pongs count as activity (so a live but silent client is never reaped)
+/// - The connection is dropped without a close frame
+#[tokio::test]
+async fn given_silent_client_when_idle_timeout_elapses_then_server_sends_close() {
+    // GIVEN: IPC server with a short idle timeout and frequent keepalive pings
+    let ipc_port = 19910;
+    let options = IpcServerOptions {
+        ping_interval: Duration::from_millis(50),
+        pong_timeout: Duration::from_secs(1),
+        idle_timeout: Duration::from_millis(300),
+        ..Default::default()
+    };
+    let handle =
+        start_test_ipc_server_with_options(ipc_port, Some(String::from(TEST_AUTH_TOKEN)), options)
+            .await
+            .expect("Failed to start IPC server");
+
+    // GIVEN: Authenticated client
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    // WHEN: Client sends nothing but keeps reading (tungstenite answers pings automatically)
+    let close = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Ping(_))) => continue,
+                Some(Ok(Message::Close(frame))) => return frame,
+                other => panic!("Expected close frame, got {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("Server should close the idle connection");
+
+    // THEN: Server closed it with the idle reason
+    let frame = close.expect("Close frame should carry a reason");
+    assert_eq!(frame.reason.as_str(), "Idle timeout");
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies that an oversized frame closes the connection without being decoded.
+///
+/// **WHY THIS MATTERS**: A buggy or malicious client could otherwise send a multi-gigabyte
+/// frame and exhaust memory in the backend.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - No size limit is configured on the WebSocket
+/// - Oversized frames are decoded (answered with an "Invalid protobuf" error response)
+/// - The connection stays open after the violation
+#[tokio::test]
+async fn given_oversized_frame_when_client_sends_then_server_closes_without_decoding() {
+    // GIVEN: IPC server with a 1 KiB message limit
+    let ipc_port = 19911;
+    let options = IpcServerOptions {
+        max_message_size: 1024,
+        ..Default::default()
+    };
+    let handle =
+        start_test_ipc_server_with_options(ipc_port, Some(String::from(TEST_AUTH_TOKEN)), options)
+            .await
+            .expect("Failed to start IPC server");
+
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    // WHEN: Sending a 4 KiB frame of garbage (would fail protobuf decoding if decoded)
+    ws.send(Message::Binary(vec![0xFF; 4096].into()))
+        .await
+        .expect("Failed to send frame");
+
+    // THEN: The server closes with a size violation instead of sending an error response
+    let close = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Ping(_))) => continue,
+                Some(Ok(Message::Close(frame))) => return frame,
+                other => panic!("Expected close frame, got {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("Server should close the connection");
+    let frame = close.expect("Close frame should carry a code");
+    assert_eq!(frame.code, CloseCode::Size);
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies that client pings are answered with a matching pong.
+///
+/// **WHY THIS MATTERS**: Clients (e.g. the .NET frontend) may run their own keepalive;
+/// an unanswered ping makes them drop a healthy connection.
+///
+/// **BUG THIS CATCHES**: Would catch if client pings fall into the "non-binary message"
+/// branch and are ignored, or if the pong doesn't echo the ping payload.
+#[tokio::test]
+async fn given_authenticated_client_when_ping_sent_then_pong_with_payload_returned() {
+    // GIVEN: Authenticated client
+    let ipc_port = 19916;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    // WHEN: Client sends a ping
+    ws.send(Message::Ping(b"keepalive".to_vec().into()))
+        .await
+        .expect("Failed to send ping");
+
+    // THEN: Server answers with a pong carrying the same payload
+    let pong = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Pong(payload))) => return payload,
+                Some(Ok(Message::Ping(_))) => continue,
+                other => panic!("Expected pong, got {other:?}"),
+            }
+        }
+    })
+    .await
+    .expect("Server should answer the ping");
+    assert_eq!(&pong[..], b"keepalive");
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies that a client-initiated close is handled as a clean disconnect.
+///
+/// **WHY THIS MATTERS**: Closing the app's window closes the socket cleanly every time;
+/// logging that as a protocol warning buries real problems in noise.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Close frames are logged as "non-binary message" warnings
+/// - The server keeps reading after the close instead of ending the connection
+#[tokio::test]
+async fn given_authenticated_client_when_close_sent_then_clean_disconnect_logged() {
+    install_log_capture();
+
+    // GIVEN: Authenticated client
+    let ipc_port = 19917;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut ws = connect_to_server(ipc_port).await;
+    let client_addr = match ws.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.local_addr().expect("Should have local addr"),
+        _ => unreachable!("Test connections are plain TCP"),
+    };
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    // WHEN: Client closes the connection cleanly
+    ws.close(None).await.expect("Failed to send close");
+    while let Some(Ok(_)) = ws.next().await {}
+
+    // THEN: Server logs a clean disconnect for this client, and no warning
+    let client = format!("Client {client_addr} ");
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    while captured_logs(log::Level::Info, &format!("{client}disconnected")).is_empty() {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "Server should log the disconnect"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!captured_logs(log::Level::Info, &format!("{client}closed the connection")).is_empty());
+    assert_eq!(
+        captured_logs(log::Level::Warn, &client),
+        Vec::<String>::new(),
+        "Clean close should not warn"
+    );
+
+    handle.shutdown().await;
+}
+
+// -------------------------------------------------------------------------- //
+
+/// **VALUE**: Verifies that a slow request does not block a fast one on the same connection.
+///
+/// **WHY THIS MATTERS**: The frontend fires requests concurrently; a multi-second
+/// `spawn_server` must not freeze `get_config` and the rest of the UI.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Messages are handled sequentially in the read loop
+/// - Responses lose their `request_id` correlation when handled concurrently
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn given_slow_and_fast_requests_when_sent_together_then_fast_response_arrives_first() {
+    use crate::ipc_tests::helpers::install_fake_opencode;
+
+    // GIVEN: A fake `opencode` binary on PATH that takes a second before failing
+    let _fake_opencode = install_fake_opencode("#!/bin/sh\nsleep 1\nexit 1\n").await;
+
+    // GIVEN: IPC server running on test port
+    let ipc_port = 19895;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    // WHEN: Sending a slow spawn_server then a fast get_config
+    let slow = IpcClientMessage {
+        request_id: 10,
+        payload: Some(ipc_client_message::Payload::SpawnServer(
+            client_core::proto::IpcSpawnServerRequest { port: None },
+        )),
+    };
+    let fast = IpcClientMessage {
+        request_id: 11,
+        payload: Some(ipc_client_message::Payload::GetConfig(
+            client_core::proto::IpcGetConfigRequest {},
+        )),
+    };
+    send_protobuf(&mut ws, &slow).await;
+    send_protobuf(&mut ws, &fast).await;
+
+    // THEN: The fast response arrives first, then the slow one, each correlated
+    let first: IpcServerMessage = receive_protobuf(&mut ws).await;
+    let second: IpcServerMessage = receive_protobuf(&mut ws).await;
+    assert_eq!(first.request_id, 11, "get_config should not wait for spawn");
+    assert!(matches!(
+        first.payload,
+        Some(client_core::proto::ipc_server_message::Payload::GetConfigResponse(_))
+    ));
+    assert_eq!(second.request_id, 10, "spawn response keeps its request_id");
+
+    handle.shutdown().await;
+}
+
+// -------------------------------------------------------------------------- //
+
+/// **VALUE**: Verifies streamed parts arrive before completion, all under the request's id.
+///
+/// **WHY THIS MATTERS**: The chat view renders replies as they are generated; the frontend
+/// routes every pushed frame to its pending request purely by `request_id`.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Parts are buffered until the message completes (no incremental delivery)
+/// - Pushed frames carry a different or zero `request_id`
+/// - Parts from other sessions are forwarded
+/// - The stream isn't terminated by exactly one `MessageCompleteEvent`
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn given_stream_message_when_parts_arrive_then_pushed_before_complete_with_same_id() {
+    use crate::ipc_tests::helpers::install_fake_opencode;
+    use client_core::proto::{IpcSpawnServerRequest, IpcStreamMessageRequest, ipc_server_message};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // GIVEN: A mock OpenCode server with two part updates on the event stream and a
+    // message endpoint that completes only after a delay
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&opencode)
+        .await;
+    let sse = [
+        r#"data: {"type":"message.part.updated","properties":{"part":{"id":"prt_1","sessionID":"ses_test","messageID":"msg_1","type":"text","text":"Hel"}}}"#,
+        r#"data: {"type":"message.part.updated","properties":{"part":{"id":"prt_x","sessionID":"ses_other","messageID":"msg_x","type":"text","text":"other"}}}"#,
+        r#"data: {"type":"message.part.updated","properties":{"part":{"id":"prt_1","sessionID":"ses_test","messageID":"msg_1","type":"text","text":"Hello"}}}"#,
+    ]
+    .map(|event| format!("{event}\n\n"))
+    .concat();
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(sse, "text/event-stream"))
+        .mount(&opencode)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/message"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({
+                    "info": {
+                        "id": "msg_1",
+                        "sessionID": "ses_test",
+                        "role": "assistant",
+                        "time": { "created": 1767225601000u64 },
+                        "modelID": "gpt-4o",
+                        "providerID": "openai"
+                    },
+                    "parts": [{
+                        "id": "prt_1",
+                        "sessionID": "ses_test",
+                        "messageID": "msg_1",
+                        "type": "text",
+                        "text": "Hello"
+                    }]
+                }))
+                .set_delay(Duration::from_millis(500)),
+        )
+        .mount(&opencode)
+        .await;
+
+    let _fake_opencode = install_fake_opencode(&format!(
+        "#!/bin/sh\necho \"opencode server listening on {}\"\n",
+        opencode.uri()
+    ))
+    .await;
+
+    // GIVEN: Authenticated connection with the (fake) server spawned
+    let ipc_port = 19901;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    send_protobuf(
+        &mut ws,
+        &IpcClientMessage {
+            request_id: 2,
+            payload: Some(ipc_client_message::Payload::SpawnServer(
+                IpcSpawnServerRequest { port: None },
+            )),
+        },
+    )
+    .await;
+    let spawned: IpcServerMessage = receive_protobuf(&mut ws).await;
+    assert!(matches!(
+        spawned.payload,
+        Some(ipc_server_message::Payload::SpawnServerResponse(_))
+    ));
+
+    // WHEN: Streaming a message
+    send_protobuf(
+        &mut ws,
+        &IpcClientMessage {
+            request_id: 3,
+            payload: Some(ipc_client_message::Payload::StreamMessage(
+                IpcStreamMessageRequest {
+                    session_id: "ses_test".to_string(),
+                    text: "Say hello".to_string(),
+                    model_id: "gpt-4o".to_string(),
+                    provider_id: "openai".to_string(),
+                    agent: None,
+                },
+            )),
+        },
+    )
+    .await;
+
+    // THEN: Both ses_test parts arrive first, then the complete event, all tagged with id 3
+    let mut part_texts = Vec::new();
+    let complete = loop {
+        let frame: IpcServerMessage = receive_protobuf(&mut ws).await;
+        assert_eq!(frame.request_id, 3, "every frame must carry the request id");
+        match frame.payload {
+            Some(ipc_server_message::Payload::MessagePartEvent(event)) => {
+                assert_eq!(event.request_id, 3);
+                match event.part.and_then(|p| p.part) {
+                    Some(client_core::proto::message::part::oc_part::Part::Text(text)) => {
+                        part_texts.push(text.text)
+                    }
+                    other => panic!("Expected text part, got {other:?}"),
+                }
+            }
+            Some(ipc_server_message::Payload::MessageCompleteEvent(event)) => break event,
+            other => panic!("Unexpected frame: {other:?}"),
+        }
+    };
+    assert_eq!(part_texts, vec!["Hel", "Hello"]);
+    assert_eq!(complete.request_id, 3);
+    assert!(complete.message.is_some());
+
+    handle.shutdown().await;
+}
+
+// -------------------------------------------------------------------------- //
+
+/// **VALUE**: Verifies aborting a session ends its stream with an aborted message, not an error.
+///
+/// **WHY THIS MATTERS**: A user pressing "stop" expects the reply to end as cancelled; an
+/// error frame would show a failure banner for an intentional action.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - `AbortMessage` isn't routed or doesn't call the server's abort endpoint
+/// - The stream keeps waiting for the (never-finishing) message after an abort
+/// - The stream ends with an error instead of a complete event carrying `OcAbortedError`
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn given_streaming_message_when_aborted_then_completes_with_aborted_error() {
+    use crate::ipc_tests::helpers::install_fake_opencode;
+    use client_core::proto::message::error::oc_message_error;
+    use client_core::proto::message::oc_message::Message as OcMessageKind;
+    use client_core::proto::{
+        IpcAbortMessageRequest, IpcSpawnServerRequest, IpcStreamMessageRequest, ipc_server_message,
+    };
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // GIVEN: A mock OpenCode server whose message never finishes in time, with one part
+    // on the event stream and an abort endpoint
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&opencode)
+        .await;
+    let sse = concat!(
+        r#"data: {"type":"message.part.updated","properties":{"part":{"id":"prt_1","sessionID":"ses_test","messageID":"msg_1","type":"text","text":"Once upon"}}}"#,
+        "\n\n"
+    );
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(sse, "text/event-stream"))
+        .mount(&opencode)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/message"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(30)))
+        .mount(&opencode)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/abort"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("true"))
+        .expect(1)
+        .mount(&opencode)
+        .await;
+
+    let _fake_opencode = install_fake_opencode(&format!(
+        "#!/bin/sh\necho \"opencode server listening on {}\"\n",
+        opencode.uri()
+    ))
+    .await;
+
+    // GIVEN: Authenticated connection with the (fake) server spawned
+    let ipc_port = 19904;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut ws = connect_to_server(ipc_port).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(auth_response.success, "Auth should succeed");
+
+    send_protobuf(
+        &mut ws,
+        &IpcClientMessage {
+            request_id: 2,
+            payload: Some(ipc_client_message::Payload::SpawnServer(
+                IpcSpawnServerRequest { port: None },
+            )),
+        },
+    )
+    .await;
+    let spawned: IpcServerMessage = receive_protobuf(&mut ws).await;
+    assert!(matches!(
+        spawned.payload,
+        Some(ipc_server_message::Payload::SpawnServerResponse(_))
+    ));
+
+    // GIVEN: A stream in progress (its first part has arrived)
+    send_protobuf(
+        &mut ws,
+        &IpcClientMessage {
+            request_id: 3,
+            payload: Some(ipc_client_message::Payload::StreamMessage(
+                IpcStreamMessageRequest {
+                    session_id: "ses_test".to_string(),
+                    text: "Tell me a long story".to_string(),
+                    model_id: "gpt-4o".to_string(),
+                    provider_id: "openai".to_string(),
+                    agent: None,
+                },
+            )),
+        },
+    )
+    .await;
+    let first: IpcServerMessage = receive_protobuf(&mut ws).await;
+    assert!(matches!(
+        first.payload,
+        Some(ipc_server_message::Payload::MessagePartEvent(_))
+    ));
+
+    // WHEN: Aborting the session
+    send_protobuf(
+        &mut ws,
+        &IpcClientMessage {
+            request_id: 4,
+            payload: Some(ipc_client_message::Payload::AbortMessage(
+                IpcAbortMessageRequest {
+                    session_id: "ses_test".to_string(),
+                },
+            )),
+        },
+    )
+    .await;
+
+    // THEN: The abort succeeds and the stream completes with an aborted message
+    let mut abort_success = None;
+    let mut complete = None;
+    while abort_success.is_none() || complete.is_none() {
+        let frame: IpcServerMessage =
+            tokio::time::timeout(Duration::from_secs(5), receive_protobuf(&mut ws))
+                .await
+                .expect("stream should end promptly after abort");
+        match (frame.request_id, frame.payload) {
+            (4, Some(ipc_server_message::Payload::AbortMessageResponse(resp))) => {
+                abort_success = Some(resp.success)
+            }
+            (3, Some(ipc_server_message::Payload::MessageCompleteEvent(event))) => {
+                complete = event.message
+            }
+            (id, other) => panic!("Unexpected frame for request {id}: {other:?}"),
+        }
+    }
+    assert_eq!(abort_success, Some(true));
+    match complete.and_then(|m| m.message) {
+        Some(OcMessageKind::Assistant(assistant)) => {
+            assert_eq!(assistant.session_id, "ses_test");
+            assert!(matches!(
+                assistant.error.and_then(|e| e.error),
+                Some(oc_message_error::Error::Aborted(_))
+            ));
+        }
+        other => panic!("Expected aborted assistant message, got {other:?}"),
+    }
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies connections beyond `max_connections` are rejected while existing ones
+/// keep working, and that a freed slot can be reused.
+///
+/// **WHY THIS MATTERS**: Each connection costs a task, buffers and an OpenCode client; a
+/// buggy or hostile local process opening thousands of them must not exhaust the backend.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Connections are accepted without a limit
+/// - Rejected clients get no close reason (indistinguishable from a crash)
+/// - Permits aren't released when a connection ends (the server locks up for good)
+#[tokio::test]
+async fn given_max_connections_reached_when_client_connects_then_rejected_with_reason() {
+    // GIVEN: IPC server limited to two connections, both in use
+    let ipc_port = 19921;
+    let options = IpcServerOptions {
+        max_connections: 2,
+        ..Default::default()
+    };
+    let handle =
+        start_test_ipc_server_with_options(ipc_port, Some(String::from(TEST_AUTH_TOKEN)), options)
+            .await
+            .expect("Failed to start IPC server");
+    let mut first = connect_to_server(ipc_port).await;
+    assert!(authenticate(&mut first, TEST_AUTH_TOKEN).await.success);
+    let mut second = connect_to_server(ipc_port).await;
+    assert!(authenticate(&mut second, TEST_AUTH_TOKEN).await.success);
+
+    // WHEN: Two more clients connect
+    for _ in 0..2 {
+        let mut excess = connect_to_server(ipc_port).await;
+
+        // THEN: Each is closed right away with a "try again" code and a reason
+        let close = tokio::time::timeout(Duration::from_secs(2), excess.next())
+            .await
+            .expect("Server should close the excess connection");
+        let frame = match close {
+            Some(Ok(Message::Close(Some(frame)))) => frame,
+            other => panic!("Expected close frame, got {other:?}"),
+        };
+        assert_eq!(frame.code, CloseCode::Again);
+        assert_eq!(frame.reason.as_str(), "Too many connections");
+    }
+
+    // THEN: Existing connections are still served
+    for (ws, request_id) in [(&mut first, 2), (&mut second, 3)] {
+        let msg = IpcClientMessage {
+            request_id,
+            payload: Some(ipc_client_message::Payload::ListSessions(
+                IpcListSessionsRequest {},
+            )),
+        };
+        send_protobuf(ws, &msg).await;
+        let response: IpcServerMessage = receive_protobuf(ws).await;
+        assert_eq!(response.request_id, request_id);
+    }
+
+    // WHEN: One connection closes
+    first.close(None).await.expect("Failed to send close");
+    while let Some(Ok(_)) = first.next().await {}
+
+    // THEN: Its slot is freed for a new client (once the server notices the close)
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    loop {
+        let mut ws = connect_to_server(ipc_port).await;
+        match tokio::time::timeout(Duration::from_millis(100), ws.next()).await {
+            Ok(Some(Ok(Message::Close(_)))) => {
+                assert!(
+                    tokio::time::Instant::now() < deadline,
+                    "Slot should be freed after a connection closes"
+                );
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            // Not closed by the server: the connection was admitted
+            _ => {
+                assert!(authenticate(&mut ws, TEST_AUTH_TOKEN).await.success);
+                break;
+            }
+        }
+    }
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies the server moves to the next port when the preferred one is taken,
+/// and reports the port it bound.
+///
+/// **WHY THIS MATTERS**: A second app instance or a leftover process holding the port
+/// would otherwise stop the app from launching at all.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - `AddrInUse` still fails startup
+/// - The handle reports the preferred port rather than the bound one
+#[tokio::test]
+async fn given_preferred_port_in_use_when_starting_server_then_binds_next_port() {
+    // GIVEN: Something else listening on the preferred port
+    let preferred_port = 19924;
+    let _squatter = std::net::TcpListener::bind(("127.0.0.1", preferred_port))
+        .expect("Preferred port should be free before the test");
+
+    // WHEN: Starting the IPC server on that port
+    let handle = start_test_ipc_server(preferred_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Server should fall back to another port");
+
+    // THEN: It bound the next port and serves clients there
+    assert_eq!(handle.port(), preferred_port + 1);
+    let mut ws = connect_to_server(handle.port()).await;
+    let auth_response = authenticate(&mut ws, TEST_AUTH_TOKEN).await;
+    assert!(
+        auth_response.success,
+        "Auth should succeed on the fallback port"
+    );
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies disabling fallback keeps the old fail-fast behavior.
+///
+/// **BUG THIS CATCHES**: Would catch `port_fallback_attempts: 0` still trying other ports.
+#[tokio::test]
+async fn given_port_in_use_and_no_fallback_when_starting_server_then_io_error() {
+    // GIVEN: The preferred port is taken and fallback is disabled
+    let preferred_port = 19926;
+    let _squatter = std::net::TcpListener::bind(("127.0.0.1", preferred_port))
+        .expect("Preferred port should be free before the test");
+    let options = IpcServerOptions {
+        port_fallback_attempts: 0,
+        ..Default::default()
+    };
+
+    // WHEN: Starting the IPC server
+    let result = start_test_ipc_server_with_options(
+        preferred_port,
+        Some(String::from(TEST_AUTH_TOKEN)),
+        options,
+    )
+    .await;
+
+    // THEN: Startup fails with an I/O error naming the port
+    match result {
+        Err(IpcError::Io { message, .. }) => {
+            assert!(message.contains("19926"), "unexpected message: {message}")
+        }
+        Err(other) => panic!("Expected Io error, got {other:?}"),
+        Ok(_) => panic!("Server must not bind another port with fallback disabled"),
+    }
+}
+
+/// Test helper: Receive the next text frame and parse it as JSON.
+async fn receive_json(
+    ws: &mut tokio_tungstenite::WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+) -> serde_json::Value {
+    let frame = tokio::time::timeout(Duration::from_secs(5), ws.next())
+        .await
+        .expect("Timed out waiting for a response");
+    match frame {
+        Some(Ok(Message::Text(text))) => {
+            serde_json::from_str(text.as_str()).expect("Response should be valid JSON")
+        }
+        other => panic!("Expected a text frame, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies a client can speak JSON end to end when the JSON protocol is enabled:
+/// shorthand handshake, then full envelopes, answered as JSON text frames.
+///
+/// **WHY THIS MATTERS**: JSON mode lets scripts and browser consoles poke the backend
+/// without protobuf tooling; it's only useful if every reply comes back readable.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Text handshakes are still refused with the option on
+/// - Replies are sent as binary protobuf to a JSON connection
+/// - Request ids or oneof payloads don't survive the JSON mapping
+#[cfg(unix)]
+#[tokio::test]
+async fn given_json_protocol_when_client_sends_text_frames_then_answered_in_json() {
+    use crate::ipc_tests::helpers::install_fake_opencode;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    // GIVEN: A mock OpenCode server with one session, reached through a fake binary
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&opencode)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "id": "ses_json",
+                "projectID": "prj_test",
+                "directory": "/work/project",
+                "title": "Scripted session",
+                "version": "1.0.0",
+                "time": { "created": 1767225600000u64, "updated": 1767225700000u64 }
+            }])),
+        )
+        .mount(&opencode)
+        .await;
+    let _fake_opencode = install_fake_opencode(&format!(
+        "#!/bin/sh\necho \"opencode server listening on {}\"\n",
+        opencode.uri()
+    ))
+    .await;
+
+    // GIVEN: IPC server with the JSON protocol enabled
+    let ipc_port = 19928;
+    let options = IpcServerOptions {
+        json_protocol: true,
+        ..Default::default()
+    };
+    let handle =
+        start_test_ipc_server_with_options(ipc_port, Some(String::from(TEST_AUTH_TOKEN)), options)
+            .await
+            .expect("Failed to start IPC server");
+    let mut ws = connect_to_server(ipc_port).await;
+
+    // WHEN: Authenticating with the shorthand handshake object
+    let handshake =
+        serde_json::json!({ "token": TEST_AUTH_TOKEN, "protocol_version": IPC_PROTOCOL_VERSION });
+    ws.send(Message::Text(handshake.to_string().into()))
+        .await
+        .expect("Failed to send handshake");
+
+    // THEN: The handshake response comes back as JSON
+    let auth = receive_json(&mut ws).await;
+    assert_eq!(auth["request_id"], 1);
+    let auth_response = &auth["payload"]["AuthHandshakeResponse"];
+    assert_eq!(
+        auth_response["success"], true,
+        "unexpected response: {auth}"
+    );
+    assert_eq!(auth_response["protocol_version"], IPC_PROTOCOL_VERSION);
+
+    // WHEN: Spawning the server and listing sessions with full JSON envelopes
+    let spawn = serde_json::json!({ "request_id": 2, "payload": { "SpawnServer": {} } });
+    ws.send(Message::Text(spawn.to_string().into()))
+        .await
+        .expect("Failed to send spawn request");
+    let spawned = receive_json(&mut ws).await;
+    assert_eq!(spawned["request_id"], 2);
+    assert!(
+        spawned["payload"]["SpawnServerResponse"].is_object(),
+        "unexpected response: {spawned}"
+    );
+
+    let list = serde_json::json!({ "request_id": 3, "payload": { "ListSessions": {} } });
+    ws.send(Message::Text(list.to_string().into()))
+        .await
+        .expect("Failed to send list request");
+
+    // THEN: The session list round-trips with its request id
+    let listed = receive_json(&mut ws).await;
+    assert_eq!(listed["request_id"], 3);
+    let sessions = &listed["payload"]["SessionList"]["sessions"];
+    assert_eq!(
+        sessions[0]["id"], "ses_json",
+        "unexpected response: {listed}"
+    );
+    assert_eq!(sessions[0]["title"], "Scripted session");
+
+    handle.shutdown().await;
+}
+
+/// **VALUE**: Verifies the default server still refuses a JSON handshake.
+///
+/// **WHY THIS MATTERS**: JSON mode is a debugging aid; production servers should keep
+/// accepting only the binary protocol the app speaks.
+///
+/// **BUG THIS CATCHES**: Would catch JSON handling leaking into servers that didn't opt in.
+#[tokio::test]
+async fn given_default_options_when_client_sends_json_handshake_then_connection_closed() {
+    // GIVEN: IPC server with default options
+    let ipc_port = 19929;
+    let handle = start_test_ipc_server(ipc_port, Some(String::from(TEST_AUTH_TOKEN)))
+        .await
+        .expect("Failed to start IPC server");
+    let mut ws = connect_to_server(ipc_port).await;
+
+    // WHEN: Sending a valid token as a JSON text frame
+    let handshake = serde_json::json!({ "token": TEST_AUTH_TOKEN });
+    ws.send(Message::Text(handshake.to_string().into()))
+        .await
+        .expect("Failed to send handshake");
+
+    // THEN: The server closes without answering
+    assert!(
+        is_connection_closed(&mut ws).await,
+        "Text handshake should be refused"
+    );
+
+    handle.shutdown().await;
+}
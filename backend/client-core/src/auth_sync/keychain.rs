@@ -0,0 +1,168 @@
+//! API key storage in the OS keychain (macOS Keychain, Windows Credential Manager,
+//! Secret Service on Linux).
+//!
+//! An alternative to plaintext `.env` files. Keys are stored under [`KEYCHAIN_SERVICE`]
+//! with the provider name as the account, and only ever handled as [`RedactedApiKey`]:
+//! log lines name the provider, never the value.
+//!
+//! The free functions use the OS keychain; the `*_with` variants take any
+//! [`KeychainBackend`] (e.g. an in-memory one in tests), and [`EnvSource`] for the env
+//! fallback.
+
+use crate::config::ModelsConfig;
+use crate::error::AuthSyncError;
+
+use super::validation::KeyValidator;
+use super::{EnvSource, LoadedKeys, ProcessEnv, load_dotenv, load_env_api_keys_with};
+
+use common::RedactedApiKey;
+
+use log::{debug, info, warn};
+
+/// Keychain service name all API keys are stored under.
+pub const KEYCHAIN_SERVICE: &str = "com.opencode.blazor.api-keys";
+
+/// Secret storage keyed by provider name.
+pub trait KeychainBackend: Send + Sync {
+    /// Store (or replace) the secret for `provider`.
+    fn set_secret(&self, provider: &str, secret: &str) -> Result<(), AuthSyncError>;
+    /// The stored secret, or `None` if there is no entry.
+    fn get_secret(&self, provider: &str) -> Result<Option<String>, AuthSyncError>;
+    /// Remove the secret (removing a missing entry is not an error).
+    fn delete_secret(&self, provider: &str) -> Result<(), AuthSyncError>;
+}
+
+/// The platform keychain, via the `keyring` crate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsKeychain;
+
+impl OsKeychain {
+    #[track_caller]
+    fn entry(provider: &str) -> Result<keyring::Entry, AuthSyncError> {
+        keyring::Entry::new(KEYCHAIN_SERVICE, provider)
+            .map_err(|e| AuthSyncError::keychain(provider, e.to_string()))
+    }
+}
+
+impl KeychainBackend for OsKeychain {
+    fn set_secret(&self, provider: &str, secret: &str) -> Result<(), AuthSyncError> {
+        Self::entry(provider)?
+            .set_password(secret)
+            .map_err(|e| AuthSyncError::keychain(provider, e.to_string()))
+    }
+
+    fn get_secret(&self, provider: &str) -> Result<Option<String>, AuthSyncError> {
+        match Self::entry(provider)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(AuthSyncError::keychain(provider, e.to_string())),
+        }
+    }
+
+    fn delete_secret(&self, provider: &str) -> Result<(), AuthSyncError> {
+        match Self::entry(provider)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(AuthSyncError::keychain(provider, e.to_string())),
+        }
+    }
+}
+
+/// Store `key` for `provider` in the OS keychain.
+pub fn store_key(provider: &str, key: &RedactedApiKey) -> Result<(), AuthSyncError> {
+    store_key_with(&OsKeychain, provider, key)
+}
+
+/// Load the key for `provider` from the OS keychain (`None` if absent or unreadable).
+pub fn load_key(provider: &str) -> Option<RedactedApiKey> {
+    load_key_with(&OsKeychain, provider)
+}
+
+/// Delete the key for `provider` from the OS keychain.
+pub fn delete_key(provider: &str) -> Result<(), AuthSyncError> {
+    delete_key_with(&OsKeychain, provider)
+}
+
+/// Load API keys from the OS keychain, falling back to .env/environment per provider.
+///
+/// See [`load_api_keys_with`].
+pub fn load_api_keys(config: &ModelsConfig) -> LoadedKeys {
+    load_dotenv();
+    load_api_keys_with(&OsKeychain, &ProcessEnv, config)
+}
+
+/// Store `key` for `provider` in `backend`.
+pub fn store_key_with(
+    backend: &dyn KeychainBackend,
+    provider: &str,
+    key: &RedactedApiKey,
+) -> Result<(), AuthSyncError> {
+    backend.set_secret(provider, key.as_str())?;
+    info!(
+        "Stored API key for provider {} in keychain ({} chars)",
+        provider,
+        key.len()
+    );
+    Ok(())
+}
+
+/// Load the key for `provider` from `backend` (`None` if absent or unreadable).
+pub fn load_key_with(backend: &dyn KeychainBackend, provider: &str) -> Option<RedactedApiKey> {
+    match backend.get_secret(provider) {
+        Ok(secret) => secret.map(RedactedApiKey::new),
+        Err(e) => {
+            warn!("Failed to read keychain: {}", e);
+            None
+        }
+    }
+}
+
+/// Delete the key for `provider` from `backend`.
+pub fn delete_key_with(backend: &dyn KeychainBackend, provider: &str) -> Result<(), AuthSyncError> {
+    backend.delete_secret(provider)?;
+    info!("Deleted API key for provider {} from keychain", provider);
+    Ok(())
+}
+
+/// Load API keys from `backend` and `env`; the keychain wins per provider.
+///
+/// Keychain keys are validated like env keys. A provider with a keychain entry never uses
+/// its env key, even if the keychain one is invalid: the user chose the keychain as the
+/// source, so an invalid entry is reported rather than silently bypassed.
+pub fn load_api_keys_with(
+    backend: &dyn KeychainBackend,
+    env: &dyn EnvSource,
+    config: &ModelsConfig,
+) -> LoadedKeys {
+    let mut loaded = load_env_api_keys_with(env, config);
+
+    for provider in &config.providers {
+        let Some(key) = load_key_with(backend, &provider.name) else {
+            debug!("No keychain entry for provider {}", provider.name);
+            continue;
+        };
+
+        loaded.keys.remove(&provider.name);
+        loaded.validation_errors.remove(&provider.name);
+
+        let validator = KeyValidator::from_config(provider);
+        match validator.validate_and_wrap(key.as_str().to_string()) {
+            Ok(redacted_key) => {
+                info!(
+                    "Found valid API key for provider: {} (from keychain, {} chars)",
+                    provider.name,
+                    redacted_key.len()
+                );
+                loaded.keys.insert(provider.name.clone(), redacted_key);
+            }
+            Err(e) => {
+                warn!(
+                    "Invalid keychain API key for provider '{}': {}",
+                    provider.name, e
+                );
+                loaded.validation_errors.insert(provider.name.clone(), e);
+            }
+        }
+    }
+
+    loaded
+}
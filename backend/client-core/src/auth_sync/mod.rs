@@ -2,37 +2,53 @@
 //!
 //! # Features
 //! - Loads .env from cwd or executable directory
+//! - Case-insensitive fallback for env var names (Windows)
 //! - Uses ModelsConfig for provider definitions (no hardcoding)
 //! - Provider-specific key validation
 //! - OAuth detection to skip configured providers
+//! - Concurrent sync with bounded parallelism
 //! - Retry with exponential backoff
 //! - Global operation timeout
+//! - Cancellation between providers and during retry waits
 //! - Secure handling via RedactedApiKey
+//! - Optional OS keychain storage (`keychain` feature)
 //!
 //! # Security
 //! - API keys wrapped in RedactedApiKey (safe Debug impl)
 //! - Keys zeroized on drop
 //! - Never logged or serialized
 
+#[cfg(feature = "keychain")]
+pub mod keychain;
 pub mod oauth;
 pub mod paths;
 pub mod validation;
 
 // Re-export key types for convenience
 pub use oauth::OAuthStatus;
+pub use validation::validate_api_key;
 
 use crate::config::ModelsConfig;
+use crate::config::models::ProviderConfig;
 use crate::error::AuthSyncError;
+use crate::opencode_client::OpencodeClient;
 
-use common::RedactedApiKey;
+use common::{RedactedApiKey, RedactedDisplay};
 
+use oauth::{AuthInfo, check_oauth_status};
 use validation::KeyValidator;
 
 use std::collections::HashMap;
 use std::env;
+use std::ffi::OsString;
 use std::time::Duration;
 
-use log::{debug, info, warn};
+use backoff::{ExponentialBackoff, backoff::Backoff};
+use futures_util::{StreamExt, stream};
+use log::{debug, error, info, warn};
+use sha2::{Digest, Sha256};
+use tokio::time::{Instant, sleep as TokioSleep, timeout_at};
+use tokio_util::sync::CancellationToken;
 
 /// Result of loading API keys from environment.
 #[derive(Debug)]
@@ -74,14 +90,38 @@ pub struct EnvLoadResult {
 /// - Keys wrapped in RedactedApiKey (never exposed in Debug)
 /// - Skips empty and placeholder values
 pub fn load_env_api_keys(config: &ModelsConfig) -> LoadedKeys {
-    // Try to load .env file (non-fatal if missing)
-    let env_result = try_load_dotenv();
-    if !env_result.loaded {
-        debug!("No .env file found - will check existing environment variables");
+    load_dotenv();
+    load_env_api_keys_with(&ProcessEnv, config)
+}
+
+/// Where [`load_env_api_keys_with`] reads environment variables from.
+pub trait EnvSource {
+    /// The value of variable `name`, or `None` if it isn't set.
+    fn var_os(&self, name: &str) -> Option<OsString>;
+    /// Every variable, for the case-insensitive fallback.
+    fn vars_os(&self) -> Vec<(OsString, OsString)>;
+}
+
+/// The process environment.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessEnv;
+
+impl EnvSource for ProcessEnv {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        env::var_os(name)
+    }
+
+    fn vars_os(&self) -> Vec<(OsString, OsString)> {
+        env::vars_os().collect()
     }
+}
 
+/// [`load_env_api_keys`], reading variables from `env` without loading a .env file.
+pub fn load_env_api_keys_with(env: &dyn EnvSource, config: &ModelsConfig) -> LoadedKeys {
     let mut keys = HashMap::new();
     let mut validation_errors = HashMap::new();
+    // Built on the first exact-case miss, then shared by the remaining providers
+    let mut env_by_lowercase = None;
 
     // Use provider config to know exactly which env vars to look for
     for provider in &config.providers {
@@ -93,8 +133,12 @@ pub fn load_env_api_keys(config: &ModelsConfig) -> LoadedKeys {
             continue;
         }
 
-        match env::var(&provider.api_key_env) {
-            Ok(value) => {
+        match read_env_var(env, &provider.api_key_env, &mut env_by_lowercase) {
+            Ok(mut value) => {
+                if strip_env_value(&mut value) {
+                    debug!("Stripped surrounding quotes from {}", provider.api_key_env);
+                }
+
                 // Validate using provider-specific rules
                 let validator = KeyValidator::from_config(provider);
 
@@ -109,7 +153,7 @@ pub fn load_env_api_keys(config: &ModelsConfig) -> LoadedKeys {
                         keys.insert(provider.name.clone(), redacted_key);
                     }
                     Err(e) => {
-                        warn!("Invalid API key for provider '{}': {}", provider.name, e);
+                        warn!("Invalid {}: {}", describe_env_key(provider), e);
                         validation_errors.insert(provider.name.clone(), e);
                     }
                 }
@@ -121,13 +165,11 @@ pub fn load_env_api_keys(config: &ModelsConfig) -> LoadedKeys {
                 );
             }
             Err(env::VarError::NotUnicode(_)) => {
-                warn!("Env var {} contains invalid unicode", provider.api_key_env);
+                let key = describe_env_key(provider);
+                warn!("{} contains invalid unicode", key);
                 validation_errors.insert(
                     provider.name.clone(),
-                    AuthSyncError::env_load(format!(
-                        "{} contains invalid unicode",
-                        provider.api_key_env
-                    )),
+                    AuthSyncError::env_load(format!("{key} contains invalid unicode")),
                 );
             }
         }
@@ -139,6 +181,101 @@ pub fn load_env_api_keys(config: &ModelsConfig) -> LoadedKeys {
     }
 }
 
+/// Trim whitespace and one matching pair of surrounding `"` or `'` from an env value.
+///
+/// Depending on how a `.env` line is written, dotenvy can hand back `"sk-..."` or
+/// `  sk-...` verbatim, which then fails prefix validation. Quotes are only stripped as a
+/// matching pair at both ends, so a lone quote that is part of the value stays put.
+/// Edits the buffer in place without reallocating, so it makes no copy of the key. The
+/// characters it removes stay in the buffer's spare capacity until its owner zeroizes it
+/// (`RedactedApiKey` does on drop), and the environment still holds the original value.
+///
+/// Returns whether quotes were stripped.
+fn strip_env_value(value: &mut String) -> bool {
+    trim_in_place(value);
+
+    let bytes = value.as_bytes();
+    let quoted =
+        bytes.len() >= 2 && matches!(bytes[0], b'"' | b'\'') && bytes[bytes.len() - 1] == bytes[0];
+    if quoted {
+        value.truncate(value.len() - 1);
+        value.remove(0);
+        trim_in_place(value);
+    }
+    quoted
+}
+
+/// `str::trim` without allocating a new string.
+fn trim_in_place(value: &mut String) {
+    let end = value.trim_end().len();
+    value.truncate(end);
+    let start = value.len() - value.trim_start().len();
+    value.drain(..start);
+}
+
+/// Environment variables keyed by lowercased name: `lowercase -> (actual name, value)`.
+type EnvByLowercase = HashMap<String, (String, OsString)>;
+
+/// Read env var `name`, falling back to a case-insensitive match.
+///
+/// Windows env var names are case-insensitive, but the OS may already have normalized the
+/// name (`OPENAI_API_KEY`) so an exact lookup for `OpenAI_API_KEY` misses. The exact name
+/// always wins; `env_by_lowercase` is filled on the first miss and reused after that.
+fn read_env_var(
+    env: &dyn EnvSource,
+    name: &str,
+    env_by_lowercase: &mut Option<EnvByLowercase>,
+) -> Result<String, env::VarError> {
+    if let Some(value) = env.var_os(name) {
+        return value.into_string().map_err(env::VarError::NotUnicode);
+    }
+
+    let env_by_lowercase = env_by_lowercase.get_or_insert_with(|| index_env_by_lowercase(env));
+    let Some((actual, value)) = env_by_lowercase.get(&name.to_lowercase()) else {
+        return Err(env::VarError::NotPresent);
+    };
+
+    info!("Using env var {actual} for {name} (names matched case-insensitively)");
+    value
+        .clone()
+        .into_string()
+        .map_err(env::VarError::NotUnicode)
+}
+
+/// Snapshot the environment by lowercased name; the first of names differing only in case wins.
+fn index_env_by_lowercase(env: &dyn EnvSource) -> EnvByLowercase {
+    let mut index = HashMap::new();
+    for (name, value) in env.vars_os() {
+        // Non-unicode names can't match a configured api_key_env
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        index
+            .entry(name.to_lowercase())
+            .or_insert_with(|| (name.to_string(), value));
+    }
+    index
+}
+
+/// Describe a provider's API key env var for users, e.g. "OpenAI API key (OPENAI_API_KEY)".
+///
+/// Names the provider by `display_name`, falling back to `name` if that is empty.
+pub fn describe_env_key(provider: &ProviderConfig) -> String {
+    let label = if provider.display_name.is_empty() {
+        &provider.name
+    } else {
+        &provider.display_name
+    };
+    format!("{label} API key ({})", provider.api_key_env)
+}
+
+/// Load a .env file into the environment, if one is found (non-fatal if missing).
+pub(crate) fn load_dotenv() {
+    if !try_load_dotenv().loaded {
+        debug!("No .env file found - will check existing environment variables");
+    }
+}
+
 /// Attempts to load .env from known locations.
 fn try_load_dotenv() -> EnvLoadResult {
     // Try current directory first
@@ -190,6 +327,10 @@ pub struct SyncConfig {
     pub initial_delay: Duration,
     /// Maximum retry delay.
     pub max_delay: Duration,
+    /// Load, validate and check OAuth as usual, but don't send any keys to the server.
+    pub dry_run: bool,
+    /// Most providers synced at once (0 is treated as 1).
+    pub concurrency: usize,
 }
 
 impl Default for SyncConfig {
@@ -200,6 +341,297 @@ impl Default for SyncConfig {
             max_retries: 3,
             initial_delay: Duration::from_millis(200),
             max_delay: Duration::from_secs(2),
+            dry_run: false,
+            concurrency: 4,
+        }
+    }
+}
+
+/// Outcome of a full key sync run.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Providers whose key was accepted by the server (in a dry run: would have been sent).
+    pub synced: Vec<String>,
+    /// Providers skipped because OAuth is already configured.
+    pub skipped_oauth: Vec<String>,
+    /// Providers skipped because the server already holds the same key.
+    pub already_current: Vec<String>,
+    /// Providers that failed validation or sync (provider -> error).
+    pub failed: HashMap<String, AuthSyncError>,
+    /// Whether this report comes from a dry run (no keys were sent).
+    pub dry_run: bool,
+}
+
+impl SyncReport {
+    /// Did every attempted provider sync successfully?
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Number of providers covered by this report.
+    pub fn total(&self) -> usize {
+        self.synced.len()
+            + self.skipped_oauth.len()
+            + self.already_current.len()
+            + self.failed.len()
+    }
+}
+
+/// Load API keys from the environment and sync them to the OpenCode server.
+///
+/// # Arguments
+/// - `client`: Client connected to the target OpenCode server
+/// - `config`: The loaded models config with provider definitions
+/// - `sync_config`: Retry, timeout and OAuth-skip behaviour
+///
+/// # Returns
+/// - `SyncReport` with synced, OAuth-skipped and failed providers
+///
+/// # Behavior
+/// - Validation failures are reported without contacting the server
+/// - Providers with OAuth configured are skipped when `skip_oauth_providers` is set
+/// - Providers whose key the server already holds are skipped (not checked in a dry run)
+/// - Up to `concurrency` providers are synced at once, each retrying independently
+/// - Retryable failures are retried with exponential backoff up to `max_retries`
+/// - Providers not reached before `timeout` fail with `GlobalTimeout`
+/// - With `dry_run` set, providers that pass validation and the OAuth check are reported
+///   as synced without contacting the server
+pub async fn sync_all_keys(
+    client: &OpencodeClient,
+    config: &ModelsConfig,
+    sync_config: &SyncConfig,
+) -> SyncReport {
+    sync_all_keys_with_cancel(client, config, sync_config, &CancellationToken::new()).await
+}
+
+/// [`sync_all_keys`], stopping early once `cancel` is cancelled (e.g. the UI closed).
+///
+/// Cancellation takes effect between providers and during retry waits; a request already
+/// sent is allowed to finish. Providers synced before cancellation stay synced and are
+/// reported as such; every provider not finished fails with [`AuthSyncError::Cancelled`].
+pub async fn sync_all_keys_with_cancel(
+    client: &OpencodeClient,
+    config: &ModelsConfig,
+    sync_config: &SyncConfig,
+    cancel: &CancellationToken,
+) -> SyncReport {
+    sync_loaded_keys(client, load_env_api_keys(config), sync_config, cancel).await
+}
+
+/// [`sync_all_keys_with_cancel`] for keys already loaded (e.g. with
+/// [`load_env_api_keys_with`], or from the keychain).
+///
+/// Providers in `loaded_keys.validation_errors` are reported as failed without contacting
+/// the server.
+pub async fn sync_loaded_keys(
+    client: &OpencodeClient,
+    loaded_keys: LoadedKeys,
+    sync_config: &SyncConfig,
+    cancel: &CancellationToken,
+) -> SyncReport {
+    let deadline = Instant::now() + sync_config.timeout;
+
+    let mut report = SyncReport {
+        failed: loaded_keys.validation_errors,
+        dry_run: sync_config.dry_run,
+        ..Default::default()
+    };
+
+    // Start providers in a stable order; they finish in any order, so sort again after
+    let mut providers: Vec<(String, RedactedApiKey)> = loaded_keys.keys.into_iter().collect();
+    providers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // Owned items and an iterator (not stream) map keep the future `Send` for callers
+    // that spawn it, such as the IPC handler
+    let syncs = providers.into_iter().map(|(provider, key)| async move {
+        let outcome = sync_provider(client, &provider, &key, sync_config, cancel, deadline).await;
+        (provider, outcome)
+    });
+    let mut outcomes: Vec<(String, ProviderOutcome)> = stream::iter(syncs)
+        .buffer_unordered(sync_config.concurrency.max(1))
+        .collect()
+        .await;
+    outcomes.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (provider, outcome) in outcomes {
+        match outcome {
+            ProviderOutcome::Synced => report.synced.push(provider),
+            ProviderOutcome::SkippedOAuth => report.skipped_oauth.push(provider),
+            ProviderOutcome::AlreadyCurrent => report.already_current.push(provider),
+            ProviderOutcome::Failed(e) => {
+                report.failed.insert(provider, e);
+            }
+        }
+    }
+
+    info!(
+        "Auth sync {}: {} synced, {} skipped (OAuth), {} already current, {} failed",
+        if report.dry_run {
+            "dry run finished"
+        } else {
+            "finished"
+        },
+        report.synced.len(),
+        report.skipped_oauth.len(),
+        report.already_current.len(),
+        report.failed.len()
+    );
+
+    report
+}
+
+/// What happened to one provider during [`sync_all_keys`].
+enum ProviderOutcome {
+    Synced,
+    SkippedOAuth,
+    AlreadyCurrent,
+    Failed(AuthSyncError),
+}
+
+/// Check one provider's OAuth status and sync its key, unless cancelled or out of time.
+async fn sync_provider(
+    client: &OpencodeClient,
+    provider: &str,
+    key: &RedactedApiKey,
+    sync_config: &SyncConfig,
+    cancel: &CancellationToken,
+    deadline: Instant,
+) -> ProviderOutcome {
+    if cancel.is_cancelled() {
+        debug!("Auth sync cancelled before provider '{}'", provider);
+        return ProviderOutcome::Failed(AuthSyncError::cancelled());
+    }
+
+    if sync_config.skip_oauth_providers {
+        match check_oauth_status(provider) {
+            Ok(status) if status.should_skip_api_key_sync() => {
+                info!("Skipping provider '{}' - OAuth configured", provider);
+                return ProviderOutcome::SkippedOAuth;
+            }
+            Ok(_) => {} // Not OAuth, proceed with sync
+            Err(e) => {
+                warn!(
+                    "Failed to check OAuth status for '{}': {}, proceeding with sync",
+                    provider,
+                    e.redacted_to_string()
+                );
+            }
+        }
+    }
+
+    if sync_config.dry_run {
+        info!("Dry run: would sync key for provider '{}'", provider);
+        return ProviderOutcome::Synced;
+    }
+
+    let sync = async {
+        if server_has_key(client, provider, key).await {
+            return Ok(false);
+        }
+        sync_with_retry(client, provider, key, sync_config, cancel)
+            .await
+            .map(|()| true)
+    };
+    let result = match timeout_at(deadline, sync).await {
+        Ok(result) => result,
+        Err(_) => Err(AuthSyncError::global_timeout(sync_config.timeout)),
+    };
+
+    match result {
+        Ok(false) => {
+            info!(
+                "Key for provider '{}' is already current on the server",
+                provider
+            );
+            ProviderOutcome::AlreadyCurrent
+        }
+        Ok(true) => {
+            info!("Successfully synced key for provider '{}'", provider);
+            ProviderOutcome::Synced
+        }
+        Err(e) => {
+            error!(
+                "Failed to sync key for provider '{}': {}",
+                provider,
+                e.redacted_to_string()
+            );
+            ProviderOutcome::Failed(e)
+        }
+    }
+}
+
+/// Does the server already hold `key` for `provider`?
+///
+/// Keys are compared by SHA-256 fingerprint, and neither is logged. Any failure to read
+/// the server's auth counts as "no", so the key is synced.
+async fn server_has_key(client: &OpencodeClient, provider: &str, key: &RedactedApiKey) -> bool {
+    match client.get_auth(provider).await {
+        Ok(Some(AuthInfo::ApiKey { key: existing })) => {
+            let existing = RedactedApiKey::new(existing);
+            key_fingerprint(&existing) == key_fingerprint(key)
+        }
+        Ok(_) => false,
+        Err(e) => {
+            debug!(
+                "Could not read current auth for '{}', syncing anyway: {}",
+                provider,
+                e.redacted_to_string()
+            );
+            false
+        }
+    }
+}
+
+/// SHA-256 digest of a key, so keys are compared by fingerprint rather than raw value.
+fn key_fingerprint(key: &RedactedApiKey) -> [u8; 32] {
+    Sha256::digest(key.as_str().as_bytes()).into()
+}
+
+/// Sync a single provider's key, retrying retryable failures with exponential backoff.
+///
+/// Returns [`AuthSyncError::Cancelled`] if `cancel` fires while waiting to retry.
+async fn sync_with_retry(
+    client: &OpencodeClient,
+    provider: &str,
+    key: &RedactedApiKey,
+    sync_config: &SyncConfig,
+    cancel: &CancellationToken,
+) -> Result<(), AuthSyncError> {
+    let mut backoff = ExponentialBackoff {
+        initial_interval: sync_config.initial_delay,
+        current_interval: sync_config.initial_delay,
+        max_interval: sync_config.max_delay,
+        max_elapsed_time: None,
+        ..Default::default()
+    };
+    let mut attempt = 0;
+
+    loop {
+        let error = match client.sync_api_key(provider, key.as_str()).await {
+            Ok(()) => return Ok(()),
+            Err(e) => AuthSyncError::from_client_error(provider, &e),
+        };
+
+        if !error.is_retryable() || attempt >= sync_config.max_retries {
+            return Err(error);
+        }
+
+        attempt += 1;
+        let delay = backoff
+            .next_backoff()
+            .unwrap_or(sync_config.max_delay)
+            .min(sync_config.max_delay);
+        debug!(
+            "Retrying sync for '{}' (attempt {}/{}) after {:?}: {}",
+            provider,
+            attempt,
+            sync_config.max_retries,
+            delay,
+            error.redacted_to_string()
+        );
+        tokio::select! {
+            _ = cancel.cancelled() => return Err(AuthSyncError::cancelled()),
+            _ = TokioSleep(delay) => {}
         }
     }
 }
@@ -1,20 +1,30 @@
-//! OAuth detection for skipping API key sync.
+//! OAuth detection for skipping API key sync, and recording auth in auth.json.
 //!
 //! Returns `Result<OAuthStatus, Error>` instead of silent `bool` fallback.
 //! Caller decides how to handle uncertainty.
 
 use super::paths::detect_opencode_paths;
+use crate::config::write_atomic_private;
 use crate::error::AuthSyncError;
 use log::{debug, info, warn};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
 
 /// OAuth detection result.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum OAuthStatus {
     /// OAuth is configured and valid for this provider.
     Configured,
+    /// OAuth is configured but the token expired (`expires` in ms since the Unix epoch).
+    ///
+    /// OpenCode may refresh it, but we can't rely on that, so the API key is still synced.
+    Expired { expires: f64 },
     /// API key auth is configured (not OAuth).
     ApiKeyConfigured,
     /// WellKnown auth is configured (not OAuth).
@@ -38,13 +48,16 @@ impl OAuthStatus {
 }
 
 /// Auth info from OpenCode's auth.json file.
-#[derive(Debug, Clone, Deserialize)]
+///
+/// `Debug` output includes key material; never log it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AuthInfo {
     #[serde(rename = "oauth")]
     OAuth {
         access: String,
         refresh: String,
+        /// Access token expiry in ms since the Unix epoch.
         expires: f64,
     },
     #[serde(rename = "api")]
@@ -62,8 +75,20 @@ impl AuthInfo {
         }
     }
 
+    /// Status as of now (expired OAuth tokens report [`OAuthStatus::Expired`]).
     pub fn to_oauth_status(&self) -> OAuthStatus {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_millis() as f64);
+        self.to_oauth_status_at(now_ms)
+    }
+
+    /// Status as of `now_ms` (ms since the Unix epoch).
+    pub fn to_oauth_status_at(&self, now_ms: f64) -> OAuthStatus {
         match self {
+            AuthInfo::OAuth { expires, .. } if *expires <= now_ms => {
+                OAuthStatus::Expired { expires: *expires }
+            }
             AuthInfo::OAuth { .. } => OAuthStatus::Configured,
             AuthInfo::ApiKey { .. } => OAuthStatus::ApiKeyConfigured,
             AuthInfo::WellKnown { .. } => OAuthStatus::WellKnownConfigured,
@@ -99,14 +124,24 @@ pub fn check_oauth_status(provider: &str) -> Result<OAuthStatus, AuthSyncError>
         paths.auth_file, paths.source
     );
 
+    check_oauth_status_in(&paths.auth_file, provider)
+}
+
+/// Check OAuth status for a provider in a specific auth.json file.
+///
+/// See [`check_oauth_status`], which resolves the file from OpenCode's data directory.
+pub fn check_oauth_status_in(
+    auth_file: &Path,
+    provider: &str,
+) -> Result<OAuthStatus, AuthSyncError> {
     // Check if file exists
-    if !paths.auth_file.exists() {
-        debug!("auth.json not found at {:?}", paths.auth_file);
+    if !auth_file.exists() {
+        debug!("auth.json not found at {:?}", auth_file);
         return Ok(OAuthStatus::NotConfigured);
     }
 
     // Read file
-    let content = match fs::read_to_string(&paths.auth_file) {
+    let content = match fs::read_to_string(auth_file) {
         Ok(c) => c,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
             return Ok(OAuthStatus::NotConfigured);
@@ -154,6 +189,11 @@ pub fn check_oauth_status(provider: &str) -> Result<OAuthStatus, AuthSyncError>
                     "Provider '{}' has OAuth configured - will skip API key sync",
                     provider
                 );
+            } else if let OAuthStatus::Expired { .. } = status {
+                info!(
+                    "Provider '{}' has an expired OAuth token - will sync API key",
+                    provider
+                );
             } else {
                 debug!(
                     "Provider '{}' has {} auth configured",
@@ -227,3 +267,103 @@ pub fn check_oauth_status_batch(providers: &[&str]) -> HashMap<String, OAuthStat
 
     results
 }
+
+/// Serializes our own auth.json writers (cross-process writers are handled by re-reading).
+static AUTH_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Attempts to merge before giving up on a file that keeps changing underneath us.
+const AUTH_WRITE_ATTEMPTS: usize = 3;
+
+/// Record `info` for `provider` in OpenCode's auth.json.
+///
+/// See [`write_auth_entry_in`].
+pub fn write_auth_entry(provider: &str, info: &AuthInfo) -> Result<(), AuthSyncError> {
+    let paths = detect_opencode_paths()?;
+    write_auth_entry_in(&paths.auth_file, provider, info)
+}
+
+/// Record `info` for `provider` in a specific auth.json file.
+///
+/// Other providers' entries are kept as-is (including ones we can't parse). The file is
+/// replaced atomically via a temp file (mode 0600 on Unix). Just before replacing it, the
+/// file is re-read: if another process (e.g. OpenCode) changed it since we merged, the
+/// merge is redone on the new content rather than overwriting that change.
+///
+/// # Errors
+///
+/// Returns [`AuthSyncError::AuthFileWrite`] if the file can't be read, parsed, or written,
+/// or keeps changing across repeated attempts.
+pub fn write_auth_entry_in(
+    auth_file: &Path,
+    provider: &str,
+    info: &AuthInfo,
+) -> Result<(), AuthSyncError> {
+    let _guard = AUTH_FILE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let entry = serde_json::to_value(info)
+        .map_err(|e| AuthSyncError::auth_file_write(format!("Serialize error: {}", e)))?;
+
+    for _ in 0..AUTH_WRITE_ATTEMPTS {
+        let original = read_auth_file(auth_file)?;
+
+        let mut auth_data: serde_json::Map<String, serde_json::Value> = match &original {
+            Some(content) => serde_json::from_str(content).map_err(|e| {
+                AuthSyncError::auth_file_write(format!("Existing auth.json is invalid: {}", e))
+            })?,
+            None => serde_json::Map::new(),
+        };
+        auth_data.insert(provider.to_string(), entry.clone());
+
+        let json = serde_json::to_string_pretty(&auth_data)
+            .map_err(|e| AuthSyncError::auth_file_write(format!("Serialize error: {}", e)))?;
+
+        // Someone else wrote in the meantime: merge again on top of their change
+        if read_auth_file(auth_file)? != original {
+            debug!("auth.json changed while merging, retrying");
+            continue;
+        }
+
+        replace_auth_file(auth_file, json.as_bytes())?;
+        info!(
+            "Recorded {} auth for provider '{}' in {:?}",
+            info.auth_type(),
+            provider,
+            auth_file
+        );
+        return Ok(());
+    }
+
+    Err(AuthSyncError::auth_file_write(format!(
+        "{:?} kept changing during write",
+        auth_file
+    )))
+}
+
+/// Current auth.json content (`None` if it doesn't exist).
+fn read_auth_file(auth_file: &Path) -> Result<Option<String>, AuthSyncError> {
+    match fs::read_to_string(auth_file) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AuthSyncError::auth_file_write(format!("Read error: {}", e))),
+    }
+}
+
+/// Atomically replace auth.json via an owner-only temp file of our own.
+///
+/// The temp name is unique per write, so concurrent writers (e.g. another app instance)
+/// never write into each other's temp file.
+fn replace_auth_file(auth_file: &Path, contents: &[u8]) -> Result<(), AuthSyncError> {
+    if let Some(dir) = auth_file.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| AuthSyncError::auth_file_write(format!("{:?}: {}", auth_file, e)))?;
+    }
+
+    let temp_path = auth_file.with_extension(format!("json.{}.tmp", Uuid::new_v4().simple()));
+    write_atomic_private(auth_file, &temp_path, contents).map_err(|e| {
+        // Don't leave a copy of the credentials behind
+        let _ = fs::remove_file(&temp_path);
+        AuthSyncError::auth_file_write(e.to_string())
+    })
+}
@@ -6,6 +6,10 @@
 //! 3. Fallback paths for common configurations
 //!
 //! Returns Result, never silently falls back to wrong path.
+//!
+//! Environment variables, the `dirs` lookup and the target OS come from a
+//! [`PathEnvironment`], so every branch (including other platforms' fallbacks) can be
+//! exercised in tests.
 
 use crate::error::AuthSyncError;
 
@@ -52,6 +56,60 @@ impl std::fmt::Display for PathSource {
     }
 }
 
+/// Operating system whose fallback path applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Linux,
+    MacOS,
+    Windows,
+    Other,
+}
+
+impl Platform {
+    /// The platform this binary was built for.
+    pub fn current() -> Self {
+        if cfg!(target_os = "linux") {
+            Platform::Linux
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOS
+        } else if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else {
+            Platform::Other
+        }
+    }
+}
+
+/// Inputs to path detection.
+///
+/// [`SystemEnvironment`] reads the real process environment; tests substitute their own.
+pub trait PathEnvironment {
+    /// Value of environment variable `key` (None if unset or not unicode).
+    fn var(&self, key: &str) -> Option<String>;
+
+    /// Platform local data directory (`dirs::data_local_dir()`).
+    fn data_local_dir(&self) -> Option<PathBuf>;
+
+    /// Platform whose fallback applies when `data_local_dir` is unavailable.
+    fn platform(&self) -> Platform {
+        Platform::current()
+    }
+}
+
+/// The real process environment and `dirs` crate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemEnvironment;
+
+impl PathEnvironment for SystemEnvironment {
+    fn var(&self, key: &str) -> Option<String> {
+        env::var(key).ok()
+    }
+
+    fn data_local_dir(&self) -> Option<PathBuf> {
+        dirs::data_local_dir()
+    }
+}
+
 /// Detect OpenCode data paths.
 ///
 /// # Errors
@@ -62,8 +120,17 @@ impl std::fmt::Display for PathSource {
 /// - **macOS**: `~/Library/Application Support/opencode`
 /// - **Windows**: `%APPDATA%/opencode`
 pub fn detect_opencode_paths() -> Result<OpenCodePaths, AuthSyncError> {
+    detect_opencode_paths_with(&SystemEnvironment)
+}
+
+/// Detect OpenCode data paths from `environment`.
+///
+/// See [`detect_opencode_paths`].
+pub fn detect_opencode_paths_with(
+    environment: &dyn PathEnvironment,
+) -> Result<OpenCodePaths, AuthSyncError> {
     // 1. Check environment variable override
-    if let Ok(custom_dir) = env::var("OPENCODE_DATA_DIR") {
+    if let Some(custom_dir) = environment.var("OPENCODE_DATA_DIR") {
         let data_dir = PathBuf::from(&custom_dir);
         let auth_file = data_dir.join("auth.json");
 
@@ -77,7 +144,7 @@ pub fn detect_opencode_paths() -> Result<OpenCodePaths, AuthSyncError> {
     }
 
     // 2. Try platform-specific detection via dirs crate
-    if let Some(data_dir) = dirs::data_local_dir() {
+    if let Some(data_dir) = environment.data_local_dir() {
         let opencode_dir = data_dir.join("opencode");
         let auth_file = opencode_dir.join("auth.json");
 
@@ -91,55 +158,41 @@ pub fn detect_opencode_paths() -> Result<OpenCodePaths, AuthSyncError> {
     }
 
     // 3. Platform-specific fallbacks
-    #[cfg(target_os = "linux")]
-    {
-        if let Ok(home) = env::var("HOME") {
-            let data_dir = PathBuf::from(home).join(".local/share/opencode");
-            let auth_file = data_dir.join("auth.json");
-
-            warn!("Using Linux fallback path: {:?}", data_dir);
-
-            return Ok(OpenCodePaths {
-                data_dir,
-                auth_file,
-                source: PathSource::LinuxFallback,
-            });
-        }
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        if let Ok(home) = env::var("HOME") {
-            let data_dir = PathBuf::from(home)
-                .join("Library")
-                .join("Application Support")
-                .join("opencode");
-            let auth_file = data_dir.join("auth.json");
-
-            warn!("Using macOS fallback path: {:?}", data_dir);
-
-            return Ok(OpenCodePaths {
-                data_dir,
-                auth_file,
-                source: PathSource::MacOSFallback,
-            });
-        }
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(appdata) = env::var("APPDATA") {
-            let data_dir = PathBuf::from(appdata).join("opencode");
-            let auth_file = data_dir.join("auth.json");
+    let fallback = match environment.platform() {
+        Platform::Linux => environment.var("HOME").map(|home| {
+            (
+                PathBuf::from(home).join(".local/share/opencode"),
+                PathSource::LinuxFallback,
+            )
+        }),
+        Platform::MacOS => environment.var("HOME").map(|home| {
+            (
+                PathBuf::from(home)
+                    .join("Library")
+                    .join("Application Support")
+                    .join("opencode"),
+                PathSource::MacOSFallback,
+            )
+        }),
+        Platform::Windows => environment.var("APPDATA").map(|appdata| {
+            (
+                PathBuf::from(appdata).join("opencode"),
+                PathSource::WindowsFallback,
+            )
+        }),
+        Platform::Other => None,
+    };
+
+    if let Some((data_dir, source)) = fallback {
+        let auth_file = data_dir.join("auth.json");
 
-            warn!("Using Windows fallback path: {:?}", data_dir);
+        warn!("Using {}: {:?}", source, data_dir);
 
-            return Ok(OpenCodePaths {
-                data_dir,
-                auth_file,
-                source: PathSource::WindowsFallback,
-            });
-        }
+        return Ok(OpenCodePaths {
+            data_dir,
+            auth_file,
+            source,
+        });
     }
 
     // No valid path could be determined
@@ -22,57 +22,48 @@ pub struct KeyValidator {
     /// Provider name for error messages.
     provider: String,
     /// Expected prefix (e.g., "sk-" for OpenAI).
-    expected_prefix: Option<&'static str>,
+    expected_prefix: Option<String>,
     /// Minimum key length.
     min_length: usize,
     /// Maximum key length.
     max_length: usize,
+    /// Placeholder patterns (lowercase) matched at word boundaries.
+    placeholder_patterns: Vec<String>,
 }
 
 impl KeyValidator {
     /// Create validator from provider config.
     ///
-    /// Uses well-known rules for recognized providers,
-    /// generic rules for unknown providers.
+    /// `key_prefix`, `key_min_length` and `key_max_length` from the config take
+    /// precedence; anything unset falls back to well-known rules for recognized
+    /// providers, or generic rules for unknown providers.
+    ///
+    /// `key_placeholder_patterns` replaces [`PLACEHOLDER_PATTERNS`] (an empty list opts
+    /// out of pattern matching; the repeated-character check still applies).
     pub fn from_config(config: &ProviderConfig) -> Self {
         // Well-known provider formats (these are stable, documented APIs)
-        match config.name.as_str() {
-            "openai" => Self {
-                provider: config.name.clone(),
-                expected_prefix: Some("sk-"),
-                min_length: 20,  // Shortest observed OpenAI key
-                max_length: 200, // Allow for project keys which are longer
-            },
-            "anthropic" => Self {
-                provider: config.name.clone(),
-                expected_prefix: Some("sk-ant-"),
-                min_length: 40,
-                max_length: 200,
-            },
-            "google" | "google_generativeai" => Self {
-                provider: config.name.clone(),
-                expected_prefix: Some("AI"), // Google keys start with AI
-                min_length: 30,
-                max_length: 100,
-            },
-            "mistral" => Self {
-                provider: config.name.clone(),
-                expected_prefix: None, // Mistral uses UUIDs
-                min_length: 32,
-                max_length: 64,
-            },
-            "cohere" => Self {
-                provider: config.name.clone(),
-                expected_prefix: None,
-                min_length: 30,
-                max_length: 100,
-            },
-            // Unknown provider: use permissive defaults
-            _ => Self {
-                provider: config.name.clone(),
-                expected_prefix: None,
-                min_length: 10,  // Minimum reasonable key length
-                max_length: 500, // Allow long keys
+        let (prefix, min_length, max_length) = match config.name.as_str() {
+            // Shortest observed OpenAI key; allow for project keys which are longer
+            "openai" => (Some("sk-"), 20, 200),
+            "anthropic" => (Some("sk-ant-"), 40, 200),
+            "google" | "google_generativeai" => (Some("AI"), 30, 100), // Google keys start with AI
+            "mistral" => (None, 32, 64),                               // Mistral uses UUIDs
+            "cohere" => (None, 30, 100),
+            // Unknown provider: permissive defaults (minimum reasonable length, allow long keys)
+            _ => (None, 10, 500),
+        };
+
+        Self {
+            provider: config.name.clone(),
+            expected_prefix: config
+                .key_prefix
+                .clone()
+                .or_else(|| prefix.map(String::from)),
+            min_length: config.key_min_length.unwrap_or(min_length),
+            max_length: config.key_max_length.unwrap_or(max_length),
+            placeholder_patterns: match &config.key_placeholder_patterns {
+                Some(patterns) => patterns.iter().map(|p| p.to_lowercase()).collect(),
+                None => PLACEHOLDER_PATTERNS.iter().map(|p| p.to_string()).collect(),
             },
         }
     }
@@ -105,18 +96,18 @@ impl KeyValidator {
         }
 
         // Check prefix (if required)
-        if let Some(expected) = self.expected_prefix {
-            if !trimmed.starts_with(expected) {
+        if let Some(expected) = &self.expected_prefix {
+            if !trimmed.starts_with(expected.as_str()) {
                 let actual_prefix: String = trimmed.chars().take(expected.len()).collect();
                 return ValidationResult::Invalid(KeyValidationFailure::InvalidPrefix {
-                    expected,
+                    expected: expected.clone(),
                     actual: actual_prefix,
                 });
             }
         }
 
         // Check for placeholder patterns
-        if let Some(pattern) = detect_placeholder(trimmed) {
+        if let Some(pattern) = detect_placeholder(trimmed, &self.placeholder_patterns) {
             return ValidationResult::Invalid(KeyValidationFailure::PlaceholderDetected {
                 pattern,
             });
@@ -144,47 +135,88 @@ impl KeyValidator {
     }
 }
 
+/// Check `key` against `provider`'s rules, without taking or wrapping it.
+///
+/// For inline feedback while a user types a key; the key stays with the caller and
+/// nothing is logged. Same checks as [`KeyValidator::validate`].
+pub fn validate_api_key(provider: &ProviderConfig, key: &str) -> Result<(), KeyValidationFailure> {
+    match KeyValidator::from_config(provider).validate(key) {
+        ValidationResult::Valid => Ok(()),
+        ValidationResult::Invalid(reason) => Err(reason),
+    }
+}
+
+/// Built-in placeholder patterns (see [`detect_placeholder`] for how they match).
+pub const PLACEHOLDER_PATTERNS: &[&str] = &[
+    "...",
+    "your-api-key",
+    "your_api_key",
+    "<your",
+    "insert",
+    "xxx",
+    "placeholder",
+    "example",
+    "test-key",
+    "dummy",
+    "fake",
+    "replace",
+    "put-your",
+    "add-your",
+    "enter-your",
+];
+
 /// Detect common placeholder patterns.
 ///
-/// Returns the matched pattern name if detected.
-fn detect_placeholder(key: &str) -> Option<&'static str> {
+/// Patterns match case-insensitively as whole words: an alphanumeric pattern edge must sit
+/// at the start/end of the key or next to a separator (`-`, `_`, `.`, `:`). So "test-key"
+/// matches "my-test-key" but "est" never matches inside "sk-testing", which random
+/// base64 keys would otherwise trip. Edges that are themselves punctuation ("...",
+/// "<your") need no boundary.
+///
+/// Returns the matched pattern if detected.
+fn detect_placeholder(key: &str, patterns: &[String]) -> Option<String> {
     let lower = key.to_lowercase();
 
-    static PATTERNS: &[(&str, &str)] = &[
-        ("...", "ellipsis"),
-        ("your-api-key", "your-api-key"),
-        ("your_api_key", "your_api_key"),
-        ("insert", "INSERT"),
-        ("<your", "<your...>"),
-        ("xxx", "xxx"),
-        ("placeholder", "placeholder"),
-        ("example", "example"),
-        ("test-key", "test-key"),
-        ("dummy", "dummy"),
-        ("fake", "fake"),
-        ("replace", "replace"),
-        ("put-your", "put-your"),
-        ("add-your", "add-your"),
-        ("enter-your", "enter-your"),
-    ];
-
-    for (pattern, name) in PATTERNS {
-        if lower.contains(pattern) {
-            return Some(name);
-        }
+    if let Some(pattern) = patterns
+        .iter()
+        .find(|pattern| contains_word(&lower, pattern))
+    {
+        return Some(pattern.clone());
     }
 
-    // Check for repeated characters (e.g., "xxxxxxxxxx")
+    // Check for repeated characters across the whole key (e.g., "xxxxxxxxxx")
     if key.len() >= 10 {
         let first_char = key.chars().next().unwrap();
         if key.chars().all(|c| c == first_char) {
-            return Some("repeated_char");
+            return Some("repeated_char".to_string());
         }
     }
 
     None
 }
 
+/// Does `pattern` occur in `haystack` with word boundaries at its alphanumeric edges?
+fn contains_word(haystack: &str, pattern: &str) -> bool {
+    let (Some(first), Some(last)) = (pattern.chars().next(), pattern.chars().last()) else {
+        return false;
+    };
+
+    haystack.match_indices(pattern).any(|(start, matched)| {
+        let end = start + matched.len();
+        let before_ok = !first.is_alphanumeric()
+            || !haystack[..start]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric);
+        let after_ok = !last.is_alphanumeric()
+            || !haystack[end..]
+                .chars()
+                .next()
+                .is_some_and(char::is_alphanumeric);
+        before_ok && after_ok
+    })
+}
+
 /// Check if key contains only valid characters.
 ///
 /// Valid: alphanumeric, hyphen, underscore, period, colon
@@ -0,0 +1,44 @@
+//! Building and normalizing OpenCode server base URLs.
+//!
+//! Base URLs are stored as `scheme://host:port` strings (see [`make_base_url`]) and turned
+//! into a [`Url`] with [`normalize_base_url`] before endpoints are joined onto them.
+//! `Url::join` replaces the last path segment of a base without a trailing slash, so
+//! `http://host:4096/api` joined with `session` would become `http://host:4096/session`;
+//! normalizing first makes it `http://host:4096/api/session`.
+
+use crate::OPENCODE_SERVER_BASE_URL;
+
+use std::net::IpAddr;
+
+use url::{ParseError, Url};
+
+/// Base URL for connecting to a server bound to `host` on `port`.
+///
+/// A wildcard bind (`0.0.0.0`, `::`) listens everywhere but isn't a connectable address,
+/// so loopback is used for those. IPv6 addresses are bracketed.
+pub fn make_base_url(host: &str, port: u16) -> String {
+    match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) if ip.is_unspecified() => format!("{OPENCODE_SERVER_BASE_URL}:{port}"),
+        Ok(IpAddr::V6(ip)) => format!("http://[{ip}]:{port}"),
+        _ => format!("http://{host}:{port}"),
+    }
+}
+
+/// Parse `base_url` into a [`Url`] whose path ends with `/`, so relative endpoints join
+/// onto it instead of replacing its last segment.
+///
+/// Surrounding whitespace, any query and any fragment are dropped.
+///
+/// # Errors
+///
+/// Returns the [`ParseError`] if `base_url` isn't an absolute URL.
+pub fn normalize_base_url(base_url: &str) -> Result<Url, ParseError> {
+    let mut url = Url::parse(base_url.trim())?;
+    url.set_query(None);
+    url.set_fragment(None);
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    Ok(url)
+}
@@ -0,0 +1,96 @@
+//! Config schema migrations.
+//!
+//! `config.json` is migrated as raw JSON before deserialization, one version at a time,
+//! so old configs keep their settings instead of failing to parse and falling back to
+//! defaults.
+//!
+//! # Adding a version
+//!
+//! 1. Bump `CONFIG_VERSION` in `config/mod.rs`
+//! 2. Append a `migrate_vN_to_vN1` step to [`MIGRATIONS`] (index `N` upgrades `N` → `N + 1`)
+
+use crate::config::CONFIG_VERSION;
+use crate::error::config::ConfigError;
+
+use common::ErrorLocation;
+
+use std::panic::Location;
+
+use log::info;
+use serde_json::Value;
+
+/// Upgrades a config JSON value by one version (the caller updates `version`).
+pub(crate) type Migration = fn(Value) -> Result<Value, ConfigError>;
+
+/// Migration chain: `MIGRATIONS[n]` upgrades version `n` to `n + 1`.
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Migrate a config JSON value from `from_version` to the current version.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::UnsupportedVersion`] if `from_version` is newer than this app
+/// supports, or any error raised by a migration step.
+#[track_caller]
+pub fn migrate(value: Value, from_version: u32) -> Result<Value, ConfigError> {
+    migrate_with(value, from_version, CONFIG_VERSION, MIGRATIONS)
+}
+
+/// Migrate `value` from `from_version` to `to_version` using `migrations`.
+#[track_caller]
+pub(crate) fn migrate_with(
+    mut value: Value,
+    from_version: u32,
+    to_version: u32,
+    migrations: &[Migration],
+) -> Result<Value, ConfigError> {
+    if from_version > to_version {
+        return Err(ConfigError::UnsupportedVersion {
+            location: ErrorLocation::from(Location::caller()),
+            version: from_version,
+            supported: to_version,
+        });
+    }
+
+    for version in from_version..to_version {
+        let step =
+            migrations
+                .get(version as usize)
+                .ok_or_else(|| ConfigError::ValidationError {
+                    location: ErrorLocation::from(Location::caller()),
+                    reason: format!("No migration from config version {version}"),
+                })?;
+
+        value = step(value)?;
+        if let Value::Object(ref mut map) = value {
+            map.insert("version".to_string(), Value::from(version + 1));
+        }
+        info!(
+            "Migrated config from version {} to {}",
+            version,
+            version + 1
+        );
+    }
+
+    Ok(value)
+}
+
+/// v0 (pre-versioning) configs already have the v1 shape.
+fn migrate_v0_to_v1(value: Value) -> Result<Value, ConfigError> {
+    Ok(value)
+}
+
+/// v2 writes `ui.font_size` and `ui.chat_density` in snake_case instead of PascalCase.
+///
+/// Every variant is a single word, so lowercasing is enough; values that are already
+/// lowercase (or not strings) are left for deserialization to judge.
+fn migrate_v1_to_v2(mut value: Value) -> Result<Value, ConfigError> {
+    if let Some(ui) = value.get_mut("ui").and_then(Value::as_object_mut) {
+        for key in ["font_size", "chat_density"] {
+            if let Some(Value::String(variant)) = ui.get_mut(key) {
+                *variant = variant.to_ascii_lowercase();
+            }
+        }
+    }
+    Ok(value)
+}
@@ -1,25 +1,41 @@
+pub mod migration;
 pub mod models;
 
-pub use models::ModelsConfig;
+pub use models::{AgentsSection, MODELS_CONFIG_ENV_VAR, ModelRef, ModelsConfig};
 
 use crate::error::config::ConfigError;
 
 use common::ErrorLocation;
 
+use std::fs::{File, OpenOptions};
+use std::io::Write;
 use std::panic::Location;
 use std::path::Path;
 
 use log::{info, warn};
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Unexpected};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 
-const CONFIG_FILE_NAME: &str = "config.json";
-const CONFIG_VERSION: u32 = 1;
+pub(crate) const CONFIG_FILE_NAME: &str = "config.json";
+pub(crate) const CONFIG_VERSION: u32 = 2;
+/// Version assumed for a config.json without `version`. Such files predate v2's snake_case
+/// enums, so they must still go through that migration.
+const UNVERSIONED_CONFIG_VERSION: u32 = 1;
+
+/// Smallest accepted `ui.base_font_points`.
+pub const MIN_BASE_FONT_POINTS: f32 = 8.0;
+/// Largest accepted `ui.base_font_points`.
+pub const MAX_BASE_FONT_POINTS: f32 = 72.0;
 
 // ============================================
 // ENUMS WITH DEFAULTS
 // ============================================
 
+/// Written in snake_case (`"standard"`); configs before version 2 used the variant
+/// names (`"Standard"`) and are migrated on load.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum FontSizePreset {
     Small,
     Standard,
@@ -32,7 +48,9 @@ impl Default for FontSizePreset {
     }
 }
 
+/// Written in snake_case (`"normal"`), like [`FontSizePreset`].
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum ChatDensity {
     Compact,
     Normal,
@@ -49,7 +67,7 @@ impl Default for ChatDensity {
 // CONFIG STRUCTS
 // ============================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub last_opencode_url: Option<String>,
     #[serde(default = "default_auto_start")]
@@ -67,11 +85,16 @@ impl Default for ServerConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UiPreferences {
     #[serde(default)]
     pub font_size: FontSizePreset,
-    #[serde(default = "default_base_font_points")]
+    /// Rejected while parsing unless within
+    /// [`MIN_BASE_FONT_POINTS`]..=[`MAX_BASE_FONT_POINTS`].
+    #[serde(
+        default = "default_base_font_points",
+        deserialize_with = "deserialize_base_font_points"
+    )]
     pub base_font_points: f32,
     #[serde(default)]
     pub chat_density: ChatDensity,
@@ -87,7 +110,7 @@ impl Default for UiPreferences {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioConfig {
     #[serde(default = "default_push_to_talk_key")]
     pub push_to_talk_key: String,
@@ -103,7 +126,7 @@ impl Default for AudioConfig {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppConfig {
     #[serde(default = "default_version")]
     pub version: u32,
@@ -116,6 +139,11 @@ pub struct AppConfig {
 
     #[serde(default)]
     pub audio: AudioConfig,
+
+    /// Keys this version doesn't know (e.g. written by a newer app), kept so `save()`
+    /// writes them back. Known fields are matched first and never land here.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl Default for AppConfig {
@@ -125,6 +153,7 @@ impl Default for AppConfig {
             server: ServerConfig::default(),
             ui: UiPreferences::default(),
             audio: AudioConfig::default(),
+            extra: Map::new(),
         }
     }
 }
@@ -146,6 +175,28 @@ fn default_push_to_talk_key() -> String {
     "AltRight".to_string()
 }
 
+// ============================================
+// FIELD DESERIALIZERS
+// ============================================
+
+/// Parse `base_font_points`, failing on out-of-range values so the error names the field.
+fn deserialize_base_font_points<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let points = f32::deserialize(deserializer)?;
+    if !(MIN_BASE_FONT_POINTS..=MAX_BASE_FONT_POINTS).contains(&points) {
+        let expected = format!(
+            "base_font_points between {MIN_BASE_FONT_POINTS:.1} and {MAX_BASE_FONT_POINTS:.1}"
+        );
+        return Err(de::Error::invalid_value(
+            Unexpected::Float(f64::from(points)),
+            &expected.as_str(),
+        ));
+    }
+    Ok(points)
+}
+
 // ============================================
 // IMPLEMENTATION
 // ============================================
@@ -153,6 +204,7 @@ fn default_push_to_talk_key() -> String {
 impl AppConfig {
     /// Load config from {config_dir}/config.json.
     ///
+    /// Older config versions are migrated (see [`migration`]) before deserialization.
     /// Falls back to defaults on any error (missing file, parse error, validation error).
     ///
     /// # Returns
@@ -180,15 +232,25 @@ impl AppConfig {
             }
         })?;
 
-        // Parse JSON
-        let config: AppConfig = serde_json::from_str(&contents).map_err(|e| {
+        let parse_error = |e: serde_json::Error| {
             warn!("Failed to parse config JSON, using defaults: {}", e);
             ConfigError::ParseError {
                 location: ErrorLocation::from(Location::caller()),
                 path: config_path.clone(),
                 reason: e.to_string(),
             }
-        })?;
+        };
+
+        // Parse JSON, then migrate to the current version
+        let value: Value = serde_json::from_str(&contents).map_err(parse_error)?;
+        let from_version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .map_or(UNVERSIONED_CONFIG_VERSION, |v| {
+                u32::try_from(v).unwrap_or(u32::MAX)
+            });
+        let value = migration::migrate(value, from_version)?;
+        let config: AppConfig = serde_json::from_value(value).map_err(parse_error)?;
 
         // Validate
         config.validate()?;
@@ -199,7 +261,9 @@ impl AppConfig {
 
     /// Save config to {config_dir}/config.json using atomic write.
     ///
-    /// Uses temp file + rename for atomicity (no corruption on crash).
+    /// Uses temp file + rename for atomicity (no corruption on crash). The temp file is
+    /// fsynced before the rename and, on Unix, the directory after it, so once this returns
+    /// `Ok` the new config survives a crash or power loss. See [`write_atomic`].
     ///
     /// # Errors
     ///
@@ -228,24 +292,89 @@ impl AppConfig {
             reason: e.to_string(),
         })?;
 
-        // Write to temp file
-        std::fs::write(&temp_path, json).map_err(|e| ConfigError::WriteError {
-            location: ErrorLocation::from(Location::caller()),
-            path: temp_path.clone(),
-            source: e,
-        })?;
-
-        // Atomic rename (POSIX guarantees atomicity)
-        std::fs::rename(&temp_path, &config_path).map_err(|e| ConfigError::WriteError {
-            location: ErrorLocation::from(Location::caller()),
-            path: config_path.clone(),
-            source: e,
-        })?;
+        write_atomic(&config_path, &temp_path, json.as_bytes())?;
 
         info!("Config saved to {}", config_path.display());
         Ok(())
     }
 
+    /// Get the value at a dotted key path (e.g. `ui.base_font_points`) as JSON.
+    ///
+    /// Paths address the serialized config, so an object path returns the whole subtree.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::UnknownKey`] if the path doesn't exist.
+    #[track_caller]
+    pub fn get_value(&self, key_path: &str) -> Result<Value, ConfigError> {
+        let config = self.to_value()?;
+        key_path_segments(key_path)
+            .and_then(|segments| {
+                segments
+                    .iter()
+                    .try_fold(&config, |value, segment| value.get(segment))
+            })
+            .cloned()
+            .ok_or_else(|| unknown_key(key_path))
+    }
+
+    /// Copy of this config with the value at a dotted key path replaced.
+    ///
+    /// The path must already exist; the result is deserialized and validated as a whole.
+    ///
+    /// # Errors
+    ///
+    /// * [`ConfigError::UnknownKey`] - If the path doesn't exist
+    /// * [`ConfigError::ValidationError`] - If the value has the wrong type or the
+    ///   resulting config is invalid
+    #[track_caller]
+    pub fn with_value(&self, key_path: &str, new_value: Value) -> Result<Self, ConfigError> {
+        let mut config = self.to_value()?;
+        let target = key_path_segments(key_path)
+            .and_then(|segments| {
+                segments
+                    .iter()
+                    .try_fold(&mut config, |value, segment| value.get_mut(segment))
+            })
+            .ok_or_else(|| unknown_key(key_path))?;
+        *target = new_value;
+
+        let updated: Self =
+            serde_json::from_value(config).map_err(|e| ConfigError::ValidationError {
+                location: ErrorLocation::from(Location::caller()),
+                reason: format!("Invalid value for '{key_path}': {e}"),
+            })?;
+        updated.validate()?;
+        Ok(updated)
+    }
+
+    /// Reset a section (`server`, `ui`, or `audio`) to its defaults.
+    ///
+    /// An empty section resets the whole config, including unknown keys kept in `extra`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::UnknownKey`] for any other section name (config unchanged).
+    #[track_caller]
+    pub fn reset_section(&mut self, section: &str) -> Result<(), ConfigError> {
+        match section {
+            "" => *self = Self::default(),
+            "server" => self.server = ServerConfig::default(),
+            "ui" => self.ui = UiPreferences::default(),
+            "audio" => self.audio = AudioConfig::default(),
+            _ => return Err(unknown_key(section)),
+        }
+        Ok(())
+    }
+
+    #[track_caller]
+    fn to_value(&self) -> Result<Value, ConfigError> {
+        serde_json::to_value(self).map_err(|e| ConfigError::SerializeError {
+            location: ErrorLocation::from(Location::caller()),
+            reason: e.to_string(),
+        })
+    }
+
     /// Validate config values.
     ///
     /// # Errors
@@ -263,12 +392,12 @@ impl AppConfig {
             });
         }
 
-        // Font size bounds
-        if self.ui.base_font_points < 8.0 || self.ui.base_font_points > 72.0 {
+        // Font size bounds (also enforced when parsing; this catches values set in code)
+        if !(MIN_BASE_FONT_POINTS..=MAX_BASE_FONT_POINTS).contains(&self.ui.base_font_points) {
             return Err(ConfigError::ValidationError {
                 location: ErrorLocation::from(Location::caller()),
                 reason: format!(
-                    "Invalid font size: {} (must be 8.0-72.0)",
+                    "Invalid font size: {} (must be {MIN_BASE_FONT_POINTS:.1}-{MAX_BASE_FONT_POINTS:.1})",
                     self.ui.base_font_points
                 ),
             });
@@ -294,4 +423,142 @@ impl AppConfig {
 
         Ok(())
     }
+
+    /// [`validate`](Self::validate), plus checks that referenced files exist.
+    ///
+    /// Opt-in because a missing file may be fine at startup (e.g. a Whisper model that's
+    /// downloaded later); use it when the user saves settings so a wrong path is reported
+    /// immediately instead of at transcription time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::ValidationError`] if any value is invalid, or if
+    /// `audio.whisper_model_path` is set but isn't a readable file.
+    pub fn validate_strict(&self) -> Result<(), ConfigError> {
+        self.validate()?;
+
+        if let Some(ref path) = self.audio.whisper_model_path {
+            let metadata = std::fs::metadata(path).map_err(|e| ConfigError::ValidationError {
+                location: ErrorLocation::from(Location::caller()),
+                reason: format!("whisper_model_path '{}' is not accessible: {}", path, e),
+            })?;
+
+            if !metadata.is_file() {
+                return Err(ConfigError::ValidationError {
+                    location: ErrorLocation::from(Location::caller()),
+                    reason: format!("whisper_model_path '{}' is not a file", path),
+                });
+            }
+
+            File::open(path).map_err(|e| ConfigError::ValidationError {
+                location: ErrorLocation::from(Location::caller()),
+                reason: format!("whisper_model_path '{}' is not readable: {}", path, e),
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Split a dotted key path, or `None` if it is empty or has an empty segment.
+fn key_path_segments(key_path: &str) -> Option<Vec<&str>> {
+    let segments: Vec<&str> = key_path.split('.').collect();
+    segments
+        .iter()
+        .all(|segment| !segment.is_empty())
+        .then_some(segments)
+}
+
+#[track_caller]
+fn unknown_key(key_path: &str) -> ConfigError {
+    ConfigError::UnknownKey {
+        location: ErrorLocation::from(Location::caller()),
+        key_path: key_path.to_string(),
+    }
+}
+
+// ============================================
+// DURABLE WRITES
+// ============================================
+
+/// Durably replace `path` with `contents` via `temp_path`.
+///
+/// 1. Write `temp_path` and fsync it, so the data is on disk before it becomes visible
+/// 2. Rename over `path` (atomic on POSIX; readers see the old or new file, never a mix)
+/// 3. On Unix, fsync the parent directory so the rename itself survives a crash
+///
+/// On Windows, `rename` fails if another process has `path` open without sharing delete;
+/// the existing file is then removed and the rename retried. That fallback is not atomic,
+/// but the fsynced temp file is left behind if the retry fails.
+#[track_caller]
+pub(crate) fn write_atomic(
+    path: &Path,
+    temp_path: &Path,
+    contents: &[u8],
+) -> Result<(), ConfigError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    write_atomic_with(path, temp_path, contents, &options)
+}
+
+/// [`write_atomic`] for files holding secrets: `temp_path` is created owner-only (mode
+/// 0600 on Unix) and must not already exist, so each writer needs its own temp path.
+#[track_caller]
+pub(crate) fn write_atomic_private(
+    path: &Path,
+    temp_path: &Path,
+    contents: &[u8],
+) -> Result<(), ConfigError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    write_atomic_with(path, temp_path, contents, &options)
+}
+
+#[track_caller]
+fn write_atomic_with(
+    path: &Path,
+    temp_path: &Path,
+    contents: &[u8],
+    options: &OpenOptions,
+) -> Result<(), ConfigError> {
+    let write_error = |path: &Path, source: std::io::Error| ConfigError::WriteError {
+        location: ErrorLocation::from(Location::caller()),
+        path: path.to_path_buf(),
+        source,
+    };
+
+    // Write and fsync the temp file
+    let mut file = options
+        .open(temp_path)
+        .map_err(|e| write_error(temp_path, e))?;
+    file.write_all(contents)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| write_error(temp_path, e))?;
+    drop(file);
+
+    // Atomic rename
+    if let Err(e) = std::fs::rename(temp_path, path) {
+        #[cfg(windows)]
+        {
+            warn!("Rename over {} failed ({}), replacing", path.display(), e);
+            std::fs::remove_file(path)
+                .and_then(|()| std::fs::rename(temp_path, path))
+                .map_err(|e| write_error(path, e))?;
+        }
+        #[cfg(not(windows))]
+        return Err(write_error(path, e));
+    }
+
+    // Persist the rename itself. Some filesystems don't support fsync on directories;
+    // the file is already in place, so that only costs durability, not correctness.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent()
+        && let Err(e) = File::open(dir).and_then(|d| d.sync_all())
+    {
+        warn!("Failed to fsync directory {}: {}", dir.display(), e);
+    }
+
+    Ok(())
 }
@@ -3,13 +3,28 @@ use crate::error::config::ConfigError;
 use common::ErrorLocation;
 
 use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt;
 use std::panic::Location;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
-const MODELS_FILE_NAME: &str = "models.toml";
+pub(crate) const MODELS_FILE_NAME: &str = "models.toml";
+
+/// Environment variable naming a directory to search for models.toml before the
+/// resource directory.
+pub const MODELS_CONFIG_ENV_VAR: &str = "OPENCODE_MODELS_CONFIG";
+
+/// Directories [`ModelsConfig::load`] searches, highest precedence first.
+pub(crate) fn search_dirs(resource_dir: &Path, override_dir: Option<PathBuf>) -> Vec<PathBuf> {
+    override_dir
+        .into_iter()
+        .chain([resource_dir.join("config"), resource_dir.to_path_buf()])
+        .collect()
+}
 
 // ============================================
 // MODELS CONFIG STRUCTS
@@ -36,6 +51,41 @@ impl CuratedModel {
     }
 }
 
+/// A model named by provider and model ID, as in the config's `provider/model` strings
+/// (e.g. `ModelsSection::default_model`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModelRef {
+    pub provider_id: String,
+    pub model_id: String,
+}
+
+impl FromStr for ModelRef {
+    type Err = ConfigError;
+
+    /// Parse `provider/model`, splitting on the first `/` (model IDs may contain more).
+    #[track_caller]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((provider_id, model_id)) if !provider_id.is_empty() && !model_id.is_empty() => {
+                Ok(Self {
+                    provider_id: provider_id.to_string(),
+                    model_id: model_id.to_string(),
+                })
+            }
+            _ => Err(ConfigError::ValidationError {
+                location: ErrorLocation::from(Location::caller()),
+                reason: format!("Invalid model '{s}': expected 'provider/model'"),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ModelRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.provider_id, self.model_id)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderConfig {
     pub name: String,
@@ -47,8 +97,21 @@ pub struct ProviderConfig {
     pub auth_header: Option<String>,
     #[serde(default)]
     pub auth_param: Option<String>,
+    /// Headers sent with the models request; values may reference `${ENV_VAR}`s.
     #[serde(default)]
     pub extra_headers: HashMap<String, String>,
+    /// Required API key prefix (overrides the built-in rule for well-known providers).
+    #[serde(default)]
+    pub key_prefix: Option<String>,
+    /// Minimum API key length (overrides the built-in rule).
+    #[serde(default)]
+    pub key_min_length: Option<usize>,
+    /// Maximum API key length (overrides the built-in rule).
+    #[serde(default)]
+    pub key_max_length: Option<usize>,
+    /// Placeholder patterns to reject (replaces the built-in list; empty disables the check).
+    #[serde(default)]
+    pub key_placeholder_patterns: Option<Vec<String>>,
     pub response_format: ResponseFormat,
 }
 
@@ -67,6 +130,9 @@ pub struct ModelsSection {
     pub default_model: String,
     #[serde(default)]
     pub curated: Vec<CuratedModel>,
+    /// Reject curated models whose `provider` isn't in `providers` (otherwise only warn).
+    #[serde(default)]
+    pub strict_curated_providers: bool,
 }
 
 impl Default for ModelsSection {
@@ -74,16 +140,46 @@ impl Default for ModelsSection {
         Self {
             default_model: default_model(),
             curated: Vec::new(),
+            strict_curated_providers: false,
         }
     }
 }
 
+/// Agents messages may be sent to (OpenCode's `agent` field).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgentsSection {
+    /// Agent used when a message doesn't name one.
+    #[serde(default = "default_agent")]
+    pub default_agent: String,
+    /// Agent names accepted by `send_message`; empty accepts any name.
+    #[serde(default = "default_known_agents")]
+    pub known: Vec<String>,
+}
+
+impl Default for AgentsSection {
+    fn default() -> Self {
+        Self {
+            default_agent: default_agent(),
+            known: default_known_agents(),
+        }
+    }
+}
+
+impl AgentsSection {
+    /// Is `agent` allowed by this section?
+    pub fn is_known(&self, agent: &str) -> bool {
+        self.known.is_empty() || self.known.iter().any(|known| known == agent)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelsConfig {
     #[serde(default)]
     pub providers: Vec<ProviderConfig>,
     #[serde(default)]
     pub models: ModelsSection,
+    #[serde(default)]
+    pub agents: AgentsSection,
 }
 
 impl Default for ModelsConfig {
@@ -91,6 +187,7 @@ impl Default for ModelsConfig {
         Self {
             providers: Vec::new(),
             models: ModelsSection::default(),
+            agents: AgentsSection::default(),
         }
     }
 }
@@ -99,6 +196,15 @@ fn default_model() -> String {
     "openai/gpt-4".to_string()
 }
 
+fn default_agent() -> String {
+    "build".to_string()
+}
+
+/// No allowlist: OpenCode also runs user-defined agents, so any name is accepted.
+fn default_known_agents() -> Vec<String> {
+    Vec::new()
+}
+
 // ============================================
 // IMPLEMENTATION
 // ============================================
@@ -106,37 +212,54 @@ fn default_model() -> String {
 impl ModelsConfig {
     /// Load models.toml from resource directory.
     ///
-    /// Tries multiple paths for dev vs production:
-    /// 1. {resource_dir}/config/models.toml (production bundle)
-    /// 2. {resource_dir}/models.toml (alternative location)
-    /// 3. Falls back to default (empty providers)
+    /// Tries multiple directories in order:
+    /// 1. `$OPENCODE_MODELS_CONFIG`, if set (see [`MODELS_CONFIG_ENV_VAR`])
+    /// 2. {resource_dir}/config/models.toml (production bundle)
+    /// 3. {resource_dir}/models.toml (alternative location)
+    /// 4. Falls back to default (empty providers)
     ///
     /// # Returns
     ///
     /// Always returns `Ok(ModelsConfig)` - either loaded or default.
     pub fn load(resource_dir: &Path) -> Result<Self, ConfigError> {
-        // Try multiple paths (production vs dev)
-        let paths = [
-            resource_dir.join("config").join(MODELS_FILE_NAME),
-            resource_dir.join(MODELS_FILE_NAME),
-        ];
+        Self::load_with_override(resource_dir, std::env::var_os(MODELS_CONFIG_ENV_VAR))
+    }
 
-        for path in &paths {
+    /// [`load`](Self::load) with the `$OPENCODE_MODELS_CONFIG` value passed in.
+    pub(crate) fn load_with_override(
+        resource_dir: &Path,
+        override_dir: Option<OsString>,
+    ) -> Result<Self, ConfigError> {
+        let override_dir = override_dir.map(PathBuf::from);
+        Self::load_from_dirs(&search_dirs(resource_dir, override_dir))
+    }
+
+    /// Load the first usable models.toml found in `dirs`, searched in order.
+    ///
+    /// A file that is missing, unreadable or invalid is skipped with a warning, so a
+    /// broken override still falls back to the bundled config.
+    ///
+    /// # Returns
+    ///
+    /// Always returns `Ok(ModelsConfig)` - either loaded or default.
+    pub fn load_from_dirs(dirs: &[PathBuf]) -> Result<Self, ConfigError> {
+        for dir in dirs {
+            let path = dir.join(MODELS_FILE_NAME);
             if path.exists() {
-                match Self::load_from_path(path) {
+                match Self::load_from_path(&path) {
                     Ok(config) => {
                         info!("Models config loaded from {}", path.display());
                         return Ok(config);
                     }
                     Err(e) => {
                         warn!("Failed to load models from {}: {}", path.display(), e);
-                        // Try next path
+                        // Try next directory
                     }
                 }
             }
         }
 
-        warn!("No models.toml found in resource dir, using defaults");
+        warn!("No models.toml found in search directories, using defaults");
         Ok(Self::default())
     }
 
@@ -161,7 +284,51 @@ impl ModelsConfig {
         Ok(config)
     }
 
-    /// Validate provider configurations.
+    /// Save models config to {config_dir}/models.toml using atomic write.
+    ///
+    /// Mirrors [`AppConfig::save`](crate::config::AppConfig::save): validates, then
+    /// durably writes a temp file and renames it over the original.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError`] if:
+    /// - Validation fails
+    /// - Directory creation fails
+    /// - Serialization fails
+    /// - Write or rename fails
+    pub fn save(&self, config_dir: &Path) -> Result<(), ConfigError> {
+        // Validate before saving
+        self.validate()?;
+
+        // Ensure directory exists
+        std::fs::create_dir_all(config_dir).map_err(|e| ConfigError::WriteError {
+            location: ErrorLocation::from(Location::caller()),
+            path: config_dir.to_path_buf(),
+            source: e,
+        })?;
+
+        let models_path = config_dir.join(MODELS_FILE_NAME);
+        let temp_path = config_dir.join(format!("{}.tmp", MODELS_FILE_NAME));
+
+        // Serialize to TOML
+        let toml = toml::to_string_pretty(self).map_err(|e| ConfigError::SerializeError {
+            location: ErrorLocation::from(Location::caller()),
+            reason: e.to_string(),
+        })?;
+
+        super::write_atomic(&models_path, &temp_path, toml.as_bytes())?;
+
+        info!("Models config saved to {}", models_path.display());
+        Ok(())
+    }
+
+    /// Validate provider configurations, agents and curated model references.
+    ///
+    /// `agents.default_agent` must be one of `agents.known` (unless that list is empty).
+    ///
+    /// Curated models for a provider not in `providers` are an error if
+    /// `models.strict_curated_providers` is set, and a logged warning otherwise (the user
+    /// may not have configured that provider yet).
     pub fn validate(&self) -> Result<(), ConfigError> {
         for provider in &self.providers {
             if provider.name.is_empty() {
@@ -191,16 +358,97 @@ impl ModelsConfig {
                     });
                 }
             }
+
+            if let (Some(min), Some(max)) = (provider.key_min_length, provider.key_max_length)
+                && min > max
+            {
+                return Err(ConfigError::ValidationError {
+                    location: ErrorLocation::from(Location::caller()),
+                    reason: format!(
+                        "key_min_length ({}) exceeds key_max_length ({}) for provider '{}'",
+                        min, max, provider.name
+                    ),
+                });
+            }
+        }
+
+        if self.agents.known.iter().any(|agent| agent.is_empty()) {
+            return Err(ConfigError::ValidationError {
+                location: ErrorLocation::from(Location::caller()),
+                reason: "Agent names cannot be empty".to_string(),
+            });
+        }
+
+        if !self.agents.is_known(&self.agents.default_agent) {
+            return Err(ConfigError::ValidationError {
+                location: ErrorLocation::from(Location::caller()),
+                reason: format!(
+                    "Default agent '{}' is not in agents.known",
+                    self.agents.default_agent
+                ),
+            });
+        }
+
+        let orphaned: Vec<String> = self
+            .models
+            .curated
+            .iter()
+            .filter(|model| self.get_provider(&model.provider).is_none())
+            .map(|model| format!("{}/{}", model.provider, model.model_id))
+            .collect();
+
+        if !orphaned.is_empty() {
+            let reason = format!(
+                "Curated models reference undefined providers: {}",
+                orphaned.join(", ")
+            );
+            if self.models.strict_curated_providers {
+                return Err(ConfigError::ValidationError {
+                    location: ErrorLocation::from(Location::caller()),
+                    reason,
+                });
+            }
+            warn!("{}", reason);
         }
 
         Ok(())
     }
 
+    /// Parse `models.default_model` into its provider and model IDs.
+    #[track_caller]
+    pub fn default_model_ref(&self) -> Result<ModelRef, ConfigError> {
+        ModelRef::from_str(&self.models.default_model)
+    }
+
     /// Get provider by name.
     pub fn get_provider(&self, name: &str) -> Option<&ProviderConfig> {
         self.providers.iter().find(|p| p.name == name)
     }
 
+    /// Get provider by the env var its API key is read from (`api_key_env`).
+    ///
+    /// If several providers share the env var, the first is returned and a warning logged.
+    pub fn get_provider_by_env(&self, env: &str) -> Option<&ProviderConfig> {
+        let mut matches = self
+            .providers
+            .iter()
+            .filter(|p| !p.api_key_env.is_empty() && p.api_key_env == env);
+        let first = matches.next()?;
+
+        let others: Vec<&str> = matches.map(|p| p.name.as_str()).collect();
+        if !others.is_empty() {
+            warn!(
+                "Env var {} is shared by providers '{}' and '{}'; using '{}'",
+                env,
+                first.name,
+                others.join("', '"),
+                first.name
+            );
+        }
+
+        Some(first)
+    }
+
     /// Add curated model (avoids duplicates).
     pub fn add_curated_model(&mut self, model: CuratedModel) {
         let exists = self
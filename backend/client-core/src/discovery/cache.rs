@@ -0,0 +1,123 @@
+//! Short-lived cache of discovery results.
+//!
+//! A process scan refreshes every process on the machine, and UI polls call [`discover`]
+//! repeatedly, so results are reused for a short TTL (see
+//! [`set_discovery_cache_ttl`](super::set_discovery_cache_ttl)). Spawning or stopping a
+//! server invalidates the cache, since either changes what a scan would find.
+//!
+//! [`discover`]: super::process::discover
+
+use crate::error::discovery::DiscoveryError;
+use crate::proto::IpcServerInfo;
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::trace;
+
+/// Default time discovery results are reused for.
+pub const DEFAULT_DISCOVERY_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// What a cached result was discovered for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum DiscoveryKey {
+    /// Full process scan (no port override).
+    Scan,
+    /// Lookup of the process listening on one port.
+    Port(u16),
+}
+
+#[derive(Debug)]
+struct Entry {
+    servers: Vec<IpcServerInfo>,
+    scanned_at: Instant,
+}
+
+#[derive(Debug)]
+struct State {
+    ttl: Duration,
+    entries: BTreeMap<DiscoveryKey, Entry>,
+    scans: u64,
+}
+
+/// Discovery results by [`DiscoveryKey`], each reused until the TTL runs out.
+#[derive(Debug)]
+pub(crate) struct DiscoveryCache {
+    state: Mutex<State>,
+}
+
+impl DiscoveryCache {
+    pub(crate) const fn new(ttl: Duration) -> Self {
+        Self {
+            state: Mutex::new(State {
+                ttl,
+                entries: BTreeMap::new(),
+                scans: 0,
+            }),
+        }
+    }
+
+    /// Cached servers for `key`, or the result of `scan` (cached on success).
+    ///
+    /// The lock is held while scanning, so concurrent callers wait for one scan instead of
+    /// each starting their own. Errors aren't cached; a zero TTL disables caching.
+    pub(crate) fn get_or_scan(
+        &self,
+        key: DiscoveryKey,
+        scan: impl FnOnce() -> Result<Vec<IpcServerInfo>, DiscoveryError>,
+    ) -> Result<Vec<IpcServerInfo>, DiscoveryError> {
+        let Ok(mut state) = self.state.lock() else {
+            // Poisoned by a panicking scan: skip the cache rather than fail discovery
+            return scan();
+        };
+
+        let ttl = state.ttl;
+        if let Some(entry) = state.entries.get(&key)
+            && entry.scanned_at.elapsed() < ttl
+        {
+            trace!("Using cached discovery result for {key:?}");
+            return Ok(entry.servers.clone());
+        }
+
+        state.scans += 1;
+        trace!("Discovery scan #{} for {key:?}", state.scans);
+        let servers = scan()?;
+        if !ttl.is_zero() {
+            state.entries.insert(
+                key,
+                Entry {
+                    servers: servers.clone(),
+                    scanned_at: Instant::now(),
+                },
+            );
+        }
+        Ok(servers)
+    }
+
+    /// Drop every cached result.
+    pub(crate) fn invalidate(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.entries.clear();
+        }
+    }
+
+    /// Change the TTL; cached results are dropped.
+    pub(crate) fn set_ttl(&self, ttl: Duration) {
+        if let Ok(mut state) = self.state.lock() {
+            state.ttl = ttl;
+            state.entries.clear();
+        }
+    }
+
+    /// Number of scans run through this cache (cache misses).
+    #[cfg(test)]
+    pub(crate) fn scans(&self) -> u64 {
+        self.state.lock().map_or(0, |state| state.scans)
+    }
+}
+
+/// The cache used by [`discover`](super::process::discover) and
+/// [`discover_all`](super::process::discover_all).
+pub(crate) static DISCOVERY_CACHE: DiscoveryCache =
+    DiscoveryCache::new(DEFAULT_DISCOVERY_CACHE_TTL);
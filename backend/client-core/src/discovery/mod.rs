@@ -3,19 +3,62 @@
 //! This module provides functionality for:
 //! - Discovering running OpenCode server processes
 //! - Spawning new server instances when none are found
+//! - Doing both in one step with [`ensure_server`]
 //! - Managing port overrides for development and testing
+//! - Choosing which `opencode` binary is spawned
+//! - Targeting a remote server (dev box, container) instead of a local process
 //!
 //! # Port Override
 //!
 //! By default, discovery scans for running servers on any port. You can override
 //! this behavior to target a specific port using [`set_override_port`].
+//!
+//! # Binary Override
+//!
+//! Spawning runs the `opencode` found on PATH. Use [`set_override_binary`] to run a
+//! specific executable instead (e.g. an install outside PATH, or a stand-in in tests).
+//!
+//! # Remote Server
+//!
+//! [`set_remote_server`] points discovery at a server on another host. Process scanning
+//! is skipped, and the server is reported as not owned so it is never stopped by us.
+//!
+//! # Caching
+//!
+//! Local discovery results are reused for [`DEFAULT_DISCOVERY_CACHE_TTL`] (adjust with
+//! [`set_discovery_cache_ttl`]), so frequent polling doesn't rescan every process.
+//! Spawning or stopping a server through this module drops the cached results.
 
+pub(crate) mod cache;
 pub mod process;
 pub mod spawn;
 
+pub use cache::DEFAULT_DISCOVERY_CACHE_TTL;
+
+use crate::discovery::cache::DISCOVERY_CACHE;
+use crate::error::CoreError;
+use crate::error::discovery::DiscoveryError;
+use crate::proto::IpcServerInfo;
+
+use common::ErrorLocation;
+
+use log::info;
+
+use std::panic::Location;
+use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 static OVERRIDE_PORT: Mutex<Option<u16>> = Mutex::new(None);
+static REMOTE_SERVER: Mutex<Option<String>> = Mutex::new(None);
+static OVERRIDE_BINARY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Current time as Unix epoch milliseconds, as stored in server info timestamps.
+pub fn now_epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
 
 /// Set a port override for server discovery and spawning.
 ///
@@ -32,6 +75,13 @@ pub fn set_override_port(port: u16) {
     }
 }
 
+/// Go back to scanning for servers and spawning on an automatic port.
+pub fn clear_override_port() {
+    if let Ok(mut p) = OVERRIDE_PORT.lock() {
+        *p = None;
+    }
+}
+
 /// Get the current port override, if set.
 ///
 /// Returns `None` if no override is configured, otherwise returns the port number
@@ -44,3 +94,119 @@ pub fn set_override_port(port: u16) {
 pub fn get_override_port() -> Option<u16> {
     OVERRIDE_PORT.lock().ok().and_then(|p| *p)
 }
+
+/// Spawn servers by running `path` instead of the `opencode` found on PATH.
+///
+/// # Arguments
+///
+/// * `path` - The executable to run; it is passed the same `serve` arguments
+pub fn set_override_binary(path: impl Into<PathBuf>) {
+    if let Ok(mut binary) = OVERRIDE_BINARY.lock() {
+        *binary = Some(path.into());
+    }
+}
+
+/// Go back to spawning the `opencode` found on PATH.
+pub fn clear_override_binary() {
+    if let Ok(mut binary) = OVERRIDE_BINARY.lock() {
+        *binary = None;
+    }
+}
+
+/// Get the binary override, if set.
+///
+/// # Returns
+///
+/// * `Some(path)` - If spawning runs a specific executable
+/// * `None` - If spawning runs the `opencode` found on PATH
+pub fn get_override_binary() -> Option<PathBuf> {
+    OVERRIDE_BINARY.lock().ok().and_then(|b| b.clone())
+}
+
+/// Point discovery at a remote OpenCode server.
+///
+/// When set, discovery skips process scanning and reports this server instead
+/// (with `owned = false`, so it is never stopped by us).
+///
+/// # Arguments
+///
+/// * `base_url` - Server URL, e.g. `http://devbox.local:4096` (http/https, no path or query)
+///
+/// # Errors
+///
+/// Returns [`DiscoveryError::Validation`] if the URL is malformed or unsupported.
+#[track_caller]
+pub fn set_remote_server(base_url: &str) -> Result<(), DiscoveryError> {
+    let server_info = process::remote_server_info(base_url)?;
+    if let Ok(mut remote) = REMOTE_SERVER.lock() {
+        *remote = Some(server_info.base_url);
+    }
+    Ok(())
+}
+
+/// Stop targeting a remote server and go back to local discovery.
+pub fn clear_remote_server() {
+    if let Ok(mut remote) = REMOTE_SERVER.lock() {
+        *remote = None;
+    }
+}
+
+/// Get the remote server base URL (normalized), if set.
+///
+/// # Returns
+///
+/// * `Some(base_url)` - If a remote server is configured
+/// * `None` - If discovery targets local processes
+pub fn get_remote_server() -> Option<String> {
+    REMOTE_SERVER.lock().ok().and_then(|r| r.clone())
+}
+
+/// Set how long local discovery results are reused.
+///
+/// `Duration::ZERO` disables caching, so every call scans. Cached results are dropped.
+pub fn set_discovery_cache_ttl(ttl: Duration) {
+    DISCOVERY_CACHE.set_ttl(ttl);
+}
+
+/// Drop cached discovery results, so the next discovery scans again.
+///
+/// Spawning and stopping servers through this module already does this; call it after
+/// starting or stopping a server some other way.
+pub fn invalidate_discovery_cache() {
+    DISCOVERY_CACHE.invalidate();
+}
+
+/// Find a running OpenCode server, spawning one if none is found.
+///
+/// Runs [`process::discover`] and falls back to [`spawn::spawn_and_wait`] when it finds
+/// nothing. With a remote server set, it is health-checked instead and nothing is ever
+/// spawned.
+///
+/// # Returns
+///
+/// * `Ok(ServerInfo)` - The discovered server (`owned = false`, so it is never stopped by
+///   us) or the spawned one (`owned = true`)
+/// * `Err(CoreError::Discovery)` - If process/network queries fail, or the remote server
+///   is unreachable
+/// * `Err(CoreError::Spawn)` - If no server was found and spawning failed
+pub async fn ensure_server() -> Result<IpcServerInfo, CoreError> {
+    if let Some(remote_url) = get_remote_server() {
+        let Some(server) = process::discover_remote(&remote_url).await? else {
+            return Err(DiscoveryError::Unreachable {
+                message: format!("Remote server {remote_url} is not responding"),
+                location: ErrorLocation::from(Location::caller()),
+            }
+            .into());
+        };
+        info!("Using remote server at {}", server.base_url);
+        return Ok(server);
+    }
+
+    if let Some(server) = process::discover()? {
+        info!("Using running server at {}", server.base_url);
+        return Ok(server);
+    }
+
+    info!("No running server found - spawning one");
+    Ok(spawn::spawn_and_wait().await?)
+}
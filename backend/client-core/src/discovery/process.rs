@@ -1,25 +1,44 @@
-use crate::discovery::get_override_port;
+use crate::OPENCODE_BINARY;
+use crate::base_url::{make_base_url, normalize_base_url};
+use crate::discovery::cache::{DISCOVERY_CACHE, DiscoveryCache, DiscoveryKey};
+use crate::discovery::spawn::is_loopback_host;
+use crate::discovery::{get_override_port, get_remote_server, now_epoch_millis};
 use crate::error::discovery::DiscoveryError;
-use crate::proto::IpcServerInfo;
-use crate::{OPENCODE_BINARY, OPENCODE_SERVER_BASE_URL};
+use crate::proto::{IpcServerInfo, IpcServerProbe};
 
 use common::ErrorLocation;
 
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::panic::Location;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use backoff::{ExponentialBackoff, backoff::Backoff};
-use log::{debug, trace};
+use log::{debug, info, trace, warn};
 use netstat2::{
     AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, SocketInfo, TcpState, get_sockets_info,
 };
 use reqwest::Client;
 use sysinfo::{Pid, Process, ProcessesToUpdate, Signal, System};
+use url::Url;
 
 const CHECK_HEALTH_DURATION: Duration = Duration::from_secs(3);
 const HEALTH_CHECK_ENDPOINT: &str = "/doc";
+const HEALTH_CLIENT_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
 const KILL_VERIFY_MAX_ELAPSED: Duration = Duration::from_secs(5);
+const REMOTE_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// PID reported for remote servers (there is no local process to signal).
+pub const REMOTE_SERVER_PID: u32 = 0;
+
+/// Health checks run on every spawn poll and UI refresh, so they share clients (and their
+/// pooled connections) instead of building one per request.
+static LOOPBACK_HEALTH_CLIENT: OnceLock<Client> = OnceLock::new();
+static REMOTE_HEALTH_CLIENT: OnceLock<Client> = OnceLock::new();
+static HEALTH_CLIENTS_BUILT: AtomicUsize = AtomicUsize::new(0);
 
 #[track_caller]
 fn query_tcp_sockets() -> Result<Vec<SocketInfo>, DiscoveryError> {
@@ -36,105 +55,203 @@ fn query_tcp_sockets() -> Result<Vec<SocketInfo>, DiscoveryError> {
 
 #[track_caller]
 fn discover_on_port(port: u16) -> Result<Option<IpcServerInfo>, DiscoveryError> {
-    let sockets = query_tcp_sockets()?;
-
-    for s in sockets {
-        if let ProtocolSocketInfo::Tcp(tcp) = s.protocol_socket_info
-            && tcp.state == TcpState::Listen
-            && tcp.local_port == port
-            && let Some(&pid) = s.associated_pids.first()
-        {
-            trace!("Found process {pid} listening on port {port}");
-
-            let data = with_process(pid, |p| {
-                (p.name().to_string_lossy().to_string(), format_command(p))
-            });
-
-            if let Some((name, command)) = data {
-                let base_url = format!("{OPENCODE_SERVER_BASE_URL}:{port}");
+    let sockets = listening_sockets()?;
+    Ok(server_on_port(port, &sockets, |pid| {
+        with_process(pid, |p| {
+            (p.name().to_string_lossy().to_string(), format_command(p))
+        })
+    }))
+}
 
-                debug!("Discovered server: {name} (PID: {pid})");
+/// The server listening on `port`, whichever process owns the socket.
+///
+/// `describe` returns a PID's process name and command line (None if it has exited).
+/// The server is reported as not owned: finding it on the override port doesn't mean
+/// we spawned it.
+pub(crate) fn server_on_port(
+    port: u16,
+    sockets: &[ListeningSocket],
+    describe: impl Fn(u32) -> Option<(String, String)>,
+) -> Option<IpcServerInfo> {
+    let mut sockets: Vec<&ListeningSocket> = sockets.iter().filter(|s| s.port == port).collect();
+    // Prefer an IPv4 listener when the port is bound for both families
+    sockets.sort_by_key(|s| s.address.is_ipv6());
+
+    for socket in sockets {
+        let Some(&pid) = socket.pids.first() else {
+            continue;
+        };
+        trace!("Found process {pid} listening on {}", socket.address);
 
-                let server_info = IpcServerInfo {
-                    pid,
-                    port: port as u32,
-                    base_url,
-                    name: OPENCODE_BINARY.to_string(),
-                    command: format!("{OPENCODE_BINARY} {command}"),
-                    owned: true,
-                };
+        if let Some((name, command)) = describe(pid) {
+            let base_url = socket.base_url();
 
-                return Ok(Some(server_info));
-            }
+            debug!("Discovered server: {name} (PID: {pid}) at {base_url}");
 
-            trace!("Process {pid} disappeared before we could read its info");
+            return Some(IpcServerInfo {
+                pid,
+                port: port as u32,
+                base_url,
+                name: OPENCODE_BINARY.to_string(),
+                command: format!("{OPENCODE_BINARY} {command}"),
+                owned: false,
+                discovered_at: now_epoch_millis(),
+                last_health_ok: 0,
+            });
         }
+
+        trace!("Process {pid} disappeared before we could read its info");
     }
 
     debug!("No process found listening on port {port}");
-    Ok(None)
+    None
 }
 
-#[track_caller]
-fn find_listening_port(pid: u32) -> Result<Option<u16>, DiscoveryError> {
-    let sockets = query_tcp_sockets()?;
-
-    for s in sockets {
-        if let ProtocolSocketInfo::Tcp(tcp) = s.protocol_socket_info
-            && tcp.state == TcpState::Listen
-            && s.associated_pids.contains(&pid)
-        {
-            return Ok(Some(tcp.local_port));
-        }
+/// A listening TCP socket and the processes that own it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ListeningSocket {
+    /// Local address the socket is bound to (`0.0.0.0`, `::`, `127.0.0.1`, `::1`, ...).
+    pub(crate) address: IpAddr,
+    pub(crate) port: u16,
+    pub(crate) pids: Vec<u32>,
+}
+
+impl ListeningSocket {
+    /// Base URL that reaches this socket from the local machine.
+    ///
+    /// A wildcard bind is reached through loopback of the same family: a server bound to
+    /// `::` alone may not accept IPv4 (Windows binds IPv6-only by default), so it gets
+    /// `[::1]`, not `127.0.0.1`. Other addresses are used as bound.
+    pub(crate) fn base_url(&self) -> String {
+        let host = match self.address.to_canonical() {
+            IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            address => address,
+        };
+        make_base_url(&host.to_string(), self.port)
     }
+}
 
-    Ok(None)
+/// A process whose name and command line look like an OpenCode server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CandidateProcess {
+    pub(crate) pid: u32,
+    pub(crate) command: String,
 }
 
 #[track_caller]
-fn discover_by_process_scan() -> Result<Option<IpcServerInfo>, DiscoveryError> {
+fn listening_sockets() -> Result<Vec<ListeningSocket>, DiscoveryError> {
+    Ok(query_tcp_sockets()?
+        .into_iter()
+        .filter_map(|s| match s.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp) if tcp.state == TcpState::Listen => {
+                Some(ListeningSocket {
+                    address: tcp.local_addr,
+                    port: tcp.local_port,
+                    pids: s.associated_pids,
+                })
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+fn candidate_processes() -> Vec<CandidateProcess> {
     let mut sys = System::new_all();
     sys.refresh_processes(ProcessesToUpdate::All, true);
 
     trace!("Scanning {} processes", sys.processes().len());
 
-    for (pid, p) in sys.processes() {
-        let name = p.name().to_string_lossy().to_string();
-        let command = format_command(p);
-
-        let is_candidate =
-            (name.contains("bun") || name.contains("node") || name.contains("opencode"))
-                && command.contains("opencode")
-                && command.contains("serve")
-                && pid.as_u32() != std::process::id();
-
-        if !is_candidate {
-            continue;
-        }
+    sys.processes()
+        .iter()
+        .filter_map(|(pid, p)| {
+            let name = p.name().to_string_lossy().to_string();
+            let command = format_command(p);
+            let pid = pid.as_u32();
+
+            let is_candidate =
+                looks_like_opencode_server(&name, &command) && pid != std::process::id();
+
+            is_candidate.then(|| {
+                trace!("Found candidate process: {name} (PID: {pid})");
+                CandidateProcess { pid, command }
+            })
+        })
+        .collect()
+}
 
-        trace!("Found candidate process: {name} (PID: {pid})");
+/// Does a process with this name and command line look like `opencode serve`?
+///
+/// OpenCode may run as its own binary or under bun/node, so the name alone isn't enough.
+pub(crate) fn looks_like_opencode_server(name: &str, command: &str) -> bool {
+    (name.contains("bun") || name.contains("node") || name.contains(OPENCODE_BINARY))
+        && command.contains(OPENCODE_BINARY)
+        && command.contains("serve")
+}
 
-        let pid_u32 = pid.as_u32();
-        if let Some(port) = find_listening_port(pid_u32)? {
-            let base_url = format!("{OPENCODE_SERVER_BASE_URL}:{port}");
+/// Pair candidate processes with the ports they listen on.
+///
+/// Returns one server per distinct port, ordered by PID then port. A process listening on
+/// several ports yields several servers; a port reported twice (IPv4 and IPv6) yields one,
+/// reached over IPv4. A port only bound on IPv6 is reached over IPv6.
+pub(crate) fn match_servers(
+    candidates: &[CandidateProcess],
+    sockets: &[ListeningSocket],
+) -> Vec<IpcServerInfo> {
+    let mut candidates: Vec<&CandidateProcess> = candidates.iter().collect();
+    candidates.sort_by_key(|c| c.pid);
+
+    let discovered_at = now_epoch_millis();
+    let mut seen_ports = HashSet::new();
+    let mut servers = Vec::new();
+
+    for candidate in candidates {
+        let mut owned: Vec<&ListeningSocket> = sockets
+            .iter()
+            .filter(|s| s.pids.contains(&candidate.pid))
+            .collect();
+        // IPv4 first within each port, so it's the one kept
+        owned.sort_by_key(|s| (s.port, s.address.is_ipv6()));
+
+        for socket in owned {
+            let port = socket.port;
+            if !seen_ports.insert(port) {
+                continue;
+            }
 
-            debug!("Discovered server: {name} on port {port} (PID: {pid_u32})");
+            debug!("Discovered server on port {port} (PID: {})", candidate.pid);
 
-            let server_info = IpcServerInfo {
-                pid: pid_u32,
+            servers.push(IpcServerInfo {
+                pid: candidate.pid,
                 port: port as u32,
-                base_url,
+                base_url: socket.base_url(),
                 name: OPENCODE_BINARY.to_string(),
-                command: format!("{OPENCODE_BINARY} {command}"),
+                command: format!("{OPENCODE_BINARY} {}", candidate.command),
                 owned: false,
-            };
-
-            return Ok(Some(server_info));
+                discovered_at,
+                last_health_ok: 0,
+            });
         }
     }
 
-    debug!("No OpenCode server found");
-    Ok(None)
+    servers
+}
+
+#[track_caller]
+fn scan_for_servers() -> Result<Vec<IpcServerInfo>, DiscoveryError> {
+    let candidates = candidate_processes();
+
+    // Skip the socket query when there is nothing to match it against
+    let servers = if candidates.is_empty() {
+        Vec::new()
+    } else {
+        match_servers(&candidates, &listening_sockets()?)
+    };
+
+    if servers.is_empty() {
+        debug!("No OpenCode server found");
+    }
+    Ok(servers)
 }
 
 #[track_caller]
@@ -169,8 +286,13 @@ pub(crate) fn format_command(process: &Process) -> String {
 /// 2. Scanning all processes for bun/node/opencode with "opencode" in the command line
 /// 3. Mapping the process to its listening port via netstat
 ///
-/// Note: Currently only discovers servers on localhost (127.0.0.1). This is intentional
-/// for security - we only connect to servers on the local machine.
+/// Scanning only finds servers on this machine (127.0.0.1, or [::1] for IPv6-only
+/// listeners). To use a server on another host, set it explicitly with
+/// [`set_remote_server`](crate::discovery::set_remote_server); it is then returned without
+/// scanning or a health check. Use [`discover_remote`] to report it only if it responds.
+///
+/// Local results are cached briefly; see
+/// [`set_discovery_cache_ttl`](crate::discovery::set_discovery_cache_ttl).
 ///
 /// # Returns
 ///
@@ -179,15 +301,69 @@ pub(crate) fn format_command(process: &Process) -> String {
 /// * `Err(DiscoveryError)` - If process/network queries fail
 #[track_caller]
 pub fn discover() -> Result<Option<IpcServerInfo>, DiscoveryError> {
+    discover_with(&DISCOVERY_CACHE)
+}
+
+/// [`discover`], reusing results from `cache`.
+#[track_caller]
+pub(crate) fn discover_with(
+    cache: &DiscoveryCache,
+) -> Result<Option<IpcServerInfo>, DiscoveryError> {
     debug!("Starting server discovery");
 
+    if let Some(remote_url) = get_remote_server() {
+        debug!("Remote server set to {remote_url} - skipping process scan");
+        return remote_server_info(&remote_url).map(Some);
+    }
+
     if let Some(override_port) = get_override_port() {
         debug!("Port override set to {override_port}");
-        return discover_on_port(override_port);
+        return Ok(cached_on_port(cache, override_port)?.into_iter().next());
     }
 
     debug!("No port override - scanning for OpenCode processes");
-    discover_by_process_scan()
+    Ok(cached_scan(cache)?.into_iter().next())
+}
+
+/// Discover every running OpenCode server.
+///
+/// Like [`discover`], but returns all matches instead of the first: every OpenCode process
+/// is paired with every port it listens on. With a remote server or port override set,
+/// the result holds at most that one server; a remote server is only included if it
+/// passes a health check (see [`discover_remote`]).
+///
+/// # Returns
+///
+/// * `Ok(servers)` - Servers ordered by PID then port (empty if none are running)
+/// * `Err(DiscoveryError)` - If process/network queries fail
+pub async fn discover_all() -> Result<Vec<IpcServerInfo>, DiscoveryError> {
+    debug!("Starting discovery of all servers");
+
+    if let Some(remote_url) = get_remote_server() {
+        debug!("Remote server set to {remote_url} - skipping process scan");
+        return Ok(discover_remote(&remote_url).await?.into_iter().collect());
+    }
+
+    if let Some(override_port) = get_override_port() {
+        debug!("Port override set to {override_port}");
+        return cached_on_port(&DISCOVERY_CACHE, override_port);
+    }
+
+    cached_scan(&DISCOVERY_CACHE)
+}
+
+/// [`discover_on_port`] through the discovery cache.
+#[track_caller]
+fn cached_on_port(cache: &DiscoveryCache, port: u16) -> Result<Vec<IpcServerInfo>, DiscoveryError> {
+    cache.get_or_scan(DiscoveryKey::Port(port), || {
+        discover_on_port(port).map(|server| server.into_iter().collect())
+    })
+}
+
+/// [`scan_for_servers`] through the discovery cache.
+#[track_caller]
+fn cached_scan(cache: &DiscoveryCache) -> Result<Vec<IpcServerInfo>, DiscoveryError> {
+    cache.get_or_scan(DiscoveryKey::Scan, scan_for_servers)
 }
 
 /// Stop a server process by PID.
@@ -202,8 +378,13 @@ pub fn discover() -> Result<Option<IpcServerInfo>, DiscoveryError> {
 /// # Returns
 ///
 /// * `true` - If the process was successfully terminated
-/// * `false` - If the process doesn't exist or couldn't be killed
+/// * `false` - If the process doesn't exist, couldn't be killed, or is a remote server
 pub fn stop_pid(pid: u32) -> bool {
+    if pid == REMOTE_SERVER_PID {
+        warn!("Refusing to stop a remote server (no local process)");
+        return false;
+    }
+
     let killed = with_process(pid, |p| {
         if let Some(sent) = p.kill_with(Signal::Term) {
             debug!("Sent SIGTERM to PID {pid}: success={sent}");
@@ -222,6 +403,8 @@ pub fn stop_pid(pid: u32) -> bool {
     if !killed {
         return false;
     }
+    // Whether or not it exits in time, the next discovery must look again
+    DISCOVERY_CACHE.invalidate();
 
     // Wait with exponential backoff to verify termination
     let mut backoff = ExponentialBackoff {
@@ -248,9 +431,326 @@ pub fn stop_pid(pid: u32) -> bool {
     }
 }
 
+/// Stop a server we spawned, after checking it is still an OpenCode process.
+///
+/// Guards [`stop_pid`] against killing processes we don't own: discovered external servers
+/// and remote servers are refused, as is a PID that has since been reused by another program.
+///
+/// # Returns
+///
+/// * `Ok(true)` - If the process was successfully terminated
+/// * `Ok(false)` - If the process is already gone or couldn't be killed
+///
+/// # Errors
+///
+/// * [`DiscoveryError::NotOwned`] - If `info.owned` is false
+/// * [`DiscoveryError::ProcessMismatch`] - If the process name isn't OpenCode's
+#[track_caller]
+pub fn stop_server_info(info: &IpcServerInfo) -> Result<bool, DiscoveryError> {
+    let pid = info.pid;
+
+    if !info.owned {
+        return Err(DiscoveryError::NotOwned {
+            message: format!("Refusing to stop server PID {pid}: it was not spawned by us"),
+            location: ErrorLocation::from(Location::caller()),
+        });
+    }
+
+    let Some(name) = with_process(pid, |p| p.name().to_string_lossy().to_string()) else {
+        debug!("Process {pid} not found");
+        return Ok(false);
+    };
+
+    if !is_opencode_process_name(&name) {
+        return Err(DiscoveryError::ProcessMismatch {
+            message: format!(
+                "Refusing to stop PID {pid}: process '{name}' is not {OPENCODE_BINARY}"
+            ),
+            location: ErrorLocation::from(Location::caller()),
+        });
+    }
+
+    Ok(stop_pid(pid))
+}
+
+/// Stop the OpenCode server listening on `port`, after confirming it is one.
+///
+/// For cleaning up a server left running by a previous session (e.g. after a crash), which
+/// is no longer tracked as spawned. The process owning the port must look like
+/// `opencode serve` by name and command line; anything else is refused.
+///
+/// # Returns
+///
+/// * `Ok(true)` - If the server was successfully terminated
+/// * `Ok(false)` - If nothing is listening on `port`, or the process couldn't be killed
+///
+/// # Errors
+///
+/// * [`DiscoveryError::NetworkQuery`] - If listening sockets can't be queried
+/// * [`DiscoveryError::ProcessMismatch`] - If the process on `port` isn't an OpenCode server
+#[track_caller]
+pub fn stop_on_port(port: u16) -> Result<bool, DiscoveryError> {
+    let sockets = listening_sockets()?;
+    let owner = opencode_pid_on_port(port, &sockets, |pid| {
+        with_process(pid, |p| {
+            (p.name().to_string_lossy().to_string(), format_command(p))
+        })
+    })?;
+
+    let Some(pid) = owner else {
+        debug!("No process listening on port {port}, nothing to stop");
+        return Ok(false);
+    };
+
+    info!("Stopping OpenCode server on port {port} (PID: {pid})");
+    Ok(stop_pid(pid))
+}
+
+/// PID of the OpenCode server listening on `port`, if anything listens there.
+///
+/// `describe` returns a PID's process name and command line (None if it has exited).
+///
+/// # Errors
+///
+/// * [`DiscoveryError::ProcessMismatch`] - If the owning process isn't an OpenCode server,
+///   or is this process
+#[track_caller]
+pub(crate) fn opencode_pid_on_port(
+    port: u16,
+    sockets: &[ListeningSocket],
+    describe: impl Fn(u32) -> Option<(String, String)>,
+) -> Result<Option<u32>, DiscoveryError> {
+    let owner = sockets
+        .iter()
+        .filter(|s| s.port == port)
+        .flat_map(|s| &s.pids)
+        .find_map(|&pid| describe(pid).map(|process| (pid, process)));
+
+    let Some((pid, (name, command))) = owner else {
+        return Ok(None);
+    };
+
+    if pid == std::process::id() {
+        return Err(DiscoveryError::ProcessMismatch {
+            message: format!("Refusing to stop PID {pid} on port {port}: it is this process"),
+            location: ErrorLocation::from(Location::caller()),
+        });
+    }
+
+    if !looks_like_opencode_server(&name, &command) {
+        return Err(DiscoveryError::ProcessMismatch {
+            message: format!(
+                "Refusing to stop PID {pid} on port {port}: process '{name}' is not an \
+                 {OPENCODE_BINARY} server"
+            ),
+            location: ErrorLocation::from(Location::caller()),
+        });
+    }
+
+    Ok(Some(pid))
+}
+
+/// Does this process name belong to an OpenCode binary (`opencode`, `opencode.exe`, ...)?
+pub(crate) fn is_opencode_process_name(name: &str) -> bool {
+    name.to_lowercase().contains(OPENCODE_BINARY)
+}
+
+/// Check that a server info's port is usable and agrees with its base URL.
+///
+/// # Errors
+///
+/// Returns [`DiscoveryError::Validation`] if the port is 0 or out of range, or if
+/// `base_url` is unparseable or names a different port.
+#[track_caller]
+pub fn validate_server_info(info: &IpcServerInfo) -> Result<(), DiscoveryError> {
+    let invalid = |message: String| DiscoveryError::Validation {
+        message,
+        location: ErrorLocation::from(Location::caller()),
+    };
+
+    if info.port == 0 {
+        return Err(invalid("Port must be non-zero".to_string()));
+    }
+    if info.port > u32::from(u16::MAX) {
+        return Err(invalid(format!("Port {} is out of range", info.port)));
+    }
+
+    let url = Url::parse(&info.base_url)
+        .map_err(|e| invalid(format!("Invalid base URL '{}': {e}", info.base_url)))?;
+    match url.port_or_known_default() {
+        Some(url_port) if u32::from(url_port) == info.port => Ok(()),
+        Some(url_port) => Err(invalid(format!(
+            "Base URL port {url_port} does not match port {}",
+            info.port
+        ))),
+        None => Err(invalid(format!("Base URL '{}' has no port", info.base_url))),
+    }
+}
+
+/// Build the server info for a remote OpenCode server.
+///
+/// The URL is normalized to `scheme://host:port`. Remote servers are never owned and
+/// report [`REMOTE_SERVER_PID`].
+///
+/// # Errors
+///
+/// Returns [`DiscoveryError::Validation`] if the URL can't be parsed, isn't http/https,
+/// has no host or a zero port, or carries credentials, a path, a query, or a fragment.
+#[track_caller]
+pub fn remote_server_info(base_url: &str) -> Result<IpcServerInfo, DiscoveryError> {
+    let invalid = |reason: &str| DiscoveryError::Validation {
+        message: format!("Invalid remote server URL '{base_url}': {reason}"),
+        location: ErrorLocation::from(Location::caller()),
+    };
+
+    let url = Url::parse(base_url.trim()).map_err(|e| invalid(&e.to_string()))?;
+
+    if !REMOTE_SCHEMES.contains(&url.scheme()) {
+        return Err(invalid("scheme must be http or https"));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(invalid("credentials are not supported"));
+    }
+    if url.path() != "/" || url.query().is_some() || url.fragment().is_some() {
+        return Err(invalid("must not have a path, query, or fragment"));
+    }
+    let host = url.host_str().ok_or_else(|| invalid("missing host"))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| invalid("missing port"))?;
+    if port == 0 {
+        return Err(invalid("Port must be non-zero"));
+    }
+
+    Ok(IpcServerInfo {
+        pid: REMOTE_SERVER_PID,
+        port: port as u32,
+        base_url: format!("{}://{host}:{port}", url.scheme()),
+        name: OPENCODE_BINARY.to_string(),
+        command: String::new(),
+        owned: false,
+        discovered_at: now_epoch_millis(),
+        last_health_ok: 0,
+    })
+}
+
+/// Discover a remote OpenCode server by checking its health.
+///
+/// # Returns
+///
+/// * `Ok(Some(ServerInfo))` - If the server responds to a health check (`owned = false`)
+/// * `Ok(None)` - If the server is unreachable or unhealthy
+/// * `Err(DiscoveryError)` - If the URL is invalid
+pub async fn discover_remote(base_url: &str) -> Result<Option<IpcServerInfo>, DiscoveryError> {
+    let mut server_info = remote_server_info(base_url)?;
+
+    if !check_health(&server_info.base_url).await {
+        debug!("Remote server {} is not responding", server_info.base_url);
+        return Ok(None);
+    }
+
+    server_info.last_health_ok = now_epoch_millis();
+    debug!("Discovered remote server at {}", server_info.base_url);
+    Ok(Some(server_info))
+}
+
+/// How [`check_health_with`] probes a server.
+///
+/// The default matches what current OpenCode versions serve: GET `/doc` within 3 seconds,
+/// any 2xx counts as healthy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheckConfig {
+    /// Path appended to the base URL (e.g. "/doc").
+    pub endpoint: String,
+    /// Time allowed for the whole request.
+    pub timeout: Duration,
+    /// Statuses that count as healthy; empty accepts any 2xx.
+    pub accept_statuses: Vec<u16>,
+}
+
+impl HealthCheckConfig {
+    fn accepts(&self, status: u16) -> bool {
+        if self.accept_statuses.is_empty() {
+            (200..300).contains(&status)
+        } else {
+            self.accept_statuses.contains(&status)
+        }
+    }
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: HEALTH_CHECK_ENDPOINT.to_string(),
+            timeout: CHECK_HEALTH_DURATION,
+            accept_statuses: Vec::new(),
+        }
+    }
+}
+
+/// Outcome of a health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The server answered with an accepted status.
+    Healthy,
+    /// The request failed or timed out (server down, wrong port).
+    Unreachable,
+    /// The server answered, but not with an accepted status (e.g. a different OpenCode version).
+    UnexpectedStatus(u16),
+}
+
+impl HealthStatus {
+    /// Whether the server is usable.
+    pub fn is_healthy(self) -> bool {
+        self == HealthStatus::Healthy
+    }
+}
+
+/// What one request to a server's health endpoint observed.
+///
+/// Keeps the raw facts behind a [`HealthStatus`], so callers can tell a server that is
+/// restarting (reachable, answering 503) from one that is gone (unreachable).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerProbe {
+    /// Whether the server answered at all, with any status.
+    pub reachable: bool,
+    /// The status it answered with; `None` when unreachable.
+    pub http_status: Option<u16>,
+    /// Time until the response arrived; `None` when unreachable.
+    pub latency_ms: Option<u64>,
+}
+
+impl ServerProbe {
+    const UNREACHABLE: ServerProbe = ServerProbe {
+        reachable: false,
+        http_status: None,
+        latency_ms: None,
+    };
+
+    /// The health status this probe means under `config`'s accepted statuses.
+    pub fn health(&self, config: &HealthCheckConfig) -> HealthStatus {
+        match self.http_status {
+            Some(status) if config.accepts(status) => HealthStatus::Healthy,
+            Some(status) => HealthStatus::UnexpectedStatus(status),
+            None => HealthStatus::Unreachable,
+        }
+    }
+}
+
+impl From<ServerProbe> for IpcServerProbe {
+    fn from(probe: ServerProbe) -> Self {
+        IpcServerProbe {
+            reachable: probe.reachable,
+            http_status: probe.http_status.map(u32::from),
+            latency_ms: probe.latency_ms,
+        }
+    }
+}
+
 /// Check if the server is healthy and responding.
 ///
-/// Performs a lightweight GET request to {base_url}/doc with a 3-second timeout.
+/// Performs a lightweight GET request to {base_url}/doc with a 3-second timeout, over a
+/// client shared by all health checks.
 ///
 /// # Arguments
 ///
@@ -261,24 +761,104 @@ pub fn stop_pid(pid: u32) -> bool {
 /// * `true` - If server responds with HTTP 2xx
 /// * `false` - If request fails or times out
 pub async fn check_health(base_url: &str) -> bool {
-    let url = format!("{base_url}{HEALTH_CHECK_ENDPOINT}");
-    let client = Client::new();
+    check_health_with(base_url, &HealthCheckConfig::default()).await
+}
 
-    match client.get(&url).timeout(CHECK_HEALTH_DURATION).send().await {
-        Ok(resp) if resp.status().is_success() => {
-            debug!("Health check succeeded for {base_url}");
-            true
+/// Check if the server is healthy using a custom probe.
+///
+/// # Returns
+///
+/// * `true` - If server responds with an accepted status
+/// * `false` - If request fails, times out, or returns any other status
+pub async fn check_health_with(base_url: &str, config: &HealthCheckConfig) -> bool {
+    check_health_status(base_url, config).await.is_healthy()
+}
+
+/// Check the server's health and report why it is unhealthy.
+///
+/// Unlike [`check_health_with`], this distinguishes a server that is down
+/// ([`HealthStatus::Unreachable`]) from one that answers unexpectedly
+/// ([`HealthStatus::UnexpectedStatus`]).
+pub async fn check_health_status(base_url: &str, config: &HealthCheckConfig) -> HealthStatus {
+    let status = probe_with(base_url, config).await.health(config);
+    match status {
+        HealthStatus::Healthy => debug!("Health check succeeded for {base_url}"),
+        HealthStatus::UnexpectedStatus(code) => {
+            debug!("Health check failed for {base_url}: status={code}")
         }
-        Ok(resp) => {
-            debug!(
-                "Health check failed for {base_url}: status={}",
-                resp.status()
-            );
-            false
+        HealthStatus::Unreachable => debug!("Health check failed for {base_url}: unreachable"),
+    }
+    status
+}
+
+/// Probe the server's health endpoint with the default [`HealthCheckConfig`].
+///
+/// Any answer counts as reachable, whatever its status; only a failed or timed-out
+/// request (or an invalid URL) is unreachable.
+pub async fn probe(base_url: &str) -> ServerProbe {
+    probe_with(base_url, &HealthCheckConfig::default()).await
+}
+
+/// Probe the server's health endpoint as configured by `config`.
+pub async fn probe_with(base_url: &str, config: &HealthCheckConfig) -> ServerProbe {
+    let url = match normalize_base_url(base_url)
+        .and_then(|base| base.join(config.endpoint.trim_start_matches('/')))
+    {
+        Ok(url) => url,
+        Err(e) => {
+            debug!("Probe of {base_url} skipped: invalid URL: {e}");
+            return ServerProbe::UNREACHABLE;
         }
+    };
+    let client = health_client(&url);
+
+    let started = Instant::now();
+    match client.get(url).timeout(config.timeout).send().await {
+        Ok(resp) => ServerProbe {
+            reachable: true,
+            http_status: Some(resp.status().as_u16()),
+            latency_ms: Some(u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)),
+        },
         Err(e) => {
-            debug!("Health check failed for {base_url}: {e}");
-            false
+            debug!("Probe of {base_url} failed: {e}");
+            ServerProbe::UNREACHABLE
         }
     }
 }
+
+/// The shared client for health checks against `url`.
+///
+/// Loopback servers get a client that bypasses any configured proxy (a proxy can't reach
+/// our localhost); remote servers use the default client, which honors proxy settings.
+fn health_client(url: &Url) -> &'static Client {
+    if url.host_str().is_some_and(is_loopback_host) {
+        LOOPBACK_HEALTH_CLIENT.get_or_init(|| build_health_client(true))
+    } else {
+        REMOTE_HEALTH_CLIENT.get_or_init(|| build_health_client(false))
+    }
+}
+
+fn build_health_client(loopback: bool) -> Client {
+    let built = HEALTH_CLIENTS_BUILT.fetch_add(1, Ordering::Relaxed) + 1;
+    trace!("Building health check client #{built} (loopback: {loopback})");
+
+    let builder = Client::builder()
+        .pool_idle_timeout(HEALTH_CLIENT_IDLE_TIMEOUT)
+        .tcp_nodelay(true);
+    let builder = if loopback {
+        builder.no_proxy()
+    } else {
+        builder
+    };
+
+    builder.build().unwrap_or_else(|e| {
+        warn!("Failed to build health check client, using defaults: {e}");
+        Client::new()
+    })
+}
+
+/// Number of health check clients built so far (at most one per kind).
+#[cfg(test)]
+pub(crate) fn health_clients_built() -> usize {
+    HEALTH_CLIENTS_BUILT.load(Ordering::Relaxed)
+}
@@ -1,17 +1,23 @@
-use crate::discovery::{get_override_port, process::check_health};
+use crate::base_url::make_base_url;
+use crate::discovery::process::{check_health, validate_server_info};
+use crate::discovery::{
+    get_override_binary, get_override_port, invalidate_discovery_cache, now_epoch_millis,
+};
 use crate::error::spawn::SpawnError;
 use crate::proto::IpcServerInfo;
-use crate::{OPENCODE_BINARY, OPENCODE_SERVER_BASE_URL, OPENCODE_SERVER_HOSTNAME};
+use crate::{OPENCODE_BINARY, OPENCODE_SERVER_HOSTNAME};
 
 use common::ErrorLocation;
 
+use std::collections::BTreeMap;
 use std::env::current_exe;
 use std::io::Error as IoError;
 use std::io::ErrorKind;
-use std::mem::forget;
+use std::net::IpAddr;
 use std::panic::Location;
+use std::path::PathBuf;
 use std::process::Stdio;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
 use backoff::{ExponentialBackoff, backoff::Backoff};
@@ -28,82 +34,271 @@ const SERVE_COMMAND: &str = "serve";
 const PORT_FLAG: &str = "--port";
 const HOSTNAME_FLAG: &str = "--hostname";
 const AUTO_SELECT_PORT: &str = "0";
-const SPAWN_MAX_OUTPUT_LINES: usize = 100;
-const HEALTH_CHECK_MAX_ELAPSED: Duration = Duration::from_secs(20);
+const DEFAULT_MAX_OUTPUT_LINES: usize = 100;
+const DEFAULT_HEALTH_TIMEOUT: Duration = Duration::from_secs(20);
 const SERVER_URL_PATTERN: &str = r"http://(?P<host>[^\s:]+):(?P<port>\d+)";
 const URL_CAPTURE_HOST: &str = "host";
 const URL_CAPTURE_PORT: &str = "port";
 
 static URL_REGEX: OnceLock<Regex> = OnceLock::new();
 
+/// Servers this app spawned, stopped on shutdown by [`stop_owned_servers`].
+static OWNED_CHILDREN: OwnedChildren = OwnedChildren::new();
+
+/// Servers we spawned, by PID, kept so they can be stopped through their handle.
+#[derive(Debug, Default)]
+pub(crate) struct OwnedChildren(Mutex<BTreeMap<u32, TokioChild>>);
+
+impl OwnedChildren {
+    pub(crate) const fn new() -> Self {
+        Self(Mutex::new(BTreeMap::new()))
+    }
+
+    /// Track a spawned server's process handle.
+    pub(crate) fn track(&self, pid: u32, child: TokioChild) {
+        if let Ok(mut children) = self.0.lock()
+            && children.insert(pid, child).is_some()
+        {
+            warn!("Replaced tracked handle for spawned server PID {pid}");
+        }
+    }
+
+    /// PIDs whose handles are still tracked.
+    pub(crate) fn pids(&self) -> Vec<u32> {
+        self.0
+            .lock()
+            .map(|children| children.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Kill and reap the tracked server `pid`; `false` if it isn't tracked or the kill failed.
+    pub(crate) async fn stop(&self, pid: u32) -> bool {
+        let child = self
+            .0
+            .lock()
+            .ok()
+            .and_then(|mut children| children.remove(&pid));
+
+        match child {
+            Some(child) => kill_owned_child(pid, child).await,
+            None => false,
+        }
+    }
+
+    /// Kill and reap every tracked server, returning how many were stopped.
+    pub(crate) async fn stop_all(&self) -> usize {
+        let children = self
+            .0
+            .lock()
+            .map(|mut children| std::mem::take(&mut *children))
+            .unwrap_or_default();
+
+        if !children.is_empty() {
+            info!("Stopping {} spawned server(s)", children.len());
+        }
+
+        let mut stopped = 0;
+        for (pid, child) in children {
+            if kill_owned_child(pid, child).await {
+                stopped += 1;
+            }
+        }
+        stopped
+    }
+}
+
 pub(crate) fn get_url_regex() -> &'static Regex {
     URL_REGEX.get_or_init(|| Regex::new(SERVER_URL_PATTERN).expect("valid regex pattern"))
 }
 
-pub(crate) fn build_spawn_command(port: &str) -> TokioCommand {
-    let mut cmd = TokioCommand::new(OPENCODE_BINARY);
+pub(crate) fn build_spawn_command(port: &str, hostname: &str) -> TokioCommand {
+    let program = get_override_binary().unwrap_or_else(|| PathBuf::from(OPENCODE_BINARY));
+    let mut cmd = TokioCommand::new(program);
     cmd.arg(SERVE_COMMAND)
         .arg(PORT_FLAG)
         .arg(port)
         .arg(HOSTNAME_FLAG)
-        .arg(OPENCODE_SERVER_HOSTNAME)
+        .arg(hostname)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
     cmd
 }
 
+/// Is `hostname` only reachable from this machine (`localhost` or a loopback IP)?
+pub(crate) fn is_loopback_host(hostname: &str) -> bool {
+    hostname.eq_ignore_ascii_case("localhost")
+        || hostname
+            .trim_matches(['[', ']'])
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// How [`spawn_and_wait_with`] starts a server and how long it waits for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnOptions {
+    /// Port to listen on (`None` uses the port override, or lets the server pick).
+    pub port: Option<u16>,
+    /// Address to bind. Defaults to loopback; anything else exposes the server (and the
+    /// keys synced to it) to the network.
+    pub hostname: String,
+    /// Stdout lines to scan for the listening URL before giving up.
+    pub max_output_lines: usize,
+    /// Time allowed for the server to pass a health check.
+    pub health_timeout: Duration,
+}
+
+impl Default for SpawnOptions {
+    fn default() -> Self {
+        Self {
+            port: None,
+            hostname: OPENCODE_SERVER_HOSTNAME.to_string(),
+            max_output_lines: DEFAULT_MAX_OUTPUT_LINES,
+            health_timeout: DEFAULT_HEALTH_TIMEOUT,
+        }
+    }
+}
+
 /// Spawn an OpenCode server process and wait for it to become healthy.
 ///
-/// Attempts to spawn `opencode serve` with the specified port (or auto-select if port override is not set).
-/// Parses the server's stdout to find the listening URL, then polls the health endpoint until ready.
+/// Uses the default [`SpawnOptions`]; see [`spawn_and_wait_with`].
 ///
 /// # Returns
 ///
 /// * `Ok(ServerInfo)` - Server spawned and is healthy
 /// * `Err(SpawnError)` - Failed to spawn, parse output, or server didn't become healthy
 pub async fn spawn_and_wait() -> Result<IpcServerInfo, SpawnError> {
-    let port_arg = get_override_port()
+    spawn_and_wait_with(&SpawnOptions::default()).await
+}
+
+/// Spawn an OpenCode server process with `options` and wait for it to become healthy.
+///
+/// Attempts to spawn `opencode serve` on `options.port` (falling back to the port override,
+/// then to an auto-selected port). Scans up to `options.max_output_lines` lines of stdout
+/// for the listening URL, then polls the health endpoint for up to `options.health_timeout`.
+///
+/// # Returns
+///
+/// * `Ok(ServerInfo)` - Server spawned and is healthy
+/// * `Err(SpawnError)` - Failed to spawn, parse output, or server didn't become healthy
+pub async fn spawn_and_wait_with(options: &SpawnOptions) -> Result<IpcServerInfo, SpawnError> {
+    let port_arg = options
+        .port
+        .or_else(get_override_port)
         .map(|p| p.to_string())
         .unwrap_or_else(|| AUTO_SELECT_PORT.to_string());
 
-    info!("Spawning OpenCode server on port {port_arg}");
-
-    let child = spawn_server_process(&port_arg).await?;
-    let (mut child, base_url, port) = parse_server_url(child).await?;
-
-    if let Err(e) = wait_for_health(&base_url).await {
+    let hostname = options.hostname.as_str();
+    if !is_loopback_host(hostname) {
         warn!(
-            "Health check failed, killing spawned server (PID: {:?})",
-            child.id()
+            "Binding OpenCode server to non-loopback address '{hostname}': \
+             it will be reachable from other machines on the network"
         );
-        let _ = child.kill().await;
-        return Err(e);
     }
 
-    let pid = child.id().unwrap_or_default();
-
-    info!("OpenCode server ready at {base_url} (PID: {pid})");
+    info!("Spawning OpenCode server on {hostname}, port {port_arg}");
 
-    // Detach the child process - it will continue running as a daemon
-    // The OS will clean it up when it exits
-    forget(child);
+    let child = spawn_server_process(&port_arg, hostname).await?;
+    let (mut child, base_url, port) =
+        parse_server_url(child, hostname, options.max_output_lines).await?;
 
-    let server_info = IpcServerInfo {
-        pid,
+    let mut server_info = IpcServerInfo {
+        pid: child.id().unwrap_or_default(),
         port: port as u32,
         base_url,
         name: OPENCODE_BINARY.to_string(),
         command: format!("{OPENCODE_BINARY} {SERVE_COMMAND}"),
         owned: true,
+        discovered_at: now_epoch_millis(),
+        last_health_ok: 0,
     };
 
+    if let Err(e) = validate_server_info(&server_info) {
+        warn!("Spawned server reported an unusable address, killing it: {e}");
+        let _ = child.kill().await;
+        return Err(SpawnError::Validation {
+            message: format!("Spawned server info is invalid: {e}"),
+            location: ErrorLocation::from(Location::caller()),
+        });
+    }
+
+    if let Err(e) = wait_for_health(&server_info.base_url, options.health_timeout).await {
+        warn!(
+            "Health check failed, killing spawned server (PID: {:?})",
+            child.id()
+        );
+        let _ = child.kill().await;
+        return Err(e);
+    }
+    server_info.last_health_ok = now_epoch_millis();
+
+    info!(
+        "OpenCode server ready at {} (PID: {})",
+        server_info.base_url, server_info.pid
+    );
+
+    // Keep the handle so the server can be stopped (and reaped) on shutdown
+    OWNED_CHILDREN.track(server_info.pid, child);
+    invalidate_discovery_cache();
+
     Ok(server_info)
 }
 
-async fn spawn_server_process(port: &str) -> Result<TokioChild, SpawnError> {
+/// PIDs of spawned servers whose handles are still tracked.
+pub fn owned_server_pids() -> Vec<u32> {
+    OWNED_CHILDREN.pids()
+}
+
+/// Stop a server we spawned through its process handle.
+///
+/// # Returns
+///
+/// * `true` - If the server was tracked and has been killed and reaped
+/// * `false` - If `pid` isn't a tracked spawned server, or killing it failed
+pub async fn stop_owned_server(pid: u32) -> bool {
+    OWNED_CHILDREN.stop(pid).await
+}
+
+/// Stop every server we spawned that is still tracked.
+///
+/// Call on application shutdown so spawned servers aren't left running.
+/// Returns the number of servers stopped.
+pub async fn stop_owned_servers() -> usize {
+    OWNED_CHILDREN.stop_all().await
+}
+
+async fn kill_owned_child(pid: u32, mut child: TokioChild) -> bool {
+    match child.kill().await {
+        Ok(()) => {
+            info!("Stopped spawned server (PID: {pid})");
+            invalidate_discovery_cache();
+            true
+        }
+        Err(e) => {
+            warn!("Failed to stop spawned server (PID: {pid}): {e}");
+            false
+        }
+    }
+}
+
+async fn spawn_server_process(port: &str, hostname: &str) -> Result<TokioChild, SpawnError> {
+    if let Some(binary) = get_override_binary() {
+        debug!("Attempting to spawn {}", binary.display());
+        return match build_spawn_command(port, hostname).spawn() {
+            Ok(child) => {
+                info!("Spawned {} (PID: {:?})", binary.display(), child.id());
+                Ok(child)
+            }
+            Err(err) => Err(SpawnError::Spawn {
+                message: format!("Failed to spawn {}: {err}", binary.display()),
+                location: ErrorLocation::from(Location::caller()),
+                source: Box::new(err),
+            }),
+        };
+    }
+
     debug!("Attempting to spawn {OPENCODE_BINARY} from PATH");
 
-    match build_spawn_command(port).spawn() {
+    match build_spawn_command(port, hostname).spawn() {
         Ok(child) => {
             info!(
                 "Spawned {OPENCODE_BINARY} from PATH (PID: {:?})",
@@ -113,7 +308,7 @@ async fn spawn_server_process(port: &str) -> Result<TokioChild, SpawnError> {
         }
         Err(err) if err.kind() == ErrorKind::NotFound => {
             debug!("{OPENCODE_BINARY} not in PATH, trying local binary");
-            spawn_local_binary(port)
+            spawn_local_binary(port, hostname)
         }
         Err(err) => Err(SpawnError::Spawn {
             message: format!("Failed to spawn {OPENCODE_BINARY}: {err}"),
@@ -123,7 +318,7 @@ async fn spawn_server_process(port: &str) -> Result<TokioChild, SpawnError> {
     }
 }
 
-fn spawn_local_binary(port: &str) -> Result<TokioChild, SpawnError> {
+fn spawn_local_binary(port: &str, hostname: &str) -> Result<TokioChild, SpawnError> {
     let exe = current_exe().map_err(|e| SpawnError::Spawn {
         message: format!("Failed to get current executable path: {e}"),
         location: ErrorLocation::from(Location::caller()),
@@ -139,7 +334,7 @@ fn spawn_local_binary(port: &str) -> Result<TokioChild, SpawnError> {
     let local_path = dir.join(OPENCODE_BINARY);
     debug!("Attempting to spawn from {}", local_path.display());
 
-    build_spawn_command(port)
+    build_spawn_command(port, hostname)
         .current_dir(dir)
         .spawn()
         .map_err(|e| SpawnError::Spawn {
@@ -152,7 +347,11 @@ fn spawn_local_binary(port: &str) -> Result<TokioChild, SpawnError> {
         })
 }
 
-async fn parse_server_url(mut child: TokioChild) -> Result<(TokioChild, String, u16), SpawnError> {
+pub(crate) async fn parse_server_url(
+    mut child: TokioChild,
+    hostname: &str,
+    max_output_lines: usize,
+) -> Result<(TokioChild, String, u16), SpawnError> {
     let stdout = child.stdout.take().ok_or_else(|| SpawnError::Parse {
         message: "Child process has no stdout".to_string(),
         location: ErrorLocation::from(Location::caller()),
@@ -173,7 +372,7 @@ async fn parse_server_url(mut child: TokioChild) -> Result<(TokioChild, String,
     let mut lines = BufReader::new(stdout).lines();
     let re = get_url_regex();
 
-    for _ in 0..SPAWN_MAX_OUTPUT_LINES {
+    for _ in 0..max_output_lines {
         match lines.next_line().await {
             Ok(Some(line)) => {
                 trace!("Server output: {line}");
@@ -201,13 +400,13 @@ async fn parse_server_url(mut child: TokioChild) -> Result<(TokioChild, String,
 
                     match port_str.parse::<u16>() {
                         Ok(port) => {
-                            if host != OPENCODE_SERVER_HOSTNAME {
+                            if host != hostname {
                                 warn!(
-                                    "Server reported unexpected hostname: {host}, expected {OPENCODE_SERVER_HOSTNAME}"
+                                    "Server reported unexpected hostname: {host}, expected {hostname}"
                                 );
                             }
 
-                            let base_url = format!("{OPENCODE_SERVER_BASE_URL}:{port}");
+                            let base_url = make_base_url(hostname, port);
                             info!("Parsed server URL: {base_url}");
                             return Ok((child, base_url, port));
                         }
@@ -231,14 +430,14 @@ async fn parse_server_url(mut child: TokioChild) -> Result<(TokioChild, String,
     }
 
     Err(SpawnError::Parse {
-        message: format!("No server URL found in first {SPAWN_MAX_OUTPUT_LINES} lines of output"),
+        message: format!("No server URL found in first {max_output_lines} lines of output"),
         location: ErrorLocation::from(Location::caller()),
     })
 }
 
-async fn wait_for_health(base_url: &str) -> Result<(), SpawnError> {
+async fn wait_for_health(base_url: &str, timeout: Duration) -> Result<(), SpawnError> {
     let mut backoff = ExponentialBackoff {
-        max_elapsed_time: Some(HEALTH_CHECK_MAX_ELAPSED),
+        max_elapsed_time: Some(timeout),
         ..Default::default()
     };
 
@@ -258,7 +457,7 @@ async fn wait_for_health(base_url: &str) -> Result<(), SpawnError> {
             None => {
                 return Err(SpawnError::Timeout {
                     message: format!(
-                        "Server at {base_url} did not become healthy within {HEALTH_CHECK_MAX_ELAPSED:?}"
+                        "Server at {base_url} did not become healthy within {timeout:?}"
                     ),
                     location: ErrorLocation::from(Location::caller()),
                 });
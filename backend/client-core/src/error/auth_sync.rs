@@ -6,8 +6,11 @@
 //! - All errors include ErrorLocation for debugging
 //! - `#[track_caller]` for automatic location capture
 
-use common::{ErrorLocation, HttpStatusCode};
+use crate::error::opencode_client::OpencodeClientError;
+
+use common::{ErrorLocation, HttpStatusCode, RedactedDisplay};
 use std::panic::Location;
+use std::time::Duration;
 use thiserror::Error as ThisError;
 
 /// Errors that can occur during auth sync operations.
@@ -19,7 +22,10 @@ pub enum AuthSyncError {
         location: ErrorLocation,
     },
 
-    #[error("Provider sync failed for '{provider}': HTTP {status_code} - {message} {location}")]
+    #[error(
+        "Provider sync failed for '{provider}': HTTP {status_code} {} - {message} {location}",
+        .status_code.reason_phrase()
+    )]
     ProviderSync {
         provider: String,
         message: String,
@@ -42,6 +48,13 @@ pub enum AuthSyncError {
     #[error("No OpenCode server connected {location}")]
     NoServer { location: ErrorLocation },
 
+    #[error("Request failed for '{provider}': {message} {location}")]
+    Request {
+        provider: String,
+        message: String,
+        location: ErrorLocation,
+    },
+
     #[error("OAuth check failed for '{provider}': {message} {location}")]
     OAuthCheck {
         provider: String,
@@ -62,9 +75,22 @@ pub enum AuthSyncError {
         location: ErrorLocation,
     },
 
-    #[error("Operation timeout after {timeout_secs}s {location}")]
+    #[error("auth.json write failed: {message} {location}")]
+    AuthFileWrite {
+        message: String,
+        location: ErrorLocation,
+    },
+
+    #[error("Keychain error for '{provider}': {message} {location}")]
+    Keychain {
+        provider: String,
+        message: String,
+        location: ErrorLocation,
+    },
+
+    #[error("Operation timeout after {}ms {location}", .timeout.as_millis())]
     GlobalTimeout {
-        timeout_secs: u64,
+        timeout: Duration,
         location: ErrorLocation,
     },
 }
@@ -73,21 +99,10 @@ pub enum AuthSyncError {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyValidationFailure {
     Empty,
-    TooShort {
-        min: usize,
-        actual: usize,
-    },
-    TooLong {
-        max: usize,
-        actual: usize,
-    },
-    InvalidPrefix {
-        expected: &'static str,
-        actual: String,
-    },
-    PlaceholderDetected {
-        pattern: &'static str,
-    },
+    TooShort { min: usize, actual: usize },
+    TooLong { max: usize, actual: usize },
+    InvalidPrefix { expected: String, actual: String },
+    PlaceholderDetected { pattern: String },
     InvalidCharacters,
 }
 
@@ -136,9 +151,9 @@ impl AuthSyncError {
     }
 
     #[track_caller]
-    pub fn global_timeout(timeout_secs: u64) -> Self {
+    pub fn global_timeout(timeout: Duration) -> Self {
         AuthSyncError::GlobalTimeout {
-            timeout_secs,
+            timeout,
             location: ErrorLocation::from(Location::caller()),
         }
     }
@@ -160,6 +175,23 @@ impl AuthSyncError {
         }
     }
 
+    #[track_caller]
+    pub fn auth_file_write(message: impl Into<String>) -> Self {
+        AuthSyncError::AuthFileWrite {
+            message: message.into(),
+            location: ErrorLocation::from(Location::caller()),
+        }
+    }
+
+    #[track_caller]
+    pub fn keychain(provider: impl Into<String>, message: impl Into<String>) -> Self {
+        AuthSyncError::Keychain {
+            provider: provider.into(),
+            message: message.into(),
+            location: ErrorLocation::from(Location::caller()),
+        }
+    }
+
     /// Create from reqwest error with proper categorization.
     #[track_caller]
     pub fn from_reqwest(provider: impl Into<String>, error: &reqwest::Error) -> Self {
@@ -214,6 +246,74 @@ impl AuthSyncError {
         }
     }
 
+    /// Create from an `OpencodeClient` error raised while syncing a provider's key.
+    ///
+    /// Server rejections keep their HTTP status so `is_retryable()` can classify them;
+    /// transport failures keep their timeout/connection category.
+    #[track_caller]
+    pub fn from_client_error(provider: impl Into<String>, error: &OpencodeClientError) -> Self {
+        let provider = provider.into();
+
+        match error {
+            OpencodeClientError::Server {
+                message, status, ..
+            } => match status {
+                Some(status_code) => AuthSyncError::ProviderSync {
+                    provider,
+                    message: message.clone(),
+                    status_code: HttpStatusCode(*status_code),
+                    location: ErrorLocation::from(Location::caller()),
+                },
+                None => AuthSyncError::Network {
+                    provider,
+                    message: message.clone(),
+                    is_timeout: false,
+                    is_connection: false,
+                    location: ErrorLocation::from(Location::caller()),
+                },
+            },
+            OpencodeClientError::Timeout { message, .. } => AuthSyncError::Network {
+                provider,
+                message: message.clone(),
+                is_timeout: true,
+                is_connection: false,
+                location: ErrorLocation::from(Location::caller()),
+            },
+            OpencodeClientError::Network {
+                message,
+                is_connection,
+                ..
+            } => AuthSyncError::Network {
+                provider,
+                message: message.clone(),
+                is_timeout: false,
+                is_connection: *is_connection,
+                location: ErrorLocation::from(Location::caller()),
+            },
+            OpencodeClientError::NotFound { .. } => AuthSyncError::ProviderSync {
+                provider,
+                message: error.to_string(),
+                status_code: HttpStatusCode(404),
+                location: ErrorLocation::from(Location::caller()),
+            },
+            OpencodeClientError::Json { message, .. }
+            | OpencodeClientError::UrlParse { message, .. } => AuthSyncError::Network {
+                provider,
+                message: message.clone(),
+                is_timeout: false,
+                is_connection: false,
+                location: ErrorLocation::from(Location::caller()),
+            },
+            OpencodeClientError::InvalidRequest { message, .. }
+            | OpencodeClientError::UnknownAgent { message, .. }
+            | OpencodeClientError::Assistant { message, .. } => AuthSyncError::Request {
+                provider,
+                message: message.clone(),
+                location: ErrorLocation::from(Location::caller()),
+            },
+        }
+    }
+
     /// Check if this error is retryable based on error category, NOT string content.
     pub fn is_retryable(&self) -> bool {
         match self {
@@ -230,10 +330,13 @@ impl AuthSyncError {
             // These are never retryable
             AuthSyncError::Cancelled { .. } => false,
             AuthSyncError::NoServer { .. } => false,
+            AuthSyncError::Request { .. } => false,
             AuthSyncError::EnvLoad { .. } => false,
             AuthSyncError::OAuthCheck { .. } => false,
             AuthSyncError::AuthPathDetection { .. } => false,
             AuthSyncError::KeyValidation { .. } => false,
+            AuthSyncError::AuthFileWrite { .. } => false,
+            AuthSyncError::Keychain { .. } => false,
             AuthSyncError::GlobalTimeout { .. } => false,
         }
     }
@@ -259,9 +362,12 @@ impl AuthSyncError {
             AuthSyncError::Network { .. } => "network",
             AuthSyncError::Cancelled { .. } => "cancelled",
             AuthSyncError::NoServer { .. } => "no_server",
+            AuthSyncError::Request { .. } => "request",
             AuthSyncError::OAuthCheck { .. } => "oauth_check",
             AuthSyncError::AuthPathDetection { .. } => "path_detection",
             AuthSyncError::KeyValidation { .. } => "validation",
+            AuthSyncError::AuthFileWrite { .. } => "auth_file_write",
+            AuthSyncError::Keychain { .. } => "keychain",
             AuthSyncError::GlobalTimeout { .. } => "global_timeout",
         }
     }
@@ -271,8 +377,10 @@ impl AuthSyncError {
         match self {
             AuthSyncError::ProviderSync { provider, .. } => Some(provider),
             AuthSyncError::Network { provider, .. } => Some(provider),
+            AuthSyncError::Request { provider, .. } => Some(provider),
             AuthSyncError::OAuthCheck { provider, .. } => Some(provider),
             AuthSyncError::KeyValidation { provider, .. } => Some(provider),
+            AuthSyncError::Keychain { provider, .. } => Some(provider),
             _ => None,
         }
     }
@@ -285,3 +393,6 @@ impl AuthSyncError {
         }
     }
 }
+
+/// Wraps client and validation errors that can quote the key being synced.
+impl RedactedDisplay for AuthSyncError {}
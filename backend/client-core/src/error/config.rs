@@ -40,6 +40,21 @@ pub enum ConfigError {
         reason: String,
     },
 
+    #[error(
+        "Config Version Error: version {version} is newer than supported version {supported} {location}"
+    )]
+    UnsupportedVersion {
+        location: ErrorLocation,
+        version: u32,
+        supported: u32,
+    },
+
+    #[error("Config Key Error: unknown key path '{key_path}' {location}")]
+    UnknownKey {
+        location: ErrorLocation,
+        key_path: String,
+    },
+
     #[error("Config Validation Error: {reason} {location}")]
     ValidationError {
         location: ErrorLocation,
@@ -1,3 +1,5 @@
+use crate::error::ErrorDetails;
+
 use common::ErrorLocation;
 
 use std::error::Error as StdError;
@@ -25,4 +27,50 @@ pub enum DiscoveryError {
         message: String,
         location: ErrorLocation,
     },
+
+    #[error("Not Owned Error: {message} {location}")]
+    NotOwned {
+        message: String,
+        location: ErrorLocation,
+    },
+
+    #[error("Process Mismatch Error: {message} {location}")]
+    ProcessMismatch {
+        message: String,
+        location: ErrorLocation,
+    },
+
+    #[error("Unreachable Error: {message} {location}")]
+    Unreachable {
+        message: String,
+        location: ErrorLocation,
+    },
+}
+
+impl ErrorDetails for DiscoveryError {
+    fn location(&self) -> &ErrorLocation {
+        match self {
+            DiscoveryError::NetworkQuery { location, .. }
+            | DiscoveryError::SystemQuery { location, .. }
+            | DiscoveryError::Validation { location, .. }
+            | DiscoveryError::NotOwned { location, .. }
+            | DiscoveryError::ProcessMismatch { location, .. }
+            | DiscoveryError::Unreachable { location, .. } => location,
+        }
+    }
+
+    fn category(&self) -> &'static str {
+        match self {
+            DiscoveryError::NetworkQuery { .. } => "network_query",
+            DiscoveryError::SystemQuery { .. } => "system_query",
+            DiscoveryError::Validation { .. } => "validation",
+            DiscoveryError::NotOwned { .. } => "not_owned",
+            DiscoveryError::ProcessMismatch { .. } => "process_mismatch",
+            DiscoveryError::Unreachable { .. } => "unreachable",
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(self, DiscoveryError::Unreachable { .. })
+    }
 }
@@ -1,11 +1,16 @@
-use common::ErrorLocation;
+use crate::error::ErrorDetails;
+use crate::proto::{IpcErrorCode, IpcErrorLocation, IpcErrorResponse};
+
+use common::{ErrorLocation, RedactedDisplay};
 
 use std::io::Error as IoError;
 use std::panic::Location;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error as ThisError;
 
-#[derive(Debug, ThisError)]
+#[derive(Debug, ThisError, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum IpcError {
     #[error("Handshake Error: {message} {location}")]
     Handshake {
@@ -37,6 +42,14 @@ pub enum IpcError {
         location: ErrorLocation,
     },
 
+    /// The connection dropped. A request in flight may or may not have been handled, so
+    /// this is never retryable: resending could apply it twice.
+    #[error("Connection Lost: {message} {location}")]
+    ConnectionLost {
+        message: String,
+        location: ErrorLocation,
+    },
+
     #[error("Protobuf Decode Error: {message} {location}")]
     ProtobufDecode {
         message: String,
@@ -48,6 +61,117 @@ pub enum IpcError {
         message: String,
         location: ErrorLocation,
     },
+
+    /// An error response from the server. `location` is where it was received;
+    /// `remote_location` is where the server raised it, if reported.
+    #[error("Remote Error ({code:?}): {message} {location}")]
+    Remote {
+        code: IpcErrorCode,
+        message: String,
+        remote_location: Option<ErrorLocation>,
+        category: String,
+        retryable: bool,
+        location: ErrorLocation,
+    },
+}
+
+impl IpcError {
+    /// Get error category for metrics and IPC error responses.
+    pub fn error_category(&self) -> &'static str {
+        match self {
+            IpcError::Handshake { .. } => "handshake",
+            IpcError::Send { .. } => "send",
+            IpcError::Read { .. } => "read",
+            IpcError::Io { .. } => "io",
+            IpcError::Auth { .. } => "auth",
+            IpcError::ConnectionLost { .. } => "connection_lost",
+            IpcError::ProtobufDecode { .. } => "protobuf_decode",
+            IpcError::ProtobufEncode { .. } => "protobuf_encode",
+            IpcError::Remote { .. } => "remote",
+        }
+    }
+}
+
+impl ErrorDetails for IpcError {
+    fn location(&self) -> &ErrorLocation {
+        match self {
+            IpcError::Remote {
+                remote_location: Some(location),
+                ..
+            } => location,
+            IpcError::Handshake { location, .. }
+            | IpcError::Send { location, .. }
+            | IpcError::Read { location, .. }
+            | IpcError::Io { location, .. }
+            | IpcError::Auth { location, .. }
+            | IpcError::ConnectionLost { location, .. }
+            | IpcError::ProtobufDecode { location, .. }
+            | IpcError::ProtobufEncode { location, .. }
+            | IpcError::Remote { location, .. } => location,
+        }
+    }
+
+    fn category(&self) -> &'static str {
+        self.error_category()
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            IpcError::Remote {
+                retryable: true,
+                ..
+            }
+        )
+    }
+}
+
+/// Handler errors reach the frontend and may wrap OpenCode or auth sync errors.
+impl RedactedDisplay for IpcError {}
+
+impl IpcErrorResponse {
+    /// Build an error response carrying `error`'s location, category and retryability.
+    ///
+    /// `message` is the human-readable text clients have always received.
+    pub fn with_details(code: IpcErrorCode, message: String, error: &dyn ErrorDetails) -> Self {
+        Self {
+            code: code as i32,
+            message,
+            location: Some(error.location().into()),
+            category: error.category().to_string(),
+            retryable: error.is_retryable(),
+        }
+    }
+}
+
+impl From<&ErrorLocation> for IpcErrorLocation {
+    fn from(location: &ErrorLocation) -> Self {
+        IpcErrorLocation {
+            file: location.file.to_string(),
+            line: location.line,
+            column: location.column,
+        }
+    }
+}
+
+impl From<IpcErrorLocation> for ErrorLocation {
+    fn from(location: IpcErrorLocation) -> Self {
+        ErrorLocation::new(location.file, location.line, location.column)
+    }
+}
+
+impl From<IpcErrorResponse> for IpcError {
+    #[track_caller]
+    fn from(response: IpcErrorResponse) -> Self {
+        IpcError::Remote {
+            code: IpcErrorCode::try_from(response.code).unwrap_or(IpcErrorCode::Unknown),
+            message: response.message,
+            remote_location: response.location.map(Into::into),
+            category: response.category,
+            retryable: response.retryable,
+            location: ErrorLocation::from(Location::caller()),
+        }
+    }
 }
 
 impl From<IoError> for IpcError {
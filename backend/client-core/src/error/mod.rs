@@ -3,12 +3,33 @@ pub mod config;
 pub mod discovery;
 pub mod ipc;
 pub mod opencode_client;
+pub mod provider_models;
 pub mod spawn;
 pub mod ws;
 pub use auth_sync::{AuthSyncError, KeyValidationFailure};
 
+use common::ErrorLocation;
+
+use std::error::Error as StdError;
+
 use thiserror::Error;
 
+/// Structured details of an error, sent alongside its message in IPC error responses
+/// so the frontend can show where it happened and whether to offer a retry.
+pub trait ErrorDetails: StdError {
+    /// Where the error was raised.
+    fn location(&self) -> &ErrorLocation;
+
+    /// Machine-readable category (e.g. "timeout", "server_error").
+    fn category(&self) -> &'static str;
+
+    /// Whether retrying the same operation may succeed.
+    fn is_retryable(&self) -> bool;
+}
+
+/// Any client-core error, for functions that span several subsystems.
+///
+/// Each variant displays exactly as the error it wraps.
 #[derive(Debug, Error)]
 pub enum CoreError {
     #[error(transparent)]
@@ -22,4 +43,22 @@ pub enum CoreError {
 
     #[error(transparent)]
     Config(#[from] config::ConfigError),
+
+    #[error(transparent)]
+    AuthSync(#[from] AuthSyncError),
+
+    #[error(transparent)]
+    OpencodeClient(#[from] opencode_client::OpencodeClientError),
+}
+
+impl CoreError {
+    /// Structured details of the wrapped error, if its type provides them.
+    pub fn details(&self) -> Option<&(dyn ErrorDetails + Sync)> {
+        match self {
+            CoreError::Discovery(e) => Some(e),
+            CoreError::Spawn(e) => Some(e),
+            CoreError::OpencodeClient(e) => Some(e),
+            CoreError::Ws(_) | CoreError::Config(_) | CoreError::AuthSync(_) => None,
+        }
+    }
 }
@@ -1,14 +1,36 @@
-use common::ErrorLocation;
+//! Error types for OpenCode server HTTP calls.
+//!
+//! Transport failures are split into [`Timeout`](OpencodeClientError::Timeout) and
+//! [`Network`](OpencodeClientError::Network) (recording whether the connection failed), and
+//! server rejections record their HTTP status, so callers can classify errors without
+//! matching on message text. Use
+//! [`OpencodeClientError::from_reqwest`] to categorize a `reqwest::Error` with context.
+
+use crate::error::ErrorDetails;
+use crate::proto::message::error::{OcMessageError, oc_message_error};
+
+use common::{ErrorLocation, HttpStatusCode, RedactedDisplay};
 
 use std::panic::Location;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error as ThisError;
 
-#[derive(Debug, ThisError)]
+#[derive(Debug, ThisError, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
 pub enum OpencodeClientError {
-    #[error("HTTP Error: {message} {location}")]
-    Http {
+    #[error("Timeout Error: {context}: {message} {location}")]
+    Timeout {
+        context: String,
+        message: String,
+        location: ErrorLocation,
+    },
+
+    #[error("Network Error: {context}: {message} {location}")]
+    Network {
+        context: String,
         message: String,
+        is_connection: bool,
         location: ErrorLocation,
     },
 
@@ -24,13 +46,202 @@ pub enum OpencodeClientError {
         location: ErrorLocation,
     },
 
+    /// The server rejected the request (`status` is set) or sent a response we couldn't
+    /// use (`status` is `None`).
     #[error("Server Error: {message} {location}")]
     Server {
         message: String,
+        status: Option<u16>,
+        location: ErrorLocation,
+    },
+
+    #[error("Session Not Found: {session_id} {location}")]
+    NotFound {
+        session_id: String,
+        location: ErrorLocation,
+    },
+
+    /// The request was rejected before sending (e.g. a blank session title).
+    #[error("Invalid Request: {message} {location}")]
+    InvalidRequest {
+        message: String,
+        location: ErrorLocation,
+    },
+
+    /// The requested agent isn't in the client's known agents; nothing was sent.
+    #[error("Unknown Agent: {message} {location}")]
+    UnknownAgent {
+        agent: String,
+        message: String,
+        location: ErrorLocation,
+    },
+
+    /// The server answered, but the assistant message reports a failed generation
+    /// (provider auth, provider API, output length, ...).
+    #[error("Assistant Error: {message} {location}")]
+    Assistant {
+        message: String,
+        error: Box<OcMessageError>,
         location: ErrorLocation,
     },
 }
 
+impl OpencodeClientError {
+    /// Create from reqwest error with proper categorization.
+    ///
+    /// `context` names the operation (e.g. "list sessions") so the message says what failed.
+    /// Timeouts become [`Timeout`](Self::Timeout), errors carrying an HTTP status become
+    /// [`Server`](Self::Server), undecodable bodies become [`Json`](Self::Json), and
+    /// everything else (including refused connections) becomes [`Network`](Self::Network).
+    #[track_caller]
+    pub fn from_reqwest(context: impl Into<String>, error: &reqwest::Error) -> Self {
+        let context = context.into();
+
+        // Check for specific error types BEFORE converting to string
+        if error.is_timeout() {
+            return OpencodeClientError::Timeout {
+                context,
+                message: error.to_string(),
+                location: ErrorLocation::from(Location::caller()),
+            };
+        }
+
+        if let Some(status) = error.status() {
+            return OpencodeClientError::Server {
+                message: format!("HTTP {} - {context}: {error}", status.as_u16()),
+                status: Some(status.as_u16()),
+                location: ErrorLocation::from(Location::caller()),
+            };
+        }
+
+        if error.is_decode() {
+            return OpencodeClientError::Json {
+                message: format!("{context}: {error}"),
+                location: ErrorLocation::from(Location::caller()),
+            };
+        }
+
+        OpencodeClientError::Network {
+            context,
+            message: error.to_string(),
+            is_connection: error.is_connect(),
+            location: ErrorLocation::from(Location::caller()),
+        }
+    }
+
+    /// Check if this error is retryable based on error category, NOT string content.
+    ///
+    /// Timeouts, connection failures and transient statuses (429, 502-504) are retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            OpencodeClientError::Timeout { .. } => true,
+            OpencodeClientError::Network { is_connection, .. } => *is_connection,
+            OpencodeClientError::Server { .. } => self
+                .status_code()
+                .is_some_and(|code| HttpStatusCode(code).is_retryable()),
+            OpencodeClientError::Assistant { error, .. } => match &error.error {
+                Some(oc_message_error::Error::Api(api)) => u16::try_from(api.status)
+                    .is_ok_and(|status| HttpStatusCode(status).is_retryable()),
+                _ => false,
+            },
+            OpencodeClientError::Json { .. }
+            | OpencodeClientError::UrlParse { .. }
+            | OpencodeClientError::NotFound { .. }
+            | OpencodeClientError::InvalidRequest { .. }
+            | OpencodeClientError::UnknownAgent { .. } => false,
+        }
+    }
+
+    /// Did the request time out?
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, OpencodeClientError::Timeout { .. })
+    }
+
+    /// Did the request fail to connect to the server?
+    pub fn is_connection(&self) -> bool {
+        matches!(
+            self,
+            OpencodeClientError::Network {
+                is_connection: true,
+                ..
+            }
+        )
+    }
+
+    /// Get error category (same vocabulary as [`AuthSyncError::error_category`](crate::error::AuthSyncError::error_category)).
+    pub fn error_category(&self) -> &'static str {
+        match self {
+            OpencodeClientError::Timeout { .. } => "timeout",
+            OpencodeClientError::Network {
+                is_connection: true,
+                ..
+            } => "connection",
+            OpencodeClientError::Network { .. } => "network",
+            OpencodeClientError::Server { .. } => match self.status_code().map(HttpStatusCode) {
+                Some(status) if status.is_client_error() => "client_error",
+                Some(status) if status.is_server_error() => "server_error",
+                _ => "server",
+            },
+            OpencodeClientError::Json { .. } => "json",
+            OpencodeClientError::UrlParse { .. } => "url_parse",
+            OpencodeClientError::NotFound { .. } => "not_found",
+            OpencodeClientError::InvalidRequest { .. } => "invalid_request",
+            OpencodeClientError::UnknownAgent { .. } => "unknown_agent",
+            OpencodeClientError::Assistant { error, .. } => match &error.error {
+                Some(oc_message_error::Error::ProviderAuth(_)) => "provider_auth",
+                Some(oc_message_error::Error::Api(_)) => "provider_api",
+                Some(oc_message_error::Error::OutputLength(_)) => "output_length",
+                Some(oc_message_error::Error::Aborted(_)) => "aborted",
+                Some(oc_message_error::Error::Unknown(_)) | None => "assistant",
+            },
+        }
+    }
+
+    /// The typed error from a failed assistant message, if this is one.
+    pub fn assistant_error(&self) -> Option<&OcMessageError> {
+        match self {
+            OpencodeClientError::Assistant { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Get HTTP status code if the server rejected the request.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            OpencodeClientError::Server { status, .. } => *status,
+            OpencodeClientError::NotFound { .. } => Some(404),
+            _ => None,
+        }
+    }
+}
+
+impl ErrorDetails for OpencodeClientError {
+    fn location(&self) -> &ErrorLocation {
+        match self {
+            OpencodeClientError::Timeout { location, .. }
+            | OpencodeClientError::Network { location, .. }
+            | OpencodeClientError::Json { location, .. }
+            | OpencodeClientError::UrlParse { location, .. }
+            | OpencodeClientError::Server { location, .. }
+            | OpencodeClientError::NotFound { location, .. }
+            | OpencodeClientError::InvalidRequest { location, .. }
+            | OpencodeClientError::UnknownAgent { location, .. }
+            | OpencodeClientError::Assistant { location, .. } => location,
+        }
+    }
+
+    fn category(&self) -> &'static str {
+        self.error_category()
+    }
+
+    fn is_retryable(&self) -> bool {
+        OpencodeClientError::is_retryable(self)
+    }
+}
+
+/// Server errors carry response bodies, which may echo the submitted key.
+impl RedactedDisplay for OpencodeClientError {}
+
 impl From<url::ParseError> for OpencodeClientError {
     #[track_caller]
     fn from(error: url::ParseError) -> Self {
@@ -44,10 +255,7 @@ impl From<url::ParseError> for OpencodeClientError {
 impl From<reqwest::Error> for OpencodeClientError {
     #[track_caller]
     fn from(error: reqwest::Error) -> Self {
-        OpencodeClientError::Http {
-            message: error.to_string(),
-            location: ErrorLocation::from(Location::caller()),
-        }
+        OpencodeClientError::from_reqwest("HTTP request", &error)
     }
 }
 
@@ -0,0 +1,96 @@
+//! Error types for fetching a provider's model list.
+//!
+//! Messages never include the request URL: providers with `auth_type = "query_param"`
+//! carry the API key in it.
+
+use crate::error::ErrorDetails;
+
+use common::{ErrorLocation, HttpStatusCode};
+
+use std::panic::Location;
+
+use thiserror::Error as ThisError;
+
+#[derive(Debug, ThisError)]
+pub enum ProviderModelsError {
+    /// The provider config can't be turned into a request (bad URL, missing auth field).
+    #[error("Provider Config Error: {message} {location}")]
+    Config {
+        message: String,
+        location: ErrorLocation,
+    },
+
+    /// The request didn't get a response (connection failure or timeout).
+    #[error("Request Error: {message} {location}")]
+    Request {
+        message: String,
+        location: ErrorLocation,
+    },
+
+    /// The provider answered with a non-success status.
+    #[error("HTTP Error: {status} - {message} {location}")]
+    Http {
+        status: u16,
+        message: String,
+        location: ErrorLocation,
+    },
+
+    /// The response isn't JSON shaped as the provider's `response_format` describes.
+    #[error("Response Format Error: {message} {location}")]
+    Format {
+        message: String,
+        location: ErrorLocation,
+    },
+}
+
+impl ProviderModelsError {
+    /// Create from a reqwest error, with the URL (and any API key in it) removed.
+    #[track_caller]
+    pub fn from_reqwest(provider: &str, error: reqwest::Error) -> Self {
+        let location = ErrorLocation::from(Location::caller());
+        let is_builder = error.is_builder();
+        let is_decode = error.is_decode();
+        let message = format!("{provider}: {}", error.without_url());
+
+        if is_builder {
+            ProviderModelsError::Config { message, location }
+        } else if is_decode {
+            ProviderModelsError::Format { message, location }
+        } else {
+            ProviderModelsError::Request { message, location }
+        }
+    }
+}
+
+impl ErrorDetails for ProviderModelsError {
+    fn location(&self) -> &ErrorLocation {
+        match self {
+            ProviderModelsError::Config { location, .. }
+            | ProviderModelsError::Request { location, .. }
+            | ProviderModelsError::Http { location, .. }
+            | ProviderModelsError::Format { location, .. } => location,
+        }
+    }
+
+    fn category(&self) -> &'static str {
+        match self {
+            ProviderModelsError::Config { .. } => "config",
+            ProviderModelsError::Request { .. } => "network",
+            ProviderModelsError::Http { status, .. }
+                if HttpStatusCode(*status).is_client_error() =>
+            {
+                "client_error"
+            }
+            ProviderModelsError::Http { .. } => "server_error",
+            ProviderModelsError::Format { .. } => "json",
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            ProviderModelsError::Request { .. } => true,
+            ProviderModelsError::Http { status, .. } => HttpStatusCode(*status).is_retryable(),
+            ProviderModelsError::Config { .. } | ProviderModelsError::Format { .. } => false,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::ErrorDetails;
+
 use common::ErrorLocation;
 
 use serde::de::StdError;
@@ -31,3 +33,28 @@ pub enum SpawnError {
         location: ErrorLocation,
     },
 }
+
+impl ErrorDetails for SpawnError {
+    fn location(&self) -> &ErrorLocation {
+        match self {
+            SpawnError::Spawn { location, .. }
+            | SpawnError::Parse { location, .. }
+            | SpawnError::Timeout { location, .. }
+            | SpawnError::Validation { location, .. } => location,
+        }
+    }
+
+    fn category(&self) -> &'static str {
+        match self {
+            SpawnError::Spawn { .. } => "spawn",
+            SpawnError::Parse { .. } => "parse",
+            SpawnError::Timeout { .. } => "timeout",
+            SpawnError::Validation { .. } => "validation",
+        }
+    }
+
+    /// Only a server that was slow to report ready may come up on a second try.
+    fn is_retryable(&self) -> bool {
+        matches!(self, SpawnError::Timeout { .. })
+    }
+}
@@ -1 +1,168 @@
 include!(concat!(env!("OUT_DIR"), "/field_normalizer.rs"));
+
+use std::fmt;
+use std::io::Read;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Map;
+
+/// Transform a single JavaScript field name to snake_case, consulting `extra` first
+/// Returns Cow::Borrowed in both cases (zero-copy)
+pub fn normalize_key_with<'a>(key: &'a str, extra: &'a HashMap<String, String>) -> Cow<'a, str> {
+    extra
+        .get(key)
+        .map(|s| Cow::Borrowed(s.as_str()))
+        .unwrap_or_else(|| normalize_key(key))
+}
+
+/// Transform JavaScript field names to snake_case at every nesting level, with runtime mappings
+/// `extra` (JavaScript → snake_case) is checked before the generated table, so fields
+/// added by newer OpenCode servers can be handled without a rebuild
+pub fn normalize_json_with(value: Value, extra: &HashMap<String, String>) -> Value {
+    rename_keys(value, &|k| {
+        extra
+            .get(k)
+            .map(String::as_str)
+            .or_else(|| TO_SNAKE.get(k).copied())
+    })
+}
+
+/// Rename object keys at every nesting level, in place
+/// `lookup` returns the new name for keys that change (None = keep)
+///
+/// Iterative (explicit heap stack), so deeply nested JSON can't overflow the call stack.
+/// Objects with no keys to rename and all arrays are left where they are; only objects
+/// with a renamed key are rebuilt. Output, including key order, matches a recursive rebuild
+fn rename_keys<'m>(mut value: Value, lookup: &dyn Fn(&str) -> Option<&'m str>) -> Value {
+    let mut stack: Vec<&mut Value> = vec![&mut value];
+
+    while let Some(current) = stack.pop() {
+        match current {
+            Value::Object(map) => {
+                rename_object(map, lookup);
+                stack.extend(map.values_mut().filter(|v| is_container(v)));
+            }
+            Value::Array(arr) => stack.extend(arr.iter_mut().filter(|v| is_container(v))),
+            _ => {}
+        }
+    }
+
+    value
+}
+
+/// Rename the keys of one object (not its children)
+/// Left untouched unless some key changes; otherwise rebuilt in the map's own order, so a
+/// renamed key that collides with an existing one resolves the same way everywhere
+fn rename_object<'m>(map: &mut Map<String, Value>, lookup: &dyn Fn(&str) -> Option<&'m str>) {
+    if map.keys().any(|k| lookup(k).is_some()) {
+        *map = std::mem::take(map)
+            .into_iter()
+            .map(|(k, v)| match lookup(&k) {
+                Some(renamed) => (renamed.to_string(), v),
+                None => (k, v),
+            })
+            .collect();
+    }
+}
+
+/// Can this value contain keys (directly or nested)?
+fn is_container(value: &Value) -> bool {
+    matches!(value, Value::Object(_) | Value::Array(_))
+}
+
+/// Parse JSON from `reader`, transforming field names to snake_case while parsing
+///
+/// Same result as `normalize_json(serde_json::from_reader(reader)?)`, without a second pass
+/// over the whole document: each object is renamed as soon as it's parsed, while it's still
+/// hot in cache. Meant for large responses (e.g. a long session's messages).
+/// `reader` is read unbuffered; wrap files and sockets in a `BufReader`
+pub fn normalize_reader<R: Read>(reader: R) -> serde_json::Result<Value> {
+    serde_json::from_reader::<_, Normalized>(reader).map(|normalized| normalized.0)
+}
+
+/// Parse JSON from `bytes`, transforming field names to snake_case while parsing
+/// Same result as `normalize_json(serde_json::from_slice(bytes)?)`
+pub fn normalize_slice(bytes: &[u8]) -> serde_json::Result<Value> {
+    serde_json::from_slice::<Normalized>(bytes).map(|normalized| normalized.0)
+}
+
+/// A JSON value whose object keys were normalized as it was deserialized
+///
+/// Builds exactly what `serde_json::Value` would, except that each finished object goes
+/// through [`rename_object`]. Nesting depth is bounded by serde_json's recursion limit,
+/// the same as for parsing into `Value`
+struct Normalized(Value);
+
+impl<'de> Deserialize<'de> for Normalized {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(NormalizedVisitor)
+            .map(Normalized)
+    }
+}
+
+struct NormalizedVisitor;
+
+impl<'de> Visitor<'de> for NormalizedVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Normalized::deserialize(deserializer).map(|normalized| normalized.0)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(Normalized(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+        let mut map = Map::new();
+        while let Some(key) = access.next_key::<String>()? {
+            let Normalized(value) = access.next_value()?;
+            map.insert(key, value);
+        }
+        // Rename after the whole object is in: duplicate keys in the document must collapse
+        // exactly as they do when parsing into `Value` first
+        rename_object(&mut map, &|k| TO_SNAKE.get(k).copied());
+        Ok(Value::Object(map))
+    }
+}
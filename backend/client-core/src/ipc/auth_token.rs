@@ -0,0 +1,72 @@
+//! IPC auth token with redacted Debug/Display output.
+//!
+//! Logs go to `opencode.log` on disk, so the token value must never be formatted.
+//! Like `RedactedApiKey`, formatting only shows a short prefix for correlating log lines.
+
+use std::fmt;
+
+use uuid::Uuid;
+use zeroize::Zeroize;
+
+/// Number of leading characters shown when the token is formatted.
+const FINGERPRINT_CHARS: usize = 4;
+
+/// The shared secret a client must present in its auth handshake.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IpcAuthToken {
+    inner: String,
+}
+
+impl IpcAuthToken {
+    /// Wrap an existing token.
+    pub fn new(token: String) -> Self {
+        Self { inner: token }
+    }
+
+    /// Generate a random (UUID v4) token.
+    pub fn generate() -> Self {
+        Self::new(Uuid::new_v4().to_string())
+    }
+
+    /// Get the actual token value.
+    ///
+    /// # Security Note
+    /// Only call this to validate a handshake or hand the token to the frontend.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+
+    /// Short prefix of the token (safe to log).
+    ///
+    /// Empty for tokens too short for a prefix to be safe (under twice the prefix length).
+    pub fn fingerprint(&self) -> &str {
+        if self.inner.chars().count() < FINGERPRINT_CHARS * 2 {
+            return "";
+        }
+        let end = self
+            .inner
+            .char_indices()
+            .nth(FINGERPRINT_CHARS)
+            .map_or(self.inner.len(), |(i, _)| i);
+        &self.inner[..end]
+    }
+}
+
+impl fmt::Debug for IpcAuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IpcAuthToken({}…[REDACTED])", self.fingerprint())
+    }
+}
+
+impl fmt::Display for IpcAuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}…[REDACTED]", self.fingerprint())
+    }
+}
+
+impl Drop for IpcAuthToken {
+    fn drop(&mut self) {
+        self.inner.zeroize();
+    }
+}
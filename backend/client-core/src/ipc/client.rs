@@ -0,0 +1,923 @@
+//! Typed client for the IPC WebSocket server.
+//!
+//! Wraps the WebSocket plumbing, protobuf encoding and request/response
+//! correlation so callers (integration tests, tooling) can issue IPC requests
+//! as plain async method calls.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), client_core::error::ipc::IpcError> {
+//! use client_core::ipc::IpcClient;
+//!
+//! let mut client = IpcClient::connect(19876, "auth-token").await?;
+//! let sessions = client.list_sessions().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::ipc::IpcError;
+use crate::proto::message::OcMessage;
+use crate::proto::message::part::OcPart;
+use crate::proto::session::{OcSessionInfo, OcSessionList};
+use crate::proto::{
+    IpcAbortMessageRequest, IpcAddCuratedModelRequest, IpcAuthHandshake, IpcCheckHealthRequest,
+    IpcClientMessage, IpcConfigValueResponse, IpcCreateSessionRequest, IpcCuratedModel,
+    IpcDeleteSessionRequest, IpcDiscoverAllServersRequest, IpcDiscoverServerRequest,
+    IpcEnsureServerRequest, IpcGetConfigRequest, IpcGetConfigResponse, IpcGetConfigValueRequest,
+    IpcGetMetricsRequest, IpcListSessionsRequest, IpcMetricsResponse, IpcRemoveCuratedModelRequest,
+    IpcResetConfigRequest, IpcResetConfigResponse, IpcSendMessageRequest, IpcServerInfo,
+    IpcServerMessage, IpcServerProbe, IpcServerStateEvent, IpcSetConfigValueRequest,
+    IpcSetDirectoryRequest, IpcSetLogLevelRequest, IpcSetLogLevelResponse, IpcSpawnServerRequest,
+    IpcStreamMessageRequest, IpcSubscribeEventsRequest, IpcUpdateConfigRequest,
+    IpcUpdateConfigResponse, IpcUpdateModelsConfigRequest, IpcUpdateSessionRequest,
+    ipc_client_message, ipc_server_message,
+};
+
+use crate::ipc::auth_token::IpcAuthToken;
+use crate::ipc::protocol::IPC_PROTOCOL_VERSION;
+use crate::ipc::reconnect::{ConnectionStatus, ReconnectPolicy};
+
+use common::ErrorLocation;
+
+use std::collections::VecDeque;
+use std::panic::Location;
+
+use backoff::backoff::Backoff;
+use futures_util::{FutureExt, SinkExt, StreamExt};
+use log::{debug, info, warn};
+use prost::Message as ProstMessage;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio::time::sleep;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
+
+/// Request ID reserved for the auth handshake (matches the server).
+const AUTH_REQUEST_ID: u64 = 1;
+
+type ClientWebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Authenticated connection to an IPC server.
+///
+/// # Reconnection
+///
+/// If the connection is found closed before a request is sent, the client reconnects
+/// (per its [`ReconnectPolicy`]) and then sends the request, re-subscribing to events if
+/// it was subscribed; events pushed while disconnected are lost. A connection that drops
+/// while a request is in flight fails that request with [`IpcError::ConnectionLost`],
+/// since the server may or may not have handled it; the next request reconnects.
+pub struct IpcClient {
+    /// `None` while disconnected
+    ws: Option<ClientWebSocket>,
+    port: u16,
+    token: IpcAuthToken,
+    reconnect: Option<ReconnectPolicy>,
+    status: watch::Sender<ConnectionStatus>,
+    next_request_id: u64,
+    protocol_version: u32,
+    /// Request ID of the event subscription, once subscribed
+    subscription_id: Option<u64>,
+    /// Events received while waiting for other responses
+    pending_events: VecDeque<IpcServerStateEvent>,
+}
+
+impl IpcClient {
+    /// Connects to the IPC server on `127.0.0.1:<port>` and performs the auth handshake.
+    ///
+    /// Lost connections are re-established with the default [`ReconnectPolicy`].
+    ///
+    /// # Errors
+    ///
+    /// - [`IpcError::Handshake`] if the WebSocket connection cannot be established
+    /// - [`IpcError::Auth`] if the server rejects the token or the protocol version
+    pub async fn connect(port: u16, token: &str) -> Result<Self, IpcError> {
+        Self::connect_with(port, token, Some(ReconnectPolicy::default())).await
+    }
+
+    /// Like [`connect`](Self::connect), with a custom reconnect policy (`None` disables
+    /// reconnection: a lost connection then fails every later request).
+    ///
+    /// The initial connection is attempted once, without retries.
+    pub async fn connect_with(
+        port: u16,
+        token: &str,
+        reconnect: Option<ReconnectPolicy>,
+    ) -> Result<Self, IpcError> {
+        let mut client = Self {
+            ws: None,
+            port,
+            token: IpcAuthToken::new(token.to_string()),
+            reconnect,
+            status: watch::Sender::new(ConnectionStatus::Disconnected),
+            next_request_id: AUTH_REQUEST_ID + 1,
+            protocol_version: IPC_PROTOCOL_VERSION,
+            subscription_id: None,
+            pending_events: VecDeque::new(),
+        };
+        client.open().await?;
+        client.status.send_replace(ConnectionStatus::Connected);
+        Ok(client)
+    }
+
+    /// Current connection state.
+    pub fn status(&self) -> ConnectionStatus {
+        *self.status.borrow()
+    }
+
+    /// Receiver that sees every connection state change (e.g. to show "reconnecting").
+    pub fn watch_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.subscribe()
+    }
+
+    /// Dials the server and performs the auth handshake, replacing any previous connection.
+    async fn open(&mut self) -> Result<(), IpcError> {
+        let url = format!("ws://127.0.0.1:{}", self.port);
+        let (ws, _) = connect_async(&url).await.map_err(|e| IpcError::Handshake {
+            message: format!("Failed to connect to {url}: {e}"),
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+        self.ws = Some(ws);
+
+        let auth = IpcClientMessage {
+            request_id: AUTH_REQUEST_ID,
+            payload: Some(ipc_client_message::Payload::AuthHandshake(
+                IpcAuthHandshake {
+                    token: self.token.as_str().to_string(),
+                    protocol_version: IPC_PROTOCOL_VERSION,
+                },
+            )),
+        };
+        self.send(&auth).await?;
+
+        match self.receive(AUTH_REQUEST_ID).await? {
+            ipc_server_message::Payload::AuthHandshakeResponse(resp) if resp.success => {
+                info!(
+                    "Authenticated with IPC server on port {} (protocol v{})",
+                    self.port, resp.protocol_version
+                );
+                self.protocol_version = resp.protocol_version;
+                Ok(())
+            }
+            ipc_server_message::Payload::AuthHandshakeResponse(resp) => {
+                self.ws = None;
+                Err(IpcError::Auth {
+                    message: resp
+                        .error
+                        .unwrap_or_else(|| "Authentication rejected".to_string()),
+                    location: ErrorLocation::from(Location::caller()),
+                })
+            }
+            other => {
+                self.ws = None;
+                Err(unexpected_payload("AuthHandshakeResponse", &other))
+            }
+        }
+    }
+
+    /// Protocol version negotiated with the server during the handshake.
+    pub fn protocol_version(&self) -> u32 {
+        self.protocol_version
+    }
+
+    /// Sends a request and waits for the response with the matching `request_id`.
+    ///
+    /// Responses for other request IDs are skipped. Server error responses are
+    /// returned as [`IpcError::Remote`].
+    pub async fn request(
+        &mut self,
+        payload: ipc_client_message::Payload,
+    ) -> Result<ipc_server_message::Payload, IpcError> {
+        let request_id = self.send_request(payload).await?;
+        self.receive_response(request_id).await
+    }
+
+    /// Discovers a running OpenCode server (`None` if none is running).
+    pub async fn discover_server(&mut self) -> Result<Option<IpcServerInfo>, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::DiscoverServer(
+                IpcDiscoverServerRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::DiscoverServerResponse(resp) => Ok(resp.server),
+            other => Err(unexpected_payload("DiscoverServerResponse", &other)),
+        }
+    }
+
+    /// Discovers every running OpenCode server (empty if none are running).
+    pub async fn discover_all_servers(&mut self) -> Result<Vec<IpcServerInfo>, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::DiscoverAllServers(
+                IpcDiscoverAllServersRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::DiscoverAllServersResponse(resp) => Ok(resp.servers),
+            other => Err(unexpected_payload("DiscoverAllServersResponse", &other)),
+        }
+    }
+
+    /// Spawns an OpenCode server (on `port`, or an auto-selected one) and connects to it.
+    pub async fn spawn_server(&mut self, port: Option<u32>) -> Result<IpcServerInfo, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::SpawnServer(
+                IpcSpawnServerRequest { port },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::SpawnServerResponse(resp) => {
+                resp.server.ok_or_else(|| IpcError::Read {
+                    message: "SpawnServerResponse has no server".to_string(),
+                    location: ErrorLocation::from(Location::caller()),
+                })
+            }
+            other => Err(unexpected_payload("SpawnServerResponse", &other)),
+        }
+    }
+
+    /// Connects to a running OpenCode server, spawning one if none is found.
+    ///
+    /// The returned server is `owned` only if it was spawned.
+    pub async fn ensure_server(&mut self) -> Result<IpcServerInfo, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::EnsureServer(
+                IpcEnsureServerRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::EnsureServerResponse(resp) => {
+                resp.server.ok_or_else(|| IpcError::Read {
+                    message: "EnsureServerResponse has no server".to_string(),
+                    location: ErrorLocation::from(Location::caller()),
+                })
+            }
+            other => Err(unexpected_payload("EnsureServerResponse", &other)),
+        }
+    }
+
+    /// Checks whether the connected OpenCode server is healthy.
+    pub async fn check_health(&mut self) -> Result<bool, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::CheckHealth(
+                IpcCheckHealthRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::CheckHealthResponse(resp) => Ok(resp.healthy),
+            other => Err(unexpected_payload("CheckHealthResponse", &other)),
+        }
+    }
+
+    /// Probes the connected OpenCode server's health endpoint.
+    ///
+    /// Unlike [`check_health`](Self::check_health), tells a server that answers with an
+    /// error (e.g. while restarting) from one that doesn't answer at all.
+    pub async fn probe_server(&mut self) -> Result<IpcServerProbe, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::CheckHealth(
+                IpcCheckHealthRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::CheckHealthResponse(resp) => {
+                resp.probe.ok_or_else(|| IpcError::Read {
+                    message: "CheckHealthResponse has no probe".to_string(),
+                    location: ErrorLocation::from(Location::caller()),
+                })
+            }
+            other => Err(unexpected_payload("CheckHealthResponse", &other)),
+        }
+    }
+
+    /// Lists sessions on the connected OpenCode server.
+    pub async fn list_sessions(&mut self) -> Result<OcSessionList, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::ListSessions(
+                IpcListSessionsRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::SessionList(list) => Ok(list),
+            other => Err(unexpected_payload("SessionList", &other)),
+        }
+    }
+
+    /// Creates a session with an optional title.
+    pub async fn create_session(&mut self, title: Option<&str>) -> Result<OcSessionInfo, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::CreateSession(
+                IpcCreateSessionRequest {
+                    title: title.map(str::to_string),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::SessionInfo(info) => Ok(info),
+            other => Err(unexpected_payload("SessionInfo", &other)),
+        }
+    }
+
+    /// Deletes a session, returning whether the server reported success.
+    pub async fn delete_session(&mut self, session_id: &str) -> Result<bool, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::DeleteSession(
+                IpcDeleteSessionRequest {
+                    session_id: session_id.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::DeleteSessionResponse(resp) => Ok(resp.success),
+            other => Err(unexpected_payload("DeleteSessionResponse", &other)),
+        }
+    }
+
+    /// Renames a session, returning the updated session.
+    pub async fn update_session(
+        &mut self,
+        session_id: &str,
+        title: &str,
+    ) -> Result<OcSessionInfo, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::UpdateSession(
+                IpcUpdateSessionRequest {
+                    session_id: session_id.to_string(),
+                    title: title.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::SessionInfo(info) => Ok(info),
+            other => Err(unexpected_payload("SessionInfo", &other)),
+        }
+    }
+
+    /// Sets the project directory sent to OpenCode, for all sessions or just `session_id`.
+    ///
+    /// `directory: None` removes the override.
+    pub async fn set_directory(
+        &mut self,
+        session_id: Option<&str>,
+        directory: Option<&str>,
+    ) -> Result<bool, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::SetDirectory(
+                IpcSetDirectoryRequest {
+                    session_id: session_id.map(str::to_string),
+                    directory: directory.map(str::to_string),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::SetDirectoryResponse(resp) => Ok(resp.success),
+            other => Err(unexpected_payload("SetDirectoryResponse", &other)),
+        }
+    }
+
+    /// Sends a chat message and waits for the assistant's reply.
+    pub async fn send_message(
+        &mut self,
+        req: IpcSendMessageRequest,
+    ) -> Result<OcMessage, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::SendMessage(req))
+            .await?
+        {
+            ipc_server_message::Payload::SendMessageResponse(message) => Ok(message),
+            other => Err(unexpected_payload("SendMessageResponse", &other)),
+        }
+    }
+
+    /// Aborts the session's in-flight generation, returning whether the server aborted one.
+    ///
+    /// A `stream_message` in progress for the session ends with an aborted final message.
+    pub async fn abort_message(&mut self, session_id: &str) -> Result<bool, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::AbortMessage(
+                IpcAbortMessageRequest {
+                    session_id: session_id.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::AbortMessageResponse(resp) => Ok(resp.success),
+            other => Err(unexpected_payload("AbortMessageResponse", &other)),
+        }
+    }
+
+    /// Changes the log level of the app process, for `target` (a module path prefix) or
+    /// every module if `None`.
+    ///
+    /// An unknown level name is rejected with [`IpcError::Remote`].
+    pub async fn set_log_level(
+        &mut self,
+        level: &str,
+        target: Option<&str>,
+    ) -> Result<IpcSetLogLevelResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::SetLogLevel(
+                IpcSetLogLevelRequest {
+                    level: level.to_string(),
+                    target: target.map(str::to_string),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::SetLogLevelResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("SetLogLevelResponse", &other)),
+        }
+    }
+
+    /// Reads the server's request counters (per message type and per error code).
+    pub async fn get_metrics(&mut self) -> Result<IpcMetricsResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::GetMetrics(
+                IpcGetMetricsRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::MetricsResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("MetricsResponse", &other)),
+        }
+    }
+
+    /// Sends a chat message and streams its parts as they are generated.
+    ///
+    /// `on_part` is called for each part update in arrival order (the same part may be
+    /// reported again as it grows). Returns the final assistant message.
+    pub async fn stream_message(
+        &mut self,
+        req: IpcStreamMessageRequest,
+        mut on_part: impl FnMut(OcPart),
+    ) -> Result<OcMessage, IpcError> {
+        let request_id = self
+            .send_request(ipc_client_message::Payload::StreamMessage(req))
+            .await?;
+
+        loop {
+            match self.receive_response(request_id).await? {
+                ipc_server_message::Payload::MessagePartEvent(event) => {
+                    if let Some(part) = event.part {
+                        on_part(part);
+                    }
+                }
+                ipc_server_message::Payload::MessageCompleteEvent(event) => {
+                    return event.message.ok_or_else(|| IpcError::Read {
+                        message: "MessageCompleteEvent has no message".to_string(),
+                        location: ErrorLocation::from(Location::caller()),
+                    });
+                }
+                other => return Err(unexpected_payload("MessagePartEvent", &other)),
+            }
+        }
+    }
+
+    /// Fetches the app and models config as JSON strings.
+    pub async fn get_config(&mut self) -> Result<IpcGetConfigResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::GetConfig(
+                IpcGetConfigRequest {},
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::GetConfigResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("GetConfigResponse", &other)),
+        }
+    }
+
+    /// Replaces the app config with `config_json` (a serialized `AppConfig`).
+    ///
+    /// Validation failures are reported in the response (`success == false`), not as errors.
+    pub async fn update_config(
+        &mut self,
+        config_json: &str,
+    ) -> Result<IpcUpdateConfigResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::UpdateConfig(
+                IpcUpdateConfigRequest {
+                    config_json: config_json.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::UpdateConfigResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("UpdateConfigResponse", &other)),
+        }
+    }
+
+    /// Reads one app config value by dotted key path (e.g. `ui.base_font_points`).
+    ///
+    /// Unknown paths are returned as [`IpcError::Remote`].
+    pub async fn get_config_value(
+        &mut self,
+        key_path: &str,
+    ) -> Result<IpcConfigValueResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::GetConfigValue(
+                IpcGetConfigValueRequest {
+                    key_path: key_path.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::ConfigValueResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("ConfigValueResponse", &other)),
+        }
+    }
+
+    /// Replaces one app config value (`value_json` is the new value as JSON).
+    ///
+    /// Unknown paths and rejected values are returned as [`IpcError::Remote`].
+    pub async fn set_config_value(
+        &mut self,
+        key_path: &str,
+        value_json: &str,
+    ) -> Result<IpcConfigValueResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::SetConfigValue(
+                IpcSetConfigValueRequest {
+                    key_path: key_path.to_string(),
+                    value_json: value_json.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::ConfigValueResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("ConfigValueResponse", &other)),
+        }
+    }
+
+    /// Resets an app config section (`server`, `ui`, `audio`, or `""` for everything) to
+    /// its defaults.
+    ///
+    /// Unknown sections are returned as [`IpcError::Remote`].
+    pub async fn reset_config(
+        &mut self,
+        section: &str,
+    ) -> Result<IpcResetConfigResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::ResetConfig(
+                IpcResetConfigRequest {
+                    section: section.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::ResetConfigResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("ResetConfigResponse", &other)),
+        }
+    }
+
+    /// Replaces the models config with `models_config_json` (a serialized `ModelsConfig`).
+    ///
+    /// Validation failures are reported in the response (`success == false`), not as errors.
+    pub async fn update_models_config(
+        &mut self,
+        models_config_json: &str,
+    ) -> Result<IpcUpdateConfigResponse, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::UpdateModelsConfig(
+                IpcUpdateModelsConfigRequest {
+                    models_config_json: models_config_json.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::UpdateModelsConfigResponse(resp) => Ok(resp),
+            other => Err(unexpected_payload("UpdateModelsConfigResponse", &other)),
+        }
+    }
+
+    /// Adds a curated model, returning the curated list after the change.
+    pub async fn add_curated_model(
+        &mut self,
+        model: IpcCuratedModel,
+    ) -> Result<Vec<IpcCuratedModel>, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::AddCuratedModel(
+                IpcAddCuratedModelRequest { model: Some(model) },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::CuratedModelsResponse(resp) => Ok(resp.models),
+            other => Err(unexpected_payload("CuratedModelsResponse", &other)),
+        }
+    }
+
+    /// Removes a curated model, returning the curated list after the change.
+    pub async fn remove_curated_model(
+        &mut self,
+        provider: &str,
+        model_id: &str,
+    ) -> Result<Vec<IpcCuratedModel>, IpcError> {
+        match self
+            .request(ipc_client_message::Payload::RemoveCuratedModel(
+                IpcRemoveCuratedModelRequest {
+                    provider: provider.to_string(),
+                    model_id: model_id.to_string(),
+                },
+            ))
+            .await?
+        {
+            ipc_server_message::Payload::CuratedModelsResponse(resp) => Ok(resp.models),
+            other => Err(unexpected_payload("CuratedModelsResponse", &other)),
+        }
+    }
+
+    /// Subscribes to server state events for the rest of the connection.
+    ///
+    /// Once this returns, every change is delivered through [`next_event`](Self::next_event),
+    /// including those that arrive while other requests are awaited. The subscription is
+    /// renewed after a reconnect.
+    pub async fn subscribe_events(&mut self) -> Result<(), IpcError> {
+        self.ensure_connected().await?;
+        self.subscribe().await
+    }
+
+    /// Waits for the next server state event.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IpcError::Read`] if [`subscribe_events`](Self::subscribe_events) hasn't
+    /// succeeded, or [`IpcError::ConnectionLost`] if the connection drops (events may have
+    /// been missed; the next request reconnects and re-subscribes).
+    pub async fn next_event(&mut self) -> Result<IpcServerStateEvent, IpcError> {
+        if let Some(event) = self.pending_events.pop_front() {
+            return Ok(event);
+        }
+
+        let subscription_id = self.subscription_id.ok_or_else(|| IpcError::Read {
+            message: "Not subscribed to events".to_string(),
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+        match self.receive_response(subscription_id).await? {
+            ipc_server_message::Payload::ServerStateEvent(event) => Ok(event),
+            other => Err(unexpected_payload("ServerStateEvent", &other)),
+        }
+    }
+
+    /// Closes the connection with a WebSocket close frame.
+    pub async fn close(mut self) -> Result<(), IpcError> {
+        let Some(ws) = self.ws.as_mut() else {
+            return Ok(());
+        };
+        ws.close(None).await.map_err(|e| IpcError::Send {
+            message: format!("Failed to close connection: {e}"),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    /// Sends the subscribe request on the current connection, without reconnecting.
+    async fn subscribe(&mut self) -> Result<(), IpcError> {
+        let message = self.next_message(ipc_client_message::Payload::SubscribeEvents(
+            IpcSubscribeEventsRequest {},
+        ));
+        self.send(&message).await?;
+        match self.receive_response(message.request_id).await? {
+            ipc_server_message::Payload::SubscribeEventsResponse(_) => {
+                self.subscription_id = Some(message.request_id);
+                Ok(())
+            }
+            other => Err(unexpected_payload("SubscribeEventsResponse", &other)),
+        }
+    }
+
+    /// Reconnects first if the connection is known or found to be closed.
+    async fn ensure_connected(&mut self) -> Result<(), IpcError> {
+        self.drain_ready_frames();
+        if self.ws.is_none() {
+            self.reconnect().await?;
+        }
+        Ok(())
+    }
+
+    /// Handles frames that arrived while no request was waiting, noticing a connection
+    /// the server closed in the meantime.
+    fn drain_ready_frames(&mut self) {
+        while let Some(ws) = self.ws.as_mut() {
+            let Some(next) = ws.next().now_or_never() else {
+                return;
+            };
+            match next {
+                Some(Ok(Message::Binary(data))) => match IpcServerMessage::decode(&data[..]) {
+                    Ok(message) => self.buffer_or_skip(message, None),
+                    Err(e) => debug!("Skipping undecodable frame: {e}"),
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => self.connection_lost(),
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+
+    /// Redials and re-authenticates with backoff, renewing the event subscription.
+    async fn reconnect(&mut self) -> Result<(), IpcError> {
+        let Some(policy) = self.reconnect.clone() else {
+            return Err(IpcError::ConnectionLost {
+                message: "Connection lost and reconnection is disabled".to_string(),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        };
+
+        let mut backoff = policy.backoff();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            self.status
+                .send_replace(ConnectionStatus::Reconnecting { attempt });
+
+            let result = match self.open().await {
+                Ok(()) if self.subscription_id.is_some() => self.subscribe().await,
+                result => result,
+            };
+            match result {
+                Ok(()) => {
+                    info!(
+                        "Reconnected to IPC server on port {} (attempt {attempt})",
+                        self.port
+                    );
+                    self.status.send_replace(ConnectionStatus::Connected);
+                    return Ok(());
+                }
+                // A rejected token or protocol version won't be accepted on retry
+                Err(e @ IpcError::Auth { .. }) => {
+                    self.connection_lost();
+                    return Err(e);
+                }
+                Err(e) => match backoff.next_backoff() {
+                    Some(delay) => {
+                        debug!("Reconnect attempt {attempt} failed, retrying in {delay:?}: {e}");
+                        sleep(delay).await;
+                    }
+                    None => {
+                        self.connection_lost();
+                        return Err(IpcError::ConnectionLost {
+                            message: format!(
+                                "Failed to reconnect to port {} after {attempt} attempts: {e}",
+                                self.port
+                            ),
+                            location: ErrorLocation::from(Location::caller()),
+                        });
+                    }
+                },
+            }
+        }
+    }
+
+    /// Forgets the current connection.
+    fn connection_lost(&mut self) {
+        if self.ws.take().is_some() {
+            warn!("Lost connection to IPC server on port {}", self.port);
+        }
+        self.status.send_replace(ConnectionStatus::Disconnected);
+    }
+
+    /// Wraps `payload` in a message with a fresh request ID.
+    fn next_message(&mut self, payload: ipc_client_message::Payload) -> IpcClientMessage {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+
+        IpcClientMessage {
+            request_id,
+            payload: Some(payload),
+        }
+    }
+
+    /// Sends `payload` under a fresh request ID, returning that ID.
+    ///
+    /// Reconnects first if needed, and once more if the send itself fails: the request
+    /// didn't go out whole, so the server can't have handled it.
+    async fn send_request(
+        &mut self,
+        payload: ipc_client_message::Payload,
+    ) -> Result<u64, IpcError> {
+        self.ensure_connected().await?;
+
+        let message = self.next_message(payload);
+        match self.send(&message).await {
+            Err(IpcError::ConnectionLost { .. }) => {
+                self.reconnect().await?;
+                self.send(&message).await?;
+            }
+            result => result?,
+        }
+        Ok(message.request_id)
+    }
+
+    /// Waits for the next frame for `request_id`, mapping server errors to [`IpcError::Remote`].
+    async fn receive_response(
+        &mut self,
+        request_id: u64,
+    ) -> Result<ipc_server_message::Payload, IpcError> {
+        match self.receive(request_id).await? {
+            ipc_server_message::Payload::Error(err) => Err(IpcError::from(err)),
+            payload => Ok(payload),
+        }
+    }
+
+    async fn send(&mut self, message: &IpcClientMessage) -> Result<(), IpcError> {
+        let mut buf = Vec::new();
+        message.encode(&mut buf)?;
+
+        let Some(ws) = self.ws.as_mut() else {
+            return Err(not_connected());
+        };
+        match ws.send(Message::Binary(buf.into())).await {
+            Ok(()) => Ok(()),
+            Err(e @ WsError::Capacity(_)) => Err(IpcError::Send {
+                message: format!("Failed to send request {}: {e}", message.request_id),
+                location: ErrorLocation::from(Location::caller()),
+            }),
+            Err(e) => {
+                self.connection_lost();
+                Err(IpcError::ConnectionLost {
+                    message: format!("Failed to send request {}: {e}", message.request_id),
+                    location: ErrorLocation::from(Location::caller()),
+                })
+            }
+        }
+    }
+
+    async fn receive(&mut self, request_id: u64) -> Result<ipc_server_message::Payload, IpcError> {
+        loop {
+            let Some(ws) = self.ws.as_mut() else {
+                return Err(not_connected());
+            };
+            let frame = match ws.next().await {
+                Some(Ok(frame)) => frame,
+                Some(Err(e)) => {
+                    self.connection_lost();
+                    return Err(IpcError::ConnectionLost {
+                        message: format!("Error reading response to request {request_id}: {e}"),
+                        location: ErrorLocation::from(Location::caller()),
+                    });
+                }
+                None => {
+                    self.connection_lost();
+                    return Err(IpcError::ConnectionLost {
+                        message: format!(
+                            "Connection closed while waiting for request {request_id}"
+                        ),
+                        location: ErrorLocation::from(Location::caller()),
+                    });
+                }
+            };
+
+            let data = match frame {
+                Message::Binary(data) => data,
+                Message::Close(_) => {
+                    self.connection_lost();
+                    return Err(IpcError::ConnectionLost {
+                        message: format!(
+                            "Server closed the connection while waiting for request {request_id}"
+                        ),
+                        location: ErrorLocation::from(Location::caller()),
+                    });
+                }
+                // Pings are answered by tungstenite; other frames are not part of the protocol
+                _ => continue,
+            };
+
+            let response = IpcServerMessage::decode(&data[..])?;
+            if response.request_id != request_id {
+                self.buffer_or_skip(response, Some(request_id));
+                continue;
+            }
+
+            return response.payload.ok_or_else(|| IpcError::Read {
+                message: format!("Response to request {request_id} has no payload"),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+    }
+
+    /// Keeps `message` if it is an event for our subscription, otherwise drops it.
+    fn buffer_or_skip(&mut self, message: IpcServerMessage, waiting_for: Option<u64>) {
+        if Some(message.request_id) == self.subscription_id
+            && let Some(ipc_server_message::Payload::ServerStateEvent(event)) = message.payload
+        {
+            self.pending_events.push_back(event);
+            return;
+        }
+        debug!(
+            "Skipping response for request {} while waiting for {:?}",
+            message.request_id, waiting_for
+        );
+    }
+}
+
+#[track_caller]
+fn not_connected() -> IpcError {
+    IpcError::ConnectionLost {
+        message: "Not connected".to_string(),
+        location: ErrorLocation::from(Location::caller()),
+    }
+}
+
+#[track_caller]
+fn unexpected_payload(expected: &str, actual: &ipc_server_message::Payload) -> IpcError {
+    IpcError::Read {
+        message: format!("Expected {expected}, got {actual:?}"),
+        location: ErrorLocation::from(Location::caller()),
+    }
+}
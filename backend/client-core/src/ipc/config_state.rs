@@ -12,27 +12,95 @@
 //!
 //! # Why Separate from IpcState?
 //!
-//! - Config is loaded once at startup (different lifecycle than server connection)
+//! - Config is loaded at startup and optionally reloaded on external edits
+//!   (different lifecycle than server connection)
 //! - Config paths come from Tauri (not runtime-discovered)
 //! - Config needs validation before updates
 
-use crate::config::{AppConfig, ModelsConfig};
+use crate::config::models::CuratedModel;
+use crate::config::{AppConfig, CONFIG_FILE_NAME, ModelsConfig};
+use crate::error::config::ConfigError;
 use crate::error::ipc::IpcError;
+use crate::ipc::events::{self, event_channel};
+use crate::proto::IpcServerStateEvent;
 
 use common::ErrorLocation;
 
 use std::panic::Location;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use log::{error, info, warn};
-use tokio::sync::{Mutex, RwLock, mpsc};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::Value;
+use tokio::sync::{Mutex, RwLock, broadcast, mpsc, oneshot};
 
 /// Commands that mutate config state.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum ConfigCommand {
-    /// Update app config (validates, updates memory, saves to disk)
-    UpdateAppConfig(AppConfig),
+    /// Update app config (validates, updates memory, saves to disk), reply with the outcome
+    ///
+    /// A validation failure leaves the config untouched. A save failure still updates
+    /// memory but is reported, since the change won't survive a restart.
+    UpdateAppConfig {
+        config: AppConfig,
+        reply: oneshot::Sender<Result<(), ConfigError>>,
+    },
+
+    /// Replace the app config value at a dotted key path, reply with the new value
+    ///
+    /// Applied to the current config inside the actor, so concurrent updates aren't lost.
+    /// Validated and saved like [`UpdateAppConfig`](Self::UpdateAppConfig).
+    SetAppConfigValue {
+        key_path: String,
+        value: Value,
+        reply: oneshot::Sender<Result<Value, ConfigError>>,
+    },
+
+    /// Reset an app config section (empty = whole config) to defaults, reply with the new config
+    ///
+    /// Validated and saved like [`UpdateAppConfig`](Self::UpdateAppConfig).
+    ResetAppConfig {
+        section: String,
+        reply: oneshot::Sender<Result<AppConfig, ConfigError>>,
+    },
+
+    /// Update models config (validates, saves models.toml, updates memory), reply with the
+    /// outcome
+    ///
+    /// Memory is only updated once models.toml is written, so a failed save leaves both
+    /// unchanged.
+    UpdateModelsConfig {
+        config: ModelsConfig,
+        reply: oneshot::Sender<Result<(), ConfigError>>,
+    },
+
+    /// Add a curated model (deduplicated), save models.toml, reply with the curated list
+    ///
+    /// Saved and applied like [`UpdateModelsConfig`](Self::UpdateModelsConfig).
+    AddCuratedModel {
+        model: CuratedModel,
+        reply: oneshot::Sender<Result<Vec<CuratedModel>, ConfigError>>,
+    },
+
+    /// Remove a curated model (no-op if absent), save models.toml, reply with the curated list
+    ///
+    /// Saved and applied like [`UpdateModelsConfig`](Self::UpdateModelsConfig).
+    RemoveCuratedModel {
+        provider: String,
+        model_id: String,
+        reply: oneshot::Sender<Result<Vec<CuratedModel>, ConfigError>>,
+    },
+
+    /// Re-read config.json after an external edit (validates, updates memory only)
+    ReloadFromDisk,
+}
+
+/// Keeps a config.json file watcher alive.
+///
+/// Returned by [`ConfigState::watch_config_file`]; dropping it stops watching.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
 }
 
 /// Config state manager for IPC server.
@@ -53,11 +121,14 @@ pub struct ConfigState {
     /// Shared read-only access to models config
     models_config: Arc<RwLock<ModelsConfig>>,
 
-    /// Config directory path (for saving)
+    /// Config directory path (for saving config.json and models.toml)
     config_dir: Arc<PathBuf>,
 
     /// Track if actor initialized
     actor_init: Arc<Mutex<bool>>,
+
+    /// App config change events for subscribed clients
+    events: broadcast::Sender<IpcServerStateEvent>,
 }
 
 impl ConfigState {
@@ -65,7 +136,7 @@ impl ConfigState {
     ///
     /// # Arguments
     ///
-    /// * `config_dir` - Directory for config.json (from Tauri `app_config_dir()`)
+    /// * `config_dir` - Directory for config.json and saved models.toml (from Tauri `app_config_dir()`)
     /// * `app_config` - Initial app config (loaded at startup)
     /// * `models_config` - Initial models config (loaded at startup)
     pub fn new(config_dir: PathBuf, app_config: AppConfig, models_config: ModelsConfig) -> Self {
@@ -75,6 +146,7 @@ impl ConfigState {
             models_config: Arc::new(RwLock::new(models_config)),
             config_dir: Arc::new(config_dir),
             actor_init: Arc::new(Mutex::new(false)),
+            events: event_channel(),
         }
     }
 
@@ -96,6 +168,104 @@ impl ConfigState {
         })
     }
 
+    /// Replace the app config, validating it and saving it to config.json.
+    ///
+    /// # Errors
+    ///
+    /// The outer [`IpcError`] means the actor couldn't be reached. The inner
+    /// [`ConfigError`] is the update's own outcome: a `ValidationError` if the config was
+    /// rejected, or a write error if it was applied but not saved.
+    pub async fn update_app_config(
+        &self,
+        config: AppConfig,
+    ) -> Result<Result<(), ConfigError>, IpcError> {
+        let (reply, rx) = oneshot::channel();
+        self.update(ConfigCommand::UpdateAppConfig { config, reply })
+            .await?;
+        rx.await.map_err(|e| IpcError::Io {
+            message: format!("Config actor dropped reply: {}", e),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    /// Replace the app config value at a dotted key path (e.g. `ui.base_font_points`).
+    ///
+    /// Returns the value now stored at the path.
+    ///
+    /// # Errors
+    ///
+    /// The outer [`IpcError`] means the actor couldn't be reached. The inner
+    /// [`ConfigError`] is `UnknownKey` for a missing path, `ValidationError` if the value
+    /// was rejected, or a write error if it was applied but not saved.
+    pub async fn set_app_config_value(
+        &self,
+        key_path: &str,
+        value: Value,
+    ) -> Result<Result<Value, ConfigError>, IpcError> {
+        let (reply, rx) = oneshot::channel();
+        self.update(ConfigCommand::SetAppConfigValue {
+            key_path: key_path.to_string(),
+            value,
+            reply,
+        })
+        .await?;
+        rx.await.map_err(|e| IpcError::Io {
+            message: format!("Config actor dropped reply: {}", e),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    /// Reset an app config section (`server`, `ui`, `audio`, or empty for everything)
+    /// to its defaults, saving the result to config.json.
+    ///
+    /// Returns the new app config.
+    ///
+    /// # Errors
+    ///
+    /// The outer [`IpcError`] means the actor couldn't be reached. The inner
+    /// [`ConfigError`] is `UnknownKey` for an unknown section, or a write error if the
+    /// reset was applied but not saved.
+    pub async fn reset_app_config(
+        &self,
+        section: &str,
+    ) -> Result<Result<AppConfig, ConfigError>, IpcError> {
+        let (reply, rx) = oneshot::channel();
+        self.update(ConfigCommand::ResetAppConfig {
+            section: section.to_string(),
+            reply,
+        })
+        .await?;
+        rx.await.map_err(|e| IpcError::Io {
+            message: format!("Config actor dropped reply: {}", e),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    /// Replace the models config, validating it and saving it to models.toml.
+    ///
+    /// # Errors
+    ///
+    /// The outer [`IpcError`] means the actor couldn't be reached. The inner
+    /// [`ConfigError`] is a `ValidationError` if the config was rejected, or a write error
+    /// if models.toml couldn't be saved; either way the current config is kept.
+    pub async fn update_models_config(
+        &self,
+        config: ModelsConfig,
+    ) -> Result<Result<(), ConfigError>, IpcError> {
+        let (reply, rx) = oneshot::channel();
+        self.update(ConfigCommand::UpdateModelsConfig { config, reply })
+            .await?;
+        rx.await.map_err(|e| IpcError::Io {
+            message: format!("Config actor dropped reply: {}", e),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    /// Subscribe to app config changes (IPC updates and external edits of config.json).
+    pub fn subscribe_events(&self) -> broadcast::Receiver<IpcServerStateEvent> {
+        self.events.subscribe()
+    }
+
     /// Get current app config (read-only).
     pub async fn get_app_config(&self) -> AppConfig {
         self.app_config.read().await.clone()
@@ -106,6 +276,106 @@ impl ConfigState {
         self.models_config.read().await.clone()
     }
 
+    /// Add a curated model and return the updated curated list.
+    ///
+    /// Duplicates (same provider + model_id) are ignored. The change is saved to models.toml.
+    ///
+    /// # Errors
+    ///
+    /// The outer [`IpcError`] means the actor couldn't be reached. The inner
+    /// [`ConfigError`] means models.toml couldn't be saved; the current list is kept.
+    pub async fn add_curated_model(
+        &self,
+        model: CuratedModel,
+    ) -> Result<Result<Vec<CuratedModel>, ConfigError>, IpcError> {
+        let (reply, rx) = oneshot::channel();
+        self.update(ConfigCommand::AddCuratedModel { model, reply })
+            .await?;
+        rx.await.map_err(|e| IpcError::Io {
+            message: format!("Config actor dropped reply: {}", e),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    /// Remove a curated model and return the updated curated list.
+    ///
+    /// Removing a model that isn't curated is a no-op. The change is saved to models.toml.
+    ///
+    /// # Errors
+    ///
+    /// The outer [`IpcError`] means the actor couldn't be reached. The inner
+    /// [`ConfigError`] means models.toml couldn't be saved; the current list is kept.
+    pub async fn remove_curated_model(
+        &self,
+        provider: &str,
+        model_id: &str,
+    ) -> Result<Result<Vec<CuratedModel>, ConfigError>, IpcError> {
+        let (reply, rx) = oneshot::channel();
+        self.update(ConfigCommand::RemoveCuratedModel {
+            provider: provider.to_string(),
+            model_id: model_id.to_string(),
+            reply,
+        })
+        .await?;
+        rx.await.map_err(|e| IpcError::Io {
+            message: format!("Config actor dropped reply: {}", e),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    /// Watch config.json for external edits and reload it into memory.
+    ///
+    /// Each change to `config.json` queues a [`ConfigCommand::ReloadFromDisk`].
+    /// Events for our own temp file (`config.json.tmp`) are ignored, and a reload
+    /// that matches the in-memory config (e.g. after our own `save`) is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IpcError::Io`] if the config directory can't be created or watched.
+    pub async fn watch_config_file(&self) -> Result<ConfigWatcher, IpcError> {
+        self.ensure_actor().await;
+
+        let tx = self
+            .command_tx
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| IpcError::Io {
+                message: "Config actor not initialized".to_string(),
+                location: ErrorLocation::from(Location::caller()),
+            })?;
+
+        std::fs::create_dir_all(self.config_dir.as_path())?;
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            match res {
+                Ok(event) if is_config_file_change(&event) => {
+                    // A full queue already holds a pending command; the actor re-reads anyway
+                    let _ = tx.try_send(ConfigCommand::ReloadFromDisk);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Config watcher error: {}", e),
+            }
+        })
+        .map_err(|e| IpcError::Io {
+            message: format!("Failed to create config watcher: {e}"),
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+
+        watcher
+            .watch(self.config_dir.as_path(), RecursiveMode::NonRecursive)
+            .map_err(|e| IpcError::Io {
+                message: format!(
+                    "Failed to watch config directory {}: {e}",
+                    self.config_dir.display()
+                ),
+                location: ErrorLocation::from(Location::caller()),
+            })?;
+
+        info!("Watching {} for config changes", self.config_dir.display());
+        Ok(ConfigWatcher { _watcher: watcher })
+    }
+
     /// Ensure actor is spawned (lazy init).
     async fn ensure_actor(&self) {
         let mut init_guard = self.actor_init.lock().await;
@@ -114,6 +384,7 @@ impl ConfigState {
             let app_config_clone = Arc::clone(&self.app_config);
             let models_config_clone = Arc::clone(&self.models_config);
             let config_dir_clone = Arc::clone(&self.config_dir);
+            let events_clone = self.events.clone();
 
             // Store tx BEFORE spawning
             let mut tx_guard = self.command_tx.lock().await;
@@ -125,6 +396,7 @@ impl ConfigState {
                 app_config_clone,
                 models_config_clone,
                 config_dir_clone,
+                events_clone,
             ));
 
             *init_guard = true;
@@ -139,31 +411,110 @@ impl ConfigState {
 async fn config_actor(
     mut command_rx: mpsc::Receiver<ConfigCommand>,
     app_config: Arc<RwLock<AppConfig>>,
-    _models_config: Arc<RwLock<ModelsConfig>>, // Future: UpdateModelsConfig command
+    models_config: Arc<RwLock<ModelsConfig>>,
     config_dir: Arc<PathBuf>,
+    events: broadcast::Sender<IpcServerStateEvent>,
 ) {
     info!("Config state actor started");
 
     while let Some(cmd) = command_rx.recv().await {
         match cmd {
-            ConfigCommand::UpdateAppConfig(new_config) => {
-                // Validate first (before any changes)
-                if let Err(e) = new_config.validate() {
-                    error!("Config validation failed: {}", e);
-                    continue;
-                }
-
-                // Update memory first (can't fail)
-                {
-                    let mut app_config_write = app_config.write().await;
-                    *app_config_write = new_config.clone();
+            ConfigCommand::UpdateAppConfig {
+                config: new_config,
+                reply,
+            } => {
+                let _ = reply
+                    .send(apply_app_config(&app_config, &config_dir, &events, new_config).await);
+            }
+            ConfigCommand::SetAppConfigValue {
+                key_path,
+                value,
+                reply,
+            } => {
+                let current = app_config.read().await.clone();
+                let result = match current.with_value(&key_path, value) {
+                    Ok(new_config) => {
+                        let stored = new_config.get_value(&key_path);
+                        apply_app_config(&app_config, &config_dir, &events, new_config)
+                            .await
+                            .and(stored)
+                    }
+                    Err(e) => {
+                        error!("Config value '{}' rejected: {}", key_path, e);
+                        Err(e)
+                    }
+                };
+                let _ = reply.send(result);
+            }
+            ConfigCommand::ResetAppConfig { section, reply } => {
+                let mut new_config = app_config.read().await.clone();
+                let result = match new_config.reset_section(&section) {
+                    Ok(()) => {
+                        apply_app_config(&app_config, &config_dir, &events, new_config.clone())
+                            .await
+                            .map(|()| new_config)
+                    }
+                    Err(e) => {
+                        error!("Config reset rejected: {}", e);
+                        Err(e)
+                    }
+                };
+                let _ = reply.send(result);
+            }
+            ConfigCommand::UpdateModelsConfig {
+                config: new_config,
+                reply,
+            } => {
+                let mut models_config_write = models_config.write().await;
+                // save() validates first, so a rejected config is never written
+                let result = save_models_config(&new_config, &config_dir);
+                if result.is_ok() {
+                    *models_config_write = new_config;
+                    info!("Models config updated in memory");
                 }
-                info!("App config updated in memory");
+                let _ = reply.send(result);
+            }
+            ConfigCommand::AddCuratedModel { model, reply } => {
+                let mut models_config_write = models_config.write().await;
+                let mut new_config = models_config_write.clone();
+                new_config.add_curated_model(model);
+                let result = save_models_config(&new_config, &config_dir).map(|()| {
+                    *models_config_write = new_config;
+                    models_config_write.get_curated_models().to_vec()
+                });
+                let _ = reply.send(result);
+            }
+            ConfigCommand::RemoveCuratedModel {
+                provider,
+                model_id,
+                reply,
+            } => {
+                let mut models_config_write = models_config.write().await;
+                let mut new_config = models_config_write.clone();
+                new_config.remove_curated_model(&provider, &model_id);
+                let result = save_models_config(&new_config, &config_dir).map(|()| {
+                    *models_config_write = new_config;
+                    models_config_write.get_curated_models().to_vec()
+                });
+                let _ = reply.send(result);
+            }
+            ConfigCommand::ReloadFromDisk => {
+                // load() validates; keep the current config if the edit is invalid
+                let reloaded = match AppConfig::load(&config_dir) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        warn!("Ignoring external config change: {}", e);
+                        continue;
+                    }
+                };
 
-                // Then persist (if this fails, memory still updated)
-                match new_config.save(&config_dir) {
-                    Ok(_) => info!("App config saved to disk"),
-                    Err(e) => error!("App config saved to memory but disk write failed: {}", e),
+                let mut app_config_write = app_config.write().await;
+                if *app_config_write != reloaded {
+                    *app_config_write = reloaded;
+                    info!("App config reloaded from disk");
+                    if let Some(event) = events::config_updated(&app_config_write) {
+                        events::publish(&events, event);
+                    }
                 }
             }
         }
@@ -171,3 +522,54 @@ async fn config_actor(
 
     warn!("Config state actor stopped - this should not happen during normal operation");
 }
+
+/// Validate `new_config`, replace the app config in memory, and save it to disk.
+///
+/// A validation failure leaves the config untouched. A save failure still updates memory
+/// (and notifies subscribers) but is returned, since the change won't survive a restart.
+async fn apply_app_config(
+    app_config: &RwLock<AppConfig>,
+    config_dir: &Path,
+    events: &broadcast::Sender<IpcServerStateEvent>,
+    new_config: AppConfig,
+) -> Result<(), ConfigError> {
+    // Validate first (before any changes)
+    if let Err(e) = new_config.validate() {
+        error!("Config validation failed: {}", e);
+        return Err(e);
+    }
+
+    // Update memory first (can't fail)
+    *app_config.write().await = new_config.clone();
+    info!("App config updated in memory");
+    if let Some(event) = events::config_updated(&new_config) {
+        events::publish(events, event);
+    }
+
+    // Then persist (if this fails, memory still updated)
+    let result = new_config.save(config_dir);
+    match &result {
+        Ok(_) => info!("App config saved to disk"),
+        Err(e) => error!("App config saved to memory but disk write failed: {}", e),
+    }
+    result
+}
+
+/// Save `models_config` to models.toml, before it replaces the config in memory.
+fn save_models_config(models_config: &ModelsConfig, config_dir: &Path) -> Result<(), ConfigError> {
+    let result = models_config.save(config_dir);
+    match &result {
+        Ok(_) => info!("Models config saved to disk"),
+        Err(e) => error!("Models config not saved: {}", e),
+    }
+    result
+}
+
+/// Does this filesystem event change config.json itself (not our temp file)?
+fn is_config_file_change(event: &Event) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|path| {
+            path.file_name()
+                .is_some_and(|name| name == CONFIG_FILE_NAME)
+        })
+}
@@ -1,34 +1,194 @@
 //! Connection state tracking for authentication.
 //!
-//! This module provides per-connection state to track whether a client
-//! has successfully authenticated with the IPC server.
+//! This module provides per-connection state to track where a client is in the
+//! auth lifecycle ([`ConnectionPhase`]) and which protocol version was negotiated.
+//! Every phase change is logged with the client's address, so a flaky frontend
+//! connection can be followed from handshake to close in `opencode.log`.
+//!
+//! Phases are also published to a [`ConnectionPhases`] registry shared with the
+//! [`IpcServerHandle`](crate::ipc::IpcServerHandle), so callers outside the crate can
+//! observe the auth lifecycle of live connections.
+
+use crate::ipc::auth_token::IpcAuthToken;
+use crate::ipc::protocol::negotiate_version;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use log::info;
+
+/// Where a connection is in its auth lifecycle.
+///
+/// Connections start in `AwaitingAuth`, move to `Authenticated` after one successful
+/// handshake, and end in `Closing`. `Closing` is final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionPhase {
+    /// Connected, but no valid handshake yet; only a handshake is accepted.
+    AwaitingAuth,
+    /// Handshake accepted; requests are served.
+    Authenticated,
+    /// The server is closing the connection (failed auth, timeout, or client close).
+    Closing,
+}
+
+/// Why a handshake was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HandshakeRejection {
+    /// The connection isn't awaiting auth (already authenticated, or closing).
+    UnexpectedHandshake(ConnectionPhase),
+    /// The token doesn't match the server's.
+    InvalidToken,
+    /// The client's protocol version isn't supported.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for HandshakeRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandshakeRejection::UnexpectedHandshake(phase) => {
+                write!(f, "handshake not expected while {phase:?}")
+            }
+            HandshakeRejection::InvalidToken => write!(f, "invalid token"),
+            HandshakeRejection::UnsupportedVersion(version) => {
+                write!(f, "unsupported protocol version {version}")
+            }
+        }
+    }
+}
+
+/// Current phase of every live connection, keyed by client address.
+///
+/// Cloning shares the registry. Entries are added when a [`ConnectionState`] is created
+/// and removed when it is dropped.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectionPhases {
+    phases: Arc<Mutex<HashMap<SocketAddr, ConnectionPhase>>>,
+}
+
+impl ConnectionPhases {
+    /// Phases of all live connections, ordered by client address.
+    pub(crate) fn snapshot(&self) -> Vec<(SocketAddr, ConnectionPhase)> {
+        let mut phases: Vec<_> = self
+            .lock()
+            .iter()
+            .map(|(peer, phase)| (*peer, *phase))
+            .collect();
+        phases.sort_by_key(|(peer, _)| *peer);
+        phases
+    }
+
+    fn set(&self, peer: SocketAddr, phase: ConnectionPhase) {
+        self.lock().insert(peer, phase);
+    }
+
+    fn remove(&self, peer: SocketAddr) {
+        self.lock().remove(&peer);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<SocketAddr, ConnectionPhase>> {
+        // A panic while holding the lock can't leave the map half-updated
+        self.phases.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
 
 /// Connection state for auth tracking.
 ///
-/// Tracks whether a connection has been authenticated and what token is expected.
+/// Tracks the connection's [`ConnectionPhase`], what token is expected, and the protocol
+/// version negotiated during the handshake.
 pub(crate) struct ConnectionState {
-    authenticated: bool,
-    expected_token: String,
+    peer: SocketAddr,
+    phase: ConnectionPhase,
+    expected_token: IpcAuthToken,
+    protocol_version: Option<u32>,
+    registry: ConnectionPhases,
 }
 
 impl ConnectionState {
-    /// Create new connection state with expected token.
-    pub(crate) fn new(token: String) -> Self {
+    /// Create state for a new connection from `peer`, awaiting a handshake with `token`.
+    ///
+    /// The connection is listed in `registry` until this state is dropped.
+    pub(crate) fn new(token: IpcAuthToken, peer: SocketAddr, registry: ConnectionPhases) -> Self {
+        registry.set(peer, ConnectionPhase::AwaitingAuth);
         Self {
-            authenticated: false,
+            peer,
+            phase: ConnectionPhase::AwaitingAuth,
             expected_token: token,
+            protocol_version: None,
+            registry,
         }
     }
 
-    /// Validate token and mark as authenticated if correct.
+    /// Address of the client.
+    pub(crate) fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Current phase of the connection.
+    pub(crate) fn phase(&self) -> ConnectionPhase {
+        self.phase
+    }
+
+    /// Protocol version negotiated during the handshake (`None` until authenticated).
+    pub(crate) fn protocol_version(&self) -> Option<u32> {
+        self.protocol_version
+    }
+
+    /// Handle a handshake: check the token, negotiate the protocol version, and move
+    /// to [`ConnectionPhase::Authenticated`].
     ///
-    /// Returns true if token matches, false otherwise.
-    pub(crate) fn validate_token(&mut self, token: &str) -> bool {
-        if token == self.expected_token {
-            self.authenticated = true;
-            true
-        } else {
-            false
+    /// Only a connection awaiting auth accepts a handshake; any later one is refused
+    /// without changing the phase. A refused first handshake moves the connection to
+    /// [`ConnectionPhase::Closing`].
+    ///
+    /// Returns the negotiated protocol version.
+    pub(crate) fn authenticate(
+        &mut self,
+        token: &str,
+        client_version: u32,
+    ) -> Result<u32, HandshakeRejection> {
+        if self.phase != ConnectionPhase::AwaitingAuth {
+            return Err(HandshakeRejection::UnexpectedHandshake(self.phase));
+        }
+
+        if token != self.expected_token.as_str() {
+            self.close("invalid token");
+            return Err(HandshakeRejection::InvalidToken);
+        }
+
+        let Some(version) = negotiate_version(client_version) else {
+            self.close("unsupported protocol version");
+            return Err(HandshakeRejection::UnsupportedVersion(client_version));
+        };
+
+        self.protocol_version = Some(version);
+        self.transition(
+            ConnectionPhase::Authenticated,
+            &format!("protocol v{version}"),
+        );
+        Ok(version)
+    }
+
+    /// Move to [`ConnectionPhase::Closing`], logging `reason`. Does nothing if already closing.
+    pub(crate) fn close(&mut self, reason: &str) {
+        if self.phase != ConnectionPhase::Closing {
+            self.transition(ConnectionPhase::Closing, reason);
         }
     }
+
+    fn transition(&mut self, to: ConnectionPhase, reason: &str) {
+        info!(
+            "Client {}: {:?} -> {:?} ({reason})",
+            self.peer, self.phase, to
+        );
+        self.phase = to;
+        self.registry.set(self.peer, to);
+    }
+}
+
+impl Drop for ConnectionState {
+    fn drop(&mut self) {
+        self.registry.remove(self.peer);
+    }
 }
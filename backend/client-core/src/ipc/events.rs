@@ -0,0 +1,61 @@
+//! State change events pushed to subscribed IPC clients.
+//!
+//! The state and config actors publish an [`IpcServerStateEvent`] on a broadcast channel
+//! whenever they change something a client may be showing (active server, health, app
+//! config). Each `subscribe_events` request gets its own receiver; events published while
+//! nobody is subscribed are dropped.
+
+use crate::config::AppConfig;
+use crate::proto::{
+    IpcConfigUpdatedEvent, IpcHealthChangedEvent, IpcServerClearedEvent, IpcServerInfo,
+    IpcServerStateEvent, ipc_server_state_event,
+};
+
+use log::error;
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before the slowest one starts missing events.
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Create the broadcast channel an actor publishes its events on.
+pub(crate) fn event_channel() -> broadcast::Sender<IpcServerStateEvent> {
+    broadcast::channel(EVENT_CHANNEL_CAPACITY).0
+}
+
+/// Publish `event` to current subscribers (a no-op if there are none).
+pub(crate) fn publish(events: &broadcast::Sender<IpcServerStateEvent>, event: IpcServerStateEvent) {
+    let _ = events.send(event);
+}
+
+pub(crate) fn server_set(server: IpcServerInfo) -> IpcServerStateEvent {
+    event(ipc_server_state_event::Event::ServerSet(server))
+}
+
+pub(crate) fn server_cleared(server_id: String) -> IpcServerStateEvent {
+    event(ipc_server_state_event::Event::ServerCleared(
+        IpcServerClearedEvent { server_id },
+    ))
+}
+
+pub(crate) fn health_changed(server_id: String, healthy: bool) -> IpcServerStateEvent {
+    event(ipc_server_state_event::Event::HealthChanged(
+        IpcHealthChangedEvent { server_id, healthy },
+    ))
+}
+
+/// Event for an app config change, or `None` if the config can't be serialized.
+pub(crate) fn config_updated(config: &AppConfig) -> Option<IpcServerStateEvent> {
+    match serde_json::to_string(config) {
+        Ok(app_config_json) => Some(event(ipc_server_state_event::Event::ConfigUpdated(
+            IpcConfigUpdatedEvent { app_config_json },
+        ))),
+        Err(e) => {
+            error!("Failed to serialize app config for event: {}", e);
+            None
+        }
+    }
+}
+
+fn event(event: ipc_server_state_event::Event) -> IpcServerStateEvent {
+    IpcServerStateEvent { event: Some(event) }
+}
@@ -3,6 +3,18 @@
 //! This module defines the handle returned when starting an IPC server.
 //! The handle represents the running server and can be used for lifecycle management.
 
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use log::{info, warn};
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::ipc::auth_token::IpcAuthToken;
+use crate::ipc::connection_state::{ConnectionPhase, ConnectionPhases};
+use crate::ipc::metrics::IpcMetrics;
+
 /// Handle to a running IPC WebSocket server.
 ///
 /// This handle is returned by [`start_ipc_server`](crate::ipc::start_ipc_server) and represents
@@ -10,12 +22,96 @@
 ///
 /// # Lifecycle
 ///
-/// Currently, dropping this handle does **not** stop the server. The server runs until
-/// the process exits. Future versions will implement graceful shutdown via `Drop`.
-///
-/// # Future Enhancements
-///
-/// - Graceful shutdown on drop
-/// - Query server statistics (connection count, message count)
-/// - Programmatic port discovery
-pub struct IpcServerHandle {}
+/// Dropping this handle does **not** stop the server; it runs until [`shutdown`](Self::shutdown)
+/// is called or the process exits.
+pub struct IpcServerHandle {
+    port: u16,
+    shutdown_token: CancellationToken,
+    accept_task: JoinHandle<()>,
+    auth_token: Arc<RwLock<IpcAuthToken>>,
+    metrics: IpcMetrics,
+    connection_slots: Arc<Semaphore>,
+    max_connections: usize,
+    phases: ConnectionPhases,
+}
+
+impl IpcServerHandle {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        port: u16,
+        shutdown_token: CancellationToken,
+        accept_task: JoinHandle<()>,
+        auth_token: Arc<RwLock<IpcAuthToken>>,
+        metrics: IpcMetrics,
+        connection_slots: Arc<Semaphore>,
+        max_connections: usize,
+        phases: ConnectionPhases,
+    ) -> Self {
+        Self {
+            port,
+            shutdown_token,
+            accept_task,
+            auth_token,
+            metrics,
+            connection_slots,
+            max_connections,
+            phases,
+        }
+    }
+
+    /// The port the server is bound to.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Is the server still accepting connections?
+    ///
+    /// `false` once shutdown was requested or the accept loop ended on its own (e.g. after
+    /// a fatal accept error).
+    pub fn is_running(&self) -> bool {
+        !self.shutdown_token.is_cancelled() && !self.accept_task.is_finished()
+    }
+
+    /// Number of connections currently being served (authenticated or still handshaking).
+    pub fn connection_count(&self) -> usize {
+        self.max_connections
+            .saturating_sub(self.connection_slots.available_permits())
+    }
+
+    /// Auth phase of each connection that has sent its first message, by client address.
+    ///
+    /// Connections still in the WebSocket handshake are counted by
+    /// [`connection_count`](Self::connection_count) but not listed here.
+    pub fn connection_phases(&self) -> Vec<(SocketAddr, ConnectionPhase)> {
+        self.phases.snapshot()
+    }
+
+    /// Request counters for every connection of this server.
+    pub fn metrics(&self) -> &IpcMetrics {
+        &self.metrics
+    }
+
+    /// Replaces the auth token with a freshly generated one and returns it.
+    ///
+    /// Only handshakes made after this call must use the new token; connections that
+    /// are already authenticated stay open.
+    pub async fn rotate_auth_token(&self) -> IpcAuthToken {
+        let new_token = IpcAuthToken::generate();
+        *self.auth_token.write().await = new_token.clone();
+        info!("IPC auth token rotated to {}", new_token);
+        new_token
+    }
+
+    /// Stops accepting new connections and releases the listening port.
+    ///
+    /// Returns once the listener has been dropped, so the port can be rebound immediately.
+    /// Connections that are already established are left to drain on their own.
+    pub async fn shutdown(self) {
+        info!("Shutting down IPC server");
+        self.shutdown_token.cancel();
+
+        if let Err(e) = self.accept_task.await {
+            warn!("IPC accept loop ended abnormally: {}", e);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-server counters for IPC requests.
+//!
+//! Every request dispatched by the server is counted by message type, with its handler
+//! duration and whether it was answered with an error; every error response is also
+//! counted by [`IpcErrorCode`]. Read them with [`IpcMetrics::snapshot`], or over IPC with
+//! `IpcGetMetricsRequest`.
+
+use crate::proto::{IpcErrorCount, IpcMessageTypeMetrics, IpcMetricsResponse};
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Upper bounds of the handler duration histogram; a final bucket counts anything slower.
+pub const DURATION_BUCKET_BOUNDS: [Duration; 7] = [
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+];
+
+#[derive(Debug, Default)]
+struct MessageTypeStats {
+    count: u64,
+    failures: u64,
+    total_duration: Duration,
+    max_duration: Duration,
+    buckets: [u64; DURATION_BUCKET_BOUNDS.len() + 1],
+}
+
+#[derive(Debug, Default)]
+struct MetricsInner {
+    message_types: BTreeMap<&'static str, MessageTypeStats>,
+    /// Error responses by `IpcErrorCode` value
+    errors: BTreeMap<i32, u64>,
+}
+
+/// Request counters shared by every connection of one IPC server.
+///
+/// This type is `Clone`; all clones share the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct IpcMetrics {
+    inner: Arc<Mutex<MetricsInner>>,
+}
+
+impl IpcMetrics {
+    /// Create empty counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a request as received (before its handler runs).
+    pub(crate) fn record_request(&self, message_type: &'static str) {
+        self.with_inner(|inner| inner.message_types.entry(message_type).or_default().count += 1);
+    }
+
+    /// Record a finished handler's duration and outcome.
+    pub(crate) fn record_completion(
+        &self,
+        message_type: &'static str,
+        duration: Duration,
+        failed: bool,
+    ) {
+        self.with_inner(|inner| {
+            let stats = inner.message_types.entry(message_type).or_default();
+            if failed {
+                stats.failures += 1;
+            }
+            stats.total_duration += duration;
+            stats.max_duration = stats.max_duration.max(duration);
+            let bucket = DURATION_BUCKET_BOUNDS
+                .iter()
+                .position(|bound| duration <= *bound)
+                .unwrap_or(DURATION_BUCKET_BOUNDS.len());
+            stats.buckets[bucket] += 1;
+        });
+    }
+
+    /// Count an error response with `code` (an `IpcErrorCode` value).
+    pub(crate) fn record_error(&self, code: i32) {
+        self.with_inner(|inner| *inner.errors.entry(code).or_default() += 1);
+    }
+
+    /// Current counters, with message types sorted by name.
+    pub fn snapshot(&self) -> IpcMetricsResponse {
+        self.with_inner(|inner| IpcMetricsResponse {
+            message_types: inner
+                .message_types
+                .iter()
+                .map(|(message_type, stats)| IpcMessageTypeMetrics {
+                    message_type: message_type.to_string(),
+                    count: stats.count,
+                    failures: stats.failures,
+                    total_duration_micros: micros(stats.total_duration),
+                    max_duration_micros: micros(stats.max_duration),
+                    duration_buckets: stats.buckets.to_vec(),
+                })
+                .collect(),
+            errors: inner
+                .errors
+                .iter()
+                .map(|(&code, &count)| IpcErrorCount { code, count })
+                .collect(),
+            duration_bucket_bounds_micros: DURATION_BUCKET_BOUNDS
+                .iter()
+                .copied()
+                .map(micros)
+                .collect(),
+        })
+    }
+
+    fn with_inner<T>(&self, f: impl FnOnce(&mut MetricsInner) -> T) -> T {
+        // Counters stay usable even if a panic interrupted an update
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut inner)
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// The request being handled on the current task.
+struct RequestScope {
+    metrics: IpcMetrics,
+    failed: Cell<bool>,
+}
+
+tokio::task_local! {
+    static CURRENT_REQUEST: RequestScope;
+}
+
+/// Run `handler` for a `message_type` request, recording its count, duration and outcome.
+///
+/// Error responses sent while `handler` runs (see [`record_error_response`]) mark the
+/// request as failed.
+pub(crate) async fn instrument(
+    metrics: IpcMetrics,
+    message_type: &'static str,
+    handler: impl Future<Output = ()>,
+) {
+    metrics.record_request(message_type);
+    let started = Instant::now();
+
+    let scope = RequestScope {
+        metrics: metrics.clone(),
+        failed: Cell::new(false),
+    };
+    let failed = CURRENT_REQUEST
+        .scope(scope, async {
+            handler.await;
+            CURRENT_REQUEST.with(|request| request.failed.get())
+        })
+        .await;
+
+    metrics.record_completion(message_type, started.elapsed(), failed);
+}
+
+/// Count an error response sent by the current request (a no-op outside [`instrument`]).
+pub(crate) fn record_error_response(code: i32) {
+    let _ = CURRENT_REQUEST.try_with(|request| {
+        request.failed.set(true);
+        request.metrics.record_error(code);
+    });
+}
@@ -2,10 +2,14 @@
 //!
 //! This module implements WebSocket-based IPC per ADR-0003. It provides:
 //!
-//! - WebSocket server (localhost-only)
+//! - WebSocket server (localhost-only), falling back to a nearby port if the preferred one is taken
 //! - Binary protobuf protocol (type-safe)
-//! - Authentication handshake (security)
+//! - Authentication handshake (security) with protocol version negotiation
 //! - Server management handlers (discover, spawn, health, stop)
+//! - Ping/pong keepalive to detect half-open connections
+//! - Server-pushed state change events for subscribed clients
+//! - Per-message-type request metrics ([`IpcMetrics`])
+//! - Typed client ([`IpcClient`]) for tests and tooling, reconnecting with backoff
 //!
 //! # Architecture
 //!
@@ -19,6 +23,9 @@
 //! - `IpcClientMessage` - Client → Server
 //! - `IpcServerMessage` - Server → Client
 //! - `IpcAuthHandshake` - Authentication (first message)
+//! - `IpcStreamMessageRequest` - The one request answered by several frames: server-pushed
+//!   `IpcMessagePartEvent`s, then a terminal `IpcMessageCompleteEvent` (or error), all
+//!   sharing the request's `request_id`
 //!
 //! # Security
 //!
@@ -26,13 +33,27 @@
 //! - Non-loopback connections rejected
 //! - Authentication token required (generated on server start)
 
+mod auth_token;
+mod client;
 pub mod config_state;
-mod connection_state;
+pub(crate) mod connection_state;
+mod events;
 mod handle;
-mod server;
+pub mod metrics;
+pub mod options;
+pub mod protocol;
+pub mod reconnect;
+pub(crate) mod server;
+mod sink;
 mod state;
 
+pub use auth_token::IpcAuthToken;
+pub use client::IpcClient;
 pub use config_state::{ConfigCommand, ConfigState};
+pub use connection_state::ConnectionPhase;
 pub use handle::IpcServerHandle;
-pub use server::start_ipc_server;
+pub use metrics::IpcMetrics;
+pub use options::IpcServerOptions;
+pub use reconnect::{ConnectionStatus, ReconnectPolicy};
+pub use server::{start_ipc_server, start_ipc_server_with_options};
 pub use state::{IpcState, StateCommand};
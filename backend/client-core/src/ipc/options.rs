@@ -0,0 +1,69 @@
+//! Tunable settings for the IPC WebSocket server.
+//!
+//! [`IpcServerOptions::default`] matches the behavior of [`start_ipc_server`](crate::ipc::start_ipc_server);
+//! pass custom options to [`start_ipc_server_with_options`](crate::ipc::start_ipc_server_with_options)
+//! to override them (e.g. shorter intervals in tests).
+
+use std::time::Duration;
+
+/// Default interval between server-initiated WebSocket pings.
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default time a client has to answer a ping before the connection is considered dead.
+pub const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time an authenticated client may stay silent before its connection is closed.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Default maximum size of a single client message (16 MiB).
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Default maximum number of connections served at once (the frontend only needs a few).
+pub const DEFAULT_MAX_CONNECTIONS: usize = 16;
+
+/// Default number of ports after the preferred one tried when it is already in use.
+pub const DEFAULT_PORT_FALLBACK_ATTEMPTS: u16 = 10;
+
+/// Settings applied to every connection accepted by the IPC server.
+#[derive(Debug, Clone)]
+pub struct IpcServerOptions {
+    /// How often the server pings each authenticated client.
+    pub ping_interval: Duration,
+    /// How long to wait for a pong before closing a half-open connection.
+    pub pong_timeout: Duration,
+    /// How long a client may go without sending a message before the connection is closed.
+    ///
+    /// Pongs don't count: they're answered by the WebSocket layer, not the client.
+    pub idle_timeout: Duration,
+    /// Largest client message (and frame) accepted, in bytes; larger ones close the connection.
+    pub max_message_size: usize,
+    /// Most connections served at once; further ones are closed right after the WebSocket
+    /// handshake until a slot frees up.
+    pub max_connections: usize,
+    /// How many following ports to try (`port + 1`, `port + 2`, ...) when the preferred
+    /// port is in use; `0` fails instead. The bound port is [`IpcServerHandle::port`].
+    ///
+    /// [`IpcServerHandle::port`]: crate::ipc::IpcServerHandle::port
+    pub port_fallback_attempts: u16,
+    /// Also accept clients that speak JSON over text frames instead of protobuf.
+    ///
+    /// A connection whose handshake arrives as a text frame holding the JSON form of an
+    /// [`IpcClientMessage`](crate::proto::IpcClientMessage) (or just the handshake object,
+    /// `{"token": "...", "protocol_version": 1}`) is answered in JSON for its lifetime.
+    /// Meant for debugging and scripting; off by default so the app only speaks protobuf.
+    pub json_protocol: bool,
+}
+
+impl Default for IpcServerOptions {
+    fn default() -> Self {
+        Self {
+            ping_interval: DEFAULT_PING_INTERVAL,
+            pong_timeout: DEFAULT_PONG_TIMEOUT,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            port_fallback_attempts: DEFAULT_PORT_FALLBACK_ATTEMPTS,
+            json_protocol: false,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! IPC protocol versioning.
+//!
+//! Clients send their protocol version in `IpcAuthHandshake`; the server answers with the
+//! negotiated version in `IpcAuthHandshakeResponse`, or rejects the handshake if it can't
+//! speak to the client. Bump [`IPC_PROTOCOL_VERSION`] on incompatible message changes, and
+//! raise [`MIN_IPC_PROTOCOL_VERSION`] when the server drops support for older clients.
+
+/// Protocol version spoken by this build.
+pub const IPC_PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest client protocol version the server accepts.
+///
+/// Version 0 is what clients that predate versioning send (the field is absent).
+pub const MIN_IPC_PROTOCOL_VERSION: u32 = 1;
+
+/// Version to use with a client speaking `client_version`, or `None` if incompatible.
+///
+/// Newer clients are talked down to [`IPC_PROTOCOL_VERSION`]; clients older than
+/// [`MIN_IPC_PROTOCOL_VERSION`] are rejected.
+pub fn negotiate_version(client_version: u32) -> Option<u32> {
+    (client_version >= MIN_IPC_PROTOCOL_VERSION).then_some(client_version.min(IPC_PROTOCOL_VERSION))
+}
@@ -0,0 +1,72 @@
+//! Reconnection settings and state for [`IpcClient`](crate::ipc::IpcClient).
+//!
+//! When the client finds its connection gone, it redials and redoes the auth handshake,
+//! waiting a jittered, exponentially growing delay between attempts until
+//! [`ReconnectPolicy::max_elapsed_time`] runs out. Progress is published as a
+//! [`ConnectionStatus`].
+
+use std::time::Duration;
+
+use backoff::ExponentialBackoff;
+
+/// Default delay before the second reconnect attempt (the first is immediate).
+pub const DEFAULT_RECONNECT_INITIAL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default cap on the delay between reconnect attempts.
+pub const DEFAULT_RECONNECT_MAX_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default time to keep retrying before giving up.
+pub const DEFAULT_RECONNECT_MAX_ELAPSED_TIME: Duration = Duration::from_secs(30);
+
+/// How [`IpcClient`](crate::ipc::IpcClient) retries a lost connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Delay after the first failed attempt.
+    pub initial_interval: Duration,
+    /// Upper bound on any single delay.
+    pub max_interval: Duration,
+    /// Factor the delay grows by after each failed attempt.
+    pub multiplier: f64,
+    /// Jitter: each delay is picked from `delay * (1 ± randomization_factor)`.
+    pub randomization_factor: f64,
+    /// Time after which reconnecting gives up.
+    pub max_elapsed_time: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_interval: DEFAULT_RECONNECT_INITIAL_INTERVAL,
+            max_interval: DEFAULT_RECONNECT_MAX_INTERVAL,
+            multiplier: 2.0,
+            randomization_factor: 0.5,
+            max_elapsed_time: DEFAULT_RECONNECT_MAX_ELAPSED_TIME,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Fresh backoff schedule for one round of reconnect attempts.
+    pub(crate) fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff {
+            current_interval: self.initial_interval,
+            initial_interval: self.initial_interval,
+            max_interval: self.max_interval,
+            multiplier: self.multiplier,
+            randomization_factor: self.randomization_factor,
+            max_elapsed_time: Some(self.max_elapsed_time),
+            ..Default::default()
+        }
+    }
+}
+
+/// Connection state of an [`IpcClient`](crate::ipc::IpcClient).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// Authenticated and ready for requests.
+    Connected,
+    /// Redialing after a lost connection; `attempt` counts from 1.
+    Reconnecting { attempt: u32 },
+    /// Connection lost and not (yet) re-established; the next request tries again.
+    Disconnected,
+}
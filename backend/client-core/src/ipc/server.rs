@@ -6,7 +6,7 @@
 //! - Listens on localhost only (security)
 //! - Uses binary protobuf messages (type safety)
 //! - Requires authentication handshake (security)
-//! - Handles concurrent connections (scalability)
+//! - Handles concurrent connections and concurrent requests per connection (scalability)
 //!
 //! # Architecture
 //!
@@ -22,38 +22,133 @@
 //! # Protocol
 //!
 //! WebSocket with binary protobuf frames. See `proto/ipc.proto` for message definitions.
-
-use crate::config::AppConfig;
-use crate::discovery::{process, spawn};
+//! With [`IpcServerOptions::json_protocol`], clients may instead send the same messages as
+//! JSON text frames, and are answered in JSON.
+
+use crate::auth_sync::{self, SyncConfig, SyncReport};
+use crate::config::models::CuratedModel;
+use crate::config::{AppConfig, ModelsConfig};
+use crate::discovery::{self, process, spawn};
+use crate::error::config::ConfigError;
 use crate::error::ipc::IpcError;
+use crate::error::{AuthSyncError, ErrorDetails};
+use crate::ipc::auth_token::IpcAuthToken;
 use crate::ipc::config_state::ConfigState;
-use crate::ipc::connection_state::ConnectionState;
+use crate::ipc::connection_state::{
+    ConnectionPhase, ConnectionPhases, ConnectionState, HandshakeRejection,
+};
 use crate::ipc::handle::IpcServerHandle;
+use crate::ipc::metrics::{self, IpcMetrics};
+use crate::ipc::options::IpcServerOptions;
+use crate::ipc::protocol::{IPC_PROTOCOL_VERSION, MIN_IPC_PROTOCOL_VERSION};
+use crate::ipc::sink::MessageSink;
 use crate::ipc::state::{IpcState, StateCommand};
+use crate::logging;
 use crate::proto::IpcErrorCode::{AuthError, InternalError, InvalidMessage, NotImplemented};
+use crate::proto::message::error::{OcAbortedError, OcMessageError, oc_message_error};
+use crate::proto::message::{OcAssistantMessage, OcMessage, oc_message};
 use crate::proto::session::OcSessionList;
 use crate::proto::{
+    IpcAbortMessageRequest, IpcAbortMessageResponse, IpcAddCuratedModelRequest, IpcAuthHandshake,
     IpcAuthHandshakeResponse, IpcAuthSyncResponse, IpcCheckHealthResponse, IpcClientMessage,
-    IpcCreateSessionRequest, IpcDeleteSessionRequest, IpcDeleteSessionResponse,
-    IpcDiscoverServerResponse, IpcErrorCode, IpcErrorResponse, IpcGetConfigResponse,
-    IpcProviderSyncResult, IpcSendMessageRequest, IpcServerMessage, IpcSpawnServerRequest,
-    IpcSpawnServerResponse, IpcStopServerResponse, IpcSyncAuthKeysRequest, IpcUpdateConfigRequest,
-    IpcUpdateConfigResponse, ipc_client_message, ipc_server_message,
+    IpcConfigValueResponse, IpcCreateSessionRequest, IpcCuratedModel, IpcCuratedModelsResponse,
+    IpcDeleteSessionRequest, IpcDeleteSessionResponse, IpcDiscoverAllServersResponse,
+    IpcDiscoverServerResponse, IpcEnsureServerResponse, IpcErrorCode, IpcErrorResponse,
+    IpcGetConfigResponse, IpcGetConfigValueRequest, IpcMessageCompleteEvent, IpcMessagePartEvent,
+    IpcProviderSyncResult, IpcRemoveCuratedModelRequest, IpcResetConfigRequest,
+    IpcResetConfigResponse, IpcSendMessageRequest, IpcServerMessage, IpcSetConfigValueRequest,
+    IpcSetDirectoryRequest, IpcSetDirectoryResponse, IpcSetLogLevelRequest, IpcSetLogLevelResponse,
+    IpcSpawnServerRequest, IpcSpawnServerResponse, IpcStopServerResponse, IpcStreamMessageRequest,
+    IpcSubscribeEventsResponse, IpcSyncAuthKeysRequest, IpcUpdateConfigRequest,
+    IpcUpdateConfigResponse, IpcUpdateModelsConfigRequest, IpcUpdateSessionRequest,
+    ipc_client_message, ipc_server_message,
 };
 
-use common::ErrorLocation;
+use common::{ErrorLocation, RedactedDisplay, redact_secrets};
 
+use std::io::ErrorKind;
 use std::net::SocketAddr;
 use std::panic::Location;
+use std::sync::Arc;
+use std::time::Duration;
 
+use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
 use prost::Message as ProstMessage;
+use serde::Deserialize;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::spawn as TokioSpawn;
-use tokio_tungstenite::accept_async;
-use tokio_tungstenite::tungstenite::Message;
-use uuid::Uuid;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{RwLock, Semaphore, mpsc};
+use tokio::time::{Instant, MissedTickBehavior, interval, sleep_until, timeout};
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{WebSocketStream, accept_async_with_config};
+use tokio_util::sync::{CancellationToken, PollSendError, PollSender};
+
+/// Write half handed to handlers; frames are queued to the connection's writer task.
+///
+/// Messages sent through [`MessageSink`] are encoded in the connection's [`WireFormat`].
+#[derive(Clone)]
+struct IpcWriter {
+    frames: PollSender<Message>,
+    format: WireFormat,
+}
+
+impl IpcWriter {
+    /// Queues a frame as-is; used for keepalive and close frames.
+    async fn send_frame(&mut self, frame: Message) -> Result<(), PollSendError<Message>> {
+        self.frames.send(frame).await
+    }
+}
+
+impl MessageSink for IpcWriter {
+    async fn send(&mut self, message: IpcServerMessage) -> Result<(), IpcError> {
+        let frame = match self.format {
+            WireFormat::Protobuf => Message::Binary(message.encode_to_vec().into()),
+            WireFormat::Json => {
+                let json =
+                    serde_json::to_string(&message).map_err(|e| IpcError::ProtobufEncode {
+                        message: format!("Failed to encode response as JSON: {e}"),
+                        location: ErrorLocation::from(Location::caller()),
+                    })?;
+                Message::Text(json.into())
+            }
+        };
+
+        self.send_frame(frame).await.map_err(|e| IpcError::Send {
+            message: format!("Failed to send response: {e}"),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+}
+
+/// Encoding a connection uses, chosen by the frame type of its handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WireFormat {
+    /// Binary protobuf frames, the app's protocol.
+    Protobuf,
+    /// Text frames holding the serde JSON form of the protobuf messages; only accepted
+    /// with [`IpcServerOptions::json_protocol`].
+    Json,
+}
+
+/// Handshake shorthand for JSON clients; `protocol_version` defaults to 0 like protobuf.
+#[derive(Deserialize)]
+struct JsonHandshake {
+    token: String,
+    #[serde(default)]
+    protocol_version: u32,
+}
+
+/// Maximum number of outgoing frames queued per connection before handlers wait.
+const WRITE_QUEUE_CAPACITY: usize = 64;
+
+/// Time a connection rejected for capacity gets to finish the WebSocket handshake.
+const REJECT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Starts the IPC WebSocket server on the specified port.
 ///
@@ -63,16 +158,19 @@ use uuid::Uuid;
 ///
 /// # Arguments
 ///
-/// * `ipc_port` - Port to bind on localhost (e.g., 19876)
+/// * `ipc_port` - Preferred port to bind on localhost (e.g., 19876); if it is in use, the
+///   next [`IpcServerOptions::port_fallback_attempts`] ports are tried in order
 ///
 /// # Returns
 ///
-/// Returns [`IpcServerHandle`] on success, representing the running server.
+/// Returns [`IpcServerHandle`] on success, representing the running server; its
+/// [`port`](IpcServerHandle::port) is the port actually bound.
+/// Call [`IpcServerHandle::shutdown`] to stop accepting connections and free the port.
 ///
 /// # Errors
 ///
 /// Returns [`IpcError::Io`] if:
-/// - Port is already in use
+/// - The preferred port and every fallback port are in use
 /// - Insufficient permissions to bind port
 /// - Network interface unavailable
 ///
@@ -80,7 +178,9 @@ use uuid::Uuid;
 ///
 /// - Binds to `127.0.0.1` only (localhost)
 /// - Individual connections reject non-loopback clients
-/// - Future: Requires auth token in first message (Step 6)
+/// - Requires auth token in first message; rotate it with [`IpcServerHandle::rotate_auth_token`]
+/// - Serves at most [`IpcServerOptions::max_connections`] connections at once; extra ones
+///   are closed with [`CloseCode::Again`] ("Too many connections")
 ///
 /// # Panics
 ///
@@ -91,29 +191,166 @@ pub async fn start_ipc_server(
     ipc_port: u16,
     auth_token: Option<String>,
     config_state: ConfigState,
+) -> Result<IpcServerHandle, IpcError> {
+    start_ipc_server_with_options(
+        ipc_port,
+        auth_token,
+        config_state,
+        IpcServerOptions::default(),
+    )
+    .await
+}
+
+/// Starts the IPC WebSocket server with custom [`IpcServerOptions`].
+///
+/// Behaves exactly like [`start_ipc_server`], but applies `options` (keepalive
+/// intervals, etc.) to every accepted connection.
+///
+/// # Errors
+///
+/// Same as [`start_ipc_server`].
+pub async fn start_ipc_server_with_options(
+    ipc_port: u16,
+    auth_token: Option<String>,
+    config_state: ConfigState,
+    options: IpcServerOptions,
 ) -> Result<IpcServerHandle, IpcError> {
     // Generate token if not provided
-    let auth_token = auth_token.unwrap_or_else(|| {
-        let token = Uuid::new_v4().to_string();
+    let auth_token = auth_token.map(IpcAuthToken::new).unwrap_or_else(|| {
+        let token = IpcAuthToken::generate();
         info!("Generated IPC auth token: {}", token);
         token
     });
+    // Shared with the handle so the token can be rotated while the server runs
+    let auth_token = Arc::new(RwLock::new(auth_token));
+    let accept_token = Arc::clone(&auth_token);
 
-    let address = format!("127.0.0.1:{ipc_port}");
-    let listener = TcpListener::bind(&address).await?;
+    let listener = bind_with_fallback(ipc_port, options.port_fallback_attempts).await?;
+    let port = listener.local_addr()?.port();
+    let address = format!("127.0.0.1:{port}");
 
     info!("IPC server listening on {}", address);
 
-    TokioSpawn(async move {
-        while let Ok((stream, addr)) = listener.accept().await {
+    let shutdown_token = CancellationToken::new();
+    let accept_shutdown = shutdown_token.clone();
+
+    // Shared by every connection so counts cover the whole server
+    let metrics = IpcMetrics::new();
+    let accept_metrics = metrics.clone();
+
+    // One permit per served connection, held until its handler returns
+    let connection_slots = Arc::new(Semaphore::new(options.max_connections));
+    let max_connections = options.max_connections;
+    let handle_slots = Arc::clone(&connection_slots);
+
+    // Auth phase of every connection past its first message, readable from the handle
+    let phases = ConnectionPhases::default();
+    let accept_phases = phases.clone();
+
+    let accept_task = TokioSpawn(async move {
+        loop {
+            let (stream, addr) = tokio::select! {
+                _ = accept_shutdown.cancelled() => {
+                    info!("IPC server on {} stopped accepting connections", address);
+                    break;
+                }
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        error!("Failed to accept IPC connection: {}", e);
+                        break;
+                    }
+                },
+            };
+
+            let Ok(permit) = Arc::clone(&connection_slots).try_acquire_owned() else {
+                warn!(
+                    "Rejecting IPC connection from {}: limit of {} concurrent connections reached",
+                    addr, options.max_connections
+                );
+                TokioSpawn(reject_over_capacity(stream, addr));
+                continue;
+            };
+
             info!("Client connecting from {}", addr);
-            let token_clone = auth_token.clone();
+            let token_clone = Arc::clone(&accept_token);
             let config_clone = config_state.clone();
-            TokioSpawn(handle_connection(stream, addr, token_clone, config_clone));
+            let options_clone = options.clone();
+            let connection_metrics = accept_metrics.clone();
+            let connection_phases = accept_phases.clone();
+            TokioSpawn(async move {
+                let _permit = permit;
+                handle_connection(
+                    stream,
+                    addr,
+                    token_clone,
+                    config_clone,
+                    options_clone,
+                    connection_metrics,
+                    connection_phases,
+                )
+                .await
+            });
         }
+        // Listener is dropped here, freeing the port
     });
 
-    Ok(IpcServerHandle {})
+    Ok(IpcServerHandle::new(
+        port,
+        shutdown_token,
+        accept_task,
+        auth_token,
+        metrics,
+        handle_slots,
+        max_connections,
+        phases,
+    ))
+}
+
+/// Binds `127.0.0.1:{preferred}`, or the first free port among the next `fallback_attempts`
+/// ports if it is in use.
+///
+/// Only `AddrInUse` moves on to the next port; any other error (e.g. permissions) is
+/// returned immediately.
+async fn bind_with_fallback(
+    preferred: u16,
+    fallback_attempts: u16,
+) -> Result<TcpListener, IpcError> {
+    let mut last_error = None;
+
+    for offset in 0..=fallback_attempts {
+        let Some(port) = preferred.checked_add(offset) else {
+            break;
+        };
+
+        match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => {
+                if offset > 0 {
+                    warn!("IPC port {preferred} is in use; using port {port} instead");
+                }
+                return Ok(listener);
+            }
+            Err(e) if e.kind() == ErrorKind::AddrInUse => {
+                debug!("IPC port {port} is in use");
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        // Port 0 lets the OS pick, so there is nothing to fall back to
+        if preferred == 0 {
+            break;
+        }
+    }
+
+    Err(IpcError::Io {
+        message: format!(
+            "IPC ports {preferred}-{} are all in use: {}",
+            preferred.saturating_add(fallback_attempts),
+            last_error.map(|e| e.to_string()).unwrap_or_default()
+        ),
+        location: ErrorLocation::from(Location::caller()),
+    })
 }
 
 /// Handles a single WebSocket connection.
@@ -129,7 +366,10 @@ pub async fn start_ipc_server(
 ///
 /// * `stream` - TCP stream from accepted connection
 /// * `addr` - Client address (for security checks)
-/// * `auth_token` - Expected auth token
+/// * `auth_token` - Expected auth token (read when the handshake arrives, so rotation
+///   affects only handshakes that happen afterwards)
+/// * `options` - Keepalive settings for this connection
+/// * `metrics` - Server-wide request counters
 ///
 /// # Returns
 ///
@@ -142,14 +382,19 @@ pub async fn start_ipc_server(
 /// - [`IpcError::ProtobufDecode`] - Failed to decode protobuf message
 /// - [`IpcError::ProtobufEncode`] - Failed to encode protobuf response
 /// - [`IpcError::Send`] - Failed to send message to client
-/// - [`IpcError::Read`] - Failed to read message from client
+/// - [`IpcError::Read`] - Failed to read message from client, or an invalid JSON handshake
 ///
 /// # Protocol
 ///
-/// 1. **First message MUST be** `IpcAuthHandshake` with valid token
+/// 1. **First message MUST be** `IpcAuthHandshake` with valid token; a text frame is only
+///    accepted with `options.json_protocol`, and makes the whole connection JSON
 /// 2. Server responds with `IpcAuthHandshakeResponse` (success or failure)
 /// 3. If auth fails, connection closes immediately
-/// 4. If auth succeeds, subsequent messages are processed (currently echoed)
+/// 4. If auth succeeds, each subsequent message is handled in its own task; responses
+///    carry the request's `request_id` and may arrive out of order
+/// 5. Server pings every `ping_interval`; no pong within `pong_timeout` closes the connection
+/// 6. No message from the client for `idle_timeout` closes the connection
+/// 7. Messages over `max_message_size` close the connection without being decoded
 ///
 /// # Security
 ///
@@ -160,8 +405,11 @@ pub async fn start_ipc_server(
 async fn handle_connection(
     stream: TcpStream,
     addr: SocketAddr,
-    auth_token: String,
+    auth_token: Arc<RwLock<IpcAuthToken>>,
     config_state: ConfigState,
+    options: IpcServerOptions,
+    metrics: IpcMetrics,
+    phases: ConnectionPhases,
 ) -> Result<(), IpcError> {
     // SECURITY: Reject non-loopback connections
     if !addr.ip().is_loopback() {
@@ -169,7 +417,11 @@ async fn handle_connection(
         return Ok(()); // Silent rejection (don't give attackers info)
     }
 
-    let ws_stream = match accept_async(stream).await {
+    // Size limits make tungstenite reject oversized frames before buffering them
+    let ws_config = WebSocketConfig::default()
+        .max_message_size(Some(options.max_message_size))
+        .max_frame_size(Some(options.max_message_size));
+    let ws_stream = match accept_async_with_config(stream, Some(ws_config)).await {
         Ok(ws_stream) => ws_stream,
         Err(e) => {
             error!("WebSocket handshake failed: {}", e);
@@ -180,77 +432,199 @@ async fn handle_connection(
         }
     };
 
-    let (mut write, mut read) = ws_stream.split();
-    let mut state = ConnectionState::new(auth_token);
+    let (ws_write, mut read) = ws_stream.split();
 
     // SECURITY: First message MUST be auth handshake
-    if let Some(msg) = read.next().await {
-        match msg {
-            Ok(Message::Binary(data)) => {
-                // Decode protobuf message
-                let client_msg = IpcClientMessage::decode(&data[..])?;
-
-                // Check if it's auth handshake
-                match client_msg.payload {
-                    Some(ipc_client_message::Payload::AuthHandshake(auth)) => {
-                        // Validate token
-                        if state.validate_token(&auth.token) {
-                            info!("Client {} authenticated successfully", addr);
-
-                            // Send success response
-                            send_auth_response(&mut write, true, None).await?;
-                        } else {
-                            warn!("Client {} auth failed: invalid token", addr);
-
-                            // Send failure response
-                            send_auth_response(
-                                &mut write,
-                                false,
-                                Some("Invalid authentication token"),
-                            )
-                            .await?;
+    // Its frame type also picks the wire format for the rest of the connection
+    let (format, first) = match read.next().await {
+        Some(Ok(Message::Binary(data))) => (WireFormat::Protobuf, data),
+        Some(Ok(Message::Text(text))) if options.json_protocol => (WireFormat::Json, text.into()),
+        Some(Ok(_)) => {
+            warn!("Client {} sent non-binary first message", addr);
+            return Ok(()); // Close connection
+        }
+        Some(Err(e)) => {
+            error!("Error reading first message from {}: {}", addr, e);
+            return Err(IpcError::Read {
+                message: format!("Error reading first message: {}", e),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+        None => {
+            warn!("Client {} disconnected before sending auth", addr);
+            return Ok(());
+        }
+    };
+    let mut write = spawn_writer(ws_write, addr, format);
 
-                            return Ok(()); // Close connection
-                        }
-                    }
-                    _ => {
-                        warn!(
-                            "Client {} auth failed: first message was not auth handshake",
-                            addr
-                        );
-                        return Ok(()); // Close connection (no response)
-                    }
-                }
-            }
-            Ok(_) => {
-                warn!("Client {} sent non-binary first message", addr);
-                return Ok(()); // Close connection
-            }
-            Err(e) => {
-                error!("Error reading first message from {}: {}", addr, e);
-                return Err(IpcError::Read {
-                    message: format!("Error reading first message: {}", e),
-                    location: ErrorLocation::from(Location::caller()),
-                });
-            }
+    if first.len() > options.max_message_size {
+        return Err(reject_oversized(&mut write, addr, &format!("{} bytes", first.len())).await);
+    }
+
+    let client_msg = match format {
+        WireFormat::Protobuf => IpcClientMessage::decode(&first[..])?,
+        WireFormat::Json => decode_json_message(&first).map_err(|e| IpcError::Read {
+            message: format!("Invalid JSON handshake: {e}"),
+            location: ErrorLocation::from(Location::caller()),
+        })?,
+    };
+
+    // Validate against the token current at handshake time
+    let mut connection = ConnectionState::new(auth_token.read().await.clone(), addr, phases);
+    let Some(ipc_client_message::Payload::AuthHandshake(auth)) = client_msg.payload else {
+        warn!(
+            "Client {} auth failed: first message was not auth handshake",
+            addr
+        );
+        connection.close("first message was not auth handshake");
+        return Ok(()); // Close connection (no response)
+    };
+
+    match connection.authenticate(&auth.token, auth.protocol_version) {
+        Ok(version) => send_auth_response(&mut write, true, None, version).await?,
+        Err(HandshakeRejection::UnsupportedVersion(client_version)) => {
+            // Reject incompatible clients before any message is decoded
+            warn!(
+                "Client {} auth failed: unsupported protocol version {}",
+                addr, client_version
+            );
+            let message = format!(
+                "Unsupported IPC protocol version {} (server supports {}-{})",
+                client_version, MIN_IPC_PROTOCOL_VERSION, IPC_PROTOCOL_VERSION
+            );
+            send_auth_response(&mut write, false, Some(&message), IPC_PROTOCOL_VERSION).await?;
+            return Ok(()); // Close connection
+        }
+        Err(rejection) => {
+            warn!("Client {} auth failed: {}", addr, rejection);
+            // Send failure response (no version info for unauthenticated clients)
+            send_auth_response(&mut write, false, Some("Invalid authentication token"), 0).await?;
+            return Ok(()); // Close connection
         }
-    } else {
-        warn!("Client {} disconnected before sending auth", addr);
-        return Ok(());
     }
 
+    // Negotiated version is kept on the connection for version-dependent behavior
+    info!(
+        "Client {} authenticated successfully (protocol v{})",
+        addr,
+        connection
+            .protocol_version()
+            .unwrap_or(IPC_PROTOCOL_VERSION)
+    );
+
     // Create shared state for server management
     let ipc_state = IpcState::new();
 
+    // Cancelled when this function returns, ending the connection's event subscriptions
+    let connection_closed = CancellationToken::new();
+    let _connection_guard = connection_closed.clone().drop_guard();
+
+    // Configured project directory applies to every OpenCode client on this connection
+    let directory_override = config_state
+        .get_app_config()
+        .await
+        .server
+        .directory_override;
+    if directory_override.is_some() {
+        ipc_state.set_directory(None, directory_override).await?;
+    }
+
+    // Keepalive: ping periodically, expect a pong before the deadline
+    let mut ping_interval = interval(options.ping_interval);
+    ping_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ping_interval.tick().await; // First tick completes immediately
+    let mut pong_deadline: Option<Instant> = None;
+
+    // Idle timeout: restarted by every client message except keepalive frames
+    let mut idle_deadline = Instant::now() + options.idle_timeout;
+
     // Main message loop (authenticated)
-    while let Some(msg) = read.next().await {
+    loop {
+        let msg = tokio::select! {
+            msg = read.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            _ = ping_interval.tick(), if pong_deadline.is_none() => {
+                write
+                    .send_frame(Message::Ping(Default::default()))
+                    .await
+                    .map_err(|e| IpcError::Send {
+                        message: format!("Failed to send ping: {e}"),
+                        location: ErrorLocation::from(Location::caller()),
+                    })?;
+                pong_deadline = Some(Instant::now() + options.pong_timeout);
+                continue;
+            }
+            _ = sleep_until(pong_deadline.unwrap_or_else(Instant::now)), if pong_deadline.is_some() => {
+                warn!(
+                    "Client {} did not answer ping within {:?}, closing connection",
+                    addr, options.pong_timeout
+                );
+                connection.close("no pong");
+                let _ = write.send_frame(Message::Close(None)).await;
+                return Ok(());
+            }
+            _ = sleep_until(idle_deadline) => {
+                info!(
+                    "Client {} idle for {:?}, closing connection",
+                    addr, options.idle_timeout
+                );
+                connection.close("idle timeout");
+                let close = CloseFrame {
+                    code: CloseCode::Normal,
+                    reason: "Idle timeout".into(),
+                };
+                let _ = write.send_frame(Message::Close(Some(close))).await;
+                return Ok(());
+            }
+        };
+
+        if !matches!(msg, Ok(Message::Ping(_) | Message::Pong(_))) {
+            idle_deadline = Instant::now() + options.idle_timeout;
+        }
+
         match msg {
+            Ok(Message::Pong(_)) => {
+                pong_deadline = None;
+            }
+            Ok(Message::Ping(payload)) => {
+                write
+                    .send_frame(Message::Pong(payload))
+                    .await
+                    .map_err(|e| IpcError::Send {
+                        message: format!("Failed to send pong: {e}"),
+                        location: ErrorLocation::from(Location::caller()),
+                    })?;
+            }
+            Ok(Message::Close(frame)) => {
+                match frame {
+                    Some(frame) => info!(
+                        "Client {} closed the connection ({}: {})",
+                        addr, frame.code, frame.reason
+                    ),
+                    None => info!("Client {} closed the connection", addr),
+                }
+                connection.close("closed by client");
+                break;
+            }
             Ok(Message::Binary(data)) => {
+                // Defensive: tungstenite enforces the limit too, but never decode past it
+                if data.len() > options.max_message_size {
+                    return Err(reject_oversized(
+                        &mut write,
+                        addr,
+                        &format!("{} bytes", data.len()),
+                    )
+                    .await);
+                }
+
                 // Decode protobuf client message
                 let client_msg = match IpcClientMessage::decode(&data[..]) {
                     Ok(msg) => msg,
                     Err(e) => {
                         error!("Failed to decode protobuf from {}: {}", addr, e);
+                        metrics.record_error(InvalidMessage as i32);
                         send_error_response(
                             &mut write,
                             0,
@@ -262,38 +636,55 @@ async fn handle_connection(
                     }
                 };
 
-                // Handle the message
-                let request_id = client_msg.request_id;
-                if let Some(payload) = client_msg.payload {
-                    match handle_message(payload, &ipc_state, &config_state, request_id, &mut write)
-                        .await
-                    {
-                        Ok(_) => {}
-                        Err(e) => {
-                            error!("Error handling message from {}: {}", addr, e);
-                            send_error_response(
-                                &mut write,
-                                request_id,
-                                InternalError,
-                                &e.to_string(),
-                            )
-                            .await?;
-                        }
-                    }
-                } else {
-                    warn!("Client {} sent message with no payload", addr);
-                    send_error_response(
+                dispatch_message(
+                    client_msg,
+                    &mut connection,
+                    &ipc_state,
+                    &config_state,
+                    &connection_closed,
+                    &metrics,
+                    &mut write,
+                )
+                .await?;
+            }
+            Ok(Message::Text(text)) if format == WireFormat::Json => {
+                if text.len() > options.max_message_size {
+                    return Err(reject_oversized(
                         &mut write,
-                        request_id,
-                        InvalidMessage,
-                        "No payload in message",
+                        addr,
+                        &format!("{} bytes", text.len()),
                     )
-                    .await?;
+                    .await);
                 }
+
+                let client_msg = match decode_json_message(text.as_bytes()) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        error!("Failed to decode JSON from {}: {}", addr, e);
+                        metrics.record_error(InvalidMessage as i32);
+                        send_error_response(&mut write, 0, InvalidMessage, "Invalid JSON message")
+                            .await?;
+                        continue;
+                    }
+                };
+
+                dispatch_message(
+                    client_msg,
+                    &mut connection,
+                    &ipc_state,
+                    &config_state,
+                    &connection_closed,
+                    &metrics,
+                    &mut write,
+                )
+                .await?;
             }
             Ok(_) => {
                 warn!("Client {} sent non-binary message after auth", addr);
-                // Ignore non-binary messages
+                // Ignore other non-binary messages (text, raw frames)
+            }
+            Err(WsError::Capacity(e)) => {
+                return Err(reject_oversized(&mut write, addr, &e.to_string()).await);
             }
             Err(e) => {
                 error!("Error reading message from {}: {}", addr, e);
@@ -306,14 +697,193 @@ async fn handle_connection(
     }
 
     info!("Client {} disconnected", addr);
+    connection.close("disconnected");
+    Ok(())
+}
+
+/// Handles an authenticated client message in its own task so slow handlers don't block
+/// fast ones.
+///
+/// A message without a payload, or a repeated handshake, is answered with an error
+/// right away.
+async fn dispatch_message(
+    client_msg: IpcClientMessage,
+    connection: &mut ConnectionState,
+    ipc_state: &IpcState,
+    config_state: &ConfigState,
+    connection_closed: &CancellationToken,
+    metrics: &IpcMetrics,
+    write: &mut IpcWriter,
+) -> Result<(), IpcError> {
+    // Only reached after a successful handshake; the message loop never runs otherwise
+    debug_assert_eq!(connection.phase(), ConnectionPhase::Authenticated);
+
+    let addr = connection.peer();
+    let request_id = client_msg.request_id;
+    let Some(payload) = client_msg.payload else {
+        warn!("Client {} sent message with no payload", addr);
+        metrics.record_error(InvalidMessage as i32);
+        return send_error_response(write, request_id, InvalidMessage, "No payload in message")
+            .await;
+    };
+
+    // Handshakes are the connection's business, not a request: the state machine refuses
+    // any after the first
+    if let ipc_client_message::Payload::AuthHandshake(auth) = &payload
+        && let Err(rejection) = connection.authenticate(&auth.token, auth.protocol_version)
+    {
+        warn!("Client {} sent another handshake: {}", addr, rejection);
+        return send_error_response(
+            write,
+            request_id,
+            AuthError,
+            "Auth handshake already completed",
+        )
+        .await;
+    }
+
+    let ipc_state = ipc_state.clone();
+    let config_state = config_state.clone();
+    let connection_closed = connection_closed.clone();
+    let request_metrics = metrics.clone();
+    let metrics = metrics.clone();
+    let mut write = write.clone();
+    let message_type = message_type(&payload);
+    TokioSpawn(metrics::instrument(
+        request_metrics,
+        message_type,
+        async move {
+            if let Err(e) = handle_message(
+                payload,
+                &ipc_state,
+                &config_state,
+                &connection_closed,
+                &metrics,
+                request_id,
+                &mut write,
+            )
+            .await
+            {
+                error!(
+                    "Error handling message from {}: {}",
+                    addr,
+                    e.redacted_to_string()
+                );
+                if let Err(e) = send_error_details_response(
+                    &mut write,
+                    request_id,
+                    InternalError,
+                    &e.to_string(),
+                    &e,
+                )
+                .await
+                {
+                    error!("Failed to send error response to {}: {}", addr, e);
+                }
+            }
+        },
+    ));
     Ok(())
 }
 
+/// Parses a client message sent in [`WireFormat::Json`].
+///
+/// Accepts the serde form of [`IpcClientMessage`] (`{"request_id": 2, "payload":
+/// {"ListSessions": {}}}`) and, as a shorthand for the handshake, a bare object with a
+/// `token` (treated as request 1).
+fn decode_json_message(data: &[u8]) -> Result<IpcClientMessage, serde_json::Error> {
+    let value: serde_json::Value = serde_json::from_slice(data)?;
+    if value.get("token").is_none() {
+        return serde_json::from_value(value);
+    }
+
+    let handshake: JsonHandshake = serde_json::from_value(value)?;
+    Ok(IpcClientMessage {
+        request_id: 1,
+        payload: Some(ipc_client_message::Payload::AuthHandshake(
+            IpcAuthHandshake {
+                token: handshake.token,
+                protocol_version: handshake.protocol_version,
+            },
+        )),
+    })
+}
+
+/// Sends a close frame for a message over the size limit and returns the error to end
+/// the connection with.
+async fn reject_oversized(write: &mut IpcWriter, addr: SocketAddr, detail: &str) -> IpcError {
+    warn!(
+        "Client {} sent oversized message ({}), closing connection",
+        addr, detail
+    );
+    let close = CloseFrame {
+        code: CloseCode::Size,
+        reason: "Message too large".into(),
+    };
+    let _ = write.send_frame(Message::Close(Some(close))).await;
+    IpcError::Read {
+        message: format!("Message too large: {}", detail),
+        location: ErrorLocation::from(Location::caller()),
+    }
+}
+
+/// Completes the WebSocket handshake only to close the connection with a "try again" code.
+///
+/// Used when the server is at [`IpcServerOptions::max_connections`]; the handshake is
+/// bounded by [`REJECT_HANDSHAKE_TIMEOUT`] so rejected clients can't pile up tasks.
+async fn reject_over_capacity(stream: TcpStream, addr: SocketAddr) {
+    let close = async {
+        let mut ws_stream = accept_async_with_config(stream, None).await?;
+        let frame = CloseFrame {
+            code: CloseCode::Again,
+            reason: "Too many connections".into(),
+        };
+        ws_stream.close(Some(frame)).await
+    };
+    match timeout(REJECT_HANDSHAKE_TIMEOUT, close).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => debug!("Failed to close rejected connection from {}: {}", addr, e),
+        Err(_) => debug!("Rejected connection from {} timed out in handshake", addr),
+    }
+}
+
+/// Spawns the task that owns the WebSocket write half for a connection.
+///
+/// Handlers run concurrently and queue frames through the returned [`IpcWriter`];
+/// this task writes them to the socket in arrival order. The socket is closed
+/// once a close frame is written or every writer has been dropped.
+fn spawn_writer(
+    mut ws_write: SplitSink<WebSocketStream<TcpStream>, Message>,
+    addr: SocketAddr,
+    format: WireFormat,
+) -> IpcWriter {
+    let (tx, mut rx) = mpsc::channel::<Message>(WRITE_QUEUE_CAPACITY);
+
+    TokioSpawn(async move {
+        while let Some(frame) = rx.recv().await {
+            let is_close = matches!(frame, Message::Close(_));
+            if let Err(e) = ws_write.send(frame).await {
+                warn!("Failed to write to client {}: {}", addr, e);
+                break;
+            }
+            if is_close {
+                break;
+            }
+        }
+        let _ = ws_write.close().await;
+    });
+
+    IpcWriter {
+        frames: PollSender::new(tx),
+        format,
+    }
+}
+
 /// Send authentication response to client.
 ///
 /// # Arguments
 ///
-/// * `write` - WebSocket write half
+/// * `write` - Sink the response is sent to
 /// * `success` - Whether authentication succeeded
 /// * `error` - Optional error message (if authentication failed)
 ///
@@ -321,12 +891,10 @@ async fn handle_connection(
 ///
 /// Returns [`IpcError::ProtobufEncode`] if encoding fails, or [`IpcError::Send`] if sending fails.
 async fn send_auth_response(
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
     success: bool,
     error: Option<&str>,
+    protocol_version: u32,
 ) -> Result<(), IpcError> {
     let response = IpcServerMessage {
         request_id: 1, // Auth handshake always uses request_id 1
@@ -334,70 +902,112 @@ async fn send_auth_response(
             IpcAuthHandshakeResponse {
                 success,
                 error: error.map(|s| s.to_string()),
+                protocol_version,
             },
         )),
     };
 
-    let mut buf = Vec::new();
-    response
-        .encode(&mut buf)
-        .map_err(|e| IpcError::ProtobufEncode {
-            message: format!("Failed to encode auth response: {e}"),
-            location: ErrorLocation::from(Location::caller()),
-        })?;
-
-    write
-        .send(Message::Binary(buf.into()))
-        .await
-        .map_err(|e| IpcError::Send {
-            message: format!("Failed to send auth response: {e}"),
-            location: ErrorLocation::from(Location::caller()),
-        })
+    write.send(response).await
 }
 
 /// Send an error response to client.
 ///
 /// # Arguments
 ///
-/// * `write` - WebSocket write half
+/// * `write` - Sink the response is sent to
 /// * `request_id` - Request ID to correlate with original request
+/// * `error_code` - Error code enum
 /// * `error_message` - Human-readable error message
 ///
 /// # Errors
 ///
 /// Returns [`IpcError`] if encoding or sending fails.
 async fn send_error_response(
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
+    request_id: u64,
+    error_code: IpcErrorCode,
+    error_message: &str,
+) -> Result<(), IpcError> {
+    let error = IpcErrorResponse {
+        code: error_code as i32,
+        message: error_message.to_string(),
+        ..Default::default()
+    };
+    send_error_payload(write, request_id, error).await
+}
+
+/// Send an error response carrying the source error's location, category and retryability.
+///
+/// # Errors
+///
+/// Returns [`IpcError`] if encoding or sending fails.
+async fn send_error_details_response(
+    write: &mut impl MessageSink,
     request_id: u64,
     error_code: IpcErrorCode,
     error_message: &str,
+    error: &(dyn ErrorDetails + Sync),
+) -> Result<(), IpcError> {
+    let error = IpcErrorResponse::with_details(error_code, error_message.to_string(), error);
+    send_error_payload(write, request_id, error).await
+}
+
+async fn send_error_payload(
+    write: &mut impl MessageSink,
+    request_id: u64,
+    mut error: IpcErrorResponse,
 ) -> Result<(), IpcError> {
+    metrics::record_error_response(error.code);
+
+    // Messages often wrap OpenCode or auth errors; never send key material to the client
+    error.message = redact_secrets(&error.message);
+
     let response = IpcServerMessage {
         request_id,
-        payload: Some(ipc_server_message::Payload::Error(IpcErrorResponse {
-            code: error_code as i32,
-            message: error_message.to_string(),
-        })),
+        payload: Some(ipc_server_message::Payload::Error(error)),
     };
 
-    let mut buf = Vec::new();
-    response
-        .encode(&mut buf)
-        .map_err(|e| IpcError::ProtobufEncode {
-            message: format!("Failed to encode error response: {e}"),
-            location: ErrorLocation::from(Location::caller()),
-        })?;
+    write.send(response).await
+}
 
-    write
-        .send(Message::Binary(buf.into()))
-        .await
-        .map_err(|e| IpcError::Send {
-            message: format!("Failed to send error response: {e}"),
-            location: ErrorLocation::from(Location::caller()),
-        })
+/// Request field name of `payload` (as in `ipc.proto`), used as its metrics label.
+fn message_type(payload: &ipc_client_message::Payload) -> &'static str {
+    use ipc_client_message::Payload;
+
+    match payload {
+        Payload::AuthHandshake(_) => "auth_handshake",
+        Payload::DiscoverServer(_) => "discover_server",
+        Payload::SpawnServer(_) => "spawn_server",
+        Payload::CheckHealth(_) => "check_health",
+        Payload::StopServer(_) => "stop_server",
+        Payload::DiscoverAllServers(_) => "discover_all_servers",
+        Payload::EnsureServer(_) => "ensure_server",
+        Payload::ListSessions(_) => "list_sessions",
+        Payload::CreateSession(_) => "create_session",
+        Payload::DeleteSession(_) => "delete_session",
+        Payload::UpdateSession(_) => "update_session",
+        Payload::SetDirectory(_) => "set_directory",
+        Payload::ListAgents(_) => "list_agents",
+        Payload::GetProviderStatus(_) => "get_provider_status",
+        Payload::SetAuth(_) => "set_auth",
+        Payload::GetAuth(_) => "get_auth",
+        Payload::GetConfig(_) => "get_config",
+        Payload::UpdateConfig(_) => "update_config",
+        Payload::SyncAuthKeys(_) => "sync_auth_keys",
+        Payload::GetOauthStatus(_) => "get_oauth_status",
+        Payload::UpdateModelsConfig(_) => "update_models_config",
+        Payload::AddCuratedModel(_) => "add_curated_model",
+        Payload::RemoveCuratedModel(_) => "remove_curated_model",
+        Payload::GetConfigValue(_) => "get_config_value",
+        Payload::SetConfigValue(_) => "set_config_value",
+        Payload::ResetConfig(_) => "reset_config",
+        Payload::SendMessage(_) => "send_message",
+        Payload::StreamMessage(_) => "stream_message",
+        Payload::AbortMessage(_) => "abort_message",
+        Payload::SetLogLevel(_) => "set_log_level",
+        Payload::GetMetrics(_) => "get_metrics",
+        Payload::SubscribeEvents(_) => "subscribe_events",
+    }
 }
 
 /// Handle a single IPC message payload.
@@ -407,49 +1017,84 @@ async fn handle_message(
     payload: ipc_client_message::Payload,
     state: &IpcState,
     config_state: &ConfigState,
+    connection_closed: &CancellationToken,
+    metrics: &IpcMetrics,
     request_id: u64,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     use ipc_client_message::Payload;
 
     match payload {
         // Server Management - Call real handlers
         Payload::DiscoverServer(_req) => handle_discover_server(state, request_id, write).await,
-        Payload::SpawnServer(_req) => handle_spawn_server(state, request_id, _req, write).await,
+        Payload::SpawnServer(req) => handle_spawn_server(state, request_id, req, write).await,
         Payload::CheckHealth(_req) => handle_check_health(state, request_id, write).await,
         Payload::StopServer(_req) => handle_stop_server(state, request_id, write).await,
+        Payload::DiscoverAllServers(_req) => {
+            handle_discover_all_servers(state, request_id, write).await
+        }
+        Payload::EnsureServer(_req) => handle_ensure_server(state, request_id, write).await,
 
         // Sessions (stub)
         Payload::ListSessions(_req) => handle_list_sessions(state, request_id, write).await,
         Payload::CreateSession(req) => handle_create_session(state, request_id, req, write).await,
         Payload::DeleteSession(req) => handle_delete_session(state, request_id, req, write).await,
+        Payload::UpdateSession(req) => handle_update_session(state, request_id, req, write).await,
+        Payload::SetDirectory(req) => handle_set_directory(state, request_id, req, write).await,
 
         // Config Operations  // 🆕 NEW
         Payload::GetConfig(_req) => handle_get_config(config_state, request_id, write).await, // 🆕 NEW
         Payload::UpdateConfig(req) => {
             handle_update_config(config_state, request_id, req, write).await
         }
+        Payload::UpdateModelsConfig(req) => {
+            handle_update_models_config(config_state, request_id, req, write).await
+        }
+        Payload::AddCuratedModel(req) => {
+            handle_add_curated_model(config_state, request_id, req, write).await
+        }
+        Payload::RemoveCuratedModel(req) => {
+            handle_remove_curated_model(config_state, request_id, req, write).await
+        }
+        Payload::GetConfigValue(req) => {
+            handle_get_config_value(config_state, request_id, req, write).await
+        }
+        Payload::SetConfigValue(req) => {
+            handle_set_config_value(config_state, request_id, req, write).await
+        }
+        Payload::ResetConfig(req) => {
+            handle_reset_config(config_state, request_id, req, write).await
+        }
 
         // Auth Sync Operations
         Payload::SyncAuthKeys(req) => {
-            handle_sync_auth_keys(config_state, state, request_id, req, write).await
+            handle_sync_auth_keys(
+                config_state,
+                state,
+                connection_closed,
+                request_id,
+                req,
+                write,
+            )
+            .await
         }
 
         // Message Operations
-        Payload::SendMessage(req) => handle_send_message(state, request_id, req, write).await,
+        Payload::SendMessage(req) => {
+            handle_send_message(state, config_state, request_id, req, write).await
+        }
+        Payload::StreamMessage(req) => {
+            handle_stream_message(state, config_state, request_id, req, write).await
+        }
+        Payload::AbortMessage(req) => handle_abort_message(state, request_id, req, write).await,
 
-        // Auth handshake should not appear after initial auth
-        Payload::AuthHandshake(_) => {
-            send_error_response(
-                write,
-                request_id,
-                AuthError,
-                "Auth handshake already completed",
-            )
-            .await
+        // Diagnostics
+        Payload::SetLogLevel(req) => handle_set_log_level(request_id, req, write).await,
+        Payload::GetMetrics(_req) => handle_get_metrics(metrics, request_id, write).await,
+
+        // Events
+        Payload::SubscribeEvents(_req) => {
+            handle_subscribe_events(state, config_state, connection_closed, request_id, write).await
         }
 
         // Catch-all for other operations
@@ -466,25 +1111,34 @@ async fn handle_message(
 }
 
 /// Handle discover server request.
-async fn handle_discover_server(
+pub(crate) async fn handle_discover_server(
     state: &IpcState,
     request_id: u64,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!("Handling discover_server request");
 
-    let result = process::discover().map_err(|e| IpcError::Io {
-        message: format!("Discovery failed: {e}"),
-        location: ErrorLocation::from(Location::caller()),
-    })?;
+    let discovered = match discovery::get_remote_server() {
+        Some(remote_url) => process::discover_remote(&remote_url).await,
+        None => process::discover(),
+    };
+    let result = match discovered {
+        Ok(result) => result,
+        Err(e) => {
+            warn!("discover_server failed: {e}");
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Discovery failed: {e}"),
+                &e,
+            )
+            .await;
+        }
+    };
 
     if let Some(ref server_info) = result {
-        state
-            .update(StateCommand::SetServer(server_info.clone()))
-            .await?;
+        state.set_server(server_info.clone()).await?;
         info!(
             "Discovered server: PID={}, port={}",
             server_info.pid, server_info.port
@@ -500,31 +1154,102 @@ async fn handle_discover_server(
         )),
     };
 
-    send_protobuf_response(write, &response).await
+    write.send(response).await
 }
 
-/// Handle spawn server request.
-async fn handle_spawn_server(
+/// Handle discover all servers request.
+///
+/// Every server found is tracked by its base URL so it can be selected later; the
+/// active server is left unchanged.
+async fn handle_discover_all_servers(
     state: &IpcState,
     request_id: u64,
-    _req: IpcSpawnServerRequest,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
-    info!("Handling spawn_server request");
-
-    let server_info = spawn::spawn_and_wait().await.map_err(|e| IpcError::Io {
-        message: format!("Spawn failed: {e}"),
-        location: ErrorLocation::from(Location::caller()),
-    })?;
+    info!("Handling discover_all_servers request");
 
-    state
-        .update(StateCommand::SetServer(server_info.clone()))
-        .await?;
-    info!(
-        "Spawned server: PID={}, port={}",
+    let servers = match process::discover_all().await {
+        Ok(servers) => servers,
+        Err(e) => {
+            warn!("discover_all_servers failed: {e}");
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Discovery failed: {e}"),
+                &e,
+            )
+            .await;
+        }
+    };
+
+    for server in &servers {
+        state
+            .update(StateCommand::AddServer {
+                id: server.base_url.clone(),
+                server: server.clone(),
+            })
+            .await?;
+    }
+    info!("Discovered {} server(s)", servers.len());
+
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::DiscoverAllServersResponse(
+            IpcDiscoverAllServersResponse { servers },
+        )),
+    };
+
+    write.send(response).await
+}
+
+/// Handle spawn server request.
+async fn handle_spawn_server(
+    state: &IpcState,
+    request_id: u64,
+    req: IpcSpawnServerRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling spawn_server request");
+
+    let port = match req.port {
+        Some(port) => match u16::try_from(port) {
+            Ok(port) => Some(port),
+            Err(_) => {
+                return send_error_response(
+                    write,
+                    request_id,
+                    InvalidMessage,
+                    &format!("Port {port} is out of range"),
+                )
+                .await;
+            }
+        },
+        None => None,
+    };
+    let options = spawn::SpawnOptions {
+        port,
+        ..Default::default()
+    };
+
+    let server_info = match spawn::spawn_and_wait_with(&options).await {
+        Ok(server_info) => server_info,
+        Err(e) => {
+            warn!("spawn_server failed: {e}");
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Spawn failed: {e}"),
+                &e,
+            )
+            .await;
+        }
+    };
+
+    state.set_server(server_info.clone()).await?;
+    info!(
+        "Spawned server: PID={}, port={}",
         server_info.pid, server_info.port
     );
 
@@ -537,58 +1262,141 @@ async fn handle_spawn_server(
         )),
     };
 
-    send_protobuf_response(write, &response).await
+    write.send(response).await
+}
+
+/// Handle ensure server request.
+///
+/// Whichever server results, discovered or spawned, becomes the active server.
+async fn handle_ensure_server(
+    state: &IpcState,
+    request_id: u64,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling ensure_server request");
+
+    let server_info = match discovery::ensure_server().await {
+        Ok(server_info) => server_info,
+        Err(e) => {
+            warn!("ensure_server failed: {e}");
+            let message = format!("Ensure server failed: {e}");
+            return match e.details() {
+                Some(details) => {
+                    send_error_details_response(
+                        write,
+                        request_id,
+                        IpcErrorCode::ServerError,
+                        &message,
+                        details,
+                    )
+                    .await
+                }
+                None => {
+                    send_error_response(write, request_id, IpcErrorCode::ServerError, &message)
+                        .await
+                }
+            };
+        }
+    };
+
+    state.set_server(server_info.clone()).await?;
+    info!(
+        "Ensured server: PID={}, port={}, owned={}",
+        server_info.pid, server_info.port, server_info.owned
+    );
+
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::EnsureServerResponse(
+            IpcEnsureServerResponse {
+                server: Some(server_info),
+            },
+        )),
+    };
+
+    write.send(response).await
 }
 
 /// Handle check health request.
-async fn handle_check_health(
+pub(crate) async fn handle_check_health(
     state: &IpcState,
     request_id: u64,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!("Handling check_health request");
 
-    let server_info = state.get_server().await.ok_or_else(|| IpcError::Io {
-        message: "No server connected".to_string(),
-        location: ErrorLocation::from(Location::caller()),
-    })?;
+    let (id, server_info) = state
+        .get_active_server()
+        .await
+        .ok_or_else(|| IpcError::Io {
+            message: "No server connected".to_string(),
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+
+    let probe = process::probe(&server_info.base_url).await;
+    let healthy = probe
+        .health(&process::HealthCheckConfig::default())
+        .is_healthy();
+    info!("Health check result: {healthy} ({probe:?})");
 
-    let healthy = process::check_health(&server_info.base_url).await;
-    info!("Health check result: {healthy}");
+    state
+        .update(StateCommand::RecordHealth { id, healthy })
+        .await?;
 
     let response = IpcServerMessage {
         request_id,
         payload: Some(ipc_server_message::Payload::CheckHealthResponse(
-            IpcCheckHealthResponse { healthy },
+            IpcCheckHealthResponse {
+                healthy,
+                probe: Some(probe.into()),
+            },
         )),
     };
 
-    send_protobuf_response(write, &response).await
+    write.send(response).await
 }
 
 /// Handle stop server request.
-async fn handle_stop_server(
+pub(crate) async fn handle_stop_server(
     state: &IpcState,
     request_id: u64,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!("Handling stop_server request");
 
-    let server_info = state.get_server().await.ok_or_else(|| IpcError::Io {
-        message: "No server connected".to_string(),
-        location: ErrorLocation::from(Location::caller()),
-    })?;
+    let (server_id, server_info) = state
+        .get_active_server()
+        .await
+        .ok_or_else(|| IpcError::Io {
+            message: "No server connected".to_string(),
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+
+    // Servers we spawned are stopped through their handle; anything else by PID
+    let result = if spawn::stop_owned_server(server_info.pid).await {
+        Ok(true)
+    } else {
+        process::stop_server_info(&server_info)
+    };
 
-    let success = process::stop_pid(server_info.pid);
+    let success = match result {
+        Ok(success) => success,
+        Err(e) => {
+            warn!("Refused to stop server PID={}: {e}", server_info.pid);
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &e.to_string(),
+                &e,
+            )
+            .await;
+        }
+    };
 
     if success {
-        state.update(StateCommand::ClearServer).await?;
+        // Remove by ID: the active selection may have changed while stopping
+        state.update(StateCommand::RemoveServer(server_id)).await?;
         info!("Stopped server PID={}", server_info.pid);
     } else {
         warn!("Failed to stop server PID={}", server_info.pid);
@@ -601,42 +1409,14 @@ async fn handle_stop_server(
         )),
     };
 
-    send_protobuf_response(write, &response).await
-}
-
-/// Send a protobuf response message.
-async fn send_protobuf_response(
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
-    response: &IpcServerMessage,
-) -> Result<(), IpcError> {
-    let mut buf = Vec::new();
-    response
-        .encode(&mut buf)
-        .map_err(|e| IpcError::ProtobufEncode {
-            message: format!("Failed to encode response: {e}"),
-            location: ErrorLocation::from(Location::caller()),
-        })?;
-
-    write
-        .send(Message::Binary(buf.into()))
-        .await
-        .map_err(|e| IpcError::Send {
-            message: format!("Failed to send response: {e}"),
-            location: ErrorLocation::from(Location::caller()),
-        })
+    write.send(response).await
 }
 
 /// Handle list sessions request.
-async fn handle_list_sessions(
+pub(crate) async fn handle_list_sessions(
     state: &IpcState,
     request_id: u64,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!("Handling list_sessions request");
 
@@ -648,10 +1428,20 @@ async fn handle_list_sessions(
             location: ErrorLocation::from(Location::caller()),
         })?;
 
-    let sessions = client.list_sessions().await.map_err(|e| IpcError::Io {
-        message: format!("Failed to list sessions: {e}"),
-        location: ErrorLocation::from(Location::caller()),
-    })?;
+    let sessions = match client.list_sessions().await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            warn!("list_sessions failed: {}", e.redacted_to_string());
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Failed to list sessions: {e}"),
+                &e,
+            )
+            .await;
+        }
+    };
 
     let response = IpcServerMessage {
         request_id,
@@ -660,7 +1450,7 @@ async fn handle_list_sessions(
         })),
     };
 
-    send_protobuf_response(write, &response).await
+    write.send(response).await
 }
 
 /// Handle create session request.
@@ -668,10 +1458,7 @@ async fn handle_create_session(
     state: &IpcState,
     request_id: u64,
     req: IpcCreateSessionRequest,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!("Handling create_session request");
 
@@ -685,20 +1472,27 @@ async fn handle_create_session(
 
     let title = req.title.as_deref();
 
-    let session = client
-        .create_session(title)
-        .await
-        .map_err(|e| IpcError::Io {
-            message: format!("Failed to create session: {e}"),
-            location: ErrorLocation::from(Location::caller()),
-        })?;
+    let session = match client.create_session(title).await {
+        Ok(session) => session,
+        Err(e) => {
+            warn!("create_session failed: {}", e.redacted_to_string());
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Failed to create session: {e}"),
+                &e,
+            )
+            .await;
+        }
+    };
 
     let response = IpcServerMessage {
         request_id,
         payload: Some(ipc_server_message::Payload::SessionInfo(session)),
     };
 
-    send_protobuf_response(write, &response).await
+    write.send(response).await
 }
 
 /// Handle delete session request.
@@ -706,28 +1500,32 @@ async fn handle_delete_session(
     state: &IpcState,
     request_id: u64,
     req: IpcDeleteSessionRequest,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!("Handling delete_session request: {}", req.session_id);
 
     let client = state
-        .get_opencode_client()
+        .get_session_opencode_client(&req.session_id)
         .await
         .ok_or_else(|| IpcError::Io {
             message: "No OpenCode server connected".to_string(),
             location: ErrorLocation::from(Location::caller()),
         })?;
 
-    let success = client
-        .delete_session(&req.session_id)
-        .await
-        .map_err(|e| IpcError::Io {
-            message: format!("Failed to delete session: {}", e),
-            location: ErrorLocation::from(Location::caller()),
-        })?;
+    let success = match client.delete_session(&req.session_id).await {
+        Ok(success) => success,
+        Err(e) => {
+            warn!("delete_session failed: {}", e.redacted_to_string());
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Failed to delete session: {e}"),
+                &e,
+            )
+            .await;
+        }
+    };
 
     let response = IpcServerMessage {
         request_id,
@@ -736,17 +1534,90 @@ async fn handle_delete_session(
         )),
     };
 
-    send_protobuf_response(write, &response).await
+    write.send(response).await
+}
+
+/// Handle update session request (rename).
+///
+/// Blank titles are rejected as invalid; an unknown session is reported with the
+/// `not_found` category.
+pub(crate) async fn handle_update_session(
+    state: &IpcState,
+    request_id: u64,
+    req: IpcUpdateSessionRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling update_session request: {}", req.session_id);
+
+    if req.session_id.is_empty() {
+        return send_error_response(write, request_id, InvalidMessage, "Missing session_id").await;
+    }
+    if req.title.trim().is_empty() {
+        return send_error_response(write, request_id, InvalidMessage, "Title cannot be empty")
+            .await;
+    }
+
+    let Some(client) = state.get_session_opencode_client(&req.session_id).await else {
+        return send_error_response(
+            write,
+            request_id,
+            IpcErrorCode::NoServer,
+            "No OpenCode server connected. Please start the server first.",
+        )
+        .await;
+    };
+
+    match client.update_session(&req.session_id, &req.title).await {
+        Ok(session) => {
+            let response = IpcServerMessage {
+                request_id,
+                payload: Some(ipc_server_message::Payload::SessionInfo(session)),
+            };
+            write.send(response).await
+        }
+        Err(e) => {
+            warn!("update_session failed: {}", e.redacted_to_string());
+            send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Failed to update session: {e}"),
+                &e,
+            )
+            .await
+        }
+    }
+}
+
+/// Handle set directory request.
+async fn handle_set_directory(
+    state: &IpcState,
+    request_id: u64,
+    req: IpcSetDirectoryRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!(
+        "Handling set_directory request: session={:?}, directory={:?}",
+        req.session_id, req.directory
+    );
+
+    state.set_directory(req.session_id, req.directory).await?;
+
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::SetDirectoryResponse(
+            IpcSetDirectoryResponse { success: true },
+        )),
+    };
+
+    write.send(response).await
 }
 
 /// Handle get config request.
-async fn handle_get_config(
+pub(crate) async fn handle_get_config(
     config_state: &ConfigState,
     request_id: u64,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!("Handling get_config request");
 
@@ -774,228 +1645,440 @@ async fn handle_get_config(
         )),
     };
 
-    send_protobuf_response(write, &response).await
+    write.send(response).await
 }
 
-/// Handle update config request.
-async fn handle_update_config(
+/// Handle get config value request.
+///
+/// Returns the value (or subtree) at a dotted key path instead of the whole config.
+pub(crate) async fn handle_get_config_value(
     config_state: &ConfigState,
     request_id: u64,
-    req: IpcUpdateConfigRequest,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    req: IpcGetConfigValueRequest,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
-    info!("Handling update_config request");
+    info!("Handling get_config_value: key_path={}", req.key_path);
 
-    // Deserialize JSON
-    let new_config: AppConfig = match serde_json::from_str(&req.config_json) {
-        Ok(config) => config,
+    let value = match config_state.get_app_config().await.get_value(&req.key_path) {
+        Ok(value) => value,
         Err(e) => {
-            let error_msg = format!("Invalid config JSON: {}", e);
-            error!("{}", error_msg);
-            let response = IpcServerMessage {
-                request_id,
-                payload: Some(ipc_server_message::Payload::UpdateConfigResponse(
-                    IpcUpdateConfigResponse {
-                        success: false,
-                        error: Some(error_msg),
-                    },
-                )),
-            };
-            return send_protobuf_response(write, &response).await;
+            return send_error_response(write, request_id, InvalidMessage, &e.to_string()).await;
         }
     };
 
-    // Send update command to actor
-    match config_state
-        .update(crate::ipc::config_state::ConfigCommand::UpdateAppConfig(
-            new_config,
-        ))
-        .await
+    send_config_value_response(write, request_id, req.key_path, &value).await
+}
+
+/// Handle set config value request.
+///
+/// The patched config is validated as a whole; rejections are sent as error responses
+/// and leave the config unchanged.
+async fn handle_set_config_value(
+    config_state: &ConfigState,
+    request_id: u64,
+    req: IpcSetConfigValueRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling set_config_value: key_path={}", req.key_path);
+
+    let value = match serde_json::from_str(&req.value_json) {
+        Ok(value) => value,
+        Err(e) => {
+            let message = format!("Invalid value JSON for '{}': {e}", req.key_path);
+            return send_error_response(write, request_id, InvalidMessage, &message).await;
+        }
+    };
+
+    let stored = match config_state
+        .set_app_config_value(&req.key_path, value)
+        .await?
     {
-        Ok(_) => {
-            info!("Config updated successfully");
-            let response = IpcServerMessage {
-                request_id,
-                payload: Some(ipc_server_message::Payload::UpdateConfigResponse(
-                    IpcUpdateConfigResponse {
-                        success: true,
-                        error: None,
-                    },
-                )),
-            };
-            send_protobuf_response(write, &response).await
+        Ok(stored) => stored,
+        Err(e @ (ConfigError::UnknownKey { .. } | ConfigError::ValidationError { .. })) => {
+            error!("Config value rejected: {}", e);
+            return send_error_response(write, request_id, InvalidMessage, &e.to_string()).await;
         }
         Err(e) => {
-            let error_msg = format!("Failed to update config: {}", e);
-            error!("{}", error_msg);
-            let response = IpcServerMessage {
-                request_id,
-                payload: Some(ipc_server_message::Payload::UpdateConfigResponse(
-                    IpcUpdateConfigResponse {
-                        success: false,
-                        error: Some(error_msg),
-                    },
-                )),
-            };
-            send_protobuf_response(write, &response).await
+            error!("Failed to save config: {}", e);
+            let message = format!("Failed to save config: {e}");
+            return send_error_response(write, request_id, InternalError, &message).await;
         }
-    }
+    };
+
+    send_config_value_response(write, request_id, req.key_path, &stored).await
 }
 
-async fn handle_sync_auth_keys(
+async fn send_config_value_response(
+    write: &mut impl MessageSink,
+    request_id: u64,
+    key_path: String,
+    value: &serde_json::Value,
+) -> Result<(), IpcError> {
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::ConfigValueResponse(
+            IpcConfigValueResponse {
+                key_path,
+                value_json: value.to_string(),
+            },
+        )),
+    };
+
+    write.send(response).await
+}
+
+/// Handle reset config request.
+///
+/// Unknown section names are sent as error responses and leave the config unchanged.
+async fn handle_reset_config(
     config_state: &ConfigState,
-    state: &IpcState,
     request_id: u64,
-    req: IpcSyncAuthKeysRequest,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
-        tokio_tungstenite::tungstenite::Message,
-    >,
+    req: IpcResetConfigRequest,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
-    use crate::auth_sync::{load_env_api_keys, oauth::check_oauth_status};
-    use std::time::Instant;
+    info!("Handling reset_config: section={:?}", req.section);
 
-    info!(
-        "Handling sync_auth_keys request (skip_oauth={})",
-        req.skip_oauth_providers
-    );
+    let config = match config_state.reset_app_config(&req.section).await? {
+        Ok(config) => config,
+        Err(e @ ConfigError::UnknownKey { .. }) => {
+            let message = format!("Unknown config section '{}': {e}", req.section);
+            return send_error_response(write, request_id, InvalidMessage, &message).await;
+        }
+        Err(e) => {
+            error!("Failed to save config: {}", e);
+            let message = format!("Failed to save config: {e}");
+            return send_error_response(write, request_id, InternalError, &message).await;
+        }
+    };
 
-    let start = Instant::now();
+    let app_config_json = serde_json::to_string(&config).map_err(|e| IpcError::Io {
+        message: format!("Failed to serialize app config: {}", e),
+        location: ErrorLocation::from(Location::caller()),
+    })?;
 
-    // Load models config
-    let models_config = config_state.get_models_config().await;
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::ResetConfigResponse(
+            IpcResetConfigResponse { app_config_json },
+        )),
+    };
 
-    // Get OpenCode client
-    let opencode_client = match state.get_opencode_client().await {
-        Some(client) => client,
-        None => {
-            error!("No OpenCode server connected");
-            send_error_response(
-                write,
-                request_id,
-                InternalError,
-                "No OpenCode server connected",
-            )
-            .await?;
-            return Ok(());
+    write.send(response).await
+}
+
+/// Handle update config request.
+///
+/// Waits for the config actor's outcome so a rejected config (e.g. out-of-range font size)
+/// is reported with its validation reason instead of as success.
+async fn handle_update_config(
+    config_state: &ConfigState,
+    request_id: u64,
+    req: IpcUpdateConfigRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling update_config request");
+
+    let result = match serde_json::from_str::<AppConfig>(&req.config_json) {
+        Err(e) => Err(format!("Invalid config JSON: {}", e)),
+        Ok(new_config) => match config_state.update_app_config(new_config).await {
+            Err(e) => Err(format!("Failed to update config: {}", e)),
+            Ok(Err(ConfigError::ValidationError { reason, .. })) => {
+                Err(format!("Invalid config: {}", reason))
+            }
+            Ok(Err(e)) => Err(format!("Failed to save config: {}", e)),
+            Ok(Ok(())) => Ok(()),
+        },
+    };
+
+    let response = match result {
+        Ok(()) => {
+            info!("Config updated successfully");
+            IpcUpdateConfigResponse {
+                success: true,
+                error: None,
+            }
+        }
+        Err(error_msg) => {
+            error!("{}", error_msg);
+            IpcUpdateConfigResponse {
+                success: false,
+                error: Some(error_msg),
+            }
         }
     };
 
-    // Load API keys from environment
-    let loaded_keys = load_env_api_keys(&models_config);
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::UpdateConfigResponse(response)),
+    };
+    write.send(response).await
+}
 
-    let mut synced = Vec::new();
-    let mut failed = Vec::new();
-    let mut skipped = Vec::new();
-
-    // Process each loaded key
-    for (provider, key) in &loaded_keys.keys {
-        // Check OAuth status if requested
-        if req.skip_oauth_providers {
-            match check_oauth_status(provider) {
-                Ok(status) if status.should_skip_api_key_sync() => {
-                    info!("Skipping provider '{}' - OAuth configured", provider);
-                    skipped.push(IpcProviderSyncResult {
-                        provider: provider.clone(),
-                        error: String::new(),
-                        retryable: false,
-                        error_category: String::new(),
-                        status_code: None,
-                    });
-                    continue;
-                }
-                Ok(_) => {} // Not OAuth, proceed with sync
-                Err(e) => {
-                    warn!(
-                        "Failed to check OAuth status for '{}': {}, proceeding with sync",
-                        provider, e
-                    );
-                }
+/// Handle update models config request.
+///
+/// Waits for the config actor's outcome so an invalid config (e.g. unknown `auth_type`) or
+/// a failed write of models.toml is reported back to the caller instead of as success.
+async fn handle_update_models_config(
+    config_state: &ConfigState,
+    request_id: u64,
+    req: IpcUpdateModelsConfigRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling update_models_config request");
+
+    let result = match serde_json::from_str::<ModelsConfig>(&req.models_config_json) {
+        Err(e) => Err(format!("Invalid models config JSON: {}", e)),
+        Ok(new_config) => match config_state.update_models_config(new_config).await {
+            Err(e) => Err(format!("Failed to update models config: {}", e)),
+            Ok(Err(e @ ConfigError::ValidationError { .. })) => {
+                Err(format!("Invalid models config: {}", e))
             }
-        }
+            Ok(Err(e)) => Err(format!("Failed to save models config: {}", e)),
+            Ok(Ok(())) => Ok(()),
+        },
+    };
 
-        // Sync to OpenCode server
-        match opencode_client.sync_api_key(provider, key.as_str()).await {
-            Ok(_) => {
-                info!("Successfully synced key for provider '{}'", provider);
-                synced.push(IpcProviderSyncResult {
-                    provider: provider.clone(),
-                    error: String::new(),
-                    retryable: false,
-                    error_category: String::new(),
-                    status_code: None,
-                });
+    let response = match result {
+        Ok(()) => {
+            info!("Models config updated successfully");
+            IpcUpdateConfigResponse {
+                success: true,
+                error: None,
             }
-            Err(e) => {
-                error!("Failed to sync key for provider '{}': {}", provider, e);
-                failed.push(IpcProviderSyncResult {
-                    provider: provider.clone(),
-                    error: e.to_string(),
-                    retryable: false, // TODO: Determine from error type
-                    error_category: "sync_error".to_string(),
-                    status_code: None,
-                });
+        }
+        Err(error_msg) => {
+            error!("{}", error_msg);
+            IpcUpdateConfigResponse {
+                success: false,
+                error: Some(error_msg),
             }
         }
+    };
+
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::UpdateModelsConfigResponse(
+            response,
+        )),
+    };
+    write.send(response).await
+}
+
+/// Handle add curated model request.
+///
+/// Duplicates (same provider + model_id) are ignored; responds with the curated list.
+async fn handle_add_curated_model(
+    config_state: &ConfigState,
+    request_id: u64,
+    req: IpcAddCuratedModelRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling add_curated_model request");
+
+    let Some(model) = req.model else {
+        return send_error_response(write, request_id, InvalidMessage, "Missing model").await;
+    };
+
+    if model.provider.is_empty() || model.model_id.is_empty() {
+        return send_error_response(
+            write,
+            request_id,
+            InvalidMessage,
+            "Curated model requires provider and model_id",
+        )
+        .await;
     }
 
-    // Convert validation errors
-    let validation_failed: Vec<IpcProviderSyncResult> = loaded_keys
-        .validation_errors
-        .iter()
-        .map(|(provider, err)| {
-            warn!("Validation failed for provider '{}': {}", provider, err);
-            IpcProviderSyncResult {
-                provider: provider.clone(),
-                error: err.to_string(),
-                retryable: false,
-                error_category: err.error_category().to_string(),
-                status_code: err.status_code().map(|c| c as u32),
-            }
+    let result = config_state
+        .add_curated_model(CuratedModel::new(
+            model.name,
+            model.provider,
+            model.model_id,
+        ))
+        .await?;
+
+    send_curated_models_result(write, request_id, result).await
+}
+
+/// Handle remove curated model request.
+///
+/// Removing a model that isn't curated is a no-op; responds with the curated list.
+async fn handle_remove_curated_model(
+    config_state: &ConfigState,
+    request_id: u64,
+    req: IpcRemoveCuratedModelRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!(
+        "Handling remove_curated_model request: {}/{}",
+        req.provider, req.model_id
+    );
+
+    let result = config_state
+        .remove_curated_model(&req.provider, &req.model_id)
+        .await?;
+
+    send_curated_models_result(write, request_id, result).await
+}
+
+/// Respond with the curated list, or an error if models.toml couldn't be saved.
+async fn send_curated_models_result(
+    write: &mut impl MessageSink,
+    request_id: u64,
+    result: Result<Vec<CuratedModel>, ConfigError>,
+) -> Result<(), IpcError> {
+    let curated = match result {
+        Ok(curated) => curated,
+        Err(e) => {
+            let error_msg = format!("Failed to save models config: {}", e);
+            error!("{}", error_msg);
+            return send_error_response(write, request_id, InternalError, &error_msg).await;
+        }
+    };
+
+    let models = curated
+        .into_iter()
+        .map(|m| IpcCuratedModel {
+            name: m.name,
+            provider: m.provider,
+            model_id: m.model_id,
         })
         .collect();
 
-    let duration_ms = start.elapsed().as_millis() as u64;
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::CuratedModelsResponse(
+            IpcCuratedModelsResponse { models },
+        )),
+    };
+    write.send(response).await
+}
 
+async fn handle_sync_auth_keys(
+    config_state: &ConfigState,
+    state: &IpcState,
+    connection_closed: &CancellationToken,
+    request_id: u64,
+    req: IpcSyncAuthKeysRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
     info!(
-        "Auth sync completed in {}ms: {} synced, {} failed, {} skipped, {} invalid",
-        duration_ms,
-        synced.len(),
-        failed.len(),
-        skipped.len(),
-        validation_failed.len()
+        "Handling sync_auth_keys request (skip_oauth={}, dry_run={})",
+        req.skip_oauth_providers, req.dry_run
     );
 
-    let response = IpcAuthSyncResponse {
-        synced,
-        failed,
-        skipped,
-        validation_failed,
-        duration_ms,
+    let start = Instant::now();
+
+    // Load models config
+    let models_config = config_state.get_models_config().await;
+
+    // Get OpenCode client
+    let opencode_client = match state.get_opencode_client().await {
+        Some(client) => client,
+        None => {
+            error!("No OpenCode server connected");
+            send_error_response(
+                write,
+                request_id,
+                InternalError,
+                "No OpenCode server connected",
+            )
+            .await?;
+            return Ok(());
+        }
     };
 
+    // Stop syncing if the client that asked goes away
+    let report = auth_sync::sync_loaded_keys(
+        &opencode_client,
+        auth_sync::load_env_api_keys(&models_config),
+        &sync_config_from_request(&req),
+        connection_closed,
+    )
+    .await;
+
+    let response = auth_sync_response(report, start.elapsed().as_millis() as u64);
+
     let server_msg = IpcServerMessage {
         request_id,
         payload: Some(ipc_server_message::Payload::AuthSyncResponse(response)),
     };
 
-    send_protobuf_response(write, &server_msg).await
+    write.send(server_msg).await
+}
+
+/// Sync options for an IPC request; unset fields keep the [`SyncConfig`] defaults.
+pub(crate) fn sync_config_from_request(req: &IpcSyncAuthKeysRequest) -> SyncConfig {
+    let defaults = SyncConfig::default();
+    SyncConfig {
+        skip_oauth_providers: req.skip_oauth_providers,
+        timeout: match req.timeout_secs {
+            0 => defaults.timeout,
+            secs => Duration::from_secs(u64::from(secs)),
+        },
+        concurrency: match req.concurrency {
+            0 => defaults.concurrency,
+            limit => limit as usize,
+        },
+        dry_run: req.dry_run,
+        ..defaults
+    }
+}
+
+/// Map a sync report to the IPC response.
+///
+/// Key validation failures are reported in `validation_failed` (never sent to the
+/// server); every other failure in `failed`. Providers are listed in name order.
+pub(crate) fn auth_sync_response(report: SyncReport, duration_ms: u64) -> IpcAuthSyncResponse {
+    let succeeded = |provider: String| IpcProviderSyncResult {
+        provider,
+        error: String::new(),
+        retryable: false,
+        error_category: String::new(),
+        status_code: None,
+    };
+
+    let mut failures: Vec<(String, AuthSyncError)> = report.failed.into_iter().collect();
+    failures.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut failed = Vec::new();
+    let mut validation_failed = Vec::new();
+    for (provider, err) in failures {
+        let result = IpcProviderSyncResult {
+            error: err.redacted_to_string(),
+            retryable: err.is_retryable(),
+            error_category: err.error_category().to_string(),
+            status_code: err.status_code().map(u32::from),
+            provider,
+        };
+        if matches!(err, AuthSyncError::KeyValidation { .. }) {
+            validation_failed.push(result);
+        } else {
+            failed.push(result);
+        }
+    }
+
+    IpcAuthSyncResponse {
+        synced: report.synced.into_iter().map(succeeded).collect(),
+        failed,
+        skipped: report.skipped_oauth.into_iter().map(succeeded).collect(),
+        validation_failed,
+        duration_ms,
+        dry_run: report.dry_run,
+        already_current: report.already_current.into_iter().map(succeeded).collect(),
+    }
 }
 
 /// Handle send_message request.
 ///
-/// Forwards the message to OpenCode server and returns the assistant response.
+/// Forwards the message to OpenCode server and returns the assistant response. The agent
+/// is checked against the models config's `[agents]` section.
 async fn handle_send_message(
     state: &IpcState,
+    config_state: &ConfigState,
     request_id: u64,
     req: IpcSendMessageRequest,
-    write: &mut futures_util::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    write: &mut impl MessageSink,
 ) -> Result<(), IpcError> {
     info!(
         "Handling send_message: session={}, model={}/{}, text_len={}",
@@ -1006,35 +2089,25 @@ async fn handle_send_message(
     );
 
     // Validate required fields
-    if req.session_id.is_empty() {
-        return send_error_response(write, request_id, InvalidMessage, "session_id is required")
-            .await;
-    }
-    if req.text.is_empty() {
-        return send_error_response(write, request_id, InvalidMessage, "text is required").await;
-    }
-    if req.model_id.is_empty() || req.provider_id.is_empty() {
-        return send_error_response(
-            write,
-            request_id,
-            InvalidMessage,
-            "model_id and provider_id are required",
-        )
-        .await;
+    if let Some(reason) =
+        missing_message_field(&req.session_id, &req.text, &req.model_id, &req.provider_id)
+    {
+        return send_error_response(write, request_id, InvalidMessage, reason).await;
     }
 
-    let client = match state.get_opencode_client().await {
+    let mut client = match state.get_session_opencode_client(&req.session_id).await {
         Some(c) => c,
         None => {
             return send_error_response(
                 write,
                 request_id,
-                IpcErrorCode::ServerError,
+                IpcErrorCode::NoServer,
                 "No OpenCode server connected. Please start the server first.",
             )
             .await;
         }
     };
+    client.set_agents(config_state.get_models_config().await.agents);
 
     match client
         .send_message(
@@ -1051,17 +2124,384 @@ async fn handle_send_message(
                 request_id,
                 payload: Some(ipc_server_message::Payload::SendMessageResponse(message)),
             };
-            send_protobuf_response(write, &response).await
+            write.send(response).await
         }
         Err(e) => {
-            error!("send_message failed: {}", e);
-            send_error_response(
+            error!("send_message failed: {}", e.redacted_to_string());
+            send_error_details_response(
                 write,
                 request_id,
                 IpcErrorCode::ServerError,
                 &format!("Failed to send message: {e}"),
+                &e,
             )
             .await
         }
     }
 }
+
+/// Handle stream message request.
+///
+/// Subscribes to the OpenCode event stream *before* sending, then forwards every part
+/// update for the session as an [`IpcMessagePartEvent`] while the send is in flight.
+/// Exactly one terminal frame follows: [`IpcMessageCompleteEvent`] with the final message,
+/// or an error response. All frames carry the request's `request_id`.
+async fn handle_stream_message(
+    state: &IpcState,
+    config_state: &ConfigState,
+    request_id: u64,
+    req: IpcStreamMessageRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!(
+        "Handling stream_message: session={}, model={}/{}, text_len={}",
+        req.session_id,
+        req.provider_id,
+        req.model_id,
+        req.text.len()
+    );
+
+    if let Some(reason) =
+        missing_message_field(&req.session_id, &req.text, &req.model_id, &req.provider_id)
+    {
+        return send_error_response(write, request_id, InvalidMessage, reason).await;
+    }
+
+    let Some(mut client) = state.get_session_opencode_client(&req.session_id).await else {
+        return send_error_response(
+            write,
+            request_id,
+            IpcErrorCode::NoServer,
+            "No OpenCode server connected. Please start the server first.",
+        )
+        .await;
+    };
+    client.set_agents(config_state.get_models_config().await.agents);
+
+    let mut events = match client.subscribe_events().await {
+        Ok(events) => Some(events),
+        Err(e) => {
+            // Still send the message; the caller just won't see intermediate parts
+            warn!(
+                "Event stream unavailable, streaming final message only: {}",
+                e
+            );
+            None
+        }
+    };
+
+    // Register before sending so an abort can end the stream cleanly
+    let cancel = CancellationToken::new();
+    state
+        .update(StateCommand::StreamStarted {
+            session_id: req.session_id.clone(),
+            request_id,
+            cancel: cancel.clone(),
+        })
+        .await?;
+
+    let streamed = async {
+        let send = client.send_message(
+            &req.session_id,
+            &req.text,
+            &req.model_id,
+            &req.provider_id,
+            req.agent.as_deref(),
+        );
+        tokio::pin!(send);
+
+        loop {
+            let Some(stream) = events.as_mut() else {
+                return Ok::<_, IpcError>(send.await);
+            };
+
+            tokio::select! {
+                result = &mut send => return Ok(result),
+                part = stream.next_part(&req.session_id) => match part {
+                    Ok(Some(part)) => {
+                        let event = IpcServerMessage {
+                            request_id,
+                            payload: Some(ipc_server_message::Payload::MessagePartEvent(
+                                IpcMessagePartEvent {
+                                    request_id,
+                                    part: Some(part),
+                                },
+                            )),
+                        };
+                        write.send(event).await?;
+                    }
+                    Ok(None) => {
+                        warn!("Event stream closed before message completed");
+                        events = None;
+                    }
+                    Err(e) => {
+                        warn!("Event stream failed, waiting for final message: {}", e.redacted_to_string());
+                        events = None;
+                    }
+                },
+            }
+        }
+    };
+
+    // Prefer the server's own reply if it finishes alongside the abort
+    let outcome = tokio::select! {
+        biased;
+        outcome = streamed => outcome,
+        _ = cancel.cancelled() => {
+            info!("stream_message aborted: session={}", req.session_id);
+            Ok(Ok(aborted_message(&req.session_id)))
+        }
+    };
+
+    // Report the stream's own outcome even if unregistering it fails
+    if let Err(e) = state
+        .update(StateCommand::StreamFinished {
+            session_id: req.session_id.clone(),
+            request_id,
+        })
+        .await
+    {
+        warn!(
+            "Failed to unregister stream for session {}: {}",
+            req.session_id, e
+        );
+    }
+    let result = outcome?;
+
+    match result {
+        Ok(message) => {
+            let response = IpcServerMessage {
+                request_id,
+                payload: Some(ipc_server_message::Payload::MessageCompleteEvent(
+                    IpcMessageCompleteEvent {
+                        request_id,
+                        message: Some(message),
+                    },
+                )),
+            };
+            write.send(response).await
+        }
+        Err(e) => {
+            error!("stream_message failed: {}", e.redacted_to_string());
+            send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Failed to send message: {e}"),
+                &e,
+            )
+            .await
+        }
+    }
+}
+
+/// Handle abort message request.
+///
+/// Aborts the generation on the OpenCode server, then ends the session's stream (if any)
+/// with an aborted complete event.
+async fn handle_abort_message(
+    state: &IpcState,
+    request_id: u64,
+    req: IpcAbortMessageRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling abort_message: session={}", req.session_id);
+
+    if req.session_id.is_empty() {
+        return send_error_response(write, request_id, InvalidMessage, "session_id is required")
+            .await;
+    }
+
+    let Some(client) = state.get_session_opencode_client(&req.session_id).await else {
+        return send_error_response(
+            write,
+            request_id,
+            IpcErrorCode::NoServer,
+            "No OpenCode server connected. Please start the server first.",
+        )
+        .await;
+    };
+
+    let success = match client.abort_message(&req.session_id).await {
+        Ok(aborted) => aborted,
+        Err(e) => {
+            error!("abort_message failed: {}", e.redacted_to_string());
+            return send_error_details_response(
+                write,
+                request_id,
+                IpcErrorCode::ServerError,
+                &format!("Failed to abort message: {e}"),
+                &e,
+            )
+            .await;
+        }
+    };
+
+    if state.cancel_stream(&req.session_id).await {
+        info!("Cancelled stream for session {}", req.session_id);
+    }
+
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::AbortMessageResponse(
+            IpcAbortMessageResponse { success },
+        )),
+    };
+
+    write.send(response).await
+}
+
+/// Handle set log level request.
+///
+/// Changes the process-wide log level (or one target's) immediately; see [`logging`].
+async fn handle_set_log_level(
+    request_id: u64,
+    req: IpcSetLogLevelRequest,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!(
+        "Handling set_log_level: level={} target={:?}",
+        req.level, req.target
+    );
+
+    let Some(level) = logging::parse_level(&req.level) else {
+        return send_error_response(
+            write,
+            request_id,
+            InvalidMessage,
+            &format!(
+                "Invalid log level '{}': expected one of {}",
+                req.level,
+                logging::LEVEL_NAMES
+            ),
+        )
+        .await;
+    };
+    let target = req.target.filter(|t| !t.is_empty());
+
+    let previous = logging::set_level(level, target.as_deref());
+    info!(
+        "Log level for {} changed from {previous} to {level}",
+        target.as_deref().unwrap_or("all targets")
+    );
+
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::SetLogLevelResponse(
+            IpcSetLogLevelResponse {
+                level: level.as_str().to_lowercase(),
+                previous_level: previous.as_str().to_lowercase(),
+                target: target.unwrap_or_default(),
+            },
+        )),
+    };
+
+    write.send(response).await
+}
+
+/// Handle get metrics request.
+async fn handle_get_metrics(
+    metrics: &IpcMetrics,
+    request_id: u64,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling get_metrics request");
+
+    let response = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::MetricsResponse(
+            metrics.snapshot(),
+        )),
+    };
+
+    write.send(response).await
+}
+
+/// Handle subscribe events request.
+///
+/// Acknowledges the subscription, then forwards every server and config state event
+/// (tagged with this request's ID) until the connection closes.
+async fn handle_subscribe_events(
+    state: &IpcState,
+    config_state: &ConfigState,
+    connection_closed: &CancellationToken,
+    request_id: u64,
+    write: &mut impl MessageSink,
+) -> Result<(), IpcError> {
+    info!("Handling subscribe_events request");
+
+    // Subscribe before acking so no event after the ack can be missed
+    let mut server_events = state.subscribe_events();
+    let mut config_events = config_state.subscribe_events();
+
+    let ack = IpcServerMessage {
+        request_id,
+        payload: Some(ipc_server_message::Payload::SubscribeEventsResponse(
+            IpcSubscribeEventsResponse {},
+        )),
+    };
+    write.send(ack).await?;
+
+    loop {
+        let event = tokio::select! {
+            _ = connection_closed.cancelled() => break,
+            event = server_events.recv() => event,
+            event = config_events.recv() => event,
+        };
+
+        let event = match event {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("Event subscription {request_id} fell behind, missed {missed} events");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        let push = IpcServerMessage {
+            request_id,
+            payload: Some(ipc_server_message::Payload::ServerStateEvent(event)),
+        };
+        if let Err(e) = write.send(push).await {
+            debug!("Event subscription {request_id} ended: {e}");
+            break;
+        }
+    }
+
+    info!("Event subscription {request_id} closed");
+    Ok(())
+}
+
+/// Final message for a stream ended by an abort.
+fn aborted_message(session_id: &str) -> OcMessage {
+    OcMessage {
+        message: Some(oc_message::Message::Assistant(OcAssistantMessage {
+            session_id: session_id.to_string(),
+            error: Some(OcMessageError {
+                error: Some(oc_message_error::Error::Aborted(OcAbortedError {
+                    name: "AbortedError".to_string(),
+                    message: "Generation cancelled by user".to_string(),
+                })),
+            }),
+            ..Default::default()
+        })),
+    }
+}
+
+/// Returns why a send/stream message request is invalid, if a required field is empty.
+fn missing_message_field(
+    session_id: &str,
+    text: &str,
+    model_id: &str,
+    provider_id: &str,
+) -> Option<&'static str> {
+    if session_id.is_empty() {
+        Some("session_id is required")
+    } else if text.is_empty() {
+        Some("text is required")
+    } else if model_id.is_empty() || provider_id.is_empty() {
+        Some("model_id and provider_id are required")
+    } else {
+        None
+    }
+}
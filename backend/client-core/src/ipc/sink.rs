@@ -0,0 +1,28 @@
+//! Destination for IPC handler output.
+//!
+//! Handlers only build [`IpcServerMessage`]s and hand them to a [`MessageSink`]. On a live
+//! connection the sink encodes each message for the client's wire format and queues it to
+//! the WebSocket writer; in tests a `Vec<IpcServerMessage>` collects them, so a handler can
+//! be called directly and its responses inspected without opening a socket.
+
+use crate::error::ipc::IpcError;
+use crate::proto::IpcServerMessage;
+
+/// Where a handler sends its responses and pushed events.
+pub(crate) trait MessageSink: Send {
+    /// Delivers `message` to the client, after any message sent before it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IpcError`] if the message can't be encoded or the client has gone away.
+    async fn send(&mut self, message: IpcServerMessage) -> Result<(), IpcError>;
+}
+
+/// In-memory sink: messages are appended in the order they were sent.
+#[cfg(test)]
+impl MessageSink for Vec<IpcServerMessage> {
+    async fn send(&mut self, message: IpcServerMessage) -> Result<(), IpcError> {
+        self.push(message);
+        Ok(())
+    }
+}
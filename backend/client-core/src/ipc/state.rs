@@ -2,13 +2,20 @@
 //!
 //! This module provides thread-safe state management for the IPC server.
 //! It tracks:
-//! - Current OpenCode server connection (PID, port, base_url, owned)
+//! - Every OpenCode server the app knows about (PID, port, base_url, owned), keyed by ID
+//! - Which of them is active (the one IPC handlers talk to)
+//! - The project directory sent to OpenCode (default and per-session overrides)
+//! - In-flight message streams, so they can be cancelled by an abort
+//! - The last health check result per server
+//!
+//! Changes clients may display are also published as events (see [`IpcState::subscribe_events`]).
 //!
 //! # Architecture
 //!
 //! Uses an actor pattern to ensure all state mutations are serialized:
 //! - Commands are sent via an mpsc channel
-//! - A dedicated task processes commands sequentially
+//! - A dedicated task processes commands sequentially, acknowledging each once applied
+//!   (so [`IpcState::update`] returning means the change is visible)
 //! - Reads use Arc<RwLock<T>> for lock-free concurrent access
 //!
 //! # Why Actor Pattern?
@@ -17,31 +24,101 @@
 //! - **Fast reads:** RwLock allows concurrent reads without blocking on writes
 //! - **Simple:** No need to reason about lock ordering or deadlocks
 
+use crate::discovery::now_epoch_millis;
 use crate::error::ipc::IpcError;
+use crate::error::opencode_client::OpencodeClientError;
+use crate::ipc::events::{self, event_channel};
 use crate::opencode_client::OpencodeClient;
-use crate::proto::IpcServerInfo;
+use crate::proto::{IpcServerInfo, IpcServerStateEvent};
 
 use common::ErrorLocation;
 
+use std::collections::HashMap;
 use std::panic::Location;
 use std::sync::Arc;
 
-use log::{info, warn};
-use tokio::sync::{Mutex, RwLock, mpsc};
+use log::{debug, info, warn};
+use tokio::sync::{Mutex, RwLock, broadcast, mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 
 /// Commands that mutate IPC state.
 ///
 /// All state mutations go through the state actor via these commands.
 /// This ensures serialized access and prevents race conditions.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum StateCommand {
-    /// Set the current server info (from discovery or spawn)
-    SetServer(IpcServerInfo),
+    /// Track a server keyed by its base URL and make it active (from discovery or spawn),
+    /// replying with the outcome
+    ///
+    /// Fails, leaving the previous selection in place, if no OpenCode client can be built
+    /// for the server's base URL.
+    SetServer {
+        server: IpcServerInfo,
+        reply: oneshot::Sender<Result<(), IpcError>>,
+    },
 
-    /// Clear the current server info (after stop)
+    /// Stop tracking the active server (after stop)
     ClearServer,
+
+    /// Track a server under `id` without changing the active selection (replaces same `id`)
+    AddServer { id: String, server: IpcServerInfo },
+
+    /// Stop tracking a server; clears the selection if it was active
+    RemoveServer(String),
+
+    /// Make a tracked server active (ignored if `id` isn't tracked)
+    SetActive(String),
+
+    /// Record a health check result for a tracked server (ignored if `id` isn't tracked)
+    ///
+    /// A pass updates `last_health_ok`; a result that differs from the previous one is
+    /// published as a health-changed event.
+    RecordHealth { id: String, healthy: bool },
+
+    /// Set the project directory for all clients (`session_id: None`) or one session.
+    /// `directory: None` removes the override.
+    SetDirectory {
+        session_id: Option<String>,
+        directory: Option<String>,
+    },
+
+    /// Register a session's in-flight stream (the `stream_message` request `request_id`)
+    /// so an abort can cancel it
+    StreamStarted {
+        session_id: String,
+        request_id: u64,
+        cancel: CancellationToken,
+    },
+
+    /// Unregister a stream once it has finished (other streams of the session stay)
+    StreamFinished { session_id: String, request_id: u64 },
 }
 
+/// In-flight streams: session ID -> `stream_message` request ID -> cancel token.
+type Streams = HashMap<String, HashMap<u64, CancellationToken>>;
+
+/// Servers tracked by the state actor and the active selection.
+#[derive(Default)]
+struct TrackedServers {
+    servers: HashMap<String, (IpcServerInfo, OpencodeClient)>,
+    active: Option<String>,
+    /// Directory applied to every client (from config `directory_override` or IPC)
+    directory: Option<String>,
+    /// Per-session directories, taking precedence over `directory`
+    session_directories: HashMap<String, String>,
+    /// Last health check result per server ID
+    health: HashMap<String, bool>,
+}
+
+impl TrackedServers {
+    fn active(&self) -> Option<&(IpcServerInfo, OpencodeClient)> {
+        self.servers.get(self.active.as_ref()?)
+    }
+}
+
+/// A command queued for the actor, with the sender it acknowledges once the command is applied.
+type QueuedCommand = (StateCommand, oneshot::Sender<()>);
+
 /// IPC state manager.
 ///
 /// Uses an actor pattern to ensure all state mutations are serialized.
@@ -57,16 +134,19 @@ pub enum StateCommand {
 #[derive(Clone)]
 pub struct IpcState {
     /// Channel to send state mutation commands to the actor
-    command_tx: Arc<Mutex<Option<mpsc::Sender<StateCommand>>>>,
+    command_tx: Arc<Mutex<Option<mpsc::Sender<QueuedCommand>>>>,
+
+    /// Shared read-only access to tracked servers (info + OpenCode HTTP client)
+    servers: Arc<RwLock<TrackedServers>>,
 
-    /// Shared read-only access to server info
-    server: Arc<RwLock<Option<IpcServerInfo>>>,
+    /// Shared read-only access to in-flight streams
+    streams: Arc<RwLock<Streams>>,
 
     /// Track if actor has been initialized
     actor_init: Arc<Mutex<bool>>,
 
-    /// Shared read-only access to OpenCode HTTP client
-    opencode_client: Arc<RwLock<Option<OpencodeClient>>>,
+    /// Server state change events for subscribed clients
+    events: broadcast::Sender<IpcServerStateEvent>,
 }
 
 impl IpcState {
@@ -76,15 +156,17 @@ impl IpcState {
     pub fn new() -> Self {
         Self {
             command_tx: Arc::new(Mutex::new(None)),
-            server: Arc::new(RwLock::new(None)),
+            servers: Arc::new(RwLock::new(TrackedServers::default())),
+            streams: Arc::new(RwLock::new(HashMap::new())),
             actor_init: Arc::new(Mutex::new(false)),
-            opencode_client: Arc::new(RwLock::new(None)),
+            events: event_channel(),
         }
     }
 
-    /// Send a state update command.
+    /// Send a state update command and wait until the actor has applied it.
     ///
-    /// This will spawn the actor on first call (lazy initialization).
+    /// Once this returns, every read (e.g. [`get_server`](Self::get_server)) sees the
+    /// change. This will spawn the actor on first call (lazy initialization).
     ///
     /// # Errors
     ///
@@ -92,35 +174,135 @@ impl IpcState {
     pub async fn update(&self, cmd: StateCommand) -> Result<(), IpcError> {
         self.ensure_actor().await;
 
-        let tx_guard = self.command_tx.lock().await;
-        let tx = tx_guard.as_ref().ok_or_else(|| IpcError::Io {
-            message: "State actor not initialized".to_string(),
-            location: ErrorLocation::from(Location::caller()),
-        })?;
+        let (ack, applied) = oneshot::channel();
+        {
+            let tx_guard = self.command_tx.lock().await;
+            let tx = tx_guard.as_ref().ok_or_else(|| IpcError::Io {
+                message: "State actor not initialized".to_string(),
+                location: ErrorLocation::from(Location::caller()),
+            })?;
+
+            tx.send((cmd, ack)).await.map_err(|e| IpcError::Io {
+                message: format!("State actor died: {}", e),
+                location: ErrorLocation::from(Location::caller()),
+            })?;
+        }
 
-        tx.send(cmd).await.map_err(|e| IpcError::Io {
-            message: format!("State actor died: {}", e),
+        applied.await.map_err(|e| IpcError::Io {
+            message: format!("State actor dropped command: {}", e),
             location: ErrorLocation::from(Location::caller()),
         })
     }
 
-    /// Get current server info (read-only).
+    /// Get the active server info (read-only).
     ///
     /// This is a lock-free read using RwLock, so it's fast and won't
     /// block on state mutations.
     ///
     /// # Returns
     ///
-    /// Returns `Some(IpcServerInfo)` if a server is connected, or `None` if not.
+    /// Returns `Some(IpcServerInfo)` if a server is active, or `None` if not.
     pub async fn get_server(&self) -> Option<IpcServerInfo> {
-        self.server.read().await.clone()
+        self.get_active_server().await.map(|(_, server)| server)
+    }
+
+    /// Get the active server's ID and info (read-only).
+    pub async fn get_active_server(&self) -> Option<(String, IpcServerInfo)> {
+        let servers = self.servers.read().await;
+        let id = servers.active.clone()?;
+        let (server, _) = servers.active()?;
+        Some((id, server.clone()))
     }
 
-    /// Get current OpenCode client (read-only).
+    /// Get the active server's OpenCode client (read-only).
     ///
-    /// Returns `Some(OpencodeClient)` if connected to a server, or `None` if not.
+    /// Returns `Some(OpencodeClient)` if a server is active, or `None` if not.
     pub async fn get_opencode_client(&self) -> Option<OpencodeClient> {
-        self.opencode_client.read().await.clone()
+        self.servers
+            .read()
+            .await
+            .active()
+            .map(|(_, client)| client.clone())
+    }
+
+    /// Get the active server's OpenCode client for requests about `session_id`.
+    ///
+    /// Uses the session's directory override if one is set, otherwise the default directory.
+    pub async fn get_session_opencode_client(&self, session_id: &str) -> Option<OpencodeClient> {
+        let servers = self.servers.read().await;
+        let (_, client) = servers.active()?;
+        let mut client = client.clone();
+        if let Some(directory) = servers.session_directories.get(session_id) {
+            client.set_directory(Some(directory.clone()));
+        }
+        Some(client)
+    }
+
+    /// Track `server`, make it active, and wait until it is applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IpcError::Io`] if no OpenCode client can be built for the server's base
+    /// URL (the previously active server, if any, stays active), or if the state actor has
+    /// died.
+    pub async fn set_server(&self, server: IpcServerInfo) -> Result<(), IpcError> {
+        let (reply, rx) = oneshot::channel();
+        self.update(StateCommand::SetServer { server, reply })
+            .await?;
+        rx.await.map_err(|e| IpcError::Io {
+            message: format!("State actor dropped reply: {}", e),
+            location: ErrorLocation::from(Location::caller()),
+        })?
+    }
+
+    /// Set the project directory sent to OpenCode and wait until it is applied.
+    ///
+    /// With `session_id: None` this sets the default for all clients (current and future);
+    /// otherwise it overrides the directory for that session only. `directory: None`
+    /// removes the override.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IpcError::Io`] if the state actor has died (should never happen).
+    pub async fn set_directory(
+        &self,
+        session_id: Option<String>,
+        directory: Option<String>,
+    ) -> Result<(), IpcError> {
+        self.update(StateCommand::SetDirectory {
+            session_id,
+            directory,
+        })
+        .await
+    }
+
+    /// Cancel the session's in-flight streams, if any.
+    ///
+    /// Returns `true` if a stream was cancelled.
+    pub async fn cancel_stream(&self, session_id: &str) -> bool {
+        match self.streams.read().await.get(session_id) {
+            Some(streams) if !streams.is_empty() => {
+                streams.values().for_each(CancellationToken::cancel);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Subscribe to server state changes (active server set/cleared, health changed).
+    pub fn subscribe_events(&self) -> broadcast::Receiver<IpcServerStateEvent> {
+        self.events.subscribe()
+    }
+
+    /// Get all tracked servers by ID (read-only).
+    pub async fn get_servers(&self) -> HashMap<String, IpcServerInfo> {
+        self.servers
+            .read()
+            .await
+            .servers
+            .iter()
+            .map(|(id, (server, _))| (id.clone(), server.clone()))
+            .collect()
     }
 
     /// Ensure actor is spawned (called lazily from async context).
@@ -131,15 +313,16 @@ impl IpcState {
         let mut init_guard = self.actor_init.lock().await;
         if !*init_guard {
             let (tx, rx) = mpsc::channel(100);
-            let server_clone = Arc::clone(&self.server);
-            let client_clone = Arc::clone(&self.opencode_client);
+            let servers_clone = Arc::clone(&self.servers);
+            let streams_clone = Arc::clone(&self.streams);
+            let events_clone = self.events.clone();
 
             // Store tx BEFORE spawning to avoid race
             let mut tx_guard = self.command_tx.lock().await;
             *tx_guard = Some(tx);
             drop(tx_guard); // Release before spawn
 
-            tokio::spawn(state_actor(rx, server_clone, client_clone));
+            tokio::spawn(state_actor(rx, servers_clone, streams_clone, events_clone));
             *init_guard = true;
             info!("IPC state actor spawned");
         }
@@ -161,66 +344,190 @@ impl Default for IpcState {
 /// This function runs in a dedicated tokio task and processes commands
 /// until the channel is closed (which happens when all IpcState handles are dropped).
 async fn state_actor(
-    mut command_rx: mpsc::Receiver<StateCommand>,
-    server: Arc<RwLock<Option<IpcServerInfo>>>,
-    opencode_client: Arc<RwLock<Option<OpencodeClient>>>,
+    mut command_rx: mpsc::Receiver<QueuedCommand>,
+    servers: Arc<RwLock<TrackedServers>>,
+    streams: Arc<RwLock<Streams>>,
+    events: broadcast::Sender<IpcServerStateEvent>,
 ) {
     info!("IPC state actor started");
 
-    while let Some(cmd) = command_rx.recv().await {
-        match cmd {
-            StateCommand::SetServer(new_server) => {
-                let mut server_write = server.write().await;
+    while let Some((cmd, ack)) = command_rx.recv().await {
+        apply(cmd, &servers, &streams, &events).await;
+        // The caller may have stopped waiting; the command is applied either way
+        let _ = ack.send(());
+    }
 
-                if let Some(ref existing) = *server_write {
-                    warn!(
-                        "Replacing existing server (PID {}, port {}) with new server (PID {}, port {})",
-                        existing.pid, existing.port, new_server.pid, new_server.port
-                    );
-                } else {
-                    info!(
-                        "Setting server state: PID={}, port={}, owned={}",
-                        new_server.pid, new_server.port, new_server.owned
-                    );
-                }
+    warn!("IPC state actor stopped - this should not happen during normal operation");
+}
 
-                *server_write = Some(new_server.clone());
+/// Apply one command, holding the servers write lock until it's done.
+async fn apply(
+    cmd: StateCommand,
+    servers: &RwLock<TrackedServers>,
+    streams: &RwLock<Streams>,
+    events: &broadcast::Sender<IpcServerStateEvent>,
+) {
+    let mut servers_write = servers.write().await;
 
-                // Create OpencodeClient
-                match OpencodeClient::new(&new_server.base_url) {
-                    Ok(client) => {
-                        let mut client_write = opencode_client.write().await;
-                        *client_write = Some(client);
-                        info!("Created OpencodeClient for {}", new_server.base_url);
-                    }
-                    Err(e) => {
-                        warn!(
-                            "Failed to create OpencodeClient: {} - session operations will fail",
-                            e
-                        );
-                        let mut client_write = opencode_client.write().await;
-                        *client_write = None;
+    match cmd {
+        StateCommand::SetServer {
+            server: new_server,
+            reply,
+        } => {
+            let id = new_server.base_url.clone();
+            let previous = servers_write.active().map(|(existing, _)| existing.clone());
+            let result =
+                track_server(&mut servers_write, id.clone(), new_server.clone()).map_err(|e| {
+                    IpcError::Io {
+                        message: format!("Cannot use server at '{}': {e}", new_server.base_url),
+                        location: ErrorLocation::from(Location::caller()),
                     }
+                });
+            if result.is_ok() {
+                if let Some(existing) = previous {
+                    info!(
+                        "Switched active server from PID {} (port {}) to PID {} (port {})",
+                        existing.pid, existing.port, new_server.pid, new_server.port
+                    );
                 }
+                servers_write.active = Some(id);
+                events::publish(events, events::server_set(new_server));
             }
-            StateCommand::ClearServer => {
-                let mut server_write = server.write().await;
-
-                if let Some(ref old_server) = *server_write {
-                    info!("Clearing server state: PID={}", old_server.pid);
-                } else {
-                    warn!("Clear server requested but no server was set");
+            let _ = reply.send(result);
+        }
+        StateCommand::ClearServer => match servers_write.active.take() {
+            Some(id) => {
+                servers_write.servers.remove(&id);
+                servers_write.health.remove(&id);
+                info!("Cleared active server '{id}'");
+                events::publish(events, events::server_cleared(id));
+            }
+            None => warn!("Clear server requested but no server was active"),
+        },
+        StateCommand::AddServer { id, server } => {
+            // Failures are logged by `track_server`; the server just isn't listed
+            let _ = track_server(&mut servers_write, id, server);
+        }
+        StateCommand::RemoveServer(id) => {
+            if servers_write.servers.remove(&id).is_none() {
+                warn!("Remove requested for untracked server '{id}'");
+                return;
+            }
+            servers_write.health.remove(&id);
+            if servers_write.active.as_deref() == Some(id.as_str()) {
+                servers_write.active = None;
+                info!("Removed active server '{id}' - no server is active");
+                events::publish(events, events::server_cleared(id));
+            } else {
+                info!("Removed server '{id}'");
+            }
+        }
+        StateCommand::SetActive(id) => {
+            if let Some((server, _)) = servers_write.servers.get(&id) {
+                info!("Active server set to '{id}'");
+                events::publish(events, events::server_set(server.clone()));
+                servers_write.active = Some(id);
+            } else {
+                warn!("Cannot activate untracked server '{id}'");
+            }
+        }
+        StateCommand::RecordHealth { id, healthy } => {
+            let Some((server, _)) = servers_write.servers.get_mut(&id) else {
+                warn!("Health recorded for untracked server '{id}'");
+                return;
+            };
+            if healthy {
+                server.last_health_ok = now_epoch_millis();
+                debug!("Server '{id}' passed health check");
+            }
+            if servers_write.health.insert(id.clone(), healthy) != Some(healthy) {
+                info!("Server '{id}' health changed: healthy={healthy}");
+                events::publish(events, events::health_changed(id, healthy));
+            }
+        }
+        StateCommand::SetDirectory {
+            session_id: Some(session_id),
+            directory,
+        } => {
+            info!("Directory for session '{session_id}' set to {directory:?}");
+            match directory {
+                Some(directory) => {
+                    servers_write
+                        .session_directories
+                        .insert(session_id, directory);
+                }
+                None => {
+                    servers_write.session_directories.remove(&session_id);
+                }
+            }
+        }
+        StateCommand::SetDirectory {
+            session_id: None,
+            directory,
+        } => {
+            info!("Default directory set to {directory:?}");
+            for (_, client) in servers_write.servers.values_mut() {
+                client.set_directory(directory.clone());
+            }
+            servers_write.directory = directory;
+        }
+        StateCommand::StreamStarted {
+            session_id,
+            request_id,
+            cancel,
+        } => {
+            debug!("Stream {request_id} started for session '{session_id}'");
+            streams
+                .write()
+                .await
+                .entry(session_id)
+                .or_default()
+                .insert(request_id, cancel);
+        }
+        StateCommand::StreamFinished {
+            session_id,
+            request_id,
+        } => {
+            debug!("Stream {request_id} finished for session '{session_id}'");
+            let mut streams_write = streams.write().await;
+            if let Some(session_streams) = streams_write.get_mut(&session_id) {
+                session_streams.remove(&request_id);
+                if session_streams.is_empty() {
+                    streams_write.remove(&session_id);
                 }
-
-                *server_write = None;
-
-                // Clear OpencodeClient
-                let mut client_write = opencode_client.write().await;
-                *client_write = None;
-                info!("Cleared OpencodeClient");
             }
         }
     }
+}
 
-    warn!("IPC state actor stopped - this should not happen during normal operation");
+/// Create an OpencodeClient for `server` and track both under `id`.
+///
+/// Tracks nothing if the client can't be created, so a server is never tracked without a
+/// usable client.
+fn track_server(
+    servers: &mut TrackedServers,
+    id: String,
+    server: IpcServerInfo,
+) -> Result<(), OpencodeClientError> {
+    let mut client = match OpencodeClient::new(&server.base_url) {
+        Ok(client) => client,
+        Err(e) => {
+            warn!(
+                "Failed to create OpencodeClient for {}: {} - not tracking server",
+                server.base_url, e
+            );
+            return Err(e);
+        }
+    };
+
+    client.set_directory(servers.directory.clone());
+
+    info!(
+        "Tracking server '{id}': PID={}, port={}, owned={}",
+        server.pid, server.port, server.owned
+    );
+    if servers.servers.insert(id, (server, client)).is_some() {
+        info!("Replaced previously tracked server with the same ID");
+    }
+    Ok(())
 }
@@ -1,14 +1,18 @@
 pub mod auth_sync;
+pub mod base_url;
 pub mod config;
 pub mod discovery;
 pub mod error;
 pub mod field_normalizer;
 pub mod ipc;
+pub mod logging;
+pub mod opencode_client;
 pub mod proto;
+pub mod provider_models;
+pub mod session;
 
 pub use config::models::{ModelsConfig, ProviderConfig};
 
-mod opencode_client;
 #[cfg(test)]
 mod tests;
 
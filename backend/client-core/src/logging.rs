@@ -0,0 +1,88 @@
+//! Runtime-adjustable log levels.
+//!
+//! The app's logger is installed once at startup, but the level it filters at can change
+//! while running (e.g. bumped to `trace` over IPC to diagnose an issue without a rebuild).
+//!
+//! There is one default level for every target, plus optional per-target overrides keyed
+//! by module path prefix (`client_core::ipc` also covers `client_core::ipc::server`). The
+//! longest matching prefix wins. A logger opts in by filtering records with [`is_enabled`];
+//! [`log::set_max_level`] is kept at the most verbose configured level so records that
+//! some override wants aren't discarded before reaching it.
+
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use log::{LevelFilter, Metadata};
+
+/// Valid level names, for error messages.
+pub const LEVEL_NAMES: &str = "off, error, warn, info, debug, trace";
+
+struct Levels {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+static LEVELS: RwLock<Levels> = RwLock::new(Levels {
+    default: LevelFilter::Info,
+    targets: Vec::new(),
+});
+
+/// Parse a level name ("off", "error", "warn", "info", "debug", "trace"; case-insensitive).
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    LevelFilter::from_str(level.trim()).ok()
+}
+
+/// Set the level for `target` (a module path prefix), or the default level if `None`.
+///
+/// Returns the level that applied to the target before the change.
+pub fn set_level(level: LevelFilter, target: Option<&str>) -> LevelFilter {
+    let mut levels = LEVELS.write().unwrap_or_else(|e| e.into_inner());
+
+    let previous = match target {
+        None => std::mem::replace(&mut levels.default, level),
+        Some(target) => {
+            let previous = level_for(&levels, target);
+            match levels.targets.iter_mut().find(|(t, _)| t == target) {
+                Some((_, existing)) => *existing = level,
+                None => levels.targets.push((target.to_string(), level)),
+            }
+            previous
+        }
+    };
+
+    let max = levels
+        .targets
+        .iter()
+        .map(|(_, l)| *l)
+        .fold(levels.default, Ord::max);
+    log::set_max_level(max);
+
+    previous
+}
+
+/// The level currently applied to `target`.
+pub fn level(target: &str) -> LevelFilter {
+    level_for(&LEVELS.read().unwrap_or_else(|e| e.into_inner()), target)
+}
+
+/// Should a record with this metadata be logged under the current levels?
+pub fn is_enabled(metadata: &Metadata) -> bool {
+    metadata.level() <= level(metadata.target())
+}
+
+fn level_for(levels: &Levels, target: &str) -> LevelFilter {
+    levels
+        .targets
+        .iter()
+        .filter(|(prefix, _)| covers(prefix, target))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or(levels.default, |(_, l)| *l)
+}
+
+/// Does `prefix` name `target` or one of its parent modules?
+fn covers(prefix: &str, target: &str) -> bool {
+    target == prefix
+        || target
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
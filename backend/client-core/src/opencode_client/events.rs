@@ -0,0 +1,509 @@
+//! Server-sent event stream from the OpenCode `/event` endpoint.
+//!
+//! OpenCode publishes every bus event (message/part updates, session status, ...) as
+//! an SSE `data:` line holding `{ "type": "...", "properties": {...} }`. This module
+//! turns that byte stream into parsed events: raw JSON and part updates through
+//! [`OcEventStream`], and typed [`OcEvent`]s through
+//! [`OpencodeClient::subscribe_oc_events`], which also reconnects when the stream drops.
+
+use crate::error::opencode_client::OpencodeClientError;
+use crate::field_normalizer::normalize_json;
+use crate::opencode_client::message_error::parse_message_error;
+use crate::opencode_client::{OpencodeClient, parse_message, tag_part};
+use crate::proto::event::{
+    OcEvent, OcMessagePartRemovedEvent, OcMessagePartUpdatedEvent, OcMessageRemovedEvent,
+    OcMessageUpdatedEvent, OcPermissionAskedEvent, OcPermissionRepliedEvent, OcSessionCreatedEvent,
+    OcSessionDeletedEvent, OcSessionStatus, OcSessionStatusEvent, OcSessionStatusIdle,
+    OcSessionStatusThinking, OcSessionUpdatedEvent, oc_event, oc_session_status,
+};
+use crate::proto::message::part::OcPart;
+use crate::proto::session::OcSessionInfo;
+use crate::proto::tool::{OcPermissionReply, OcPermissionRequest, OcPermissionToolContext};
+
+use common::{ErrorLocation, RedactedDisplay};
+
+use std::panic::Location;
+
+use backoff::ExponentialBackoff;
+use backoff::backoff::Backoff;
+use futures_util::Stream;
+use futures_util::stream::unfold;
+use log::{debug, info, warn};
+use reqwest::Response;
+use serde_json::Value;
+use tokio::time::sleep as TokioSleep;
+
+const MESSAGE_UPDATED_EVENT: &str = "message.updated";
+const MESSAGE_REMOVED_EVENT: &str = "message.removed";
+const PART_UPDATED_EVENT: &str = "message.part.updated";
+const PART_REMOVED_EVENT: &str = "message.part.removed";
+const SESSION_CREATED_EVENT: &str = "session.created";
+const SESSION_UPDATED_EVENT: &str = "session.updated";
+const SESSION_DELETED_EVENT: &str = "session.deleted";
+const SESSION_STATUS_EVENT: &str = "session.status";
+const PERMISSION_ASKED_EVENT: &str = "permission.asked";
+const PERMISSION_REPLIED_EVENT: &str = "permission.replied";
+
+/// Failed reconnects in a row after which a typed event subscription gives up.
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Live subscription to the OpenCode event stream.
+///
+/// Created by [`OpencodeClient::subscribe_events`](crate::opencode_client::OpencodeClient::subscribe_events).
+/// Dropping it closes the HTTP connection.
+pub struct OcEventStream {
+    response: Response,
+    buffer: SseBuffer,
+}
+
+impl OcEventStream {
+    pub(crate) fn new(response: Response) -> Self {
+        Self {
+            response,
+            buffer: SseBuffer::default(),
+        }
+    }
+
+    /// Next raw event (`{ "type": ..., "properties": ... }`), or `None` once the server closes the stream.
+    pub async fn next_event(&mut self) -> Result<Option<Value>, OpencodeClientError> {
+        loop {
+            if let Some(data) = self.buffer.next_data() {
+                match serde_json::from_str::<Value>(&data) {
+                    Ok(event) => return Ok(Some(event)),
+                    Err(e) => {
+                        warn!("Skipping malformed event: {e}");
+                        continue;
+                    }
+                }
+            }
+
+            let chunk = self
+                .response
+                .chunk()
+                .await
+                .map_err(|e| OpencodeClientError::from_reqwest("read event stream", &e))?;
+            match chunk {
+                Some(chunk) => self.buffer.push(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Next part update for `session_id`, skipping all other events.
+    ///
+    /// Returns `None` once the server closes the stream.
+    pub async fn next_part(
+        &mut self,
+        session_id: &str,
+    ) -> Result<Option<OcPart>, OpencodeClientError> {
+        while let Some(event) = self.next_event().await? {
+            if event.get("type").and_then(Value::as_str) != Some(PART_UPDATED_EVENT) {
+                continue;
+            }
+
+            let Some(raw_part) = event.pointer("/properties/part").cloned() else {
+                continue;
+            };
+            if raw_part.get("sessionID").and_then(Value::as_str) != Some(session_id) {
+                continue;
+            }
+
+            return parse_part(raw_part).map(Some);
+        }
+
+        Ok(None)
+    }
+}
+
+/// Bytes read from an SSE response that don't yet form a complete event.
+///
+/// Chunks can end anywhere, including inside a line or a multi-byte character, so bytes
+/// are only decoded once their event's terminating blank line has arrived.
+#[derive(Debug, Default)]
+pub(crate) struct SseBuffer {
+    bytes: Vec<u8>,
+    /// Bytes already searched for a blank line without finding one.
+    scanned: usize,
+    /// The last byte pushed was `\r`; a `\n` right after it (even in the next chunk)
+    /// belongs to the same line ending.
+    after_cr: bool,
+}
+
+impl SseBuffer {
+    /// Appends a chunk; CRLF and bare CR line endings become LF, as SSE allows all three.
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        self.bytes.reserve(chunk.len());
+        for &byte in chunk {
+            match byte {
+                b'\n' if self.after_cr => {}
+                b'\r' => self.bytes.push(b'\n'),
+                _ => self.bytes.push(byte),
+            }
+            self.after_cr = byte == b'\r';
+        }
+    }
+
+    /// Pops the `data:` payload of the next complete event (blank-line terminated).
+    pub(crate) fn next_data(&mut self) -> Option<String> {
+        loop {
+            // A blank line may straddle the end of what was searched last time
+            let start = self.scanned.saturating_sub(1);
+            let Some(offset) = self.bytes[start..]
+                .windows(2)
+                .position(|pair| pair == b"\n\n")
+            else {
+                self.scanned = self.bytes.len();
+                return None;
+            };
+            let end = start + offset + 2;
+            let block: Vec<u8> = self.bytes.drain(..end).collect();
+            self.scanned = 0;
+            let block = String::from_utf8_lossy(&block);
+
+            let data: Vec<&str> = block
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(|data| data.strip_prefix(' ').unwrap_or(data))
+                .collect();
+
+            // Comment/keepalive blocks carry no data
+            if !data.is_empty() {
+                return Some(data.join("\n"));
+            }
+        }
+    }
+}
+
+/// Typed, reconnecting view of the event stream, starting from an open connection.
+///
+/// When the connection ends or fails, a new one is opened after the client's retry
+/// backoff; after [`MAX_RECONNECT_ATTEMPTS`] failed attempts in a row the last error is
+/// yielded and the stream ends. Events that fail to parse are yielded as errors without
+/// ending the stream.
+pub(crate) fn typed_events(
+    client: OpencodeClient,
+    events: OcEventStream,
+) -> impl Stream<Item = Result<OcEvent, OpencodeClientError>> + Send + 'static {
+    let subscription = Subscription {
+        backoff: client.retry_policy().backoff(),
+        client,
+        events: Some(events),
+    };
+    unfold(Some(subscription), |subscription| async move {
+        let mut subscription = subscription?;
+        match subscription.next().await {
+            Ok(event) => Some((Ok(event), Some(subscription))),
+            // Reconnecting gave up; report why and end the stream
+            Err(Failure::Disconnected(e)) => Some((Err(e), None)),
+            Err(Failure::Event(e)) => Some((Err(e), Some(subscription))),
+        }
+    })
+}
+
+/// Why [`Subscription::next`] returned without an event.
+enum Failure {
+    /// One event couldn't be parsed; later ones may still be fine.
+    Event(OpencodeClientError),
+    /// The server couldn't be reached again.
+    Disconnected(OpencodeClientError),
+}
+
+/// State of a typed subscription between events.
+struct Subscription {
+    client: OpencodeClient,
+    /// `None` while reconnecting.
+    events: Option<OcEventStream>,
+    backoff: ExponentialBackoff,
+}
+
+impl Subscription {
+    async fn next(&mut self) -> Result<OcEvent, Failure> {
+        loop {
+            let Some(events) = self.events.as_mut() else {
+                self.reconnect().await.map_err(Failure::Disconnected)?;
+                continue;
+            };
+
+            match events.next_event().await {
+                Ok(Some(raw)) => {
+                    // Reading again means the connection is healthy; start delays over
+                    self.backoff.reset();
+                    match parse_oc_event(raw) {
+                        Ok(Some(event)) => return Ok(event),
+                        Ok(None) => continue,
+                        Err(e) => return Err(Failure::Event(e)),
+                    }
+                }
+                Ok(None) => {
+                    info!("OpenCode event stream closed, reconnecting");
+                    self.events = None;
+                }
+                Err(e) => {
+                    warn!(
+                        "OpenCode event stream failed, reconnecting: {}",
+                        e.redacted_to_string()
+                    );
+                    self.events = None;
+                }
+            }
+        }
+    }
+
+    async fn reconnect(&mut self) -> Result<(), OpencodeClientError> {
+        let mut attempt = 1;
+        loop {
+            if let Some(delay) = self.backoff.next_backoff() {
+                TokioSleep(delay).await;
+            }
+
+            match self.client.subscribe_events().await {
+                Ok(events) => {
+                    self.events = Some(events);
+                    return Ok(());
+                }
+                Err(e) if attempt >= MAX_RECONNECT_ATTEMPTS => return Err(e),
+                Err(e) => {
+                    debug!(
+                        "Event stream reconnect attempt {attempt} failed: {}",
+                        e.redacted_to_string()
+                    );
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Converts a raw bus event into an [`OcEvent`].
+///
+/// Returns `None` for event types the proto doesn't model (`server.connected`, file
+/// watcher and LSP events, ...).
+#[track_caller]
+pub(crate) fn parse_oc_event(raw: Value) -> Result<Option<OcEvent>, OpencodeClientError> {
+    use oc_event::Event;
+
+    let event_type = raw
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let mut raw_properties = raw.get("properties").cloned().unwrap_or(Value::Null);
+
+    // Parts are normalized (and tagged) by `parse_part` itself
+    if event_type == PART_UPDATED_EVENT {
+        let raw_part = raw_properties
+            .get_mut("part")
+            .map(Value::take)
+            .ok_or_else(|| missing_field(&event_type, "part"))?;
+        let session_id = raw_string(&raw_part, "sessionID");
+        let message_id = raw_string(&raw_part, "messageID");
+        let event = Event::MessagePartUpdated(OcMessagePartUpdatedEvent {
+            r#type: event_type,
+            session_id,
+            message_id,
+            part: Some(parse_part(raw_part)?),
+        });
+        return Ok(Some(OcEvent { event: Some(event) }));
+    }
+
+    let mut properties = normalize_json(raw_properties);
+    let field = |key: &str| raw_string(&properties, key);
+
+    let event = match event_type.as_str() {
+        MESSAGE_UPDATED_EVENT => {
+            let mut info = take_field(&mut properties, &event_type, "info")?;
+            let session_id = raw_string(&info, "session_id");
+            convert_message_error(&mut info);
+            Event::MessageUpdated(OcMessageUpdatedEvent {
+                r#type: event_type,
+                session_id,
+                message: Some(parse_message(info)?),
+            })
+        }
+        MESSAGE_REMOVED_EVENT => Event::MessageRemoved(OcMessageRemovedEvent {
+            session_id: field("session_id"),
+            message_id: field("message_id"),
+            r#type: event_type,
+        }),
+        PART_REMOVED_EVENT => Event::MessagePartRemoved(OcMessagePartRemovedEvent {
+            session_id: field("session_id"),
+            message_id: field("message_id"),
+            part_id: field("part_id"),
+            r#type: event_type,
+        }),
+        SESSION_CREATED_EVENT => Event::SessionCreated(OcSessionCreatedEvent {
+            session: Some(parse_session(&mut properties, &event_type)?),
+            r#type: event_type,
+        }),
+        SESSION_UPDATED_EVENT => Event::SessionUpdated(OcSessionUpdatedEvent {
+            session: Some(parse_session(&mut properties, &event_type)?),
+            r#type: event_type,
+        }),
+        SESSION_DELETED_EVENT => Event::SessionDeleted(OcSessionDeletedEvent {
+            // OpenCode sends the deleted session's info; older servers sent just the id
+            session_id: properties
+                .pointer("/info/id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| field("session_id")),
+            r#type: event_type,
+        }),
+        SESSION_STATUS_EVENT => Event::SessionStatus(OcSessionStatusEvent {
+            session_id: field("session_id"),
+            status: Some(parse_session_status(
+                properties.get("status").unwrap_or(&Value::Null),
+            )),
+            r#type: event_type,
+        }),
+        PERMISSION_ASKED_EVENT => Event::PermissionAsked(OcPermissionAskedEvent {
+            session_id: field("session_id"),
+            request: Some(parse_permission_request(&properties)),
+            r#type: event_type,
+        }),
+        PERMISSION_REPLIED_EVENT => Event::PermissionReplied(OcPermissionRepliedEvent {
+            session_id: field("session_id"),
+            request_id: first_string(&properties, &["request_id", "permissionID"]),
+            reply: parse_permission_reply(&first_string(&properties, &["reply", "response"]))
+                as i32,
+            r#type: event_type,
+        }),
+        _ => {
+            debug!("Ignoring unmodeled event type '{event_type}'");
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(OcEvent { event: Some(event) }))
+}
+
+/// Converts an assistant's `NamedError` into the proto's `oneof` shape, so the message
+/// parses instead of failing on the error it reports.
+fn convert_message_error(info: &mut Value) {
+    let Some(info) = info.as_object_mut() else {
+        return;
+    };
+    let Some(raw_error) = info.remove("error") else {
+        return;
+    };
+    if let Some(error) = parse_message_error(&raw_error)
+        && let Ok(error) = serde_json::to_value(error)
+    {
+        info.insert("error".to_string(), error);
+    }
+}
+
+#[track_caller]
+fn parse_session(
+    properties: &mut Value,
+    event_type: &str,
+) -> Result<OcSessionInfo, OpencodeClientError> {
+    let info = take_field(properties, event_type, "info")?;
+    serde_json::from_value(info).map_err(|e| OpencodeClientError::Server {
+        message: format!("Failed to parse {event_type} session: {e}"),
+        status: None,
+        location: ErrorLocation::from(Location::caller()),
+    })
+}
+
+/// OpenCode reports `idle`, `busy` and `retry`; anything but idle is generation in progress.
+fn parse_session_status(status: &Value) -> OcSessionStatus {
+    let kind = first_string(status, &["type", "status"]);
+    let status = if kind == "idle" {
+        oc_session_status::Status::Idle(OcSessionStatusIdle { status: kind })
+    } else {
+        oc_session_status::Status::Thinking(OcSessionStatusThinking {
+            message_id: raw_string(status, "message_id"),
+            status: kind,
+        })
+    };
+    OcSessionStatus {
+        status: Some(status),
+    }
+}
+
+fn parse_permission_request(properties: &Value) -> OcPermissionRequest {
+    let permission = first_string(properties, &["permission", "type"]);
+    OcPermissionRequest {
+        id: raw_string(properties, "id"),
+        action: match first_string(properties, &["title", "action"]) {
+            action if action.is_empty() => permission.clone(),
+            action => action,
+        },
+        tool: Some(OcPermissionToolContext {
+            name: properties
+                .pointer("/tool/name")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| permission.clone()),
+            input: properties
+                .get("metadata")
+                .filter(|metadata| metadata.is_object())
+                .and_then(|metadata| serde_json::from_value(metadata.clone()).ok()),
+        }),
+        timestamp: properties
+            .pointer("/time/created")
+            .and_then(Value::as_i64)
+            .unwrap_or_default(),
+        permission,
+    }
+}
+
+/// Accepts both the proto's names and OpenCode's `once`/`always`/`reject`.
+fn parse_permission_reply(reply: &str) -> OcPermissionReply {
+    match reply {
+        "allow" | "once" => OcPermissionReply::Allow,
+        "allow-all" | "always" => OcPermissionReply::AllowAll,
+        "deny" | "reject" => OcPermissionReply::Deny,
+        _ => OcPermissionReply::PermissionReplyUnspecified,
+    }
+}
+
+#[track_caller]
+fn take_field(
+    properties: &mut Value,
+    event_type: &str,
+    key: &str,
+) -> Result<Value, OpencodeClientError> {
+    properties
+        .get_mut(key)
+        .map(Value::take)
+        .ok_or_else(|| missing_field(event_type, key))
+}
+
+#[track_caller]
+fn missing_field(event_type: &str, key: &str) -> OpencodeClientError {
+    OpencodeClientError::Server {
+        message: format!("{event_type} event missing '{key}' field"),
+        status: None,
+        location: ErrorLocation::from(Location::caller()),
+    }
+}
+
+fn raw_string(value: &Value, key: &str) -> String {
+    first_string(value, &[key])
+}
+
+/// First of `keys` holding a string, or empty.
+fn first_string(value: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[track_caller]
+fn parse_part(raw_part: Value) -> Result<OcPart, OpencodeClientError> {
+    let tagged = tag_part(normalize_json(raw_part)).ok_or_else(|| OpencodeClientError::Server {
+        message: "Part event missing 'type' field".to_string(),
+        status: None,
+        location: ErrorLocation::from(Location::caller()),
+    })?;
+
+    debug!("Part event: {tagged}");
+
+    serde_json::from_value(tagged).map_err(|e| OpencodeClientError::Server {
+        message: format!("Failed to parse part event: {e}"),
+        status: None,
+        location: ErrorLocation::from(Location::caller()),
+    })
+}
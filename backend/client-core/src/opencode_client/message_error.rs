@@ -0,0 +1,102 @@
+//! Typed errors reported inside OpenCode assistant messages.
+//!
+//! When generation fails, OpenCode still answers with an assistant message, whose `info.error`
+//! is a `NamedError`: `{ "name": "ProviderAuthError", "data": { ... } }`. That shape doesn't
+//! match the proto `oneof`, so it is converted field by field here; fields that are missing
+//! get their proto defaults rather than failing the whole conversion.
+
+use crate::proto::message::error::{
+    OcAbortedError, OcApiError, OcMessageError, OcOutputLengthError, OcProviderAuthError,
+    OcUnknownError, oc_message_error,
+};
+
+use serde_json::Value;
+
+const API_ERROR: &str = "APIError";
+const PROVIDER_AUTH_ERROR: &str = "ProviderAuthError";
+const OUTPUT_LENGTH_ERROR: &str = "MessageOutputLengthError";
+const ABORTED_ERROR: &str = "MessageAbortedError";
+
+/// Convert a (normalized) assistant `error` value into an [`OcMessageError`].
+///
+/// Accepts the `NamedError` shape and the same fields without the `data` wrapper. Returns
+/// `None` for `null` or non-object values; unrecognized names become
+/// [`OcUnknownError`] so the failure is still reported.
+pub(crate) fn parse_message_error(value: &Value) -> Option<OcMessageError> {
+    let object = value.as_object()?;
+    let name = object
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let data = object
+        .get("data")
+        .filter(|d| d.is_object())
+        .unwrap_or(value);
+    let message = string_field(data, "message").unwrap_or_default();
+
+    let error = match name {
+        API_ERROR => oc_message_error::Error::Api(OcApiError {
+            name: name.to_string(),
+            message,
+            cause: string_field(data, "cause"),
+            status: int_field(data, "status_code"),
+            code: string_field(data, "code"),
+            headers: data
+                .get("response_headers")
+                .and_then(|headers| serde_json::from_value(headers.clone()).ok()),
+        }),
+        PROVIDER_AUTH_ERROR => oc_message_error::Error::ProviderAuth(OcProviderAuthError {
+            name: name.to_string(),
+            message,
+            provider_id: string_field(data, "provider_id").unwrap_or_default(),
+        }),
+        // The proto schema names these without OpenCode's `Message` prefix; accept both
+        OUTPUT_LENGTH_ERROR | "OutputLengthError" => {
+            oc_message_error::Error::OutputLength(OcOutputLengthError {
+                name: name.to_string(),
+                message,
+                max_tokens: int_field(data, "max_tokens"),
+            })
+        }
+        ABORTED_ERROR | "AbortedError" => oc_message_error::Error::Aborted(OcAbortedError {
+            name: name.to_string(),
+            message,
+        }),
+        _ => oc_message_error::Error::Unknown(OcUnknownError {
+            name: name.to_string(),
+            message,
+            cause: string_field(data, "cause"),
+        }),
+    };
+
+    Some(OcMessageError { error: Some(error) })
+}
+
+/// One-line description for error messages, e.g.
+/// `ProviderAuthError (anthropic): Invalid API key`.
+pub(crate) fn describe_message_error(error: &OcMessageError) -> String {
+    use oc_message_error::Error;
+
+    match &error.error {
+        Some(Error::Api(e)) if e.status != 0 => {
+            format!("{} (HTTP {}): {}", e.name, e.status, e.message)
+        }
+        Some(Error::Api(e)) => format!("{}: {}", e.name, e.message),
+        Some(Error::ProviderAuth(e)) => format!("{} ({}): {}", e.name, e.provider_id, e.message),
+        Some(Error::OutputLength(e)) => format!("{}: {}", e.name, e.message),
+        Some(Error::Aborted(e)) => format!("{}: {}", e.name, e.message),
+        Some(Error::Unknown(e)) => format!("{}: {}", e.name, e.message),
+        None => "unspecified assistant error".to_string(),
+    }
+}
+
+fn string_field(data: &Value, key: &str) -> Option<String> {
+    data.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn int_field(data: &Value, key: &str) -> i32 {
+    data.get(key)
+        .and_then(Value::as_i64)
+        .and_then(|n| i32::try_from(n).ok())
+        .unwrap_or_default()
+}
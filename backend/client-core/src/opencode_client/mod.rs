@@ -1,6 +1,18 @@
+pub(crate) mod events;
+mod message_error;
+mod retry;
+
+pub use events::OcEventStream;
+pub use retry::RetryPolicy;
+
+use crate::auth_sync::oauth::AuthInfo;
+use crate::base_url::normalize_base_url;
+use crate::config::AgentsSection;
 use crate::error::opencode_client::OpencodeClientError;
-use crate::field_normalizer::normalize_json;
-use crate::proto::message::OcMessage;
+use crate::field_normalizer::{normalize_json, normalize_slice};
+use crate::opencode_client::message_error::{describe_message_error, parse_message_error};
+use crate::proto::event::OcEvent;
+use crate::proto::message::{OcAssistantMessage, OcMessage, OcUserMessage, oc_message};
 use crate::proto::session::OcSessionInfo;
 
 use common::ErrorLocation;
@@ -8,37 +20,126 @@ use common::ErrorLocation;
 use std::panic::Location;
 use std::time::Duration;
 
-use log::{debug, info};
-use reqwest::Client;
+use backoff::backoff::Backoff;
+use futures_util::Stream;
+use log::{debug, info, warn};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde_json::Value;
+use tokio::time::sleep as TokioSleep;
 use url::Url;
 
 const DEFAULT_TIMEOUT_DURATION: Duration = Duration::from_secs(30);
 const OPENCODE_DIRECTORY_HEADER_KEY: &str = "x-opencode-directory";
 const OPENCODE_SERVER_SESSION_ENDPOINT: &str = "session";
+const OPENCODE_SERVER_EVENT_ENDPOINT: &str = "event";
+const OPENCODE_SERVER_AUTH_ENDPOINT: &str = "auth";
 
 #[derive(Clone)]
 pub struct OpencodeClient {
     base_url: Url,
     client: Client,
+    /// Client for the event stream: no request timeout, since the stream stays open.
+    streaming_client: Client,
+    timeout: Duration,
+    retry_policy: RetryPolicy,
+    agents: AgentsSection,
     pub directory: Option<String>,
 }
 
 impl OpencodeClient {
     pub fn new(base_url_str: &str) -> Result<Self, OpencodeClientError> {
-        let base_url = Url::parse(base_url_str)?;
-        let client = Client::builder()
-            .timeout(DEFAULT_TIMEOUT_DURATION)
-            .build()?;
+        Self::with_timeout(base_url_str, DEFAULT_TIMEOUT_DURATION)
+    }
+
+    /// Creates a client whose requests time out after `timeout`.
+    ///
+    /// Long-lived calls (e.g. streaming) should use a separate client with a longer
+    /// timeout rather than raising it for one-shot requests.
+    pub fn with_timeout(
+        base_url_str: &str,
+        timeout: Duration,
+    ) -> Result<Self, OpencodeClientError> {
+        let base_url = normalize_base_url(base_url_str)?;
+        let client = build_http_client(timeout)?;
+        let streaming_client = build_streaming_client(timeout)?;
 
         Ok(Self {
             base_url,
             client,
+            streaming_client,
+            timeout,
+            retry_policy: RetryPolicy::default(),
+            agents: AgentsSection::default(),
             directory: None,
         })
     }
 
-    fn prepare_request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    /// Creates a client that retries transient failures according to `retry_policy`.
+    ///
+    /// Only idempotent reads are retried unless the policy opts into
+    /// [`RetryPolicy::retry_non_idempotent`].
+    pub fn with_retry_policy(
+        base_url_str: &str,
+        retry_policy: RetryPolicy,
+    ) -> Result<Self, OpencodeClientError> {
+        let mut client = Self::new(base_url_str)?;
+        client.retry_policy = retry_policy;
+        Ok(client)
+    }
+
+    /// Changes the request timeout, rebuilding the underlying HTTP clients.
+    pub fn set_timeout(&mut self, timeout: Duration) -> Result<(), OpencodeClientError> {
+        self.client = build_http_client(timeout)?;
+        self.streaming_client = build_streaming_client(timeout)?;
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    /// The request timeout currently applied to every call.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets the project directory sent as the `x-opencode-directory` header (`None` omits it).
+    pub fn set_directory(&mut self, directory: Option<String>) {
+        self.directory = directory;
+    }
+
+    /// The retry policy applied to session operations.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Sets the default agent and the agents [`send_message`](Self::send_message) accepts
+    /// (usually [`ModelsConfig::agents`](crate::config::ModelsConfig::agents)).
+    pub fn set_agents(&mut self, agents: AgentsSection) {
+        self.agents = agents;
+    }
+
+    /// The default and known agents used by [`send_message`](Self::send_message).
+    pub fn agents(&self) -> &AgentsSection {
+        &self.agents
+    }
+
+    /// The agent to send: `agent`, or the configured default when `None`.
+    #[track_caller]
+    fn resolve_agent<'a>(&'a self, agent: Option<&'a str>) -> Result<&'a str, OpencodeClientError> {
+        let agent = agent.unwrap_or(&self.agents.default_agent);
+        if self.agents.is_known(agent) {
+            return Ok(agent);
+        }
+
+        Err(OpencodeClientError::UnknownAgent {
+            agent: agent.to_string(),
+            message: format!(
+                "'{agent}' is not a known agent (expected one of: {})",
+                self.agents.known.join(", ")
+            ),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    }
+
+    fn prepare_request(&self, request: RequestBuilder) -> RequestBuilder {
         let mut request = request;
         if let Some(dir) = &self.directory {
             request = request.header(OPENCODE_DIRECTORY_HEADER_KEY, dir);
@@ -46,34 +147,137 @@ impl OpencodeClient {
         request
     }
 
+    /// Sends the request built by `build`, retrying transient failures per the retry policy.
+    ///
+    /// `context` names the operation in transport errors (see [`OpencodeClientError::from_reqwest`]).
+    ///
+    /// Timeouts, connection errors and 5xx responses are retried; any other response
+    /// (including 4xx) is returned as-is. After the last retry the final outcome is returned.
+    async fn send_with_retry(
+        &self,
+        context: &str,
+        idempotent: bool,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, OpencodeClientError> {
+        let policy = &self.retry_policy;
+        let mut backoff = policy.backoff();
+        let mut attempt = 0;
+
+        loop {
+            let result = self.prepare_request(build()).send().await;
+
+            if !policy.allows(idempotent)
+                || attempt >= policy.max_retries
+                || !retry::is_transient(&result)
+            {
+                return result.map_err(|e| OpencodeClientError::from_reqwest(context, &e));
+            }
+
+            attempt += 1;
+            let delay = backoff
+                .next_backoff()
+                .unwrap_or(policy.max_delay)
+                .min(policy.max_delay);
+            let outcome = match &result {
+                Ok(response) => format!("HTTP {}", response.status().as_u16()),
+                Err(e) => e.to_string(),
+            };
+            debug!(
+                "Retrying OpenCode request (attempt {}/{}) after {:?}: {}",
+                attempt, policy.max_retries, delay, outcome
+            );
+            TokioSleep(delay).await;
+        }
+    }
+
+    /// Absolute URL of `endpoint` (e.g. `session/{id}`), relative to the base URL's path.
+    ///
+    /// A leading `/` on `endpoint` is ignored, since joining it would discard the base path.
+    #[track_caller]
+    pub(crate) fn endpoint_url(&self, endpoint: &str) -> Result<Url, OpencodeClientError> {
+        Ok(self.base_url.join(endpoint.trim_start_matches('/'))?)
+    }
+
     pub async fn list_sessions(&self) -> Result<Vec<OcSessionInfo>, OpencodeClientError> {
-        let url = self.base_url.join(OPENCODE_SERVER_SESSION_ENDPOINT)?;
+        let url = self.endpoint_url(OPENCODE_SERVER_SESSION_ENDPOINT)?;
 
-        let response = self.prepare_request(self.client.get(url)).send().await?;
+        let response = self
+            .send_with_retry("list sessions", true, || self.client.get(url.clone()))
+            .await?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
             return Err(OpencodeClientError::Server {
                 message: format!(
                     "HTTP {} - {}",
-                    response.status().as_u16(),
+                    status,
                     response.text().await.unwrap_or_default()
                 ),
+                status: Some(status),
                 location: ErrorLocation::from(Location::caller()),
             });
         }
 
-        let json: Value = response.json().await?;
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("list sessions", &e))?;
         let normalized = normalize_json(json);
         let sessions: Vec<OcSessionInfo> = serde_json::from_value(normalized)?;
 
         Ok(sessions)
     }
 
+    /// Fetches a single session by ID.
+    ///
+    /// # Errors
+    /// Returns [`OpencodeClientError::NotFound`] if the server has no such session, so
+    /// callers can tell "gone" from a server failure.
+    pub async fn get_session(
+        &self,
+        session_id: &str,
+    ) -> Result<OcSessionInfo, OpencodeClientError> {
+        let url = self.endpoint_url(&format!("{OPENCODE_SERVER_SESSION_ENDPOINT}/{session_id}"))?;
+
+        let response = self
+            .send_with_retry("get session", true, || self.client.get(url.clone()))
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(OpencodeClientError::NotFound {
+                session_id: session_id.to_string(),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            return Err(OpencodeClientError::Server {
+                message: format!(
+                    "HTTP {} - {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
+                status: Some(status),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("get session", &e))?;
+        let normalized = normalize_json(json);
+        let session: OcSessionInfo = serde_json::from_value(normalized)?;
+
+        Ok(session)
+    }
+
     pub async fn create_session(
         &self,
         title: Option<&str>,
     ) -> Result<OcSessionInfo, OpencodeClientError> {
-        let url = self.base_url.join(OPENCODE_SERVER_SESSION_ENDPOINT)?;
+        let url = self.endpoint_url(OPENCODE_SERVER_SESSION_ENDPOINT)?;
 
         let body = match title {
             Some(t) => serde_json::json!({"title": t}),
@@ -81,39 +285,138 @@ impl OpencodeClient {
         };
 
         let response = self
-            .prepare_request(self.client.post(url))
-            .json(&body)
-            .send()
+            .send_with_retry("create session", false, || {
+                self.client.post(url.clone()).json(&body)
+            })
             .await?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
             return Err(OpencodeClientError::Server {
                 message: format!(
                     "HTTP {} - {}",
-                    response.status().as_u16(),
+                    status,
                     response.text().await.unwrap_or_default(),
                 ),
+                status: Some(status),
                 location: ErrorLocation::from(Location::caller()),
             });
         }
 
-        let json: Value = response.json().await?;
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("create session", &e))?;
         let normalized = normalize_json(json);
         let session: OcSessionInfo = serde_json::from_value(normalized)?;
 
         Ok(session)
     }
 
+    /// Renames a session, returning the updated session.
+    ///
+    /// # Errors
+    /// Returns [`OpencodeClientError::InvalidRequest`] for a blank title (nothing is sent)
+    /// and [`OpencodeClientError::NotFound`] if the server has no such session.
+    pub async fn update_session(
+        &self,
+        session_id: &str,
+        title: &str,
+    ) -> Result<OcSessionInfo, OpencodeClientError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(OpencodeClientError::InvalidRequest {
+                message: format!("Session title for {session_id} cannot be empty"),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        let url = self.endpoint_url(&format!("{OPENCODE_SERVER_SESSION_ENDPOINT}/{session_id}"))?;
+        let body = serde_json::json!({ "title": title });
+
+        // Setting the same title twice is harmless, so this is retried like a read
+        let response = self
+            .send_with_retry("update session", true, || {
+                self.client.patch(url.clone()).json(&body)
+            })
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(OpencodeClientError::NotFound {
+                session_id: session_id.to_string(),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            return Err(OpencodeClientError::Server {
+                message: format!(
+                    "HTTP {} - {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
+                status: Some(status),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("update session", &e))?;
+        let normalized = normalize_json(json);
+        let session: OcSessionInfo = serde_json::from_value(normalized)?;
+
+        info!("Renamed session {session_id} to {title:?}");
+        Ok(session)
+    }
+
     pub async fn delete_session(&self, session_id: &str) -> Result<bool, OpencodeClientError> {
-        let url = self
-            .base_url
-            .join(&format!("{OPENCODE_SERVER_SESSION_ENDPOINT}/{session_id}"))?;
+        let url = self.endpoint_url(&format!("{OPENCODE_SERVER_SESSION_ENDPOINT}/{session_id}"))?;
 
-        let response = self.prepare_request(self.client.delete(url)).send().await?;
+        let response = self
+            .send_with_retry("delete session", false, || self.client.delete(url.clone()))
+            .await?;
 
         Ok(response.status().is_success())
     }
 
+    /// Aborts the session's in-flight generation.
+    ///
+    /// Returns the server's verdict (`true` if a generation was aborted). Retried only if
+    /// the retry policy allows non-idempotent requests.
+    pub async fn abort_message(&self, session_id: &str) -> Result<bool, OpencodeClientError> {
+        let url = self.endpoint_url(&format!(
+            "{OPENCODE_SERVER_SESSION_ENDPOINT}/{session_id}/abort"
+        ))?;
+
+        let response = self
+            .send_with_retry("abort message", false, || self.client.post(url.clone()))
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            return Err(OpencodeClientError::Server {
+                message: format!(
+                    "HTTP {} - {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
+                status: Some(status),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        let aborted: bool = response
+            .json()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("abort message", &e))?;
+        info!("Abort requested for session {session_id}: aborted={aborted}");
+
+        Ok(aborted)
+    }
+
     /// Sync an API key for a provider to the OpenCode server.
     ///
     /// # Arguments
@@ -127,7 +430,7 @@ impl OpencodeClient {
         provider: &str,
         api_key: &str,
     ) -> Result<(), OpencodeClientError> {
-        let url = self.base_url.join(&format!("auth/{}", provider))?;
+        let url = self.endpoint_url(&format!("{OPENCODE_SERVER_AUTH_ENDPOINT}/{provider}"))?;
 
         let body = serde_json::json!({
             "type": "api",
@@ -138,15 +441,18 @@ impl OpencodeClient {
             .prepare_request(self.client.put(url))
             .json(&body)
             .send()
-            .await?;
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("sync API key", &e))?;
 
         if !response.status().is_success() {
+            let status = response.status().as_u16();
             return Err(OpencodeClientError::Server {
                 message: format!(
                     "HTTP {} - {}",
-                    response.status().as_u16(),
+                    status,
                     response.text().await.unwrap_or_default()
                 ),
+                status: Some(status),
                 location: ErrorLocation::from(Location::caller()),
             });
         }
@@ -154,10 +460,164 @@ impl OpencodeClient {
         Ok(())
     }
 
+    /// Fetches the credentials the server currently holds for a provider.
+    ///
+    /// Returns `None` if the server has none (404 or a `null` body). The result contains
+    /// key material; never log it.
+    ///
+    /// # Errors
+    /// Returns [`OpencodeClientError`] if the request fails, the server answers with another
+    /// error status, or the body isn't a known auth entry.
+    pub async fn get_auth(&self, provider: &str) -> Result<Option<AuthInfo>, OpencodeClientError> {
+        let url = self.endpoint_url(&format!("{OPENCODE_SERVER_AUTH_ENDPOINT}/{provider}"))?;
+
+        let response = self
+            .send_with_retry("get auth", true, || self.client.get(url.clone()))
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            return Err(OpencodeClientError::Server {
+                message: format!(
+                    "HTTP {} - {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
+                status: Some(status),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("get auth", &e))?;
+        if json.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_value(json)?))
+    }
+
+    /// Lists all messages in a session, oldest first.
+    ///
+    /// Each entry is routed to the user or assistant variant of [`OcMessage`]
+    /// based on its `role`.
+    pub async fn list_messages(
+        &self,
+        session_id: &str,
+    ) -> Result<Vec<OcMessage>, OpencodeClientError> {
+        let url = self.endpoint_url(&format!(
+            "{OPENCODE_SERVER_SESSION_ENDPOINT}/{session_id}/message"
+        ))?;
+
+        let response = self
+            .send_with_retry("list messages", true, || self.client.get(url.clone()))
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            return Err(OpencodeClientError::Server {
+                message: format!(
+                    "HTTP {} - {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
+                status: Some(status),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        // Message lists grow with the session; normalize while parsing instead of after
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("list messages", &e))?;
+        let normalized = normalize_slice(&body)?;
+
+        // The response is [{ "info": {...}, "parts": [...] }, ...]
+        let Value::Array(entries) = normalized else {
+            return Err(OpencodeClientError::Server {
+                message: "Expected an array of messages".to_string(),
+                status: None,
+                location: ErrorLocation::from(Location::caller()),
+            });
+        };
+
+        let messages = entries
+            .into_iter()
+            .map(|mut entry| {
+                let info_value = inject_tagged_parts(&mut entry)?;
+                parse_message(info_value)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        debug!(
+            "Listed {} messages for session {session_id}",
+            messages.len()
+        );
+
+        Ok(messages)
+    }
+
+    /// Subscribes to the server's event stream (SSE).
+    ///
+    /// The stream is not bound by the request timeout, since it stays open for as long
+    /// as the caller reads from it; only connecting is.
+    pub async fn subscribe_events(&self) -> Result<OcEventStream, OpencodeClientError> {
+        let url = self.endpoint_url(OPENCODE_SERVER_EVENT_ENDPOINT)?;
+
+        let response = self
+            .prepare_request(self.streaming_client.get(url))
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("subscribe to events", &e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(OpencodeClientError::Server {
+                message: format!("HTTP {} - {}", status.as_u16(), error_body),
+                status: Some(status.as_u16()),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+
+        info!("Subscribed to OpenCode event stream");
+        Ok(OcEventStream::new(response))
+    }
+
+    /// Subscribes to the server's events as typed [`OcEvent`]s.
+    ///
+    /// Events the proto doesn't model are skipped. A dropped stream is reopened with this
+    /// client's retry backoff; the stream ends with an error once the server can't be
+    /// reached several times in a row.
+    ///
+    /// # Errors
+    /// Fails like [`subscribe_events`](Self::subscribe_events) if the first connection
+    /// can't be opened.
+    pub async fn subscribe_oc_events(
+        &self,
+    ) -> Result<
+        impl Stream<Item = Result<OcEvent, OpencodeClientError>> + Send + 'static,
+        OpencodeClientError,
+    > {
+        let events = self.subscribe_events().await?;
+        Ok(events::typed_events(self.clone(), events))
+    }
+
     /// Sends a message to an AI session and returns the assistant's response.
     ///
     /// This is a blocking call that waits for the complete AI response.
-    /// For streaming, use SSE subscription (Session 15-16).
+    /// For streaming, use [`subscribe_events`](Self::subscribe_events).
+    ///
+    /// `agent` defaults to the configured default agent; an agent outside the known
+    /// agents (see [`set_agents`](Self::set_agents)) fails with
+    /// [`OpencodeClientError::UnknownAgent`] before anything is sent.
     pub async fn send_message(
         &self,
         session_id: &str,
@@ -166,13 +626,14 @@ impl OpencodeClient {
         provider_id: &str,
         agent: Option<&str>,
     ) -> Result<OcMessage, OpencodeClientError> {
-        let url = self.base_url.join(&format!(
+        let agent = self.resolve_agent(agent)?;
+        let url = self.endpoint_url(&format!(
             "{OPENCODE_SERVER_SESSION_ENDPOINT}/{session_id}/message"
         ))?;
 
         info!(
-            "Sending message to session {} with model {}/{}",
-            session_id, provider_id, model_id
+            "Sending message to session {} with model {}/{} (agent {})",
+            session_id, provider_id, model_id, agent
         );
 
         // Build request body with camelCase field names (OpenCode server format)
@@ -185,7 +646,7 @@ impl OpencodeClient {
                 "type": "text",
                 "text": text
             }],
-            "agent": agent.unwrap_or("build")
+            "agent": agent
         });
 
         debug!("Sending message to session {session_id}: {body:?}");
@@ -194,75 +655,48 @@ impl OpencodeClient {
             .prepare_request(self.client.post(url))
             .json(&body)
             .send()
-            .await?;
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("send message", &e))?;
 
         let status = response.status();
         if !status.is_success() {
             let error_body = response.text().await.unwrap_or_default();
             return Err(OpencodeClientError::Server {
                 message: format!("HTTP {} - {}", status.as_u16(), error_body),
+                status: Some(status.as_u16()),
                 location: ErrorLocation::from(Location::caller()),
             });
         }
 
-        let json: Value = response.json().await?;
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| OpencodeClientError::from_reqwest("send message", &e))?;
         let mut normalized = normalize_json(json);
 
         // The response is { "info": {...}, "parts": [...] }
-        // Parts come as flat objects with "type" discriminator, but proto expects
-        // them wrapped as {"text": {...}} or {"tool": {...}} etc.
-        let raw_parts = normalized
-            .get("parts")
-            .cloned()
-            .unwrap_or(Value::Array(vec![]));
-
-        // Transform parts from flat format to tagged format for proto oneOf
-        let transformed_parts = if let Value::Array(parts_arr) = raw_parts {
-            let wrapped: Vec<Value> = parts_arr
-                .into_iter()
-                .filter_map(|part| {
-                    if let Value::Object(ref obj) = part {
-                        // Get the "type" field to determine the variant
-                        if let Some(Value::String(type_name)) = obj.get("type") {
-                            // Convert kebab-case to snake_case for proto field names
-                            let proto_field_name = type_name.replace('-', "_");
-                            // Wrap the part object with its type as the key
-                            let mut wrapper = serde_json::Map::new();
-                            wrapper.insert(proto_field_name, part);
-                            return Some(Value::Object(wrapper));
-                        }
-                    }
-                    None
-                })
-                .collect();
-            Value::Array(wrapped)
-        } else {
-            Value::Array(vec![])
-        };
-
-        let info_value = normalized
-            .get_mut("info")
-            .ok_or_else(|| OpencodeClientError::Server {
-                message: "Response missing 'info' field".to_string(),
+        let mut info_value = inject_tagged_parts(&mut normalized)?;
+
+        // A failed generation still arrives as an assistant message; report it as an error
+        let error_value = info_value
+            .as_object_mut()
+            .and_then(|info| info.remove("error"));
+        if let Some(error) = error_value.as_ref().and_then(parse_message_error) {
+            let message = describe_message_error(&error);
+            warn!("Assistant message in session {session_id} failed: {message}");
+            return Err(OpencodeClientError::Assistant {
+                message,
+                error: Box::new(error),
                 location: ErrorLocation::from(Location::caller()),
-            })?;
-
-        debug!(
-            "Transformed parts JSON: {}",
-            serde_json::to_string_pretty(&transformed_parts).unwrap_or_default()
-        );
-
-        // Inject transformed parts into the info object
-        if let Value::Object(info_map) = info_value {
-            info_map.insert("parts".to_string(), transformed_parts);
+            });
         }
 
-        let assistant: crate::proto::message::OcAssistantMessage =
-            serde_json::from_value(info_value.clone()).map_err(|e| {
-                OpencodeClientError::Server {
-                    message: format!("Failed to parse assistant message: {e}"),
-                    location: ErrorLocation::from(Location::caller()),
-                }
+        flatten_token_cache(&mut info_value);
+        let assistant: OcAssistantMessage =
+            serde_json::from_value(info_value).map_err(|e| OpencodeClientError::Server {
+                message: format!("Failed to parse assistant message: {e}"),
+                status: None,
+                location: ErrorLocation::from(Location::caller()),
             })?;
 
         info!(
@@ -274,9 +708,137 @@ impl OpencodeClient {
         debug!("Assistant message received for session {session_id}: {assistant:?}");
 
         Ok(OcMessage {
-            message: Some(crate::proto::message::oc_message::Message::Assistant(
-                assistant,
-            )),
+            message: Some(oc_message::Message::Assistant(assistant)),
         })
     }
 }
+
+#[track_caller]
+fn build_http_client(timeout: Duration) -> Result<Client, OpencodeClientError> {
+    Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| OpencodeClientError::from_reqwest("build HTTP client", &e))
+}
+
+/// Client for long-lived streams: only connecting is bound by `connect_timeout`.
+#[track_caller]
+fn build_streaming_client(connect_timeout: Duration) -> Result<Client, OpencodeClientError> {
+    Client::builder()
+        .connect_timeout(connect_timeout)
+        .build()
+        .map_err(|e| OpencodeClientError::from_reqwest("build HTTP client", &e))
+}
+
+/// Moves the top-level `parts` of a `{ "info": {...}, "parts": [...] }` entry into
+/// `info`, wrapping each part for the proto `oneof` and returning the updated info.
+///
+/// Parts come as flat objects with a "type" discriminator, but proto expects
+/// them wrapped as {"text": {...}} or {"tool": {...}} etc.
+#[track_caller]
+fn inject_tagged_parts(entry: &mut Value) -> Result<Value, OpencodeClientError> {
+    let raw_parts = entry.get("parts").cloned().unwrap_or(Value::Array(vec![]));
+
+    // Transform parts from flat format to tagged format for proto oneOf
+    let transformed_parts = if let Value::Array(parts_arr) = raw_parts {
+        let wrapped: Vec<Value> = parts_arr.into_iter().filter_map(tag_part).collect();
+        Value::Array(wrapped)
+    } else {
+        Value::Array(vec![])
+    };
+
+    let info_value = entry
+        .get_mut("info")
+        .ok_or_else(|| OpencodeClientError::Server {
+            message: "Response missing 'info' field".to_string(),
+            status: None,
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+
+    debug!(
+        "Transformed parts JSON: {}",
+        serde_json::to_string_pretty(&transformed_parts).unwrap_or_default()
+    );
+
+    // Inject transformed parts into the info object
+    if let Value::Object(info_map) = info_value {
+        info_map.insert("parts".to_string(), transformed_parts);
+    }
+
+    Ok(info_value.take())
+}
+
+/// Moves OpenCode's `tokens.cache.{read,write}` to the proto's `tokens.cache_{read,write}`.
+fn flatten_token_cache(info_value: &mut Value) {
+    let Some(tokens) = info_value.get_mut("tokens").and_then(Value::as_object_mut) else {
+        return;
+    };
+    let Some(Value::Object(cache)) = tokens.remove("cache") else {
+        return;
+    };
+    for (from, to) in [("read", "cache_read"), ("write", "cache_write")] {
+        if let Some(value) = cache.get(from) {
+            tokens.entry(to).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// Wraps a flat part (`{"type": "text", ...}`) as `{"text": {...}}` for the proto `oneof`.
+///
+/// Returns `None` if the part has no string `type` discriminator.
+pub(crate) fn tag_part(part: Value) -> Option<Value> {
+    // Get the "type" field to determine the variant
+    let Value::String(type_name) = part.get("type")? else {
+        return None;
+    };
+    // Convert kebab-case to snake_case for proto field names
+    let proto_field_name = type_name.replace('-', "_");
+    // Wrap the part object with its type as the key
+    let mut wrapper = serde_json::Map::new();
+    wrapper.insert(proto_field_name, part);
+    Some(Value::Object(wrapper))
+}
+
+/// Parses a message `info` object into the [`OcMessage`] variant matching its `role`.
+#[track_caller]
+pub(crate) fn parse_message(info_value: Value) -> Result<OcMessage, OpencodeClientError> {
+    let role = info_value
+        .get("role")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    let message = match role.as_str() {
+        "user" => {
+            let user: OcUserMessage =
+                serde_json::from_value(info_value).map_err(|e| OpencodeClientError::Server {
+                    message: format!("Failed to parse user message: {e}"),
+                    status: None,
+                    location: ErrorLocation::from(Location::caller()),
+                })?;
+            oc_message::Message::User(user)
+        }
+        "assistant" => {
+            let mut info_value = info_value;
+            flatten_token_cache(&mut info_value);
+            let assistant: OcAssistantMessage =
+                serde_json::from_value(info_value).map_err(|e| OpencodeClientError::Server {
+                    message: format!("Failed to parse assistant message: {e}"),
+                    status: None,
+                    location: ErrorLocation::from(Location::caller()),
+                })?;
+            oc_message::Message::Assistant(assistant)
+        }
+        other => {
+            return Err(OpencodeClientError::Server {
+                message: format!("Unknown message role: '{other}'"),
+                status: None,
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+    };
+
+    Ok(OcMessage {
+        message: Some(message),
+    })
+}
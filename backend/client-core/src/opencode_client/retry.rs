@@ -0,0 +1,75 @@
+//! Retry policy for transient OpenCode server failures.
+//!
+//! The server may be mid-restart (connection refused), briefly overloaded (502-504) or
+//! rate limiting (429). Those failures are worth retrying; other statuses are not. The
+//! statuses are [`HttpStatusCode::is_retryable`]'s, so a retried failure is also one
+//! that [`OpencodeClientError::is_retryable`](crate::error::opencode_client::OpencodeClientError::is_retryable)
+//! reports as retryable.
+
+use common::HttpStatusCode;
+
+use std::time::Duration;
+
+use backoff::ExponentialBackoff;
+use reqwest::Response;
+
+/// How an [`OpencodeClient`](crate::opencode_client::OpencodeClient) retries transient failures.
+///
+/// The default policy never retries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retry).
+    pub max_retries: u32,
+    /// Initial retry delay.
+    pub initial_delay: Duration,
+    /// Maximum retry delay.
+    pub max_delay: Duration,
+    /// Also retry non-idempotent requests (create/delete session).
+    ///
+    /// Off by default: if the server applied the request before failing, a retry repeats it.
+    pub retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    /// Retry idempotent requests up to `max_retries` times with default delays.
+    pub fn with_max_retries(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Whether a request may be retried under this policy.
+    pub(crate) fn allows(&self, idempotent: bool) -> bool {
+        self.max_retries > 0 && (idempotent || self.retry_non_idempotent)
+    }
+
+    pub(crate) fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff {
+            initial_interval: self.initial_delay,
+            current_interval: self.initial_delay,
+            max_interval: self.max_delay,
+            max_elapsed_time: None,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(2),
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+/// Is this attempt's outcome worth retrying (timeout, connection error, or retryable status)?
+pub(crate) fn is_transient(result: &Result<Response, reqwest::Error>) -> bool {
+    match result {
+        Ok(response) => HttpStatusCode(response.status().as_u16()).is_retryable(),
+        Err(e) => e.is_timeout() || e.is_connect(),
+    }
+}
@@ -0,0 +1,258 @@
+//! Fetch the models a provider offers, as described by its [`ProviderConfig`].
+//!
+//! Each provider in `models.toml` says where its model list lives (`models_url`), how to
+//! authenticate (`auth_type` plus `auth_header`/`auth_param`), and how to read the response
+//! (`response_format`). [`fetch_models`] turns that into one request and returns the
+//! models as [`CuratedModel`]s the user can pick from.
+//!
+//! # Extra headers
+//!
+//! `extra_headers` values may reference environment variables as `${NAME}`, e.g.
+//! `OpenAI-Organization = "${OPENAI_ORG}"`. They are resolved when the request is built;
+//! a `$` not followed by `{` is kept as-is. Substituted values are treated as secrets and
+//! never logged.
+//!
+//! # Response paths
+//!
+//! `models_path`, `model_id_field` and `model_name_field` are dot-separated object keys
+//! (e.g. `data`, `result.models`); an empty `models_path` means the response is the array.
+
+use crate::config::models::{CuratedModel, ProviderConfig};
+use crate::error::provider_models::ProviderModelsError;
+
+use common::{ErrorLocation, RedactedApiKey};
+
+use std::panic::Location;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use reqwest::header::HeaderValue;
+use reqwest::{Client, RequestBuilder};
+use serde_json::Value;
+use url::Url;
+
+/// Time allowed for a provider to answer the models request.
+const FETCH_MODELS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fetch the models `provider` offers, authenticating with `key`.
+///
+/// Entries without a string ID are skipped; entries without a name use their ID.
+///
+/// # Errors
+///
+/// - [`ProviderModelsError::Config`] - Unknown `auth_type`, missing `auth_header`/`auth_param`,
+///   an invalid URL or header, or an extra header referencing an unset environment variable
+/// - [`ProviderModelsError::Request`] - The provider couldn't be reached
+/// - [`ProviderModelsError::Http`] - The provider rejected the request (e.g. bad key)
+/// - [`ProviderModelsError::Format`] - The response doesn't match `response_format`
+pub async fn fetch_models(
+    provider: &ProviderConfig,
+    key: &RedactedApiKey,
+) -> Result<Vec<CuratedModel>, ProviderModelsError> {
+    fetch_models_with_env(provider, key, |var| std::env::var(var).ok()).await
+}
+
+/// [`fetch_models`], resolving `${NAME}` references in extra headers with `env`.
+pub(crate) async fn fetch_models_with_env(
+    provider: &ProviderConfig,
+    key: &RedactedApiKey,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<CuratedModel>, ProviderModelsError> {
+    let client = Client::builder()
+        .timeout(FETCH_MODELS_TIMEOUT)
+        .build()
+        .map_err(|e| ProviderModelsError::from_reqwest(&provider.name, e))?;
+
+    debug!("Fetching models for provider '{}'", provider.name);
+
+    let response = build_request(&client, provider, key, &env)?
+        .send()
+        .await
+        .map_err(|e| ProviderModelsError::from_reqwest(&provider.name, e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(ProviderModelsError::Http {
+            status: status.as_u16(),
+            message: format!("{}: {body}", provider.name),
+            location: ErrorLocation::from(Location::caller()),
+        });
+    }
+
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| ProviderModelsError::from_reqwest(&provider.name, e))?;
+
+    let models = extract_models(provider, &body)?;
+    info!(
+        "Fetched {} models for provider '{}'",
+        models.len(),
+        provider.name
+    );
+    Ok(models)
+}
+
+/// Build the models request with the provider's auth and extra headers.
+fn build_request(
+    client: &Client,
+    provider: &ProviderConfig,
+    key: &RedactedApiKey,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<RequestBuilder, ProviderModelsError> {
+    let mut url = Url::parse(&provider.models_url).map_err(|e| ProviderModelsError::Config {
+        message: format!("Invalid models_url for provider '{}': {e}", provider.name),
+        location: ErrorLocation::from(Location::caller()),
+    })?;
+
+    let request = match provider.auth_type.as_str() {
+        "bearer" => client.get(url).bearer_auth(key.as_str()),
+        "header" => {
+            let header = required_field(provider, "auth_header", &provider.auth_header)?;
+            client.get(url).header(header, key.as_str())
+        }
+        "query_param" => {
+            let param = required_field(provider, "auth_param", &provider.auth_param)?;
+            url.query_pairs_mut().append_pair(param, key.as_str());
+            client.get(url)
+        }
+        other => {
+            return Err(ProviderModelsError::Config {
+                message: format!(
+                    "Invalid auth_type '{other}' for provider '{}'",
+                    provider.name
+                ),
+                location: ErrorLocation::from(Location::caller()),
+            });
+        }
+    };
+
+    provider
+        .extra_headers
+        .iter()
+        .try_fold(request, |request, (name, template)| {
+            Ok(request.header(name, header_value(provider, name, template, env)?))
+        })
+}
+
+/// Resolve the `${NAME}` references in an extra header's `template`, looking each up with
+/// `env`.
+///
+/// Values with a substitution are marked sensitive so they're redacted from debug output.
+fn header_value(
+    provider: &ProviderConfig,
+    name: &str,
+    template: &str,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<HeaderValue, ProviderModelsError> {
+    let mut resolved = String::with_capacity(template.len());
+    let mut rest = template;
+    let mut substituted = false;
+
+    while let Some(start) = rest.find("${") {
+        resolved.push_str(&rest[..start]);
+        let Some(len) = rest[start + 2..].find('}') else {
+            return Err(header_error(provider, name, "has an unclosed '${'"));
+        };
+        let var = &rest[start + 2..start + 2 + len];
+        if var.is_empty() {
+            return Err(header_error(provider, name, "has an empty '${}'"));
+        }
+        let value = env(var).ok_or_else(|| {
+            header_error(
+                provider,
+                name,
+                &format!("references environment variable '{var}', which is not set"),
+            )
+        })?;
+        resolved.push_str(&value);
+        substituted = true;
+        rest = &rest[start + 2 + len + 1..];
+    }
+    resolved.push_str(rest);
+
+    let mut value = HeaderValue::from_str(&resolved)
+        .map_err(|_| header_error(provider, name, "has a value that isn't a valid header"))?;
+    value.set_sensitive(substituted);
+    Ok(value)
+}
+
+#[track_caller]
+fn header_error(provider: &ProviderConfig, name: &str, problem: &str) -> ProviderModelsError {
+    ProviderModelsError::Config {
+        message: format!(
+            "Extra header '{name}' for provider '{}' {problem}",
+            provider.name
+        ),
+        location: ErrorLocation::from(Location::caller()),
+    }
+}
+
+#[track_caller]
+fn required_field<'a>(
+    provider: &ProviderConfig,
+    field: &str,
+    value: &'a Option<String>,
+) -> Result<&'a str, ProviderModelsError> {
+    match value.as_deref() {
+        Some(value) if !value.is_empty() => Ok(value),
+        _ => Err(ProviderModelsError::Config {
+            message: format!(
+                "Provider '{}' uses auth_type '{}' but has no {field}",
+                provider.name, provider.auth_type
+            ),
+            location: ErrorLocation::from(Location::caller()),
+        }),
+    }
+}
+
+/// Read the models out of a response `body` per the provider's `response_format`.
+pub fn extract_models(
+    provider: &ProviderConfig,
+    body: &Value,
+) -> Result<Vec<CuratedModel>, ProviderModelsError> {
+    let format = &provider.response_format;
+
+    let entries = lookup(body, &format.models_path)
+        .and_then(Value::as_array)
+        .ok_or_else(|| ProviderModelsError::Format {
+            message: format!(
+                "{}: no array at models_path '{}'",
+                provider.name, format.models_path
+            ),
+            location: ErrorLocation::from(Location::caller()),
+        })?;
+
+    let mut models = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let Some(raw_id) = lookup(entry, &format.model_id_field).and_then(Value::as_str) else {
+            warn!(
+                "Skipping {} model without a string '{}'",
+                provider.name, format.model_id_field
+            );
+            continue;
+        };
+
+        let model_id = format
+            .model_id_strip_prefix
+            .as_deref()
+            .and_then(|prefix| raw_id.strip_prefix(prefix))
+            .unwrap_or(raw_id);
+        let name = lookup(entry, &format.model_name_field)
+            .and_then(Value::as_str)
+            .unwrap_or(model_id);
+
+        models.push(CuratedModel::new(name, &provider.name, model_id));
+    }
+
+    Ok(models)
+}
+
+/// Follow a dot-separated key `path` into `value` (empty path: `value` itself).
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
@@ -0,0 +1,114 @@
+//! Session-level views over OpenCode sessions and their message history.
+//!
+//! [`summarize`] totals the token usage of a session's assistant messages for cost and
+//! quota display. [`sort_sessions_by_recent`] and [`sort_sessions_by_created`] order a
+//! session list (which the server returns unordered) newest first.
+
+use crate::proto::message::{OcMessage, oc_message};
+use crate::proto::session::OcSessionInfo;
+
+use std::cmp::Ordering;
+
+/// Token usage totalled over a session's assistant messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TokenSummary {
+    /// Input (prompt) tokens.
+    pub input: u64,
+    /// Output (completion) tokens.
+    pub output: u64,
+    /// Tokens served from the provider's prompt cache.
+    pub cache_read: u64,
+    /// Tokens written to the provider's prompt cache.
+    pub cache_write: u64,
+    /// Cost in USD.
+    pub cost: f64,
+    /// Assistant messages counted, including those without token info.
+    pub assistant_messages: usize,
+}
+
+impl TokenSummary {
+    /// Add `message`'s usage. User messages are ignored; missing counts add zero.
+    pub fn accumulate(&mut self, message: &OcMessage) {
+        let Some(oc_message::Message::Assistant(assistant)) = &message.message else {
+            return;
+        };
+
+        self.assistant_messages += 1;
+        self.cost += assistant.cost;
+        if let Some(tokens) = &assistant.tokens {
+            self.input += count(tokens.input);
+            self.output += count(tokens.output);
+            self.cache_read += tokens.cache_read.map_or(0, count);
+            self.cache_write += tokens.cache_write.map_or(0, count);
+        }
+    }
+
+    /// Input plus output tokens.
+    pub fn total(&self) -> u64 {
+        self.input + self.output
+    }
+}
+
+/// Total the token usage of every assistant message in `messages`.
+pub fn summarize(messages: &[OcMessage]) -> TokenSummary {
+    messages
+        .iter()
+        .fold(TokenSummary::default(), |mut summary, message| {
+            summary.accumulate(message);
+            summary
+        })
+}
+
+/// Token counts are `int32` on the wire; a negative count is treated as zero.
+fn count(tokens: i32) -> u64 {
+    u64::try_from(tokens).unwrap_or(0)
+}
+
+impl OcSessionInfo {
+    /// When the session was last updated (Unix ms), or `None` if missing or zero.
+    pub fn updated_at(&self) -> Option<i64> {
+        self.time
+            .as_ref()
+            .map(|time| time.updated)
+            .filter(|&ms| ms > 0)
+    }
+
+    /// When the session was created (Unix ms), or `None` if missing or zero.
+    pub fn created_at(&self) -> Option<i64> {
+        self.time
+            .as_ref()
+            .map(|time| time.created)
+            .filter(|&ms| ms > 0)
+    }
+
+    /// Order for "most recently updated first": later `updated` first, sessions without a
+    /// timestamp last, ties broken by ID.
+    pub fn cmp_by_recent(&self, other: &Self) -> Ordering {
+        newest_first(self.updated_at(), other.updated_at()).then_with(|| self.id.cmp(&other.id))
+    }
+
+    /// Like [`cmp_by_recent`](Self::cmp_by_recent), on the `created` timestamp.
+    pub fn cmp_by_created(&self, other: &Self) -> Ordering {
+        newest_first(self.created_at(), other.created_at()).then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+/// Sort sessions most recently updated first (see [`OcSessionInfo::cmp_by_recent`]).
+pub fn sort_sessions_by_recent(sessions: &mut [OcSessionInfo]) {
+    sessions.sort_by(OcSessionInfo::cmp_by_recent);
+}
+
+/// Sort sessions most recently created first (see [`OcSessionInfo::cmp_by_created`]).
+pub fn sort_sessions_by_created(sessions: &mut [OcSessionInfo]) {
+    sessions.sort_by(OcSessionInfo::cmp_by_created);
+}
+
+/// Descending by timestamp, with `None` after every timestamp.
+fn newest_first(a: Option<i64>, b: Option<i64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => b.cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,104 @@
+// Unit tests for keychain module (requires the `keychain` feature)
+// Tests key storage and the keychain/env merge against an in-memory backend
+
+use crate::auth_sync::keychain::{
+    KeychainBackend, delete_key_with, load_api_keys_with, load_key_with, store_key_with,
+};
+use crate::config::models::ModelsConfig;
+use crate::error::AuthSyncError;
+use crate::tests::fixtures::{FakeEnv, provider};
+
+use common::RedactedApiKey;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// In-memory keychain for tests.
+#[derive(Debug, Default)]
+struct MemoryKeychain {
+    secrets: Mutex<HashMap<String, RedactedApiKey>>,
+}
+
+impl KeychainBackend for MemoryKeychain {
+    fn set_secret(&self, provider: &str, secret: &str) -> Result<(), AuthSyncError> {
+        self.secrets.lock().unwrap().insert(
+            provider.to_string(),
+            RedactedApiKey::new(secret.to_string()),
+        );
+        Ok(())
+    }
+
+    fn get_secret(&self, provider: &str) -> Result<Option<String>, AuthSyncError> {
+        Ok(self
+            .secrets
+            .lock()
+            .unwrap()
+            .get(provider)
+            .map(|key| key.as_str().to_string()))
+    }
+
+    fn delete_secret(&self, provider: &str) -> Result<(), AuthSyncError> {
+        self.secrets.lock().unwrap().remove(provider);
+        Ok(())
+    }
+}
+
+/// **VALUE**: Verifies a key stored in the keychain loads back unchanged and can be deleted.
+///
+/// **WHY THIS MATTERS**: Users moving keys out of .env must get the exact key back on the
+/// next launch, and removing it must actually remove it.
+///
+/// **BUG THIS CATCHES**: Would catch keys stored under the wrong account, truncated, or
+/// left behind after delete.
+#[test]
+fn given_stored_key_when_loaded_then_round_trips_until_deleted() {
+    // GIVEN: A key stored in the keychain
+    let keychain = MemoryKeychain::default();
+    let key = RedactedApiKey::new("kc-a1B2c3D4e5F6g7H8".to_string());
+    store_key_with(&keychain, "acme", &key).unwrap();
+
+    // WHEN: Loading it, then deleting and loading again
+    let loaded = load_key_with(&keychain, "acme");
+    delete_key_with(&keychain, "acme").unwrap();
+    let after_delete = load_key_with(&keychain, "acme");
+
+    // THEN: The first load returns the same key; the second finds nothing
+    assert_eq!(loaded.unwrap().as_str(), key.as_str());
+    assert!(after_delete.is_none());
+    assert!(load_key_with(&keychain, "other").is_none());
+}
+
+/// **VALUE**: Verifies the keychain wins over .env per provider, with env as fallback.
+///
+/// **WHY THIS MATTERS**: Users migrating to the keychain often leave an old .env around;
+/// the key they stored deliberately must be the one synced.
+///
+/// **BUG THIS CATCHES**: Would catch the merge order being reversed, or providers without
+/// a keychain entry losing their env key.
+#[test]
+fn given_keychain_and_env_keys_when_loading_then_keychain_wins() {
+    // GIVEN: Env keys for two providers and a keychain key for one of them
+    let env = FakeEnv::new(&[
+        ("KCTEST_BOTH_API_KEY", "env-a1B2c3D4e5F6g7H8"),
+        ("KCTEST_ENV_API_KEY", "env-z9Y8x7W6v5U4t3S2"),
+    ]);
+    let keychain = MemoryKeychain::default();
+    store_key_with(
+        &keychain,
+        "kctest-both",
+        &RedactedApiKey::new("kc-a1B2c3D4e5F6g7H8".to_string()),
+    )
+    .unwrap();
+    let config = ModelsConfig {
+        providers: vec![provider("kctest-both"), provider("kctest-env")],
+        ..Default::default()
+    };
+
+    // WHEN: Loading keys from both sources
+    let loaded = load_api_keys_with(&keychain, &env, &config);
+
+    // THEN: Each provider gets its key from the right source
+    assert_eq!(loaded.keys["kctest-both"].as_str(), "kc-a1B2c3D4e5F6g7H8");
+    assert_eq!(loaded.keys["kctest-env"].as_str(), "env-z9Y8x7W6v5U4t3S2");
+    assert!(loaded.validation_errors.is_empty());
+}
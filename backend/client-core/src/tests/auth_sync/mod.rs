@@ -0,0 +1,6 @@
+#[cfg(feature = "keychain")]
+mod keychain;
+mod oauth;
+mod paths;
+mod sync;
+mod validation;
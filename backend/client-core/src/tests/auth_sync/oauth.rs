@@ -0,0 +1,139 @@
+// Unit tests for oauth module
+// Tests OAuth status detection against an auth.json fixture
+
+use crate::auth_sync::OAuthStatus;
+use crate::auth_sync::oauth::{AuthInfo, check_oauth_status_in, write_auth_entry_in};
+
+use std::path::Path;
+
+const AUTH_FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/tests/fixtures/auth_expired_oauth.json"
+);
+
+/// **VALUE**: Verifies an expired OAuth entry is reported as expired and doesn't skip sync.
+///
+/// **WHY THIS MATTERS**: A stale OAuth login left in auth.json would otherwise stop the
+/// user's API key from ever being synced, leaving the provider unusable.
+///
+/// **BUG THIS CATCHES**: Would catch if `expires` is ignored (every OAuth entry counted
+/// as configured) or compared in the wrong unit.
+#[test]
+fn given_expired_oauth_entry_when_checking_status_then_sync_not_skipped() {
+    // GIVEN: An auth.json whose anthropic token expired in 2023
+    let auth_file = Path::new(AUTH_FIXTURE);
+
+    // WHEN: Checking the provider's status
+    let status = check_oauth_status_in(auth_file, "anthropic").unwrap();
+
+    // THEN: It is expired, and the API key is still synced
+    assert_eq!(
+        status,
+        OAuthStatus::Expired {
+            expires: 1_700_000_000_000.0
+        }
+    );
+    assert!(!status.should_skip_api_key_sync());
+}
+
+/// **VALUE**: Verifies unexpired OAuth and API-key entries keep their existing statuses.
+///
+/// **WHY THIS MATTERS**: Providers with a valid OAuth login must still skip API key sync.
+///
+/// **BUG THIS CATCHES**: Would catch an inverted expiry comparison.
+#[test]
+fn given_valid_oauth_and_api_entries_when_checking_status_then_unchanged() {
+    // GIVEN: The same auth.json with a token valid until 2100 and an API key entry
+    let auth_file = Path::new(AUTH_FIXTURE);
+
+    // WHEN: Checking each provider
+    let oauth = check_oauth_status_in(auth_file, "github-copilot").unwrap();
+    let api = check_oauth_status_in(auth_file, "openai").unwrap();
+    let missing = check_oauth_status_in(auth_file, "mistral").unwrap();
+
+    // THEN: Only the valid OAuth entry skips sync
+    assert_eq!(oauth, OAuthStatus::Configured);
+    assert!(oauth.should_skip_api_key_sync());
+    assert_eq!(api, OAuthStatus::ApiKeyConfigured);
+    assert_eq!(missing, OAuthStatus::NotConfigured);
+}
+
+/// **VALUE**: Verifies a token counts as expired from its expiry instant onward.
+///
+/// **WHY THIS MATTERS**: The boundary decides whether a key is synced for a token that's
+/// about to be rejected.
+///
+/// **BUG THIS CATCHES**: Would catch an off-by-one (strict vs. inclusive) comparison.
+#[test]
+fn given_oauth_info_when_status_at_expiry_boundary_then_expired() {
+    // GIVEN: An OAuth entry expiring at t = 1000 ms
+    let info = AuthInfo::OAuth {
+        access: String::new(),
+        refresh: String::new(),
+        expires: 1000.0,
+    };
+
+    // WHEN / THEN: Valid just before, expired at the instant
+    assert_eq!(info.to_oauth_status_at(999.0), OAuthStatus::Configured);
+    assert_eq!(
+        info.to_oauth_status_at(1000.0),
+        OAuthStatus::Expired { expires: 1000.0 }
+    );
+}
+
+/// **VALUE**: Verifies a written entry reads back as the same auth info, next to existing ones.
+///
+/// **WHY THIS MATTERS**: auth.json is shared with OpenCode; a write that loses or reshapes
+/// other providers' entries would log the user out of them.
+///
+/// **BUG THIS CATCHES**: Would catch serialization without the `type` tag, replacing the
+/// whole file instead of merging, or dropping entries we can't parse.
+#[test]
+fn given_existing_auth_file_when_writing_entry_then_round_trips_and_keeps_others() {
+    // GIVEN: An auth.json with an OAuth entry and an entry of an unknown type
+    let dir = tempfile::TempDir::new().unwrap();
+    let auth_file = dir.path().join("auth.json");
+    std::fs::write(
+        &auth_file,
+        r#"{
+            "github-copilot": {"type": "oauth", "access": "a", "refresh": "r", "expires": 4102444800000},
+            "future": {"type": "something-new", "value": 1}
+        }"#,
+    )
+    .unwrap();
+
+    // WHEN: Recording an API key for another provider
+    let info = AuthInfo::ApiKey {
+        key: "sk-written-a1B2c3D4".to_string(),
+    };
+    write_auth_entry_in(&auth_file, "openai", &info).unwrap();
+
+    // THEN: The new entry reads back, and the existing entries are untouched
+    assert_eq!(
+        check_oauth_status_in(&auth_file, "openai").unwrap(),
+        OAuthStatus::ApiKeyConfigured
+    );
+    assert_eq!(
+        check_oauth_status_in(&auth_file, "github-copilot").unwrap(),
+        OAuthStatus::Configured
+    );
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&auth_file).unwrap()).unwrap();
+    assert_eq!(
+        written["openai"],
+        serde_json::json!({"type": "api", "key": "sk-written-a1B2c3D4"})
+    );
+    assert_eq!(written["future"]["type"], "something-new");
+    let files: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(files, ["auth.json"], "temp file left behind");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&auth_file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
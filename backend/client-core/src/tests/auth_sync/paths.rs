@@ -0,0 +1,157 @@
+// Unit tests for paths module
+// Tests each lookup step, including other platforms' fallbacks, with a fake environment
+
+use crate::auth_sync::paths::{PathEnvironment, PathSource, Platform, detect_opencode_paths_with};
+use crate::error::AuthSyncError;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Environment with fixed variables, `dirs` result, and platform.
+struct FakeEnvironment {
+    vars: HashMap<&'static str, &'static str>,
+    data_local_dir: Option<PathBuf>,
+    platform: Platform,
+}
+
+impl FakeEnvironment {
+    /// `dirs` unavailable, so detection must use the platform fallback.
+    fn without_dirs(platform: Platform, vars: &[(&'static str, &'static str)]) -> Self {
+        Self {
+            vars: vars.iter().copied().collect(),
+            data_local_dir: None,
+            platform,
+        }
+    }
+}
+
+impl PathEnvironment for FakeEnvironment {
+    fn var(&self, key: &str) -> Option<String> {
+        self.vars.get(key).map(|v| v.to_string())
+    }
+
+    fn data_local_dir(&self) -> Option<PathBuf> {
+        self.data_local_dir.clone()
+    }
+
+    fn platform(&self) -> Platform {
+        self.platform
+    }
+}
+
+/// **VALUE**: Verifies the Linux fallback is `$HOME/.local/share/opencode`.
+///
+/// **WHY THIS MATTERS**: Minimal containers and service accounts often have HOME but no
+/// XDG setup; auth.json must still be found where OpenCode writes it.
+///
+/// **BUG THIS CATCHES**: Would catch a wrong fallback path or source for Linux.
+#[test]
+fn given_linux_without_dirs_when_detecting_then_uses_home_local_share() {
+    // GIVEN: Linux with HOME set and no dirs result
+    let environment = FakeEnvironment::without_dirs(Platform::Linux, &[("HOME", "/home/dev")]);
+
+    // WHEN: Detecting paths
+    let paths = detect_opencode_paths_with(&environment).unwrap();
+
+    // THEN: The Linux fallback is used
+    assert_eq!(paths.source, PathSource::LinuxFallback);
+    assert_eq!(
+        paths.data_dir,
+        PathBuf::from("/home/dev/.local/share/opencode")
+    );
+    assert_eq!(
+        paths.auth_file,
+        PathBuf::from("/home/dev/.local/share/opencode/auth.json")
+    );
+}
+
+/// **VALUE**: Verifies the macOS fallback is `$HOME/Library/Application Support/opencode`.
+///
+/// **WHY THIS MATTERS**: This branch only compiles into macOS builds, so without an
+/// injectable platform it was never exercised by CI.
+///
+/// **BUG THIS CATCHES**: Would catch a wrong fallback path or source for macOS.
+#[test]
+fn given_macos_without_dirs_when_detecting_then_uses_application_support() {
+    // GIVEN: macOS with HOME set and no dirs result
+    let environment = FakeEnvironment::without_dirs(Platform::MacOS, &[("HOME", "/Users/dev")]);
+
+    // WHEN: Detecting paths
+    let paths = detect_opencode_paths_with(&environment).unwrap();
+
+    // THEN: The macOS fallback is used
+    assert_eq!(paths.source, PathSource::MacOSFallback);
+    assert_eq!(
+        paths.data_dir,
+        PathBuf::from("/Users/dev/Library/Application Support/opencode")
+    );
+}
+
+/// **VALUE**: Verifies the Windows fallback is `%APPDATA%\opencode` and ignores HOME.
+///
+/// **WHY THIS MATTERS**: Git Bash and similar shells set HOME on Windows; using it would
+/// point at a directory OpenCode never writes.
+///
+/// **BUG THIS CATCHES**: Would catch a wrong variable or path for the Windows fallback.
+#[test]
+fn given_windows_without_dirs_when_detecting_then_uses_appdata() {
+    // GIVEN: Windows with APPDATA (and a HOME that must be ignored) and no dirs result
+    let environment = FakeEnvironment::without_dirs(
+        Platform::Windows,
+        &[
+            ("APPDATA", "C:/Users/dev/AppData/Roaming"),
+            ("HOME", "/c/Users/dev"),
+        ],
+    );
+
+    // WHEN: Detecting paths
+    let paths = detect_opencode_paths_with(&environment).unwrap();
+
+    // THEN: The Windows fallback is used
+    assert_eq!(paths.source, PathSource::WindowsFallback);
+    assert_eq!(
+        paths.data_dir,
+        PathBuf::from("C:/Users/dev/AppData/Roaming").join("opencode")
+    );
+}
+
+/// **VALUE**: Verifies the override and `dirs` steps take precedence over fallbacks, and
+/// that no usable input is an error rather than a guessed path.
+///
+/// **WHY THIS MATTERS**: The lookup order is the module's contract; a guessed path would
+/// silently read the wrong auth.json.
+///
+/// **BUG THIS CATCHES**: Would catch reordered steps or a default path when nothing is set.
+#[test]
+fn given_override_dirs_or_nothing_when_detecting_then_follows_lookup_order() {
+    // GIVEN: An override, a dirs result, and an environment with neither (and no HOME)
+    let overridden = FakeEnvironment {
+        vars: HashMap::from([
+            ("OPENCODE_DATA_DIR", "/data/opencode"),
+            ("HOME", "/home/dev"),
+        ]),
+        data_local_dir: Some(PathBuf::from("/home/dev/.local/share")),
+        platform: Platform::Linux,
+    };
+    let with_dirs = FakeEnvironment {
+        vars: HashMap::from([("HOME", "/home/dev")]),
+        data_local_dir: Some(PathBuf::from("/xdg/data")),
+        platform: Platform::Linux,
+    };
+    let empty = FakeEnvironment::without_dirs(Platform::Linux, &[]);
+
+    // WHEN: Detecting paths in each
+    let overridden = detect_opencode_paths_with(&overridden).unwrap();
+    let with_dirs = detect_opencode_paths_with(&with_dirs).unwrap();
+    let empty = detect_opencode_paths_with(&empty);
+
+    // THEN: Each step wins in order
+    assert_eq!(overridden.source, PathSource::EnvVar);
+    assert_eq!(overridden.data_dir, PathBuf::from("/data/opencode"));
+    assert_eq!(with_dirs.source, PathSource::PlatformDefault);
+    assert_eq!(with_dirs.data_dir, PathBuf::from("/xdg/data/opencode"));
+    assert!(matches!(
+        empty,
+        Err(AuthSyncError::AuthPathDetection { .. })
+    ));
+}
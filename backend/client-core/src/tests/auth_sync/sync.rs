@@ -0,0 +1,798 @@
+// Unit tests for auth_sync module
+// Tests the end-to-end key sync pipeline against a mock OpenCode server
+
+use crate::auth_sync::{
+    LoadedKeys, SyncConfig, SyncReport, describe_env_key, load_env_api_keys_with, sync_loaded_keys,
+};
+use crate::config::models::{ModelsConfig, ProviderConfig};
+use crate::error::AuthSyncError;
+use crate::opencode_client::OpencodeClient;
+use crate::tests::fixtures::{FakeEnv, provider};
+
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use wiremock::matchers::{any, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn models_config(providers: Vec<ProviderConfig>) -> ModelsConfig {
+    ModelsConfig {
+        providers,
+        ..Default::default()
+    }
+}
+
+fn fast_sync_config() -> SyncConfig {
+    SyncConfig {
+        skip_oauth_providers: false,
+        timeout: Duration::from_secs(5),
+        max_retries: 3,
+        initial_delay: Duration::from_millis(5),
+        max_delay: Duration::from_millis(20),
+        dry_run: false,
+        concurrency: 4,
+    }
+}
+
+/// `sync_all_keys`, reading keys from `env` instead of the process environment.
+async fn sync_all_keys_from(
+    client: &OpencodeClient,
+    env: &FakeEnv,
+    config: &ModelsConfig,
+    sync_config: &SyncConfig,
+    cancel: &CancellationToken,
+) -> SyncReport {
+    let loaded_keys = load_env_api_keys_with(env, config);
+    sync_loaded_keys(client, loaded_keys, sync_config, cancel).await
+}
+
+/// **VALUE**: Verifies a valid key is PUT to the server and reported as synced.
+///
+/// **WHY THIS MATTERS**: This is the happy path of the whole module - if it breaks,
+/// no API keys ever reach the OpenCode server.
+///
+/// **BUG THIS CATCHES**: Would catch if sync_all_keys forgets to call sync_api_key,
+/// or reports success without the server accepting the key.
+#[tokio::test]
+async fn given_valid_key_when_sync_all_keys_then_provider_reported_synced() {
+    // GIVEN: A valid key in the environment and a server that accepts it
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-ok"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[("SYNCTEST_OK_API_KEY", "abcdefghijklmnop1234")]);
+    let config = models_config(vec![provider("synctest-ok")]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Syncing all keys
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &fast_sync_config(),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The provider is synced with no failures
+    assert_eq!(report.synced, vec!["synctest-ok".to_string()]);
+    assert!(report.skipped_oauth.is_empty());
+    assert!(report.is_success());
+}
+
+/// **VALUE**: Verifies transient server errors are retried until the sync succeeds.
+///
+/// **WHY THIS MATTERS**: The OpenCode server may be mid-restart when the app launches;
+/// a single 503 must not leave the user without their API key.
+///
+/// **BUG THIS CATCHES**: Would catch if retry logic is skipped or if the server status
+/// is lost when mapping client errors, making 503 look non-retryable.
+#[tokio::test]
+async fn given_transient_failures_when_sync_all_keys_then_retries_until_success() {
+    // GIVEN: A server that fails twice with 503 then succeeds
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-retry"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-retry"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[("SYNCTEST_RETRY_API_KEY", "abcdefghijklmnop1234")]);
+    let config = models_config(vec![provider("synctest-retry")]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Syncing all keys
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &fast_sync_config(),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The provider is eventually synced
+    assert_eq!(report.synced, vec!["synctest-retry".to_string()]);
+    assert!(report.is_success());
+}
+
+/// **VALUE**: Verifies client errors fail immediately with the underlying status code.
+///
+/// **WHY THIS MATTERS**: A 401 means the key is wrong - retrying only delays feedback
+/// and hammers the server.
+///
+/// **BUG THIS CATCHES**: Would catch if 4xx responses are retried or if the failure
+/// is reported without the original HTTP status.
+#[tokio::test]
+async fn given_rejected_key_when_sync_all_keys_then_fails_without_retry() {
+    // GIVEN: A server that rejects the key with 401
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-reject"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[("SYNCTEST_REJECT_API_KEY", "abcdefghijklmnop1234")]);
+    let config = models_config(vec![provider("synctest-reject")]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Syncing all keys
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &fast_sync_config(),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The failure carries the 401 status and no retries were made
+    assert!(report.synced.is_empty());
+    let error = report.failed.get("synctest-reject").expect("should fail");
+    assert_eq!(error.status_code(), Some(401));
+    assert!(!error.is_retryable());
+}
+
+/// **VALUE**: Verifies invalid keys surface as failures without contacting the server.
+///
+/// **WHY THIS MATTERS**: Placeholder values copied from .env.example should be reported
+/// to the user, not pushed to the server.
+///
+/// **BUG THIS CATCHES**: Would catch if validation errors are dropped from the report.
+#[tokio::test]
+async fn given_placeholder_key_when_sync_all_keys_then_reports_validation_failure() {
+    // GIVEN: A placeholder key and a server that must not be called
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[("SYNCTEST_INVALID_API_KEY", "your-api-key-goes-here")]);
+    let config = models_config(vec![provider("synctest-invalid")]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Syncing all keys
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &fast_sync_config(),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The provider is reported as a validation failure
+    assert!(matches!(
+        report.failed.get("synctest-invalid"),
+        Some(AuthSyncError::KeyValidation { .. })
+    ));
+    assert!(report.synced.is_empty());
+}
+
+/// **VALUE**: Verifies the global timeout bounds the whole operation.
+///
+/// **WHY THIS MATTERS**: A hung server must not block app startup indefinitely.
+///
+/// **BUG THIS CATCHES**: Would catch if `SyncConfig::timeout` is ignored, or if the error
+/// rounds a sub-second timeout down to "0s".
+#[tokio::test]
+async fn given_slow_server_when_sync_all_keys_then_reports_global_timeout() {
+    // GIVEN: A server that responds slower than the sync timeout
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-slow"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(2)))
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[("SYNCTEST_SLOW_API_KEY", "abcdefghijklmnop1234")]);
+    let config = models_config(vec![provider("synctest-slow")]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    let sync_config = SyncConfig {
+        timeout: Duration::from_millis(100),
+        ..fast_sync_config()
+    };
+
+    // WHEN: Syncing all keys
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &sync_config,
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The provider fails with GlobalTimeout, reporting the sub-second timeout as set
+    let error = report.failed.get("synctest-slow");
+    assert!(matches!(error, Some(AuthSyncError::GlobalTimeout { .. })));
+    assert!(error.unwrap().to_string().contains("after 100ms"));
+}
+
+/// **VALUE**: Verifies a provider is found by its API key env var and described by name.
+///
+/// **WHY THIS MATTERS**: Errors about env vars are easier to act on when they name the
+/// provider the user knows ("OpenAI") rather than only `OPENAI_API_KEY`.
+///
+/// **BUG THIS CATCHES**: Would catch if lookup matches on the provider name instead of
+/// `api_key_env`, or if the description ignores the display name.
+#[test]
+fn given_known_env_var_when_get_provider_by_env_then_returns_provider() {
+    // GIVEN: Two providers with their own env vars
+    let config = models_config(vec![
+        ProviderConfig {
+            display_name: "OpenAI".to_string(),
+            ..provider("openai")
+        },
+        provider("anthropic"),
+    ]);
+
+    // WHEN: Looking up by env var
+    let found = config.get_provider_by_env("OPENAI_API_KEY");
+
+    // THEN: The matching provider is returned and named in descriptions
+    assert_eq!(found.map(|p| p.name.as_str()), Some("openai"));
+    assert_eq!(
+        describe_env_key(found.unwrap()),
+        "OpenAI API key (OPENAI_API_KEY)"
+    );
+}
+
+/// **VALUE**: Verifies unknown env vars find nothing.
+///
+/// **WHY THIS MATTERS**: Providers without `api_key_env` (OAuth-only) must not match an
+/// empty env var name.
+///
+/// **BUG THIS CATCHES**: Would catch if an unknown or empty env var matches some provider.
+#[test]
+fn given_unknown_env_var_when_get_provider_by_env_then_none() {
+    // GIVEN: A provider with an env var and one without
+    let config = models_config(vec![
+        provider("openai"),
+        ProviderConfig {
+            api_key_env: "".to_string(),
+            ..provider("copilot")
+        },
+    ]);
+
+    // WHEN/THEN: Unknown and empty env vars match nothing
+    assert!(config.get_provider_by_env("MISTRAL_API_KEY").is_none());
+    assert!(config.get_provider_by_env("").is_none());
+}
+
+/// **VALUE**: Verifies a shared env var resolves to the first provider declaring it.
+///
+/// **WHY THIS MATTERS**: Two OpenAI-compatible providers may read the same key; lookup
+/// must be deterministic rather than depend on iteration details.
+///
+/// **BUG THIS CATCHES**: Would catch if the last match wins or lookup fails on duplicates.
+#[test]
+fn given_shared_env_var_when_get_provider_by_env_then_returns_first() {
+    // GIVEN: Two providers sharing an env var
+    let config = models_config(vec![
+        provider("openai"),
+        ProviderConfig {
+            api_key_env: "OPENAI_API_KEY".to_string(),
+            ..provider("azure-openai")
+        },
+    ]);
+
+    // WHEN: Looking up by the shared env var
+    let found = config.get_provider_by_env("OPENAI_API_KEY");
+
+    // THEN: The first provider is returned
+    assert_eq!(found.map(|p| p.name.as_str()), Some("openai"));
+}
+
+/// **VALUE**: Verifies a key description names the provider it's given, not the first
+/// provider sharing its env var.
+///
+/// **WHY THIS MATTERS**: A warning about `azure-openai`'s key that names OpenAI sends the
+/// user to fix the wrong provider.
+///
+/// **BUG THIS CATCHES**: Would catch if the description looks the provider up again by
+/// env var.
+#[test]
+fn given_shared_env_var_when_describing_second_provider_then_names_it() {
+    // GIVEN: Two providers sharing an env var
+    let config = models_config(vec![
+        provider("openai"),
+        ProviderConfig {
+            api_key_env: "OPENAI_API_KEY".to_string(),
+            ..provider("azure-openai")
+        },
+    ]);
+
+    // WHEN: Describing the second provider's key
+    let description = describe_env_key(&config.providers[1]);
+
+    // THEN: It names that provider
+    assert_eq!(description, "azure-openai API key (OPENAI_API_KEY)");
+}
+
+/// **VALUE**: Verifies a key is loaded when the env var's case differs from `api_key_env`.
+///
+/// **WHY THIS MATTERS**: Windows env var names are case-insensitive and the OS may hand
+/// back `OPENAI_API_KEY` for a provider configured as `OpenAI_API_KEY`; an exact-case
+/// lookup would report the key as missing.
+///
+/// **BUG THIS CATCHES**: Would catch if the fallback is dropped or only matches exactly.
+#[test]
+fn given_differently_cased_env_var_when_load_env_api_keys_then_key_loaded() {
+    // GIVEN: The env var set in upper case, the provider configured in mixed case
+    let env = FakeEnv::new(&[("CASETEST_FALLBACK_API_KEY", "abcdefghijklmnop1234")]);
+    let config = models_config(vec![ProviderConfig {
+        api_key_env: "CaseTest_Fallback_Api_Key".to_string(),
+        ..provider("casetest-fallback")
+    }]);
+
+    // WHEN: Loading keys
+    let loaded = load_env_api_keys_with(&env, &config);
+
+    // THEN: The key is found through the case-insensitive fallback
+    let key = loaded
+        .keys
+        .get("casetest-fallback")
+        .expect("key should be loaded despite the case mismatch");
+    assert_eq!(key.as_str(), "abcdefghijklmnop1234");
+    assert!(loaded.validation_errors.is_empty());
+}
+
+/// **VALUE**: Verifies an exact-case env var wins over one differing only in case.
+///
+/// **WHY THIS MATTERS**: On Linux and macOS both names can exist with different values;
+/// the one the user named exactly is the one they meant.
+///
+/// **BUG THIS CATCHES**: Would catch if the case-insensitive index is consulted first.
+#[test]
+fn given_exact_and_differently_cased_env_vars_when_load_env_api_keys_then_exact_wins() {
+    // GIVEN: Two env vars differing only in case
+    let env = FakeEnv::new(&[
+        ("CaseTest_Exact_Api_Key", "exactexactexact12345"),
+        ("CASETEST_EXACT_API_KEY", "otherotherother12345"),
+    ]);
+    let config = models_config(vec![ProviderConfig {
+        api_key_env: "CaseTest_Exact_Api_Key".to_string(),
+        ..provider("casetest-exact")
+    }]);
+
+    // WHEN: Loading keys
+    let loaded = load_env_api_keys_with(&env, &config);
+
+    // THEN: The exact match is used
+    assert_eq!(
+        loaded.keys.get("casetest-exact").map(|k| k.as_str()),
+        Some("exactexactexact12345")
+    );
+}
+
+/// Load the key for a single provider reading `env`, with `env` set to `value`.
+fn load_single_key(name: &str, env: &str, value: &str) -> LoadedKeys {
+    load_env_api_keys_with(
+        &FakeEnv::new(&[(env, value)]),
+        &models_config(vec![ProviderConfig {
+            api_key_env: env.to_string(),
+            ..provider(name)
+        }]),
+    )
+}
+
+/// **VALUE**: Verifies double- and single-quoted .env values load without their quotes.
+///
+/// **WHY THIS MATTERS**: `OPENAI_API_KEY="sk-..."` is how most .env examples are written;
+/// if the quotes reach validation, the prefix check fails and the key is never synced.
+///
+/// **BUG THIS CATCHES**: Would catch quotes surviving into the wrapped key, or only one
+/// quote style being handled.
+#[test]
+fn given_quoted_env_values_when_load_env_api_keys_then_quotes_stripped() {
+    // GIVEN/WHEN: Keys wrapped in double and single quotes
+    let double = load_single_key(
+        "quotetest-double",
+        "QUOTETEST_DOUBLE_API_KEY",
+        "\"abcdefghijklmnop1234\"",
+    );
+    let single = load_single_key(
+        "quotetest-single",
+        "QUOTETEST_SINGLE_API_KEY",
+        "'abcdefghijklmnop1234'",
+    );
+
+    // THEN: Both load as the bare key
+    assert_eq!(
+        double.keys.get("quotetest-double").map(|k| k.as_str()),
+        Some("abcdefghijklmnop1234")
+    );
+    assert_eq!(
+        single.keys.get("quotetest-single").map(|k| k.as_str()),
+        Some("abcdefghijklmnop1234")
+    );
+}
+
+/// **VALUE**: Verifies whitespace around a value (and inside its quotes) is trimmed.
+///
+/// **WHY THIS MATTERS**: Validation already ignores surrounding whitespace, so a padded key
+/// would pass and then be sent to the server with the padding still attached.
+///
+/// **BUG THIS CATCHES**: Would catch trimming only for validation, or quotes not being
+/// recognized when the value is padded.
+#[test]
+fn given_whitespace_padded_env_values_when_load_env_api_keys_then_trimmed() {
+    // GIVEN/WHEN: A padded bare key and a padded quoted key
+    let padded = load_single_key(
+        "padtest-bare",
+        "PADTEST_BARE_API_KEY",
+        "  abcdefghijklmnop1234\t",
+    );
+    let padded_quoted = load_single_key(
+        "padtest-quoted",
+        "PADTEST_QUOTED_API_KEY",
+        " \" abcdefghijklmnop1234 \" ",
+    );
+
+    // THEN: Both load as the bare key
+    assert_eq!(
+        padded.keys.get("padtest-bare").map(|k| k.as_str()),
+        Some("abcdefghijklmnop1234")
+    );
+    assert_eq!(
+        padded_quoted.keys.get("padtest-quoted").map(|k| k.as_str()),
+        Some("abcdefghijklmnop1234")
+    );
+}
+
+/// **VALUE**: Verifies bare values are unchanged and unmatched quotes are not stripped.
+///
+/// **WHY THIS MATTERS**: Stripping is only safe for a matching pair; removing a lone quote
+/// would silently change what the user wrote.
+///
+/// **BUG THIS CATCHES**: Would catch stripping any leading or trailing quote character, or
+/// mismatched pairs like `"...'`.
+#[test]
+fn given_bare_or_unmatched_quote_env_values_when_load_env_api_keys_then_left_as_is() {
+    // GIVEN/WHEN: A bare key and a key with mismatched quotes
+    let bare = load_single_key(
+        "baretest-plain",
+        "BARETEST_PLAIN_API_KEY",
+        "abcdefghijklmnop1234",
+    );
+    let mismatched = load_single_key(
+        "baretest-mismatched",
+        "BARETEST_MISMATCHED_API_KEY",
+        "\"abcdefghijklmnop1234'",
+    );
+
+    // THEN: The bare key loads as-is; the mismatched one keeps its quotes and fails validation
+    assert_eq!(
+        bare.keys.get("baretest-plain").map(|k| k.as_str()),
+        Some("abcdefghijklmnop1234")
+    );
+    assert!(mismatched.keys.is_empty());
+    assert!(
+        mismatched
+            .validation_errors
+            .contains_key("baretest-mismatched")
+    );
+}
+
+/// **VALUE**: Verifies a dry run reports what would happen without sending any key.
+///
+/// **WHY THIS MATTERS**: Ops users preview a sync before pushing keys to a shared server;
+/// a dry run that still PUTs keys defeats the point, and one that hides validation errors
+/// gives a false all-clear.
+///
+/// **BUG THIS CATCHES**: Would catch if the dry-run check comes after the PUT, or if it
+/// returns early before validation failures are collected.
+#[tokio::test]
+async fn given_dry_run_when_sync_all_keys_then_report_populated_without_requests() {
+    // GIVEN: A server that must not receive any request
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    // GIVEN: One valid key and one placeholder key
+    let env = FakeEnv::new(&[
+        ("SYNCTEST_DRY_OK_API_KEY", "abcdefghijklmnop1234"),
+        ("SYNCTEST_DRY_BAD_API_KEY", "your-api-key-here"),
+    ]);
+    let config = models_config(vec![
+        provider("synctest-dry-ok"),
+        provider("synctest-dry-bad"),
+    ]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    let sync_config = SyncConfig {
+        skip_oauth_providers: true,
+        dry_run: true,
+        ..fast_sync_config()
+    };
+
+    // WHEN: Syncing all keys as a dry run
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &sync_config,
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The valid key would be synced and the placeholder still fails validation
+    assert!(report.dry_run);
+    assert_eq!(report.synced, vec!["synctest-dry-ok".to_string()]);
+    assert!(report.skipped_oauth.is_empty());
+    assert!(matches!(
+        report.failed.get("synctest-dry-bad"),
+        Some(AuthSyncError::KeyValidation { .. })
+    ));
+    assert_eq!(report.total(), 2);
+
+    // THEN: The server was never contacted (verified again when the mock server drops)
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+/// **VALUE**: Verifies cancelling mid-sync keeps finished providers and cancels the rest.
+///
+/// **WHY THIS MATTERS**: Closing the app during a slow sync must stop promptly, without
+/// misreporting keys the server already accepted.
+///
+/// **BUG THIS CATCHES**: Would catch if cancellation doesn't interrupt a retry wait, if
+/// later providers are still contacted, or if already-synced providers are dropped from
+/// the report.
+#[tokio::test]
+async fn given_cancel_during_retry_wait_when_syncing_then_completed_kept_and_rest_cancelled() {
+    // GIVEN: A server that accepts the first provider, keeps failing the second, and must
+    // never see the third
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-cancel-a"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-cancel-b"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-cancel-c"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[
+        ("SYNCTEST_CANCEL_A_API_KEY", "abcdefghijklmnop1234"),
+        ("SYNCTEST_CANCEL_B_API_KEY", "abcdefghijklmnop1234"),
+        ("SYNCTEST_CANCEL_C_API_KEY", "abcdefghijklmnop1234"),
+    ]);
+    let config = models_config(vec![
+        provider("synctest-cancel-a"),
+        provider("synctest-cancel-b"),
+        provider("synctest-cancel-c"),
+    ]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // GIVEN: One provider at a time, with a long retry delay so the second provider is
+    // waiting when cancelled
+    let sync_config = SyncConfig {
+        concurrency: 1,
+        timeout: Duration::from_secs(30),
+        initial_delay: Duration::from_secs(10),
+        max_delay: Duration::from_secs(10),
+        ..fast_sync_config()
+    };
+    let cancel = CancellationToken::new();
+    let canceller = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        canceller.cancel();
+    });
+
+    // WHEN: Syncing all keys
+    let report = tokio::time::timeout(
+        Duration::from_secs(5),
+        sync_all_keys_from(&client, &env, &config, &sync_config, &cancel),
+    )
+    .await
+    .expect("Cancellation should interrupt the retry wait");
+
+    // THEN: The first provider stays synced; the others are cancelled
+    assert_eq!(report.synced, vec!["synctest-cancel-a".to_string()]);
+    for provider in ["synctest-cancel-b", "synctest-cancel-c"] {
+        assert!(
+            matches!(
+                report.failed.get(provider),
+                Some(AuthSyncError::Cancelled { .. })
+            ),
+            "{provider}: {:?}",
+            report.failed.get(provider)
+        );
+    }
+    assert_eq!(report.total(), 3);
+}
+
+/// **VALUE**: Verifies providers sync in parallel, never beyond the configured bound, and
+/// are reported in sorted order.
+///
+/// **WHY THIS MATTERS**: With many providers configured, a sequential sync delays startup
+/// by one round trip each; an unbounded one floods the server.
+///
+/// **BUG THIS CATCHES**: Would catch if `concurrency` is ignored (all at once or one at a
+/// time), or if the report lists providers in completion order.
+#[tokio::test]
+async fn given_concurrency_bound_when_syncing_many_providers_then_parallel_within_bound() {
+    // GIVEN: Six providers, each answered after a fixed delay
+    const DELAY: Duration = Duration::from_millis(300);
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200).set_delay(DELAY))
+        .expect(6)
+        .mount(&server)
+        .await;
+    let config = models_config(
+        (1..=6)
+            .rev()
+            .map(|i| provider(&format!("synctest-parallel-{i}")))
+            .collect(),
+    );
+    let vars: Vec<(&str, &str)> = config
+        .providers
+        .iter()
+        .map(|p| (p.api_key_env.as_str(), "abcdefghijklmnop1234"))
+        .collect();
+    let env = FakeEnv::new(&vars);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    let sync_config = SyncConfig {
+        concurrency: 2,
+        ..fast_sync_config()
+    };
+
+    // WHEN: Syncing all keys two at a time
+    let started = std::time::Instant::now();
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &sync_config,
+        &CancellationToken::new(),
+    )
+    .await;
+    let elapsed = started.elapsed();
+
+    // THEN: All providers are synced, in sorted order
+    let expected: Vec<String> = (1..=6).map(|i| format!("synctest-parallel-{i}")).collect();
+    assert_eq!(report.synced, expected);
+    assert!(report.is_success());
+
+    // THEN: Six requests two at a time take three rounds - more parallelism would be
+    // faster, none would take six
+    assert!(elapsed >= DELAY * 3, "Bound exceeded: took {elapsed:?}");
+    assert!(elapsed < DELAY * 6, "Not parallel: took {elapsed:?}");
+}
+
+/// **VALUE**: Verifies a key the server already holds is not sent again.
+///
+/// **WHY THIS MATTERS**: Sync runs on every launch and server switch; re-sending identical
+/// keys is needless traffic and log noise.
+///
+/// **BUG THIS CATCHES**: Would catch if the pre-check is skipped, or if unchanged providers
+/// are reported as synced instead of already current.
+#[tokio::test]
+async fn given_server_has_same_key_when_sync_all_keys_then_reported_already_current() {
+    // GIVEN: A server already holding the same key, which must not receive a PUT
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/auth/synctest-current"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "type": "api",
+            "key": "abcdefghijklmnop1234"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-current"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[("SYNCTEST_CURRENT_API_KEY", "abcdefghijklmnop1234")]);
+    let config = models_config(vec![provider("synctest-current")]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Syncing all keys
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &fast_sync_config(),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The provider is reported as already current, not synced
+    assert_eq!(report.already_current, vec!["synctest-current".to_string()]);
+    assert!(report.synced.is_empty());
+    assert!(report.is_success());
+}
+
+/// **VALUE**: Verifies a key that differs from the server's is sent.
+///
+/// **WHY THIS MATTERS**: Rotated keys must reach the server; the pre-check must only skip
+/// exact matches.
+///
+/// **BUG THIS CATCHES**: Would catch if any existing API key counts as current, e.g. by
+/// comparing auth types instead of key fingerprints.
+#[tokio::test]
+async fn given_server_has_different_key_when_sync_all_keys_then_key_sent() {
+    // GIVEN: A server holding an older key
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/auth/synctest-changed"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "type": "api",
+            "key": "old-key-0000000000000000"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/synctest-changed"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let env = FakeEnv::new(&[("SYNCTEST_CHANGED_API_KEY", "abcdefghijklmnop1234")]);
+    let config = models_config(vec![provider("synctest-changed")]);
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Syncing all keys
+    let report = sync_all_keys_from(
+        &client,
+        &env,
+        &config,
+        &fast_sync_config(),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // THEN: The new key is sent and reported as synced
+    assert_eq!(report.synced, vec!["synctest-changed".to_string()]);
+    assert!(report.already_current.is_empty());
+}
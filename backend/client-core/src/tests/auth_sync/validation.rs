@@ -0,0 +1,246 @@
+// Unit tests for API key validation
+// Tests provider-specific rules, including rules supplied through models.toml
+
+use crate::auth_sync::validate_api_key;
+use crate::auth_sync::validation::{KeyValidator, ValidationResult};
+use crate::config::models::ProviderConfig;
+use crate::error::KeyValidationFailure;
+use crate::tests::fixtures::provider;
+
+/// A custom provider whose keys look like `pat-` followed by 16-32 characters.
+fn pat_provider() -> ProviderConfig {
+    ProviderConfig {
+        key_prefix: Some("pat-".to_string()),
+        key_min_length: Some(20),
+        key_max_length: Some(36),
+        ..provider("acme")
+    }
+}
+
+/// **VALUE**: Verifies a key matching a custom provider's configured format is accepted.
+///
+/// **WHY THIS MATTERS**: Providers added to models.toml should validate against their own
+/// format, not only the permissive defaults.
+///
+/// **BUG THIS CATCHES**: Would catch if the config rules are ignored or misapplied (e.g.
+/// the prefix counted against the wrong bound).
+#[test]
+fn given_custom_prefix_rule_when_key_matches_then_valid() {
+    // GIVEN: A validator for a provider with a `pat-` prefix and a 20-36 length window
+    let validator = KeyValidator::from_config(&pat_provider());
+
+    // WHEN: Validating a well-formed key
+    let result = validator.validate("pat-a1B2c3D4e5F6g7H8");
+
+    // THEN: It is accepted
+    assert!(matches!(result, ValidationResult::Valid));
+}
+
+/// **VALUE**: Verifies a custom provider's prefix is enforced.
+///
+/// **WHY THIS MATTERS**: Pasting a key for the wrong provider should fail fast with a
+/// message naming the expected prefix.
+///
+/// **BUG THIS CATCHES**: Would catch if the configured prefix is dropped in favor of the
+/// unknown-provider default (no prefix).
+#[test]
+fn given_custom_prefix_rule_when_prefix_wrong_then_invalid_prefix() {
+    // GIVEN: A validator for a provider with a `pat-` prefix
+    let validator = KeyValidator::from_config(&pat_provider());
+
+    // WHEN: Validating a key with another provider's prefix
+    let result = validator.validate("sk-a1B2c3D4e5F6g7H8i9");
+
+    // THEN: The configured prefix is reported
+    match result {
+        ValidationResult::Invalid(KeyValidationFailure::InvalidPrefix { expected, actual }) => {
+            assert_eq!(expected, "pat-");
+            assert_eq!(actual, "sk-a");
+        }
+        other => panic!("Expected InvalidPrefix, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies both ends of a configured length window are enforced.
+///
+/// **WHY THIS MATTERS**: Truncated or doubled pastes are the most common key mistakes.
+///
+/// **BUG THIS CATCHES**: Would catch if only one configured bound is applied and the
+/// other falls back to the defaults (10-500).
+#[test]
+fn given_custom_length_window_when_key_outside_then_rejected() {
+    // GIVEN: A validator for a provider with a 20-36 length window
+    let validator = KeyValidator::from_config(&pat_provider());
+
+    // WHEN: Validating keys just outside the window
+    let short = validator.validate("pat-a1B2c3D4e5F6g7H");
+    let long = validator.validate("pat-a1B2c3D4e5F6g7H8i9J0k1L2m3N4o5P6q");
+
+    // THEN: Each is rejected with the configured bound
+    assert!(matches!(
+        short,
+        ValidationResult::Invalid(KeyValidationFailure::TooShort {
+            min: 20,
+            actual: 19
+        })
+    ));
+    assert!(matches!(
+        long,
+        ValidationResult::Invalid(KeyValidationFailure::TooLong {
+            max: 36,
+            actual: 37
+        })
+    ));
+}
+
+/// **VALUE**: Verifies well-known rules still apply when the config doesn't override them.
+///
+/// **WHY THIS MATTERS**: Existing models.toml files don't set the new fields; OpenAI keys
+/// must still require `sk-`.
+///
+/// **BUG THIS CATCHES**: Would catch if unset config fields replace the built-in rules
+/// with the unknown-provider defaults.
+#[test]
+fn given_well_known_provider_without_overrides_when_validating_then_builtin_rules_apply() {
+    // GIVEN: An OpenAI provider with no key rules in config
+    let validator = KeyValidator::from_config(&provider("openai"));
+
+    // WHEN: Validating a key without the `sk-` prefix
+    let result = validator.validate("pk-a1B2c3D4e5F6g7H8i9J0");
+
+    // THEN: The built-in prefix is enforced
+    assert!(matches!(
+        result,
+        ValidationResult::Invalid(KeyValidationFailure::InvalidPrefix { ref expected, .. })
+            if expected == "sk-"
+    ));
+}
+
+/// **VALUE**: Verifies placeholder words inside random key material don't reject a real key.
+///
+/// **WHY THIS MATTERS**: Base64 keys regularly contain fragments like "test" or "fake";
+/// rejecting them blocks users with valid keys and no workaround.
+///
+/// **BUG THIS CATCHES**: Would catch a return to unanchored substring matching.
+#[test]
+fn given_real_key_containing_placeholder_fragment_when_validating_then_valid() {
+    // GIVEN: A validator with the built-in placeholder patterns
+    let validator = KeyValidator::from_config(&provider("openai"));
+
+    // WHEN: Validating a real-looking key containing "est", "test" and "fake" mid-token
+    let result = validator.validate("sk-proj-Q3testZ9k2LmN8pR4fakevW1xY6");
+
+    // THEN: It is accepted
+    assert!(matches!(result, ValidationResult::Valid));
+}
+
+/// **VALUE**: Verifies template text is still rejected as a placeholder.
+///
+/// **WHY THIS MATTERS**: Values copied from .env.example must be caught before they are
+/// pushed to the server.
+///
+/// **BUG THIS CATCHES**: Would catch if anchoring is too strict to match multi-word
+/// patterns at the start of the key.
+#[test]
+fn given_template_key_when_validating_then_placeholder_detected() {
+    // GIVEN: A validator with the built-in placeholder patterns
+    let validator = KeyValidator::from_config(&provider("acme"));
+
+    // WHEN: Validating template text
+    let result = validator.validate("your-api-key-here");
+
+    // THEN: The matched pattern is reported
+    match result {
+        ValidationResult::Invalid(KeyValidationFailure::PlaceholderDetected { pattern }) => {
+            assert_eq!(pattern, "your-api-key");
+        }
+        other => panic!("Expected PlaceholderDetected, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies providers can replace or disable the placeholder patterns.
+///
+/// **WHY THIS MATTERS**: Some providers issue keys containing words like "example"; they
+/// need an escape hatch in models.toml.
+///
+/// **BUG THIS CATCHES**: Would catch if configured patterns are merged with (rather than
+/// replace) the built-in list, or if an empty list is treated as "use defaults".
+#[test]
+fn given_configured_placeholder_patterns_when_validating_then_they_replace_builtins() {
+    // GIVEN: One provider opted out and one with its own pattern
+    let opted_out = KeyValidator::from_config(&ProviderConfig {
+        key_placeholder_patterns: Some(Vec::new()),
+        ..provider("acme")
+    });
+    let custom = KeyValidator::from_config(&ProviderConfig {
+        key_placeholder_patterns: Some(vec!["CHANGEME".to_string()]),
+        ..provider("acme")
+    });
+
+    // WHEN: Validating a key containing a built-in pattern, and one with the custom pattern
+    let builtin_word = "acme-example-7f3k2m9q";
+    let custom_word = "acme-changeme-7f3k2m9q";
+
+    // THEN: Only the configured patterns apply
+    assert!(matches!(
+        opted_out.validate(builtin_word),
+        ValidationResult::Valid
+    ));
+    assert!(matches!(
+        custom.validate(builtin_word),
+        ValidationResult::Valid
+    ));
+    assert!(matches!(
+        custom.validate(custom_word),
+        ValidationResult::Invalid(KeyValidationFailure::PlaceholderDetected { .. })
+    ));
+}
+
+/// **VALUE**: Verifies validate_api_key accepts a well-formed key and reports every failure
+/// reason the validator can produce.
+///
+/// **WHY THIS MATTERS**: The UI shows these reasons inline as the user types a key; a
+/// reason that never surfaces (or surfaces as the wrong variant) gives misleading feedback.
+///
+/// **BUG THIS CATCHES**: Would catch the convenience entry point drifting from
+/// KeyValidator, e.g. building generic rules instead of the provider's configured ones.
+#[test]
+fn given_each_failure_when_validate_api_key_then_matching_reason() {
+    // GIVEN: A custom provider expecting `pat-` keys of 20-36 characters
+    let provider = pat_provider();
+
+    // WHEN/THEN: Each kind of bad key yields its own reason
+    assert_eq!(validate_api_key(&provider, "pat-abcdefghijklmnop"), Ok(()));
+    assert_eq!(
+        validate_api_key(&provider, "   "),
+        Err(KeyValidationFailure::Empty)
+    );
+    assert_eq!(
+        validate_api_key(&provider, "pat-abc"),
+        Err(KeyValidationFailure::TooShort { min: 20, actual: 7 })
+    );
+    assert_eq!(
+        validate_api_key(&provider, &format!("pat-{}", "a1".repeat(20))),
+        Err(KeyValidationFailure::TooLong {
+            max: 36,
+            actual: 44
+        })
+    );
+    assert_eq!(
+        validate_api_key(&provider, "key-abcdefghijklmnop"),
+        Err(KeyValidationFailure::InvalidPrefix {
+            expected: "pat-".to_string(),
+            actual: "key-".to_string()
+        })
+    );
+    assert_eq!(
+        validate_api_key(&provider, "pat-your-api-key-here"),
+        Err(KeyValidationFailure::PlaceholderDetected {
+            pattern: "your-api-key".to_string()
+        })
+    );
+    assert_eq!(
+        validate_api_key(&provider, "pat-abcdefgh ijklmnop"),
+        Err(KeyValidationFailure::InvalidCharacters)
+    );
+}
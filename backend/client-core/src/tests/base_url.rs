@@ -0,0 +1,90 @@
+// Unit tests for base_url module
+// Tests base URL construction and trailing-slash normalization before endpoint joins
+
+use crate::base_url::{make_base_url, normalize_base_url};
+
+/// **VALUE**: Verifies the base URL reflects the bind address.
+///
+/// **WHY THIS MATTERS**: A server bound to a LAN address doesn't answer on 127.0.0.1, so
+/// a hardcoded loopback URL would fail every health check.
+///
+/// **BUG THIS CATCHES**: Would catch if the URL ignores the host, uses an unconnectable
+/// wildcard address, or leaves IPv6 addresses unbracketed.
+#[test]
+fn given_hostnames_when_make_base_url_then_reflects_connectable_host() {
+    // GIVEN / WHEN / THEN: Each bind address maps to the URL clients connect to
+    assert_eq!(make_base_url("127.0.0.1", 4096), "http://127.0.0.1:4096");
+    assert_eq!(
+        make_base_url("192.168.1.20", 4096),
+        "http://192.168.1.20:4096"
+    );
+    assert_eq!(
+        make_base_url("devbox.local", 4096),
+        "http://devbox.local:4096"
+    );
+    assert_eq!(make_base_url("0.0.0.0", 4096), "http://127.0.0.1:4096");
+    assert_eq!(make_base_url("::", 4096), "http://127.0.0.1:4096");
+    assert_eq!(make_base_url("::1", 4096), "http://[::1]:4096");
+}
+
+/// **VALUE**: Verifies endpoints join onto a base with or without a trailing slash alike.
+///
+/// **WHY THIS MATTERS**: `Url::join` replaces the last path segment of a base without a
+/// trailing slash, so a server behind a path prefix (e.g. a reverse proxy at `/opencode`)
+/// would be sent requests for `/session` instead of `/opencode/session`.
+///
+/// **BUG THIS CATCHES**: Would catch if normalization doesn't append the slash, appends a
+/// second one, or keeps a query that leaks into joined URLs.
+#[test]
+fn given_base_urls_with_and_without_trailing_slash_when_joined_then_same_endpoint_urls() {
+    let cases = [
+        ("http://127.0.0.1:4096", "http://127.0.0.1:4096/"),
+        ("http://127.0.0.1:4096/", "http://127.0.0.1:4096/"),
+        (
+            "https://devbox.local/opencode",
+            "https://devbox.local/opencode/",
+        ),
+        (
+            "https://devbox.local/opencode/",
+            "https://devbox.local/opencode/",
+        ),
+        (" http://[::1]:4096?x=1#top ", "http://[::1]:4096/"),
+    ];
+
+    for (input, expected_base) in cases {
+        // WHEN: Normalizing and joining endpoints
+        let base = normalize_base_url(input).unwrap();
+        let session = base.join("session").unwrap();
+        let abort = base.join("session/ses_1/abort").unwrap();
+
+        // THEN: The base ends with one slash and endpoints extend its path
+        assert_eq!(base.as_str(), expected_base, "input: {input}");
+        assert_eq!(session.as_str(), format!("{expected_base}session"));
+        assert_eq!(
+            abort.as_str(),
+            format!("{expected_base}session/ses_1/abort")
+        );
+    }
+}
+
+/// **VALUE**: Verifies URLs built by `make_base_url` normalize cleanly and relative input fails.
+///
+/// **WHY THIS MATTERS**: Discovery stores `make_base_url` strings and the OpenCode client
+/// normalizes them; the two must agree, and a bare `host:port` must be an error rather than
+/// a URL with a bogus scheme.
+///
+/// **BUG THIS CATCHES**: Would catch if built URLs fail to parse (e.g. unbracketed IPv6) or
+/// if relative input is silently accepted.
+#[test]
+fn given_built_and_relative_urls_when_normalized_then_built_parse_and_relative_fails() {
+    // GIVEN / WHEN / THEN: Built URLs normalize to the root path
+    for host in ["127.0.0.1", "::1", "0.0.0.0", "devbox.local"] {
+        let base = normalize_base_url(&make_base_url(host, 4096)).unwrap();
+        assert_eq!(base.path(), "/");
+        assert_eq!(base.port(), Some(4096));
+    }
+
+    // GIVEN / WHEN / THEN: Relative input is rejected
+    assert!(normalize_base_url("/session").is_err());
+    assert!(normalize_base_url("").is_err());
+}
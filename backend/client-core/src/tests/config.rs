@@ -0,0 +1,764 @@
+// Unit tests for config loading and schema migrations
+// Tests the migration chain with hypothetical steps and load() against a temp directory
+
+use crate::config::migration::{Migration, migrate, migrate_with};
+use crate::config::models::{CuratedModel, search_dirs};
+use crate::config::{
+    AppConfig, CONFIG_VERSION, ChatDensity, FontSizePreset, MAX_BASE_FONT_POINTS,
+    MIN_BASE_FONT_POINTS, ModelRef, ModelsConfig, UiPreferences,
+};
+use crate::error::config::ConfigError;
+use crate::tests::fixtures::provider;
+
+use std::fmt::Debug;
+use std::path::Path;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+/// Hypothetical v0 -> v1 step: v0 stored the font size as `ui.font_points`.
+fn rename_font_points(mut value: Value) -> Result<Value, ConfigError> {
+    if let Some(ui) = value.get_mut("ui").and_then(Value::as_object_mut)
+        && let Some(points) = ui.remove("font_points")
+    {
+        ui.insert("base_font_points".to_string(), points);
+    }
+    Ok(value)
+}
+
+/// **VALUE**: Verifies a migration step runs and the version is bumped afterwards.
+///
+/// **WHY THIS MATTERS**: Renamed or restructured settings must carry over on upgrade;
+/// otherwise users silently lose them.
+///
+/// **BUG THIS CATCHES**: Would catch if steps are skipped, run from the wrong index, or
+/// if `version` isn't updated (so the migration would rerun on every load).
+#[test]
+fn given_v0_config_when_migrated_then_step_applies_and_version_bumps() {
+    // GIVEN: A v0 config and a chain with one hypothetical step
+    let v0 = json!({ "version": 0, "ui": { "font_points": 18.0 } });
+    let chain: &[Migration] = &[rename_font_points];
+
+    // WHEN: Migrating to v1
+    let migrated = migrate_with(v0, 0, 1, chain).unwrap();
+
+    // THEN: The field was renamed and the version is current
+    assert_eq!(migrated["version"], 1);
+    assert_eq!(migrated["ui"]["base_font_points"], 18.0);
+    assert!(migrated["ui"].get("font_points").is_none());
+}
+
+/// **VALUE**: Verifies a current-version config passes through unchanged.
+///
+/// **WHY THIS MATTERS**: Loading must not rewrite settings that are already current.
+///
+/// **BUG THIS CATCHES**: Would catch if the chain runs an extra step at the current version.
+#[test]
+fn given_current_config_when_migrated_then_unchanged() {
+    // GIVEN: A current-version config
+    let current = json!({
+        "version": CONFIG_VERSION,
+        "ui": { "base_font_points": 16.0, "font_size": "large" }
+    });
+
+    // WHEN: Migrating with the shipped chain
+    let migrated = migrate(current.clone(), CONFIG_VERSION).unwrap();
+
+    // THEN: Nothing changed
+    assert_eq!(migrated, current);
+}
+
+/// **VALUE**: Verifies a config from a newer app version fails with a clear version error.
+///
+/// **WHY THIS MATTERS**: Downgrading the app must not misread (and later overwrite) a
+/// newer config; the user needs to know why it wasn't loaded.
+///
+/// **BUG THIS CATCHES**: Would catch if future versions are passed through unmigrated or
+/// reported as a generic parse error.
+#[test]
+fn given_future_version_when_loading_then_returns_unsupported_version() {
+    // GIVEN: A config.json written by a future version
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        json!({ "version": 99 }).to_string(),
+    )
+    .unwrap();
+
+    // WHEN: Loading it
+    let result = AppConfig::load(dir.path());
+
+    // THEN: The version error names both versions
+    match result {
+        Err(
+            ref err @ ConfigError::UnsupportedVersion {
+                version, supported, ..
+            },
+        ) => {
+            assert_eq!(version, 99);
+            assert_eq!(supported, CONFIG_VERSION);
+            assert!(err.to_string().contains("newer than supported"));
+        }
+        other => panic!("Expected UnsupportedVersion, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies `load()` migrates a pre-versioning (v0) config instead of rejecting it.
+///
+/// **WHY THIS MATTERS**: `validate()` rejects version 0, so without migration these configs
+/// fell back to defaults and lost the user's settings.
+///
+/// **BUG THIS CATCHES**: Would catch if `load()` deserializes before migrating.
+#[test]
+fn given_v0_config_file_when_loading_then_migrated_with_settings_kept() {
+    // GIVEN: A v0 config.json with a custom font size
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        json!({ "version": 0, "ui": { "base_font_points": 20.0 } }).to_string(),
+    )
+    .unwrap();
+
+    // WHEN: Loading it
+    let config = AppConfig::load(dir.path()).unwrap();
+
+    // THEN: It is current and keeps the setting
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.ui.base_font_points, 20.0);
+}
+
+/// **VALUE**: Verifies `load()` migrates a config without `version` from v1.
+///
+/// **WHY THIS MATTERS**: Files written before versioning have no `version` and PascalCase
+/// enum values. Treating them as current skipped the v1 → v2 step, failed to parse, and
+/// replaced every setting with defaults.
+///
+/// **BUG THIS CATCHES**: Would catch a missing `version` defaulting to `CONFIG_VERSION`.
+#[test]
+fn given_versionless_pascal_case_config_when_loading_then_migrated_with_settings_kept() {
+    // GIVEN: A config.json without `version`, with PascalCase enum values
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        json!({
+            "ui": { "base_font_points": 18.0, "font_size": "Large", "chat_density": "Compact" }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    // WHEN: Loading it
+    let config = AppConfig::load(dir.path()).unwrap();
+
+    // THEN: It is current and keeps every setting
+    assert_eq!(config.version, CONFIG_VERSION);
+    assert_eq!(config.ui.base_font_points, 18.0);
+    assert_eq!(config.ui.font_size, FontSizePreset::Large);
+    assert_eq!(config.ui.chat_density, ChatDensity::Compact);
+}
+
+/// **VALUE**: Verifies unknown keys survive a load → save round-trip.
+///
+/// **WHY THIS MATTERS**: Running an older app (or hand-editing the file) must not strip
+/// settings a newer version wrote.
+///
+/// **BUG THIS CATCHES**: Would catch if unknown keys are dropped on deserialize, or if a
+/// known key is captured as "extra" and written twice.
+#[test]
+fn given_unknown_key_when_loaded_and_saved_then_key_preserved() {
+    // GIVEN: A config.json with an unknown top-level section
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        json!({
+            "version": 1,
+            "ui": { "base_font_points": 16.0 },
+            "experimental": { "foo": true }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    // WHEN: Loading and saving it back
+    let config = AppConfig::load(dir.path()).unwrap();
+    config.save(dir.path()).unwrap();
+
+    // THEN: The unknown key is still on disk and known fields were parsed normally
+    let saved: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("config.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved["experimental"]["foo"], true);
+    assert_eq!(saved["ui"]["base_font_points"], 16.0);
+    assert!(!config.extra.contains_key("ui"));
+    assert_eq!(AppConfig::load(dir.path()).unwrap(), config);
+}
+
+/// **VALUE**: Verifies save → reload yields the same config and leaves no temp file behind.
+///
+/// **WHY THIS MATTERS**: Save goes through temp file + fsync + rename; a slip in that
+/// sequence (e.g. writing the temp file but renaming the wrong path) loses the user's settings.
+///
+/// **BUG THIS CATCHES**: Would catch a partial write, a missing rename, or a stale
+/// `config.json.tmp` that accumulates in the config directory.
+#[test]
+fn given_saved_config_when_reloaded_then_identical_and_temp_file_removed() {
+    // GIVEN: A non-default config saved over an existing file
+    let dir = TempDir::new().unwrap();
+    AppConfig::default().save(dir.path()).unwrap();
+    let mut config = AppConfig::default();
+    config.ui.base_font_points = 18.0;
+    config.server.directory_override = Some("/work/project".to_string());
+
+    // WHEN: Saving and reloading
+    config.save(dir.path()).unwrap();
+    let reloaded = AppConfig::load(dir.path()).unwrap();
+
+    // THEN: The content matches and only config.json remains
+    assert_eq!(reloaded, config);
+    assert!(!dir.path().join("config.json.tmp").exists());
+}
+
+/// **VALUE**: Verifies a dotted key path reads a single value or a subtree.
+///
+/// **WHY THIS MATTERS**: The frontend reads individual settings (e.g. the font size)
+/// without fetching and parsing the whole config.
+///
+/// **BUG THIS CATCHES**: Would catch if paths address the Rust field names inconsistently
+/// with the serialized config, or if object paths don't return the subtree.
+#[test]
+fn given_key_paths_when_get_value_then_returns_value_or_subtree() {
+    // GIVEN: The default config
+    let config = AppConfig::default();
+
+    // WHEN: Reading a leaf and a section
+    let points = config.get_value("ui.base_font_points").unwrap();
+    let server = config.get_value("server").unwrap();
+
+    // THEN: The leaf is the value and the section is the whole object
+    assert_eq!(points, json!(14.0));
+    assert_eq!(server["auto_start"], json!(true));
+}
+
+/// **VALUE**: Verifies unknown or malformed key paths are rejected.
+///
+/// **WHY THIS MATTERS**: A typo must be reported, not read as "unset" or written as a
+/// new unknown key.
+///
+/// **BUG THIS CATCHES**: Would catch if missing paths return null or if `with_value`
+/// inserts new keys.
+#[test]
+fn given_unknown_key_paths_when_get_or_set_value_then_unknown_key_error() {
+    // GIVEN: The default config
+    let config = AppConfig::default();
+
+    for key_path in ["ui.missing", "nope", "", "ui.", "ui.base_font_points.x"] {
+        // WHEN: Reading and writing the path
+        let read = config.get_value(key_path);
+        let written = config.with_value(key_path, json!(1));
+
+        // THEN: Both fail with UnknownKey
+        assert!(
+            matches!(read, Err(ConfigError::UnknownKey { .. })),
+            "read '{key_path}': {read:?}"
+        );
+        assert!(
+            matches!(written, Err(ConfigError::UnknownKey { .. })),
+            "write '{key_path}': {written:?}"
+        );
+    }
+}
+
+/// **VALUE**: Verifies `with_value` patches one value and validates the result.
+///
+/// **WHY THIS MATTERS**: Single-value writes bypass the full-config update path, so they
+/// must enforce the same rules (font size range, value types).
+///
+/// **BUG THIS CATCHES**: Would catch if out-of-range or wrongly typed values are accepted,
+/// or if patching changes other settings.
+#[test]
+fn given_values_when_with_value_then_valid_applied_and_invalid_rejected() {
+    // GIVEN: The default config
+    let config = AppConfig::default();
+
+    // WHEN: Setting a valid, an out-of-range, and a wrongly typed font size
+    let valid = config.with_value("ui.base_font_points", json!(18.0));
+    let out_of_range = config.with_value("ui.base_font_points", json!(200.0));
+    let wrong_type = config.with_value("ui.base_font_points", json!("big"));
+
+    // THEN: Only the valid value is applied, and nothing else changes
+    let valid = valid.unwrap();
+    assert_eq!(valid.ui.base_font_points, 18.0);
+    assert_eq!(valid.server, config.server);
+    assert!(matches!(
+        out_of_range,
+        Err(ConfigError::ValidationError { .. })
+    ));
+    assert!(matches!(
+        wrong_type,
+        Err(ConfigError::ValidationError { .. })
+    ));
+}
+
+/// **VALUE**: Verifies font sizes inside the allowed range, bounds included, parse as-is.
+///
+/// **BUG THIS CATCHES**: Would catch the parse-time check using exclusive bounds or
+/// clamping values it should keep.
+#[test]
+fn given_font_size_in_range_when_parsing_ui_then_value_kept() {
+    for points in [8.0, 14.5, 72.0] {
+        // GIVEN: UI preferences with an in-range font size
+        let json = json!({ "base_font_points": points }).to_string();
+
+        // WHEN: Parsing them
+        let ui: UiPreferences = serde_json::from_str(&json).unwrap();
+
+        // THEN: The value is kept unchanged
+        assert_eq!(ui.base_font_points, points);
+    }
+}
+
+/// **VALUE**: Verifies out-of-range font sizes fail while parsing, naming the field.
+///
+/// **WHY THIS MATTERS**: A hand-edited config.json with a huge font size should be
+/// reported as a parse error pointing at the bad setting, not load and fail later.
+///
+/// **BUG THIS CATCHES**: Would catch the range check only running in `validate`, or an
+/// error message that doesn't say which value was wrong.
+#[test]
+fn given_font_size_out_of_range_when_parsing_then_error_names_field() {
+    for points in [MIN_BASE_FONT_POINTS - 0.1, MAX_BASE_FONT_POINTS + 1.0] {
+        // GIVEN: A config with a font size below the minimum or above the maximum
+        let json = json!({ "ui": { "base_font_points": points } }).to_string();
+
+        // WHEN: Parsing it
+        let error = serde_json::from_str::<AppConfig>(&json).unwrap_err();
+
+        // THEN: Parsing fails with the field and the allowed range in the message
+        let message = error.to_string();
+        assert!(message.contains("base_font_points"), "{message}");
+        let range = format!("{MIN_BASE_FONT_POINTS:.1} and {MAX_BASE_FONT_POINTS:.1}");
+        assert!(message.contains(&range), "{message}");
+    }
+}
+
+/// Assert each value serializes to its JSON form and that form deserializes back to it.
+fn assert_round_trips<T>(cases: &[(T, &str)])
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    for (value, expected) in cases {
+        let written = serde_json::to_value(value).unwrap();
+        assert_eq!(written, json!(expected), "{value:?} written as");
+        let read: T = serde_json::from_value(written).unwrap();
+        assert_eq!(&read, value, "{expected} read back as");
+    }
+}
+
+/// **VALUE**: Verifies the UI enums are written in snake_case and read back unchanged.
+///
+/// **WHY THIS MATTERS**: The frontend stores and compares these strings as-is; a casing
+/// change on either side silently resets the user's choice to the default.
+///
+/// **BUG THIS CATCHES**: Would catch a missing or different `rename_all`, or a variant
+/// that serializes but no longer deserializes.
+#[test]
+fn given_ui_enums_when_round_tripped_then_snake_case_written_and_read() {
+    assert_round_trips(&[
+        (FontSizePreset::Small, "small"),
+        (FontSizePreset::Standard, "standard"),
+        (FontSizePreset::Large, "large"),
+    ]);
+    assert_round_trips(&[
+        (ChatDensity::Compact, "compact"),
+        (ChatDensity::Normal, "normal"),
+        (ChatDensity::Comfortable, "comfortable"),
+    ]);
+}
+
+/// **VALUE**: Verifies a v1 config.json with PascalCase enum values still loads, and is
+/// saved back in snake_case.
+///
+/// **WHY THIS MATTERS**: Every config written before version 2 uses `"Large"`-style
+/// values; without the migration they'd fail to parse and fall back to defaults.
+///
+/// **BUG THIS CATCHES**: Would catch the v1 -> v2 step missing from the chain, touching
+/// the wrong keys, or the old casing being written again on save.
+#[test]
+fn given_v1_config_with_pascal_case_enums_when_loaded_and_saved_then_snake_case_on_disk() {
+    // GIVEN: A v1 config.json using the old casing
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        json!({
+            "version": 1,
+            "ui": { "font_size": "Large", "chat_density": "Comfortable" }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    // WHEN: Loading and saving it back
+    let config = AppConfig::load(dir.path()).unwrap();
+    config.save(dir.path()).unwrap();
+
+    // THEN: The choices survive and are written in the new casing
+    assert_eq!(config.ui.font_size, FontSizePreset::Large);
+    assert_eq!(config.ui.chat_density, ChatDensity::Comfortable);
+    let saved: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.path().join("config.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved["version"], CONFIG_VERSION);
+    assert_eq!(saved["ui"]["font_size"], "large");
+    assert_eq!(saved["ui"]["chat_density"], "comfortable");
+}
+
+/// **VALUE**: Verifies a v1 config already using snake_case values migrates unchanged.
+///
+/// **BUG THIS CATCHES**: Would catch the migration mangling values that don't need it
+/// (e.g. a config saved by a v2 frontend that still sent `version: 1`).
+#[test]
+fn given_v1_config_with_snake_case_enums_when_migrated_then_values_kept() {
+    // GIVEN: A v1 config with new-style values
+    let v1 = json!({ "version": 1, "ui": { "font_size": "small", "chat_density": "compact" } });
+
+    // WHEN: Migrating it
+    let migrated = migrate(v1, 1).unwrap();
+
+    // THEN: Only the version changed
+    assert_eq!(
+        migrated,
+        json!({ "version": 2, "ui": { "font_size": "small", "chat_density": "compact" } })
+    );
+}
+
+/// **VALUE**: Verifies resetting one section restores its defaults and nothing else.
+///
+/// **WHY THIS MATTERS**: Users recover from a broken setting without losing the rest of
+/// their config (e.g. resetting UI must keep their server URL).
+///
+/// **BUG THIS CATCHES**: Would catch if a section reset falls through to a full reset.
+#[test]
+fn given_customized_config_when_reset_ui_section_then_only_ui_restored() {
+    // GIVEN: A config with customized server and UI settings
+    let mut config = AppConfig::default();
+    config.server.last_opencode_url = Some("http://localhost:4096".to_string());
+    config.ui.base_font_points = 20.0;
+    let server = config.server.clone();
+
+    // WHEN: Resetting the UI section
+    config.reset_section("ui").unwrap();
+
+    // THEN: UI is back to defaults and server settings are untouched
+    assert_eq!(config.ui, AppConfig::default().ui);
+    assert_eq!(config.server, server);
+}
+
+/// **VALUE**: Verifies an empty section resets the whole config and unknown names error.
+///
+/// **WHY THIS MATTERS**: A typo in the section name must not silently reset nothing
+/// (or everything).
+///
+/// **BUG THIS CATCHES**: Would catch if unknown sections are ignored or partially applied.
+#[test]
+fn given_section_names_when_reset_section_then_whole_reset_or_unknown_key() {
+    // GIVEN: A customized config
+    let mut config = AppConfig::default();
+    config.ui.base_font_points = 20.0;
+    config.audio.push_to_talk_key = "F13".to_string();
+
+    // WHEN: Resetting an unknown section
+    let result = config.reset_section("network");
+
+    // THEN: It errors and leaves the config unchanged
+    assert!(matches!(result, Err(ConfigError::UnknownKey { .. })));
+    assert_eq!(config.ui.base_font_points, 20.0);
+
+    // WHEN: Resetting with an empty section
+    config.reset_section("").unwrap();
+
+    // THEN: The whole config is back to defaults
+    assert_eq!(config, AppConfig::default());
+}
+
+/// **VALUE**: Verifies strict validation accepts an existing Whisper model file.
+///
+/// **WHY THIS MATTERS**: Strict mode must not reject a correctly configured model.
+///
+/// **BUG THIS CATCHES**: Would catch if the existence check is inverted or too strict.
+#[test]
+fn given_existing_model_file_when_validate_strict_then_ok() {
+    // GIVEN: A config pointing at an existing model file
+    let dir = TempDir::new().unwrap();
+    let model = dir.path().join("ggml-base.bin");
+    std::fs::write(&model, b"model").unwrap();
+    let mut config = AppConfig::default();
+    config.audio.whisper_model_path = Some(model.to_string_lossy().into_owned());
+
+    // WHEN / THEN: Strict validation passes
+    config.validate_strict().unwrap();
+}
+
+/// **VALUE**: Verifies strict validation rejects missing files and directories, while
+/// regular validation stays lenient.
+///
+/// **WHY THIS MATTERS**: A stale model path should be reported when settings are saved,
+/// not at transcription time; but a model downloaded later must not block startup.
+///
+/// **BUG THIS CATCHES**: Would catch if directories pass as model files, if missing files
+/// are accepted in strict mode, or if `validate()` starts touching the filesystem.
+#[test]
+fn given_missing_or_directory_model_path_when_validate_strict_then_validation_error() {
+    // GIVEN: Configs pointing at a missing file and at a directory
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing.bin");
+
+    for (path, expected) in [
+        (missing.as_path(), "not accessible"),
+        (dir.path(), "not a file"),
+    ] {
+        let mut config = AppConfig::default();
+        config.audio.whisper_model_path = Some(path.to_string_lossy().into_owned());
+
+        // WHEN: Validating normally and strictly
+        let lenient = config.validate();
+        let strict = config.validate_strict();
+
+        // THEN: Only strict validation fails, with a descriptive reason
+        assert!(lenient.is_ok());
+        match strict {
+            Err(ConfigError::ValidationError { reason, .. }) => {
+                assert!(reason.contains(expected), "unexpected reason: {reason}")
+            }
+            other => panic!("Expected ValidationError, got {other:?}"),
+        }
+    }
+}
+
+/// **VALUE**: Verifies `provider/model` strings split on the first slash and round-trip.
+///
+/// **WHY THIS MATTERS**: Model IDs like OpenRouter's `anthropic/claude-3.5-sonnet` contain
+/// slashes themselves; only the first one separates the provider.
+///
+/// **BUG THIS CATCHES**: Would catch if parsing splits on the last slash, or if `Display`
+/// doesn't reproduce the config form.
+#[test]
+fn given_well_formed_model_strings_when_parsed_then_split_on_first_slash() {
+    // GIVEN/WHEN: A plain and a nested model string, and the default config
+    let plain: ModelRef = "openai/gpt-4".parse().unwrap();
+    let nested: ModelRef = "openrouter/anthropic/claude-3.5-sonnet".parse().unwrap();
+    let default = ModelsConfig::default().default_model_ref().unwrap();
+
+    // THEN: The provider is everything before the first slash, and Display round-trips
+    assert_eq!(plain.provider_id, "openai");
+    assert_eq!(plain.model_id, "gpt-4");
+    assert_eq!(nested.provider_id, "openrouter");
+    assert_eq!(nested.model_id, "anthropic/claude-3.5-sonnet");
+    assert_eq!(nested.to_string(), "openrouter/anthropic/claude-3.5-sonnet");
+    assert_eq!(default, plain);
+}
+
+/// **VALUE**: Verifies malformed model strings are rejected.
+///
+/// **WHY THIS MATTERS**: An empty provider or model ID would be sent to OpenCode as-is and
+/// fail far from the config value that caused it.
+///
+/// **BUG THIS CATCHES**: Would catch if a missing slash or an empty half is accepted.
+#[test]
+fn given_missing_slash_or_empty_half_when_parsed_then_validation_error() {
+    for input in ["gpt-4", "", "/gpt-4", "openai/", "/"] {
+        // WHEN: Parsing the malformed string
+        let result = input.parse::<ModelRef>();
+
+        // THEN: Validation fails
+        assert!(
+            matches!(result, Err(ConfigError::ValidationError { .. })),
+            "Expected validation error for '{input}', got {result:?}"
+        );
+    }
+}
+
+/// Models config with one `openai` provider and curated models for `providers`.
+fn models_config_with_curated(providers: &[&str], strict: bool) -> ModelsConfig {
+    let mut config = ModelsConfig {
+        providers: vec![provider("openai")],
+        ..Default::default()
+    };
+    config.models.strict_curated_providers = strict;
+    config.models.curated = providers
+        .iter()
+        .map(|provider| {
+            CuratedModel::new(
+                format!("{provider} model"),
+                *provider,
+                format!("{provider}-model"),
+            )
+        })
+        .collect();
+    config
+}
+
+/// **VALUE**: Verifies curated models for defined providers pass even strict validation.
+///
+/// **WHY THIS MATTERS**: The bundled models.toml only curates models for its own providers;
+/// turning on strict mode must not reject it.
+///
+/// **BUG THIS CATCHES**: Would catch if provider names are compared against display names
+/// or model IDs instead of `ProviderConfig::name`.
+#[test]
+fn given_curated_models_for_defined_providers_when_validated_then_ok() {
+    // GIVEN: Curated models that all reference the configured provider
+    let config = models_config_with_curated(&["openai", "openai"], true);
+
+    // WHEN / THEN: Validation passes
+    config.validate().unwrap();
+}
+
+/// **VALUE**: Verifies orphaned curated models fail strict validation and are listed.
+///
+/// **WHY THIS MATTERS**: A curated model for an unconfigured provider fails later at key
+/// sync with a confusing error; strict mode reports it up front, while the default lets
+/// users curate ahead of configuring the provider.
+///
+/// **BUG THIS CATCHES**: Would catch if the check ignores `strict_curated_providers`, or if
+/// the error doesn't name the orphaned models.
+#[test]
+fn given_orphaned_curated_models_when_validated_then_strict_mode_rejects() {
+    // GIVEN: Curated models for two providers that aren't configured
+    let lenient = models_config_with_curated(&["openai", "anthropic", "mistral"], false);
+    let strict = models_config_with_curated(&["openai", "anthropic", "mistral"], true);
+
+    // WHEN: Validating in both modes
+    let lenient_result = lenient.validate();
+    let strict_result = strict.validate();
+
+    // THEN: Only strict mode fails, naming every orphaned model
+    assert!(lenient_result.is_ok());
+    match strict_result {
+        Err(ConfigError::ValidationError { reason, .. }) => {
+            assert!(reason.contains("anthropic/anthropic-model"), "{reason}");
+            assert!(reason.contains("mistral/mistral-model"), "{reason}");
+            assert!(!reason.contains("openai"), "{reason}");
+        }
+        other => panic!("Expected validation error, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies the `[agents]` section accepts any agent by default and rejects a
+/// default agent outside a configured allowlist.
+///
+/// **BUG THIS CATCHES**: Would catch a config whose every default-agent message fails with
+/// `UnknownAgent` being accepted at load time.
+#[test]
+fn given_agents_section_when_validated_then_default_must_be_known() {
+    // GIVEN: A config without an [agents] section
+    let config: ModelsConfig = toml::from_str("").unwrap();
+
+    // THEN: "build" is the default and user-defined agents are accepted
+    assert_eq!(config.agents.default_agent, "build");
+    assert!(config.agents.is_known("plan"));
+    assert!(config.agents.is_known("my-reviewer"));
+    assert!(config.validate().is_ok());
+
+    // WHEN: The default agent isn't in the allowlist
+    let config: ModelsConfig =
+        toml::from_str("[agents]\ndefault_agent = \"review\"\nknown = [\"build\"]\n").unwrap();
+
+    // THEN: Validation fails
+    assert!(config.validate().is_err());
+
+    // WHEN: The allowlist is empty
+    let config: ModelsConfig =
+        toml::from_str("[agents]\ndefault_agent = \"review\"\nknown = []\n").unwrap();
+
+    // THEN: Any agent is accepted
+    assert!(config.agents.is_known("anything"));
+    assert!(config.validate().is_ok());
+}
+
+/// Write a models.toml into `dir` whose default model identifies where it came from.
+fn write_models_toml(dir: &Path, default_model: &str) {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(
+        dir.join("models.toml"),
+        format!("[models]\ndefault_model = \"{default_model}\"\n"),
+    )
+    .unwrap();
+}
+
+/// **VALUE**: Verifies the override directory is searched before both resource-dir
+/// locations.
+///
+/// **WHY THIS MATTERS**: `OPENCODE_MODELS_CONFIG` exists so developers can try a models.toml
+/// without rebuilding the bundle; it is useless if the bundled copy still wins.
+///
+/// **BUG THIS CATCHES**: Would catch the override being appended after the resource dir,
+/// or dropped from the search list.
+#[test]
+fn given_override_dir_when_loading_then_override_wins_over_resource_dir() {
+    // GIVEN: A bundled models.toml and one in an override directory
+    let resources = TempDir::new().unwrap();
+    let custom = TempDir::new().unwrap();
+    write_models_toml(&resources.path().join("config"), "bundled/model");
+    write_models_toml(custom.path(), "custom/model");
+
+    // WHEN: Loading with and without $OPENCODE_MODELS_CONFIG set
+    let overridden =
+        ModelsConfig::load_with_override(resources.path(), Some(custom.path().into())).unwrap();
+    let bundled = ModelsConfig::load_with_override(resources.path(), None).unwrap();
+
+    // THEN: The override is used only when given
+    assert_eq!(overridden.models.default_model, "custom/model");
+    assert_eq!(bundled.models.default_model, "bundled/model");
+}
+
+/// **VALUE**: Verifies directories are searched in order, skipping ones without a usable file.
+///
+/// **WHY THIS MATTERS**: A typo in a hand-edited models.toml should fall through to the next
+/// location rather than leave the app with no providers.
+///
+/// **BUG THIS CATCHES**: Would catch the search stopping at the first directory, at the
+/// first invalid file, or taking the last match instead of the first.
+#[test]
+fn given_several_dirs_when_load_from_dirs_then_first_valid_file_wins() {
+    // GIVEN: An empty dir, a dir with invalid TOML, and two valid dirs
+    let root = TempDir::new().unwrap();
+    let empty = root.path().join("empty");
+    let broken = root.path().join("broken");
+    let first = root.path().join("first");
+    let second = root.path().join("second");
+    std::fs::create_dir_all(&empty).unwrap();
+    std::fs::create_dir_all(&broken).unwrap();
+    std::fs::write(broken.join("models.toml"), "[models\n").unwrap();
+    write_models_toml(&first, "first/model");
+    write_models_toml(&second, "second/model");
+
+    // WHEN: Searching them in that order
+    let config = ModelsConfig::load_from_dirs(&[empty, broken, first, second]).unwrap();
+
+    // THEN: The first valid file is loaded
+    assert_eq!(config.models.default_model, "first/model");
+}
+
+/// **VALUE**: Verifies loading falls back to defaults when no directory has a models.toml.
+///
+/// **BUG THIS CATCHES**: Would catch a missing file, including a dangling override
+/// directory, turning into a startup error.
+#[test]
+fn given_no_models_toml_anywhere_when_loading_then_defaults() {
+    // GIVEN: An empty resource dir and an override pointing nowhere
+    let resources = TempDir::new().unwrap();
+    let missing = resources.path().join("does-not-exist");
+
+    // WHEN: Loading
+    let config =
+        ModelsConfig::load_from_dirs(&search_dirs(resources.path(), Some(missing))).unwrap();
+
+    // THEN: The defaults are used
+    assert_eq!(
+        config.models.default_model,
+        ModelsConfig::default().models.default_model
+    );
+    assert!(config.providers.is_empty());
+}
@@ -0,0 +1,150 @@
+// Unit tests for the discovery cache
+// Scans are counted by the cache itself; tests use their own cache unless they exercise
+// the global one that stop/spawn invalidate
+
+use crate::discovery::cache::{DISCOVERY_CACHE, DiscoveryCache, DiscoveryKey};
+use crate::discovery::process::{discover, discover_with, stop_pid};
+use crate::error::discovery::DiscoveryError;
+use crate::proto::IpcServerInfo;
+
+use common::ErrorLocation;
+
+use std::panic::Location;
+use std::time::Duration;
+
+fn server(port: u32) -> IpcServerInfo {
+    IpcServerInfo {
+        pid: 100,
+        port,
+        base_url: format!("http://127.0.0.1:{port}"),
+        name: "opencode".to_string(),
+        command: "opencode serve".to_string(),
+        owned: false,
+        discovered_at: 0,
+        last_health_ok: 0,
+    }
+}
+
+/// **VALUE**: Verifies two rapid discover() calls share one process scan.
+///
+/// **WHY THIS MATTERS**: The UI polls discovery; a full process refresh per poll burns CPU
+/// for a result that can't have changed in the meantime.
+///
+/// **BUG THIS CATCHES**: Would catch discover() bypassing the cache, or the cache storing
+/// results it never serves.
+#[test]
+fn given_ttl_when_discover_called_twice_then_scans_once() {
+    // GIVEN: A cache whose TTL outlasts the test
+    let cache = DiscoveryCache::new(Duration::from_secs(60));
+
+    // WHEN: Discovering twice in a row
+    let first = discover_with(&cache).unwrap();
+    let second = discover_with(&cache).unwrap();
+
+    // THEN: Only the first call scanned, and both saw the same result
+    assert_eq!(cache.scans(), 1);
+    assert_eq!(first, second);
+}
+
+/// **VALUE**: Verifies results expire after the TTL and are looked up again.
+///
+/// **BUG THIS CATCHES**: Would catch entries that never expire, which would hide servers
+/// started after the first scan forever.
+#[test]
+fn given_expired_entry_when_get_or_scan_then_rescans() {
+    // GIVEN: A cache with a short TTL holding one result
+    let cache = DiscoveryCache::new(Duration::from_millis(50));
+    let scan = || Ok(vec![server(4096)]);
+    cache.get_or_scan(DiscoveryKey::Scan, scan).unwrap();
+
+    // WHEN: Asking again within and after the TTL
+    cache.get_or_scan(DiscoveryKey::Scan, scan).unwrap();
+    std::thread::sleep(Duration::from_millis(80));
+    cache.get_or_scan(DiscoveryKey::Scan, scan).unwrap();
+
+    // THEN: Only the expired lookup scanned again
+    assert_eq!(cache.scans(), 2);
+}
+
+/// **VALUE**: Verifies a full scan and a port lookup are cached separately.
+///
+/// **WHY THIS MATTERS**: With a port override set, discovery must only report the server
+/// on that port, not whatever the last full scan found.
+///
+/// **BUG THIS CATCHES**: Would catch a single cache slot shared by every kind of lookup.
+#[test]
+fn given_scan_cached_when_port_lookup_then_scans_separately() {
+    // GIVEN: A cached full scan
+    let cache = DiscoveryCache::new(Duration::from_secs(60));
+    cache
+        .get_or_scan(DiscoveryKey::Scan, || Ok(vec![server(4096), server(4097)]))
+        .unwrap();
+
+    // WHEN: Looking up one port
+    let on_port = cache
+        .get_or_scan(DiscoveryKey::Port(4097), || Ok(vec![server(4097)]))
+        .unwrap();
+
+    // THEN: The port lookup ran its own scan
+    assert_eq!(on_port, vec![server(4097)]);
+    assert_eq!(cache.scans(), 2);
+}
+
+/// **VALUE**: Verifies failed scans aren't cached and a zero TTL disables caching.
+///
+/// **BUG THIS CATCHES**: Would catch a transient netstat failure being replayed for the
+/// whole TTL, or `Duration::ZERO` still serving cached results.
+#[test]
+fn given_failed_scan_or_zero_ttl_when_get_or_scan_then_scans_every_time() {
+    // GIVEN: A scan that fails once
+    let cache = DiscoveryCache::new(Duration::from_secs(60));
+    let failed = cache.get_or_scan(DiscoveryKey::Scan, || {
+        Err(DiscoveryError::SystemQuery {
+            message: "process table unavailable".to_string(),
+            location: ErrorLocation::from(Location::caller()),
+        })
+    });
+
+    // WHEN: Asking again, then switching caching off
+    cache
+        .get_or_scan(DiscoveryKey::Scan, || Ok(vec![]))
+        .unwrap();
+    cache.set_ttl(Duration::ZERO);
+    cache
+        .get_or_scan(DiscoveryKey::Scan, || Ok(vec![]))
+        .unwrap();
+    cache
+        .get_or_scan(DiscoveryKey::Scan, || Ok(vec![]))
+        .unwrap();
+
+    // THEN: Every call scanned
+    assert!(failed.is_err());
+    assert_eq!(cache.scans(), 4);
+}
+
+/// **VALUE**: Verifies stopping a process drops the global discovery cache.
+///
+/// **WHY THIS MATTERS**: After the user stops a server, the next poll must not report it
+/// as still running for up to a TTL.
+///
+/// **BUG THIS CATCHES**: Would catch stop_pid() forgetting to invalidate.
+#[cfg(unix)]
+#[test]
+fn given_cached_discovery_when_stop_pid_then_next_discover_rescans() {
+    // GIVEN: A warm global cache and a process to stop (reaped by a thread once killed)
+    discover().unwrap();
+    let mut child = std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .expect("sleep should be available");
+    let pid = child.id();
+    std::thread::spawn(move || child.wait());
+    let scans_before = DISCOVERY_CACHE.scans();
+
+    // WHEN: Stopping the process and discovering again
+    assert!(stop_pid(pid));
+    discover().unwrap();
+
+    // THEN: Discovery scanned again instead of serving the cached result
+    assert!(DISCOVERY_CACHE.scans() > scans_before);
+}
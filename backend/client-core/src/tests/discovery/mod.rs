@@ -1,2 +1,3 @@
+mod cache;
 mod process;
 mod spawn;
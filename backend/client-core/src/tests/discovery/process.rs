@@ -1,7 +1,62 @@
 // Unit tests for process module private functions
 // Integration tests for public API are in integration_tests/discovery/process.rs
 
-use crate::discovery::process::{format_command, with_process};
+use crate::OPENCODE_BINARY;
+use crate::discovery::process::{
+    CandidateProcess, ListeningSocket, check_health, format_command, health_clients_built,
+    is_opencode_process_name, match_servers, opencode_pid_on_port, server_on_port,
+    stop_server_info, with_process,
+};
+use crate::error::discovery::DiscoveryError;
+use crate::proto::IpcServerInfo;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn server_info(pid: u32, owned: bool) -> IpcServerInfo {
+    IpcServerInfo {
+        pid,
+        port: 4096,
+        base_url: "http://127.0.0.1:4096".to_string(),
+        name: OPENCODE_BINARY.to_string(),
+        command: format!("{OPENCODE_BINARY} serve"),
+        owned,
+        discovered_at: 0,
+        last_health_ok: 0,
+    }
+}
+
+/// A harmless long-running process to aim stop attempts at; the caller kills it.
+#[cfg(unix)]
+fn sleeping_child() -> std::process::Child {
+    std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .expect("sleep should be available")
+}
+
+fn candidate(pid: u32) -> CandidateProcess {
+    CandidateProcess {
+        pid,
+        command: "opencode serve".to_string(),
+    }
+}
+
+fn socket(port: u16, pids: &[u32]) -> ListeningSocket {
+    socket_on("127.0.0.1", port, pids)
+}
+
+fn socket_on(address: &str, port: u16, pids: &[u32]) -> ListeningSocket {
+    ListeningSocket {
+        address: address.parse().unwrap(),
+        port,
+        pids: pids.to_vec(),
+    }
+}
+
+fn ports(servers: &[IpcServerInfo]) -> Vec<(u32, u32)> {
+    servers.iter().map(|s| (s.pid, s.port)).collect()
+}
 
 /// **VALUE**: Tests the private `format_command()` helper's ability to handle edge cases.
 ///
@@ -69,3 +124,377 @@ fn given_valid_pid_when_with_process_called_then_executes_closure() {
         "Should execute closure with correct process"
     );
 }
+
+/// **VALUE**: Verifies that `stop_server_info()` refuses servers we didn't spawn.
+///
+/// **WHY THIS MATTERS**: A discovered server belongs to the user (e.g. `opencode serve` in a
+/// terminal). Stopping it from the app would kill work we have no right to touch.
+///
+/// **BUG THIS CATCHES**: Would catch if the ownership check is dropped and `stop_pid()` is
+/// called for any server in state.
+#[cfg(unix)]
+#[test]
+fn given_unowned_server_when_stop_server_info_called_then_refuses_with_not_owned() {
+    // GIVEN: A live process that we did not spawn
+    let mut child = sleeping_child();
+    let info = server_info(child.id(), false);
+
+    // WHEN: Attempting to stop it
+    let result = stop_server_info(&info);
+
+    // THEN: Should refuse before touching the process
+    let still_running = child.try_wait().unwrap().is_none();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(
+        matches!(result, Err(DiscoveryError::NotOwned { .. })),
+        "Expected NotOwned, got {result:?}"
+    );
+    assert!(still_running, "the process was signalled");
+}
+
+/// **VALUE**: Verifies that `stop_server_info()` refuses a PID whose process isn't OpenCode.
+///
+/// **WHY THIS MATTERS**: PIDs are reused. If our server died and the OS handed its PID to
+/// another program, stopping the "server" would kill an unrelated process.
+///
+/// **BUG THIS CATCHES**: Would catch if the process-name check is removed or inverted.
+#[cfg(unix)]
+#[test]
+fn given_owned_server_with_foreign_process_when_stop_server_info_called_then_refuses_with_mismatch()
+{
+    // GIVEN: An owned server whose PID now belongs to some other program
+    let mut child = sleeping_child();
+    let info = server_info(child.id(), true);
+
+    // WHEN: Attempting to stop it
+    let result = stop_server_info(&info);
+
+    // THEN: Should refuse because the process isn't OpenCode, leaving it running
+    let still_running = child.try_wait().unwrap().is_none();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(
+        matches!(result, Err(DiscoveryError::ProcessMismatch { .. })),
+        "Expected ProcessMismatch, got {result:?}"
+    );
+    assert!(still_running, "the process was signalled");
+}
+
+/// **VALUE**: Verifies that `stop_server_info()` treats a vanished process as nothing to stop.
+///
+/// **WHY THIS MATTERS**: The server may exit on its own between discovery and stop; that
+/// isn't an identity failure and shouldn't be reported as one.
+///
+/// **BUG THIS CATCHES**: Would catch if a missing process is reported as a mismatch error.
+#[test]
+fn given_owned_server_that_exited_when_stop_server_info_called_then_returns_false() {
+    // GIVEN: An owned server whose process no longer exists
+    let info = server_info(u32::MAX, true);
+
+    // WHEN: Attempting to stop it
+    let result = stop_server_info(&info);
+
+    // THEN: Should report that nothing was stopped
+    assert!(
+        matches!(result, Ok(false)),
+        "Expected Ok(false), got {result:?}"
+    );
+}
+
+/// **VALUE**: Verifies which process names count as OpenCode.
+///
+/// **WHY THIS MATTERS**: The binary is `opencode.exe` on Windows and may be a versioned name
+/// elsewhere; rejecting those would make owned servers impossible to stop.
+///
+/// **BUG THIS CATCHES**: Would catch an exact-match-only check, or one that accepts anything.
+#[test]
+fn given_process_names_when_is_opencode_process_name_called_then_matches_only_opencode() {
+    // GIVEN / WHEN / THEN: OpenCode names match, unrelated names don't
+    assert!(is_opencode_process_name("opencode"));
+    assert!(is_opencode_process_name("opencode.exe"));
+    assert!(is_opencode_process_name("OpenCode"));
+    assert!(!is_opencode_process_name("bash"));
+    assert!(!is_opencode_process_name("node"));
+}
+
+/// **VALUE**: Verifies every OpenCode process is reported with its own port.
+///
+/// **WHY THIS MATTERS**: Users running a server per project need to see all of them to
+/// pick one; stopping at the first match hides the rest.
+///
+/// **BUG THIS CATCHES**: Would catch if matching stops after the first server, attaches a
+/// port to the wrong process, or includes sockets owned by unrelated processes.
+#[test]
+fn given_several_candidates_when_match_servers_then_each_reported_distinctly() {
+    // GIVEN: Two OpenCode processes (listed out of order) and an unrelated listener
+    let candidates = [candidate(200), candidate(100)];
+    let sockets = [
+        socket(4200, &[200]),
+        socket(8080, &[999]),
+        socket(4100, &[100]),
+    ];
+
+    // WHEN: Matching them
+    let servers = match_servers(&candidates, &sockets);
+
+    // THEN: One server per process, ordered by PID, with consistent URLs
+    assert_eq!(ports(&servers), vec![(100, 4100), (200, 4200)]);
+    assert_eq!(servers[0].base_url, "http://127.0.0.1:4100");
+    assert_eq!(servers[1].base_url, "http://127.0.0.1:4200");
+    assert!(servers.iter().all(|s| !s.owned && s.discovered_at > 0));
+}
+
+/// **VALUE**: Verifies a process listening on several ports yields one server per port,
+/// and a port seen twice yields one server.
+///
+/// **WHY THIS MATTERS**: netstat reports IPv4 and IPv6 sockets separately; listing the
+/// same server twice would show duplicate entries in the picker.
+///
+/// **BUG THIS CATCHES**: Would catch if only the first port of a process is kept, or if
+/// duplicate sockets aren't collapsed.
+#[test]
+fn given_process_with_multiple_ports_when_match_servers_then_one_server_per_port() {
+    // GIVEN: One process on two ports, one of them reported for IPv4 and IPv6
+    let candidates = [candidate(100)];
+    let sockets = [
+        socket(4097, &[100]),
+        socket(4096, &[100]),
+        socket_on("::", 4096, &[100]),
+    ];
+
+    // WHEN: Matching them
+    let servers = match_servers(&candidates, &sockets);
+
+    // THEN: Each port appears once, in order
+    assert_eq!(ports(&servers), vec![(100, 4096), (100, 4097)]);
+}
+
+/// **VALUE**: Verifies candidates without a listening socket are skipped.
+///
+/// **WHY THIS MATTERS**: A server still starting up (or a stray `opencode serve --help`)
+/// has no port to connect to.
+///
+/// **BUG THIS CATCHES**: Would catch if such processes are reported with port 0.
+#[test]
+fn given_candidate_without_socket_when_match_servers_then_not_reported() {
+    // GIVEN: A candidate that listens on nothing
+    let candidates = [candidate(100)];
+    let sockets = [socket(8080, &[999])];
+
+    // WHEN: Matching them
+    let servers = match_servers(&candidates, &sockets);
+
+    // THEN: Nothing is reported
+    assert!(servers.is_empty());
+}
+
+/// **VALUE**: Verifies a server listening only on IPv6 gets a bracketed IPv6 loopback URL.
+///
+/// **WHY THIS MATTERS**: Discovery finds such a server by PID, but health checks and the
+/// client would probe `127.0.0.1`, where nothing listens, and report it as down.
+///
+/// **BUG THIS CATCHES**: Would catch base URLs built from a fixed IPv4 host, or IPv6
+/// addresses left unbracketed (`http://::1:4096` isn't a valid URL).
+#[test]
+fn given_ipv6_only_listeners_when_match_servers_then_ipv6_loopback_base_url() {
+    // GIVEN: One server bound to ::1 and one bound to the IPv6 wildcard
+    let candidates = [candidate(100), candidate(200)];
+    let sockets = [
+        socket_on("::1", 4096, &[100]),
+        socket_on("::", 4097, &[200]),
+    ];
+
+    // WHEN: Matching them
+    let servers = match_servers(&candidates, &sockets);
+
+    // THEN: Both are reached over [::1]
+    assert_eq!(ports(&servers), vec![(100, 4096), (200, 4097)]);
+    assert_eq!(servers[0].base_url, "http://[::1]:4096");
+    assert_eq!(servers[1].base_url, "http://[::1]:4097");
+}
+
+/// **VALUE**: Verifies a port bound for both families is reached over IPv4, whichever
+/// socket is reported first, and that an IPv4 wildcard bind uses IPv4 loopback.
+///
+/// **WHY THIS MATTERS**: IPv4 loopback is what OpenCode binds by default and what every
+/// earlier release connected to; dual-stack servers shouldn't change address.
+///
+/// **BUG THIS CATCHES**: Would catch the base URL depending on netstat's socket order.
+#[test]
+fn given_dual_stack_listener_when_match_servers_then_ipv4_base_url() {
+    // GIVEN: The IPv6 socket reported before the IPv4 wildcard one
+    let candidates = [candidate(100)];
+    let sockets = [
+        socket_on("::", 4096, &[100]),
+        socket_on("0.0.0.0", 4096, &[100]),
+    ];
+
+    // WHEN: Matching them
+    let servers = match_servers(&candidates, &sockets);
+
+    // THEN: One server, reached over 127.0.0.1
+    assert_eq!(ports(&servers), vec![(100, 4096)]);
+    assert_eq!(servers[0].base_url, "http://127.0.0.1:4096");
+}
+
+/// **VALUE**: Verifies IPv4-mapped IPv6 addresses are reported as plain IPv4.
+///
+/// **BUG THIS CATCHES**: Would catch URLs like `http://[::ffff:127.0.0.1]:4096`, which some
+/// HTTP stacks refuse to connect to.
+#[test]
+fn given_ipv4_mapped_address_when_base_url_then_plain_ipv4() {
+    // GIVEN / WHEN
+    let base_url = socket_on("::ffff:127.0.0.1", 4096, &[100]).base_url();
+
+    // THEN
+    assert_eq!(base_url, "http://127.0.0.1:4096");
+}
+
+/// Fake process table for `opencode_pid_on_port()`: PID -> (name, command line).
+fn describe_from(processes: &[(u32, &str, &str)]) -> impl Fn(u32) -> Option<(String, String)> {
+    let processes: Vec<(u32, String, String)> = processes
+        .iter()
+        .map(|&(pid, name, command)| (pid, name.to_string(), command.to_string()))
+        .collect();
+    move |pid| {
+        processes
+            .iter()
+            .find(|(p, _, _)| *p == pid)
+            .map(|(_, name, command)| (name.clone(), command.clone()))
+    }
+}
+
+/// **VALUE**: Verifies the PID on a port is returned once it is confirmed to be OpenCode,
+/// whether it runs as its own binary or under bun.
+///
+/// **WHY THIS MATTERS**: This is how a server orphaned by a crashed session is found and
+/// stopped on the next launch.
+///
+/// **BUG THIS CATCHES**: Would catch the PID of another port being picked, or bun-hosted
+/// servers being refused because only the process name is checked.
+#[test]
+fn given_opencode_on_port_when_opencode_pid_on_port_then_returns_pid() {
+    // GIVEN: OpenCode on 4096 (IPv4 and IPv6) and a bun-hosted OpenCode on 4097
+    let sockets = [
+        socket(4096, &[100]),
+        socket_on("::", 4096, &[100]),
+        socket(4097, &[200]),
+    ];
+    let describe = describe_from(&[
+        (100, "opencode", "opencode serve --port 4096"),
+        (200, "bun", "bun run opencode serve --port 4097"),
+    ]);
+
+    // WHEN: Looking up each port
+    let on_4096 = opencode_pid_on_port(4096, &sockets, &describe).unwrap();
+    let on_4097 = opencode_pid_on_port(4097, &sockets, &describe).unwrap();
+
+    // THEN: Each port's own PID is confirmed
+    assert_eq!(on_4096, Some(100));
+    assert_eq!(on_4097, Some(200));
+}
+
+/// **VALUE**: Verifies a port held by something other than OpenCode is refused.
+///
+/// **WHY THIS MATTERS**: After a crash, another program may have taken the port; killing
+/// whatever listens there would take down the user's database or dev server.
+///
+/// **BUG THIS CATCHES**: Would catch the identity check being skipped, or only the process
+/// name being checked (a `node` process running something else).
+#[test]
+fn given_foreign_process_on_port_when_opencode_pid_on_port_then_process_mismatch() {
+    // GIVEN: postgres on one port and an unrelated node app on another
+    let sockets = [socket(5432, &[300]), socket(3000, &[400])];
+    let describe = describe_from(&[
+        (300, "postgres", "postgres -D /var/lib/postgres"),
+        (400, "node", "node server.js"),
+    ]);
+
+    // WHEN / THEN: Both are refused
+    for port in [5432, 3000] {
+        let result = opencode_pid_on_port(port, &sockets, &describe);
+        assert!(
+            matches!(result, Err(DiscoveryError::ProcessMismatch { .. })),
+            "port {port}: {result:?}"
+        );
+    }
+}
+
+/// **VALUE**: Verifies an unused port, or one whose process has exited, finds nothing.
+///
+/// **BUG THIS CATCHES**: Would catch an error being reported when there's simply nothing
+/// to clean up.
+#[test]
+fn given_nothing_on_port_when_opencode_pid_on_port_then_none() {
+    // GIVEN: A socket on another port, and one whose process has exited
+    let sockets = [socket(4096, &[100]), socket(4097, &[999])];
+    let describe = describe_from(&[(100, "opencode", "opencode serve")]);
+
+    // WHEN / THEN
+    assert_eq!(
+        opencode_pid_on_port(4098, &sockets, &describe).unwrap(),
+        None
+    );
+    assert_eq!(
+        opencode_pid_on_port(4097, &sockets, &describe).unwrap(),
+        None
+    );
+}
+
+/// **VALUE**: Verifies repeated health checks all succeed over one shared client.
+///
+/// **WHY THIS MATTERS**: Spawn polls health every few hundred milliseconds and the UI polls
+/// too; a client per check rebuilds the connection pool (and TLS setup) every time.
+///
+/// **BUG THIS CATCHES**: Would catch `check_health` constructing a client per call, or a
+/// shared client that stops working after the first request.
+#[tokio::test]
+async fn given_many_health_checks_when_run_sequentially_then_client_reused() {
+    // GIVEN: A healthy server on loopback, checked once so its client exists
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(50)
+        .mount(&server)
+        .await;
+    assert!(check_health(&server.uri()).await);
+    let built = health_clients_built();
+
+    // WHEN: Checking it many more times
+    for attempt in 1..50 {
+        assert!(check_health(&server.uri()).await, "check {attempt} failed");
+    }
+
+    // THEN: No further clients were built (one per kind at most)
+    assert_eq!(health_clients_built(), built);
+    assert!(built <= 2, "built {built} health clients");
+}
+
+/// **VALUE**: Verifies a server found on the override port is reported as not owned.
+///
+/// **WHY THIS MATTERS**: `owned` is what lets `stop_server` kill a process. A server the
+/// user started on that port must never be marked as ours.
+///
+/// **BUG THIS CATCHES**: Would catch override-port discovery claiming ownership, which
+/// made `discover_server` followed by `stop_server` kill a user's `opencode serve`.
+#[test]
+fn given_process_on_override_port_when_server_on_port_then_not_owned() {
+    // GIVEN: A process listening on the override port (IPv6 and IPv4) and one elsewhere
+    let sockets = [
+        socket_on("::", 4096, &[100]),
+        socket(4096, &[100]),
+        socket(8080, &[200]),
+    ];
+    let describe = |pid: u32| Some(("opencode".to_string(), format!("serve #{pid}")));
+
+    // WHEN: Looking up the server on that port
+    let server = server_on_port(4096, &sockets, describe).expect("server on port");
+
+    // THEN: It is reported through the IPv4 socket, and not as ours
+    assert_eq!(server.pid, 100);
+    assert_eq!(server.base_url, "http://127.0.0.1:4096");
+    assert!(!server.owned);
+    assert!(server_on_port(9999, &sockets, describe).is_none());
+}
@@ -1,8 +1,13 @@
 // Unit tests for spawn module private functions
 // Integration tests for public API are in integration_tests/discovery/spawn.rs
 
-use crate::OPENCODE_BINARY;
-use crate::discovery::spawn::{build_spawn_command, get_url_regex};
+use crate::discovery::process::with_process;
+use crate::discovery::spawn::{
+    OwnedChildren, SpawnOptions, build_spawn_command, get_url_regex, is_loopback_host,
+    parse_server_url,
+};
+use crate::error::spawn::SpawnError;
+use crate::{OPENCODE_BINARY, OPENCODE_SERVER_HOSTNAME};
 
 /// **VALUE**: Verifies that `build_spawn_command()` constructs commands with the correct binary name.
 ///
@@ -17,7 +22,7 @@ fn given_port_arg_when_build_spawn_command_called_then_sets_correct_binary() {
     let port = "4096";
 
     // WHEN: Building the spawn command
-    let cmd = build_spawn_command(port);
+    let cmd = build_spawn_command(port, OPENCODE_SERVER_HOSTNAME);
 
     // THEN: Should use the correct binary name
     let program = cmd.as_std().get_program();
@@ -106,3 +111,156 @@ fn given_various_port_numbers_when_regex_applied_then_extracts_correctly() {
         );
     }
 }
+
+/// Spawn a stand-in "server" that prints `noise_lines` lines and then its URL on `host`.
+#[cfg(unix)]
+fn fake_server(noise_lines: usize, host: &str) -> tokio::process::Child {
+    let script = format!(
+        "i=0; while [ $i -lt {noise_lines} ]; do echo \"loading plugin $i\"; i=$((i+1)); done; \
+         echo 'opencode server listening on http://{host}:4321'"
+    );
+    tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("sh should be available")
+}
+
+/// **VALUE**: Verifies a larger line budget finds a URL printed after many lines of output.
+///
+/// **WHY THIS MATTERS**: Verbose OpenCode builds log plugin loading before the URL; with a
+/// fixed 100-line budget those spawns fail with "No server URL found".
+///
+/// **BUG THIS CATCHES**: Would catch if `max_output_lines` isn't threaded into the parser.
+#[cfg(unix)]
+#[tokio::test]
+async fn given_late_url_when_parse_server_url_with_larger_budget_then_finds_url() {
+    // GIVEN: A server that prints 150 lines before its URL
+    let child = fake_server(150, OPENCODE_SERVER_HOSTNAME);
+
+    // WHEN: Parsing with a 200-line budget
+    let result = parse_server_url(child, OPENCODE_SERVER_HOSTNAME, 200).await;
+
+    // THEN: The URL is found
+    let (_, base_url, port) = result.expect("URL should be found within the budget");
+    assert_eq!(base_url, "http://127.0.0.1:4321");
+    assert_eq!(port, 4321);
+}
+
+/// **VALUE**: Verifies the default line budget still gives up on a URL printed too late.
+///
+/// **WHY THIS MATTERS**: The budget keeps a server that never prints its URL from being
+/// read forever; the error must name the budget so users know what to raise.
+///
+/// **BUG THIS CATCHES**: Would catch if the budget is ignored or the error hides it.
+#[cfg(unix)]
+#[tokio::test]
+async fn given_late_url_when_parse_server_url_with_default_budget_then_parse_error() {
+    // GIVEN: A server that prints 150 lines before its URL
+    let child = fake_server(150, OPENCODE_SERVER_HOSTNAME);
+
+    // WHEN: Parsing with the default budget
+    let options = SpawnOptions::default();
+    let result = parse_server_url(child, &options.hostname, options.max_output_lines).await;
+
+    // THEN: Parsing fails and reports the budget
+    match result {
+        Err(SpawnError::Parse { message, .. }) => assert!(message.contains("100 lines")),
+        other => panic!(
+            "Expected parse error, got {:?}",
+            other.map(|(_, url, _)| url)
+        ),
+    }
+}
+
+/// **VALUE**: Verifies tracked spawned servers are stopped and reaped by the shutdown path.
+///
+/// **WHY THIS MATTERS**: Servers we start must not outlive the app; without the handle they
+/// could only be killed by PID and were left running when the app quit.
+///
+/// **BUG THIS CATCHES**: Would catch if handles are dropped instead of tracked, if
+/// `stop_owned_servers()` misses one, or if a killed child is left as a zombie.
+#[cfg(unix)]
+#[tokio::test]
+async fn given_tracked_child_when_stop_owned_servers_then_process_stopped() {
+    // GIVEN: A long-running child tracked in a registry of its own (the global one may
+    // hold other tests' children)
+    let owned = OwnedChildren::new();
+    let child = tokio::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .expect("sleep should be available");
+    let pid = child.id().expect("running child has a PID");
+    owned.track(pid, child);
+    assert_eq!(owned.pids(), vec![pid]);
+
+    // WHEN: Running the shutdown path
+    let stopped = owned.stop_all().await;
+
+    // THEN: The child was stopped, untracked, and no longer exists
+    assert_eq!(stopped, 1);
+    assert!(owned.pids().is_empty());
+    assert!(with_process(pid, |_| ()).is_none());
+}
+
+/// **VALUE**: Verifies a custom hostname is passed to `opencode serve --hostname`.
+///
+/// **WHY THIS MATTERS**: Container and multi-host setups need the server bound somewhere
+/// other than loopback; if the flag keeps the default, the server is unreachable.
+///
+/// **BUG THIS CATCHES**: Would catch if the hostname is ignored in favor of the constant.
+#[test]
+fn given_custom_hostname_when_build_spawn_command_then_passes_hostname_flag() {
+    // GIVEN: A custom bind address
+    let hostname = "0.0.0.0";
+
+    // WHEN: Building the spawn command
+    let cmd = build_spawn_command("4096", hostname);
+
+    // THEN: The hostname follows the --hostname flag
+    let args: Vec<_> = cmd
+        .as_std()
+        .get_args()
+        .map(|a| a.to_string_lossy().to_string())
+        .collect();
+    assert_eq!(args, ["serve", "--port", "4096", "--hostname", "0.0.0.0"]);
+}
+
+/// **VALUE**: Verifies which bind addresses count as loopback.
+///
+/// **WHY THIS MATTERS**: A non-loopback bind exposes the server to the network and must
+/// be warned about; a false "loopback" would silence that warning.
+///
+/// **BUG THIS CATCHES**: Would catch if wildcard or LAN addresses are treated as local.
+#[test]
+fn given_hostnames_when_is_loopback_host_then_only_local_addresses_match() {
+    // GIVEN / WHEN / THEN: Local addresses match, exposed ones don't
+    assert!(is_loopback_host("127.0.0.1"));
+    assert!(is_loopback_host("localhost"));
+    assert!(is_loopback_host("::1"));
+    assert!(!is_loopback_host("0.0.0.0"));
+    assert!(!is_loopback_host("192.168.1.20"));
+    assert!(!is_loopback_host("devbox.local"));
+}
+
+/// **VALUE**: Verifies the parsed server URL uses the requested hostname.
+///
+/// **WHY THIS MATTERS**: The resulting `ServerInfo.base_url` is what every later request
+/// uses; it must point where the server was bound.
+///
+/// **BUG THIS CATCHES**: Would catch if parsing falls back to the loopback base URL.
+#[cfg(unix)]
+#[tokio::test]
+async fn given_custom_hostname_when_parse_server_url_then_base_url_uses_it() {
+    // GIVEN: A server bound to a LAN address
+    let child = fake_server(0, "192.168.1.20");
+
+    // WHEN: Parsing its output
+    let result = parse_server_url(child, "192.168.1.20", 10).await;
+
+    // THEN: The base URL points at that address
+    let (_, base_url, _) = result.expect("URL should be found");
+    assert_eq!(base_url, "http://192.168.1.20:4321");
+}
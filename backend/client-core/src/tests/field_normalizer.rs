@@ -1,8 +1,13 @@
 // Unit tests for field_normalizer module
 // Tests key transformations, round-trip safety, and JSON recursion
 
-use crate::field_normalizer::{denormalize_json, denormalize_key, normalize_json, normalize_key};
-use serde_json::json;
+use crate::field_normalizer::{
+    denormalize_json, denormalize_key, normalize_json, normalize_json_with, normalize_key,
+    normalize_reader, normalize_slice,
+};
+use serde_json::{Map, Value, json};
+use std::borrow::Cow;
+use std::collections::HashMap;
 
 // ============================================
 // UNIT TESTS: Individual Key Transformations
@@ -298,3 +303,328 @@ fn given_realistic_opencode_json_when_round_trip_then_returns_original() {
     // Should match original exactly
     assert_eq!(denormalized, opencode_json);
 }
+
+// ============================================
+// UNIT TESTS: Runtime Mappings
+// ============================================
+
+/// **VALUE**: Verifies runtime mappings apply at every nesting level, including arrays.
+///
+/// **WHY THIS MATTERS**: New OpenCode fields (e.g. `workspaceID`) can be handled by
+/// configuration instead of waiting for a rebuild with an updated opencode_fields.toml.
+///
+/// **BUG THIS CATCHES**: Would catch if the overlay is only consulted at the top level
+/// or dropped when recursing into arrays.
+#[test]
+fn given_runtime_mapping_when_normalize_json_with_then_applies_recursively() {
+    let extra = HashMap::from([("workspaceID".to_string(), "workspace_id".to_string())]);
+    let input = json!({
+        "workspaceID": "ws_1",
+        "nested": {
+            "sessions": [{"workspaceID": "ws_2", "sessionID": "ses_1"}]
+        }
+    });
+
+    let expected = json!({
+        "workspace_id": "ws_1",
+        "nested": {
+            "sessions": [{"workspace_id": "ws_2", "session_id": "ses_1"}]
+        }
+    });
+
+    assert_eq!(normalize_json_with(input, &extra), expected);
+}
+
+/// **VALUE**: Verifies generated mappings still apply to keys the overlay doesn't mention,
+/// and that the overlay wins for keys it does.
+///
+/// **WHY THIS MATTERS**: The overlay extends the generated table; it must not disable it,
+/// while still letting a deployment correct a mapping without a rebuild.
+///
+/// **BUG THIS CATCHES**: Would catch if lookup order is reversed or if a non-empty overlay
+/// short-circuits the generated table.
+#[test]
+fn given_runtime_mappings_when_normalize_json_with_then_generated_table_still_applies() {
+    let extra = HashMap::from([
+        ("workspaceID".to_string(), "workspace_id".to_string()),
+        ("baseURL".to_string(), "base_uri".to_string()),
+    ]);
+    let input = json!({"projectID": "proj_1", "baseURL": "http://x", "unknownField": 1});
+
+    let normalized = normalize_json_with(input.clone(), &extra);
+
+    assert_eq!(normalized["project_id"], "proj_1");
+    assert_eq!(normalized["base_uri"], "http://x");
+    assert_eq!(normalized["unknownField"], 1);
+    assert_eq!(
+        normalize_json_with(input.clone(), &HashMap::new()),
+        normalize_json(input)
+    );
+}
+
+// ============================================
+// UNIT TESTS: Deep Nesting
+// ============================================
+
+/// The original recursive implementation, kept as the reference for the iterative one
+fn recursive_reference(value: Value, rename: fn(&str) -> Cow<'_, str>) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (rename(&k).into_owned(), recursive_reference(v, rename)))
+                .collect(),
+        ),
+        Value::Array(arr) => Value::Array(
+            arr.into_iter()
+                .map(|v| recursive_reference(v, rename))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Small deterministic PRNG (xorshift64) so failures reproduce from the seed
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+/// Random JSON mixing mapped, unmapped, and already-normalized keys
+fn random_json(rng: &mut XorShift, depth: u32) -> Value {
+    const KEYS: &[&str] = &[
+        "sessionID",
+        "projectID",
+        "baseURL",
+        "topP",
+        "session_id",
+        "title",
+        "time",
+        "unknownField",
+    ];
+
+    let kind = if depth == 0 { rng.next(4) } else { rng.next(6) };
+    match kind {
+        0 => Value::Null,
+        1 => Value::Bool(rng.next(2) == 1),
+        2 => json!(rng.next(1000)),
+        3 => json!(format!("s{}", rng.next(100))),
+        4 => {
+            let mut map = Map::new();
+            for _ in 0..rng.next(5) {
+                let key = KEYS[rng.next(KEYS.len() as u64) as usize];
+                map.insert(key.to_string(), random_json(rng, depth - 1));
+            }
+            Value::Object(map)
+        }
+        _ => Value::Array(
+            (0..rng.next(5))
+                .map(|_| random_json(rng, depth - 1))
+                .collect(),
+        ),
+    }
+}
+
+/// **VALUE**: Verifies normalizing 50,000 levels of nesting completes without overflowing.
+///
+/// **WHY THIS MATTERS**: Tool output relayed from OpenCode can be arbitrarily deep; a stack
+/// overflow aborts the whole app, not just the request.
+///
+/// **BUG THIS CATCHES**: Would catch a return to per-level recursion in the traversal.
+#[test]
+fn given_deeply_nested_json_when_normalize_json_then_no_stack_overflow() {
+    const DEPTH: usize = 50_000;
+    let mut value = json!({"sessionID": "ses_1"});
+    for _ in 0..DEPTH {
+        let mut map = Map::new();
+        map.insert("parentID".to_string(), value);
+        value = Value::Object(map);
+    }
+
+    let mut current = normalize_json(value);
+
+    // Walk (and take apart) level by level: Value's own Drop and PartialEq recurse
+    for _ in 0..DEPTH {
+        let Value::Object(mut map) = current else {
+            panic!("Expected an object at every level");
+        };
+        current = map
+            .remove("parent_id")
+            .expect("parentID should be normalized");
+        assert!(map.is_empty());
+    }
+    assert_eq!(current, json!({"session_id": "ses_1"}));
+}
+
+/// **VALUE**: Verifies the iterative traversal matches the recursive one on random JSON.
+///
+/// **WHY THIS MATTERS**: The traversal rewrite must be invisible to callers: same keys,
+/// same values, same order, same handling of colliding renames.
+///
+/// **BUG THIS CATCHES**: Would catch children attached to the wrong parent, dropped empty
+/// containers, or reordered array elements.
+#[test]
+fn given_random_json_when_normalized_then_matches_recursive_reference() {
+    for seed in 1..=500u64 {
+        let mut rng = XorShift(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let input = random_json(&mut rng, 6);
+
+        assert_eq!(
+            normalize_json(input.clone()),
+            recursive_reference(input.clone(), normalize_key),
+            "normalize_json diverged for seed {seed}"
+        );
+        assert_eq!(
+            denormalize_json(input.clone()),
+            recursive_reference(input, denormalize_key),
+            "denormalize_json diverged for seed {seed}"
+        );
+    }
+}
+
+/// **VALUE**: Verifies the in-place fast path produces exactly the naive rebuild's output on
+/// a large list_sessions-shaped payload (the case benchmarked in benches/field_normalizer.rs).
+///
+/// **WHY THIS MATTERS**: Most objects in such payloads (time, summary, tags) have nothing to
+/// rename and are skipped; a skipped object whose children do need renaming must still be
+/// descended into.
+///
+/// **BUG THIS CATCHES**: Would catch if the fast path stops at unchanged objects or arrays
+/// of primitives, or if rebuilt objects lose keys.
+#[test]
+fn given_large_session_list_when_normalize_json_then_matches_naive_rebuild() {
+    let sessions: Vec<Value> = (0..500)
+        .map(|i| {
+            json!({
+                "id": format!("ses_{i:04}"),
+                "projectID": "proj_abc",
+                "parentID": if i % 10 == 0 { Value::Null } else { json!("ses_0000") },
+                "title": format!("Session {i}"),
+                "time": { "created": 1_700_000_000 + i, "updated": 1_700_000_500 + i },
+                "summary": { "additions": i % 50, "files": i % 5 },
+                "tags": ["rust", "tauri"],
+                "share": { "meta": { "messageID": "msg_1" } }
+            })
+        })
+        .collect();
+    let payload = json!({ "sessions": sessions });
+
+    let normalized = normalize_json(payload.clone());
+
+    assert_eq!(normalized, recursive_reference(payload, normalize_key));
+    assert_eq!(normalized["sessions"][1]["project_id"], "proj_abc");
+    assert_eq!(
+        normalized["sessions"][499]["share"]["meta"]["message_id"],
+        "msg_1"
+    );
+}
+
+// ============================================
+// UNIT TESTS: Streaming Normalization
+// ============================================
+
+const SESSION_MESSAGES_FIXTURE: &str = include_str!("fixtures/session_messages.json");
+
+/// Reader that hands out at most 7 bytes per read, so tokens straddle read boundaries
+struct TrickleReader<'a>(&'a [u8]);
+
+impl std::io::Read for TrickleReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = buf.len().min(7).min(self.0.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+/// **VALUE**: Verifies normalizing while parsing gives exactly the parse-then-normalize result
+/// on a large message list (the fixture's messages repeated 2,000 times).
+///
+/// **WHY THIS MATTERS**: list_messages switched to the streaming path; any difference would
+/// silently change what the UI receives for long sessions.
+///
+/// **BUG THIS CATCHES**: Would catch objects missed inside arrays, numbers parsed differently,
+/// or a reader-based parse that breaks on tokens split across reads.
+#[test]
+fn given_large_message_list_when_normalize_reader_then_matches_normalize_json() {
+    // GIVEN
+    let messages: Vec<Value> = serde_json::from_str(SESSION_MESSAGES_FIXTURE).unwrap();
+    let large: Vec<&Value> = messages
+        .iter()
+        .cycle()
+        .take(messages.len() * 2_000)
+        .collect();
+    let payload = serde_json::to_string(&large).unwrap();
+
+    // WHEN
+    let expected = normalize_json(serde_json::from_str(&payload).unwrap());
+    let from_slice = normalize_slice(payload.as_bytes()).unwrap();
+    let from_reader = normalize_reader(TrickleReader(payload.as_bytes())).unwrap();
+
+    // THEN
+    assert_eq!(from_slice, expected);
+    assert_eq!(from_reader, expected);
+    assert_eq!(from_slice[0]["info"]["session_id"], "ses_test");
+}
+
+/// **VALUE**: Verifies the streaming and two-pass paths agree on random documents mixing
+/// mapped, unmapped, and already-normalized keys, including keys that collide after renaming.
+///
+/// **WHY THIS MATTERS**: A payload holding both `sessionID` and `session_id` must keep the
+/// same value whichever path parsed it.
+///
+/// **BUG THIS CATCHES**: Would catch renaming keys one by one as they're parsed, which lets
+/// document order (not map order) decide which colliding value survives.
+#[test]
+fn given_random_json_when_normalize_slice_then_matches_normalize_json() {
+    let mut rng = XorShift(0x5EED_CAFE_F00D_D00D);
+
+    for _ in 0..500 {
+        // GIVEN
+        let payload = serde_json::to_string(&random_json(&mut rng, 6)).unwrap();
+
+        // WHEN
+        let streamed = normalize_slice(payload.as_bytes()).unwrap();
+
+        // THEN
+        let expected = normalize_json(serde_json::from_str(&payload).unwrap());
+        assert_eq!(streamed, expected, "payload: {payload}");
+    }
+}
+
+/// **VALUE**: Verifies duplicate keys in the raw text collapse the same way on both paths.
+///
+/// **WHY THIS MATTERS**: serde_json keeps the last of duplicate keys; the streaming path
+/// must apply that before renaming, not after.
+///
+/// **BUG THIS CATCHES**: Would catch the streaming path keeping both values of a duplicated
+/// key under different names, or picking a different winner.
+#[test]
+fn given_duplicate_and_colliding_keys_when_normalize_slice_then_matches_normalize_json() {
+    // GIVEN
+    let payload = r#"{"session_id":"a","sessionID":"b","sessionID":"c","nested":[{"parentID":1,"parent_id":2}]}"#;
+
+    // WHEN
+    let streamed = normalize_slice(payload.as_bytes()).unwrap();
+
+    // THEN
+    assert_eq!(
+        streamed,
+        normalize_json(serde_json::from_str(payload).unwrap())
+    );
+}
+
+/// **VALUE**: Verifies malformed input is an error, not a partial value.
+///
+/// **BUG THIS CATCHES**: Would catch trailing garbage being ignored after a complete value.
+#[test]
+fn given_invalid_json_when_normalize_slice_then_error() {
+    assert!(normalize_slice(br#"{"sessionID": "ses_1""#).is_err());
+    assert!(normalize_slice(br#"{"sessionID": "ses_1"} trailing"#).is_err());
+    assert!(normalize_reader(TrickleReader(b"[1, 2,")).is_err());
+}
@@ -0,0 +1,65 @@
+// Shared test fixtures
+// Builders for config values that several test modules need
+
+use crate::auth_sync::EnvSource;
+use crate::config::models::{ProviderConfig, ResponseFormat};
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+
+/// A valid bearer-auth provider `name` with an OpenAI-shaped model list and no key rules.
+///
+/// Its key is read from `{NAME}_API_KEY` (`-` becomes `_`, e.g. `MY_PROVIDER_API_KEY`).
+/// Override fields with struct update syntax:
+/// `ProviderConfig { auth_type: "header".to_string(), ..provider("anthropic") }`.
+pub(crate) fn provider(name: &str) -> ProviderConfig {
+    ProviderConfig {
+        name: name.to_string(),
+        display_name: name.to_string(),
+        api_key_env: format!("{}_API_KEY", name.to_uppercase().replace('-', "_")),
+        models_url: format!("https://{name}.example.com/v1/models"),
+        auth_type: "bearer".to_string(),
+        auth_header: None,
+        auth_param: None,
+        extra_headers: HashMap::new(),
+        key_prefix: None,
+        key_min_length: None,
+        key_max_length: None,
+        key_placeholder_patterns: None,
+        response_format: ResponseFormat {
+            models_path: "data".to_string(),
+            model_id_field: "id".to_string(),
+            model_id_strip_prefix: None,
+            model_name_field: "id".to_string(),
+        },
+    }
+}
+
+/// Environment variables for a test, read instead of the process environment.
+///
+/// Setting real variables is unsound while other test threads read the environment.
+#[derive(Debug, Default)]
+pub(crate) struct FakeEnv(HashMap<String, String>);
+
+impl FakeEnv {
+    pub(crate) fn new(vars: &[(&str, &str)]) -> Self {
+        Self(
+            vars.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl EnvSource for FakeEnv {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        self.0.get(name).map(OsString::from)
+    }
+
+    fn vars_os(&self) -> Vec<(OsString, OsString)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect()
+    }
+}
@@ -0,0 +1,64 @@
+// Unit tests for IpcAuthToken
+// Tests that formatting never exposes the token value
+
+use crate::ipc::IpcAuthToken;
+
+const TOKEN: &str = "3f2a9c1e-7b4d-4e8a-9c0f-5d6e7f8a9b0c";
+
+/// **VALUE**: Verifies Display and Debug show only a short prefix.
+///
+/// **WHY THIS MATTERS**: Logs are written to `opencode.log` on disk; any `{}` or `{:?}`
+/// of the token in a log line would persist a live credential.
+///
+/// **BUG THIS CATCHES**: Would catch if a derived `Debug` or a naive `Display` prints
+/// the full token.
+#[test]
+fn given_token_when_formatted_then_value_is_masked() {
+    // GIVEN: A token
+    let token = IpcAuthToken::new(TOKEN.to_string());
+
+    // WHEN: Formatting it both ways
+    let display = format!("{token}");
+    let debug = format!("{token:?}");
+
+    // THEN: Neither contains the token, both carry the prefix and a redaction marker
+    for output in [&display, &debug] {
+        assert!(!output.contains(TOKEN), "token leaked: {output}");
+        assert!(!output.contains("7b4d"), "token body leaked: {output}");
+        assert!(output.contains("3f2a"));
+        assert!(output.contains("[REDACTED]"));
+    }
+}
+
+/// **VALUE**: Verifies the real value stays reachable for handshake validation.
+///
+/// **WHY THIS MATTERS**: Redaction must not break auth; `as_str` is the only way to the value.
+///
+/// **BUG THIS CATCHES**: Would catch if `as_str` returns the masked form.
+#[test]
+fn given_token_when_as_str_then_returns_real_value() {
+    // GIVEN: A token
+    let token = IpcAuthToken::new(TOKEN.to_string());
+
+    // WHEN / THEN: The accessor exposes the exact value
+    assert_eq!(token.as_str(), TOKEN);
+    assert_eq!(token.fingerprint(), "3f2a");
+}
+
+/// **VALUE**: Verifies short and non-ASCII tokens are masked without panicking.
+///
+/// **WHY THIS MATTERS**: Callers may pass any string via `start_ipc_server(Some(..))`;
+/// for a short token the "prefix" would be most of the secret.
+///
+/// **BUG THIS CATCHES**: Would catch slicing past the end (or mid-character), or printing
+/// a prefix that reveals most of a short token.
+#[test]
+fn given_short_or_unicode_token_when_formatted_then_masked() {
+    // GIVEN: A token shorter than two prefixes, and a multi-byte one
+    let short = IpcAuthToken::new("abc123".to_string());
+    let unicode = IpcAuthToken::new("äöüßéàèìò".to_string());
+
+    // WHEN / THEN: The short token shows no prefix; the unicode one is cut on a char boundary
+    assert_eq!(format!("{short}"), "…[REDACTED]");
+    assert_eq!(unicode.fingerprint(), "äöüß");
+}
@@ -0,0 +1,241 @@
+// Unit tests for ConfigState
+// Tests hot-reload and models updates against a real temp directory
+
+use crate::config::models::{CuratedModel, ProviderConfig};
+use crate::config::{AppConfig, ModelsConfig};
+use crate::error::config::ConfigError;
+use crate::ipc::config_state::ConfigState;
+use crate::tests::fixtures::provider;
+
+use std::path::Path;
+use std::time::Duration;
+
+use tempfile::TempDir;
+
+/// How long to wait for the watcher to pick up a change.
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn write_config(dir: &Path, config: &AppConfig) {
+    let json = serde_json::to_string_pretty(config).unwrap();
+    std::fs::write(dir.join("config.json"), json).unwrap();
+}
+
+/// Poll until the in-memory font size matches `expected` or the timeout elapses.
+async fn wait_for_font_points(state: &ConfigState, expected: f32) -> bool {
+    let deadline = tokio::time::Instant::now() + RELOAD_TIMEOUT;
+    while tokio::time::Instant::now() < deadline {
+        if state.get_app_config().await.ui.base_font_points == expected {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    false
+}
+
+/// **VALUE**: Verifies an external edit to config.json reaches the in-memory config.
+///
+/// **WHY THIS MATTERS**: Users (and other tools) edit config.json by hand; without
+/// hot-reload those edits are invisible until the app restarts.
+///
+/// **BUG THIS CATCHES**: Would catch if the watcher filters out config.json events,
+/// is dropped early, or if the reload command never updates the RwLock.
+#[tokio::test]
+async fn given_watched_config_when_file_edited_then_memory_reloaded() {
+    // GIVEN: A config state watching an existing config.json
+    let dir = TempDir::new().unwrap();
+    let initial = AppConfig::default();
+    write_config(dir.path(), &initial);
+    let state = ConfigState::new(dir.path().to_path_buf(), initial, ModelsConfig::default());
+    let _watcher = state.watch_config_file().await.unwrap();
+
+    // WHEN: config.json is rewritten with a different font size
+    let mut edited = AppConfig::default();
+    edited.ui.base_font_points = 18.0;
+    write_config(dir.path(), &edited);
+
+    // THEN: The in-memory config picks up the change
+    assert!(
+        wait_for_font_points(&state, 18.0).await,
+        "config was not reloaded within {RELOAD_TIMEOUT:?}"
+    );
+}
+
+/// **VALUE**: Verifies an invalid external edit is ignored.
+///
+/// **WHY THIS MATTERS**: A half-typed or bad hand edit must not push invalid values
+/// (e.g. a 500pt font) into the running UI.
+///
+/// **BUG THIS CATCHES**: Would catch if the reload path skips validation and swaps in
+/// whatever JSON is on disk.
+#[tokio::test]
+async fn given_watched_config_when_invalid_edit_then_keeps_current_config() {
+    // GIVEN: A config state watching an existing config.json
+    let dir = TempDir::new().unwrap();
+    let initial = AppConfig::default();
+    write_config(dir.path(), &initial);
+    let state = ConfigState::new(dir.path().to_path_buf(), initial, ModelsConfig::default());
+    let _watcher = state.watch_config_file().await.unwrap();
+
+    // WHEN: An invalid edit is written, followed by a valid one
+    let mut invalid = AppConfig::default();
+    invalid.ui.base_font_points = 500.0;
+    write_config(dir.path(), &invalid);
+
+    let mut valid = AppConfig::default();
+    valid.ui.base_font_points = 20.0;
+    write_config(dir.path(), &valid);
+
+    // THEN: The invalid value never lands, and the later valid edit still does
+    let deadline = tokio::time::Instant::now() + RELOAD_TIMEOUT;
+    let mut reloaded = false;
+    while tokio::time::Instant::now() < deadline {
+        let points = state.get_app_config().await.ui.base_font_points;
+        assert_ne!(points, 500.0, "invalid config was loaded into memory");
+        if points == 20.0 {
+            reloaded = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(reloaded, "valid config was not reloaded after invalid edit");
+}
+
+/// **VALUE**: Verifies a valid models update lands in memory and in models.toml.
+///
+/// **WHY THIS MATTERS**: Curated models edited from the UI must survive a restart.
+///
+/// **BUG THIS CATCHES**: Would catch if the actor ignores `UpdateModelsConfig`, or if
+/// the saved TOML can't be read back by `ModelsConfig::load`.
+#[tokio::test]
+async fn given_valid_models_config_when_updated_then_saved_to_models_toml() {
+    // GIVEN: A config state with default models config
+    let dir = TempDir::new().unwrap();
+    let state = ConfigState::new(
+        dir.path().to_path_buf(),
+        AppConfig::default(),
+        ModelsConfig::default(),
+    );
+
+    // WHEN: Updating with one valid provider
+    let mut updated = ModelsConfig::default();
+    updated.providers.push(ProviderConfig {
+        auth_type: "bearer".to_string(),
+        ..provider("openai")
+    });
+    updated.models.default_model = "openai/gpt-4o".to_string();
+    state.update_models_config(updated).await.unwrap().unwrap();
+
+    // THEN: models.toml round-trips through load
+    let loaded = ModelsConfig::load(dir.path()).unwrap();
+    assert_eq!(loaded.models.default_model, "openai/gpt-4o");
+    assert_eq!(loaded.providers.len(), 1);
+    assert_eq!(loaded.providers[0].auth_type, "bearer");
+
+    // THEN: Memory matches
+    let in_memory = state.get_models_config().await;
+    assert_eq!(in_memory.models.default_model, "openai/gpt-4o");
+}
+
+/// **VALUE**: Verifies an invalid models update leaves the old config intact.
+///
+/// **WHY THIS MATTERS**: A provider with an unknown `auth_type` would break auth sync
+/// for every provider after it; the running config must keep the last good value.
+///
+/// **BUG THIS CATCHES**: Would catch if the actor writes memory or disk before validating,
+/// or reports the rejection as success.
+#[tokio::test]
+async fn given_invalid_auth_type_when_models_updated_then_old_config_kept() {
+    // GIVEN: A config state with default models config
+    let dir = TempDir::new().unwrap();
+    let state = ConfigState::new(
+        dir.path().to_path_buf(),
+        AppConfig::default(),
+        ModelsConfig::default(),
+    );
+
+    // WHEN: Updating with an invalid auth_type
+    let mut invalid = ModelsConfig::default();
+    invalid.providers.push(ProviderConfig {
+        auth_type: "magic".to_string(),
+        ..provider("openai")
+    });
+    let result = state.update_models_config(invalid).await.unwrap();
+
+    // THEN: The update is rejected
+    assert!(
+        matches!(result, Err(ConfigError::ValidationError { .. })),
+        "got {result:?}"
+    );
+
+    // THEN: Models config is unchanged and nothing was written
+    let models = state.get_models_config().await;
+    assert!(models.providers.is_empty());
+    assert!(!dir.path().join("models.toml").exists());
+}
+
+/// **VALUE**: Verifies a models update that can't be written is reported and not applied.
+///
+/// **WHY THIS MATTERS**: The UI tells the user their change was saved based on this reply;
+/// a config that only lives in memory is silently lost on restart.
+///
+/// **BUG THIS CATCHES**: Would catch the actor updating memory before the write, or
+/// swallowing the write error.
+#[tokio::test]
+async fn given_unwritable_config_dir_when_models_updated_then_error_and_memory_unchanged() {
+    // GIVEN: A config dir that is actually a file, so models.toml can't be written
+    let dir = TempDir::new().unwrap();
+    let not_a_dir = dir.path().join("config");
+    std::fs::write(&not_a_dir, "").unwrap();
+    let state = ConfigState::new(not_a_dir, AppConfig::default(), ModelsConfig::default());
+
+    // WHEN: Updating with a valid config
+    let mut updated = ModelsConfig::default();
+    updated.providers.push(ProviderConfig {
+        auth_type: "bearer".to_string(),
+        ..provider("openai")
+    });
+    let result = state.update_models_config(updated).await.unwrap();
+
+    // THEN: The write error comes back and memory keeps the old config
+    assert!(
+        matches!(result, Err(ConfigError::WriteError { .. })),
+        "got {result:?}"
+    );
+    assert!(state.get_models_config().await.providers.is_empty());
+}
+
+/// **VALUE**: Verifies a curated model change that can't be written is reported and not
+/// applied.
+///
+/// **WHY THIS MATTERS**: The UI shows the returned list as the saved curated models; a
+/// change that only lives in memory disappears on restart.
+///
+/// **BUG THIS CATCHES**: Would catch the actor changing the curated list before the write,
+/// or replying with the new list when the write fails.
+#[tokio::test]
+async fn given_unwritable_config_dir_when_curated_model_added_then_error_and_list_unchanged() {
+    // GIVEN: A config dir that is actually a file, so models.toml can't be written
+    let dir = TempDir::new().unwrap();
+    let not_a_dir = dir.path().join("config");
+    std::fs::write(&not_a_dir, "").unwrap();
+    let state = ConfigState::new(not_a_dir, AppConfig::default(), ModelsConfig::default());
+
+    // WHEN: Adding a curated model
+    let result = state
+        .add_curated_model(CuratedModel::new("GPT-4o", "openai", "gpt-4o"))
+        .await
+        .unwrap();
+
+    // THEN: The write error comes back and the curated list is unchanged
+    assert!(
+        matches!(result, Err(ConfigError::WriteError { .. })),
+        "got {result:?}"
+    );
+    assert!(
+        state
+            .get_models_config()
+            .await
+            .get_curated_models()
+            .is_empty()
+    );
+}
@@ -0,0 +1,162 @@
+// Unit tests for ConnectionState
+// Tests the auth lifecycle: AwaitingAuth -> Authenticated -> Closing
+
+use crate::ipc::IpcAuthToken;
+use crate::ipc::connection_state::{
+    ConnectionPhase, ConnectionPhases, ConnectionState, HandshakeRejection,
+};
+use crate::ipc::protocol::{IPC_PROTOCOL_VERSION, MIN_IPC_PROTOCOL_VERSION};
+
+use std::net::SocketAddr;
+
+const TOKEN: &str = "expected-token";
+
+fn new_connection() -> ConnectionState {
+    let peer: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    ConnectionState::new(
+        IpcAuthToken::new(TOKEN.to_string()),
+        peer,
+        ConnectionPhases::default(),
+    )
+}
+
+/// **VALUE**: Verifies a valid handshake authenticates the connection with the
+/// negotiated version.
+///
+/// **BUG THIS CATCHES**: Would catch a connection that starts anywhere but
+/// `AwaitingAuth`, or a successful handshake that doesn't reach `Authenticated`.
+#[test]
+fn given_new_connection_when_valid_handshake_then_authenticated() {
+    // GIVEN: A fresh connection
+    let mut connection = new_connection();
+    assert_eq!(connection.phase(), ConnectionPhase::AwaitingAuth);
+    assert_eq!(connection.protocol_version(), None);
+
+    // WHEN: Authenticating with the right token and current version
+    let result = connection.authenticate(TOKEN, IPC_PROTOCOL_VERSION);
+
+    // THEN: The version is negotiated, kept, and the connection is authenticated
+    assert_eq!(result, Ok(IPC_PROTOCOL_VERSION));
+    assert_eq!(connection.protocol_version(), Some(IPC_PROTOCOL_VERSION));
+    assert_eq!(connection.phase(), ConnectionPhase::Authenticated);
+}
+
+/// **VALUE**: Verifies refused first handshakes close the connection.
+///
+/// **WHY THIS MATTERS**: The server drops a client right after a failed handshake; the
+/// state (and its log line) must say so instead of leaving it awaiting auth.
+///
+/// **BUG THIS CATCHES**: Would catch a wrong token or an old client being authenticated,
+/// or the rejection reason being mixed up.
+#[test]
+fn given_new_connection_when_bad_token_or_version_then_closing() {
+    // GIVEN/WHEN: A wrong token
+    let mut connection = new_connection();
+    let result = connection.authenticate("wrong-token", IPC_PROTOCOL_VERSION);
+
+    // THEN: Refused as an invalid token, and closing with no version
+    assert_eq!(result, Err(HandshakeRejection::InvalidToken));
+    assert_eq!(connection.phase(), ConnectionPhase::Closing);
+    assert_eq!(connection.protocol_version(), None);
+
+    // GIVEN/WHEN: The right token from a client older than supported
+    let old_version = MIN_IPC_PROTOCOL_VERSION - 1;
+    let mut connection = new_connection();
+    let result = connection.authenticate(TOKEN, old_version);
+
+    // THEN: Refused for its version, and closing
+    assert_eq!(
+        result,
+        Err(HandshakeRejection::UnsupportedVersion(old_version))
+    );
+    assert_eq!(connection.phase(), ConnectionPhase::Closing);
+}
+
+/// **VALUE**: Verifies a second handshake is refused by the state machine itself.
+///
+/// **WHY THIS MATTERS**: A client re-sending its handshake (e.g. after a reconnect bug)
+/// must get an error, not be treated as a fresh login or routed like a request.
+///
+/// **BUG THIS CATCHES**: Would catch `authenticate` accepting a handshake after the
+/// first, or a refused repeat downgrading an authenticated connection.
+#[test]
+fn given_authenticated_connection_when_second_handshake_then_rejected_and_still_authenticated() {
+    // GIVEN: An authenticated connection
+    let mut connection = new_connection();
+    connection
+        .authenticate(TOKEN, IPC_PROTOCOL_VERSION)
+        .unwrap();
+
+    // WHEN: Handshaking again, even with a valid token
+    let result = connection.authenticate(TOKEN, IPC_PROTOCOL_VERSION);
+
+    // THEN: Refused as unexpected, and the connection stays authenticated
+    assert_eq!(
+        result,
+        Err(HandshakeRejection::UnexpectedHandshake(
+            ConnectionPhase::Authenticated
+        ))
+    );
+    assert_eq!(connection.phase(), ConnectionPhase::Authenticated);
+}
+
+/// **VALUE**: Verifies `Closing` is final.
+///
+/// **BUG THIS CATCHES**: Would catch a closing connection being authenticated by a late
+/// handshake.
+#[test]
+fn given_closing_connection_when_handshake_then_rejected() {
+    // GIVEN: A connection closed before it authenticated
+    let mut connection = new_connection();
+    connection.close("idle timeout");
+
+    // WHEN: A handshake arrives anyway
+    let result = connection.authenticate(TOKEN, IPC_PROTOCOL_VERSION);
+
+    // THEN: Refused, and still closing
+    assert_eq!(
+        result,
+        Err(HandshakeRejection::UnexpectedHandshake(
+            ConnectionPhase::Closing
+        ))
+    );
+    assert_eq!(connection.phase(), ConnectionPhase::Closing);
+}
+
+/// **VALUE**: Verifies the shared registry follows each phase change and forgets the
+/// connection once its state is dropped.
+///
+/// **WHY THIS MATTERS**: `IpcServerHandle::connection_phases` reads this registry; a
+/// stale entry would show a disconnected client as still connected.
+///
+/// **BUG THIS CATCHES**: Would catch a transition that skips the registry, or a
+/// connection left listed after its handler returned.
+#[test]
+fn given_registry_when_connection_moves_and_drops_then_registry_follows() {
+    // GIVEN: A connection registered in a shared registry
+    let peer: SocketAddr = "127.0.0.1:50001".parse().unwrap();
+    let registry = ConnectionPhases::default();
+    let mut connection =
+        ConnectionState::new(IpcAuthToken::new(TOKEN.to_string()), peer, registry.clone());
+    assert_eq!(
+        registry.snapshot(),
+        vec![(peer, ConnectionPhase::AwaitingAuth)]
+    );
+
+    // WHEN: It authenticates
+    connection
+        .authenticate(TOKEN, IPC_PROTOCOL_VERSION)
+        .expect("handshake should succeed");
+
+    // THEN: The registry reports it as authenticated
+    assert_eq!(
+        registry.snapshot(),
+        vec![(peer, ConnectionPhase::Authenticated)]
+    );
+
+    // WHEN: The connection state is dropped
+    drop(connection);
+
+    // THEN: It is no longer listed
+    assert!(registry.snapshot().is_empty());
+}
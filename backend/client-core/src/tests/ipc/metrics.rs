@@ -0,0 +1,66 @@
+// Unit tests for IpcMetrics
+// Tests histogram placement and failure attribution through instrument()
+
+use crate::ipc::IpcMetrics;
+use crate::ipc::metrics::{DURATION_BUCKET_BOUNDS, instrument, record_error_response};
+use crate::proto::IpcErrorCode;
+
+use std::time::Duration;
+
+/// **VALUE**: Verifies durations land in the first bucket whose bound they don't exceed.
+///
+/// **WHY THIS MATTERS**: The histogram is the only view of latency distribution; an
+/// off-by-one shifts every request into the wrong range.
+///
+/// **BUG THIS CATCHES**: Would catch if bounds are treated as exclusive, or if slow
+/// requests beyond the last bound are dropped.
+#[test]
+fn given_durations_when_recorded_then_placed_in_matching_buckets() {
+    // GIVEN: Empty metrics
+    let metrics = IpcMetrics::new();
+
+    // WHEN: Recording a request exactly on the first bound and one past the last bound
+    metrics.record_completion("get_config", DURATION_BUCKET_BOUNDS[0], false);
+    metrics.record_completion("get_config", Duration::from_secs(5), false);
+
+    // THEN: One lands in the first bucket, the other in the overflow bucket
+    let snapshot = metrics.snapshot();
+    let buckets = &snapshot.message_types[0].duration_buckets;
+    assert_eq!(buckets.len(), DURATION_BUCKET_BOUNDS.len() + 1);
+    assert_eq!(buckets[0], 1);
+    assert_eq!(buckets[DURATION_BUCKET_BOUNDS.len()], 1);
+    assert_eq!(snapshot.message_types[0].max_duration_micros, 5_000_000);
+}
+
+/// **VALUE**: Verifies error responses inside `instrument` fail the request they belong to.
+///
+/// **WHY THIS MATTERS**: Handlers report errors through the shared send path; attribution
+/// relies on the task-local request scope rather than each handler.
+///
+/// **BUG THIS CATCHES**: Would catch if errors outside a request are counted, or if a
+/// handler's error response doesn't mark its request failed.
+#[tokio::test]
+async fn given_error_response_in_handler_when_instrumented_then_request_marked_failed() {
+    // GIVEN: Empty metrics, and an error response sent outside any request
+    let metrics = IpcMetrics::new();
+    record_error_response(IpcErrorCode::InternalError as i32);
+
+    // WHEN: One handler sends an error response and another doesn't
+    instrument(metrics.clone(), "get_config_value", async {
+        record_error_response(IpcErrorCode::InvalidMessage as i32);
+    })
+    .await;
+    instrument(metrics.clone(), "get_config", async {}).await;
+
+    // THEN: Only the failing request is marked, and only its error is counted
+    let snapshot = metrics.snapshot();
+    let failures: Vec<_> = snapshot
+        .message_types
+        .iter()
+        .map(|m| (m.message_type.as_str(), m.count, m.failures))
+        .collect();
+    assert_eq!(failures, [("get_config", 1, 0), ("get_config_value", 1, 1)]);
+    assert_eq!(snapshot.errors.len(), 1);
+    assert_eq!(snapshot.errors[0].code, IpcErrorCode::InvalidMessage as i32);
+    assert_eq!(snapshot.errors[0].count, 1);
+}
@@ -0,0 +1,6 @@
+mod auth_token;
+mod config_state;
+mod connection_state;
+mod metrics;
+mod server;
+mod state;
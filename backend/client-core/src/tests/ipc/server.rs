@@ -0,0 +1,447 @@
+// Unit tests for IPC request handlers
+// Handlers write into an in-memory sink; socket-level behavior is covered in
+// integration_tests/ipc_tests
+
+use crate::auth_sync::{SyncConfig, SyncReport};
+use crate::config::{AppConfig, ModelsConfig};
+use crate::discovery::{clear_override_port, set_override_port};
+use crate::error::{AuthSyncError, KeyValidationFailure};
+use crate::ipc::IpcState;
+use crate::ipc::config_state::ConfigState;
+use crate::ipc::server::{
+    auth_sync_response, handle_check_health, handle_discover_server, handle_get_config,
+    handle_get_config_value, handle_list_sessions, handle_stop_server, handle_update_session,
+    sync_config_from_request,
+};
+use crate::proto::{
+    IpcErrorCode, IpcGetConfigValueRequest, IpcProviderSyncResult, IpcServerInfo, IpcServerMessage,
+    IpcSyncAuthKeysRequest, IpcUpdateSessionRequest, ipc_server_message::Payload,
+};
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn config_state(app_config: AppConfig) -> ConfigState {
+    ConfigState::new(
+        PathBuf::from("/tmp/opencode-test"),
+        app_config,
+        ModelsConfig::default(),
+    )
+}
+
+/// Track a server at `base_url` and make it active.
+async fn state_with_server(base_url: &str) -> IpcState {
+    let state = IpcState::new();
+    let server = IpcServerInfo {
+        pid: 4242,
+        port: 0,
+        base_url: base_url.to_string(),
+        name: "opencode".to_string(),
+        command: "opencode serve".to_string(),
+        owned: false,
+        discovered_at: 0,
+        last_health_ok: 0,
+    };
+    state.set_server(server).await.unwrap();
+    state
+}
+
+/// The only message a handler sent, checked against the request it answers.
+fn single_response(sent: Vec<IpcServerMessage>, request_id: u64) -> Payload {
+    assert_eq!(sent.len(), 1, "expected exactly one response: {sent:?}");
+    let response = sent.into_iter().next().unwrap();
+    assert_eq!(response.request_id, request_id);
+    response.payload.expect("response should have a payload")
+}
+
+/// **VALUE**: Verifies `get_config` answers with the configuration currently in memory.
+///
+/// **WHY THIS MATTERS**: The settings screen is populated from this response; stale or
+/// default values would overwrite the user's settings on the next save.
+///
+/// **BUG THIS CATCHES**: Would catch the handler serializing defaults instead of the
+/// state's config, or swapping the app and models documents.
+#[tokio::test]
+async fn given_customized_config_when_get_config_then_response_carries_it() {
+    // GIVEN: A config state with a non-default font size
+    let mut app_config = AppConfig::default();
+    app_config.ui.base_font_points = 18.0;
+    let config_state = config_state(app_config);
+    let mut sent = Vec::new();
+
+    // WHEN: Handling get_config
+    handle_get_config(&config_state, 7, &mut sent)
+        .await
+        .unwrap();
+
+    // THEN: Both documents come back, with the customized value
+    let Payload::GetConfigResponse(response) = single_response(sent, 7) else {
+        panic!("expected GetConfigResponse");
+    };
+    let app: AppConfig = serde_json::from_str(&response.app_config_json).unwrap();
+    assert_eq!(app.ui.base_font_points, 18.0);
+    let models: ModelsConfig = serde_json::from_str(&response.models_config_json).unwrap();
+    assert_eq!(
+        models.agents.default_agent,
+        ModelsConfig::default().agents.default_agent
+    );
+}
+
+/// **VALUE**: Verifies an unknown key path is answered with an error response, not a
+/// handler failure.
+///
+/// **WHY THIS MATTERS**: A typo in a key path is the caller's mistake; it should get a
+/// precise `InvalidMessage`, not the generic internal error used for handler failures.
+///
+/// **BUG THIS CATCHES**: Would catch `get_value` errors being propagated with `?`.
+#[tokio::test]
+async fn given_unknown_key_when_get_config_value_then_invalid_message_response() {
+    // GIVEN: Default configuration
+    let config_state = config_state(AppConfig::default());
+    let mut sent = Vec::new();
+    let req = IpcGetConfigValueRequest {
+        key_path: "ui.no_such_setting".to_string(),
+    };
+
+    // WHEN: Asking for a key that doesn't exist
+    let result = handle_get_config_value(&config_state, 3, req, &mut sent).await;
+
+    // THEN: The handler succeeds and the client gets an InvalidMessage error
+    assert!(result.is_ok(), "{result:?}");
+    let Payload::Error(error) = single_response(sent, 3) else {
+        panic!("expected Error");
+    };
+    assert_eq!(error.code, IpcErrorCode::InvalidMessage as i32);
+    assert!(
+        error.message.contains("no_such_setting"),
+        "{}",
+        error.message
+    );
+}
+
+/// **VALUE**: Verifies `update_session` rejects a blank title before looking for a server.
+///
+/// **BUG THIS CATCHES**: Would catch validation running after the server lookup, which
+/// reports "no server" for what is really a bad request.
+#[tokio::test]
+async fn given_blank_title_when_update_session_then_invalid_message_without_server() {
+    // GIVEN: No server tracked at all
+    let state = IpcState::new();
+    let mut sent = Vec::new();
+    let req = IpcUpdateSessionRequest {
+        session_id: "ses_1".to_string(),
+        title: "   ".to_string(),
+    };
+
+    // WHEN: Renaming to whitespace
+    handle_update_session(&state, 9, req, &mut sent)
+        .await
+        .unwrap();
+
+    // THEN: The request itself is rejected
+    let Payload::Error(error) = single_response(sent, 9) else {
+        panic!("expected Error");
+    };
+    assert_eq!(error.code, IpcErrorCode::InvalidMessage as i32);
+}
+
+/// **VALUE**: Verifies `list_sessions` forwards the active server's sessions under the
+/// request's id.
+///
+/// **WHY THIS MATTERS**: The session sidebar is built from this one response.
+///
+/// **BUG THIS CATCHES**: Would catch the handler querying a server other than the active
+/// one, or dropping sessions while normalizing OpenCode's response.
+#[tokio::test]
+async fn given_active_server_when_list_sessions_then_session_list_sent() {
+    // GIVEN: An active server with two sessions
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "id": "ses_a", "projectID": "prj", "directory": "/work", "title": "A", "version": "1" },
+            { "id": "ses_b", "projectID": "prj", "directory": "/work", "title": "B", "version": "1" }
+        ])))
+        .expect(1)
+        .mount(&opencode)
+        .await;
+    let state = state_with_server(&opencode.uri()).await;
+    let mut sent = Vec::new();
+
+    // WHEN: Handling list_sessions
+    handle_list_sessions(&state, 4, &mut sent).await.unwrap();
+
+    // THEN: Both sessions are sent back
+    let Payload::SessionList(list) = single_response(sent, 4) else {
+        panic!("expected SessionList");
+    };
+    let ids: Vec<&str> = list.sessions.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, ["ses_a", "ses_b"]);
+}
+
+/// **VALUE**: Verifies an OpenCode failure while listing sessions reaches the client with
+/// the client error's category and retryability, not as a generic internal error.
+///
+/// **WHY THIS MATTERS**: The frontend offers "retry" only for retryable errors; a
+/// stringified error always reads as a permanent IO failure.
+///
+/// **BUG THIS CATCHES**: Would catch the handler flattening `OpencodeClientError` into
+/// `IpcError::Io` again.
+#[tokio::test]
+async fn given_unavailable_opencode_when_list_sessions_then_typed_server_error_sent() {
+    // GIVEN: An active server that answers 503
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&opencode)
+        .await;
+    let state = state_with_server(&opencode.uri()).await;
+    let mut sent = Vec::new();
+
+    // WHEN: Handling list_sessions
+    handle_list_sessions(&state, 8, &mut sent).await.unwrap();
+
+    // THEN: One ServerError response carrying the OpenCode error's details
+    let Payload::Error(error) = single_response(sent, 8) else {
+        panic!("expected Error");
+    };
+    assert_eq!(error.code, IpcErrorCode::ServerError as i32);
+    assert!(
+        error.message.starts_with("Failed to list sessions"),
+        "{}",
+        error.message
+    );
+    assert_ne!(error.category, "io");
+    assert!(error.retryable, "a 503 should be retryable: {error:?}");
+}
+
+/// **VALUE**: Verifies `list_sessions` fails without writing anything when no server is
+/// connected.
+///
+/// **WHY THIS MATTERS**: The connection loop turns a handler error into the client's
+/// error response; a handler that also answered would send two replies for one request.
+///
+/// **BUG THIS CATCHES**: Would catch a partial or empty `SessionList` being sent first.
+#[tokio::test]
+async fn given_no_server_when_list_sessions_then_error_and_nothing_sent() {
+    // GIVEN: No server tracked
+    let state = IpcState::new();
+    let mut sent = Vec::new();
+
+    // WHEN: Handling list_sessions
+    let result = handle_list_sessions(&state, 5, &mut sent).await;
+
+    // THEN: The handler fails and the sink is untouched
+    assert!(result.is_err());
+    assert!(sent.is_empty(), "unexpected messages: {sent:?}");
+}
+
+/// **VALUE**: Verifies `discover_server` always answers, and that a server it finds
+/// becomes the active one.
+///
+/// **WHY THIS MATTERS**: The frontend waits on this response at startup whether or not
+/// OpenCode is running; later requests rely on the discovered server being tracked.
+///
+/// **BUG THIS CATCHES**: Would catch no response being sent when nothing is found, or a
+/// found server not being stored in state.
+#[tokio::test]
+async fn given_any_machine_when_discover_server_then_response_matches_state() {
+    // GIVEN: Empty state (whatever OpenCode servers happen to run on this machine)
+    let state = IpcState::new();
+    let mut sent = Vec::new();
+
+    // WHEN: Handling discover_server
+    handle_discover_server(&state, 6, &mut sent).await.unwrap();
+
+    // THEN: One response, and a reported server is now tracked
+    let Payload::DiscoverServerResponse(response) = single_response(sent, 6) else {
+        panic!("expected DiscoverServerResponse");
+    };
+    if let Some(server) = response.server {
+        assert_eq!(state.get_server().await.map(|s| s.pid), Some(server.pid));
+    }
+}
+
+/// **VALUE**: Verifies `check_health` sends the probe behind its verdict.
+///
+/// **WHY THIS MATTERS**: `healthy: false` alone can't tell "restarting" from "gone"; the
+/// probe's reachability and status are what let the frontend show the difference.
+///
+/// **BUG THIS CATCHES**: Would catch the probe being left out of the response, or
+/// `healthy` disagreeing with the probe it was computed from.
+#[tokio::test]
+async fn given_server_answering_503_when_check_health_then_unhealthy_but_reachable() {
+    // GIVEN: An active server whose health endpoint answers 503
+    let opencode = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/doc"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&opencode)
+        .await;
+    let state = state_with_server(&opencode.uri()).await;
+    let mut sent = Vec::new();
+
+    // WHEN: Handling check_health
+    handle_check_health(&state, 2, &mut sent).await.unwrap();
+
+    // THEN: Unhealthy, yet reachable with the status it answered
+    let Payload::CheckHealthResponse(response) = single_response(sent, 2) else {
+        panic!("expected CheckHealthResponse");
+    };
+    assert!(!response.healthy);
+    let probe = response.probe.expect("response should carry the probe");
+    assert!(probe.reachable);
+    assert_eq!(probe.http_status, Some(503));
+}
+
+/// **VALUE**: Verifies an IPC sync request maps onto the sync pipeline's options.
+///
+/// **WHY THIS MATTERS**: The IPC handler is the only caller of the sync pipeline in the
+/// app; a request's timeout must reach it, and unset fields must not zero out retries.
+///
+/// **BUG THIS CATCHES**: Would catch `timeout_secs = 0` becoming a zero timeout, unset
+/// concurrency syncing providers one at a time, or the OAuth or dry-run flag being dropped.
+#[test]
+fn given_sync_request_when_building_sync_config_then_unset_fields_keep_defaults() {
+    // GIVEN: A request with only the OAuth flag set, and a dry run with a timeout and
+    // concurrency
+    let unset = IpcSyncAuthKeysRequest {
+        skip_oauth_providers: true,
+        ..Default::default()
+    };
+    let with_timeout = IpcSyncAuthKeysRequest {
+        timeout_secs: 5,
+        concurrency: 2,
+        dry_run: true,
+        ..Default::default()
+    };
+
+    // WHEN: Building sync options from them
+    let from_unset = sync_config_from_request(&unset);
+    let from_timeout = sync_config_from_request(&with_timeout);
+
+    // THEN: Unset fields keep the defaults and set ones are honored
+    let defaults = SyncConfig::default();
+    assert!(from_unset.skip_oauth_providers);
+    assert_eq!(from_unset.timeout, defaults.timeout);
+    assert_eq!(from_unset.max_retries, defaults.max_retries);
+    assert_eq!(from_unset.concurrency, defaults.concurrency);
+    assert!(!from_unset.dry_run);
+    assert!(!from_timeout.skip_oauth_providers);
+    assert_eq!(from_timeout.timeout, Duration::from_secs(5));
+    assert_eq!(from_timeout.concurrency, 2);
+    assert!(from_timeout.dry_run);
+}
+
+/// Providers named in IPC sync results, in order.
+fn providers(results: &[IpcProviderSyncResult]) -> Vec<&str> {
+    results.iter().map(|r| r.provider.as_str()).collect()
+}
+
+/// **VALUE**: Verifies a sync report is mapped to the IPC response, separating key
+/// validation failures from sync failures.
+///
+/// **WHY THIS MATTERS**: The settings screen tells users to fix a malformed key
+/// differently from a server that rejected one; both arrive in the report's `failed`.
+///
+/// **BUG THIS CATCHES**: Would catch validation failures listed under `failed`, unchanged
+/// keys going unreported, results in hash order, or error details leaking an unredacted key.
+#[test]
+fn given_sync_report_when_mapped_then_failures_split_by_kind() {
+    // GIVEN: A dry-run report with synced, skipped, unchanged, rejected and malformed
+    // providers
+    let report = SyncReport {
+        synced: vec!["anthropic".to_string(), "openai".to_string()],
+        skipped_oauth: vec!["github-copilot".to_string()],
+        already_current: vec!["deepseek".to_string()],
+        failed: HashMap::from([
+            (
+                "mistral".to_string(),
+                AuthSyncError::from_http_response(
+                    "mistral",
+                    401,
+                    "bad key sk-abcdefghijklmnop1234",
+                ),
+            ),
+            (
+                "groq".to_string(),
+                AuthSyncError::key_validation("groq", KeyValidationFailure::Empty),
+            ),
+            (
+                "cohere".to_string(),
+                AuthSyncError::from_http_response("cohere", 503, "down"),
+            ),
+        ]),
+        dry_run: true,
+    };
+
+    // WHEN: Mapping it to the IPC response
+    let response = auth_sync_response(report, 12);
+
+    // THEN: Each provider lands in its list, in name order, with redacted details
+    assert_eq!(providers(&response.synced), vec!["anthropic", "openai"]);
+    assert_eq!(providers(&response.skipped), vec!["github-copilot"]);
+    assert_eq!(providers(&response.already_current), vec!["deepseek"]);
+    assert_eq!(providers(&response.failed), vec!["cohere", "mistral"]);
+    assert_eq!(providers(&response.validation_failed), vec!["groq"]);
+    assert!(response.failed[0].retryable);
+    assert_eq!(response.failed[1].status_code, Some(401));
+    assert!(!response.failed[1].error.contains("abcdefghijklmnop"));
+    assert_eq!(response.duration_ms, 12);
+    assert!(response.dry_run);
+}
+
+/// **VALUE**: Verifies a server discovered on the override port can't be stopped.
+///
+/// **WHY THIS MATTERS**: The override port points at whatever the user runs there, often
+/// an `opencode serve` in a terminal. Discovering it must not make it ours to kill.
+///
+/// **BUG THIS CATCHES**: Would catch override-port discovery marking the server owned,
+/// which let `discover_server` followed by `stop_server` kill a user's process.
+#[cfg(unix)]
+#[tokio::test]
+async fn given_server_on_override_port_when_stop_server_then_not_owned_and_still_running() {
+    use std::os::fd::OwnedFd;
+    use std::process::{Command, Stdio};
+
+    // GIVEN: A process we didn't spawn holding a listening socket (as its stdin), and the
+    // override port pointing at it
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let mut child = Command::new("sleep")
+        .arg("30")
+        .stdin(Stdio::from(OwnedFd::from(listener)))
+        .spawn()
+        .expect("sleep should be available");
+    let state = IpcState::new();
+    let mut discovered = Vec::new();
+    set_override_port(port);
+    let result = handle_discover_server(&state, 1, &mut discovered).await;
+    clear_override_port();
+    result.unwrap();
+
+    // WHEN: Discovering it, then asking to stop it
+    let Payload::DiscoverServerResponse(response) = single_response(discovered, 1) else {
+        panic!("expected DiscoverServerResponse");
+    };
+    let server = response.server.expect("server on the override port");
+    let mut stopped = Vec::new();
+    handle_stop_server(&state, 2, &mut stopped).await.unwrap();
+
+    // THEN: It was found as not owned, the stop was refused, and it is still running
+    let still_running = child.try_wait().unwrap().is_none();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(server.pid, child.id());
+    assert!(!server.owned);
+    let Payload::Error(error) = single_response(stopped, 2) else {
+        panic!("expected Error");
+    };
+    assert_eq!(error.category, "not_owned", "{}", error.message);
+    assert!(still_running, "the process was signalled");
+}
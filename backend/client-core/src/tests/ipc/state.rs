@@ -0,0 +1,448 @@
+// Unit tests for IpcState
+// Tests multi-server tracking and the active selection through the state actor
+
+use crate::ipc::{IpcState, StateCommand};
+use crate::proto::ipc_server_state_event::Event;
+use crate::proto::{IpcHealthChangedEvent, IpcServerInfo, IpcServerStateEvent};
+
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+/// How long to wait for a published event.
+const EVENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+fn server(port: u32) -> IpcServerInfo {
+    IpcServerInfo {
+        pid: 1000 + port,
+        port,
+        base_url: format!("http://127.0.0.1:{port}"),
+        name: "opencode".to_string(),
+        command: "opencode serve".to_string(),
+        owned: true,
+        discovered_at: 0,
+        last_health_ok: 0,
+    }
+}
+
+/// Wait for the next published event (panics if none arrives in time).
+async fn next_event(events: &mut tokio::sync::broadcast::Receiver<IpcServerStateEvent>) -> Event {
+    tokio::time::timeout(EVENT_TIMEOUT, events.recv())
+        .await
+        .expect("event should be published")
+        .expect("event channel should be open")
+        .event
+        .expect("event should have a payload")
+}
+
+/// **VALUE**: Verifies servers can be tracked side by side and switched between.
+///
+/// **WHY THIS MATTERS**: Users with several projects keep a server alive per project;
+/// switching must retarget every IPC handler without dropping the other servers.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Adding a server replaces the others or silently changes the selection
+/// - The active client doesn't follow the active server
+#[tokio::test]
+async fn given_two_servers_when_set_active_then_active_server_and_client_switch() {
+    // GIVEN: Two tracked servers, none active
+    let state = IpcState::new();
+    state
+        .update(StateCommand::AddServer {
+            id: "project-a".to_string(),
+            server: server(4001),
+        })
+        .await
+        .unwrap();
+    state
+        .update(StateCommand::AddServer {
+            id: "project-b".to_string(),
+            server: server(4002),
+        })
+        .await
+        .unwrap();
+    assert_eq!(state.get_servers().await.len(), 2);
+    assert!(state.get_server().await.is_none());
+
+    // WHEN: Selecting project-b
+    state
+        .update(StateCommand::SetActive("project-b".to_string()))
+        .await
+        .unwrap();
+
+    // THEN: project-b and its client are active
+    let (id, active) = state.get_active_server().await.unwrap();
+    assert_eq!(id, "project-b");
+    assert_eq!(active.port, 4002);
+    assert!(state.get_opencode_client().await.is_some());
+}
+
+/// **VALUE**: Verifies removing the active server clears the selection.
+///
+/// **WHY THIS MATTERS**: Handlers must report "no server" rather than talk to a server
+/// that was just stopped.
+///
+/// **BUG THIS CATCHES**: Would catch if the selection dangles after removal, or if
+/// removing one server drops the others.
+#[tokio::test]
+async fn given_active_server_when_removed_then_selection_cleared() {
+    // GIVEN: Two tracked servers with project-a active
+    let state = IpcState::new();
+    for (id, port) in [("project-a", 4001), ("project-b", 4002)] {
+        state
+            .update(StateCommand::AddServer {
+                id: id.to_string(),
+                server: server(port),
+            })
+            .await
+            .unwrap();
+    }
+    state
+        .update(StateCommand::SetActive("project-a".to_string()))
+        .await
+        .unwrap();
+
+    // WHEN: Removing the active server
+    state
+        .update(StateCommand::RemoveServer("project-a".to_string()))
+        .await
+        .unwrap();
+
+    // THEN: Nothing is active, and the other server is still tracked
+    assert!(state.get_server().await.is_none());
+    assert!(state.get_opencode_client().await.is_none());
+    let servers = state.get_servers().await;
+    assert_eq!(servers.len(), 1);
+    assert!(servers.contains_key("project-b"));
+}
+
+/// **VALUE**: Verifies selecting an untracked server leaves the selection unchanged.
+///
+/// **WHY THIS MATTERS**: A stale ID from the UI must not leave the app with no server.
+///
+/// **BUG THIS CATCHES**: Would catch if `SetActive` accepts unknown IDs.
+#[tokio::test]
+async fn given_active_server_when_set_active_unknown_then_selection_unchanged() {
+    // GIVEN: One active server
+    let state = IpcState::new();
+    state.set_server(server(4001)).await.unwrap();
+
+    // WHEN: Selecting an untracked ID
+    state
+        .update(StateCommand::SetActive("missing".to_string()))
+        .await
+        .unwrap();
+
+    // THEN: The original server is still active
+    assert_eq!(state.get_server().await.unwrap().port, 4001);
+}
+
+/// **VALUE**: Verifies a server whose base URL can't back an OpenCode client is refused.
+///
+/// **WHY THIS MATTERS**: Every handler asks state for the active client; a server that is
+/// reported as active without one fails each request with a misleading "no server" error.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - `set_server` reports success for a server it couldn't track
+/// - The bad server is listed or selected anyway
+#[tokio::test]
+async fn given_no_server_when_set_server_with_invalid_url_then_error_and_nothing_tracked() {
+    // GIVEN: Empty state
+    let state = IpcState::new();
+    let invalid = IpcServerInfo {
+        base_url: "not a url".to_string(),
+        ..server(4001)
+    };
+
+    // WHEN: Setting a server whose base URL is not a URL
+    let result = state.set_server(invalid).await;
+
+    // THEN: The caller is told, and state doesn't claim a usable server
+    let error = result.expect_err("invalid base URL should be rejected");
+    assert!(error.to_string().contains("not a url"), "{error}");
+    assert!(state.get_server().await.is_none());
+    assert!(state.get_opencode_client().await.is_none());
+    assert!(state.get_servers().await.is_empty());
+}
+
+/// **VALUE**: Verifies a refused server leaves the previously active server in charge.
+///
+/// **BUG THIS CATCHES**: Would catch the selection being cleared (or moved to the bad
+/// server) before the client is known to be buildable.
+#[tokio::test]
+async fn given_active_server_when_set_server_with_invalid_url_then_selection_unchanged() {
+    // GIVEN: One active server
+    let state = IpcState::new();
+    state.set_server(server(4001)).await.unwrap();
+
+    // WHEN: Switching to a server with an invalid base URL
+    let result = state
+        .set_server(IpcServerInfo {
+            base_url: "http://".to_string(),
+            ..server(4002)
+        })
+        .await;
+
+    // THEN: The switch fails and the original server and client are still active
+    assert!(result.is_err());
+    assert_eq!(state.get_server().await.unwrap().port, 4001);
+    assert!(state.get_opencode_client().await.is_some());
+    assert_eq!(state.get_servers().await.len(), 1);
+}
+
+/// **VALUE**: Verifies `RecordHealth` records a passing health check on the tracked server.
+///
+/// **WHY THIS MATTERS**: `last_health_ok` is how the UI tells a live server from a stale
+/// entry; it must move forward each time a check passes.
+///
+/// **BUG THIS CATCHES**: Would catch if the timestamp is written to a copy instead of the
+/// tracked server, or if touching an unknown ID creates an entry.
+#[tokio::test]
+async fn given_tracked_server_when_record_health_then_last_health_ok_updated() {
+    // GIVEN: An active server that has never passed a health check
+    let state = IpcState::new();
+    let tracked = server(4001);
+    let id = tracked.base_url.clone();
+    state.set_server(tracked).await.unwrap();
+
+    // WHEN: Recording a health check for it and for an unknown ID
+    state
+        .update(StateCommand::RecordHealth {
+            id: "missing".to_string(),
+            healthy: true,
+        })
+        .await
+        .unwrap();
+    state
+        .update(StateCommand::RecordHealth { id, healthy: true })
+        .await
+        .unwrap();
+
+    // THEN: The tracked server gets a timestamp and nothing else is tracked
+    assert!(state.get_server().await.unwrap().last_health_ok > 0);
+    assert_eq!(state.get_servers().await.len(), 1);
+}
+
+/// **VALUE**: Verifies `update` returns only after the actor has applied the command.
+///
+/// **WHY THIS MATTERS**: Handlers update state and then read it straight back (e.g. to
+/// find the client for the server they just selected); reads must not see stale state.
+///
+/// **BUG THIS CATCHES**: Would catch `update` returning as soon as the command is queued,
+/// which only works when the actor happens to run first.
+#[tokio::test]
+async fn given_update_when_reading_immediately_then_change_visible() {
+    // GIVEN: A tracked but inactive server
+    let state = IpcState::new();
+    state
+        .update(StateCommand::AddServer {
+            id: "project-a".to_string(),
+            server: server(4001),
+        })
+        .await
+        .unwrap();
+
+    // WHEN/THEN: Each update is visible to the very next read, with no yield in between
+    state
+        .update(StateCommand::SetActive("project-a".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(state.get_server().await.map(|s| s.port), Some(4001));
+
+    state.update(StateCommand::ClearServer).await.unwrap();
+    assert!(state.get_server().await.is_none());
+}
+
+/// **VALUE**: Verifies the single-server convenience commands still behave as before.
+///
+/// **WHY THIS MATTERS**: Discover/spawn/stop use `SetServer`/`ClearServer`; they must
+/// keep meaning "use this server" and "forget the current server".
+///
+/// **BUG THIS CATCHES**: Would catch if `SetServer` doesn't select the new server or if
+/// `ClearServer` leaves the stopped server tracked.
+#[tokio::test]
+async fn given_set_server_when_clear_server_then_behaves_like_single_server() {
+    // GIVEN: A server set through the convenience command
+    let state = IpcState::new();
+    state.set_server(server(4001)).await.unwrap();
+    let (id, _) = state.get_active_server().await.unwrap();
+    assert_eq!(id, "http://127.0.0.1:4001");
+
+    // WHEN: Clearing it
+    state.update(StateCommand::ClearServer).await.unwrap();
+
+    // THEN: No server is active or tracked
+    assert!(state.get_server().await.is_none());
+    assert!(state.get_servers().await.is_empty());
+}
+
+/// **VALUE**: Verifies the default directory reaches every client and session overrides win.
+///
+/// **WHY THIS MATTERS**: The configured `directory_override` must apply to servers added
+/// later, while a session opened from another project keeps its own directory.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Servers tracked after `set_directory` are created without the directory
+/// - A session override leaks into other sessions
+/// - Removing a session override doesn't fall back to the default
+#[tokio::test]
+async fn given_directories_when_getting_clients_then_session_override_takes_precedence() {
+    // GIVEN: A default directory set before any server, and one session override
+    let state = IpcState::new();
+    state
+        .set_directory(None, Some("/work/default".to_string()))
+        .await
+        .unwrap();
+    state
+        .set_directory(
+            Some("ses_other".to_string()),
+            Some("/work/other".to_string()),
+        )
+        .await
+        .unwrap();
+    state.set_server(server(4001)).await.unwrap();
+
+    // WHEN: Getting clients for the default and the overridden session
+    let default_client = state.get_opencode_client().await.unwrap();
+    let plain_session = state
+        .get_session_opencode_client("ses_plain")
+        .await
+        .unwrap();
+    let other_session = state
+        .get_session_opencode_client("ses_other")
+        .await
+        .unwrap();
+
+    // THEN: The override applies only to its session
+    assert_eq!(default_client.directory.as_deref(), Some("/work/default"));
+    assert_eq!(plain_session.directory.as_deref(), Some("/work/default"));
+    assert_eq!(other_session.directory.as_deref(), Some("/work/other"));
+
+    // WHEN: Removing the override
+    state
+        .set_directory(Some("ses_other".to_string()), None)
+        .await
+        .unwrap();
+
+    // THEN: The session falls back to the default
+    let other_session = state
+        .get_session_opencode_client("ses_other")
+        .await
+        .unwrap();
+    assert_eq!(other_session.directory.as_deref(), Some("/work/default"));
+}
+
+/// **VALUE**: Verifies subscribers are told when the active server is set and cleared.
+///
+/// **WHY THIS MATTERS**: The frontend reacts to server changes from events instead of
+/// polling `check_health`; a missed event leaves the UI showing a stale server.
+///
+/// **BUG THIS CATCHES**: Would catch if `SetServer` or `ClearServer` don't publish, or
+/// publish before the state is updated.
+#[tokio::test]
+async fn given_subscriber_when_set_and_clear_server_then_events_published() {
+    // GIVEN: A subscriber
+    let state = IpcState::new();
+    let mut events = state.subscribe_events();
+    let tracked = server(4001);
+
+    // WHEN: Setting, then clearing, the active server
+    state.set_server(tracked.clone()).await.unwrap();
+    state.update(StateCommand::ClearServer).await.unwrap();
+
+    // THEN: Both changes are published in order
+    assert_eq!(
+        next_event(&mut events).await,
+        Event::ServerSet(tracked.clone())
+    );
+    match next_event(&mut events).await {
+        Event::ServerCleared(cleared) => assert_eq!(cleared.server_id, tracked.base_url),
+        other => panic!("Expected ServerCleared, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies health events are published only when the result changes.
+///
+/// **WHY THIS MATTERS**: Health is checked periodically; publishing every result would
+/// flood subscribers with no-op updates.
+///
+/// **BUG THIS CATCHES**: Would catch if repeated identical results are published, or if a
+/// transition (healthy -> unhealthy) is missed.
+#[tokio::test]
+async fn given_subscriber_when_health_recorded_then_only_changes_published() {
+    // GIVEN: A subscriber and an active server
+    let state = IpcState::new();
+    let mut events = state.subscribe_events();
+    let tracked = server(4001);
+    let id = tracked.base_url.clone();
+    state.set_server(tracked.clone()).await.unwrap();
+    assert_eq!(next_event(&mut events).await, Event::ServerSet(tracked));
+
+    // WHEN: Recording healthy, healthy, unhealthy
+    for healthy in [true, true, false] {
+        state
+            .update(StateCommand::RecordHealth {
+                id: id.clone(),
+                healthy,
+            })
+            .await
+            .unwrap();
+    }
+
+    // THEN: Only the two transitions are published
+    for healthy in [true, false] {
+        assert_eq!(
+            next_event(&mut events).await,
+            Event::HealthChanged(IpcHealthChangedEvent {
+                server_id: id.clone(),
+                healthy,
+            })
+        );
+    }
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), events.recv())
+            .await
+            .is_err(),
+        "repeated result should not be published"
+    );
+}
+
+/// **VALUE**: Verifies a finished stream doesn't unregister another stream of its session.
+///
+/// **WHY THIS MATTERS**: Two `stream_message` requests can run on one session; an abort
+/// must still reach the one that is left after the other completes.
+///
+/// **BUG THIS CATCHES**: Would catch streams keyed by session alone, where the second
+/// stream replaces the first and the first one's finish removes the second's token.
+#[tokio::test]
+async fn given_two_streams_on_session_when_first_finishes_then_second_still_cancellable() {
+    // GIVEN: Two streams registered on the same session
+    let state = IpcState::new();
+    let first = CancellationToken::new();
+    let second = CancellationToken::new();
+    for (request_id, cancel) in [(1, &first), (2, &second)] {
+        state
+            .update(StateCommand::StreamStarted {
+                session_id: "ses_1".to_string(),
+                request_id,
+                cancel: cancel.clone(),
+            })
+            .await
+            .unwrap();
+    }
+
+    // WHEN: The first stream finishes, then the session is aborted
+    state
+        .update(StateCommand::StreamFinished {
+            session_id: "ses_1".to_string(),
+            request_id: 1,
+        })
+        .await
+        .unwrap();
+    let cancelled = state.cancel_stream("ses_1").await;
+
+    // THEN: The remaining stream is cancelled; the finished one is left alone
+    assert!(cancelled);
+    assert!(second.is_cancelled());
+    assert!(!first.is_cancelled());
+}
@@ -0,0 +1,114 @@
+// Unit tests for runtime log levels
+// The levels and logger are process-wide, so tests that change them hold LEVELS_LOCK
+
+use crate::logging::{is_enabled, level, parse_level, set_level};
+
+use std::sync::{Mutex, Once};
+
+use log::{LevelFilter, Log, Metadata, Record, trace};
+
+const PROBE_TARGET: &str = "client_core::tests::logging::probe";
+
+static LEVELS_LOCK: Mutex<()> = Mutex::new(());
+static CAPTURED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static INSTALL_ONCE: Once = Once::new();
+
+/// Records messages for the probe target, filtering like the app logger does.
+struct CaptureLogger;
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        is_enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target().starts_with(PROBE_TARGET) && self.enabled(record.metadata()) {
+            CAPTURED.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger;
+
+fn captured(message: &str) -> bool {
+    CAPTURED.lock().unwrap().iter().any(|m| m == message)
+}
+
+/// **VALUE**: Verifies that raising the level to trace at runtime makes trace records appear.
+///
+/// **WHY THIS MATTERS**: This is the whole point of the IPC command: a user reproducing a bug
+/// turns on trace logging without rebuilding or restarting the app.
+///
+/// **BUG THIS CATCHES**: Would catch if `set_level` updates the filter but not
+/// `log::set_max_level`, in which case the `trace!` macro drops records before the logger
+/// ever sees them.
+#[test]
+fn given_info_level_when_set_to_trace_then_trace_records_are_emitted() {
+    let _guard = LEVELS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    INSTALL_ONCE.call_once(|| log::set_logger(&LOGGER).expect("No other logger in unit tests"));
+
+    // GIVEN: The default level is info
+    set_level(LevelFilter::Info, None);
+
+    // WHEN: Logging at trace before and after raising the level
+    trace!(target: PROBE_TARGET, "before raise");
+    let previous = set_level(LevelFilter::Trace, None);
+    trace!(target: PROBE_TARGET, "after raise");
+
+    // THEN: Only the record logged after the change is emitted
+    assert_eq!(previous, LevelFilter::Info);
+    assert!(
+        !captured("before raise"),
+        "Trace should be filtered at info"
+    );
+    assert!(captured("after raise"), "Trace should be emitted at trace");
+
+    // Cleanup
+    set_level(LevelFilter::Info, None);
+}
+
+/// **VALUE**: Verifies per-target levels apply to the module and its submodules only.
+///
+/// **WHY THIS MATTERS**: Tracing everything is noisy (HTTP and WebSocket crates log a lot);
+/// users should be able to trace just `client_core::ipc`.
+///
+/// **BUG THIS CATCHES**: Would catch plain `starts_with` matching (`client_core::ip` covering
+/// `client_core::ipc`), or a shorter prefix winning over a more specific one.
+#[test]
+fn given_target_overrides_when_level_queried_then_longest_module_prefix_wins() {
+    let _guard = LEVELS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    // GIVEN: Default info, client_core at debug, client_core::ipc at trace
+    set_level(LevelFilter::Info, None);
+    set_level(LevelFilter::Debug, Some("client_core"));
+    set_level(LevelFilter::Trace, Some("client_core::ipc"));
+
+    // WHEN / THEN: The most specific matching module decides
+    assert_eq!(level("client_core::ipc"), LevelFilter::Trace);
+    assert_eq!(level("client_core::ipc::server"), LevelFilter::Trace);
+    assert_eq!(level("client_core::ipc_extra"), LevelFilter::Debug);
+    assert_eq!(level("client_core::discovery"), LevelFilter::Debug);
+    assert_eq!(level("reqwest::connect"), LevelFilter::Info);
+    assert_eq!(log::max_level(), LevelFilter::Trace);
+
+    // Cleanup
+    set_level(LevelFilter::Info, Some("client_core"));
+    set_level(LevelFilter::Info, Some("client_core::ipc"));
+}
+
+/// **VALUE**: Verifies level names are parsed case-insensitively and junk is rejected.
+///
+/// **WHY THIS MATTERS**: The level comes from user input over IPC.
+///
+/// **BUG THIS CATCHES**: Would catch if an unknown name silently maps to some level.
+#[test]
+fn given_level_names_when_parsed_then_known_names_accepted_and_others_rejected() {
+    // GIVEN / WHEN / THEN
+    assert_eq!(parse_level("trace"), Some(LevelFilter::Trace));
+    assert_eq!(parse_level(" WARN "), Some(LevelFilter::Warn));
+    assert_eq!(parse_level("off"), Some(LevelFilter::Off));
+    assert_eq!(parse_level("verbose"), None);
+    assert_eq!(parse_level(""), None);
+}
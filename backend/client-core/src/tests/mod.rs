@@ -1,3 +1,12 @@
+mod auth_sync;
+mod base_url;
+mod config;
 mod discovery;
 mod error;
 mod field_normalizer;
+mod fixtures;
+mod ipc;
+mod logging;
+mod opencode_client;
+mod provider_models;
+mod session;
@@ -0,0 +1,1214 @@
+// Unit tests for opencode_client module
+// Tests response parsing against a mock OpenCode server
+
+use crate::config::AgentsSection;
+use crate::error::opencode_client::OpencodeClientError;
+use crate::opencode_client::events::SseBuffer;
+use crate::opencode_client::{OpencodeClient, RetryPolicy};
+use crate::proto::event::oc_event::Event;
+use crate::proto::event::oc_session_status::Status;
+use crate::proto::message::OcMessage;
+use crate::proto::message::error::oc_message_error;
+use crate::proto::message::oc_message::Message;
+use crate::proto::message::part::oc_part::Part;
+
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SESSION_MESSAGES_FIXTURE: &str = include_str!("fixtures/session_messages.json");
+const SESSION_EVENTS_FIXTURE: &str = include_str!("fixtures/session_events.sse");
+const SESSION_INFO_FIXTURE: &str = include_str!("fixtures/session_info.json");
+const PROVIDER_AUTH_ERROR_FIXTURE: &str =
+    include_str!("fixtures/assistant_provider_auth_error.json");
+const OUTPUT_LENGTH_ERROR_FIXTURE: &str =
+    include_str!("fixtures/assistant_output_length_error.json");
+
+/// Retries idempotent requests with near-zero delays so tests run fast.
+fn fast_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_retries: 3,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+        retry_non_idempotent: false,
+    }
+}
+
+/// Mounts a mock answering `http_method path` with 503 twice, then `success`.
+async fn mount_flaky(
+    server: &MockServer,
+    http_method: &str,
+    route: &str,
+    success: ResponseTemplate,
+) {
+    Mock::given(method(http_method))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .mount(server)
+        .await;
+    Mock::given(method(http_method))
+        .and(path(route))
+        .respond_with(success)
+        .mount(server)
+        .await;
+}
+
+fn first_text(message: &OcMessage) -> Option<&str> {
+    let parts = match message.message.as_ref()? {
+        Message::User(user) => &user.parts,
+        Message::Assistant(assistant) => &assistant.parts,
+    };
+    parts.iter().find_map(|p| match p.part.as_ref()? {
+        Part::Text(text) => Some(text.text.as_str()),
+        _ => None,
+    })
+}
+
+/// **VALUE**: Verifies a mixed user/assistant history is routed to the right variants.
+///
+/// **WHY THIS MATTERS**: Session history is what the chat view renders on reopen;
+/// misrouting would show user prompts as AI replies (or drop them entirely).
+///
+/// **BUG THIS CATCHES**: Would catch if the `role` discriminator is ignored or if the
+/// flat-to-tagged part transformation isn't applied to list responses.
+#[tokio::test]
+async fn given_mixed_history_when_list_messages_then_routes_by_role() {
+    // GIVEN: A server returning one user and one assistant message
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session/ses_test/message"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(SESSION_MESSAGES_FIXTURE, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Listing messages
+    let messages = client.list_messages("ses_test").await.unwrap();
+
+    // THEN: Both messages are parsed in order with their parts
+    assert_eq!(messages.len(), 2);
+
+    match messages[0].message.as_ref() {
+        Some(Message::User(user)) => {
+            assert_eq!(user.id, "msg_user_1");
+            assert_eq!(user.session_id, "ses_test");
+        }
+        other => panic!("Expected user message, got {other:?}"),
+    }
+    assert_eq!(first_text(&messages[0]), Some("What is 2 + 2?"));
+
+    match messages[1].message.as_ref() {
+        Some(Message::Assistant(assistant)) => {
+            assert_eq!(assistant.id, "msg_assistant_1");
+            assert_eq!(assistant.tokens.as_ref().map(|t| t.output), Some(5));
+        }
+        other => panic!("Expected assistant message, got {other:?}"),
+    }
+    assert_eq!(first_text(&messages[1]), Some("2 + 2 = 4"));
+}
+
+/// **VALUE**: Verifies server errors surface with the HTTP status.
+///
+/// **WHY THIS MATTERS**: A missing session must be reported, not shown as empty history.
+///
+/// **BUG THIS CATCHES**: Would catch if non-success responses are parsed as an empty list.
+#[tokio::test]
+async fn given_unknown_session_when_list_messages_then_returns_server_error() {
+    // GIVEN: A server returning 404 for the session
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session/ses_missing/message"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Listing messages
+    let result = client.list_messages("ses_missing").await;
+
+    // THEN: A server error with the status is returned
+    let error = result.expect_err("should fail for unknown session");
+    assert!(error.to_string().contains("HTTP 404"));
+}
+
+/// **VALUE**: Verifies a short client timeout aborts a slow request with a network error.
+///
+/// **WHY THIS MATTERS**: Callers choose the timeout for their workload; it must actually
+/// bound the request instead of falling back to the 30s default.
+///
+/// **BUG THIS CATCHES**: Would catch if `with_timeout` ignores its argument or if a
+/// timeout is reported as a server/JSON error instead of a transport failure.
+#[tokio::test]
+async fn given_short_timeout_when_server_is_slow_then_returns_http_error() {
+    // GIVEN: A server that takes far longer than the client timeout
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("[]")
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::with_timeout(&server.uri(), Duration::from_millis(1)).unwrap();
+
+    // WHEN: Listing sessions
+    let result = tokio::time::timeout(Duration::from_secs(2), client.list_sessions())
+        .await
+        .expect("request should time out instead of hanging");
+
+    // THEN: A timeout error is returned
+    assert!(matches!(result, Err(OpencodeClientError::Timeout { .. })));
+}
+
+/// **VALUE**: Verifies `set_timeout` replaces the timeout on an existing client.
+///
+/// **WHY THIS MATTERS**: The IPC state keeps a long-lived client; adjusting its timeout
+/// must not require reconstructing it.
+///
+/// **BUG THIS CATCHES**: Would catch if the stored timeout and the HTTP client diverge.
+#[tokio::test]
+async fn given_client_when_set_timeout_then_new_timeout_applies() {
+    // GIVEN: A default client against a slow server
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("[]")
+                .set_delay(Duration::from_secs(5)),
+        )
+        .mount(&server)
+        .await;
+    let mut client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Lowering the timeout
+    client.set_timeout(Duration::from_millis(1)).unwrap();
+
+    // THEN: The getter reflects it and requests time out quickly
+    assert_eq!(client.timeout(), Duration::from_millis(1));
+    let result = tokio::time::timeout(Duration::from_secs(2), client.list_sessions())
+        .await
+        .expect("request should time out instead of hanging");
+    assert!(result.is_err());
+}
+
+/// **VALUE**: Verifies the event stream yields only part updates for the requested session.
+///
+/// **WHY THIS MATTERS**: The event stream carries every session's traffic; streaming a
+/// reply must not leak another session's text into the chat view.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - SSE comment/keepalive blocks are parsed as events
+/// - Non-part events (status, connected) are returned as parts
+/// - The session filter is skipped or parts aren't tagged for the proto `oneof`
+#[tokio::test]
+async fn given_event_stream_when_next_part_then_yields_session_parts_in_order() {
+    // GIVEN: A server whose event stream mixes sessions and event types
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(SESSION_EVENTS_FIXTURE, "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    let mut events = client.subscribe_events().await.unwrap();
+
+    // WHEN: Reading parts for ses_test until the stream ends
+    let mut texts = Vec::new();
+    while let Some(part) = events.next_part("ses_test").await.unwrap() {
+        match part.part {
+            Some(Part::Text(text)) => texts.push(text.text),
+            other => panic!("Expected text part, got {other:?}"),
+        }
+    }
+
+    // THEN: Only ses_test's two part updates are returned, in order
+    assert_eq!(texts, vec!["2 + ", "2 + 2 = 4"]);
+}
+
+/// **VALUE**: Verifies the event stream isn't cut off by the request timeout.
+///
+/// **WHY THIS MATTERS**: The stream stays open for the whole session; applying the
+/// one-shot request timeout would drop it (and every live update) every few seconds.
+///
+/// **BUG THIS CATCHES**: Would catch if subscribing uses the request client, or if
+/// `set_timeout` rebuilds the streaming client with a request timeout.
+#[tokio::test]
+async fn given_short_timeout_when_event_stream_slower_then_still_subscribed() {
+    // GIVEN: A client with a short timeout and an event stream slower than it
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(SESSION_EVENTS_FIXTURE, "text/event-stream")
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&server)
+        .await;
+    let mut client = OpencodeClient::new(&server.uri()).unwrap();
+    client.set_timeout(Duration::from_millis(100)).unwrap();
+
+    // WHEN: Subscribing and reading to the end of the stream
+    let mut events = client.subscribe_events().await.unwrap();
+    let mut count = 0;
+    while events.next_part("ses_test").await.unwrap().is_some() {
+        count += 1;
+    }
+
+    // THEN: Every part arrived despite outliving the request timeout
+    assert_eq!(count, 2);
+}
+
+/// SSE body with one `data:` block per event.
+fn sse_body(events: &[serde_json::Value]) -> String {
+    events
+        .iter()
+        .map(|event| format!("data: {event}\n\n"))
+        .collect()
+}
+
+/// **VALUE**: Verifies the typed subscription decodes OpenCode events into `OcEvent`s.
+///
+/// **WHY THIS MATTERS**: Live session and message updates in the UI are driven by these
+/// events; raw JSON would leave every consumer to redo the normalization.
+///
+/// **BUG THIS CATCHES**: Would catch if:
+/// - Unmodeled events (`server.connected`) are surfaced instead of skipped
+/// - JavaScript field names (`sessionID`) aren't normalized before decoding
+/// - Parts aren't tagged for the `oneof`, or status kinds are mapped the wrong way
+#[tokio::test]
+async fn given_event_stream_when_subscribe_oc_events_then_yields_typed_events() {
+    // GIVEN: A stream with a connect notice, a status change and a part update
+    let server = MockServer::start().await;
+    let body = sse_body(&[
+        serde_json::json!({"type": "server.connected", "properties": {}}),
+        serde_json::json!({"type": "session.status", "properties": {
+            "sessionID": "ses_test", "status": {"type": "busy"}
+        }}),
+        serde_json::json!({"type": "message.part.updated", "properties": {"part": {
+            "id": "prt_1", "sessionID": "ses_test", "messageID": "msg_1",
+            "type": "text", "text": "Hello"
+        }}}),
+    ]);
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Reading the first two typed events
+    let events = client.subscribe_oc_events().await.unwrap();
+    let events: Vec<Event> = Box::pin(events)
+        .take(2)
+        .map(|event| event.unwrap().event.unwrap())
+        .collect()
+        .await;
+
+    // THEN: The status and the part arrive, decoded
+    match &events[0] {
+        Event::SessionStatus(status) => {
+            assert_eq!(status.session_id, "ses_test");
+            assert!(matches!(
+                status.status.as_ref().and_then(|s| s.status.as_ref()),
+                Some(Status::Thinking(thinking)) if thinking.status == "busy"
+            ));
+        }
+        other => panic!("Expected session status, got {other:?}"),
+    }
+    match &events[1] {
+        Event::MessagePartUpdated(update) => {
+            assert_eq!(
+                (update.session_id.as_str(), update.message_id.as_str()),
+                ("ses_test", "msg_1")
+            );
+            assert!(matches!(
+                update.part.as_ref().and_then(|p| p.part.as_ref()),
+                Some(Part::Text(text)) if text.text == "Hello"
+            ));
+        }
+        other => panic!("Expected part update, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies the typed subscription reconnects when the server ends the stream.
+///
+/// **WHY THIS MATTERS**: Proxies and server restarts close long-lived connections; the UI
+/// would silently stop updating if the subscription ended with them.
+///
+/// **BUG THIS CATCHES**: Would catch the stream ending on the first disconnect, or events
+/// from the new connection being dropped.
+#[tokio::test]
+async fn given_stream_closed_by_server_when_reading_oc_events_then_reconnects() {
+    // GIVEN: A first connection announcing a session, then one reporting its deletion
+    let server = MockServer::start().await;
+    let session = serde_json::json!({
+        "id": "ses_new", "projectID": "prj", "directory": "/work", "title": "New", "version": "1"
+    });
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            sse_body(&[
+                serde_json::json!({"type": "session.created", "properties": {"info": session}}),
+            ]),
+            "text/event-stream",
+        ))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            sse_body(&[
+                serde_json::json!({"type": "session.deleted", "properties": {"info": session}}),
+            ]),
+            "text/event-stream",
+        ))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::with_retry_policy(&server.uri(), fast_retry_policy()).unwrap();
+
+    // WHEN: Reading past the end of the first connection
+    let events = client.subscribe_oc_events().await.unwrap();
+    let events: Vec<Event> = Box::pin(events)
+        .take(2)
+        .map(|event| event.unwrap().event.unwrap())
+        .collect()
+        .await;
+
+    // THEN: Events from both connections arrive in order
+    assert!(matches!(&events[0], Event::SessionCreated(created)
+        if created.session.as_ref().map(|s| s.id.as_str()) == Some("ses_new")));
+    assert!(
+        matches!(&events[1], Event::SessionDeleted(deleted) if deleted.session_id == "ses_new")
+    );
+}
+
+/// **VALUE**: Verifies the typed subscription gives up with an error when the server
+/// stays unreachable.
+///
+/// **BUG THIS CATCHES**: Would catch reconnecting forever (a consumer waiting on the stream
+/// would hang), or ending without telling the consumer why.
+#[tokio::test]
+async fn given_server_gone_when_reading_oc_events_then_error_then_end() {
+    // GIVEN: One event, after which every reconnect fails
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            sse_body(&[
+                serde_json::json!({"type": "message.removed", "properties": {
+                    "sessionID": "ses_test", "messageID": "msg_1"
+                }}),
+            ]),
+            "text/event-stream",
+        ))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/event"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::with_retry_policy(&server.uri(), fast_retry_policy()).unwrap();
+
+    // WHEN: Reading until the stream ends
+    let events = client.subscribe_oc_events().await.unwrap();
+    let results: Vec<_> = tokio::time::timeout(Duration::from_secs(5), Box::pin(events).collect())
+        .await
+        .expect("stream should end once reconnecting gives up");
+
+    // THEN: The event, then the reconnect error
+    assert_eq!(results.len(), 2, "{results:?}");
+    assert!(matches!(
+        results[0].as_ref().unwrap().event,
+        Some(Event::MessageRemoved(ref removed)) if removed.message_id == "msg_1"
+    ));
+    assert!(matches!(
+        results[1],
+        Err(OpencodeClientError::Server { .. })
+    ));
+}
+
+/// **VALUE**: Verifies SSE events split across chunks are reassembled byte-exact.
+///
+/// **WHY THIS MATTERS**: HTTP chunks end wherever the network splits them, including
+/// inside a multi-byte character or between `\r` and `\n`.
+///
+/// **BUG THIS CATCHES**: Would catch chunks being decoded one at a time (turning a split
+/// `é` into replacement characters) or CRLF endings split across chunks hiding the end of
+/// an event.
+#[test]
+fn given_event_split_across_chunks_when_buffering_then_reassembled() {
+    // GIVEN: One event whose bytes arrive in awkward pieces
+    let event = "data: {\"text\":\"café\"}\r\n\r\n".as_bytes();
+    let split_char = event.iter().position(|&b| b == 0xC3).unwrap() + 1;
+    let split_crlf = event.len() - 3;
+    let mut buffer = SseBuffer::default();
+
+    // WHEN: Pushing the chunks one by one
+    buffer.push(&event[..split_char]);
+    assert_eq!(buffer.next_data(), None);
+    buffer.push(&event[split_char..split_crlf]);
+    assert_eq!(buffer.next_data(), None);
+    buffer.push(&event[split_crlf..]);
+
+    // THEN: The complete payload comes out intact, once
+    assert_eq!(buffer.next_data().as_deref(), Some(r#"{"text":"café"}"#));
+    assert_eq!(buffer.next_data(), None);
+}
+
+/// **VALUE**: Verifies events from a server ending lines with a bare `\r` are delimited.
+///
+/// **WHY THIS MATTERS**: SSE allows CR, LF and CRLF line endings; a server (or proxy)
+/// using bare CR must still produce events instead of one ever-growing buffer.
+///
+/// **BUG THIS CATCHES**: Would catch `\r` being dropped instead of treated as a line end,
+/// or a `\r` at the end of one chunk and `\n` at the start of the next being counted as
+/// two line ends.
+#[test]
+fn given_cr_line_endings_when_buffering_then_events_delimited() {
+    // GIVEN: Two events ending lines with bare CR
+    let mut buffer = SseBuffer::default();
+    buffer.push(b"data: one\r\rdata: two\r\r");
+
+    // WHEN/THEN: Each comes out whole
+    assert_eq!(buffer.next_data().as_deref(), Some("one"));
+    assert_eq!(buffer.next_data().as_deref(), Some("two"));
+    assert_eq!(buffer.next_data(), None);
+
+    // GIVEN/WHEN: An event with CRLF endings, split between a CR and its LF
+    buffer.push(b"data: three\r");
+    buffer.push(b"\ndata: still three\r\n");
+
+    // THEN: The split CRLF isn't taken for a blank line, so the event isn't over yet
+    assert_eq!(buffer.next_data(), None);
+
+    // WHEN/THEN: Its blank line arrives and the whole event comes out
+    buffer.push(b"\r\n");
+    assert_eq!(buffer.next_data().as_deref(), Some("three\nstill three"));
+    assert_eq!(buffer.next_data(), None);
+}
+
+/// **VALUE**: Verifies idempotent reads are retried through transient 5xx responses.
+///
+/// **WHY THIS MATTERS**: The OpenCode server may be mid-restart; listing sessions
+/// should ride out a brief outage instead of showing the user an error.
+///
+/// **BUG THIS CATCHES**: Would catch if the retry policy is ignored or if a 5xx response
+/// is returned before the retry budget is spent.
+#[tokio::test]
+async fn given_retry_policy_when_list_sessions_fails_twice_then_succeeds() {
+    // GIVEN: A server that fails twice with 503 then returns sessions
+    let server = MockServer::start().await;
+    mount_flaky(
+        &server,
+        "GET",
+        "/session",
+        ResponseTemplate::new(200).set_body_string("[]"),
+    )
+    .await;
+    let client = OpencodeClient::with_retry_policy(&server.uri(), fast_retry_policy()).unwrap();
+
+    // WHEN: Listing sessions
+    let sessions = client.list_sessions().await.unwrap();
+
+    // THEN: The third attempt succeeds
+    assert!(sessions.is_empty());
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+/// **VALUE**: Verifies 4xx responses are never retried.
+///
+/// **WHY THIS MATTERS**: A client error won't fix itself; retrying only delays
+/// feedback and hammers the server.
+///
+/// **BUG THIS CATCHES**: Would catch if every non-success status is treated as transient.
+#[tokio::test]
+async fn given_retry_policy_when_server_returns_4xx_then_fails_without_retry() {
+    // GIVEN: A server that rejects the request
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session/ses_missing/message"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::with_retry_policy(&server.uri(), fast_retry_policy()).unwrap();
+
+    // WHEN: Listing messages
+    let result = client.list_messages("ses_missing").await;
+
+    // THEN: A single attempt is made and the status is reported
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("HTTP 404"));
+}
+
+/// **VALUE**: Verifies the client retries exactly the statuses its errors call retryable.
+///
+/// **WHY THIS MATTERS**: The UI offers a retry based on `is_retryable()`; retrying a 500
+/// the UI calls permanent (or giving up on a 429 it calls transient) contradicts it.
+///
+/// **BUG THIS CATCHES**: Would catch the retry loop and `OpencodeClientError` drifting
+/// back to separate status lists.
+#[tokio::test]
+async fn given_retry_policy_when_server_returns_500_or_429_then_retries_only_429() {
+    for (status, expected_attempts) in [(500, 1), (429, 4)] {
+        // GIVEN: A server that always fails with `status`
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+        let client = OpencodeClient::with_retry_policy(&server.uri(), fast_retry_policy()).unwrap();
+
+        // WHEN: Listing sessions
+        let error = client.list_sessions().await.unwrap_err();
+
+        // THEN: Retries happen only when the final error is retryable
+        let attempts = server.received_requests().await.unwrap().len();
+        assert_eq!(attempts, expected_attempts, "status {status}");
+        assert_eq!(
+            error.is_retryable(),
+            expected_attempts > 1,
+            "status {status}"
+        );
+    }
+}
+
+/// **VALUE**: Verifies non-idempotent requests are not retried unless opted in.
+///
+/// **WHY THIS MATTERS**: A 5xx after the server created the session would make a
+/// retry create a duplicate.
+///
+/// **BUG THIS CATCHES**: Would catch if `create_session` is retried by a policy that
+/// only allows idempotent retries.
+#[tokio::test]
+async fn given_idempotent_only_policy_when_create_session_fails_then_does_not_retry() {
+    // GIVEN: A server that fails create with 503
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/session"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::with_retry_policy(&server.uri(), fast_retry_policy()).unwrap();
+
+    // WHEN: Creating a session
+    let result = client.create_session(None).await;
+
+    // THEN: The first failure is returned
+    assert!(result.unwrap_err().to_string().contains("HTTP 503"));
+}
+
+/// **VALUE**: Verifies non-idempotent requests are retried once explicitly enabled.
+///
+/// **WHY THIS MATTERS**: Callers that can tolerate duplicates (e.g. deleting an
+/// already-deleted session) should be able to opt into resilience.
+///
+/// **BUG THIS CATCHES**: Would catch if `retry_non_idempotent` has no effect.
+#[tokio::test]
+async fn given_non_idempotent_retry_enabled_when_delete_session_fails_twice_then_succeeds() {
+    // GIVEN: A server that fails delete twice with 503 then succeeds
+    let server = MockServer::start().await;
+    mount_flaky(
+        &server,
+        "DELETE",
+        "/session/ses_flaky",
+        ResponseTemplate::new(200).set_body_string("true"),
+    )
+    .await;
+    let policy = RetryPolicy {
+        retry_non_idempotent: true,
+        ..fast_retry_policy()
+    };
+    let client = OpencodeClient::with_retry_policy(&server.uri(), policy).unwrap();
+
+    // WHEN: Deleting the session
+    let deleted = client.delete_session("ses_flaky").await.unwrap();
+
+    // THEN: The third attempt succeeds
+    assert!(deleted);
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+/// **VALUE**: Verifies the default client makes a single attempt.
+///
+/// **WHY THIS MATTERS**: Retry is opt-in; existing callers (e.g. auth sync, which
+/// retries on its own) must not have their attempts multiplied.
+///
+/// **BUG THIS CATCHES**: Would catch if `RetryPolicy::default()` enables retries.
+#[tokio::test]
+async fn given_default_client_when_list_sessions_fails_then_does_not_retry() {
+    // GIVEN: A server that fails with 503
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Listing sessions
+    let result = client.list_sessions().await;
+
+    // THEN: The failure is returned immediately
+    assert!(result.unwrap_err().to_string().contains("HTTP 503"));
+    assert_eq!(client.retry_policy(), &RetryPolicy::default());
+}
+
+/// **VALUE**: Verifies the directory header is sent when set and omitted when unset.
+///
+/// **WHY THIS MATTERS**: OpenCode resolves sessions against the header's project
+/// directory; a missing or stale header lists another project's sessions.
+///
+/// **BUG THIS CATCHES**: Would catch if `set_directory` doesn't reach `prepare_request`,
+/// or if clearing it still sends an (empty) header.
+#[tokio::test]
+async fn given_directory_when_set_and_cleared_then_header_follows() {
+    // GIVEN: A server that lists sessions
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .mount(&server)
+        .await;
+    let mut client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Listing with a directory set, then again after clearing it
+    client.set_directory(Some("/work/project".to_string()));
+    client.list_sessions().await.unwrap();
+    client.set_directory(None);
+    client.list_sessions().await.unwrap();
+
+    // THEN: Only the first request carries the header, with the configured value
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[0]
+            .headers
+            .get("x-opencode-directory")
+            .and_then(|v| v.to_str().ok()),
+        Some("/work/project")
+    );
+    assert!(!requests[1].headers.contains_key("x-opencode-directory"));
+}
+
+/// **VALUE**: Verifies a single session is fetched and normalized from OpenCode's JSON.
+///
+/// **WHY THIS MATTERS**: Reopening one session shouldn't require listing them all.
+///
+/// **BUG THIS CATCHES**: Would catch if the wrong endpoint is hit or if camelCase fields
+/// (`projectID`) aren't normalized before parsing.
+#[tokio::test]
+async fn given_existing_session_when_get_session_then_returns_session_info() {
+    // GIVEN: A server that knows the session
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session/ses_test"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(SESSION_INFO_FIXTURE, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Fetching the session
+    let session = client.get_session("ses_test").await.unwrap();
+
+    // THEN: All required fields are parsed
+    assert_eq!(session.id, "ses_test");
+    assert_eq!(session.project_id, "prj_test");
+    assert_eq!(session.title, "Fix the flaky test");
+    assert_eq!(session.time.unwrap().updated, 1767225603000);
+}
+
+/// **VALUE**: Verifies a 404 is reported as `NotFound` rather than a generic server error.
+///
+/// **WHY THIS MATTERS**: A deleted session should be dropped from the UI, while a server
+/// failure should be retried or reported - callers need to tell them apart.
+///
+/// **BUG THIS CATCHES**: Would catch if 404 falls through to `Server` or if the session ID
+/// is lost from the error.
+#[tokio::test]
+async fn given_missing_session_when_get_session_then_returns_not_found() {
+    // GIVEN: A server that doesn't know the session
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session/ses_gone"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Session not found"))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Fetching the session
+    let result = client.get_session("ses_gone").await;
+
+    // THEN: NotFound names the session and isn't retryable
+    match result {
+        Err(ref err @ OpencodeClientError::NotFound { ref session_id, .. }) => {
+            assert_eq!(session_id, "ses_gone");
+            assert!(!err.is_retryable());
+        }
+        other => panic!("Expected NotFound, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies renaming PATCHes the session with the trimmed title and returns the
+/// updated session.
+///
+/// **WHY THIS MATTERS**: The sidebar replaces its entry with the returned session; a reply
+/// parsed without normalization (`projectID`) would fail after the rename already happened.
+///
+/// **BUG THIS CATCHES**: Would catch the wrong method or path, the title not being sent (or
+/// sent with the user's stray whitespace), or the response not being normalized.
+#[tokio::test]
+async fn given_existing_session_when_update_session_then_returns_renamed_session() {
+    // GIVEN: A server that renames the session
+    let server = MockServer::start().await;
+    let mut renamed: serde_json::Value = serde_json::from_str(SESSION_INFO_FIXTURE).unwrap();
+    renamed["title"] = serde_json::json!("Flaky test fixed");
+    Mock::given(method("PATCH"))
+        .and(path("/session/ses_test"))
+        .and(body_partial_json(
+            serde_json::json!({ "title": "Flaky test fixed" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(renamed))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Renaming it (with surrounding whitespace from the text box)
+    let session = client
+        .update_session("ses_test", "  Flaky test fixed \n")
+        .await
+        .unwrap();
+
+    // THEN: The updated session is returned
+    assert_eq!(session.id, "ses_test");
+    assert_eq!(session.project_id, "prj_test");
+    assert_eq!(session.title, "Flaky test fixed");
+}
+
+/// **VALUE**: Verifies renaming an unknown session is reported as `NotFound`.
+///
+/// **BUG THIS CATCHES**: Would catch a 404 falling through to a generic server error, so
+/// the UI can't tell a deleted session from a failing server.
+#[tokio::test]
+async fn given_missing_session_when_update_session_then_returns_not_found() {
+    // GIVEN: A server that doesn't know the session
+    let server = MockServer::start().await;
+    Mock::given(method("PATCH"))
+        .and(path("/session/ses_gone"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("Session not found"))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Renaming it
+    let result = client.update_session("ses_gone", "New title").await;
+
+    // THEN: NotFound names the session
+    match result {
+        Err(OpencodeClientError::NotFound { session_id, .. }) => {
+            assert_eq!(session_id, "ses_gone")
+        }
+        other => panic!("Expected NotFound, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies a blank title is rejected without contacting the server.
+///
+/// **BUG THIS CATCHES**: Would catch a whitespace-only title being sent, leaving the
+/// session with an empty name in the sidebar.
+#[tokio::test]
+async fn given_blank_title_when_update_session_then_invalid_request_without_request() {
+    // GIVEN: A server that must receive no requests
+    let server = MockServer::start().await;
+    Mock::given(method("PATCH"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Renaming to a blank title
+    let result = client.update_session("ses_test", "   ").await;
+
+    // THEN: It is rejected locally
+    match result {
+        Err(ref err @ OpencodeClientError::InvalidRequest { .. }) => {
+            assert_eq!(err.error_category(), "invalid_request");
+            assert!(!err.is_retryable());
+        }
+        other => panic!("Expected InvalidRequest, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies abort POSTs to the session's abort endpoint and returns the verdict.
+///
+/// **WHY THIS MATTERS**: Cancelling a runaway generation is the only way to stop spending
+/// tokens on it.
+///
+/// **BUG THIS CATCHES**: Would catch if the wrong method/path is used or the server's
+/// boolean reply is ignored.
+#[tokio::test]
+async fn given_running_generation_when_abort_message_then_posts_abort() {
+    // GIVEN: A server that aborts the session's generation
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/abort"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("true"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Aborting
+    let aborted = client.abort_message("ses_test").await.unwrap();
+
+    // THEN: The server's verdict is returned
+    assert!(aborted);
+}
+
+/// **VALUE**: Verifies a failed abort is reported as a server error with its status.
+///
+/// **WHY THIS MATTERS**: The UI must not claim the generation stopped when it didn't.
+///
+/// **BUG THIS CATCHES**: Would catch if non-success statuses are parsed as a verdict.
+#[tokio::test]
+async fn given_server_failure_when_abort_message_then_returns_server_error() {
+    // GIVEN: A server that fails the abort
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/abort"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+
+    // WHEN: Aborting
+    let result = client.abort_message("ses_test").await;
+
+    // THEN: The status is reported
+    assert_eq!(result.unwrap_err().status_code(), Some(500));
+}
+
+/// Client for a server answering send_message in `ses_test` with `body`.
+async fn send_message_replying(body: &str) -> (MockServer, OpencodeClient) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/message"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    (server, client)
+}
+
+/// **VALUE**: Verifies an assistant message carrying a ProviderAuthError is returned as a
+/// typed assistant error, not as a successful reply.
+///
+/// **WHY THIS MATTERS**: A rejected API key produces an empty assistant message; shown as a
+/// normal reply, the user sees nothing happen instead of "Anthropic rejected your key".
+///
+/// **BUG THIS CATCHES**: Would catch the `error` field being ignored, or parsed without its
+/// `data` wrapper so the provider and message are lost.
+#[tokio::test]
+async fn given_provider_auth_error_when_send_message_then_returns_assistant_error() {
+    // GIVEN: A server replying with a provider auth failure
+    let (_server, client) = send_message_replying(PROVIDER_AUTH_ERROR_FIXTURE).await;
+
+    // WHEN: Sending a message
+    let result = client
+        .send_message(
+            "ses_test",
+            "hi",
+            "claude-3-5-sonnet-20241022",
+            "anthropic",
+            None,
+        )
+        .await;
+
+    // THEN: A typed provider auth error, categorized for the UI
+    let err = result.unwrap_err();
+    let Some(oc_message_error::Error::ProviderAuth(auth)) =
+        err.assistant_error().and_then(|e| e.error.as_ref())
+    else {
+        panic!("Expected a ProviderAuth assistant error, got {err:?}");
+    };
+    assert_eq!(auth.provider_id, "anthropic");
+    assert_eq!(auth.message, "invalid x-api-key");
+    assert_eq!(err.error_category(), "provider_auth");
+    assert!(!err.is_retryable());
+    assert!(
+        err.to_string()
+            .contains("ProviderAuthError (anthropic): invalid x-api-key")
+    );
+}
+
+/// **VALUE**: Verifies an assistant message that hit the output limit is returned as a
+/// typed output length error.
+///
+/// **WHY THIS MATTERS**: The UI should suggest a shorter request or a larger model rather
+/// than show a truncated answer as if it were complete.
+///
+/// **BUG THIS CATCHES**: Would catch OpenCode's `MessageOutputLengthError` name not being
+/// recognized (falling back to Unknown), or `maxTokens` not being read.
+#[tokio::test]
+async fn given_output_length_error_when_send_message_then_returns_assistant_error() {
+    // GIVEN: A server replying with an output length failure
+    let (_server, client) = send_message_replying(OUTPUT_LENGTH_ERROR_FIXTURE).await;
+
+    // WHEN: Sending a message
+    let result = client
+        .send_message(
+            "ses_test",
+            "hi",
+            "claude-3-5-sonnet-20241022",
+            "anthropic",
+            None,
+        )
+        .await;
+
+    // THEN: A typed output length error with the limit
+    let err = result.unwrap_err();
+    let Some(oc_message_error::Error::OutputLength(length)) =
+        err.assistant_error().and_then(|e| e.error.as_ref())
+    else {
+        panic!("Expected an OutputLength assistant error, got {err:?}");
+    };
+    assert_eq!(length.name, "MessageOutputLengthError");
+    assert_eq!(length.max_tokens, 8192);
+    assert_eq!(err.error_category(), "output_length");
+}
+
+/// **VALUE**: Verifies a reply whose `error` is null is still a successful message.
+///
+/// **BUG THIS CATCHES**: Would catch treating the mere presence of the `error` key as a
+/// failure.
+#[tokio::test]
+async fn given_null_error_when_send_message_then_returns_message() {
+    // GIVEN: A normal reply with an explicit null error
+    let mut reply: serde_json::Value = serde_json::from_str(OUTPUT_LENGTH_ERROR_FIXTURE).unwrap();
+    reply["info"]["error"] = serde_json::Value::Null;
+    let (_server, client) = send_message_replying(&reply.to_string()).await;
+
+    // WHEN: Sending a message
+    let result = client
+        .send_message(
+            "ses_test",
+            "hi",
+            "claude-3-5-sonnet-20241022",
+            "anthropic",
+            None,
+        )
+        .await;
+
+    // THEN: The assistant message is returned
+    let message = result.expect("null error should not fail the send");
+    assert!(matches!(message.message, Some(Message::Assistant(_))));
+}
+
+/// **VALUE**: Verifies every endpoint resolves to the exact absolute URL, with or without a
+/// trailing slash or path prefix on the base URL, and for bracketed IPv6 hosts.
+///
+/// **WHY THIS MATTERS**: `Url::join` replaces the base's last path segment unless the base
+/// ends with `/`, and a leading `/` on the endpoint discards the base path entirely; either
+/// sends requests for a server behind a path prefix to the wrong place.
+///
+/// **BUG THIS CATCHES**: Would catch if the base isn't normalized, if an endpoint is joined
+/// with a leading slash, or if an endpoint string is misspelled.
+#[test]
+fn given_base_urls_when_endpoint_url_then_resolves_to_exact_urls() {
+    let bases = [
+        ("http://127.0.0.1:4096", "http://127.0.0.1:4096/"),
+        ("http://127.0.0.1:4096/", "http://127.0.0.1:4096/"),
+        ("http://[::1]:4096", "http://[::1]:4096/"),
+        (
+            "https://devbox.local/opencode",
+            "https://devbox.local/opencode/",
+        ),
+        (
+            "https://devbox.local/opencode/",
+            "https://devbox.local/opencode/",
+        ),
+    ];
+    let endpoints = [
+        ("session", "session"),
+        ("session/ses_1", "session/ses_1"),
+        ("session/ses_1/message", "session/ses_1/message"),
+        ("session/ses_1/abort", "session/ses_1/abort"),
+        ("auth/openai", "auth/openai"),
+        ("event", "event"),
+        ("/session/ses_1/message", "session/ses_1/message"),
+    ];
+
+    for (base, expected_base) in bases {
+        // GIVEN: A client for the base URL
+        let client = OpencodeClient::new(base).unwrap();
+
+        for (endpoint, expected_path) in endpoints {
+            // WHEN: Resolving the endpoint
+            let url = client.endpoint_url(endpoint).unwrap();
+
+            // THEN: It extends the base path
+            assert_eq!(
+                url.as_str(),
+                format!("{expected_base}{expected_path}"),
+                "base: {base}, endpoint: {endpoint}"
+            );
+        }
+    }
+}
+
+/// **VALUE**: Verifies requests reach a server mounted under a path prefix.
+///
+/// **WHY THIS MATTERS**: A remote OpenCode server behind a reverse proxy is addressed with a
+/// path; session and auth calls must keep it.
+///
+/// **BUG THIS CATCHES**: Would catch if a method builds its URL without `endpoint_url`.
+#[tokio::test]
+async fn given_prefixed_base_url_when_requests_sent_then_prefix_kept() {
+    // GIVEN: A mock server answering only under /opencode
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/opencode/session/ses_1/message"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/opencode/auth/openai"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("true"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&format!("{}/opencode", server.uri())).unwrap();
+
+    // WHEN: Listing messages and setting an auth key
+    let messages = client.list_messages("ses_1").await.unwrap();
+    client.sync_api_key("openai", "sk-test").await.unwrap();
+
+    // THEN: Both requests hit the prefixed paths
+    assert!(messages.is_empty());
+}
+
+/// Client for a server that answers send_message in `ses_test` only when the request
+/// names `agent`, expecting `expected_requests` such requests.
+async fn send_message_expecting_agent(
+    agent: &str,
+    expected_requests: u64,
+) -> (MockServer, OpencodeClient) {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/session/ses_test/message"))
+        .and(body_partial_json(serde_json::json!({ "agent": agent })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "info": {
+                "id": "msg_reply",
+                "sessionID": "ses_test",
+                "role": "assistant",
+                "time": { "created": 1767225600000_u64 },
+                "modelID": "gpt-4",
+                "providerID": "openai"
+            },
+            "parts": []
+        })))
+        .expect(expected_requests)
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    (server, client)
+}
+
+/// **VALUE**: Verifies a message without an agent is sent to the configured default agent.
+///
+/// **WHY THIS MATTERS**: The default was hard-coded to "build"; a config that makes "plan"
+/// the default must be honored, and the default itself must pass the allowlist.
+///
+/// **BUG THIS CATCHES**: Would catch `None` still falling back to a hard-coded name.
+#[tokio::test]
+async fn given_configured_default_agent_when_send_message_without_agent_then_uses_default() {
+    // GIVEN: A client whose default agent is "plan"
+    let (_server, mut client) = send_message_expecting_agent("plan", 1).await;
+    client.set_agents(AgentsSection {
+        default_agent: "plan".to_string(),
+        ..Default::default()
+    });
+
+    // WHEN: Sending a message without naming an agent
+    let result = client
+        .send_message("ses_test", "hi", "gpt-4", "openai", None)
+        .await;
+
+    // THEN: The request named the default agent (the mock matched it)
+    assert!(result.is_ok(), "expected success, got {result:?}");
+}
+
+/// **VALUE**: Verifies a known agent is passed through to the server.
+///
+/// **BUG THIS CATCHES**: Would catch the allowlist rejecting built-in agents, or the
+/// default replacing an explicit agent.
+#[tokio::test]
+async fn given_known_agent_when_send_message_then_sends_that_agent() {
+    // GIVEN: A client with the default agents
+    let (_server, client) = send_message_expecting_agent("plan", 1).await;
+
+    // WHEN: Sending to the built-in "plan" agent
+    let result = client
+        .send_message("ses_test", "hi", "gpt-4", "openai", Some("plan"))
+        .await;
+
+    // THEN: The message was sent to it
+    assert!(result.is_ok(), "expected success, got {result:?}");
+}
+
+/// **VALUE**: Verifies an unknown agent fails with a typed error before any request.
+///
+/// **WHY THIS MATTERS**: The server's response to a misspelled agent doesn't say what went
+/// wrong; rejecting it locally names the agent and the accepted ones.
+///
+/// **BUG THIS CATCHES**: Would catch the request being sent anyway, or the error not
+/// naming the rejected agent.
+#[tokio::test]
+async fn given_unknown_agent_when_send_message_then_returns_unknown_agent_without_request() {
+    // GIVEN: A client restricted to two agents, and a server that must receive no requests
+    let (_server, mut client) = send_message_expecting_agent("biuld", 0).await;
+    client.set_agents(AgentsSection {
+        known: vec!["build".to_string(), "plan".to_string()],
+        ..Default::default()
+    });
+
+    // WHEN: Sending to a misspelled agent
+    let result = client
+        .send_message("ses_test", "hi", "gpt-4", "openai", Some("biuld"))
+        .await;
+
+    // THEN: It fails locally, naming the agent and the known ones
+    let error = result.unwrap_err();
+    match &error {
+        OpencodeClientError::UnknownAgent { agent, message, .. } => {
+            assert_eq!(agent, "biuld");
+            assert!(message.contains("build"), "message: {message}");
+        }
+        other => panic!("expected UnknownAgent, got {other:?}"),
+    }
+    assert_eq!(error.error_category(), "unknown_agent");
+    assert!(!error.is_retryable());
+}
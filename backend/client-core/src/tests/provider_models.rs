@@ -0,0 +1,287 @@
+// Unit tests for provider_models module
+// Tests fetch_models() against mock providers with different auth and response shapes
+
+use crate::config::models::{CuratedModel, ProviderConfig, ResponseFormat};
+use crate::error::provider_models::ProviderModelsError;
+use crate::provider_models::{fetch_models, fetch_models_with_env};
+use crate::tests::fixtures::provider;
+
+use common::RedactedApiKey;
+
+use std::collections::HashMap;
+
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const API_KEY: &str = "sk-test-1234567890abcdef";
+
+/// Provider `name` listing its models at `models_url` with `auth_type` auth.
+fn provider_at(name: &str, models_url: String, auth_type: &str) -> ProviderConfig {
+    ProviderConfig {
+        models_url,
+        auth_type: auth_type.to_string(),
+        ..provider(name)
+    }
+}
+
+fn key() -> RedactedApiKey {
+    RedactedApiKey::new(API_KEY.to_string())
+}
+
+/// **VALUE**: Verifies an OpenAI-shaped model list is fetched with bearer auth.
+///
+/// **WHY THIS MATTERS**: OpenAI and OpenRouter use this shape; it's the common case for
+/// populating the model picker.
+///
+/// **BUG THIS CATCHES**: Would catch if the bearer header is missing, if `models_path`
+/// isn't followed, or if names don't fall back to IDs.
+#[tokio::test]
+async fn given_openai_shaped_response_when_fetch_models_then_models_extracted() {
+    // GIVEN: A provider answering only bearer-authenticated requests
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header(
+            "authorization",
+            format!("Bearer {API_KEY}").as_str(),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "object": "list",
+            "data": [
+                { "id": "gpt-4o", "object": "model" },
+                { "id": "gpt-4o-mini", "object": "model" },
+                { "object": "model" }
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let openai = provider_at("openai", format!("{}/v1/models", server.uri()), "bearer");
+
+    // WHEN: Fetching models
+    let models = fetch_models(&openai, &key()).await.unwrap();
+
+    // THEN: Models with IDs are returned, named by ID
+    assert_eq!(
+        models,
+        [
+            CuratedModel::new("gpt-4o", "openai", "gpt-4o"),
+            CuratedModel::new("gpt-4o-mini", "openai", "gpt-4o-mini"),
+        ]
+    );
+}
+
+/// **VALUE**: Verifies custom auth, extra headers, nested paths and prefix stripping.
+///
+/// **WHY THIS MATTERS**: Gemini passes the key as a query parameter and prefixes IDs with
+/// `models/`; Anthropic needs a version header. Each provider's quirks live only in config.
+///
+/// **BUG THIS CATCHES**: Would catch if `auth_param` or `extra_headers` are ignored, if dotted
+/// paths aren't followed, or if `model_id_strip_prefix` isn't applied.
+#[tokio::test]
+async fn given_custom_shaped_response_when_fetch_models_then_format_applied() {
+    // GIVEN: A provider with query-param auth, a version header and a nested model list
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(query_param("key", API_KEY))
+        .and(header("x-api-version", "2024-01-01"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": {
+                "models": [
+                    { "name": "models/gemini-2.5-pro", "displayName": "Gemini 2.5 Pro" },
+                    { "name": "tuned-model", "displayName": "Tuned" }
+                ]
+            }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let gemini = ProviderConfig {
+        auth_param: Some("key".to_string()),
+        extra_headers: HashMap::from([("x-api-version".to_string(), "2024-01-01".to_string())]),
+        response_format: ResponseFormat {
+            models_path: "result.models".to_string(),
+            model_id_field: "name".to_string(),
+            model_id_strip_prefix: Some("models/".to_string()),
+            model_name_field: "displayName".to_string(),
+        },
+        ..provider_at(
+            "google",
+            format!("{}/v1/models", server.uri()),
+            "query_param",
+        )
+    };
+
+    // WHEN: Fetching models
+    let models = fetch_models(&gemini, &key()).await.unwrap();
+
+    // THEN: IDs are stripped where prefixed and named by displayName
+    assert_eq!(
+        models,
+        [
+            CuratedModel::new("Gemini 2.5 Pro", "google", "gemini-2.5-pro"),
+            CuratedModel::new("Tuned", "google", "tuned-model"),
+        ]
+    );
+}
+
+/// **VALUE**: Verifies rejections, shape mismatches and incomplete auth config are reported.
+///
+/// **WHY THIS MATTERS**: A bad key must show up as an auth failure the user can fix, not an
+/// empty model list.
+///
+/// **BUG THIS CATCHES**: Would catch if HTTP errors are parsed as bodies, if a missing
+/// array returns no models instead of an error, or if `auth_header` is silently optional.
+#[tokio::test]
+async fn given_failures_when_fetch_models_then_categorized_errors() {
+    // GIVEN: A provider rejecting one endpoint and returning the wrong shape on another
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rejected"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid api key"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/wrong-shape"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "models": []
+        })))
+        .mount(&server)
+        .await;
+
+    // WHEN: Fetching from each, and with header auth but no header name
+    let rejected = fetch_models(
+        &provider_at("openai", format!("{}/rejected", server.uri()), "bearer"),
+        &key(),
+    )
+    .await;
+    let wrong_shape = fetch_models(
+        &provider_at("openai", format!("{}/wrong-shape", server.uri()), "bearer"),
+        &key(),
+    )
+    .await;
+    let no_header = fetch_models(
+        &provider_at("anthropic", format!("{}/v1/models", server.uri()), "header"),
+        &key(),
+    )
+    .await;
+
+    // THEN: Each failure has its own category
+    assert!(
+        matches!(rejected, Err(ProviderModelsError::Http { status: 401, .. })),
+        "got {rejected:?}"
+    );
+    assert!(
+        matches!(wrong_shape, Err(ProviderModelsError::Format { .. })),
+        "got {wrong_shape:?}"
+    );
+    assert!(
+        matches!(no_header, Err(ProviderModelsError::Config { .. })),
+        "got {no_header:?}"
+    );
+}
+
+/// Provider at `server` whose only extra header is `name: template`.
+fn provider_with_header(server: &MockServer, name: &str, template: &str) -> ProviderConfig {
+    ProviderConfig {
+        extra_headers: HashMap::from([(name.to_string(), template.to_string())]),
+        ..provider_at("openai", format!("{}/v1/models", server.uri()), "bearer")
+    }
+}
+
+/// **VALUE**: Verifies `${NAME}` in an extra header is replaced by the variable's value.
+///
+/// **WHY THIS MATTERS**: Org and project IDs differ per user; templating lets the bundled
+/// models.toml send them without anyone editing it.
+///
+/// **BUG THIS CATCHES**: Would catch the template being sent verbatim, or text around the
+/// reference being dropped.
+#[tokio::test]
+async fn given_header_referencing_set_var_when_fetch_models_then_value_substituted() {
+    // GIVEN: A set variable and a provider that requires the substituted header
+    let env = |var: &str| (var == "PROVIDER_MODELS_TEST_ORG_ID").then(|| "org-a1b2c3".to_string());
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header("openai-organization", "team/org-a1b2c3"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": [{ "id": "gpt-4o" }]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let openai = provider_with_header(
+        &server,
+        "OpenAI-Organization",
+        "team/${PROVIDER_MODELS_TEST_ORG_ID}",
+    );
+
+    // WHEN: Fetching models
+    let models = fetch_models_with_env(&openai, &key(), env).await.unwrap();
+
+    // THEN: The request matched the substituted header
+    assert_eq!(models, [CuratedModel::new("gpt-4o", "openai", "gpt-4o")]);
+}
+
+/// **VALUE**: Verifies a header referencing an unset variable fails before any request.
+///
+/// **WHY THIS MATTERS**: Sending an empty or literal `${...}` org header gets a confusing
+/// 401 from the provider; the user needs to know which variable to set.
+///
+/// **BUG THIS CATCHES**: Would catch unset variables being substituted as empty strings,
+/// or the error not naming the variable and header.
+#[tokio::test]
+async fn given_header_referencing_unset_var_when_fetch_models_then_config_error() {
+    // GIVEN: A provider whose header references a variable nobody sets
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let openai = provider_with_header(
+        &server,
+        "OpenAI-Organization",
+        "${PROVIDER_MODELS_TEST_NEVER_SET}",
+    );
+
+    // WHEN: Fetching models
+    let result = fetch_models(&openai, &key()).await;
+
+    // THEN: A config error names both the variable and the header
+    match result {
+        Err(ProviderModelsError::Config { message, .. }) => {
+            assert!(
+                message.contains("PROVIDER_MODELS_TEST_NEVER_SET"),
+                "{message}"
+            );
+            assert!(message.contains("OpenAI-Organization"), "{message}");
+        }
+        other => panic!("expected Config error, got {other:?}"),
+    }
+}
+
+/// **VALUE**: Verifies header values without `${...}` are sent unchanged.
+///
+/// **BUG THIS CATCHES**: Would catch a lone `$` or `{` being treated as the start of a
+/// reference, breaking existing literal headers.
+#[tokio::test]
+async fn given_literal_header_when_fetch_models_then_sent_unchanged() {
+    // GIVEN: A provider requiring a literal header containing `$` and braces
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .and(header("x-client-tag", "$HOME {beta}"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": []
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let openai = provider_with_header(&server, "x-client-tag", "$HOME {beta}");
+
+    // WHEN / THEN: The request matches the literal header
+    let models = fetch_models(&openai, &key()).await.unwrap();
+    assert!(models.is_empty());
+}
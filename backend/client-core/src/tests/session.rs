@@ -0,0 +1,189 @@
+// Unit tests for session module
+// Tests token usage totals over message histories and session list ordering
+
+use crate::opencode_client::OpencodeClient;
+use crate::proto::message::{
+    OcAssistantMessage, OcMessage, OcTokenUsage, OcUserMessage, oc_message,
+};
+use crate::proto::session::OcSessionInfo;
+use crate::session::{TokenSummary, sort_sessions_by_created, sort_sessions_by_recent, summarize};
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SESSION_TOKEN_HISTORY_FIXTURE: &str = include_str!("fixtures/session_token_history.json");
+const SESSION_LIST_FIXTURE: &str = include_str!("fixtures/session_list.json");
+
+/// The fixture session list, as returned by `list_sessions`.
+async fn fixture_sessions() -> Vec<OcSessionInfo> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SESSION_LIST_FIXTURE))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    client.list_sessions().await.unwrap()
+}
+
+fn ids(sessions: &[OcSessionInfo]) -> Vec<&str> {
+    sessions.iter().map(|session| session.id.as_str()).collect()
+}
+
+fn assistant(tokens: Option<OcTokenUsage>, cost: f64) -> OcMessage {
+    OcMessage {
+        message: Some(oc_message::Message::Assistant(OcAssistantMessage {
+            tokens,
+            cost,
+            ..Default::default()
+        })),
+    }
+}
+
+fn user() -> OcMessage {
+    OcMessage {
+        message: Some(oc_message::Message::User(OcUserMessage::default())),
+    }
+}
+
+/// **VALUE**: Verifies a session history fetched from the server totals input, output and
+/// cache tokens over its assistant messages.
+///
+/// **WHY THIS MATTERS**: This is the number shown next to a session for cost and quota;
+/// OpenCode nests cache counts (`tokens.cache.read`), so they only count if parsing
+/// flattens them into the proto fields.
+///
+/// **BUG THIS CATCHES**: Would catch cache counts being dropped during parsing, assistant
+/// messages without `tokens` breaking the parse or the sum, or user messages being counted.
+#[tokio::test]
+async fn given_fixture_history_when_summarize_then_totals_assistant_usage() {
+    // GIVEN: A history of two user and three assistant messages, one without tokens
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/session/ses_tokens/message"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(SESSION_TOKEN_HISTORY_FIXTURE))
+        .mount(&server)
+        .await;
+    let client = OpencodeClient::new(&server.uri()).unwrap();
+    let messages = client.list_messages("ses_tokens").await.unwrap();
+
+    // WHEN: Summarizing it
+    let summary = summarize(&messages);
+
+    // THEN: Assistant usage is totalled; the message without tokens adds nothing
+    assert_eq!(summary.assistant_messages, 3);
+    assert_eq!(summary.input, 2800);
+    assert_eq!(summary.output, 500);
+    assert_eq!(summary.cache_read, 800);
+    assert_eq!(summary.cache_write, 400);
+    assert_eq!(summary.total(), 3300);
+    assert!((summary.cost - 0.02).abs() < 1e-9);
+}
+
+/// **VALUE**: Verifies accumulate ignores user messages and treats missing or negative
+/// counts as zero.
+///
+/// **BUG THIS CATCHES**: Would catch a panic or wraparound on malformed counts (a negative
+/// `int32` cast to `u64` becomes huge).
+#[test]
+fn given_mixed_messages_when_accumulate_then_only_assistant_counts_added() {
+    // GIVEN: An empty summary
+    let mut summary = TokenSummary::default();
+
+    // WHEN: Accumulating user, token-less, malformed and normal messages
+    summary.accumulate(&user());
+    summary.accumulate(&assistant(None, 0.0));
+    summary.accumulate(&assistant(
+        Some(OcTokenUsage {
+            input: -5,
+            output: 10,
+            cache_read: None,
+            cache_write: Some(-1),
+        }),
+        0.0,
+    ));
+    summary.accumulate(&assistant(
+        Some(OcTokenUsage {
+            input: 100,
+            output: 20,
+            cache_read: Some(60),
+            cache_write: None,
+        }),
+        0.5,
+    ));
+    summary.accumulate(&OcMessage { message: None });
+
+    // THEN: Only assistant usage counts, malformed values as zero
+    assert_eq!(
+        summary,
+        TokenSummary {
+            input: 100,
+            output: 30,
+            cache_read: 60,
+            cache_write: 0,
+            cost: 0.5,
+            assistant_messages: 3,
+        }
+    );
+}
+
+/// **VALUE**: Verifies an empty history summarizes to zero.
+#[test]
+fn given_no_messages_when_summarize_then_default() {
+    assert_eq!(summarize(&[]), TokenSummary::default());
+}
+
+/// **VALUE**: Verifies sessions sort most recently updated first, ties by ID, and sessions
+/// without timestamps last.
+///
+/// **WHY THIS MATTERS**: The sidebar lists sessions in this order; the server's order is
+/// arbitrary, so without sorting the session the user just worked in can be anywhere.
+///
+/// **BUG THIS CATCHES**: Would catch ascending order, zero timestamps sorting ahead of
+/// real ones, or tied sessions swapping places between refreshes.
+#[tokio::test]
+async fn given_fixture_sessions_when_sort_by_recent_then_newest_update_first() {
+    // GIVEN: The server's unordered session list
+    let mut sessions = fixture_sessions().await;
+
+    // WHEN: Sorting by last update
+    sort_sessions_by_recent(&mut sessions);
+
+    // THEN: Newest first, ties by ID, untimed sessions last (by ID)
+    assert_eq!(
+        ids(&sessions),
+        [
+            "ses_d_newest",
+            "ses_a_tied",
+            "ses_c_tied",
+            "ses_b_old",
+            "ses_no_time",
+            "ses_zero",
+        ]
+    );
+}
+
+/// **VALUE**: Verifies the created-time variant orders by creation, not last update.
+///
+/// **BUG THIS CATCHES**: Would catch the two sorts sharing a timestamp field.
+#[tokio::test]
+async fn given_fixture_sessions_when_sort_by_created_then_newest_creation_first() {
+    // GIVEN: The server's unordered session list
+    let mut sessions = fixture_sessions().await;
+
+    // WHEN: Sorting by creation
+    sort_sessions_by_created(&mut sessions);
+
+    // THEN: Newest creation first; the most recently updated session was created first
+    assert_eq!(
+        ids(&sessions),
+        [
+            "ses_a_tied",
+            "ses_c_tied",
+            "ses_b_old",
+            "ses_d_newest",
+            "ses_no_time",
+            "ses_zero",
+        ]
+    );
+}
@@ -1,26 +1,76 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize, Serializer};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::panic::Location as PanicLocation;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy, Serialize)]
+/// Where an error was created, plus an optional backtrace.
+///
+/// The backtrace is captured when `RUST_BACKTRACE` (or `RUST_LIB_BACKTRACE`) enables it, or
+/// always with the `backtrace` feature. Without one, `Display` is the single `[file:line:column]`.
+///
+/// Serializable so errors can cross the IPC boundary; the backtrace is sent as text and not
+/// restored on deserialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErrorLocation {
-    pub file: &'static str,
+    pub file: Cow<'static, str>,
     pub line: u32,
     pub column: u32,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_backtrace",
+        skip_deserializing
+    )]
+    pub backtrace: Option<Arc<Backtrace>>,
 }
 
 impl ErrorLocation {
-    pub const fn from(location: &'static PanicLocation<'static>) -> Self {
+    pub fn from(location: &'static PanicLocation<'static>) -> Self {
         Self {
-            file: location.file(),
+            file: Cow::Borrowed(location.file()),
             line: location.line(),
             column: location.column(),
+            backtrace: capture_backtrace(),
+        }
+    }
+
+    /// A location reported from elsewhere (e.g. received over IPC), without a backtrace.
+    pub fn new(file: impl Into<Cow<'static, str>>, line: u32, column: u32) -> Self {
+        Self {
+            file: file.into(),
+            line,
+            column,
+            backtrace: None,
         }
     }
 }
 
 impl Display for ErrorLocation {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
-        write!(formatter, "[{}:{}:{}]", self.file, self.line, self.column)
+        write!(formatter, "[{}:{}:{}]", self.file, self.line, self.column)?;
+        if let Some(backtrace) = &self.backtrace {
+            write!(formatter, "\n{backtrace}")?;
+        }
+        Ok(())
+    }
+}
+
+fn capture_backtrace() -> Option<Arc<Backtrace>> {
+    #[cfg(feature = "backtrace")]
+    let backtrace = Backtrace::force_capture();
+    #[cfg(not(feature = "backtrace"))]
+    let backtrace = Backtrace::capture();
+
+    (backtrace.status() == BacktraceStatus::Captured).then(|| Arc::new(backtrace))
+}
+
+fn serialize_backtrace<S: Serializer>(
+    backtrace: &Option<Arc<Backtrace>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match backtrace {
+        Some(backtrace) => serializer.serialize_str(&backtrace.to_string()),
+        None => serializer.serialize_none(),
     }
 }
@@ -7,6 +7,21 @@
 pub struct HttpStatusCode(pub u16);
 
 impl HttpStatusCode {
+    /// 1xx informational responses.
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.0)
+    }
+
+    /// 2xx success responses.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.0)
+    }
+
+    /// 3xx redirects.
+    pub fn is_redirect(&self) -> bool {
+        (300..400).contains(&self.0)
+    }
+
     /// 4xx client errors (not retryable).
     pub fn is_client_error(&self) -> bool {
         (400..500).contains(&self.0)
@@ -21,6 +36,52 @@ impl HttpStatusCode {
     pub fn is_retryable(&self) -> bool {
         matches!(self.0, 502 | 503 | 504 | 429)
     }
+
+    /// Standard reason phrase for common codes (e.g. "Not Found" for 404).
+    ///
+    /// Codes without a listed phrase fall back to their class ("Client Error" for an
+    /// unlisted 4xx), or "Unknown Status" outside 100-599.
+    pub fn reason_phrase(&self) -> &'static str {
+        match self.0 {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            204 => "No Content",
+            206 => "Partial Content",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            406 => "Not Acceptable",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            410 => "Gone",
+            413 => "Payload Too Large",
+            415 => "Unsupported Media Type",
+            422 => "Unprocessable Entity",
+            429 => "Too Many Requests",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            _ if self.is_informational() => "Informational",
+            _ if self.is_success() => "Success",
+            _ if self.is_redirect() => "Redirect",
+            _ if self.is_client_error() => "Client Error",
+            _ if self.is_server_error() => "Server Error",
+            _ => "Unknown Status",
+        }
+    }
 }
 
 impl From<u16> for HttpStatusCode {
@@ -14,9 +14,14 @@
 
 pub mod error;
 pub mod http_status;
+pub mod redact;
 pub mod redacted_key;
 
 pub use error::error_location::ErrorLocation;
 pub use error::redact_error::RedactError;
 pub use http_status::HttpStatusCode;
-pub use redacted_key::RedactedApiKey;
+pub use redact::{RedactedDisplay, redact_secrets};
+pub use redacted_key::{ExposedApiKey, RedactedApiKey};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+//! Redaction policy for text that may carry secrets (error messages, log lines).
+//!
+//! [`redact_secrets`] masks:
+//! - Words starting with a known key prefix ([`SECRET_PREFIXES`]) that are at least
+//!   [`MIN_SECRET_LEN`] characters long; the prefix is kept so the key type stays visible
+//! - The value after `Bearer `
+//! - The value of `name=value` pairs whose name is in [`SECRET_PARAMS`] (e.g. query
+//!   strings carrying `key=`)
+//!
+//! Error types opt in by implementing [`RedactedDisplay`] and are logged or shown to users
+//! through [`RedactedDisplay::redacted_to_string`].
+
+use std::fmt;
+
+/// Replacement for masked values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Prefixes of provider API keys (OpenAI/Anthropic `sk-`, Google `AIza`, Groq `gsk_`, xAI `xai-`).
+pub const SECRET_PREFIXES: &[&str] = &["sk-", "sk_", "AIza", "gsk_", "xai-"];
+
+/// Shortest prefixed word treated as a key; shorter ones (e.g. `sk-1`) are left alone.
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// Parameter names (case-insensitive) whose `=value` is always masked.
+pub const SECRET_PARAMS: &[&str] = &["key", "api_key", "apikey", "token", "access_token"];
+
+/// Label (case-insensitive) whose following word is always masked.
+const BEARER: &str = "bearer";
+
+/// Mask anything key-like in `text` (see the [module docs](self) for the policy).
+pub fn redact_secrets(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut mask_next = false;
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let separator_len = rest.find(is_word_char).unwrap_or(rest.len());
+        let (separator, after) = rest.split_at(separator_len);
+        redacted.push_str(separator);
+
+        let word_len = after.find(|c| !is_word_char(c)).unwrap_or(after.len());
+        let (word, next) = after.split_at(word_len);
+        if word.is_empty() {
+            break;
+        }
+
+        if mask_next {
+            redacted.push_str(REDACTED);
+        } else if let Some(prefix) = secret_prefix(word) {
+            redacted.push_str(prefix);
+            redacted.push_str(REDACTED);
+        } else {
+            redacted.push_str(word);
+        }
+
+        mask_next = (word.eq_ignore_ascii_case(BEARER) && next.starts_with(' '))
+            || (SECRET_PARAMS.iter().any(|p| word.eq_ignore_ascii_case(p))
+                && next.starts_with('='));
+        rest = next;
+    }
+
+    redacted
+}
+
+/// Characters a key or token value can contain.
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+/// The key prefix `word` starts with, if it is long enough to be a key.
+fn secret_prefix(word: &str) -> Option<&'static str> {
+    if word.len() < MIN_SECRET_LEN {
+        return None;
+    }
+    SECRET_PREFIXES
+        .iter()
+        .copied()
+        .find(|prefix| word.starts_with(prefix))
+}
+
+/// Display with secrets masked per [`redact_secrets`].
+///
+/// Implement it (no methods needed) for error types whose messages may include request
+/// URLs, server responses or key material, and use [`redacted_to_string`](Self::redacted_to_string)
+/// wherever they are logged or sent to a user.
+pub trait RedactedDisplay: fmt::Display {
+    /// This value's `Display` output with secrets masked.
+    fn redacted_to_string(&self) -> String {
+        redact_secrets(&self.to_string())
+    }
+}
@@ -1,4 +1,11 @@
 //! Secure API key handling with redacted Debug output.
+//!
+//! # Serialization
+//!
+//! Serializing a [`RedactedApiKey`] directly is an error, so a key can never leak through
+//! serde by accident. Mark key fields `#[serde(skip_serializing)]` to leave them out, and
+//! use [`RedactedApiKey::expose_for_serialization`] where the real value must be written
+//! (e.g. to the OS keychain). Deserializing from a plain string is always allowed.
 
 use crate::{ErrorLocation, RedactError};
 
@@ -40,6 +47,37 @@ impl RedactedApiKey {
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
+
+    /// Wrap the key so it serializes as its real value.
+    ///
+    /// # Security Note
+    /// Only call this when writing the key to secure storage.
+    pub fn expose_for_serialization(&self) -> ExposedApiKey<'_> {
+        ExposedApiKey { key: self }
+    }
+}
+
+/// A borrowed [`RedactedApiKey`] that serializes as the plain key string.
+///
+/// Created by [`RedactedApiKey::expose_for_serialization`]; `Debug` stays redacted.
+#[derive(Clone, Copy)]
+pub struct ExposedApiKey<'a> {
+    key: &'a RedactedApiKey,
+}
+
+impl fmt::Debug for ExposedApiKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ExposedApiKey([REDACTED])")
+    }
+}
+
+impl serde::Serialize for ExposedApiKey<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.key.as_str())
+    }
 }
 
 impl fmt::Debug for RedactedApiKey {
@@ -60,7 +98,7 @@ impl Drop for RedactedApiKey {
     }
 }
 
-// Prevent accidental serialization
+// Prevent accidental serialization (see module docs for the explicit path)
 impl serde::Serialize for RedactedApiKey {
     fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -72,3 +110,12 @@ impl serde::Serialize for RedactedApiKey {
         }))
     }
 }
+
+impl<'de> serde::Deserialize<'de> for RedactedApiKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
@@ -0,0 +1,73 @@
+// Unit tests for ErrorLocation formatting with and without backtraces
+
+use crate::ErrorLocation;
+
+use std::panic::Location;
+
+#[track_caller]
+fn nested_error_location() -> ErrorLocation {
+    ErrorLocation::from(Location::caller())
+}
+
+/// **VALUE**: Verifies that the `backtrace` feature makes errors carry the full call path.
+///
+/// **WHY THIS MATTERS**: Failures that cross the client → IPC boundary can't be traced from a
+/// single file:line; the backtrace is what lets us reconstruct how we got there.
+///
+/// **BUG THIS CATCHES**: Would catch if the feature doesn't force capture, or if `Display`
+/// drops the captured backtrace.
+#[cfg(feature = "backtrace")]
+#[test]
+fn given_backtrace_feature_when_location_formatted_then_includes_multiple_frames() {
+    // GIVEN: A location created a few calls deep
+    let location = nested_error_location();
+
+    // WHEN: Formatting it
+    let formatted = location.to_string();
+
+    // THEN: The location line is followed by more than one backtrace frame
+    let (first_line, backtrace) = formatted
+        .split_once('\n')
+        .expect("Backtrace should follow the location line");
+    assert_eq!(
+        first_line,
+        format!("[{}:{}:{}]", location.file, location.line, location.column)
+    );
+    let frames = backtrace
+        .lines()
+        .filter(|line| line.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+        .count();
+    assert!(frames > 1, "Expected several frames, got:\n{backtrace}");
+}
+
+/// **VALUE**: Verifies that without the feature, output is exactly the one-line location.
+///
+/// **WHY THIS MATTERS**: Every error message in the app ends with this location; log parsing
+/// and existing assertions rely on it staying `[file:line:column]`.
+///
+/// **BUG THIS CATCHES**: Would catch if a backtrace is captured or printed by default.
+#[cfg(not(feature = "backtrace"))]
+#[test]
+fn given_no_backtrace_feature_when_location_formatted_then_matches_single_line_format() {
+    // Backtraces are also enabled by the environment; that is opt-in, not the default
+    let env_enabled = ["RUST_LIB_BACKTRACE", "RUST_BACKTRACE"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .is_some_and(|value| value != "0");
+    if env_enabled {
+        return;
+    }
+
+    // GIVEN: A location created without backtrace support
+    let location = nested_error_location();
+
+    // WHEN: Formatting it
+    let formatted = location.to_string();
+
+    // THEN: The output is the single-line format, with no backtrace
+    assert!(location.backtrace.is_none());
+    assert_eq!(
+        formatted,
+        format!("[{}:{}:{}]", location.file, location.line, location.column)
+    );
+}
@@ -0,0 +1,107 @@
+// Unit tests for http_status module
+// Tests status classes and reason phrases across the full range
+
+use crate::HttpStatusCode;
+
+/// **VALUE**: Verifies every code in 100-599 belongs to exactly one status class.
+///
+/// **WHY THIS MATTERS**: Retry and error-display decisions branch on these classes; a gap
+/// or overlap at a range boundary (e.g. 399 vs 400) misroutes a whole block of codes.
+///
+/// **BUG THIS CATCHES**: Would catch off-by-one range bounds in any of the class checks.
+#[test]
+fn given_any_standard_code_when_classified_then_exactly_one_class_matches() {
+    for code in 100..600 {
+        // GIVEN: A status code in the standard range
+        let status = HttpStatusCode(code);
+
+        // WHEN: Checking every class
+        let classes = [
+            status.is_informational(),
+            status.is_success(),
+            status.is_redirect(),
+            status.is_client_error(),
+            status.is_server_error(),
+        ];
+
+        // THEN: Exactly the class for its hundreds digit matches
+        let expected = usize::from(code / 100 - 1);
+        for (index, matched) in classes.into_iter().enumerate() {
+            assert_eq!(matched, index == expected, "code {code}, class {index}");
+        }
+    }
+}
+
+/// **VALUE**: Verifies codes outside 100-599 belong to no class.
+///
+/// **WHY THIS MATTERS**: Malformed statuses must not be treated as success or as a
+/// retryable server error.
+///
+/// **BUG THIS CATCHES**: Would catch open-ended range checks like `code >= 500`.
+#[test]
+fn given_out_of_range_code_when_classified_then_no_class_matches() {
+    for code in [0, 99, 600, 999] {
+        // GIVEN: A status code outside the standard range
+        let status = HttpStatusCode(code);
+
+        // WHEN/THEN: No class matches and the phrase is unknown
+        assert!(!status.is_informational(), "code {code}");
+        assert!(!status.is_success(), "code {code}");
+        assert!(!status.is_redirect(), "code {code}");
+        assert!(!status.is_client_error(), "code {code}");
+        assert!(!status.is_server_error(), "code {code}");
+        assert_eq!(status.reason_phrase(), "Unknown Status");
+    }
+}
+
+/// **VALUE**: Verifies the reason phrases and classes of commonly seen codes.
+///
+/// **WHY THIS MATTERS**: These are the codes users actually see from providers and the
+/// OpenCode server, so their messages must be right.
+///
+/// **BUG THIS CATCHES**: Would catch a typo'd or swapped phrase for a common code.
+#[test]
+fn given_common_codes_when_reason_phrase_then_standard_phrase() {
+    // GIVEN: Common codes with their expected phrase and class
+    let cases = [
+        (
+            200,
+            "OK",
+            HttpStatusCode::is_success as fn(&HttpStatusCode) -> bool,
+        ),
+        (301, "Moved Permanently", HttpStatusCode::is_redirect),
+        (404, "Not Found", HttpStatusCode::is_client_error),
+        (429, "Too Many Requests", HttpStatusCode::is_client_error),
+        (
+            500,
+            "Internal Server Error",
+            HttpStatusCode::is_server_error,
+        ),
+        (503, "Service Unavailable", HttpStatusCode::is_server_error),
+    ];
+
+    for (code, phrase, class) in cases {
+        // WHEN: Looking up the phrase
+        let status = HttpStatusCode(code);
+
+        // THEN: Phrase and class match
+        assert_eq!(status.reason_phrase(), phrase, "code {code}");
+        assert!(class(&status), "code {code}");
+    }
+}
+
+/// **VALUE**: Verifies unlisted codes fall back to their class name.
+///
+/// **WHY THIS MATTERS**: Providers return uncommon codes (e.g. 418, 599); the message
+/// should still say what kind of failure it was.
+///
+/// **BUG THIS CATCHES**: Would catch unlisted codes showing "Unknown Status".
+#[test]
+fn given_unlisted_code_when_reason_phrase_then_class_fallback() {
+    // GIVEN/WHEN/THEN: One unlisted code per class
+    assert_eq!(HttpStatusCode(199).reason_phrase(), "Informational");
+    assert_eq!(HttpStatusCode(299).reason_phrase(), "Success");
+    assert_eq!(HttpStatusCode(399).reason_phrase(), "Redirect");
+    assert_eq!(HttpStatusCode(418).reason_phrase(), "Client Error");
+    assert_eq!(HttpStatusCode(599).reason_phrase(), "Server Error");
+}
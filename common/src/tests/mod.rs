@@ -0,0 +1,4 @@
+mod error_location;
+mod http_status;
+mod redact;
+mod redacted_key;
@@ -89,15 +89,15 @@
           // Arrange
           var json = """
                      {
-                         "version": 1,
+                         "version": 2,
                          "server": {
                              "last_opencode_url": "http://localhost:3000",
                              "auto_start": true
                          },
                          "ui": {
-                             "font_size": "Large",
+                             "font_size": "large",
                              "base_font_points": 16.0,
-                             "chat_density": "Compact"
+                             "chat_density": "compact"
                          }
                      }
                      """;
@@ -107,10 +107,10 @@
 
           // Assert
           Assert.NotNull(config);
-          Assert.Equal(1, config.Version);
+          Assert.Equal(2, config.Version);
           Assert.Equal("http://localhost:3000", config.Server.LastOpencodeUrl);
           Assert.True(config.Server.AutoStart);
-          Assert.Equal("Large", config.Ui.FontSize);
+          Assert.Equal("large", config.Ui.FontSize);
           Assert.Equal(16.0f, config.Ui.BaseFontPoints);
       }
 
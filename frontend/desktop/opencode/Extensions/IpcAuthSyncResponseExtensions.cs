@@ -18,6 +18,7 @@
           => response.Synced.Count == 0
              && response.Failed.Count == 0
              && response.Skipped.Count == 0
+             && response.AlreadyCurrent.Count == 0
              && response.ValidationFailed.Count == 0;
 
       /// <summary>
@@ -47,6 +48,8 @@
               parts.Add($"{response.Failed.Count} failed");
           if (response.Skipped.Count > 0)
               parts.Add($"{response.Skipped.Count} OAuth");
+          if (response.AlreadyCurrent.Count > 0)
+              parts.Add($"{response.AlreadyCurrent.Count} unchanged");
           if (response.ValidationFailed.Count > 0)
               parts.Add($"{response.ValidationFailed.Count} invalid");
 
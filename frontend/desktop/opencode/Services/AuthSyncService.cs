@@ -95,7 +95,7 @@
               // Call IPC
               var response = await _ipcClient.SyncAuthKeysAsync(
                   skipOAuthProviders,
-                  linkedCts.Token);
+                  cancellationToken: linkedCts.Token);
 
               stopwatch.Stop();
 
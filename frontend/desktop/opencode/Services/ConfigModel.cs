@@ -9,7 +9,7 @@ using System.Text.Json.Serialization;
 public record AppConfig
 {
   [JsonPropertyName("version")]
-  public int Version { get; init; } = 1;
+  public int Version { get; init; } = 2;
 
   [JsonPropertyName("server")]
   public ServerConfig Server { get; init; } = new();
@@ -36,13 +36,13 @@ public record ServerConfig
 public record UiPreferences
 {
   [JsonPropertyName("font_size")]
-  public string FontSize { get; init; } = "Standard";
+  public string FontSize { get; init; } = "standard";
 
   [JsonPropertyName("base_font_points")]
   public float BaseFontPoints { get; init; } = 14.0f;
 
   [JsonPropertyName("chat_density")]
-  public string ChatDensity { get; init; } = "Normal";
+  public string ChatDensity { get; init; } = "normal";
 }
 
 public record AudioConfig
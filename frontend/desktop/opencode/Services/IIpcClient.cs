@@ -119,12 +119,14 @@ public interface IIpcClient : IAsyncDisposable
     /// Syncs API keys from .env to OpenCode server.
     /// </summary>
     /// <param name="skipOAuthProviders">Skip providers with existing OAuth config.</param>
+    /// <param name="dryRun">Check keys without sending any to the server.</param>
     /// <param name="cancellationToken">Cancellation token.</param>
     /// <returns>Sync result with synced/failed/skipped providers.</returns>
     /// <exception cref="Exceptions.IpcConnectionException">Not connected to IPC.</exception>
     /// <exception cref="Exceptions.IpcTimeoutException">Request timed out.</exception>
     Task<IpcAuthSyncResponse> SyncAuthKeysAsync(
         bool skipOAuthProviders = true,
+        bool dryRun = false,
         CancellationToken cancellationToken = default);
     
     // Message operations
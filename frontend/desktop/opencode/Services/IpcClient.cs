@@ -23,6 +23,11 @@ public class IpcClient : IIpcClient, IDisposable
     private readonly IpcClientOptions _options;
     private readonly IIpcClientMetrics _metrics;
 
+    /// <summary>
+    /// IPC protocol version this client speaks (must match client-core's IPC_PROTOCOL_VERSION range).
+    /// </summary>
+    private const uint ProtocolVersion = 1;
+
     // WebSocket and state
     private ClientWebSocket? _ws;
     private ConnectionState _state = ConnectionState.Disconnected;
@@ -147,7 +152,7 @@ public class IpcClient : IIpcClient, IDisposable
         var authMsg = new IpcClientMessage
         {
             RequestId = 1,
-            AuthHandshake = new IpcAuthHandshake { Token = token }
+            AuthHandshake = new IpcAuthHandshake { Token = token, ProtocolVersion = ProtocolVersion }
         };
 
         using var authCts = new CancellationTokenSource(_options.AuthenticationTimeout);
@@ -160,7 +165,9 @@ public class IpcClient : IIpcClient, IDisposable
             throw new IpcAuthenticationException(errorMsg);
         }
 
-        _logger.LogInformation("Authentication successful");
+        _logger.LogInformation(
+            "Authentication successful (protocol v{Version})",
+            response.AuthHandshakeResponse.ProtocolVersion);
     }
 
     /// <summary>
@@ -797,11 +804,15 @@ public class IpcClient : IIpcClient, IDisposable
 
     public async Task<IpcAuthSyncResponse> SyncAuthKeysAsync(
         bool skipOAuthProviders = true,
+        bool dryRun = false,
         CancellationToken cancellationToken = default)
     {
         ThrowIfDisposed();
 
-        _logger.LogDebug("Syncing auth keys (skipOAuth={SkipOAuth})", skipOAuthProviders);
+        _logger.LogDebug(
+            "Syncing auth keys (skipOAuth={SkipOAuth}, dryRun={DryRun})",
+            skipOAuthProviders,
+            dryRun);
 
         try
         {
@@ -810,7 +821,8 @@ public class IpcClient : IIpcClient, IDisposable
                 SyncAuthKeys = new IpcSyncAuthKeysRequest
                 {
                     SkipOauthProviders = skipOAuthProviders, // lowercase 'o'
-                    TimeoutSecs = 30
+                    TimeoutSecs = 30,
+                    DryRun = dryRun
                 }
             };
 